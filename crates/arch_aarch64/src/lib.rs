@@ -2,8 +2,11 @@
 
 use hal::{Errno, PageFlags, PagingOps, PagingRoot, PhysAddr, VirtAddr};
 
-/// Initializes AArch64 CPU state (minimal stub).
-pub fn init() {}
+/// Initializes AArch64 CPU state: starts the generic timer event stream so
+/// `wait_for_event` wakes periodically without an interrupt controller.
+pub fn init() {
+    enable_event_stream();
+}
 
 /// Enables interrupts (stub; real mask setup is TBD).
 pub fn enable_interrupts() {}
@@ -17,6 +20,46 @@ pub fn halt_loop() -> ! {
     }
 }
 
+/// Reads the generic timer's virtual count (`CNTVCT_EL0`).
+pub fn counter() -> u64 {
+    let count: u64;
+    unsafe {
+        core::arch::asm!("isb", "mrs {}, cntvct_el0", out(reg) count, options(nostack));
+    }
+    count
+}
+
+/// Returns the generic timer frequency in Hz (`CNTFRQ_EL0`).
+pub fn counter_frequency() -> u64 {
+    let frequency: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, cntfrq_el0", out(reg) frequency, options(nomem, nostack));
+    }
+    frequency
+}
+
+/// Sleeps with `wfe` until the next event-stream pulse (or any other event).
+pub fn wait_for_event() {
+    unsafe {
+        core::arch::asm!("wfe", options(nomem, nostack));
+    }
+}
+
+/// Sets `CNTKCTL_EL1.EVNTEN`, picking the counter bit whose flips come
+/// closest to a 10ms period (EVNTI only reaches bit 15).
+fn enable_event_stream() {
+    let per_tick = (counter_frequency() / 100).max(1);
+    let bit = u64::from((63 - per_tick.leading_zeros()).min(15));
+    let mut control: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, cntkctl_el1", out(reg) control, options(nomem, nostack));
+    }
+    control = (control & !(0xf << 4)) | (bit << 4) | (1 << 2);
+    unsafe {
+        core::arch::asm!("msr cntkctl_el1, {}", "isb", in(reg) control, options(nostack));
+    }
+}
+
 /// AArch64 paging operations (stub implementation).
 pub struct AArch64Paging;
 
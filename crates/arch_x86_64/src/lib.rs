@@ -17,7 +17,9 @@ use x86_64::VirtAddr;
 
 mod keyboard;
 mod usb_input;
+mod virtio;
 mod virtio_input;
+mod virtio_net;
 mod vga;
 
 /// Primary 8259 PIC offset for hardware interrupts.
@@ -46,7 +48,10 @@ static IDT: Once<InterruptDescriptorTable> = Once::new();
 pub use keyboard::{keyboard_has_data, keyboard_init, keyboard_read_byte};
 pub use usb_input::{usb_input_has_data, usb_input_init, usb_input_read_byte};
 pub use virtio_input::{virtio_input_has_data, virtio_input_init, virtio_input_read_byte};
-pub use vga::{vga_init, vga_write_str};
+pub use virtio_net::{
+    virtio_net_init, virtio_net_link_up, virtio_net_mac, virtio_net_receive, virtio_net_transmit,
+};
+pub use vga::{vga_clear, vga_init, vga_write_str};
 
 /// Stores memory offsets used for MMIO and DMA translations.
 pub fn set_memory_offsets(hhdm_offset: u64, kernel_virtual_base: u64, kernel_physical_base: u64) {
@@ -94,6 +99,11 @@ pub fn halt_loop() -> ! {
     }
 }
 
+/// Sleeps with `hlt` until the next interrupt (at the latest the PIT tick).
+pub fn wait_for_interrupt() {
+    x86_64::instructions::hlt();
+}
+
 /// Writes a byte to the legacy serial port.
 pub fn serial_write_byte(byte: u8) {
     unsafe {
@@ -154,6 +164,26 @@ pub fn acknowledge_irq(irq: u8) {
     }
 }
 
+/// Unmasks a legacy PIC line, including the cascade line for the secondary PIC.
+pub(crate) fn unmask_irq(line: u8) {
+    if line >= 16 {
+        return;
+    }
+    interrupts::without_interrupts(|| {
+        let mut pics = PICS.lock();
+        let [mut primary, mut secondary] = unsafe { pics.read_masks() };
+        if line < 8 {
+            primary &= !(1 << line);
+        } else {
+            primary &= !(1 << 2);
+            secondary &= !(1 << (line - 8));
+        }
+        unsafe {
+            pics.write_masks(primary, secondary);
+        }
+    });
+}
+
 fn init_gdt() {
     let (gdt, selectors) = GDT.call_once(|| {
         let tss = TSS.call_once(build_tss);
@@ -183,6 +213,9 @@ fn init_idt() {
         idt.general_protection_fault
             .set_handler_fn(general_protection_handler);
         idt[InterruptIndex::Timer.as_u8()].set_handler_fn(timer_interrupt_handler);
+        idt[InterruptIndex::Pci9.as_u8()].set_handler_fn(pci9_interrupt_handler);
+        idt[InterruptIndex::Pci10.as_u8()].set_handler_fn(pci10_interrupt_handler);
+        idt[InterruptIndex::Pci11.as_u8()].set_handler_fn(pci11_interrupt_handler);
         idt
     });
     idt.load();
@@ -221,6 +254,10 @@ fn build_tss() -> TaskStateSegment {
 #[repr(u8)]
 enum InterruptIndex {
     Timer = PIC_1_OFFSET,
+    /// Lines QEMU and most firmware route PCI INTx to.
+    Pci9 = PIC_1_OFFSET + 9,
+    Pci10 = PIC_1_OFFSET + 10,
+    Pci11 = PIC_1_OFFSET + 11,
 }
 
 impl InterruptIndex {
@@ -256,3 +293,18 @@ extern "x86-interrupt" fn timer_interrupt_handler(_stack: InterruptStackFrame) {
     TICKS.fetch_add(1, Ordering::Relaxed);
     acknowledge_irq(InterruptIndex::Timer as u8);
 }
+
+extern "x86-interrupt" fn pci9_interrupt_handler(_stack: InterruptStackFrame) {
+    virtio_net::virtio_net_handle_interrupt();
+    acknowledge_irq(InterruptIndex::Pci9 as u8);
+}
+
+extern "x86-interrupt" fn pci10_interrupt_handler(_stack: InterruptStackFrame) {
+    virtio_net::virtio_net_handle_interrupt();
+    acknowledge_irq(InterruptIndex::Pci10 as u8);
+}
+
+extern "x86-interrupt" fn pci11_interrupt_handler(_stack: InterruptStackFrame) {
+    virtio_net::virtio_net_handle_interrupt();
+    acknowledge_irq(InterruptIndex::Pci11 as u8);
+}
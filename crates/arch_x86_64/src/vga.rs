@@ -12,6 +12,10 @@ pub fn vga_init() {
     VGA_WRITER.lock().clear();
 }
 
+pub fn vga_clear() {
+    VGA_WRITER.lock().clear();
+}
+
 pub fn vga_write_str(text: &str) {
     let mut writer = VGA_WRITER.lock();
     for byte in text.bytes() {
@@ -0,0 +1,298 @@
+use core::alloc::Layout;
+use core::mem::{align_of, size_of};
+use core::ptr::{read_volatile, write_volatile};
+use core::sync::atomic::{fence, Ordering};
+
+use x86_64::instructions::port::Port;
+
+use crate::virt_to_phys;
+
+const PCI_CONFIG_ADDRESS: u16 = 0xCF8;
+const PCI_CONFIG_DATA: u16 = 0xCFC;
+
+pub(crate) const VIRTIO_VENDOR_ID: u16 = 0x1AF4;
+
+pub(crate) const VIRTIO_PCI_HOST_FEATURES: u16 = 0x00;
+pub(crate) const VIRTIO_PCI_GUEST_FEATURES: u16 = 0x04;
+pub(crate) const VIRTIO_PCI_QUEUE_PFN: u16 = 0x08;
+pub(crate) const VIRTIO_PCI_QUEUE_NUM: u16 = 0x0C;
+pub(crate) const VIRTIO_PCI_QUEUE_SEL: u16 = 0x0E;
+pub(crate) const VIRTIO_PCI_QUEUE_NOTIFY: u16 = 0x10;
+pub(crate) const VIRTIO_PCI_STATUS: u16 = 0x12;
+pub(crate) const VIRTIO_PCI_ISR: u16 = 0x13;
+/// Start of the device-specific config space when MSI-X is disabled.
+pub(crate) const VIRTIO_PCI_CONFIG: u16 = 0x14;
+
+pub(crate) const VIRTIO_STATUS_ACKNOWLEDGE: u8 = 0x01;
+pub(crate) const VIRTIO_STATUS_DRIVER: u8 = 0x02;
+pub(crate) const VIRTIO_STATUS_FEATURES_OK: u8 = 0x08;
+pub(crate) const VIRTIO_STATUS_DRIVER_OK: u8 = 0x04;
+
+pub(crate) const VIRTQ_DESC_F_WRITE: u16 = 0x2;
+/// Alignment of the used ring in a legacy virtqueue.
+pub(crate) const LEGACY_VRING_ALIGN: usize = 4096;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub(crate) struct VirtqDesc {
+    pub(crate) addr: u64,
+    pub(crate) len: u32,
+    pub(crate) flags: u16,
+    pub(crate) next: u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub(crate) struct VirtqUsedElem {
+    pub(crate) id: u32,
+    pub(crate) len: u32,
+}
+
+/// Legacy (I/O port) virtio PCI function.
+#[derive(Clone, Copy)]
+pub(crate) struct PciDevice {
+    pub(crate) io_base: u16,
+    /// Legacy PIC line from the interrupt-line register.
+    pub(crate) irq_line: u8,
+}
+
+/// Finds the first legacy virtio function with `device_id` and enables it.
+pub(crate) fn find_legacy_device(device_id: u16) -> Option<PciDevice> {
+    for bus in 0u8..=0xff {
+        for device in 0u8..32 {
+            let header = pci_config_read16(bus, device, 0, 0x0E);
+            if header == 0xFFFF {
+                continue;
+            }
+            let functions = if header & 0x80 != 0 { 8 } else { 1 };
+            for function in 0u8..functions {
+                let vendor = pci_config_read16(bus, device, function, 0x00);
+                if vendor == 0xFFFF {
+                    continue;
+                }
+                if vendor != VIRTIO_VENDOR_ID {
+                    continue;
+                }
+                if pci_config_read16(bus, device, function, 0x02) != device_id {
+                    continue;
+                }
+                let bar0 = pci_config_read32(bus, device, function, 0x10);
+                if bar0 & 0x1 == 0 {
+                    continue;
+                }
+                let io_base = (bar0 & 0xFFFC) as u16;
+                let irq_line = (pci_config_read32(bus, device, function, 0x3C) & 0xFF) as u8;
+                enable_pci_io_master(bus, device, function);
+                return Some(PciDevice { io_base, irq_line });
+            }
+        }
+    }
+    None
+}
+
+/// Split virtqueue in one page-aligned allocation, as legacy devices expect.
+pub(crate) struct Virtqueue {
+    index: u16,
+    /// Entries in the device's rings; indices wrap at this size.
+    ring_size: u16,
+    /// Descriptors the driver actually fills.
+    size: u16,
+    desc: *mut VirtqDesc,
+    avail: *mut u8,
+    used: *mut u8,
+    avail_idx: u16,
+    used_idx: u16,
+}
+
+impl Virtqueue {
+    /// Allocates queue `index` with at most `max_size` entries and hands it to the device.
+    pub(crate) fn setup(base_port: u16, index: u16, max_size: u16) -> Option<Self> {
+        write_port_u16(base_port + VIRTIO_PCI_QUEUE_SEL, index);
+        let size = read_port_u16(base_port + VIRTIO_PCI_QUEUE_NUM);
+        if size == 0 {
+            return None;
+        }
+        // Legacy devices fix the ring size, so the rings are laid out for all
+        // of it even though only the first `max_size` descriptors are used.
+        let (queue_mem, desc, avail, used) = alloc_queue(size, LEGACY_VRING_ALIGN)?;
+        let queue_pfn = virt_to_phys(queue_mem as *const u8) >> 12;
+        write_port_u32(base_port + VIRTIO_PCI_QUEUE_PFN, queue_pfn as u32);
+        Some(Self {
+            index,
+            ring_size: size,
+            size: size.min(max_size),
+            desc,
+            avail,
+            used,
+            avail_idx: 0,
+            used_idx: 0,
+        })
+    }
+
+    /// Returns how many descriptors the driver uses.
+    pub(crate) fn size(&self) -> u16 {
+        self.size
+    }
+
+    /// Points descriptor `index` at `len` bytes of `buffer`.
+    pub(crate) fn set_desc(&mut self, index: u16, buffer: *const u8, len: u32, flags: u16) {
+        let desc = VirtqDesc {
+            addr: virt_to_phys(buffer),
+            len,
+            flags,
+            next: 0,
+        };
+        unsafe {
+            write_volatile(self.desc.add(index as usize), desc);
+        }
+    }
+
+    /// Makes descriptor `index` available to the device.
+    pub(crate) fn publish(&mut self, index: u16) {
+        let ring = unsafe { (self.avail as *mut u16).add(2) };
+        let slot = (self.avail_idx % self.ring_size) as usize;
+        unsafe {
+            write_volatile(ring.add(slot), index);
+        }
+        self.avail_idx = self.avail_idx.wrapping_add(1);
+        fence(Ordering::SeqCst);
+        unsafe {
+            write_volatile((self.avail as *mut u16).add(1), self.avail_idx);
+        }
+    }
+
+    /// Takes the next descriptor the device has finished with.
+    pub(crate) fn pop_used(&mut self) -> Option<VirtqUsedElem> {
+        let device_idx = unsafe { read_volatile((self.used as *mut u16).add(1)) };
+        if device_idx == self.used_idx {
+            return None;
+        }
+        fence(Ordering::SeqCst);
+        let ring = unsafe { (self.used as *mut u16).add(2) as *mut VirtqUsedElem };
+        let slot = (self.used_idx % self.ring_size) as usize;
+        let elem = unsafe { read_volatile(ring.add(slot)) };
+        self.used_idx = self.used_idx.wrapping_add(1);
+        Some(elem)
+    }
+
+    /// Tells the device that new buffers are available.
+    pub(crate) fn notify(&self, base_port: u16) {
+        write_port_u16(base_port + VIRTIO_PCI_QUEUE_NOTIFY, self.index);
+    }
+}
+
+/// Allocates zeroed rings for `queue_size` entries, starting the used ring at `used_align`.
+pub(crate) fn alloc_queue(
+    queue_size: u16,
+    used_align: usize,
+) -> Option<(*mut u8, *mut VirtqDesc, *mut u8, *mut u8)> {
+    let desc_size = size_of::<VirtqDesc>() * queue_size as usize;
+    let avail_size = 4 + 2 * queue_size as usize + 2;
+    let used_align = used_align.max(align_of::<VirtqUsedElem>());
+    let used_offset = align_up(desc_size + avail_size, used_align);
+    let used_size = 4 + size_of::<VirtqUsedElem>() * queue_size as usize + 2;
+    let total = align_up(used_offset + used_size, 4096);
+    let layout = Layout::from_size_align(total, 4096).ok()?;
+    let mem = unsafe { alloc::alloc::alloc_zeroed(layout) };
+    if mem.is_null() {
+        return None;
+    }
+    let desc = mem as *mut VirtqDesc;
+    let avail = unsafe { mem.add(desc_size) };
+    let used = unsafe { mem.add(used_offset) };
+    Some((mem, desc, avail, used))
+}
+
+fn align_up(value: usize, align: usize) -> usize {
+    (value + align - 1) & !(align - 1)
+}
+
+fn enable_pci_io_master(bus: u8, device: u8, function: u8) {
+    let mut cmd = pci_config_read16(bus, device, function, 0x04);
+    cmd |= 0x1 | 0x4;
+    pci_config_write16(bus, device, function, 0x04, cmd);
+}
+
+fn pci_config_read32(bus: u8, device: u8, function: u8, offset: u8) -> u32 {
+    let address = 0x8000_0000u32
+        | ((bus as u32) << 16)
+        | ((device as u32) << 11)
+        | ((function as u32) << 8)
+        | (offset as u32 & 0xFC);
+    unsafe {
+        let mut addr_port: Port<u32> = Port::new(PCI_CONFIG_ADDRESS);
+        addr_port.write(address);
+        let mut data_port: Port<u32> = Port::new(PCI_CONFIG_DATA);
+        data_port.read()
+    }
+}
+
+fn pci_config_read16(bus: u8, device: u8, function: u8, offset: u8) -> u16 {
+    let value = pci_config_read32(bus, device, function, offset);
+    let shift = (offset & 2) * 8;
+    ((value >> shift) & 0xFFFF) as u16
+}
+
+fn pci_config_write16(bus: u8, device: u8, function: u8, offset: u8, value: u16) {
+    let mut current = pci_config_read32(bus, device, function, offset);
+    let shift = (offset & 2) * 8;
+    current &= !(0xFFFFu32 << shift);
+    current |= (value as u32) << shift;
+    pci_config_write32(bus, device, function, offset, current);
+}
+
+fn pci_config_write32(bus: u8, device: u8, function: u8, offset: u8, value: u32) {
+    let address = 0x8000_0000u32
+        | ((bus as u32) << 16)
+        | ((device as u32) << 11)
+        | ((function as u32) << 8)
+        | (offset as u32 & 0xFC);
+    unsafe {
+        let mut addr_port: Port<u32> = Port::new(PCI_CONFIG_ADDRESS);
+        addr_port.write(address);
+        let mut data_port: Port<u32> = Port::new(PCI_CONFIG_DATA);
+        data_port.write(value);
+    }
+}
+
+pub(crate) fn read_port_u8(port: u16) -> u8 {
+    unsafe {
+        let mut p: Port<u8> = Port::new(port);
+        p.read()
+    }
+}
+
+pub(crate) fn read_port_u32(port: u16) -> u32 {
+    unsafe {
+        let mut p: Port<u32> = Port::new(port);
+        p.read()
+    }
+}
+
+pub(crate) fn read_port_u16(port: u16) -> u16 {
+    unsafe {
+        let mut p: Port<u16> = Port::new(port);
+        p.read()
+    }
+}
+
+pub(crate) fn write_port_u32(port: u16, value: u32) {
+    unsafe {
+        let mut p: Port<u32> = Port::new(port);
+        p.write(value);
+    }
+}
+
+pub(crate) fn write_port_u16(port: u16, value: u16) {
+    unsafe {
+        let mut p: Port<u16> = Port::new(port);
+        p.write(value);
+    }
+}
+
+pub(crate) fn write_port_u8(port: u16, value: u8) {
+    unsafe {
+        let mut p: Port<u8> = Port::new(port);
+        p.write(value);
+    }
+}
@@ -1,36 +1,22 @@
 use alloc::boxed::Box;
 use alloc::vec::Vec;
-use core::alloc::Layout;
 use core::cmp::min;
-use core::mem::{align_of, size_of};
+use core::mem::size_of;
 use core::ptr::{read_volatile, write_volatile};
 
 use spin::Mutex;
-use x86_64::instructions::port::Port;
 
 use crate::virt_to_phys;
+use crate::virtio::{
+    alloc_queue, find_legacy_device, read_port_u16, read_port_u32, write_port_u16, write_port_u32,
+    write_port_u8, PciDevice, VirtqDesc, VirtqUsedElem, VIRTIO_PCI_GUEST_FEATURES,
+    VIRTIO_PCI_HOST_FEATURES, VIRTIO_PCI_QUEUE_NOTIFY, VIRTIO_PCI_QUEUE_NUM, VIRTIO_PCI_QUEUE_PFN,
+    VIRTIO_PCI_QUEUE_SEL, VIRTIO_PCI_STATUS, VIRTIO_STATUS_ACKNOWLEDGE, VIRTIO_STATUS_DRIVER,
+    VIRTIO_STATUS_DRIVER_OK, VIRTIO_STATUS_FEATURES_OK, VIRTQ_DESC_F_WRITE,
+};
 
-const PCI_CONFIG_ADDRESS: u16 = 0xCF8;
-const PCI_CONFIG_DATA: u16 = 0xCFC;
-
-const VIRTIO_VENDOR_ID: u16 = 0x1AF4;
 const VIRTIO_DEVICE_ID_LEGACY_INPUT: u16 = 0x1012;
 
-const VIRTIO_PCI_HOST_FEATURES: u16 = 0x00;
-const VIRTIO_PCI_GUEST_FEATURES: u16 = 0x04;
-const VIRTIO_PCI_QUEUE_PFN: u16 = 0x08;
-const VIRTIO_PCI_QUEUE_NUM: u16 = 0x0C;
-const VIRTIO_PCI_QUEUE_SEL: u16 = 0x0E;
-const VIRTIO_PCI_QUEUE_NOTIFY: u16 = 0x10;
-const VIRTIO_PCI_STATUS: u16 = 0x12;
-
-const VIRTIO_STATUS_ACKNOWLEDGE: u8 = 0x01;
-const VIRTIO_STATUS_DRIVER: u8 = 0x02;
-const VIRTIO_STATUS_FEATURES_OK: u8 = 0x08;
-const VIRTIO_STATUS_DRIVER_OK: u8 = 0x04;
-
-const VIRTQ_DESC_F_WRITE: u16 = 0x2;
-
 const EV_SYN: u16 = 0x00;
 const EV_KEY: u16 = 0x01;
 
@@ -61,22 +47,6 @@ struct VirtioInputEvent {
     value: i32,
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, Default)]
-struct VirtqDesc {
-    addr: u64,
-    len: u32,
-    flags: u16,
-    next: u16,
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Default)]
-struct VirtqUsedElem {
-    id: u32,
-    len: u32,
-}
-
 struct VirtioInput {
     base_port: u16,
     queue_size: u16,
@@ -96,7 +66,7 @@ pub fn virtio_input_init() {
     if state.virtio.is_some() {
         return;
     }
-    let Some(dev) = find_virtio_input_device() else {
+    let Some(dev) = find_legacy_device(VIRTIO_DEVICE_ID_LEGACY_INPUT) else {
         return;
     };
     if let Some(input) = VirtioInput::new(dev) {
@@ -126,92 +96,6 @@ pub fn virtio_input_read_byte() -> Option<u8> {
     state.buffer.pop()
 }
 
-#[derive(Clone, Copy)]
-struct PciDevice {
-    io_base: u16,
-}
-
-fn find_virtio_input_device() -> Option<PciDevice> {
-    for bus in 0u8..=0xff {
-        for device in 0u8..32 {
-            let header = pci_config_read16(bus, device, 0, 0x0E);
-            if header == 0xFFFF {
-                continue;
-            }
-            let functions = if header & 0x80 != 0 { 8 } else { 1 };
-            for function in 0u8..functions {
-                let vendor = pci_config_read16(bus, device, function, 0x00);
-                if vendor == 0xFFFF {
-                    continue;
-                }
-                if vendor != VIRTIO_VENDOR_ID {
-                    continue;
-                }
-                let device_id = pci_config_read16(bus, device, function, 0x02);
-                if device_id != VIRTIO_DEVICE_ID_LEGACY_INPUT {
-                    continue;
-                }
-                let bar0 = pci_config_read32(bus, device, function, 0x10);
-                if bar0 & 0x1 == 0 {
-                    continue;
-                }
-                let io_base = (bar0 & 0xFFFC) as u16;
-                enable_pci_io_master(bus, device, function);
-                return Some(PciDevice { io_base });
-            }
-        }
-    }
-    None
-}
-
-fn enable_pci_io_master(bus: u8, device: u8, function: u8) {
-    let mut cmd = pci_config_read16(bus, device, function, 0x04);
-    cmd |= 0x1 | 0x4;
-    pci_config_write16(bus, device, function, 0x04, cmd);
-}
-
-fn pci_config_read32(bus: u8, device: u8, function: u8, offset: u8) -> u32 {
-    let address = 0x8000_0000u32
-        | ((bus as u32) << 16)
-        | ((device as u32) << 11)
-        | ((function as u32) << 8)
-        | (offset as u32 & 0xFC);
-    unsafe {
-        let mut addr_port: Port<u32> = Port::new(PCI_CONFIG_ADDRESS);
-        addr_port.write(address);
-        let mut data_port: Port<u32> = Port::new(PCI_CONFIG_DATA);
-        data_port.read()
-    }
-}
-
-fn pci_config_read16(bus: u8, device: u8, function: u8, offset: u8) -> u16 {
-    let value = pci_config_read32(bus, device, function, offset);
-    let shift = (offset & 2) * 8;
-    ((value >> shift) & 0xFFFF) as u16
-}
-
-fn pci_config_write16(bus: u8, device: u8, function: u8, offset: u8, value: u16) {
-    let mut current = pci_config_read32(bus, device, function, offset);
-    let shift = (offset & 2) * 8;
-    current &= !(0xFFFFu32 << shift);
-    current |= (value as u32) << shift;
-    pci_config_write32(bus, device, function, offset, current);
-}
-
-fn pci_config_write32(bus: u8, device: u8, function: u8, offset: u8, value: u32) {
-    let address = 0x8000_0000u32
-        | ((bus as u32) << 16)
-        | ((device as u32) << 11)
-        | ((function as u32) << 8)
-        | (offset as u32 & 0xFC);
-    unsafe {
-        let mut addr_port: Port<u32> = Port::new(PCI_CONFIG_ADDRESS);
-        addr_port.write(address);
-        let mut data_port: Port<u32> = Port::new(PCI_CONFIG_DATA);
-        data_port.write(value);
-    }
-}
-
 impl VirtioInput {
     fn new(dev: PciDevice) -> Option<Self> {
         let base_port = dev.io_base;
@@ -235,7 +119,7 @@ impl VirtioInput {
         }
 
         let queue_size = min(queue_size, 64);
-        let (queue_mem, desc, avail, used) = alloc_queue(queue_size)?;
+        let (queue_mem, desc, avail, used) = alloc_queue(queue_size, 4)?;
         let events = alloc_events(queue_size as usize);
 
         let queue_pfn = virt_to_phys(queue_mem as *const u8) >> 12;
@@ -353,63 +237,6 @@ fn alloc_events(count: usize) -> Box<[VirtioInputEvent]> {
     events.into_boxed_slice()
 }
 
-fn alloc_queue(queue_size: u16) -> Option<(*mut u8, *mut VirtqDesc, *mut u8, *mut u8)> {
-    let desc_size = size_of::<VirtqDesc>() * queue_size as usize;
-    let avail_size = 4 + 2 * queue_size as usize + 2;
-    let used_align = 4usize.max(align_of::<VirtqUsedElem>());
-    let used_offset = align_up(desc_size + avail_size, used_align);
-    let used_size = 4 + size_of::<VirtqUsedElem>() * queue_size as usize + 2;
-    let total = align_up(used_offset + used_size, 4096);
-    let layout = Layout::from_size_align(total, 4096).ok()?;
-    let mem = unsafe { alloc::alloc::alloc_zeroed(layout) };
-    if mem.is_null() {
-        return None;
-    }
-    let desc = mem as *mut VirtqDesc;
-    let avail = unsafe { mem.add(desc_size) };
-    let used = unsafe { mem.add(used_offset) };
-    Some((mem, desc, avail, used))
-}
-
-fn align_up(value: usize, align: usize) -> usize {
-    (value + align - 1) & !(align - 1)
-}
-
-fn read_port_u32(port: u16) -> u32 {
-    unsafe {
-        let mut p: Port<u32> = Port::new(port);
-        p.read()
-    }
-}
-
-fn read_port_u16(port: u16) -> u16 {
-    unsafe {
-        let mut p: Port<u16> = Port::new(port);
-        p.read()
-    }
-}
-
-fn write_port_u32(port: u16, value: u32) {
-    unsafe {
-        let mut p: Port<u32> = Port::new(port);
-        p.write(value);
-    }
-}
-
-fn write_port_u16(port: u16, value: u16) {
-    unsafe {
-        let mut p: Port<u16> = Port::new(port);
-        p.write(value);
-    }
-}
-
-fn write_port_u8(port: u16, value: u8) {
-    unsafe {
-        let mut p: Port<u8> = Port::new(port);
-        p.write(value);
-    }
-}
-
 fn keycode_to_ascii(code: u16, shift: bool) -> Option<u8> {
     match code {
         2 => Some(if shift { b'!' } else { b'1' }),
@@ -0,0 +1,235 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+
+use crate::virtio::{
+    find_legacy_device, read_port_u16, read_port_u32, read_port_u8, write_port_u32, write_port_u8,
+    Virtqueue, VIRTIO_PCI_CONFIG, VIRTIO_PCI_GUEST_FEATURES, VIRTIO_PCI_HOST_FEATURES,
+    VIRTIO_PCI_ISR, VIRTIO_PCI_STATUS, VIRTIO_STATUS_ACKNOWLEDGE, VIRTIO_STATUS_DRIVER,
+    VIRTIO_STATUS_DRIVER_OK, VIRTIO_STATUS_FEATURES_OK, VIRTQ_DESC_F_WRITE,
+};
+
+const VIRTIO_DEVICE_ID_LEGACY_NET: u16 = 0x1000;
+
+const VIRTIO_NET_F_MAC: u32 = 1 << 5;
+const VIRTIO_NET_F_STATUS: u32 = 1 << 16;
+const VIRTIO_NET_S_LINK_UP: u16 = 1;
+
+const ISR_QUEUE: u8 = 0x1;
+const ISR_CONFIG: u8 = 0x2;
+
+const RX_QUEUE: u16 = 0;
+const TX_QUEUE: u16 = 1;
+const MAX_QUEUE_SIZE: u16 = 64;
+
+/// Legacy `virtio_net_hdr` without mergeable buffers; always zero on transmit.
+const NET_HDR_LEN: usize = 10;
+/// Largest Ethernet frame without FCS.
+const MAX_FRAME_LEN: usize = 1514;
+const BUFFER_LEN: usize = NET_HDR_LEN + MAX_FRAME_LEN;
+/// Received frames kept until `virtio_net_receive` takes them.
+const RX_BACKLOG: usize = 64;
+
+/// MAC used when the device does not offer `VIRTIO_NET_F_MAC`.
+const FALLBACK_MAC: [u8; 6] = [0x02, 0x52, 0x5a, 0x00, 0x00, 0x01];
+
+static NET_STATE: Mutex<Option<VirtioNet>> = Mutex::new(None);
+
+struct VirtioNet {
+    base_port: u16,
+    irq_line: u8,
+    features: u32,
+    mac: [u8; 6],
+    link_up: bool,
+    rx: Virtqueue,
+    tx: Virtqueue,
+    rx_buffers: Vec<Vec<u8>>,
+    tx_buffers: Vec<Vec<u8>>,
+    tx_free: Vec<u16>,
+    backlog: VecDeque<Vec<u8>>,
+}
+
+unsafe impl Send for VirtioNet {}
+
+/// Probes for a legacy virtio-net PCI device and starts it; returns its IRQ line.
+pub fn virtio_net_init() -> Option<u8> {
+    let mut state = NET_STATE.lock();
+    if let Some(net) = state.as_ref() {
+        return Some(net.irq_line);
+    }
+    let dev = find_legacy_device(VIRTIO_DEVICE_ID_LEGACY_NET)?;
+    let net = VirtioNet::new(dev.io_base, dev.irq_line)?;
+    let irq_line = net.irq_line;
+    *state = Some(net);
+    drop(state);
+    // Only these lines have handlers; on others the rings are reaped by polling.
+    if matches!(irq_line, 9..=11) {
+        crate::unmask_irq(irq_line);
+    }
+    Some(irq_line)
+}
+
+/// Returns the device MAC address once `virtio_net_init` has succeeded.
+pub fn virtio_net_mac() -> Option<[u8; 6]> {
+    with_device(|net| net.mac)
+}
+
+/// Returns true if a device is present and reports its link up.
+pub fn virtio_net_link_up() -> bool {
+    with_device(|net| net.link_up).unwrap_or(false)
+}
+
+/// Queues one Ethernet frame; returns false if there is no device or no free slot.
+pub fn virtio_net_transmit(frame: &[u8]) -> bool {
+    with_device(|net| net.transmit(frame)).unwrap_or(false)
+}
+
+/// Takes the next received Ethernet frame.
+pub fn virtio_net_receive() -> Option<Vec<u8>> {
+    with_device(|net| {
+        net.reap_rx();
+        net.backlog.pop_front()
+    })
+    .flatten()
+}
+
+/// Services a virtio-net interrupt on a shared PCI line.
+pub(crate) fn virtio_net_handle_interrupt() {
+    // The interrupted code may hold the lock; its next call reaps the rings.
+    let Some(mut state) = NET_STATE.try_lock() else {
+        return;
+    };
+    let Some(net) = state.as_mut() else {
+        return;
+    };
+    let isr = read_port_u8(net.base_port + VIRTIO_PCI_ISR);
+    if isr & ISR_CONFIG != 0 {
+        net.link_up = net.read_link_status();
+    }
+    if isr & ISR_QUEUE != 0 {
+        net.reap_rx();
+        net.reap_tx();
+    }
+}
+
+fn with_device<T>(f: impl FnOnce(&mut VirtioNet) -> T) -> Option<T> {
+    interrupts::without_interrupts(|| NET_STATE.lock().as_mut().map(f))
+}
+
+impl VirtioNet {
+    fn new(base_port: u16, irq_line: u8) -> Option<Self> {
+        write_port_u8(base_port + VIRTIO_PCI_STATUS, 0);
+        let mut status = VIRTIO_STATUS_ACKNOWLEDGE;
+        write_port_u8(base_port + VIRTIO_PCI_STATUS, status);
+        status |= VIRTIO_STATUS_DRIVER;
+        write_port_u8(base_port + VIRTIO_PCI_STATUS, status);
+
+        let offered = read_port_u32(base_port + VIRTIO_PCI_HOST_FEATURES);
+        let features = offered & (VIRTIO_NET_F_MAC | VIRTIO_NET_F_STATUS);
+        write_port_u32(base_port + VIRTIO_PCI_GUEST_FEATURES, features);
+        status |= VIRTIO_STATUS_FEATURES_OK;
+        write_port_u8(base_port + VIRTIO_PCI_STATUS, status);
+
+        let rx = Virtqueue::setup(base_port, RX_QUEUE, MAX_QUEUE_SIZE)?;
+        let tx = Virtqueue::setup(base_port, TX_QUEUE, MAX_QUEUE_SIZE)?;
+        let rx_buffers = (0..rx.size()).map(|_| vec![0u8; BUFFER_LEN]).collect();
+        let tx_buffers = (0..tx.size()).map(|_| vec![0u8; BUFFER_LEN]).collect();
+        let tx_free = (0..tx.size()).rev().collect();
+
+        let mut net = Self {
+            base_port,
+            irq_line,
+            features,
+            mac: FALLBACK_MAC,
+            link_up: true,
+            rx,
+            tx,
+            rx_buffers,
+            tx_buffers,
+            tx_free,
+            backlog: VecDeque::new(),
+        };
+        if features & VIRTIO_NET_F_MAC != 0 {
+            for (offset, byte) in net.mac.iter_mut().enumerate() {
+                *byte = read_port_u8(base_port + VIRTIO_PCI_CONFIG + offset as u16);
+            }
+        }
+        net.link_up = net.read_link_status();
+        for index in 0..net.rx.size() {
+            net.post_rx(index);
+        }
+        status |= VIRTIO_STATUS_DRIVER_OK;
+        write_port_u8(base_port + VIRTIO_PCI_STATUS, status);
+        net.rx.notify(base_port);
+        Some(net)
+    }
+
+    fn read_link_status(&self) -> bool {
+        if self.features & VIRTIO_NET_F_STATUS == 0 {
+            return true;
+        }
+        let status = read_port_u16(self.base_port + VIRTIO_PCI_CONFIG + 6);
+        status & VIRTIO_NET_S_LINK_UP != 0
+    }
+
+    fn post_rx(&mut self, index: u16) {
+        let buffer = &self.rx_buffers[index as usize];
+        self.rx.set_desc(
+            index,
+            buffer.as_ptr(),
+            BUFFER_LEN as u32,
+            VIRTQ_DESC_F_WRITE,
+        );
+        self.rx.publish(index);
+    }
+
+    fn reap_rx(&mut self) {
+        let mut reposted = false;
+        while let Some(elem) = self.rx.pop_used() {
+            let index = elem.id as u16;
+            let Some(buffer) = self.rx_buffers.get(index as usize) else {
+                continue;
+            };
+            let len = (elem.len as usize).min(BUFFER_LEN);
+            if len > NET_HDR_LEN && self.backlog.len() < RX_BACKLOG {
+                self.backlog.push_back(buffer[NET_HDR_LEN..len].to_vec());
+            }
+            self.post_rx(index);
+            reposted = true;
+        }
+        if reposted {
+            self.rx.notify(self.base_port);
+        }
+    }
+
+    fn reap_tx(&mut self) {
+        while let Some(elem) = self.tx.pop_used() {
+            let index = elem.id as u16;
+            if index < self.tx.size() {
+                self.tx_free.push(index);
+            }
+        }
+    }
+
+    fn transmit(&mut self, frame: &[u8]) -> bool {
+        if frame.len() > MAX_FRAME_LEN || !self.link_up {
+            return false;
+        }
+        self.reap_tx();
+        let Some(index) = self.tx_free.pop() else {
+            return false;
+        };
+        let buffer = &mut self.tx_buffers[index as usize];
+        buffer[..NET_HDR_LEN].fill(0);
+        buffer[NET_HDR_LEN..NET_HDR_LEN + frame.len()].copy_from_slice(frame);
+        let len = (NET_HDR_LEN + frame.len()) as u32;
+        let ptr = buffer.as_ptr();
+        self.tx.set_desc(index, ptr, len, 0);
+        self.tx.publish(index);
+        self.tx.notify(self.base_port);
+        true
+    }
+}
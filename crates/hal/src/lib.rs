@@ -19,6 +19,8 @@ pub enum Errno {
     QueueFull,
     QueueEmpty,
     Unimplemented,
+    /// A capability was requested without being granted.
+    PermissionDenied,
 }
 
 /// Page table mapping flags.
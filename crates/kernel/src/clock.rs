@@ -0,0 +1,51 @@
+#[cfg(feature = "aarch64")]
+use arch_aarch64 as arch;
+#[cfg(feature = "x86_64")]
+use arch_x86_64 as arch;
+
+/// Nanoseconds per PIT tick (the PIT is programmed at 100 Hz).
+const NS_PER_TICK: u64 = 10_000_000;
+
+/// Returns monotonic nanoseconds since boot.
+#[cfg(feature = "x86_64")]
+pub fn now_ns() -> u64 {
+    arch::ticks().saturating_mul(NS_PER_TICK)
+}
+
+/// Returns monotonic nanoseconds since boot, read from the generic timer.
+#[cfg(all(not(feature = "x86_64"), feature = "aarch64"))]
+pub fn now_ns() -> u64 {
+    let frequency = arch::counter_frequency();
+    if frequency == 0 {
+        return 0;
+    }
+    (u128::from(arch::counter()) * 1_000_000_000 / u128::from(frequency)) as u64
+}
+
+/// Returns monotonic nanoseconds since boot (no timer source yet).
+#[cfg(not(any(feature = "x86_64", feature = "aarch64")))]
+pub fn now_ns() -> u64 {
+    0
+}
+
+/// Returns timer ticks since boot.
+#[cfg(feature = "x86_64")]
+pub fn ticks() -> u64 {
+    arch::ticks()
+}
+
+/// Returns timer ticks since boot, at the same 100 Hz rate as the PIT.
+#[cfg(not(feature = "x86_64"))]
+pub fn ticks() -> u64 {
+    now_ns() / NS_PER_TICK
+}
+
+/// Parks the CPU until the next timer tick instead of spinning on the clock.
+pub fn wait_tick() {
+    #[cfg(feature = "x86_64")]
+    arch::wait_for_interrupt();
+    #[cfg(all(not(feature = "x86_64"), feature = "aarch64"))]
+    arch::wait_for_event();
+    #[cfg(not(any(feature = "x86_64", feature = "aarch64")))]
+    core::hint::spin_loop();
+}
@@ -1,13 +1,20 @@
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::fmt::{self, Write};
 
 #[cfg(feature = "x86_64")]
 use arch_x86_64 as arch;
 #[cfg(feature = "aarch64")]
 use platform_qemu_aarch64_virt as platform;
-#[cfg(feature = "x86_64")]
 use spin::Mutex;
 
 use kernel_core::FramebufferInfo;
+pub use user_console_service::LogLevel;
+use user_console_service::{
+    format_log, LogBuffer, LogFilter, Notification, NotificationQueue, LOG_BUFFER_LINES,
+    NOTIFICATION_HISTORY,
+};
+use user_tui_shell::MAX_OUTPUT_BYTES;
 
 #[cfg(feature = "x86_64")]
 use crate::framebuffer::FramebufferConsole;
@@ -15,6 +22,25 @@ use crate::framebuffer::FramebufferConsole;
 #[cfg(feature = "x86_64")]
 static FRAMEBUFFER: Mutex<Option<FramebufferConsole>> = Mutex::new(None);
 
+/// Upper bound on bytes kept by a single output capture.
+const CAPTURE_LIMIT: usize = MAX_OUTPUT_BYTES;
+
+/// Active output captures, innermost last; every print is appended to all of them.
+static CAPTURES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Recent kernel and module log lines served by `log tail`.
+static LOG: Mutex<LogBuffer> = Mutex::new(LogBuffer::new(LOG_BUFFER_LINES));
+
+/// Notifications posted by background producers, shown between prompts.
+static NOTIFICATIONS: Mutex<NotificationQueue> =
+    Mutex::new(NotificationQueue::new(NOTIFICATION_HISTORY));
+
+/// Target used for log lines emitted by the kernel itself.
+pub const KERNEL_LOG_TARGET: &str = "kernel";
+
+/// Per-target minimum levels applied before a line is printed or buffered.
+static LOG_FILTER: Mutex<LogFilter> = Mutex::new(LogFilter::new(LogLevel::Info));
+
 /// Initializes the early serial console.
 pub fn init_early() {
     #[cfg(feature = "x86_64")]
@@ -111,6 +137,128 @@ pub fn read_byte() -> u8 {
     0
 }
 
+/// Writes raw bytes to the serial line, bypassing the screen and captures.
+pub fn write_serial(bytes: &[u8]) {
+    #[cfg(feature = "x86_64")]
+    for byte in bytes {
+        arch::serial_write_byte(*byte);
+    }
+    #[cfg(feature = "aarch64")]
+    for byte in bytes {
+        platform::uart_write(*byte);
+    }
+    #[cfg(not(any(feature = "x86_64", feature = "aarch64")))]
+    let _ = bytes;
+}
+
+/// Clears the visible console and moves the cursor to the top-left corner.
+pub fn clear_screen() {
+    #[cfg(feature = "x86_64")]
+    {
+        arch::serial_write_str("\x1b[2J\x1b[H");
+        let mut fb = FRAMEBUFFER.lock();
+        if let Some(console) = fb.as_mut() {
+            console.clear();
+        } else {
+            arch::vga_clear();
+        }
+    }
+    #[cfg(feature = "aarch64")]
+    {
+        for byte in b"\x1b[2J\x1b[H" {
+            platform::uart_write(*byte);
+        }
+    }
+}
+
+/// Starts collecting console output while still echoing it.
+pub fn begin_capture() {
+    CAPTURES.lock().push(String::new());
+}
+
+/// Stops the innermost capture and returns what was printed since it began.
+pub fn end_capture() -> String {
+    CAPTURES.lock().pop().unwrap_or_default()
+}
+
+/// Logs a kernel line; see [`log_target`].
+pub fn log(level: LogLevel, args: fmt::Arguments) {
+    log_target(KERNEL_LOG_TARGET, level, args);
+}
+
+/// Prints a log line for `target` and records it, unless filtered out.
+pub fn log_target(target: &str, level: LogLevel, args: fmt::Arguments) {
+    if !LOG_FILTER.lock().enabled(target, level) {
+        return;
+    }
+    let line = format_log(0, level, &alloc::format!("{}", args));
+    LOG.lock().push(&line);
+    print(format_args!("{}\n", line));
+}
+
+/// Sets the minimum level logged for `target`.
+pub fn set_log_level(target: &str, level: LogLevel) {
+    LOG_FILTER.lock().set(target, level);
+}
+
+/// Returns the default level and the per-target overrides.
+pub fn log_levels() -> (LogLevel, Vec<(String, LogLevel)>) {
+    let filter = LOG_FILTER.lock();
+    (filter.default_level(), filter.targets())
+}
+
+/// Returns the last `count` log lines, or all buffered lines for `None`.
+pub fn log_tail(count: Option<usize>) -> Vec<String> {
+    LOG.lock().tail(count)
+}
+
+/// Returns the sequence number of the next log line.
+pub fn log_cursor() -> u64 {
+    LOG.lock().next_seq()
+}
+
+/// Returns log lines recorded at or after `cursor` and the cursor to poll next.
+pub fn log_since(cursor: u64) -> (Vec<String>, u64) {
+    let log = LOG.lock();
+    (log.since(cursor), log.next_seq())
+}
+
+/// Posts a notification for the shell to show before its next prompt.
+pub fn notify(source: &str, message: &str) {
+    NOTIFICATIONS.lock().post(source, message);
+}
+
+/// Returns notifications not yet shown and marks them as shown.
+pub fn take_notifications() -> Vec<Notification> {
+    NOTIFICATIONS.lock().take_pending()
+}
+
+/// Returns every kept notification, oldest first.
+pub fn notification_history() -> Vec<Notification> {
+    NOTIFICATIONS.lock().history()
+}
+
+/// Drops every kept notification.
+pub fn clear_notifications() {
+    NOTIFICATIONS.lock().clear();
+}
+
+fn append_captures(s: &str) {
+    let mut captures = CAPTURES.lock();
+    for capture in captures.iter_mut() {
+        let room = CAPTURE_LIMIT.saturating_sub(capture.len());
+        if room >= s.len() {
+            capture.push_str(s);
+        } else {
+            let mut end = room;
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            capture.push_str(&s[..end]);
+        }
+    }
+}
+
 struct ConsoleWriter;
 
 impl Write for ConsoleWriter {
@@ -131,6 +279,7 @@ impl Write for ConsoleWriter {
                 platform::uart_write(byte);
             }
         }
+        append_captures(s);
         Ok(())
     }
 }
@@ -151,3 +300,17 @@ macro_rules! kprintln {
         $crate::kprint!("{}\n", format_args!($($arg)*));
     }};
 }
+
+#[macro_export]
+macro_rules! klog {
+    (target: $target:expr, $($arg:tt)*) => {{
+        $crate::console::log_target(
+            $target,
+            $crate::console::LogLevel::Info,
+            format_args!($($arg)*),
+        );
+    }};
+    ($($arg:tt)*) => {{
+        $crate::console::log($crate::console::LogLevel::Info, format_args!($($arg)*));
+    }};
+}
@@ -45,8 +45,10 @@ impl FramebufferConsole {
         })
     }
 
-    /// Clears the framebuffer with the background color.
+    /// Clears the framebuffer with the background color and homes the cursor.
     pub fn clear(&mut self) {
+        self.col = 0;
+        self.row = 0;
         let total_rows = self.info.height as usize;
         let fb_ptr = self.info.addr as *mut u8;
         for y in 0..total_rows {
@@ -14,14 +14,18 @@ use platform_qemu_x86_64 as platform;
 use platform_qemu_aarch64_virt as platform;
 
 pub mod boot;
+pub mod clock;
 pub mod console;
 #[cfg(feature = "x86_64")]
 mod framebuffer;
 #[cfg(feature = "x86_64")]
 mod font;
+#[cfg(feature = "x86_64")]
+mod nic;
 pub mod smp;
 pub mod allocator;
 pub mod init;
+pub mod power;
 pub mod shell;
 
 use kernel_core::BootInfo;
@@ -30,7 +34,7 @@ use kernel_core::BootInfo;
 pub fn entry(boot_info: BootInfo) -> ! {
     console::init_framebuffer(boot_info.framebuffer);
     allocator::init_heap();
-    kprintln!("Ruzzle OS: kernel entry");
+    klog!("Ruzzle OS: kernel entry");
     #[cfg(feature = "x86_64")]
     arch::set_memory_offsets(
         boot_info.hhdm_offset.unwrap_or(0),
@@ -43,6 +47,10 @@ pub fn entry(boot_info: BootInfo) -> ! {
     arch::virtio_input_init();
     #[cfg(feature = "x86_64")]
     arch::usb_input_init();
+    #[cfg(feature = "x86_64")]
+    if let Some(irq) = arch::virtio_net_init() {
+        klog!("virtio-net: ready on irq {}", irq);
+    }
     #[cfg(feature = "aarch64")]
     arch::init();
     #[cfg(feature = "x86_64")]
@@ -53,7 +61,7 @@ pub fn entry(boot_info: BootInfo) -> ! {
     #[cfg(feature = "qemu_virt")]
     platform::init();
 
-    kprintln!(
+    klog!(
         "boot: regions={}, kernel=[{:#x}-{:#x}]",
         boot_info.memory_map.len(),
         boot_info.kernel_start,
@@ -68,15 +76,15 @@ pub fn entry(boot_info: BootInfo) -> ! {
     if let Some(initramfs) = initramfs_slice {
         match init::load_init_process(initramfs) {
             Ok(process) => {
-                kprintln!("init: loaded pid={} entry={:#x}", process.pid, process.ctx.pc);
+                klog!("init: loaded pid={} entry={:#x}", process.pid, process.ctx.pc);
             }
             Err(err) => {
-                kprintln!("init: failed to load ({:?})", err);
+                klog!("init: failed to load ({:?})", err);
             }
         }
     } else {
-        kprintln!("init: no initramfs provided");
+        klog!("init: no initramfs provided");
     }
 
-    shell::run(initramfs_slice);
+    shell::run(initramfs_slice, boot_info.usable_memory_bytes());
 }
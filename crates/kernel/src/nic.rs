@@ -0,0 +1,45 @@
+use alloc::vec::Vec;
+
+use arch_x86_64 as arch;
+use user_net_service::device::{check_frame, DeviceError, NetDevice};
+use user_net_service::MacAddr;
+
+/// Interface name the virtio-net card is attached as.
+pub const NIC_IFACE: &str = "eth0";
+
+/// `NetDevice` backed by the x86_64 virtio-net driver.
+pub struct VirtioNet {
+    mac: MacAddr,
+}
+
+impl VirtioNet {
+    /// Returns the card if `arch::virtio_net_init` found one.
+    pub fn probe() -> Option<Self> {
+        arch::virtio_net_mac().map(|mac| Self { mac: MacAddr(mac) })
+    }
+}
+
+impl NetDevice for VirtioNet {
+    fn mac(&self) -> MacAddr {
+        self.mac
+    }
+
+    fn link_up(&self) -> bool {
+        arch::virtio_net_link_up()
+    }
+
+    fn transmit(&mut self, frame: &[u8]) -> Result<(), DeviceError> {
+        check_frame(frame)?;
+        if !self.link_up() {
+            return Err(DeviceError::NotReady);
+        }
+        if !arch::virtio_net_transmit(frame) {
+            return Err(DeviceError::QueueFull);
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        arch::virtio_net_receive()
+    }
+}
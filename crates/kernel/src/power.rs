@@ -0,0 +1,14 @@
+#[cfg(feature = "aarch64")]
+use arch_aarch64 as arch;
+#[cfg(feature = "x86_64")]
+use arch_x86_64 as arch;
+
+/// Parks the CPU forever once the shutdown sequence has finished.
+pub fn halt() -> ! {
+    #[cfg(any(feature = "x86_64", feature = "aarch64"))]
+    arch::halt_loop();
+    #[cfg(not(any(feature = "x86_64", feature = "aarch64")))]
+    loop {
+        core::hint::spin_loop();
+    }
+}
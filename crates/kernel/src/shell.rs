@@ -2,25 +2,130 @@ use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU16, AtomicU8, Ordering};
 
-use kernel_core::{parse_initramfs, parse_module_bundle, parse_module_manifest, ModuleManifest};
+use hal::Errno;
+use kernel_core::{
+    dispatch_sandboxed, manifest_caps, parse_initramfs, parse_module_bundle,
+    parse_module_manifest, ModuleManifest, SandboxTable, Syscall, SyscallFilter, SyscallResult,
+};
+use ruzzle_protocol::shell::{
+    describe_shell, encode_command, is_authorized, IpCommand, ModuleInfo, RouteCommand,
+    ShellCommand, ShellErrorCode, ShellResponse, ShellStatus, SlotInfo,
+};
+use user_clipboard_service::{encode_osc52, Clipboard, Osc52Feed, Osc52Reader};
+use user_console_service::BELL;
+use user_container_service::{ContainerManager, ContainerSpec};
+use user_dhcp_server::{format_ipv4, parse_ipv4, DhcpConfig, DhcpServer, LeaseState};
 use user_file_manager::FileManager;
-use user_fs_service::{FileSystem, FsError};
-use user_net_service::NetManager;
-use user_puzzle_board::{BoardError, PuzzleBoard, PuzzleSlot};
-use user_session_service::SessionManager;
-use user_settings_service::SystemSettings;
+use user_fs_service::{
+    format_mode, parse_mode, EvictionPolicy, FileKind, FileSystem, FsError, Identity,
+};
+use user_gpu_service::{parse_tensor, GpuDevice, GpuError, Tensor};
+use user_net_manager::{NetProfile, NetProfileManager};
+use user_net_service::config::parse_net_config;
+use user_net_service::dns::{
+    parse_resolv_conf, set_self_entry, DnsError, DnsResolver, ResolveSource,
+};
+use user_net_service::firewall::{Direction, FirewallAction, FirewallRule, FirewallTable};
+use user_net_service::icmp::{echo_reply_for, PingSession};
+use user_net_service::{parse_ipv4_cidr, MacAddr, NetError, NetManager, RouteEntry};
+use user_puzzle_board::{
+    parse_board_config, BoardError, ModuleStatus, PlanCandidate, PuzzleBoard, PuzzleSlot,
+    SwapError, SwapLifecycle,
+};
+use user_server_stack::{
+    format_http_top, format_shutdown_report, HttpRequest, HttpResponse, ServerConfig, ServerError,
+    ServerStack,
+};
+use user_session_service::{SessionError, SessionManager, ShellRegistry, DEFAULT_SHELL_MODULE};
+use user_settings_service::{SettingsWatcher, SystemSettings};
+use user_setup_wizard::apply::{
+    apply_net_profile, parse_system_manifest, plan_apply, ApplyStep, SystemState,
+};
 use user_setup_wizard::{run_first_boot, SetupPlan, SetupError};
-use user_sysinfo_service::{build_system_info, format_system_info, SystemMetrics};
-use user_text_editor::TextBuffer;
+use user_sysinfo_service::{
+    build_system_info, format_duration_ns, BootProfile, format_metrics, format_system_info, MetricsRegistry,
+    NetworkStatus, SystemMetrics,
+};
+use user_text_editor::EditError;
+use user_tui_shell::batch::{
+    format_archive_export, run_batch, BatchCollector, ARCHIVE_EXPORT, BATCH_BEGIN, FRAMED_BEGIN,
+};
+use user_tui_shell::editor::{
+    format_editor_buffer, format_editor_help, parse_editor_command, EditorCommand, LineEditor,
+};
 use user_tui_shell::{
-    format_catalog, format_graph, format_help, format_log_tail_empty, format_modules,
-    format_processes, format_slots, format_unknown_command, parse_command, Command, GraphRow,
-    ModuleRow, ProcessRow, SlotRow,
+    confirmation_prompt, expand_echo, expand_last_status, format_boot_report, format_catalog,
+    format_checksum, format_description, format_df, format_du, format_graph, format_graph_dot,
+    format_help, format_log_follow_header, format_log_tail_empty, format_module_status,
+    format_modules, format_plug_plan, format_processes, format_slots, format_stat,
+    format_unknown_command, format_watch_header, format_word_count, from_ipc, is_confirmed,
+    parse_command, render_prompt, run_sequence, sequence_succeeded, to_ipc, BootTimingRow,
+    ChainStep,
+    Command, CommandResult, DfRow, ExitCode, GraphRow, ModuleRow, ModuleStatusRow, PlanRow,
+    ProcessRow, SlotRow, StatInfo, Style, WordCount, DEFAULT_LOG_TAIL_LINES,
 };
 use user_user_service::{default_home_dir, UserManager};
+use user_web_console::{
+    bridge_frame, decode_frame, encode_frame, install_assets, register_routes, BridgeRequest,
+    Frame, Opcode, StreamBridge, WebConsoleError, ASSETS, ASSET_ROOT, WS_PATH,
+};
+
+use crate::console::LogLevel;
+use crate::{clock, console, klog, kprint, kprintln, power, smp};
+
+/// Grace period given to in-flight HTTP requests when the server stops.
+const SERVER_DRAIN_GRACE_NS: u64 = 5_000_000_000;
+
+/// Initramfs entry whose filesystem archive seeds the tree at boot.
+const ROOTFS_ARCHIVE: &str = "rootfs.rzfs";
+
+/// Where the plugged board is saved between boots.
+const BOARD_CONFIG: &str = "/system/config/board";
+/// Directory holding the saved network configuration.
+const NET_CONFIG_DIR: &str = "/etc/net";
+/// Interfaces and manual routes saved between boots.
+const NET_INTERFACES_CONFIG: &str = "/etc/net/interfaces";
+/// Network profiles saved between boots.
+const NET_PROFILES_CONFIG: &str = "/etc/net/profiles";
+
+/// Static host table consulted before DNS.
+const HOSTS_FILE: &str = "/etc/hosts";
+/// DNS servers used when the `dns` setting is empty.
+const RESOLV_CONF: &str = "/etc/resolv.conf";
 
-use crate::{console, kprint, kprintln, smp};
+/// Board changes listed under `slots`.
+const SLOT_HISTORY_SHOWN: usize = 5;
+
+/// Slots locked once boot finishes; unplug and swap need `--override`.
+const LOCKED_SLOTS: [&str; 2] = ["ruzzle.slot.console@1", "ruzzle.slot.shell@1"];
+
+/// Directories mounted as size-bounded tmpfs at boot.
+const TMPFS_DIRS: [&str; 2] = ["/tmp", "/var/tmp"];
+
+/// Exit status reported by the command being dispatched.
+static COMMAND_STATUS: AtomicU8 = AtomicU8::new(0);
+
+/// Error code of the failing command, or 0 when it set none.
+static COMMAND_ERROR: AtomicU16 = AtomicU16::new(0);
+
+/// Prints an error line and marks the current command as failed.
+macro_rules! kfail {
+    ($($arg:tt)*) => {{
+        COMMAND_STATUS.store(ExitCode::FAILURE.0, Ordering::Relaxed);
+        kprintln!($($arg)*);
+    }};
+}
+
+/// Like `kfail!` for a filesystem error, also recording its error code.
+macro_rules! kfail_fs {
+    ($what:expr, $err:expr) => {{
+        let err = $err;
+        COMMAND_ERROR.store(fs_error_code(&err).as_u16(), Ordering::Relaxed);
+        kfail!("{} error: {:?}", $what, err);
+    }};
+}
 
 #[derive(Debug, Clone)]
 struct ModuleEntry {
@@ -28,6 +133,41 @@ struct ModuleEntry {
     manifest: Option<ModuleManifest>,
     running: bool,
     verified: bool,
+    /// Failed starts since the module last started.
+    failures: u32,
+    /// Milliseconds since boot and reason of the last failed start.
+    last_failure: Option<(u64, String)>,
+    /// Microseconds since boot when the last successful start began, and its duration.
+    start_timing: Option<(u64, u64)>,
+}
+
+impl ModuleEntry {
+    fn new(name: String, manifest: Option<ModuleManifest>, verified: bool) -> Self {
+        Self {
+            name,
+            manifest,
+            running: false,
+            verified,
+            failures: 0,
+            last_failure: None,
+            start_timing: None,
+        }
+    }
+
+    /// Marks the module running, clears its consecutive failure count and
+    /// records how long the start that began at `begin_ns` took.
+    fn mark_started(&mut self, begin_ns: u64) {
+        self.running = true;
+        self.failures = 0;
+        let elapsed_ns = clock::now_ns().saturating_sub(begin_ns);
+        self.start_timing = Some((begin_ns / 1_000, elapsed_ns / 1_000));
+    }
+
+    /// Counts a failed start, stamped with the current uptime.
+    fn record_failure(&mut self, reason: &str) {
+        self.failures += 1;
+        self.last_failure = Some((clock::now_ns() / 1_000_000, reason.to_string()));
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +177,88 @@ struct CatalogEntry {
     verified: bool,
 }
 
+/// Module start/stop steps driven by a staged `plug --swap`.
+struct ModuleLifecycle<'a> {
+    modules: &'a mut Vec<ModuleEntry>,
+    sandbox: &'a mut SandboxTable,
+    fs: &'a FileSystem,
+}
+
+impl SwapLifecycle for ModuleLifecycle<'_> {
+    type Error = String;
+
+    fn drain(&mut self, module: &str) -> Result<(), String> {
+        match self.modules.iter().find(|entry| entry.name == module) {
+            Some(entry) if entry.running => {
+                klog!(target: module, "module draining: {}", module);
+                Ok(())
+            }
+            Some(_) => Err(format!("provider not running: {}", module)),
+            None => Err(format!("provider not installed: {}", module)),
+        }
+    }
+
+    fn start(&mut self, module: &str) -> Result<(), String> {
+        let begin = clock::now_ns();
+        let entry = self
+            .modules
+            .iter_mut()
+            .find(|entry| entry.name == module)
+            .ok_or_else(|| format!("module not found: {}", module))?;
+        if entry.running {
+            return Ok(());
+        }
+        if let Some(manifest) = &entry.manifest {
+            let missing = manifest.missing_paths(|path| self.fs.metadata(path).is_ok());
+            if !missing.is_empty() {
+                return Err(format!("module waiting for paths: {}", missing.join(", ")));
+            }
+            let Ok(filter) = SyscallFilter::from_manifest(manifest) else {
+                entry.record_failure("invalid capabilities");
+                return Err(format!("invalid capabilities: {}", module));
+            };
+            self.sandbox.install(module, filter);
+        }
+        entry.mark_started(begin);
+        klog!(target: module, "module started: {}", module);
+        Ok(())
+    }
+
+    fn stop(&mut self, module: &str) -> Result<(), String> {
+        if module == "init" {
+            return Err("init cannot be stopped".to_string());
+        }
+        let entry = self
+            .modules
+            .iter_mut()
+            .find(|entry| entry.name == module)
+            .ok_or_else(|| format!("module not found: {}", module))?;
+        entry.running = false;
+        self.sandbox.remove(module);
+        klog!(target: module, "module stopped: {}", module);
+        Ok(())
+    }
+
+    fn resume(&mut self, module: &str) {
+        klog!(target: module, "module resumed: {}", module);
+    }
+}
+
+/// State restored when an `apply` step fails.
+struct ApplySnapshot {
+    modules: Vec<ModuleEntry>,
+    catalog: Vec<CatalogEntry>,
+    fs: FileSystem,
+    users: UserManager,
+    shells: ShellRegistry,
+    settings: SystemSettings,
+    board: PuzzleBoard,
+    sandbox: SandboxTable,
+    net: NetManager,
+    net_profiles: NetProfileManager,
+    waiting: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 struct MountEntry {
     source: String,
@@ -44,14 +266,38 @@ struct MountEntry {
     fstype: String,
 }
 
-pub fn run(initramfs: Option<&[u8]>) -> ! {
-    let mut state = ShellState::new(initramfs);
+pub fn run(initramfs: Option<&[u8]>, memory_bytes: u64) -> ! {
+    let mut state = ShellState::new(initramfs, memory_bytes);
     kprintln!("Ruzzle OS shell ready. Type 'help' for commands.");
     loop {
-        kprint!("ruzzle> ");
-        let line = read_line();
+        #[cfg(feature = "x86_64")]
+        state.poll_dhcpd();
+        state.check_session_shell();
+        state.show_notifications();
+        kprint!("{} ", state.prompt());
+        let line = read_line_with(Some(&mut state.clipboard));
+        if line.trim() == BATCH_BEGIN {
+            state.run_batch_from_console();
+            continue;
+        }
+        if line.trim() == ARCHIVE_EXPORT {
+            state.export_archive_to_console();
+            continue;
+        }
+        if line.trim() == FRAMED_BEGIN {
+            state.run_framed_from_console();
+            continue;
+        }
+        let line = expand_last_status(&line, state.last_status);
         let command = parse_command(&line);
-        state.handle(command, &line);
+        let timed = state.timing && !matches!(command, Command::Time(_) | Command::Unknown(_));
+        let start = clock::now_ns();
+        state.dispatch(command);
+        let elapsed = clock::now_ns().saturating_sub(start);
+        if timed {
+            kprintln!("took {}", format_duration_ns(elapsed));
+        }
+        state.record_latency(&line, elapsed);
     }
 }
 
@@ -62,26 +308,69 @@ struct ShellState {
     fs: FileSystem,
     file_manager: FileManager,
     net: NetManager,
+    net_status: NetworkStatus,
+    net_profiles: NetProfileManager,
+    dns: DnsResolver,
+    firewall: FirewallTable,
+    /// DHCP server and the interface it answers on, while `dhcpd start` is in effect.
+    dhcpd: Option<(String, DhcpServer)>,
     mounts: Vec<MountEntry>,
     users: UserManager,
     session: SessionManager,
+    shells: ShellRegistry,
     settings: SystemSettings,
+    /// Tracks setting changes that other subsystems follow, such as the hostname.
+    settings_watch: SettingsWatcher,
     board: PuzzleBoard,
+    sandbox: SandboxTable,
+    clipboard: Clipboard,
+    metrics: MetricsRegistry,
+    latency_enabled: bool,
+    memory_bytes: u64,
+    profile: BootProfile,
+    tensor: Option<Tensor>,
+    containers: ContainerManager,
+    server: ServerStack,
+    last_status: ExitCode,
+    style: Style,
+    confirm: fn(&str) -> bool,
+    /// False while commands come from a client rather than a person at the
+    /// console, so nothing can answer a prompt or press a key to stop.
+    interactive: bool,
+    bell: bool,
+    timing: bool,
+    waiting: Vec<String>,
     login_tip_shown: bool,
+    /// Microseconds since boot when the boot sequence finished.
+    boot_us: u64,
 }
 
 impl ShellState {
-    fn new(initramfs: Option<&[u8]>) -> Self {
+    fn new(initramfs: Option<&[u8]>, memory_bytes: u64) -> Self {
         let initramfs_data = initramfs.map(|data| data.to_vec());
+        let profile = BootProfile::select(memory_bytes);
         let (modules, catalog) = build_modules(initramfs);
-        let fs = FileSystem::new();
+        let mut fs = FileSystem::new();
+        fs.set_quota(profile.fs_quota_bytes());
+        seed_rootfs(&mut fs, initramfs);
+        mount_tmpfs_dirs(&mut fs, profile.tmpfs_bytes());
         let file_manager = FileManager::new();
-        let net = NetManager::new();
+        let mut net = NetManager::new();
+        net.init_defaults();
+        #[cfg(feature = "x86_64")]
+        if let Some(nic) = crate::nic::VirtioNet::probe() {
+            if let Err(err) = net.attach_device(crate::nic::NIC_IFACE, &nic) {
+                klog!("virtio-net: attach failed: {:?}", err);
+            }
+        }
+        let net_status = NetworkStatus::watch(&mut net);
         let mounts = default_mounts();
         let users = UserManager::new();
         let session = SessionManager::new();
         let settings = SystemSettings::new_defaults();
         let board = build_puzzle_board(&modules);
+        let sandbox = build_sandbox(&modules, profile.log_ring_len());
+        let shells = build_shell_registry(&modules);
         let mut state = Self {
             modules,
             catalog,
@@ -89,46 +378,214 @@ impl ShellState {
             fs,
             file_manager,
             net,
+            net_status,
+            net_profiles: NetProfileManager::new(),
+            dns: DnsResolver::new(),
+            firewall: FirewallTable::new(),
+            dhcpd: None,
             mounts,
             users,
             session,
+            shells,
             settings,
+            settings_watch: SettingsWatcher::default(),
             board,
+            sandbox,
+            clipboard: Clipboard::new(),
+            metrics: MetricsRegistry::new(),
+            latency_enabled: false,
+            memory_bytes,
+            profile,
+            tensor: None,
+            containers: ContainerManager::new(),
+            server: ServerStack::new(ServerConfig {
+                host: "0.0.0.0".to_string(),
+                port: 80,
+                tls_enabled: false,
+                metrics_enabled: true,
+            }),
+            last_status: ExitCode::SUCCESS,
+            style: Style::Plain,
+            confirm: confirm_on_console,
+            interactive: true,
+            bell: true,
+            timing: false,
+            waiting: Vec::new(),
             login_tip_shown: false,
+            boot_us: 0,
         };
+        if profile == BootProfile::LowMemory {
+            kprintln!(
+                "boot profile: {} ({} MiB usable); disabled: {}",
+                profile.name(),
+                memory_bytes / (1024 * 1024),
+                profile.disabled_modules().join(", ")
+            );
+        }
         state.ensure_setup();
+        state.restore_settings();
+        state.sync_settings();
+        state.restore_board();
+        state.restore_network();
         state.ensure_base_profile();
+        state.autoselect_network();
+        for slot in LOCKED_SLOTS {
+            let _ = state.board.set_locked(slot, true);
+        }
+        state.boot_us = clock::now_ns() / 1_000;
         state
     }
 
-    fn handle(&mut self, command: Command, raw: &str) {
+    /// Runs a command, collecting its output and exit status.
+    fn dispatch(&mut self, command: Command) -> CommandResult {
+        COMMAND_STATUS.store(ExitCode::SUCCESS.0, Ordering::Relaxed);
+        COMMAND_ERROR.store(0, Ordering::Relaxed);
+        console::begin_capture();
+        self.fs.set_time(clock::now_ns() / 1_000_000);
+        self.board.set_time(clock::now_ns() / 1_000_000);
+        self.handle(command);
+        self.last_status = ExitCode(COMMAND_STATUS.load(Ordering::Relaxed));
+        self.start_ready_modules();
+        self.sync_settings();
+        self.save_board();
+        self.save_network();
+        let output = console::end_capture();
+        CommandResult::new(self.last_status, output)
+    }
+
+    /// Runs a command for a structured client, returning typed rows where
+    /// the command has them and a coded error when it fails.
+    fn respond(&mut self, command: Command) -> ShellResponse {
+        let lsmod = command == Command::Lsmod;
+        let slots = command == Command::Slots { verbose: false };
+        let result = self.dispatch(command);
+        if !result.status.is_success() {
+            let code = match ShellErrorCode::from_u16(COMMAND_ERROR.load(Ordering::Relaxed)) {
+                Ok(code) => code,
+                Err(_) if result.status == ExitCode::UNKNOWN_COMMAND => {
+                    ShellErrorCode::UnknownCommand
+                }
+                Err(_) => ShellErrorCode::Failed,
+            };
+            return ShellResponse::Error {
+                code,
+                message: result.output,
+            };
+        }
+        if lsmod {
+            ShellResponse::Modules(
+                self.module_rows()
+                    .into_iter()
+                    .map(|row| ModuleInfo {
+                        name: row.name,
+                        state: row.state,
+                        provides: row.provides,
+                    })
+                    .collect(),
+            )
+        } else if slots {
+            ShellResponse::Slots(
+                self.board
+                    .list()
+                    .into_iter()
+                    .map(|slot| SlotInfo {
+                        name: slot.name,
+                        required: slot.required,
+                        locked: slot.locked,
+                        providers: slot.providers,
+                    })
+                    .collect(),
+            )
+        } else {
+            result.to_response()
+        }
+    }
+
+    /// Reads commands until `@end`, then runs them with delimited results.
+    fn run_batch_from_console(&mut self) {
+        let mut collector = BatchCollector::new();
+        let commands = loop {
+            let line = read_line_with(None);
+            if let Some(commands) = collector.push_line(&line) {
+                break commands;
+            }
+        };
+        // Nobody is at the console to answer y/N, so prompts decline.
+        let confirm = core::mem::replace(&mut self.confirm, |_| false);
+        let interactive = core::mem::replace(&mut self.interactive, false);
+        run_batch(
+            &commands,
+            |line| {
+                let line = expand_last_status(line, self.last_status);
+                self.dispatch(parse_command(&line)).status
+            },
+            |delimiter| kprintln!("{}", delimiter),
+        );
+        self.confirm = confirm;
+        self.interactive = interactive;
+    }
+
+    /// Dumps the filesystem archive as hex lines (admin session required).
+    fn export_archive_to_console(&self) {
+        if self.require_login().is_none() {
+            return;
+        }
+        match self.fs.export_archive() {
+            Ok(bytes) => {
+                for line in format_archive_export(&bytes) {
+                    kprintln!("{}", line);
+                }
+            }
+            Err(err) => kfail!("export error: {:?}", err),
+        }
+    }
+
+    fn handle(&mut self, command: Command) {
         if command_requires_login(&command) && self.require_login().is_none() {
             return;
         }
+        if !self.interactive && command_needs_console(&command) {
+            kfail!("command needs an interactive console");
+            return;
+        }
+        if let Some(prompt) = confirmation_prompt(&command, |slot| self.board.is_required(slot)) {
+            if !(self.confirm)(&prompt) {
+                kfail!("aborted");
+                return;
+            }
+        }
         match command {
             Command::Ps { tree } => self.print_running(tree),
             Command::Lsmod => self.print_modules(),
             Command::Start(name) => self.start_module(&name),
             Command::Stop(name) => self.stop_module(&name),
-            Command::LogTail => {
-                kprintln!("{}", format_log_tail_empty());
-            }
+            Command::Status(name) => self.print_module_status(&name),
+            Command::BootReport => self.print_boot_report(),
+            Command::Resolve(name) => self.run_resolve(&name),
+            Command::Ping(args) => self.run_ping(&args),
+            Command::Fw(args) => self.run_fw(args.as_deref()),
+            Command::Dhcpd(args) => self.run_dhcpd(args.as_deref()),
+            Command::LogTail { follow, lines } => self.log_tail(follow, lines),
             Command::Help(topic) => self.print_help(topic.as_deref()),
             Command::Catalog {
                 slot,
                 verified_only,
             } => self.print_catalog(slot.as_deref(), verified_only),
             Command::PieceCheck(name) => self.piece_check(&name),
-            Command::Ip(args) => self.run_ip(args.as_deref()),
-            Command::Route(args) => self.run_route(args.as_deref()),
+            Command::PieceCheckAll => self.piece_check_all(),
+            Command::PieceRuntime(name) => self.piece_runtime(&name),
+            Command::Ip(ip) => self.run_ip(ip),
+            Command::Route(route) => self.run_route(route),
             Command::Mount(args) => self.run_mount(args.as_deref()),
-            Command::Df(path) => self.print_df(path.as_deref()),
-            Command::Du(path) => self.print_du(&path),
+            Command::Df { path, human } => self.print_df(path.as_deref(), human),
+            Command::Du { path, human } => self.print_du(&path, human),
             Command::MarketScan => self.market_scan(),
             Command::Install(name) => self.install_module(&name),
-            Command::Remove(name) => self.remove_module(&name),
+            Command::Remove { module, .. } => self.remove_module(&module),
             Command::Setup => self.run_setup_wizard(),
+            Command::Apply { path, dry_run } => self.apply_manifest(&path, dry_run),
             Command::Login(user) => self.login(&user),
+            Command::Chsh { user, shell } => self.change_shell(user.as_deref(), shell.as_deref()),
             Command::Logout => self.logout(),
             Command::Whoami => self.whoami(),
             Command::Users => self.list_users(),
@@ -139,26 +596,60 @@ impl ShellState {
             Command::Mkdir(path) => self.make_dir(&path),
             Command::Touch(path) => self.touch_file(&path),
             Command::Cat(path) => self.cat_file(&path),
+            Command::Wc(path) => self.wc_file(&path),
+            Command::Stat(path) => self.stat_path(&path),
+            Command::Checksum(path) => self.checksum_paths(&path),
+            Command::Find { path, name } => self.find_paths(&path, name.as_deref()),
+            Command::Ln { target, link } => self.link_path(&target, &link),
+            Command::Chmod { mode, path } => self.change_mode(&mode, &path),
+            Command::Chown { user, path } => self.change_owner(&user, &path),
+            Command::Gpu(args) => self.run_gpu(args.as_deref()),
+            Command::Container(args) => self.run_container(args.as_deref()),
+            Command::Http(args) => self.run_http(args.as_deref()),
+            Command::WebConsole(args) => self.run_webconsole(args.as_deref()),
+            Command::LogLevel(args) => self.run_log_level(args.as_deref()),
+            Command::Notifications { clear } => self.print_notifications(clear),
+            Command::Watch {
+                interval_secs,
+                command,
+            } => self.watch_command(interval_secs, &command),
             Command::Edit(path) => self.edit_file(&path),
             Command::Cp { src, dst, recursive } => self.copy_path(&src, &dst, recursive),
             Command::Mv { src, dst } => self.move_path(&src, &dst),
             Command::MkdirP(path) => self.make_dir_p(&path),
             Command::Write { path, contents } => self.write_file(&path, &contents),
             Command::Rm(path) => self.remove_path(&path),
-            Command::RmRecursive(path) => self.remove_path_recursive(&path),
-            Command::Slots => self.print_slots(),
+            Command::RmRecursive { path, .. } => self.remove_path_recursive(&path),
+            Command::Slots { verbose } => self.print_slots(verbose),
             Command::Plug {
                 slot,
                 module,
                 dry_run,
                 swap,
-            } => self.plug_slot(&slot, &module, dry_run, swap),
-            Command::Unplug(slot) => self.unplug_slot(&slot),
-            Command::Graph => self.print_graph(),
+                override_lock,
+            } => self.plug_slot(&slot, &module, dry_run, swap, override_lock),
+            Command::Unplug {
+                slot,
+                override_lock,
+                ..
+            } => self.with_lock_override(&slot, override_lock, |state| state.unplug_slot(&slot)),
+            Command::Autoplug { dry_run } => self.autoplug(dry_run),
+            Command::Graph { dot } => self.print_graph(dot),
             Command::Sysinfo => self.print_sysinfo(),
-            Command::Unknown(_) => {
-                if !raw.trim().is_empty() {
-                    kprintln!("{}", format_unknown_command(raw.trim()));
+            Command::Clip(text) => self.run_clip(text.as_deref()),
+            Command::Time(line) => self.time_command(&line),
+            Command::Metrics(args) => self.run_metrics(args.as_deref()),
+            Command::Echo(text) => {
+                kprintln!("{}", expand_echo(&text, |name| self.shell_var(name)))
+            }
+            Command::Shutdown => self.shutdown(),
+            Command::Describe => kprint!("{}", format_description(&describe_shell())),
+            Command::Set(args) => self.run_set(args.as_deref()),
+            Command::Sequence(steps) => self.run_chain(&steps),
+            Command::Unknown(text) => {
+                if !text.is_empty() {
+                    kprintln!("{}", format_unknown_command(&text));
+                    COMMAND_STATUS.store(ExitCode::UNKNOWN_COMMAND.0, Ordering::Relaxed);
                     self.print_help(None);
                 }
             }
@@ -209,25 +700,88 @@ impl ShellState {
         }
     }
 
+    fn module_state(&self, module: &ModuleEntry) -> &'static str {
+        if module.running {
+            "running"
+        } else if self.waiting.contains(&module.name) {
+            "waiting"
+        } else {
+            "stopped"
+        }
+    }
+
+    fn print_module_status(&self, name: &str) {
+        let Some(module) = self.modules.iter().find(|module| module.name == name) else {
+            kfail!("module not found: {}", name);
+            return;
+        };
+        let row = ModuleStatusRow {
+            name: module.name.clone(),
+            state: self.module_state(module).to_string(),
+            failures: module.failures,
+            last_error: module
+                .last_failure
+                .as_ref()
+                .map(|(_, reason)| reason.clone()),
+            last_failure: module.last_failure.as_ref().map(|(tick, _)| *tick),
+        };
+        kprint!("{}", format_module_status(&row));
+    }
+
+    /// Prints modules started during boot, slowest first, and the dependency
+    /// chain that ended last.
+    fn print_boot_report(&self) {
+        let timing = |module: &ModuleEntry| {
+            module
+                .start_timing
+                .filter(|(started_us, _)| *started_us <= self.boot_us)
+                .map(|(started_us, duration_us)| BootTimingRow {
+                    name: module.name.clone(),
+                    started_us,
+                    duration_us,
+                })
+        };
+        let mut rows = self.modules.iter().filter_map(timing).collect::<Vec<_>>();
+        rows.sort_by(|a, b| b.duration_us.cmp(&a.duration_us).then(a.name.cmp(&b.name)));
+
+        let ready = |row: &BootTimingRow| row.started_us + row.duration_us;
+        let mut critical_path = Vec::new();
+        let mut next = rows.iter().max_by_key(|row| ready(row)).cloned();
+        while let Some(row) = next {
+            next = self
+                .modules
+                .iter()
+                .find(|module| module.name == row.name)
+                .and_then(|module| module.manifest.as_ref())
+                .into_iter()
+                .flat_map(|manifest| manifest.depends.iter())
+                .filter_map(|dep| self.modules.iter().find(|module| module.name == *dep))
+                .filter_map(timing)
+                .filter(|dep| ready(dep) <= row.started_us)
+                .max_by_key(|dep| ready(dep));
+            critical_path.push(row);
+        }
+        critical_path.reverse();
+        kprint!("{}", format_boot_report(self.boot_us, &rows, &critical_path));
+    }
+
     fn print_modules(&self) {
-        let rows = self
-            .modules
+        kprintln!("{}", format_modules(&self.module_rows(), self.style));
+    }
+
+    fn module_rows(&self) -> Vec<ModuleRow> {
+        self.modules
             .iter()
             .map(|module| ModuleRow {
                 name: module.name.clone(),
-                state: if module.running {
-                    "running".to_string()
-                } else {
-                    "stopped".to_string()
-                },
+                state: self.module_state(module).to_string(),
                 provides: module
                     .manifest
                     .as_ref()
                     .map(|manifest| manifest.provides.clone())
                     .unwrap_or_default(),
             })
-            .collect::<Vec<ModuleRow>>();
-        kprintln!("{}", format_modules(&rows));
+            .collect()
     }
 
     fn print_catalog(&self, slot: Option<&str>, verified_only: bool) {
@@ -285,12 +839,12 @@ impl ShellState {
                 entry.verified,
             )
         } else {
-            kprintln!("piece not found: {}", name);
+            kfail!("piece not found: {}", name);
             return;
         };
 
         let Some(manifest) = manifest else {
-            kprintln!("piece has no manifest: {}", name);
+            kfail!("piece has no manifest: {}", name);
             return;
         };
 
@@ -299,7 +853,6 @@ impl ShellState {
         kprintln!("  location: {} ({})", location, run_state);
         kprintln!("  signature: {}", signature);
 
-        let board_slots = self.board.list();
         let mut warnings = Vec::new();
 
         kprintln!("  dependencies:");
@@ -339,10 +892,12 @@ impl ShellState {
             kprintln!("    <none>");
         } else {
             for slot in &manifest.slots {
-                let slot_entry = board_slots.iter().find(|entry| entry.name == *slot);
+                let slot_entry = self.board.slot(slot);
                 let status = match slot_entry {
-                    Some(entry) => match entry.provider.as_deref() {
-                        Some(provider) if provider == name => "active".to_string(),
+                    Some(entry) if entry.providers.iter().any(|provider| provider == name) => {
+                        "active".to_string()
+                    }
+                    Some(entry) => match entry.provider() {
                         Some(provider) => {
                             warnings.push(format!(
                                 "slot occupied: {} -> {}",
@@ -376,12 +931,14 @@ impl ShellState {
             name: name.to_string(),
             state: run_state.to_string(),
             depends: manifest.depends.clone(),
+            wants: manifest.wants.clone(),
+            required_by: self.dependents_of(name),
         });
         let mut deps_sorted = manifest.depends.clone();
         deps_sorted.sort();
         deps_sorted.dedup();
         for dep in deps_sorted {
-            let (state, depends) = if let Some(dep_module) =
+            let (state, dep_manifest) = if let Some(dep_module) =
                 self.modules.iter().find(|module| module.name == dep)
             {
                 (
@@ -390,11 +947,7 @@ impl ShellState {
                     } else {
                         "installed".to_string()
                     },
-                    dep_module
-                        .manifest
-                        .as_ref()
-                        .map(|manifest| manifest.depends.clone())
-                        .unwrap_or_default(),
+                    dep_module.manifest.as_ref(),
                 )
             } else if let Some(dep_entry) = self.catalog.iter().find(|entry| entry.name == dep) {
                 (
@@ -403,44 +956,214 @@ impl ShellState {
                     } else {
                         "unsigned".to_string()
                     },
-                    dep_entry.manifest.depends.clone(),
+                    Some(&dep_entry.manifest),
                 )
             } else {
-                ("missing".to_string(), Vec::new())
+                ("missing".to_string(), None)
             };
             rows.push(GraphRow {
-                name: dep,
                 state,
-                depends,
+                depends: dep_manifest
+                    .map(|manifest| manifest.depends.clone())
+                    .unwrap_or_default(),
+                wants: dep_manifest
+                    .map(|manifest| manifest.wants.clone())
+                    .unwrap_or_default(),
+                required_by: self.dependents_of(&dep),
+                name: dep,
             });
         }
 
         kprintln!("{}", format_graph(&rows));
     }
 
+    fn piece_check_all(&self) {
+        let catalog: Vec<(String, Vec<String>)> = self
+            .catalog
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.manifest.slots.clone()))
+            .collect();
+        let modules: Vec<ModuleStatus> = self
+            .modules
+            .iter()
+            .map(|module| ModuleStatus {
+                name: module.name.clone(),
+                slots: module
+                    .manifest
+                    .as_ref()
+                    .map(|manifest| manifest.slots.clone())
+                    .unwrap_or_default(),
+                running: module.running,
+            })
+            .collect();
+        let report = self.board.validate(&catalog, &modules);
+        kprintln!("board check: {} slots", report.slots_checked);
+        if report.is_clean() {
+            kprintln!("  ok");
+            return;
+        }
+        for issue in &report.issues {
+            kprintln!("  - {}", issue.format());
+        }
+        kfail!("board check failed: {} issues", report.issues.len());
+    }
+
+    fn piece_runtime(&self, name: &str) {
+        let Some(module) = self.modules.iter().find(|module| module.name == name) else {
+            kfail!("piece not installed: {}", name);
+            return;
+        };
+        kprintln!("piece runtime: {}", name);
+        kprintln!(
+            "  state: {}",
+            if module.running { "running" } else { "stopped" }
+        );
+        match self.sandbox.profile(name) {
+            Some(filter) => {
+                kprintln!(
+                    "  sandbox: enforced (on violation: {})",
+                    filter.action().name()
+                );
+                kprintln!("  allowed: {}", join_syscalls(&filter.allowed()));
+                kprintln!("  filtered: {}", join_syscalls(&filter.denied()));
+            }
+            None => kprintln!("  sandbox: <none>"),
+        }
+        let violations = self.sandbox.violations_for(name);
+        kprintln!("  violations:");
+        if violations.is_empty() {
+            kprintln!("    <none>");
+        } else {
+            for violation in violations {
+                kprintln!("    {} [{:?}]", violation.syscall.name(), violation.action);
+            }
+        }
+    }
+
     fn start_module(&mut self, name: &str) {
+        let begin = clock::now_ns();
+        if !self.profile.allows_module(name) {
+            kprintln!("module disabled by {} profile: {}", self.profile.name(), name);
+            return;
+        }
         let Some(module) = self.modules.iter_mut().find(|m| m.name == name) else {
-            kprintln!("module not found: {}", name);
+            kfail!("module not found: {}", name);
             return;
         };
         if module.running {
             kprintln!("module already running: {}", name);
             return;
         }
-        module.running = true;
         if let Some(manifest) = &module.manifest {
-            self.board.mark_running(&module.name, &manifest.slots);
+            let missing = manifest.missing_paths(|path| self.fs.metadata(path).is_ok());
+            if !missing.is_empty() {
+                kprintln!("module waiting for paths: {}: {}", name, missing.join(", "));
+                klog!(target: name, "module waiting for paths: {}", name);
+                if !self.waiting.iter().any(|waiting| waiting == name) {
+                    self.waiting.push(name.to_string());
+                }
+                return;
+            }
+        }
+        self.waiting.retain(|waiting| waiting != name);
+        if let Some(manifest) = &module.manifest {
+            match SyscallFilter::from_manifest(manifest) {
+                Ok(filter) => self.sandbox.install(&module.name, filter),
+                Err(_) => {
+                    module.record_failure("invalid capabilities");
+                    kprintln!("module start blocked: invalid capabilities: {}", name);
+                    klog!(target: name, "module failed: {}: invalid capabilities", name);
+                    return;
+                }
+            }
+        }
+        module.mark_started(begin);
+        if let Some(manifest) = &module.manifest {
+            for tie in self.board.mark_running(&module.name, &manifest.slots) {
+                kprintln!(
+                    "slot tie: {} between {}; keeping {}",
+                    tie.slot,
+                    tie.modules.join(", "),
+                    tie.modules[0]
+                );
+            }
+        }
+        klog!(target: name, "module started: {}", name);
+    }
+
+    /// Starts waiting modules whose required paths now exist.
+    ///
+    /// Runs after every command, standing in for a filesystem watch.
+    fn start_ready_modules(&mut self) {
+        let ready: Vec<String> = self
+            .waiting
+            .iter()
+            .filter(|name| {
+                self.modules
+                    .iter()
+                    .find(|module| &module.name == *name)
+                    .and_then(|module| module.manifest.as_ref())
+                    .map(|manifest| {
+                        manifest
+                            .missing_paths(|path| self.fs.metadata(path).is_ok())
+                            .is_empty()
+                    })
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        for name in ready {
+            self.waiting.retain(|waiting| waiting != &name);
+            self.start_module(&name);
+            if self.module_running(&name) {
+                console::notify(
+                    "init",
+                    &format!("module started after waiting for paths: {}", name),
+                );
+            }
+        }
+    }
+
+    /// Prints notifications posted since the last prompt, ringing the bell first.
+    fn show_notifications(&self) {
+        let pending = console::take_notifications();
+        if pending.is_empty() {
+            return;
+        }
+        if self.bell {
+            kprint!("{}", BELL);
+        }
+        for notification in pending {
+            kprintln!("{}", notification.format());
+        }
+    }
+
+    fn print_notifications(&self, clear: bool) {
+        console::take_notifications();
+        let history = console::notification_history();
+        if history.is_empty() {
+            kprintln!("no notifications");
+        }
+        for notification in history {
+            kprintln!("{}", notification.format());
+        }
+        if clear {
+            console::clear_notifications();
         }
-        kprintln!("module started: {}", name);
     }
 
     fn stop_module(&mut self, name: &str) {
         if name == "init" {
-            kprintln!("init cannot be stopped");
+            kfail!("init cannot be stopped");
+            return;
+        }
+        if self.waiting.iter().any(|waiting| waiting == name) {
+            self.waiting.retain(|waiting| waiting != name);
+            kprintln!("module start cancelled: {}", name);
             return;
         }
         let Some(module) = self.modules.iter_mut().find(|m| m.name == name) else {
-            kprintln!("module not found: {}", name);
+            kfail!("module not found: {}", name);
             return;
         };
         if !module.running {
@@ -451,30 +1174,84 @@ impl ShellState {
         if let Some(manifest) = &module.manifest {
             detach_module_slots(&mut self.board, &module.name, &manifest.slots);
         }
-        kprintln!("module stopped: {}", name);
+        self.sandbox.remove(name);
+        klog!(target: name, "module stopped: {}", name);
+    }
+
+    /// Dispatches a syscall a running module issues through the kernel.
+    ///
+    /// The module's sandbox profile is enforced first; a terminating
+    /// violation stops the module before the error is returned.
+    fn module_syscall(&mut self, name: &str, syscall: Syscall) -> Result<SyscallResult, Errno> {
+        let caps = match self
+            .modules
+            .iter()
+            .find(|module| module.name == name && module.running)
+        {
+            Some(module) => match &module.manifest {
+                Some(manifest) => manifest_caps(manifest)?,
+                None => return Err(Errno::NoPerm),
+            },
+            None => return Err(Errno::NotFound),
+        };
+        let result = dispatch_sandboxed(name, syscall, caps, None, &mut self.sandbox);
+        if result.is_err() {
+            klog!(target: name, "sandbox: {} rejected: {}", name, syscall.name());
+        }
+        self.reap_sandbox();
+        result
+    }
+
+    /// Stops modules whose sandbox violation calls for termination.
+    fn reap_sandbox(&mut self) {
+        let modules = &mut self.modules;
+        let board = &mut self.board;
+        let stopped = self.sandbox.reap_terminated(|name| {
+            let Some(module) = modules.iter_mut().find(|module| module.name == name) else {
+                return false;
+            };
+            if name == "init" || !module.running {
+                return false;
+            }
+            module.running = false;
+            if let Some(manifest) = &module.manifest {
+                detach_module_slots(board, &module.name, &manifest.slots);
+            }
+            true
+        });
+        for name in stopped {
+            klog!(target: &name, "module terminated: {}: sandbox violation", name);
+            console::notify("init", &format!("module terminated: {}", name));
+        }
     }
 
     fn install_module(&mut self, name: &str) {
+        if !self.profile.allows_module(name) {
+            kprintln!("module disabled by {} profile: {}", self.profile.name(), name);
+            return;
+        }
         if self.modules.iter().any(|module| module.name == name) {
             kprintln!("module already installed: {}", name);
             return;
         }
         let Some(index) = self.catalog.iter().position(|module| module.name == name) else {
-            kprintln!("module not found in catalog: {}", name);
+            kfail!("module not found in catalog: {}", name);
             return;
         };
         if !self.catalog[index].verified {
-            kprintln!("module not verified: {}", name);
+            kfail!("module not verified: {}", name);
             return;
         }
         let entry = self.catalog.remove(index);
         let manifest = entry.manifest.clone();
-        self.modules.push(ModuleEntry {
-            name: entry.name.clone(),
-            manifest: Some(entry.manifest),
-            running: false,
-            verified: entry.verified,
-        });
+        if let Some(path) = self.shells.register_module(&entry.name, &manifest.slots) {
+            kprintln!("shell registered: {}", path);
+        }
+        self.modules.push(ModuleEntry::new(
+            entry.name.clone(),
+            Some(entry.manifest),
+            entry.verified,
+        ));
         kprintln!("module installed: {}", name);
         self.print_manifest_summary(&manifest);
     }
@@ -485,15 +1262,16 @@ impl ShellState {
         kprintln!("  slots: {}", join_list(&manifest.slots));
         kprintln!("  requires: {}", join_list(&manifest.requires_caps));
         kprintln!("  depends: {}", join_list(&manifest.depends));
+        kprintln!("  wants: {}", join_list(&manifest.wants));
     }
 
     fn remove_module(&mut self, name: &str) {
         if matches!(name, "init" | "console-service" | "tui-shell") {
-            kprintln!("module cannot be removed: {}", name);
+            kfail!("module cannot be removed: {}", name);
             return;
         }
         let Some(index) = self.modules.iter().position(|module| module.name == name) else {
-            kprintln!("module not installed: {}", name);
+            kfail!("module not installed: {}", name);
             return;
         };
         if self.modules[index].running {
@@ -501,6 +1279,8 @@ impl ShellState {
             return;
         }
         let entry = self.modules.remove(index);
+        self.waiting.retain(|waiting| waiting != name);
+        self.shells.unregister_module(&entry.name);
         if let Some(manifest) = &entry.manifest {
             detach_module_slots(&mut self.board, &entry.name, &manifest.slots);
         }
@@ -522,37 +1302,265 @@ impl ShellState {
         self.run_setup_wizard();
     }
 
-    fn ensure_base_profile(&mut self) {
-        let mut base_modules = vec![
-            "fs-service",
-            "user-service",
-            "session-service",
-            "settings-service",
-            "sysinfo-service",
-            "file-manager",
-            "net-service",
-            "net-manager",
-            "input-service",
-            "device-manager",
-            "setup-wizard",
-        ];
-
-        if let Some(editor) = self.preferred_editor() {
-            base_modules.push(editor);
-        }
-
-        let mut changed = false;
-        for name in base_modules {
-            if !self.modules.iter().any(|module| module.name == name) {
-                if self.catalog.iter().any(|entry| entry.name == name) {
-                    self.install_module(name);
-                    changed = true;
-                }
+    /// Re-plugs the board saved in `BOARD_CONFIG` and starts its providers.
+    fn restore_board(&mut self) {
+        let Ok(bytes) = self.fs.read_file(BOARD_CONFIG) else {
+            return;
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        let entries = match parse_board_config(&text) {
+            Ok(entries) => entries,
+            Err(err) => {
+                kprintln!("board: ignoring {}: {:?}", BOARD_CONFIG, err);
+                return;
             }
-            if let Some(module) = self.modules.iter().find(|module| module.name == name) {
-                if !module.running {
+        };
+        let entries: Vec<(String, Vec<String>)> = entries
+            .into_iter()
+            .map(|(slot, providers)| {
+                let providers = providers
+                    .into_iter()
+                    .filter(|name| {
+                        self.profile.allows_module(name)
+                            && self.modules.iter().any(|module| module.name == *name)
+                    })
+                    .collect::<Vec<String>>();
+                (slot, providers)
+            })
+            .filter(|(_, providers)| !providers.is_empty())
+            .collect();
+        self.board.restore(&entries);
+        for (_, providers) in &entries {
+            for name in providers {
+                if self
+                    .modules
+                    .iter()
+                    .any(|module| module.name == *name && !module.running)
+                {
                     self.start_module(name);
-                    changed = true;
+                }
+            }
+        }
+    }
+
+    /// Writes the plugged board to `BOARD_CONFIG` when it changed.
+    fn save_board(&mut self) {
+        let text = self.board.to_config_text();
+        if let Err(err) = self.save_system_file("/system/config", BOARD_CONFIG, &text) {
+            kprintln!("board: cannot save {}: {:?}", BOARD_CONFIG, err);
+        }
+    }
+
+    /// Re-applies the interfaces, routes and profiles saved under `NET_CONFIG_DIR`.
+    /// Reapplies saved settings that only live in `/etc/ruzzle.conf`: the
+    /// prompt template and per-module log levels.
+    fn restore_settings(&mut self) {
+        let Ok(bytes) = self.fs.read_file("/etc/ruzzle.conf") else {
+            return;
+        };
+        for line in String::from_utf8_lossy(&bytes).lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            if key == "prompt" {
+                if let Err(err) = self.settings.set_prompt(value) {
+                    kprintln!("settings: ignoring {}: {:?}", key, err);
+                }
+                continue;
+            }
+            let Some(module) = key.strip_prefix("log.") else {
+                continue;
+            };
+            match (self.settings.set_log_level(module, value), LogLevel::parse(value)) {
+                (Ok(()), Some(level)) => console::set_log_level(module, level),
+                (Err(err), _) => kprintln!("settings: ignoring {}: {:?}", key, err),
+                (Ok(()), None) => {}
+            }
+        }
+    }
+
+    fn restore_network(&mut self) {
+        if let Ok(bytes) = self.fs.read_file(NET_INTERFACES_CONFIG) {
+            match parse_net_config(&String::from_utf8_lossy(&bytes)) {
+                Ok(config) => {
+                    for entry in self.net.restore_config(&config) {
+                        kprintln!("net: skipped saved {}", entry);
+                    }
+                }
+                Err(err) => kprintln!("net: ignoring {}: {:?}", NET_INTERFACES_CONFIG, err),
+            }
+        }
+        if let Ok(bytes) = self.fs.read_file(NET_PROFILES_CONFIG) {
+            if let Err(err) = self
+                .net_profiles
+                .load_config(&String::from_utf8_lossy(&bytes))
+            {
+                kprintln!("net: ignoring {}: {:?}", NET_PROFILES_CONFIG, err);
+            }
+        }
+        // The boot profile is read here because the profiles above must be loaded first.
+        let Ok(bytes) = self.fs.read_file("/etc/ruzzle.conf") else {
+            return;
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        let Some(name) = text
+            .lines()
+            .find_map(|line| line.strip_prefix("net.profile="))
+        else {
+            return;
+        };
+        if let Err(err) = self.settings.set_net_profile(name.trim()) {
+            kprintln!("net: ignoring boot profile {}: {:?}", name, err);
+        } else if let Err(err) = self
+            .net_profiles
+            .set_boot_profile(self.settings.net_profile())
+        {
+            kprintln!("net: ignoring boot profile {}: {:?}", name, err);
+        }
+    }
+
+    /// Picks a network profile once `net-service` runs, preferring the boot profile.
+    fn autoselect_network(&mut self) {
+        if !self
+            .modules
+            .iter()
+            .any(|module| module.name == "net-service" && module.running)
+        {
+            return;
+        }
+        match self.net_profiles.autoselect(&mut self.net) {
+            Ok(Some(name)) => {
+                kprintln!("net: profile {} selected", name);
+                if let Some(NetProfile::Dhcp { iface }) = self.net_profiles.profile(&name).cloned()
+                {
+                    self.send_dhcp_discover(&iface);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => kprintln!("net: autoselect failed: {:?}", err),
+        }
+    }
+
+    /// Broadcasts a DHCPDISCOVER on `iface`, announcing the hostname as option 12.
+    ///
+    /// Only the NIC carries frames, so other interfaces send nothing.
+    fn send_dhcp_discover(&mut self, iface: &str) {
+        let discover = match user_dhcp_server::discover(&self.net, iface, clock::ticks() as u32 | 1)
+        {
+            Ok(discover) => discover,
+            Err(err) => {
+                klog!("dhcp: no discover for {}: {:?}", iface, err);
+                return;
+            }
+        };
+        #[cfg(feature = "x86_64")]
+        {
+            if iface != crate::nic::NIC_IFACE {
+                return;
+            }
+            let Some(mut nic) = crate::nic::VirtioNet::probe() else {
+                return;
+            };
+            match self.net.transmit(iface, &mut nic, &discover.encode()) {
+                Ok(()) => klog!("dhcp: discover sent on {}", iface),
+                Err(err) => klog!("dhcp: discover not sent: {:?}", err),
+            }
+        }
+        #[cfg(not(feature = "x86_64"))]
+        let _ = discover;
+    }
+
+    /// Passes setting changes on to the subsystems that follow them.
+    ///
+    /// The first call at boot reports every setting, so the network stack
+    /// and `/etc/hosts` pick up the saved hostname.
+    fn sync_settings(&mut self) {
+        for change in self.settings_watch.poll(&self.settings) {
+            if let ("hostname", Some(hostname)) = (change.key.as_str(), change.new.as_deref()) {
+                self.rename_host(change.old.as_deref(), hostname);
+            }
+        }
+    }
+
+    /// Announces `hostname` on the network and points the `/etc/hosts` self entry at it.
+    fn rename_host(&mut self, previous: Option<&str>, hostname: &str) {
+        if let Err(err) = self.net.set_hostname(hostname) {
+            kprintln!("net: hostname {} not applied: {:?}", hostname, err);
+            return;
+        }
+        let hosts = match self.fs.read_file(HOSTS_FILE) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(_) => "127.0.0.1 localhost\n".to_string(),
+        };
+        let text = set_self_entry(&hosts, previous, hostname);
+        if let Err(err) = self.save_system_file("/etc", HOSTS_FILE, &text) {
+            kprintln!("net: cannot save {}: {:?}", HOSTS_FILE, err);
+        }
+    }
+
+    /// Writes the network configuration under `NET_CONFIG_DIR` when it changed.
+    fn save_network(&mut self) {
+        let files = [
+            (NET_INTERFACES_CONFIG, self.net.to_config_text()),
+            (NET_PROFILES_CONFIG, self.net_profiles.to_config_text()),
+        ];
+        for (path, text) in files {
+            if let Err(err) = self.save_system_file(NET_CONFIG_DIR, path, &text) {
+                kprintln!("net: cannot save {}: {:?}", path, err);
+            }
+        }
+    }
+
+    /// Writes `text` to `path` as the system, skipping the write when unchanged.
+    fn save_system_file(&mut self, dir: &str, path: &str, text: &str) -> Result<(), FsError> {
+        let identity = self.fs.identity().cloned();
+        self.fs.set_identity(None);
+        let unchanged = self
+            .fs
+            .read_file(path)
+            .is_ok_and(|saved| saved == text.as_bytes());
+        let result = if unchanged {
+            Ok(())
+        } else {
+            self.fs
+                .mkdir_p(dir)
+                .and_then(|_| self.fs.write_file(path, text.as_bytes()))
+        };
+        self.fs.set_identity(identity);
+        result
+    }
+
+    fn ensure_base_profile(&mut self) {
+        let mut base_modules = vec![
+            "fs-service",
+            "user-service",
+            "session-service",
+            "settings-service",
+            "sysinfo-service",
+            "file-manager",
+            "net-service",
+            "net-manager",
+            "input-service",
+            "device-manager",
+            "setup-wizard",
+        ];
+
+        if let Some(editor) = self.preferred_editor() {
+            base_modules.push(editor);
+        }
+
+        let mut changed = false;
+        for name in base_modules {
+            if !self.modules.iter().any(|module| module.name == name) {
+                if self.catalog.iter().any(|entry| entry.name == name) {
+                    self.install_module(name);
+                    changed = true;
+                }
+            }
+            if let Some(module) = self.modules.iter().find(|module| module.name == name) {
+                if !module.running {
+                    self.start_module(name);
+                    changed = true;
                 }
             }
         }
@@ -577,6 +1585,161 @@ impl ShellState {
             || self.catalog.iter().any(|entry| entry.name == name)
     }
 
+    fn apply_manifest(&mut self, path: &str, dry_run: bool) {
+        let admin = self
+            .session
+            .active_user()
+            .and_then(|name| self.users.get_user(name))
+            .is_some_and(|user| user.is_admin);
+        if !admin {
+            kfail!("admin privilege required");
+            return;
+        }
+        let manifest = match self.file_manager.cat(&self.fs, path) {
+            Ok(text) => match parse_system_manifest(&text) {
+                Ok(manifest) => manifest,
+                Err(err) => {
+                    kfail!("apply error: {:?}", err);
+                    return;
+                }
+            },
+            Err(err) => {
+                kfail!("apply error: {:?}", err);
+                return;
+            }
+        };
+        let steps = plan_apply(&manifest, &self.system_state());
+        if steps.is_empty() {
+            kprintln!("apply: no changes");
+            return;
+        }
+        kprintln!("apply plan: {} step(s)", steps.len());
+        for step in &steps {
+            kprintln!("  {}", step.describe());
+        }
+        if dry_run {
+            return;
+        }
+        let saved = self.capture_apply_snapshot();
+        for (index, step) in steps.iter().enumerate() {
+            self.run_apply_step(step);
+            if !step.is_satisfied(&self.system_state()) {
+                self.restore_apply_snapshot(saved);
+                kfail!(
+                    "apply failed at step {} ({}); rolled back",
+                    index + 1,
+                    step.describe()
+                );
+                return;
+            }
+        }
+        if steps
+            .iter()
+            .any(|step| matches!(step, ApplyStep::Set { .. }))
+        {
+            if let Err(err) = self
+                .net_profiles
+                .set_boot_profile(self.settings.net_profile())
+            {
+                kprintln!("net: boot profile not set: {:?}", err);
+            }
+            let config = self.settings.to_config_text();
+            if let Err(err) = self.fs.write_file("/etc/ruzzle.conf", config.as_bytes()) {
+                self.restore_apply_snapshot(saved);
+                kfail!("apply failed writing settings: {:?}; rolled back", err);
+                return;
+            }
+            for (module, level) in self.settings.log_levels() {
+                if let Some(level) = LogLevel::parse(level) {
+                    console::set_log_level(module, level);
+                }
+            }
+        }
+        kprintln!("apply complete: {} step(s)", steps.len());
+    }
+
+    fn run_apply_step(&mut self, step: &ApplyStep) {
+        match step {
+            ApplyStep::Set { key, value } => match self.settings.set(key, value) {
+                Ok(()) => kprintln!("{}={}", key, value),
+                Err(err) => kprintln!("settings error: {:?}", err),
+            },
+            ApplyStep::AddUser { name, admin } => match self.users.add_user(name, *admin) {
+                Ok(()) => {
+                    let _ = create_home_dirs(&mut self.fs, &default_home_dir(name), name);
+                    kprintln!("user added: {}", name);
+                }
+                Err(err) => kprintln!("user add failed: {:?}", err),
+            },
+            ApplyStep::Install(name) => self.install_module(name),
+            ApplyStep::Start(name) => self.start_module(name),
+            ApplyStep::Plug { slot, module } => self.plug_slot(slot, module, false, true, false),
+            ApplyStep::Net(profile) => match apply_net_profile(profile, &mut self.net) {
+                Ok(()) => {
+                    let _ = self
+                        .net_profiles
+                        .set_profile(profile.iface(), profile.clone());
+                    kprintln!("net configured: {}", profile.iface());
+                    if let NetProfile::Dhcp { iface } = profile {
+                        self.send_dhcp_discover(iface);
+                    }
+                }
+                Err(err) => kprintln!("net error: {:?}", err),
+            },
+        }
+    }
+
+    fn system_state(&self) -> SystemState<'_> {
+        SystemState {
+            installed: self
+                .modules
+                .iter()
+                .map(|module| module.name.clone())
+                .collect(),
+            running: self
+                .modules
+                .iter()
+                .filter(|module| module.running)
+                .map(|module| module.name.clone())
+                .collect(),
+            board: &self.board,
+            users: &self.users,
+            settings: &self.settings,
+            net: &self.net,
+        }
+    }
+
+    fn capture_apply_snapshot(&self) -> ApplySnapshot {
+        ApplySnapshot {
+            modules: self.modules.clone(),
+            catalog: self.catalog.clone(),
+            fs: self.fs.clone(),
+            users: self.users.clone(),
+            shells: self.shells.clone(),
+            settings: self.settings.clone(),
+            board: self.board.clone(),
+            sandbox: self.sandbox.clone(),
+            net: self.net.clone(),
+            net_profiles: self.net_profiles.clone(),
+            waiting: self.waiting.clone(),
+        }
+    }
+
+    fn restore_apply_snapshot(&mut self, saved: ApplySnapshot) {
+        self.modules = saved.modules;
+        self.catalog = saved.catalog;
+        self.fs = saved.fs;
+        self.users = saved.users;
+        self.shells = saved.shells;
+        self.settings = saved.settings;
+        self.board = saved.board;
+        self.sandbox = saved.sandbox;
+        self.net = saved.net;
+        self.net_status.resync(&mut self.net);
+        self.net_profiles = saved.net_profiles;
+        self.waiting = saved.waiting;
+    }
+
     fn is_setup_complete(&self) -> bool {
         if self.users.list_users().is_empty() {
             return false;
@@ -596,13 +1759,16 @@ impl ShellState {
             Ok(report) => {
                 kprintln!("setup complete. created {} directories.", report.created_dirs.len());
                 let _ = self.session.login(&self.users, &report.user);
+                self.sync_fs_identity();
                 self.file_manager = FileManager::new();
                 let home = default_home_dir(&report.user);
                 let _ = self.file_manager.cd(&self.fs, &home);
-                self.show_login_tips(&report.user);
+                if self.dispatch_session() {
+                    self.show_login_tips(&report.user);
+                }
             }
             Err(err) => {
-                kprintln!("setup failed: {}", format_setup_error(&err));
+                kfail!("setup failed: {}", format_setup_error(&err));
             }
         }
     }
@@ -610,17 +1776,101 @@ impl ShellState {
     fn login(&mut self, user: &str) {
         match self.session.login(&self.users, user) {
             Ok(()) => {
+                self.sync_fs_identity();
                 let home = default_home_dir(user);
                 let _ = self.file_manager.cd(&self.fs, &home);
-                kprintln!("logged in as {}", user);
-                self.show_login_tips(user);
+                if self.dispatch_session() {
+                    kprintln!("logged in as {}", user);
+                    self.show_login_tips(user);
+                }
             }
             Err(_) => {
-                kprintln!("login failed for {}", user);
+                kfail!("login failed for {}", user);
             }
         }
     }
 
+    fn sync_fs_identity(&mut self) {
+        let identity = self
+            .session
+            .active_user()
+            .and_then(|name| self.users.get_user(name))
+            .map(|user| Identity::new(&user.name, user.is_admin));
+        self.fs.set_identity(identity);
+    }
+
+    /// Hands the new session to its shell piece, ending it if the piece is absent.
+    fn dispatch_session(&mut self) -> bool {
+        let module = match self.session.dispatch(&self.users, &self.shells) {
+            Ok(module) => module.to_string(),
+            Err(SessionError::UnknownShell) => {
+                self.end_session();
+                kfail!("login failed: login shell not provided by any piece");
+                return false;
+            }
+            Err(_) => return false,
+        };
+        if !self.module_running(&module) {
+            self.start_module(&module);
+        }
+        if !self.module_running(&module) {
+            self.end_session();
+            kfail!("login failed: shell {} is not running", module);
+            return false;
+        }
+        kprintln!("session shell: {}", module);
+        true
+    }
+
+    /// Ends the session whose shell piece stopped since the last prompt.
+    fn check_session_shell(&mut self) {
+        let Some(module) = self.session.active_shell().map(|module| module.to_string()) else {
+            return;
+        };
+        if !self.module_running(&module) {
+            self.end_session();
+            kprintln!("session shell {} stopped; logged out", module);
+        }
+    }
+
+    fn module_running(&self, module: &str) -> bool {
+        self.modules
+            .iter()
+            .any(|entry| entry.name == module && entry.running)
+    }
+
+    fn end_session(&mut self) {
+        let _ = self.session.logout();
+        self.sync_fs_identity();
+    }
+
+    fn change_shell(&mut self, user: Option<&str>, shell: Option<&str>) {
+        let Some(shell) = shell else {
+            kprintln!("shells:");
+            for (path, module) in self.shells.list() {
+                kprintln!("  {} ({})", path, module);
+            }
+            return;
+        };
+        let Some(active) = self.session.active_user().map(|name| name.to_string()) else {
+            kfail!("login required");
+            return;
+        };
+        let target = user.unwrap_or(&active).to_string();
+        if target != active && !self.users.get_user(&active).is_some_and(|user| user.is_admin) {
+            kprintln!("admin privilege required");
+            return;
+        }
+        if self.shells.provider(shell).is_none() {
+            kfail!("chsh: unknown shell: {}", shell);
+            return;
+        }
+        match self.users.set_shell(&target, shell) {
+            Ok(()) => kprintln!("shell for {} set to {}", target, shell),
+            Err(err) => kfail!("chsh error: {:?}", err),
+        }
+    }
+
     fn show_login_tips(&mut self, user: &str) {
         if self.login_tip_shown {
             return;
@@ -638,8 +1888,11 @@ impl ShellState {
 
     fn logout(&mut self) {
         match self.session.logout() {
-            Ok(()) => kprintln!("logged out"),
-            Err(_) => kprintln!("no active session"),
+            Ok(()) => {
+                self.sync_fs_identity();
+                kprintln!("logged out");
+            }
+            Err(_) => kfail!("no active session"),
         }
     }
 
@@ -659,17 +1912,23 @@ impl ShellState {
         kprintln!("users:");
         for user in users {
             let role = if user.is_admin { "admin" } else { "user" };
-            kprintln!("  {} ({}) home={}", user.name, role, user.home_dir);
+            kprintln!(
+                "  {} ({}) home={} shell={}",
+                user.name,
+                role,
+                user.home_dir,
+                user.shell
+            );
         }
     }
 
     fn user_add(&mut self, name: &str) {
         let Some(active) = self.session.active_user() else {
-            kprintln!("login required");
+            kfail!("login required");
             return;
         };
         let Some(user) = self.users.get_user(active) else {
-            kprintln!("login required");
+            kfail!("login required");
             return;
         };
         if !user.is_admin {
@@ -677,17 +1936,37 @@ impl ShellState {
             return;
         }
         if let Err(err) = self.users.add_user(name, false) {
-            kprintln!("user add failed: {:?}", err);
+            kfail!("user add failed: {:?}", err);
             return;
         }
         let home = default_home_dir(name);
-        if let Err(err) = create_home_dirs(&mut self.fs, &home) {
-            kprintln!("user created but home setup failed: {:?}", err);
+        if let Err(err) = create_home_dirs(&mut self.fs, &home, name) {
+            kfail!("user created but home setup failed: {:?}", err);
         } else {
             kprintln!("user added: {}", name);
         }
     }
 
+    fn prompt(&self) -> String {
+        render_prompt(
+            self.settings.prompt(),
+            self.session.active_user(),
+            self.settings.hostname(),
+            self.file_manager.pwd(),
+        )
+    }
+
+    /// Returns the value of a shell variable for `$NAME` expansion.
+    fn shell_var(&self, name: &str) -> Option<String> {
+        match name {
+            "USER" => self.session.active_user().map(|user| user.to_string()),
+            "HOME" => self.session.active_user().map(default_home_dir),
+            "PWD" => Some(self.file_manager.pwd().to_string()),
+            "HOSTNAME" => Some(self.settings.hostname().to_string()),
+            _ => None,
+        }
+    }
+
     fn print_pwd(&self) {
         kprintln!("{}", self.file_manager.pwd());
     }
@@ -711,7 +1990,7 @@ impl ShellState {
                     }
                 }
             }
-            Err(err) => kprintln!("ls error: {:?}", err),
+            Err(err) => kfail_fs!("ls", err),
         }
     }
 
@@ -721,7 +2000,7 @@ impl ShellState {
         }
         match self.file_manager.cd(&self.fs, path) {
             Ok(()) => kprintln!("cwd={}", self.file_manager.pwd()),
-            Err(err) => kprintln!("cd error: {:?}", err),
+            Err(err) => kfail_fs!("cd", err),
         }
     }
 
@@ -731,38 +2010,16 @@ impl ShellState {
         }
         match self.file_manager.mkdir(&mut self.fs, path) {
             Ok(()) => kprintln!("dir created"),
-            Err(err) => kprintln!("mkdir error: {:?}", err),
+            Err(err) => kfail_fs!("mkdir", err),
         }
     }
 
     fn make_dir_p(&mut self, path: &str) {
-        let resolved = match self.file_manager.resolve(path) {
-            Ok(path) => path,
-            Err(err) => {
-                kprintln!("mkdir -p error: {:?}", err);
-                return;
-            }
-        };
-        if resolved == "/" {
-            kprintln!("dir exists");
-            return;
+        match self.file_manager.mkdir_p(&mut self.fs, path) {
+            Ok(created) if created.is_empty() => kprintln!("dir exists"),
+            Ok(_) => kprintln!("dir created"),
+            Err(err) => kfail_fs!("mkdir -p", err),
         }
-        let mut current = String::new();
-        for segment in resolved.split('/').filter(|s| !s.is_empty()) {
-            if current.is_empty() {
-                current = format!("/{}", segment);
-            } else {
-                current = format!("{}/{}", current, segment);
-            }
-            match self.fs.mkdir(&current) {
-                Ok(()) | Err(FsError::AlreadyExists) => {}
-                Err(err) => {
-                    kprintln!("mkdir -p error: {:?}", err);
-                    return;
-                }
-            }
-        }
-        kprintln!("dir created");
     }
 
     fn touch_file(&mut self, path: &str) {
@@ -771,7 +2028,7 @@ impl ShellState {
         }
         match self.file_manager.write(&mut self.fs, path, "") {
             Ok(()) => kprintln!("file ready"),
-            Err(err) => kprintln!("touch error: {:?}", err),
+            Err(err) => kfail_fs!("touch", err),
         }
     }
 
@@ -781,79 +2038,657 @@ impl ShellState {
         }
         match self.file_manager.cat(&self.fs, path) {
             Ok(text) => kprintln!("{}", text),
-            Err(err) => kprintln!("cat error: {:?}", err),
+            Err(err) => kfail_fs!("cat", err),
         }
     }
 
-    fn edit_file(&mut self, path: &str) {
-        let Some(provider) = self.board.provider_for("ruzzle.slot.editor@1") else {
-            kprintln!("editor slot is empty. plug a piece into ruzzle.slot.editor@1 first.");
+    fn wc_file(&self, path: &str) {
+        if self.require_login().is_none() {
             return;
-        };
-
-        let contents = match self.file_manager.cat(&self.fs, path) {
-            Ok(text) => text,
-            Err(FsError::NotFound) => String::new(),
-            Err(err) => {
-                kprintln!("edit error: {:?}", err);
-                return;
-            }
-        };
+        }
+        match self.file_manager.cat(&self.fs, path) {
+            Ok(text) => kprintln!("{}", format_word_count(&WordCount::from_text(&text), path)),
+            Err(err) => kfail_fs!("wc", err),
+        }
+    }
 
-        let mut buffer = TextBuffer::from_text(&contents);
-        kprintln!("editor: {} (provider={})", path, provider);
-        print_editor_help();
+    fn stat_path(&self, path: &str) {
+        let result = self.file_manager.resolve(path).and_then(|resolved| {
+            let metadata = self.fs.metadata(&resolved)?;
+            let (size, target) = match metadata.kind {
+                FileKind::Symlink => {
+                    let target = self.fs.read_link(&resolved)?;
+                    (target.len(), Some(target))
+                }
+                _ => (self.fs.size_of(&resolved)?, None),
+            };
+            Ok(StatInfo {
+                path: resolved,
+                kind: metadata.kind.name().to_string(),
+                size,
+                owner: metadata.owner,
+                group: metadata.group,
+                mode: metadata.mode,
+                permissions: format_mode(metadata.kind, metadata.mode),
+                target,
+                created: metadata.created,
+                modified: metadata.modified,
+            })
+        });
+        match result {
+            Ok(info) => kprintln!("{}", format_stat(&info)),
+            Err(err) => kfail_fs!("stat", err),
+        }
+    }
 
-        loop {
-            kprint!("edit> ");
-            let input = read_line();
-            match parse_editor_command(&input) {
-                EditorCommand::Append(text) => {
-                    let index = buffer.line_count();
-                    if let Err(err) = buffer.insert_line(index, &text) {
-                        kprintln!("append error: {:?}", err);
-                    }
+    fn checksum_paths(&self, path: &str) {
+        if self.require_login().is_none() {
+            return;
+        }
+        match self.file_manager.checksum(&self.fs, path) {
+            Ok(sums) => {
+                for (path, sum) in sums {
+                    kprintln!("{}", format_checksum(sum, &path));
                 }
-                EditorCommand::Insert { index, text } => {
-                    if let Err(err) = buffer.insert_line(index, &text) {
-                        kprintln!("insert error: {:?}", err);
-                    }
+            }
+            Err(err) => kfail_fs!("checksum", err),
+        }
+    }
+
+    fn find_paths(&self, path: &str, name: Option<&str>) {
+        if self.require_login().is_none() {
+            return;
+        }
+        match self.file_manager.find(&self.fs, path, name) {
+            Ok(paths) if paths.is_empty() => kprintln!("<none>"),
+            Ok(paths) => {
+                for path in paths {
+                    kprintln!("{}", path);
                 }
-                EditorCommand::Replace { index, text } => {
-                    if let Err(err) = buffer.replace_line(index, &text) {
-                        kprintln!("replace error: {:?}", err);
-                    }
+            }
+            Err(err) => kfail_fs!("find", err),
+        }
+    }
+
+    fn link_path(&mut self, target: &str, link: &str) {
+        if self.require_login().is_none() {
+            return;
+        }
+        match self.file_manager.ln_s(&mut self.fs, target, link) {
+            Ok(()) => kprintln!("linked {} -> {}", link, target),
+            Err(err) => kfail_fs!("ln", err),
+        }
+    }
+
+    fn change_mode(&mut self, mode: &str, path: &str) {
+        let result = parse_mode(mode).and_then(|mode| {
+            let resolved = self.file_manager.resolve(path)?;
+            self.fs.chmod(&resolved, mode)
+        });
+        match result {
+            Ok(()) => kprintln!("mode of {} set to {}", path, mode),
+            Err(err) => kfail_fs!("chmod", err),
+        }
+    }
+
+    fn change_owner(&mut self, spec: &str, path: &str) {
+        let (user, group) = match spec.split_once(':') {
+            Some((user, group)) => (user, Some(group)),
+            None => (spec, None),
+        };
+        if !user.is_empty() && !self.users.has_user(user) {
+            kfail!("chown error: unknown user: {}", user);
+            return;
+        }
+        let result = self.file_manager.resolve(path).and_then(|resolved| {
+            if !user.is_empty() {
+                self.fs.chown(&resolved, user)?;
+            }
+            match group {
+                Some(group) => self.fs.chgrp(&resolved, group),
+                None => Ok(()),
+            }
+        });
+        match result {
+            Ok(()) => kprintln!("owner of {} set to {}", path, spec),
+            Err(err) => kfail_fs!("chown", err),
+        }
+    }
+
+    fn run_gpu(&mut self, args: Option<&str>) {
+        if self.board.provider_for("ruzzle.slot.gpu@1").is_none() {
+            kfail!("gpu slot is empty. plug a piece into ruzzle.slot.gpu@1 first.");
+            return;
+        }
+        let args = args.unwrap_or("show");
+        let mut parts = args.split_whitespace();
+        let cmd = parts.next().unwrap_or("");
+        let rest = parts.collect::<Vec<&str>>();
+        let device = GpuDevice;
+        let result = match (cmd, rest.as_slice()) {
+            ("show", []) => {
+                match &self.tensor {
+                    Some(tensor) => kprint!("{}", tensor.format()),
+                    None => kprintln!("<none>"),
+                }
+                return;
+            }
+            ("new", values) => parse_tensor(values),
+            ("save", [path]) => {
+                let Some(tensor) = &self.tensor else {
+                    kfail!("gpu error: no tensor");
+                    return;
+                };
+                let result = self
+                    .file_manager
+                    .resolve(path)
+                    .map_err(GpuError::Fs)
+                    .and_then(|resolved| tensor.save(&mut self.fs, &resolved));
+                match result {
+                    Ok(()) => kprintln!("tensor saved: {}", path),
+                    Err(err) => kfail!("gpu error: {:?}", err),
+                }
+                return;
+            }
+            ("load", [path]) => self.load_tensor(path),
+            ("add" | "matmul", [path]) => {
+                let Some(lhs) = &self.tensor else {
+                    kfail!("gpu error: no tensor");
+                    return;
+                };
+                self.load_tensor(path).and_then(|rhs| {
+                    if cmd == "add" {
+                        device.add(lhs, &rhs)
+                    } else {
+                        device.matmul(lhs, &rhs)
+                    }
+                })
+            }
+            _ => {
+                kfail!("gpu [show|new <rows> <cols> <values..>|add <path>|matmul <path>|save <path>|load <path>]");
+                return;
+            }
+        };
+        match result {
+            Ok(tensor) => {
+                kprintln!("tensor {}x{}", tensor.rows, tensor.cols);
+                self.tensor = Some(tensor);
+            }
+            Err(err) => kfail!("gpu error: {:?}", err),
+        }
+    }
+
+    fn run_container(&mut self, args: Option<&str>) {
+        let Some(provider) = self
+            .board
+            .provider_for("ruzzle.slot.container@1")
+            .map(str::to_string)
+        else {
+            kfail!("container slot is empty. plug a piece into ruzzle.slot.container@1 first.");
+            return;
+        };
+        let args = args.unwrap_or("list");
+        let parts = args.split_whitespace().collect::<Vec<&str>>();
+        let result = match parts.as_slice() {
+            ["list"] => {
+                let containers = self.containers.list();
+                kprintln!("containers:");
+                if containers.is_empty() {
+                    kprintln!("  <none>");
+                }
+                for info in containers {
+                    kprintln!(
+                        "  {} {} {}",
+                        info.spec.name,
+                        info.state.name(),
+                        info.spec.image
+                    );
+                }
+                return;
+            }
+            ["create", name, image, command @ ..] => {
+                let spec = ContainerSpec {
+                    name: name.to_string(),
+                    image: image.to_string(),
+                    command: command.iter().map(|arg| arg.to_string()).collect(),
+                    env: Vec::new(),
+                };
+                self.containers
+                    .create(spec)
+                    .and_then(|()| self.containers.ensure_overlay(&mut self.fs, name))
+                    .map(|overlay| format!("container created: {} ({})", name, overlay))
+            }
+            ["start", name] => {
+                // The slot provider spawns the container process.
+                if let Err(err) = self.module_syscall(&provider, Syscall::Spawn) {
+                    kfail!("container error: {} cannot spawn: {:?}", provider, err);
+                    return;
+                }
+                self.containers
+                    .start(name)
+                    .map(|()| format!("container started: {}", name))
+            }
+            ["stop", name] => self
+                .containers
+                .stop(name)
+                .map(|()| format!("container stopped: {}", name)),
+            ["rm", name] => self
+                .containers
+                .remove(name)
+                .map(|()| format!("container removed: {}", name)),
+            ["checkpoint", name] => self
+                .containers
+                .checkpoint(&mut self.fs, name)
+                .map(|dir| format!("checkpoint saved: {}", dir)),
+            ["restore", name] => self
+                .containers
+                .restore(&mut self.fs, name)
+                .map(|state| format!("container restored: {} ({})", name, state.name())),
+            _ => {
+                kfail!("container [list|create <name> <image> [cmd..]|start|stop|rm|checkpoint|restore <name>]");
+                return;
+            }
+        };
+        match result {
+            Ok(message) => kprintln!("{}", message),
+            Err(err) => kfail!("container error: {:?}", err),
+        }
+    }
+
+    fn run_http(&mut self, args: Option<&str>) {
+        if self.board.provider_for("ruzzle.slot.server@1").is_none() {
+            kfail!("server slot is empty. plug a piece into ruzzle.slot.server@1 first.");
+            return;
+        }
+        let args = args.unwrap_or("top");
+        let parts = args.split_whitespace().collect::<Vec<&str>>();
+        match parts.as_slice() {
+            ["top"] | ["top", _] => {
+                let limit = match parts.get(1).map(|value| value.parse::<usize>()) {
+                    None => 5,
+                    Some(Ok(limit)) => limit,
+                    Some(Err(_)) => {
+                        kfail!("http top [n]");
+                        return;
+                    }
+                };
+                let rows = self.server.route_metrics();
+                kprint!(
+                    "{}",
+                    format_http_top(&rows, self.server.unmatched_requests(), limit)
+                );
+            }
+            ["routes"] => {
+                let routes = self.server.list_routes();
+                kprintln!("routes:");
+                if routes.is_empty() {
+                    kprintln!("  <none>");
+                }
+                for (method, path) in routes {
+                    kprintln!("  {} {}", method, path);
+                }
+            }
+            ["route", method, path, status, body @ ..] => {
+                let Ok(status) = status.parse::<u16>() else {
+                    kfail!("http error: invalid status: {}", status);
+                    return;
+                };
+                let response = HttpResponse {
+                    status,
+                    body: body.join(" "),
+                };
+                match self.server.register_route(method, path, response) {
+                    Ok(()) => kprintln!("route added: {} {}", method, path),
+                    Err(err) => kfail!("http error: {:?}", err),
+                }
+            }
+            ["request", method, path, body @ ..] => {
+                let body = body.join(" ");
+                let raw = format!(
+                    "{} {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                    method,
+                    path,
+                    body.len(),
+                    body
+                );
+                let now = clock::now_ns();
+                let mut connection = self.server.open_connection(now);
+                connection.receive(raw.as_bytes());
+                self.server.drive(&mut connection, now);
+                let output = connection.take_output(usize::MAX);
+                kprintln!("{}", String::from_utf8_lossy(&output));
+            }
+            ["limits"] => {
+                let limits = self.server.limits();
+                kprintln!(
+                    "limits: header={}B body={}B read={} write={}",
+                    limits.max_header_bytes,
+                    limits.max_body_bytes,
+                    format_duration_ns(limits.read_timeout_ns),
+                    format_duration_ns(limits.write_timeout_ns)
+                );
+            }
+            ["limits", header, body] => {
+                let (Ok(header), Ok(body)) = (header.parse::<usize>(), body.parse::<usize>())
+                else {
+                    kfail!("http limits <max_header_bytes> <max_body_bytes>");
+                    return;
+                };
+                let mut limits = self.server.limits();
+                limits.max_header_bytes = header;
+                limits.max_body_bytes = body;
+                self.server.set_limits(limits);
+                kprintln!("limits updated: header={}B body={}B", header, body);
+            }
+            ["accept", method, path] => {
+                let request = HttpRequest {
+                    method: method.to_string(),
+                    path: path.to_string(),
+                    body: String::new(),
+                };
+                match self.server.accept(request) {
+                    Ok(()) => kprintln!("accepted: in-flight={}", self.server.in_flight()),
+                    Err(err) => kfail!("http error: {:?}", err),
+                }
+            }
+            ["start"] => match self.server.start() {
+                Ok(()) => kprintln!("server started"),
+                Err(err) => kfail!("http error: {:?}", err),
+            },
+            ["stop"] => {
+                if !self.drain_server() {
+                    kfail!("http error: {:?}", ServerError::NotRunning);
                 }
-                EditorCommand::Delete(index) => {
-                    if let Err(err) = buffer.remove_line(index) {
-                        kprintln!("delete error: {:?}", err);
+            }
+            _ => kfail!("http [top [n]|routes|route <method> <path> <status> [body]|request <method> <path> [body]|accept <method> <path>|limits [header body]|start|stop]"),
+        }
+    }
+
+    fn run_webconsole(&mut self, args: Option<&str>) {
+        if self
+            .board
+            .provider_for("ruzzle.slot.webconsole@1")
+            .is_none()
+        {
+            kfail!("webconsole slot is empty. plug a piece into ruzzle.slot.webconsole@1 first.");
+            return;
+        }
+        let args = args.unwrap_or("status");
+        match args.split_once(' ').unwrap_or((args, "")) {
+            ("status", "") => {
+                let installed = self
+                    .server
+                    .list_routes()
+                    .iter()
+                    .any(|(method, path)| method == "GET" && path == ASSETS[1].route);
+                kprintln!(
+                    "webconsole: assets={} routes={} bridge={}",
+                    ASSET_ROOT,
+                    if installed { "installed" } else { "missing" },
+                    WS_PATH
+                );
+            }
+            ("install", "") => {
+                let result = install_assets(&mut self.fs, ASSET_ROOT)
+                    .and_then(|_| register_routes(&mut self.server, &self.fs, ASSET_ROOT));
+                match result {
+                    Ok(count) => {
+                        kprintln!("webconsole: {} assets served from {}", count, ASSET_ROOT)
                     }
+                    Err(err) => kfail!("webconsole error: {:?}", err),
+                }
+            }
+            ("send", line) if !line.is_empty() => {
+                // Lines with a wire form go out as binary commands, like a browser sends them.
+                let frame = match to_ipc(&parse_command(line)) {
+                    Some(command) => Frame {
+                        opcode: Opcode::Binary,
+                        payload: encode_command(&command),
+                    },
+                    None => Frame {
+                        opcode: Opcode::Text,
+                        payload: line.as_bytes().to_vec(),
+                    },
+                };
+                let inbound = encode_frame(&frame, Some([0x52, 0x5A, 0x4C, 0x45]));
+                match self.bridge_bytes(&inbound) {
+                    Ok(outbound) => kprintln!(
+                        "webconsole: {} bytes in, {} bytes out",
+                        inbound.len(),
+                        outbound
+                    ),
+                    Err(err) => kfail!("webconsole error: {:?}", err),
+                }
+            }
+            _ => kfail!("webconsole [status|install|send <line>]"),
+        }
+    }
+
+    /// Runs one client frame through the shell bridge; returns the reply size.
+    fn bridge_bytes(&mut self, inbound: &[u8]) -> Result<usize, WebConsoleError> {
+        let Some((frame, _)) = decode_frame(inbound).map_err(WebConsoleError::Frame)? else {
+            return Ok(0);
+        };
+        let credential = self.session.credential(&self.users);
+        let reply = bridge_frame(&frame, credential.as_ref(), |request| {
+            self.bridge_request(request)
+        })?;
+        Ok(reply.map_or(0, |reply| encode_frame(&reply, None).len()))
+    }
+
+    /// Runs one bridged request as the shell.
+    fn bridge_request(&mut self, request: BridgeRequest) -> ShellResponse {
+        let interactive = core::mem::replace(&mut self.interactive, false);
+        let response = self.run_bridged(request);
+        self.interactive = interactive;
+        response
+    }
+
+    fn run_bridged(&mut self, request: BridgeRequest) -> ShellResponse {
+        match request {
+            BridgeRequest::Line(line) => {
+                // Text lines run as the console session, so they carry its credential.
+                let command = parse_command(&line);
+                let credential = self.session.credential(&self.users);
+                if to_ipc(&command).is_some_and(|wire| !is_authorized(&wire, credential.as_ref())) {
+                    return ShellResponse::Error {
+                        code: ShellErrorCode::PermissionDenied,
+                        message: "admin privilege required".to_string(),
+                    };
+                }
+                self.dispatch(command).to_response()
+            }
+            BridgeRequest::Command(command) => self.respond(from_ipc(command)),
+        }
+    }
+
+    /// Serves framed binary shell messages on the serial line until the
+    /// host sends an empty frame.
+    ///
+    /// `log tail --follow` answers with `Streaming` responses carrying new
+    /// log lines on each timer tick; the next byte from the host ends it
+    /// with an `Ok`.
+    fn run_framed_from_console(&mut self) {
+        let mut bridge = StreamBridge::new();
+        let mut follow: Option<u64> = None;
+        // Nobody is at the console to answer y/N, so prompts decline.
+        let confirm = core::mem::replace(&mut self.confirm, |_| false);
+        while !bridge.is_closed() {
+            if !console::has_input() {
+                if let Some(cursor) = follow {
+                    let (lines, next) = console::log_since(cursor);
+                    follow = Some(next);
+                    if !lines.is_empty() {
+                        console::write_serial(&bridge.stream(&ShellResponse::Text {
+                            status: ShellStatus::Streaming,
+                            text: lines.join("\n"),
+                        }));
+                    }
+                }
+                clock::wait_tick();
+                continue;
+            }
+            if follow.take().is_some() {
+                console::write_serial(&bridge.stream(&ShellResponse::Text {
+                    status: ShellStatus::Ok,
+                    text: "log tail stopped".to_string(),
+                }));
+            }
+            let byte = console::read_byte();
+            let credential = self.session.credential(&self.users);
+            let reply = bridge.push(&[byte], credential.as_ref(), |request| match request {
+                BridgeRequest::Command(ShellCommand::LogTail {
+                    follow: true,
+                    lines,
+                }) => {
+                    follow = Some(console::log_cursor());
+                    let count = lines.map_or(DEFAULT_LOG_TAIL_LINES, |lines| lines as usize);
+                    let mut text = console::log_tail(Some(count));
+                    text.push(format_log_follow_header());
+                    ShellResponse::Text {
+                        status: ShellStatus::Streaming,
+                        text: text.join("\n"),
+                    }
+                }
+                request => self.bridge_request(request),
+            });
+            console::write_serial(&reply);
+        }
+        self.confirm = confirm;
+        klog!(
+            "framed bridge closed: {} corrupt frames, {} bytes dropped",
+            bridge.decoder().corrupt_frames(),
+            bridge.decoder().dropped_bytes()
+        );
+    }
+
+    /// Gracefully stops the server; returns false if it was not running.
+    fn drain_server(&mut self) -> bool {
+        let deadline = clock::now_ns().saturating_add(SERVER_DRAIN_GRACE_NS);
+        match self.server.shutdown(deadline, clock::now_ns) {
+            Ok(report) => {
+                kprintln!("{}", format_shutdown_report(&report));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn shutdown(&mut self) {
+        kprintln!("shutdown: stopping services");
+        self.drain_server();
+        kprintln!("shutdown: system halted");
+        power::halt();
+    }
+
+    fn load_tensor(&self, path: &str) -> Result<Tensor, GpuError> {
+        let resolved = self
+            .file_manager
+            .resolve(path)
+            .map_err(GpuError::Fs)?;
+        Tensor::load(&self.fs, &resolved)
+    }
+
+    fn edit_file(&mut self, path: &str) {
+        let Some(provider) = self.board.provider_for("ruzzle.slot.editor@1") else {
+            kfail!("editor slot is empty. plug a piece into ruzzle.slot.editor@1 first.");
+            return;
+        };
+
+        let contents = match self.file_manager.cat(&self.fs, path) {
+            Ok(text) => text,
+            Err(FsError::NotFound) => String::new(),
+            Err(err) => {
+                kfail!("edit error: {:?}", err);
+                return;
+            }
+        };
+
+        let mut editor = LineEditor::from_text(&contents);
+        kprintln!("editor: {} (provider={})", path, provider);
+        kprint!("{}", format_editor_help());
+
+        loop {
+            kprint!("edit> ");
+            let input = read_line_with(Some(&mut self.clipboard));
+            let result = match parse_editor_command(&input) {
+                EditorCommand::Append(text) => {
+                    editor.append(&text);
+                    Ok(())
                 }
+                EditorCommand::Insert { index, text } => editor.insert(index, &text),
+                EditorCommand::Replace { index, text } => editor.replace(index, &text),
+                EditorCommand::Delete(index) => editor.delete(index),
+                EditorCommand::Goto(index) => editor.goto(index).map(|()| {
+                    kprintln!("{:>3} {}", index + 1, editor.line(index).unwrap_or(""));
+                }),
+                EditorCommand::Yank(index) => match editor.line(index) {
+                    Some(line) => {
+                        self.clipboard.copy(line);
+                        kprint!("{}", encode_osc52(line));
+                        kprintln!("yanked line {}", index + 1);
+                        Ok(())
+                    }
+                    None => Err(EditError::IndexOutOfBounds),
+                },
+                EditorCommand::Paste(index) => match self.clipboard.paste() {
+                    Ok(text) => editor.paste(index, &text),
+                    Err(_) => {
+                        kprintln!("clipboard is empty");
+                        Ok(())
+                    }
+                },
                 EditorCommand::Print => {
-                    print_editor_buffer(&buffer);
+                    kprint!("{}", format_editor_buffer(&editor));
+                    Ok(())
                 }
                 EditorCommand::Save => {
-                    if save_editor_buffer(&mut self.file_manager, &mut self.fs, path, &buffer) {
-                        kprintln!("saved");
-                    }
+                    self.save_editor(path, &mut editor);
+                    Ok(())
                 }
                 EditorCommand::SaveQuit => {
-                    if save_editor_buffer(&mut self.file_manager, &mut self.fs, path, &buffer) {
-                        kprintln!("saved");
+                    if self.save_editor(path, &mut editor) {
                         break;
                     }
+                    Ok(())
                 }
-                EditorCommand::Quit => {
+                EditorCommand::Quit if editor.is_dirty() => {
+                    kprintln!("unsaved changes; use :wq to save or :q! to discard");
+                    Ok(())
+                }
+                EditorCommand::Quit | EditorCommand::ForceQuit => {
                     kprintln!("editor closed");
                     break;
                 }
                 EditorCommand::Help => {
-                    print_editor_help();
+                    kprint!("{}", format_editor_help());
+                    Ok(())
                 }
                 EditorCommand::Unknown => {
                     kprintln!("editor: unknown command");
-                    print_editor_help();
+                    kprint!("{}", format_editor_help());
+                    Ok(())
                 }
+            };
+            if let Err(err) = result {
+                kprintln!("edit error: {:?}", err);
+            }
+        }
+    }
+
+    fn save_editor(&mut self, path: &str, editor: &mut LineEditor) -> bool {
+        match self
+            .file_manager
+            .write(&mut self.fs, path, &editor.to_text())
+        {
+            Ok(()) => {
+                editor.mark_saved();
+                kprintln!("saved");
+                true
+            }
+            Err(err) => {
+                kfail!("save error: {:?}", err);
+                false
             }
         }
     }
@@ -864,7 +2699,7 @@ impl ShellState {
         }
         match self.file_manager.write(&mut self.fs, path, contents) {
             Ok(()) => kprintln!("write ok"),
-            Err(err) => kprintln!("write error: {:?}", err),
+            Err(err) => kfail_fs!("write", err),
         }
     }
 
@@ -874,70 +2709,32 @@ impl ShellState {
         }
         match self.file_manager.rm(&mut self.fs, path) {
             Ok(()) => kprintln!("removed"),
-            Err(err) => kprintln!("rm error: {:?}", err),
+            Err(err) => kfail_fs!("rm", err),
         }
     }
 
     fn remove_path_recursive(&mut self, path: &str) {
-        let resolved = match self.file_manager.resolve(path) {
-            Ok(path) => path,
-            Err(err) => {
-                kprintln!("rm -r error: {:?}", err);
-                return;
-            }
-        };
-        match remove_recursive(&mut self.fs, &resolved) {
-            Ok(()) => kprintln!("removed"),
-            Err(err) => kprintln!("rm -r error: {:?}", err),
+        match self.file_manager.rm_r(&mut self.fs, path) {
+            Ok(removed) => kprintln!("removed {} files, {} dirs", removed.files, removed.dirs),
+            Err(err) => kfail_fs!("rm -r", err),
         }
     }
 
     fn copy_path(&mut self, src: &str, dst: &str, recursive: bool) {
-        let src_path = match self.file_manager.resolve(src) {
-            Ok(path) => path,
-            Err(err) => {
-                kprintln!("cp error: {:?}", err);
-                return;
-            }
-        };
-        let dst_path = match self.file_manager.resolve(dst) {
-            Ok(path) => path,
-            Err(err) => {
-                kprintln!("cp error: {:?}", err);
-                return;
-            }
-        };
-        match copy_recursive(&mut self.fs, &src_path, &dst_path, recursive) {
+        match self.file_manager.cp(&mut self.fs, src, dst, recursive) {
             Ok(()) => kprintln!("copied"),
-            Err(err) => kprintln!("cp error: {:?}", err),
+            Err(err) => kfail_fs!("cp", err),
         }
     }
 
     fn move_path(&mut self, src: &str, dst: &str) {
-        let src_path = match self.file_manager.resolve(src) {
-            Ok(path) => path,
-            Err(err) => {
-                kprintln!("mv error: {:?}", err);
-                return;
-            }
-        };
-        let dst_path = match self.file_manager.resolve(dst) {
-            Ok(path) => path,
-            Err(err) => {
-                kprintln!("mv error: {:?}", err);
-                return;
-            }
-        };
-        match copy_recursive(&mut self.fs, &src_path, &dst_path, true) {
-            Ok(()) => match remove_recursive(&mut self.fs, &src_path) {
-                Ok(()) => kprintln!("moved"),
-                Err(err) => kprintln!("mv cleanup error: {:?}", err),
-            },
-            Err(err) => kprintln!("mv error: {:?}", err),
+        match self.file_manager.mv(&mut self.fs, src, dst) {
+            Ok(()) => kprintln!("moved"),
+            Err(err) => kfail_fs!("mv", err),
         }
     }
 
-    fn print_slots(&self) {
+    fn print_slots(&self, verbose: bool) {
         let rows = self
             .board
             .list()
@@ -945,78 +2742,61 @@ impl ShellState {
             .map(|slot| SlotRow {
                 name: slot.name,
                 required: slot.required,
-                provider: slot.provider,
+                provider: (!slot.providers.is_empty()).then(|| slot.providers.join(", ")),
+                description: slot.description,
+                category: slot.category,
+                tags: slot.tags,
+                locked: slot.locked,
             })
             .collect::<Vec<SlotRow>>();
-        kprintln!("{}", format_slots(&rows));
+        kprintln!("{}", format_slots(&rows, self.style, verbose));
+        let history = self.board.history();
+        let recent = &history[history.len().saturating_sub(SLOT_HISTORY_SHOWN)..];
+        if !recent.is_empty() {
+            kprintln!("recent changes:");
+            for event in recent {
+                kprintln!("  [{}ms] {}", event.tick, event.format());
+            }
+        }
     }
 
-    fn run_ip(&mut self, args: Option<&str>) {
-        let Some(args) = args else {
-            self.print_interfaces();
-            return;
-        };
-        let mut parts = args.split_whitespace();
-        let cmd = parts.next().unwrap_or("");
-        let rest = parts.collect::<Vec<&str>>();
-        match cmd {
-            "add" => {
-                if rest.len() != 1 {
-                    kprintln!("ip add <iface>");
-                    return;
-                }
-                match self.net.add_interface(rest[0]) {
-                    Ok(()) => kprintln!("interface added: {}", rest[0]),
-                    Err(err) => kprintln!("ip error: {:?}", err),
-                }
+    fn run_ip(&mut self, ip: IpCommand) {
+        let (result, done, iface) = match ip {
+            IpCommand::Show => {
+                self.print_interfaces();
+                return;
             }
-            "del" => {
-                if rest.len() != 1 {
-                    kprintln!("ip del <iface>");
-                    return;
-                }
-                match self.net.remove_interface(rest[0]) {
-                    Ok(()) => kprintln!("interface removed: {}", rest[0]),
-                    Err(err) => kprintln!("ip error: {:?}", err),
-                }
+            IpCommand::Stats(iface) => {
+                self.print_interface_stats(iface.as_deref());
+                return;
             }
-            "up" => {
-                if rest.len() != 1 {
-                    kprintln!("ip up <iface>");
-                    return;
-                }
-                match self.net.set_up(rest[0], true) {
-                    Ok(()) => kprintln!("interface up: {}", rest[0]),
-                    Err(err) => kprintln!("ip error: {:?}", err),
-                }
+            IpCommand::LinkAdd(iface) => (self.net.add_interface(&iface), "interface added", iface),
+            IpCommand::LinkDel(iface) => {
+                let result = self.net.remove_interface(&iface);
+                (result, "interface removed", iface)
             }
-            "down" => {
-                if rest.len() != 1 {
-                    kprintln!("ip down <iface>");
-                    return;
-                }
-                match self.net.set_up(rest[0], false) {
-                    Ok(()) => kprintln!("interface down: {}", rest[0]),
-                    Err(err) => kprintln!("ip error: {:?}", err),
-                }
+            IpCommand::LinkSet { iface, up } => {
+                let done = if up { "interface up" } else { "interface down" };
+                (self.net.set_up(&iface, up), done, iface)
             }
-            "addr" => {
-                if rest.len() != 2 {
-                    kprintln!("ip addr <iface> <addr|none>");
-                    return;
-                }
-                let addr = match rest[1] {
-                    "none" | "-" => None,
-                    value => Some(value),
-                };
-                match self.net.set_ipv4(rest[0], addr) {
-                    Ok(()) => kprintln!("ip addr updated: {}", rest[0]),
-                    Err(err) => kprintln!("ip error: {:?}", err),
-                }
+            IpCommand::LinkAddress { iface, mac } => {
+                let result = MacAddr::parse(&mac)
+                    .ok_or(NetError::InvalidMac)
+                    .and_then(|mac| self.net.set_mac(&iface, mac));
+                (result, "ip mac updated", iface)
             }
-            _ => {
-                kprintln!("ip [add|del|up|down|addr]");
+            IpCommand::AddrAdd { iface, cidr } => {
+                let result = self.net.set_ipv4(&iface, Some(&cidr));
+                (result, "ip addr updated", iface)
+            }
+            IpCommand::AddrDel(iface) => {
+                let result = self.net.set_ipv4(&iface, None);
+                (result, "ip addr removed", iface)
             }
+        };
+        match result {
+            Ok(()) => kprintln!("{}: {}", done, iface),
+            Err(err) => kfail!("ip error: {:?}", err),
         }
     }
 
@@ -1029,41 +2809,337 @@ impl ShellState {
         kprintln!("interfaces:");
         for iface in list {
             let state = if iface.up { "up" } else { "down" };
-            let addr = iface.ipv4.as_deref().unwrap_or("-");
-            kprintln!("  {} [{}] ipv4={}", iface.name, state, addr);
+            let addr = iface.cidr().unwrap_or_else(|| "-".to_string());
+            kprintln!(
+                "  {} [{}] mac={} ipv4={}",
+                iface.name,
+                state,
+                iface.mac.format(),
+                addr
+            );
         }
     }
 
-    fn run_route(&mut self, args: Option<&str>) {
-        let Some(args) = args else {
-            self.print_routes();
+    fn print_interface_stats(&self, name: Option<&str>) {
+        let list = self.net.list();
+        if let Some(name) = name {
+            if !list.iter().any(|iface| iface.name == name) {
+                kfail!("ip error: {:?}", NetError::NotFound);
+                return;
+            }
+        }
+        kprintln!("interface statistics:");
+        for iface in list
+            .iter()
+            .filter(|iface| name.is_none_or(|name| iface.name == name))
+        {
+            let stats = &iface.stats;
+            kprintln!("  {}:", iface.name);
+            kprintln!(
+                "    rx: packets={} bytes={} errors={} dropped={}",
+                stats.rx_packets,
+                stats.rx_bytes,
+                stats.rx_errors,
+                stats.rx_dropped
+            );
+            kprintln!(
+                "    tx: packets={} bytes={} errors={} dropped={}",
+                stats.tx_packets,
+                stats.tx_bytes,
+                stats.tx_errors,
+                stats.tx_dropped
+            );
+        }
+    }
+
+    fn run_route(&mut self, route: RouteCommand) {
+        match route {
+            RouteCommand::Show => self.print_routes(),
+            RouteCommand::Add {
+                destination,
+                iface,
+                gateway,
+                metric,
+            } => match self
+                .net
+                .add_route(&destination, &iface, gateway.as_deref(), metric)
+            {
+                Ok(()) => kprintln!("route added: {} -> {}", destination, iface),
+                Err(err) => kfail!("route error: {:?}", err),
+            },
+            RouteCommand::Get(addr) => match self.net.lookup_route(&addr) {
+                Some(route) => kprintln!("{}: {}", addr, format_route(&route)),
+                None => kfail!("route: no route to {}", addr),
+            },
+            RouteCommand::Del(destination) => match self.net.remove_route(&destination) {
+                Ok(()) => kprintln!("route removed: {}", destination),
+                Err(err) => kfail!("route error: {:?}", err),
+            },
+        }
+    }
+
+    fn run_fw(&mut self, args: Option<&str>) {
+        let args = args.unwrap_or("list");
+        let (cmd, rest) = args.split_once(' ').unwrap_or((args, ""));
+        let rest = rest.trim();
+        match cmd {
+            "list" if rest.is_empty() => self.print_firewall(),
+            "add" => match FirewallRule::parse(rest).and_then(|rule| {
+                let text = rule.format();
+                self.firewall.add(rule).map(|()| text)
+            }) {
+                Ok(text) => kprintln!("rule {} added: {}", self.firewall.rules().len(), text),
+                Err(err) => kfail!("fw error: {:?}", err),
+            },
+            "del" => {
+                let Ok(position) = rest.parse::<usize>() else {
+                    kfail!("fw del <n>");
+                    return;
+                };
+                match self.firewall.remove(position) {
+                    Ok(rule) => kprintln!("rule {} removed: {}", position, rule.format()),
+                    Err(err) => kfail!("fw error: {:?}", err),
+                }
+            }
+            "policy" => {
+                let direction = match rest.split_whitespace().next() {
+                    Some("in") => Direction::In,
+                    Some("out") => Direction::Out,
+                    _ => {
+                        kfail!("fw policy <in|out> <allow|deny>");
+                        return;
+                    }
+                };
+                let action = match rest.split_whitespace().nth(1) {
+                    Some("allow") => FirewallAction::Allow,
+                    Some("deny") => FirewallAction::Deny,
+                    _ => {
+                        kfail!("fw policy <in|out> <allow|deny>");
+                        return;
+                    }
+                };
+                self.firewall.set_policy(direction, action);
+                self.print_firewall();
+            }
+            _ => kfail!("fw [list|add <rule>|del <n>|policy <in|out> <allow|deny>]"),
+        }
+    }
+
+    fn run_dhcpd(&mut self, args: Option<&str>) {
+        if self.board.provider_for("ruzzle.slot.dhcpd@1").is_none() {
+            kfail!("dhcpd slot is empty. plug a piece into ruzzle.slot.dhcpd@1 first.");
             return;
-        };
-        let mut parts = args.split_whitespace();
-        let cmd = parts.next().unwrap_or("");
-        let rest = parts.collect::<Vec<&str>>();
+        }
+        let args = args.unwrap_or("status");
+        let (cmd, rest) = args.split_once(' ').unwrap_or((args, ""));
+        let rest = rest.trim();
+        let now = clock::now_ns() / 1_000_000_000;
         match cmd {
-            "add" => {
-                if rest.len() != 2 {
-                    kprintln!("route add <dest> <iface>");
+            "status" if rest.is_empty() => self.print_dhcpd_status(now),
+            "start" => self.start_dhcpd(rest),
+            "stop" if rest.is_empty() => match self.dhcpd.take() {
+                Some((iface, _)) => kprintln!("dhcpd: stopped on {}", iface),
+                None => kfail!("dhcpd: not running"),
+            },
+            "leases" if rest.is_empty() => {
+                let Some((_, server)) = self.dhcpd.as_mut() else {
+                    kfail!("dhcpd: not running");
                     return;
+                };
+                server.expire(now);
+                let leases = server.leases();
+                if leases.is_empty() {
+                    kprintln!("  <no leases>");
                 }
-                match self.net.add_route(rest[0], rest[1]) {
-                    Ok(()) => kprintln!("route added: {} -> {}", rest[0], rest[1]),
-                    Err(err) => kprintln!("route error: {:?}", err),
+                for lease in leases {
+                    let state = match lease.state {
+                        LeaseState::Offered => "offered",
+                        LeaseState::Bound => "bound",
+                        LeaseState::Declined => "declined",
+                    };
+                    kprintln!(
+                        "  {:<15} {} {:<8} {:>6}s left  {}",
+                        format_ipv4(lease.addr),
+                        lease.mac.format(),
+                        state,
+                        lease.expires - now,
+                        lease.hostname.as_deref().unwrap_or("-")
+                    );
                 }
             }
-            "del" => {
-                if rest.len() != 1 {
-                    kprintln!("route del <dest>");
+            "release" => {
+                let Some((_, server)) = self.dhcpd.as_mut() else {
+                    kfail!("dhcpd: not running");
+                    return;
+                };
+                let Some(addr) = parse_ipv4(rest) else {
+                    kfail!("dhcpd release <ipv4>");
                     return;
+                };
+                match server.release(addr) {
+                    Ok(()) => kprintln!("dhcpd: released {}", rest),
+                    Err(err) => kfail!("dhcpd error: {:?}", err),
                 }
-                match self.net.remove_route(rest[0]) {
-                    Ok(()) => kprintln!("route removed: {}", rest[0]),
-                    Err(err) => kprintln!("route error: {:?}", err),
+            }
+            _ => kfail!("dhcpd [status|start <iface> <first> <last>|stop|leases|release <ipv4>]"),
+        }
+    }
+
+    fn start_dhcpd(&mut self, args: &str) {
+        const USAGE: &str =
+            "dhcpd start <iface> <first> <last> [lease <secs>] [router <ipv4>] [dns <ipv4>]";
+        if let Some((iface, _)) = &self.dhcpd {
+            kfail!("dhcpd: already running on {}; stop it first", iface);
+            return;
+        }
+        let mut parts = args.split_whitespace();
+        let (Some(name), Some(first), Some(last)) = (parts.next(), parts.next(), parts.next())
+        else {
+            kfail!("{}", USAGE);
+            return;
+        };
+        let Some(iface) = self.net.list().into_iter().find(|iface| iface.name == name) else {
+            kfail!("dhcpd: unknown interface: {}", name);
+            return;
+        };
+        let (Some(server), Some(prefix)) =
+            (iface.ipv4.as_deref().and_then(parse_ipv4), iface.prefix)
+        else {
+            kfail!("dhcpd: {} has no IPv4 address", name);
+            return;
+        };
+        let (Some(first), Some(last)) = (parse_ipv4(first), parse_ipv4(last)) else {
+            kfail!("{}", USAGE);
+            return;
+        };
+        let mut config = DhcpConfig::new(server, prefix, first, last);
+        while let Some(option) = parts.next() {
+            let value = parts.next();
+            match (option, value) {
+                ("lease", Some(value)) if value.parse::<u32>().is_ok() => {
+                    config.lease_secs = value.parse().unwrap_or_default();
+                }
+                ("router", Some(value)) if parse_ipv4(value).is_some() => {
+                    config.router = parse_ipv4(value);
+                }
+                ("dns", Some(value)) if parse_ipv4(value).is_some() => {
+                    config.dns.extend(parse_ipv4(value));
+                }
+                _ => {
+                    kfail!("{}", USAGE);
+                    return;
+                }
+            }
+        }
+        match DhcpServer::new(config) {
+            Ok(server) => {
+                self.dhcpd = Some((name.to_string(), server));
+                self.print_dhcpd_status(clock::now_ns() / 1_000_000_000);
+            }
+            Err(err) => kfail!("dhcpd error: {:?}", err),
+        }
+    }
+
+    fn print_dhcpd_status(&mut self, now: u64) {
+        let Some((iface, server)) = self.dhcpd.as_mut() else {
+            kprintln!("dhcpd: stopped");
+            return;
+        };
+        server.expire(now);
+        let config = server.config();
+        kprintln!(
+            "dhcpd: running on {} as {}/{}, pool {}-{}, lease {}s",
+            iface,
+            format_ipv4(config.server),
+            config.prefix,
+            format_ipv4(config.pool_first),
+            format_ipv4(config.pool_last),
+            config.lease_secs
+        );
+        kprintln!(
+            "leases: {} in use, {} free",
+            server.leases().len(),
+            server.free_count()
+        );
+    }
+
+    /// Answers DHCP requests waiting on the NIC while `dhcpd` runs on it.
+    #[cfg(feature = "x86_64")]
+    fn poll_dhcpd(&mut self) {
+        use user_dhcp_server::{Datagram, SERVER_PORT};
+        use user_net_service::device::NetDevice;
+        use user_net_service::firewall::{PacketInfo, Protocol};
+
+        let Some((iface, server)) = self.dhcpd.as_mut() else {
+            return;
+        };
+        if iface != crate::nic::NIC_IFACE {
+            return;
+        }
+        let Some(mut nic) = crate::nic::VirtioNet::probe() else {
+            return;
+        };
+        let Ok(frames) = self.net.receive(iface, &mut nic) else {
+            return;
+        };
+        let now = clock::now_ns() / 1_000_000_000;
+        let bound = |server: &DhcpServer| {
+            server
+                .leases()
+                .into_iter()
+                .filter(|lease| lease.state == LeaseState::Bound)
+                .collect::<Vec<_>>()
+        };
+        let before = bound(server);
+        for frame in frames {
+            let Some(request) = Datagram::decode(&frame) else {
+                continue;
+            };
+            let remote = format_ipv4(request.src_ip);
+            let packet = PacketInfo {
+                direction: Direction::In,
+                protocol: Protocol::Udp,
+                remote: &remote,
+                port: SERVER_PORT,
+            };
+            if request.dst_port != SERVER_PORT
+                || self.firewall.evaluate(&packet) == FirewallAction::Deny
+            {
+                continue;
+            }
+            if let Some(reply) = server.serve(&request, nic.mac(), now) {
+                if let Err(err) = self.net.transmit(iface, &mut nic, &reply.encode()) {
+                    klog!("dhcpd: reply not sent: {:?}", err);
                 }
             }
-            _ => kprintln!("route [add|del]"),
+        }
+        for lease in bound(server) {
+            if before.iter().any(|old| old.addr == lease.addr && old.mac == lease.mac) {
+                continue;
+            }
+            let client = lease.hostname.unwrap_or_else(|| lease.mac.format());
+            console::notify(
+                "dhcpd",
+                &format!("leased {} to {}", format_ipv4(lease.addr), client),
+            );
+        }
+    }
+
+    fn print_firewall(&self) {
+        let policy = |direction| match self.firewall.policy(direction) {
+            FirewallAction::Allow => "allow",
+            FirewallAction::Deny => "deny",
+        };
+        kprintln!(
+            "firewall: policy in={} out={}",
+            policy(Direction::In),
+            policy(Direction::Out)
+        );
+        if self.firewall.rules().is_empty() {
+            kprintln!("  <no rules>");
+        }
+        for (index, rule) in self.firewall.rules().iter().enumerate() {
+            kprintln!("  {:>3}  {}", index + 1, rule.format());
         }
     }
 
@@ -1073,9 +3149,126 @@ impl ShellState {
             kprintln!("routes:\n  <none>");
             return;
         }
-        kprintln!("routes:");
-        for route in routes {
-            kprintln!("  {} -> {}", route.destination, route.iface);
+        kprintln!("routes:");
+        for route in routes {
+            kprintln!("  {}", format_route(&route));
+        }
+    }
+
+    /// Resolves `name` via `/etc/hosts`, the answer cache, then DNS servers
+    /// from the `dns` setting or `/etc/resolv.conf`.
+    fn run_resolve(&mut self, name: &str) {
+        let hosts = match self.fs.read_file(HOSTS_FILE) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(_) => format!("127.0.0.1 localhost {}\n", self.settings.hostname()),
+        };
+        self.dns.load_hosts(&hosts);
+        let servers = if self.settings.dns_servers().is_empty() {
+            self.fs
+                .read_file(RESOLV_CONF)
+                .map(|bytes| parse_resolv_conf(&String::from_utf8_lossy(&bytes)))
+                .unwrap_or_default()
+        } else {
+            self.settings.dns_servers().to_vec()
+        };
+        self.dns
+            .set_servers(&servers.iter().map(String::as_str).collect::<Vec<_>>());
+        let now_secs = clock::now_ns() / 1_000_000_000;
+        // No NIC driver carries UDP yet, so every server query times out.
+        match self.dns.resolve(name, now_secs, |_, _| None) {
+            Ok(resolution) => {
+                let source = match resolution.source {
+                    ResolveSource::Hosts => HOSTS_FILE.to_string(),
+                    ResolveSource::Cache => "cache".to_string(),
+                    ResolveSource::Server(server) => server,
+                };
+                for addr in resolution.addrs {
+                    kprintln!("{} {} ({})", name, addr, source);
+                }
+            }
+            Err(DnsError::NoServers) => kfail!(
+                "resolve: {}: no DNS servers (set dns= or {})",
+                name,
+                RESOLV_CONF
+            ),
+            Err(DnsError::NoAnswer) => kfail!(
+                "resolve: {}: no answer from {}",
+                name,
+                self.dns.servers().join(", ")
+            ),
+            Err(err) => kfail!("resolve: {}: {:?}", name, err),
+        }
+    }
+
+    /// Sends ICMP echo requests to `addr`, answering loopback and local
+    /// interface addresses in place.
+    fn run_ping(&mut self, args: &str) {
+        let mut count = 4u32;
+        let mut target = None;
+        let mut parts = args.split_whitespace();
+        while let Some(part) = parts.next() {
+            match part {
+                "-c" => match parts.next().and_then(|value| value.parse().ok()) {
+                    Some(value @ 1..=100) => count = value,
+                    _ => {
+                        kfail!("ping: -c expects a count from 1 to 100");
+                        return;
+                    }
+                },
+                addr if target.is_none() => target = Some(addr),
+                _ => {
+                    kfail!("ping [-c <count>] <ipv4>");
+                    return;
+                }
+            }
+        }
+        let Some(target) = target else {
+            kfail!("ping [-c <count>] <ipv4>");
+            return;
+        };
+        if target.contains('/') || parse_ipv4_cidr(target).is_none() {
+            kfail!("ping: invalid address: {}", target);
+            return;
+        }
+        let local = target.starts_with("127.")
+            || self
+                .net
+                .list()
+                .iter()
+                .any(|iface| iface.up && iface.ipv4.as_deref() == Some(target));
+        let mut session = PingSession::new(clock::ticks() as u16 | 1);
+        kprintln!("PING {}: {} echo requests", target, count);
+        for _ in 0..count {
+            let request = session.next_request(clock::ticks());
+            // Only local addresses answer until a NIC driver carries packets.
+            let Some(reply) = echo_reply_for(&request).filter(|_| local) else {
+                continue;
+            };
+            if let Ok(Some(reply)) = session.handle_reply(&reply, clock::ticks()) {
+                kprintln!(
+                    "reply from {}: seq={} time={} ticks",
+                    target,
+                    reply.seq,
+                    reply.rtt_ticks
+                );
+            }
+        }
+        for seq in session.expire(clock::ticks(), 0) {
+            kprintln!("request timeout for seq={}", seq);
+        }
+        let stats = session.stats();
+        kprintln!("--- {} ping statistics ---", target);
+        kprintln!(
+            "{} sent, {} received, {}% loss",
+            stats.sent,
+            stats.received,
+            stats.loss_percent()
+        );
+        match (stats.min_ticks, stats.avg_ticks(), stats.max_ticks) {
+            (Some(min), Some(avg), Some(max)) => {
+                kprintln!("rtt min/avg/max = {}/{}/{} ticks", min, avg, max)
+            }
+            _ => kfail!("ping: no reply from {}", target),
         }
     }
 
@@ -1086,26 +3279,36 @@ impl ShellState {
         };
         let parts = args.split_whitespace().collect::<Vec<&str>>();
         if parts.len() < 2 || parts.len() > 3 {
-            kprintln!("mount <source> <target> [type]");
+            kfail!("mount <source> <target> [type]");
             return;
         }
         let source = parts[0];
         let target = match self.file_manager.resolve(parts[1]) {
             Ok(path) => path,
             Err(err) => {
-                kprintln!("mount error: {:?}", err);
+                kfail!("mount error: {:?}", err);
                 return;
             }
         };
         if self.fs.list_dir(&target).is_err() {
-            kprintln!("mount error: target not a directory");
+            kfail!("mount error: target not a directory");
             return;
         }
         if self.mounts.iter().any(|entry| entry.target == target) {
-            kprintln!("mount error: target already mounted");
+            kfail!("mount error: target already mounted");
             return;
         }
         let fstype = if parts.len() == 3 { parts[2] } else { "memfs" };
+        if fstype == "tmpfs" {
+            let cap = self.profile.tmpfs_bytes();
+            if let Err(err) = self
+                .fs
+                .mount_tmpfs(&target, cap, EvictionPolicy::OldestFirst)
+            {
+                kfail!("mount error: {:?}", err);
+                return;
+            }
+        }
         self.mounts.push(MountEntry {
             source: source.to_string(),
             target: target.clone(),
@@ -1120,55 +3323,67 @@ impl ShellState {
             return;
         }
         kprintln!("mounts:");
+        let tmpfs = self.fs.tmpfs_mounts();
         for entry in &self.mounts {
-            kprintln!(
-                "  {} {} ({})",
-                entry.source, entry.target, entry.fstype
-            );
+            match tmpfs.iter().find(|mount| mount.path == entry.target) {
+                Some(mount) => kprintln!(
+                    "  {} {} ({}, {}/{} bytes, evicts {})",
+                    entry.source,
+                    entry.target,
+                    entry.fstype,
+                    mount.used,
+                    mount.cap,
+                    mount.policy.name()
+                ),
+                None => kprintln!("  {} {} ({})", entry.source, entry.target, entry.fstype),
+            }
         }
     }
 
-    fn print_df(&self, path: Option<&str>) {
+    fn print_df(&self, path: Option<&str>, human: bool) {
         let target = path.unwrap_or(self.file_manager.pwd());
         let resolved = match self.file_manager.resolve(target) {
             Ok(path) => path,
             Err(err) => {
-                kprintln!("df error: {:?}", err);
+                kfail!("df error: {:?}", err);
                 return;
             }
         };
         match self.fs.stats_for(&resolved) {
             Ok(stats) => {
-                kprintln!("df {}", resolved);
-                kprintln!("  files: {}", stats.files);
-                kprintln!("  dirs: {}", stats.dirs);
-                kprintln!("  bytes: {}", stats.bytes);
+                let row = DfRow {
+                    path: resolved,
+                    files: stats.files,
+                    dirs: stats.dirs,
+                    bytes: stats.bytes,
+                };
+                kprint!("{}", format_df(&[row], human));
             }
-            Err(err) => kprintln!("df error: {:?}", err),
+            Err(err) => kfail!("df error: {:?}", err),
         }
     }
 
-    fn print_du(&self, path: &str) {
+    fn print_du(&self, path: &str, human: bool) {
         let resolved = match self.file_manager.resolve(path) {
             Ok(path) => path,
             Err(err) => {
-                kprintln!("du error: {:?}", err);
+                kfail!("du error: {:?}", err);
                 return;
             }
         };
         match self.fs.size_of(&resolved) {
-            Ok(bytes) => kprintln!("du {} -> {} bytes", resolved, bytes),
-            Err(err) => kprintln!("du error: {:?}", err),
+            Ok(bytes) => kprintln!("{}", format_du(&resolved, bytes, human)),
+            Err(err) => kfail!("du error: {:?}", err),
         }
     }
 
     fn market_scan(&mut self) {
         let Some(initramfs) = self.initramfs.as_deref() else {
-            kprintln!("market scan: no initramfs available");
+            kfail!("market scan: no initramfs available");
             return;
         };
         let Ok(entries) = parse_initramfs(initramfs) else {
-            kprintln!("market scan failed: unable to parse initramfs");
+            kfail!("market scan failed: unable to parse initramfs");
             return;
         };
         let mut catalog = Vec::new();
@@ -1190,13 +3405,20 @@ impl ShellState {
         kprintln!("market scan complete: {} entries", count);
     }
 
-    fn plug_slot(&mut self, slot: &str, module: &str, dry_run: bool, swap: bool) {
+    fn plug_slot(
+        &mut self,
+        slot: &str,
+        module: &str,
+        dry_run: bool,
+        swap: bool,
+        override_lock: bool,
+    ) {
         let Some(entry) = self.modules.iter().find(|entry| entry.name == module) else {
-            kprintln!("module not found: {}", module);
+            kfail!("module not found: {}", module);
             return;
         };
         let Some(manifest) = &entry.manifest else {
-            kprintln!("module has no manifest: {}", module);
+            kfail!("module has no manifest: {}", module);
             return;
         };
         match self.board.can_plug(slot, &manifest.slots) {
@@ -1207,21 +3429,21 @@ impl ShellState {
                 }
                 match self.board.plug(slot, module, &manifest.slots) {
                     Ok(()) => kprintln!("plugged {} -> {}", slot, module),
-                    Err(err) => kprintln!("plug failed: {:?}", err),
+                    Err(err) => kfail!("plug failed: {:?}", err),
                 }
             }
             Err(BoardError::SlotAlreadyFilled) => {
                 if !swap {
                     if dry_run {
-                        kprintln!("dry-run failed: slot already filled");
+                        kfail!("dry-run failed: slot already filled");
                     } else {
-                        kprintln!("plug failed: slot already filled");
+                        kfail!("plug failed: slot already filled");
                     }
                     return;
                 }
                 let Some(current) = self.board.provider_for(slot).map(|provider| provider.to_string())
                 else {
-                    kprintln!("swap failed: cannot resolve provider");
+                    kfail!("swap failed: cannot resolve provider");
                     return;
                 };
                 if current == module {
@@ -1232,58 +3454,222 @@ impl ShellState {
                     }
                     return;
                 }
+                let locked = self.board.slot(slot).is_some_and(|entry| entry.locked);
+                if locked && !override_lock {
+                    let prefix = if dry_run {
+                        "dry-run failed"
+                    } else {
+                        "swap failed"
+                    };
+                    kfail!("{}: {} is locked; use --override", prefix, slot);
+                    return;
+                }
                 if dry_run {
                     kprintln!("dry-run swap: {} -> {} (replace {})", slot, module, current);
                     return;
                 }
-                let Some(old_entry) = self.modules.iter().find(|entry| entry.name == current)
-                else {
-                    kprintln!("swap failed: provider not installed: {}", current);
-                    return;
-                };
-                let Some(old_manifest) = &old_entry.manifest else {
-                    kprintln!("swap failed: provider has no manifest: {}", current);
-                    return;
+                let new_slots = manifest.slots.clone();
+                self.with_lock_override(slot, override_lock, |state| {
+                    state.swap_provider(slot, module, &new_slots)
+                });
+            }
+            Err(BoardError::SlotBlocked(waiting)) => {
+                let prefix = if dry_run {
+                    "dry-run failed"
+                } else {
+                    "plug failed"
                 };
-                match self.board.unplug(slot) {
-                    Ok(_) => match self.board.plug(slot, module, &manifest.slots) {
-                    Ok(()) => kprintln!("swapped {} -> {} (was {})", slot, module, current),
-                    Err(err) => {
-                        let rollback = self
-                            .board
-                            .plug(slot, current.as_str(), &old_manifest.slots)
-                            .is_ok();
-                            if rollback {
-                                kprintln!("swap failed: {:?} (rolled back)", err);
-                            } else {
-                                kprintln!("swap failed: {:?} (rollback failed)", err);
-                            }
-                        }
-                    },
-                    Err(err) => kprintln!("swap failed: {:?}", err),
-                }
+                kfail!(
+                    "{}: {} needs {} filled first",
+                    prefix,
+                    slot,
+                    waiting.join(", ")
+                );
             }
             Err(err) => {
                 if dry_run {
-                    kprintln!("dry-run failed: {:?}", err);
+                    kfail!("dry-run failed: {:?}", err);
+                } else {
+                    kfail!("plug failed: {:?}", err);
+                }
+            }
+        }
+    }
+
+    /// Runs a staged swap: drain the old provider, start the new one,
+    /// re-point the slot, then stop the old provider.
+    fn swap_provider(&mut self, slot: &str, module: &str, module_slots: &[String]) {
+        if !self.profile.allows_module(module) {
+            kfail!(
+                "swap failed: module disabled by {} profile: {}",
+                self.profile.name(),
+                module
+            );
+            return;
+        }
+        let mut lifecycle = ModuleLifecycle {
+            modules: &mut self.modules,
+            sandbox: &mut self.sandbox,
+            fs: &self.fs,
+        };
+        match self.board.swap(slot, module, module_slots, &mut lifecycle) {
+            Ok(report) => {
+                self.board.mark_running(module, module_slots);
+                let old_slots = self
+                    .modules
+                    .iter()
+                    .find(|entry| entry.name == report.old)
+                    .and_then(|entry| entry.manifest.as_ref())
+                    .map(|manifest| manifest.slots.clone())
+                    .unwrap_or_default();
+                if report.old_stopped {
+                    detach_module_slots(&mut self.board, &report.old, &old_slots);
+                    kprintln!(
+                        "swapped {} -> {} (stopped {})",
+                        report.slot,
+                        module,
+                        report.old
+                    );
                 } else {
-                    kprintln!("plug failed: {:?}", err);
+                    kprintln!(
+                        "swapped {} -> {} ({} still draining)",
+                        report.slot,
+                        module,
+                        report.old
+                    );
+                }
+            }
+            Err(SwapError::Board(err)) => kfail!("swap failed: {:?}", err),
+            Err(SwapError::Drain(err)) => kfail!("swap failed: cannot drain provider: {}", err),
+            Err(SwapError::Start(err)) => {
+                kfail!("swap failed: {}; slot kept its provider", err)
+            }
+        }
+    }
+
+    /// Plans providers for empty required slots and plugs them unless
+    /// `dry_run` is set.
+    ///
+    /// Running modules are preferred over installed ones, and installed
+    /// ones over verified catalog entries, which get installed on apply.
+    fn autoplug(&mut self, dry_run: bool) {
+        let mut candidates = Vec::new();
+        for module in &self.modules {
+            if let Some(manifest) = &module.manifest {
+                candidates.push(PlanCandidate {
+                    module: module.name.clone(),
+                    slots: manifest.slots.clone(),
+                    priority: if module.running { 2 } else { 1 },
+                });
+            }
+        }
+        for entry in self.catalog.iter().filter(|entry| entry.verified) {
+            candidates.push(PlanCandidate {
+                module: entry.name.clone(),
+                slots: entry.manifest.slots.clone(),
+                priority: 0,
+            });
+        }
+        candidates.retain(|candidate| self.profile.allows_module(&candidate.module));
+        let plan = self.board.plan(&candidates);
+        let rows: Vec<PlanRow> = plan
+            .steps
+            .iter()
+            .map(|step| {
+                let source = match self.modules.iter().find(|entry| entry.name == step.module) {
+                    Some(entry) if entry.running => "running",
+                    Some(_) => "installed",
+                    None => "catalog",
+                };
+                PlanRow {
+                    slot: step.slot.clone(),
+                    module: step.module.clone(),
+                    source: source.to_string(),
+                }
+            })
+            .collect();
+        let ties: Vec<(String, Vec<String>)> = plan
+            .ties
+            .iter()
+            .map(|tie| (tie.slot.clone(), tie.modules.clone()))
+            .collect();
+        kprint!("{}", format_plug_plan(&rows, &plan.unfilled, &ties));
+        if !dry_run {
+            for step in &plan.steps {
+                if !self.modules.iter().any(|entry| entry.name == step.module) {
+                    self.install_module(&step.module);
+                }
+                if self
+                    .modules
+                    .iter()
+                    .any(|entry| entry.name == step.module && !entry.running)
+                {
+                    self.start_module(&step.module);
+                }
+                if !self
+                    .board
+                    .providers_for(&step.slot)
+                    .contains(&step.module.as_str())
+                {
+                    self.plug_slot(&step.slot, &step.module, false, false, false);
                 }
             }
         }
+        if !plan.unfilled.is_empty() {
+            kfail!(
+                "autoplug incomplete: no provider for {}",
+                plan.unfilled.join(", ")
+            );
+        }
+    }
+
+    /// Runs `action` with `slot` unlocked when an admin overrides its lock,
+    /// locking it again afterwards.
+    fn with_lock_override(
+        &mut self,
+        slot: &str,
+        override_lock: bool,
+        action: impl FnOnce(&mut Self),
+    ) {
+        if !override_lock {
+            action(self);
+            return;
+        }
+        let admin = self
+            .session
+            .active_user()
+            .and_then(|name| self.users.get_user(name))
+            .is_some_and(|user| user.is_admin);
+        if !admin {
+            kfail!("admin privilege required to override a locked slot");
+            return;
+        }
+        let was_locked = self.board.set_locked(slot, false).unwrap_or(false);
+        action(self);
+        if was_locked {
+            let _ = self.board.set_locked(slot, true);
+        }
     }
 
     fn unplug_slot(&mut self, slot: &str) {
         match self.board.unplug(slot) {
             Ok(Some(provider)) => kprintln!("unplugged {} from {}", slot, provider),
             Ok(None) => kprintln!("slot already empty: {}", slot),
-            Err(BoardError::SlotNotFound) => kprintln!("slot not found: {}", slot),
-            Err(BoardError::InvalidSlot) => kprintln!("invalid slot: {}", slot),
-            Err(err) => kprintln!("unplug failed: {:?}", err),
+            Err(BoardError::SlotNotFound) => kfail!("slot not found: {}", slot),
+            Err(BoardError::InvalidSlot) => kfail!("invalid slot: {}", slot),
+            Err(BoardError::SlotLocked) => {
+                kfail!("unplug failed: {} is locked; use --override", slot)
+            }
+            Err(BoardError::MultipleProviders) => kfail!(
+                "unplug failed: {} has several providers; remove one of: {}",
+                slot,
+                self.board.providers_for(slot).join(", ")
+            ),
+            Err(err) => kfail!("unplug failed: {:?}", err),
         }
     }
 
-    fn print_graph(&self) {
+    fn print_graph(&self, dot: bool) {
         let mut rows = Vec::new();
         for module in &self.modules {
             let Some(manifest) = &module.manifest else {
@@ -1298,6 +3684,8 @@ impl ShellState {
                 name: module.name.clone(),
                 state: state.to_string(),
                 depends: manifest.depends.clone(),
+                wants: manifest.wants.clone(),
+                required_by: self.dependents_of(&module.name),
             });
         }
         for entry in &self.catalog {
@@ -1306,13 +3694,43 @@ impl ShellState {
                 name: entry.name.clone(),
                 state: state.to_string(),
                 depends: entry.manifest.depends.clone(),
+                wants: entry.manifest.wants.clone(),
+                required_by: Vec::new(),
             });
         }
         rows.sort_by(|a, b| a.name.cmp(&b.name));
-        kprintln!("{}", format_graph(&rows));
+        if dot {
+            let slots = self
+                .board
+                .list()
+                .into_iter()
+                .map(|slot| (slot.name, slot.providers))
+                .collect::<Vec<(String, Vec<String>)>>();
+            kprint!("{}", format_graph_dot(&rows, &slots));
+        } else {
+            kprintln!("{}", format_graph(&rows));
+        }
+    }
+
+    /// Lists installed modules whose manifest `depends` on `name`, sorted by name.
+    fn dependents_of(&self, name: &str) -> Vec<String> {
+        let mut dependents: Vec<String> = self
+            .modules
+            .iter()
+            .filter(|module| {
+                module
+                    .manifest
+                    .as_ref()
+                    .is_some_and(|manifest| manifest.depends.iter().any(|dep| dep == name))
+            })
+            .map(|module| module.name.clone())
+            .collect();
+        dependents.sort();
+        dependents
     }
 
-    fn print_sysinfo(&self) {
+    fn print_sysinfo(&mut self) {
+        self.net_status.update(&mut self.net);
         let gpu_devices = self
             .board
             .provider_for("ruzzle.slot.gpu@1")
@@ -1322,16 +3740,252 @@ impl ShellState {
             cpu_total: smp::cpu_total(),
             cpu_online: smp::cpu_online(),
             gpu_devices,
+            memory_bytes: self.memory_bytes,
+            profile: self.profile,
+            net_links_up: self.net_status.links_up(),
+            net_interfaces: self.net_status.interfaces(),
         };
         let info = build_system_info(&self.settings, &self.session, &self.board, metrics);
         kprintln!("{}", format_system_info(&info));
     }
 
+    fn run_clip(&mut self, text: Option<&str>) {
+        match text {
+            Some(text) => {
+                self.clipboard.copy(text);
+                kprint!("{}", encode_osc52(text));
+                kprintln!("copied {} bytes", text.len());
+            }
+            None => match self.clipboard.paste() {
+                Ok(text) => {
+                    kprint!("{}", encode_osc52(text));
+                    kprintln!("{}", text);
+                }
+                Err(_) => kprintln!("clipboard: <empty>"),
+            },
+        }
+    }
+
+    fn time_command(&mut self, line: &str) {
+        let command = parse_command(line);
+        let start = clock::now_ns();
+        self.handle(command);
+        let elapsed = clock::now_ns().saturating_sub(start);
+        kprintln!("real {}", format_duration_ns(elapsed));
+    }
+
+    fn run_chain(&mut self, steps: &[ChainStep]) {
+        let outcomes = run_sequence(steps, |command| {
+            self.dispatch(command.clone()).status.is_success()
+        });
+        let status = if sequence_succeeded(&outcomes) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+        COMMAND_STATUS.store(status.0, Ordering::Relaxed);
+    }
+
+    fn watch_command(&mut self, interval_secs: u32, line: &str) {
+        let command = parse_command(line);
+        if matches!(command, Command::Watch { .. } | Command::Unknown(_)) {
+            kfail!("watch: cannot watch '{}'", line);
+            return;
+        }
+        let interval_ns = u64::from(interval_secs).saturating_mul(1_000_000_000);
+        loop {
+            console::clear_screen();
+            kprint!("{}", format_watch_header(interval_secs, line));
+            kprintln!();
+            self.handle(command.clone());
+            let start = clock::now_ns();
+            while clock::now_ns().saturating_sub(start) < interval_ns {
+                if console::has_input() {
+                    let _ = console::read_byte();
+                    return;
+                }
+                clock::wait_tick();
+            }
+        }
+    }
+
+    fn log_tail(&mut self, follow: bool, lines: Option<usize>) {
+        let mut cursor = console::log_cursor();
+        let tail = console::log_tail(Some(lines.unwrap_or(DEFAULT_LOG_TAIL_LINES)));
+        if tail.is_empty() && !follow {
+            kprintln!("{}", format_log_tail_empty());
+            return;
+        }
+        for line in tail {
+            kprintln!("{}", line);
+        }
+        if !follow {
+            return;
+        }
+        kprintln!("{}", format_log_follow_header());
+        loop {
+            if console::has_input() {
+                let _ = console::read_byte();
+                return;
+            }
+            let (lines, next) = console::log_since(cursor);
+            for line in lines {
+                kprintln!("{}", line);
+            }
+            cursor = next;
+            clock::wait_tick();
+        }
+    }
+
+    fn run_log_level(&mut self, args: Option<&str>) {
+        let Some(args) = args else {
+            let (default, targets) = console::log_levels();
+            kprintln!("log levels (default {}):", default.name());
+            for (target, level) in targets {
+                kprintln!("  {} {}", target, level.name());
+            }
+            return;
+        };
+        let mut words = args.split_whitespace();
+        let (Some(module), Some(name), None) = (words.next(), words.next(), words.next()) else {
+            kfail!("usage: log level <module> <trace|debug|info|warn|error>");
+            return;
+        };
+        let Some(level) = LogLevel::parse(name) else {
+            kfail!("usage: log level <module> <trace|debug|info|warn|error>");
+            return;
+        };
+        let mut settings = self.settings.clone();
+        if let Err(err) = settings.set_log_level(module, name) {
+            kfail!("log level error: {:?}", err);
+            return;
+        }
+        let config = settings.to_config_text();
+        if let Err(err) = self.fs.write_file("/etc/ruzzle.conf", config.as_bytes()) {
+            kfail!("log level error: {:?}", err);
+            return;
+        }
+        self.settings = settings;
+        console::set_log_level(module, level);
+        kprintln!("log level {} = {}", module, level.name());
+    }
+
+    fn run_set(&mut self, args: Option<&str>) {
+        match args.map(|args| args.split_once('=')) {
+            None => {
+                kprintln!("color={}", self.style.name());
+                kprintln!("bell={}", if self.bell { "on" } else { "off" });
+                kprintln!("timing={}", if self.timing { "on" } else { "off" });
+                kprintln!("prompt={}", self.settings.prompt());
+                if let Some(profile) = self.settings.net_profile() {
+                    kprintln!("net.profile={}", profile);
+                }
+            }
+            Some(Some(("color", value))) => match Style::parse(value) {
+                Some(style) => {
+                    self.style = style;
+                    kprintln!("color={}", style.name());
+                }
+                None => kfail!("usage: set color=on|off"),
+            },
+            Some(Some(("bell", value))) => match value {
+                "on" | "off" => {
+                    self.bell = value == "on";
+                    kprintln!("bell={}", value);
+                }
+                _ => kfail!("usage: set bell=on|off"),
+            },
+            Some(Some(("timing", value))) => match value {
+                "on" | "off" => {
+                    self.timing = value == "on";
+                    kprintln!("timing={}", value);
+                }
+                _ => kfail!("usage: set timing=on|off"),
+            },
+            Some(Some(("prompt", value))) => self.set_prompt(value.trim()),
+            Some(Some(("net.profile", value))) => self.set_boot_profile(value.trim()),
+            Some(_) => kfail!(
+                "usage: set color|bell|timing=on|off, set prompt=<template> or set net.profile=<name>"
+            ),
+        }
+    }
+
+    fn set_prompt(&mut self, template: &str) {
+        let mut settings = self.settings.clone();
+        if settings.set_prompt(template).is_err() {
+            kfail!("usage: set prompt=<template> ({{user}}, {{hostname}}, {{cwd}})");
+            return;
+        }
+        let config = settings.to_config_text();
+        if let Err(err) = self.fs.write_file("/etc/ruzzle.conf", config.as_bytes()) {
+            kfail!("set prompt error: {:?}", err);
+            return;
+        }
+        self.settings = settings;
+        kprintln!("prompt={}", self.settings.prompt());
+    }
+
+    fn set_boot_profile(&mut self, name: &str) {
+        let mut settings = self.settings.clone();
+        if settings.set_net_profile(name).is_err() {
+            kfail!("usage: set net.profile=<name>");
+            return;
+        }
+        let previous = self.net_profiles.boot_profile().map(str::to_string);
+        if let Err(err) = self.net_profiles.set_boot_profile(settings.net_profile()) {
+            kfail!("set net.profile error: {:?}", err);
+            return;
+        }
+        let config = settings.to_config_text();
+        if let Err(err) = self.fs.write_file("/etc/ruzzle.conf", config.as_bytes()) {
+            let _ = self.net_profiles.set_boot_profile(previous.as_deref());
+            kfail!("set net.profile error: {:?}", err);
+            return;
+        }
+        self.settings = settings;
+        kprintln!("net.profile={}", name);
+    }
+
+    fn run_metrics(&mut self, args: Option<&str>) {
+        match args {
+            None => {
+                self.server.export_metrics(&mut self.metrics);
+                kprintln!("{}", format_metrics(&self.metrics));
+            }
+            Some("on") => {
+                self.latency_enabled = true;
+                kprintln!("command latency recording enabled");
+            }
+            Some("off") => {
+                self.latency_enabled = false;
+                kprintln!("command latency recording disabled");
+            }
+            Some("reset") => {
+                self.metrics.reset();
+                self.server.reset_metrics();
+                kprintln!("metrics reset");
+            }
+            Some(other) => kfail!("usage: metrics [on|off|reset] (got {})", other),
+        }
+    }
+
+    fn record_latency(&mut self, line: &str, elapsed_ns: u64) {
+        if !self.latency_enabled {
+            return;
+        }
+        let Some(name) = line.split_whitespace().next() else {
+            return;
+        };
+        let mut key = String::from("shell.command.");
+        key.push_str(name);
+        self.metrics.record_latency(&key, elapsed_ns);
+    }
+
     fn require_login(&self) -> Option<&str> {
         if let Some(user) = self.session.active_user() {
             Some(user)
         } else {
-            kprintln!("login required");
+            kfail!("login required");
             None
         }
     }
@@ -1345,10 +3999,23 @@ fn command_requires_login(command: &Command) -> bool {
             | Command::Logout
             | Command::Setup
             | Command::Whoami
+            | Command::Echo(_)
+            | Command::Describe
             | Command::Unknown(_)
     )
 }
 
+/// Commands that read keys or lines from the console while they run.
+fn command_needs_console(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Setup
+            | Command::Edit(_)
+            | Command::Watch { .. }
+            | Command::LogTail { follow: true, .. }
+    )
+}
+
 fn normalize_slot_filter(slot: &str) -> Result<String, ()> {
     let trimmed = slot.trim();
     if trimmed.is_empty() {
@@ -1367,19 +4034,19 @@ fn normalize_slot_filter(slot: &str) -> Result<String, ()> {
 }
 
 fn default_mounts() -> Vec<MountEntry> {
-    vec![MountEntry {
+    let mut mounts = vec![MountEntry {
         source: "memfs".to_string(),
         target: "/".to_string(),
         fstype: "memfs".to_string(),
-    }]
-}
-
-fn join_path(base: &str, child: &str) -> String {
-    if base == "/" {
-        format!("/{}", child)
-    } else {
-        format!("{}/{}", base, child)
+    }];
+    for dir in TMPFS_DIRS {
+        mounts.push(MountEntry {
+            source: "tmpfs".to_string(),
+            target: dir.to_string(),
+            fstype: "tmpfs".to_string(),
+        });
     }
+    mounts
 }
 
 fn join_list(values: &[String]) -> String {
@@ -1396,177 +4063,58 @@ fn join_list(values: &[String]) -> String {
     out
 }
 
-fn remove_recursive(fs: &mut FileSystem, path: &str) -> Result<(), FsError> {
-    match fs.list_dir(path) {
-        Ok(entries) => {
-            for entry in entries {
-                let child = join_path(path, &entry);
-                remove_recursive(fs, &child)?;
-            }
-        }
-        Err(FsError::NotDir) => {
-            return fs.remove(path);
-        }
-        Err(err) => return Err(err),
-    }
-    fs.remove(path)
-}
-
-fn copy_recursive(
-    fs: &mut FileSystem,
-    src: &str,
-    dst: &str,
-    recursive: bool,
-) -> Result<(), FsError> {
-    match fs.read_file(src) {
-        Ok(data) => return fs.write_file(dst, &data),
-        Err(FsError::IsDir) => {}
-        Err(err) => return Err(err),
+fn format_route(route: &RouteEntry) -> String {
+    let mut out = format!("{} -> {}", route.destination, route.iface);
+    if let Some(gateway) = &route.gateway {
+        out.push_str(&format!(" via {}", gateway));
     }
-
-    if !recursive {
-        return Err(FsError::IsDir);
-    }
-
-    match fs.list_dir(dst) {
-        Ok(_) => {}
-        Err(FsError::NotFound) => {
-            fs.mkdir(dst)?;
-        }
-        Err(FsError::NotDir) => {
-            return Err(FsError::NotDir);
-        }
-        Err(err) => return Err(err),
-    }
-
-    let entries = fs.list_dir(src)?;
-    for entry in entries {
-        let src_child = join_path(src, &entry);
-        let dst_child = join_path(dst, &entry);
-        copy_recursive(fs, &src_child, &dst_child, recursive)?;
+    if route.metric > 0 {
+        out.push_str(&format!(" metric {}", route.metric));
     }
-    Ok(())
-}
-
-#[derive(Debug, Clone)]
-enum EditorCommand {
-    Append(String),
-    Insert { index: usize, text: String },
-    Replace { index: usize, text: String },
-    Delete(usize),
-    Print,
-    Save,
-    Quit,
-    SaveQuit,
-    Help,
-    Unknown,
-}
-
-fn parse_editor_command(input: &str) -> EditorCommand {
-    let trimmed = input.trim();
-    if trimmed.is_empty() {
-        return EditorCommand::Unknown;
-    }
-    match trimmed {
-        ":w" => return EditorCommand::Save,
-        ":q" => return EditorCommand::Quit,
-        ":wq" => return EditorCommand::SaveQuit,
-        ":p" | "p" => return EditorCommand::Print,
-        ":h" | ":help" | "help" => return EditorCommand::Help,
-        _ => {}
-    }
-
-    let mut parts = trimmed.split_whitespace();
-    let cmd = parts.next().unwrap_or("");
-    match cmd {
-        "a" => {
-            let text = parts.collect::<Vec<&str>>().join(" ");
-            if text.is_empty() {
-                EditorCommand::Unknown
-            } else {
-                EditorCommand::Append(text)
-            }
-        }
-        "i" => parse_editor_indexed(parts, EditorIndexedKind::Insert),
-        "r" => parse_editor_indexed(parts, EditorIndexedKind::Replace),
-        "d" => {
-            let Some(index) = parse_editor_index(parts.next()) else {
-                return EditorCommand::Unknown;
-            };
-            EditorCommand::Delete(index)
-        }
-        _ => EditorCommand::Unknown,
+    if route.on_link {
+        out.push_str(" (on-link)");
     }
+    out
 }
 
-enum EditorIndexedKind {
-    Insert,
-    Replace,
+fn join_syscalls(syscalls: &[Syscall]) -> String {
+    let names = syscalls
+        .iter()
+        .map(|syscall| syscall.name().to_string())
+        .collect::<Vec<String>>();
+    join_list(&names)
 }
 
-fn parse_editor_indexed<'a>(
-    mut parts: impl Iterator<Item = &'a str>,
-    kind: EditorIndexedKind,
-) -> EditorCommand {
-    let Some(index) = parse_editor_index(parts.next()) else {
-        return EditorCommand::Unknown;
+fn seed_rootfs(fs: &mut FileSystem, initramfs: Option<&[u8]>) {
+    let Some(Ok(entries)) = initramfs.map(parse_initramfs) else {
+        return;
     };
-    let text = parts.collect::<Vec<&str>>().join(" ");
-    if text.is_empty() {
-        return EditorCommand::Unknown;
-    }
-    match kind {
-        EditorIndexedKind::Insert => EditorCommand::Insert { index, text },
-        EditorIndexedKind::Replace => EditorCommand::Replace { index, text },
-    }
-}
-
-fn parse_editor_index(value: Option<&str>) -> Option<usize> {
-    let raw = value?.parse::<usize>().ok()?;
-    if raw == 0 {
-        return None;
-    }
-    Some(raw - 1)
-}
-
-fn print_editor_buffer(buffer: &TextBuffer) {
-    if buffer.line_count() == 0 {
-        kprintln!("<empty>");
+    let Some(entry) = entries.iter().find(|entry| entry.name == ROOTFS_ARCHIVE) else {
         return;
-    }
-    for (index, line) in buffer.lines().iter().enumerate() {
-        kprintln!("{:>3} {}", index + 1, line);
+    };
+    match fs.import_archive(&entry.data) {
+        Ok(stats) => kprintln!(
+            "rootfs: seeded {} files, {} dirs from {}",
+            stats.files,
+            stats.dirs,
+            ROOTFS_ARCHIVE
+        ),
+        Err(err) => kprintln!("rootfs: ignoring {}: {:?}", ROOTFS_ARCHIVE, err),
     }
 }
 
-fn save_editor_buffer(
-    file_manager: &mut FileManager,
-    fs: &mut FileSystem,
-    path: &str,
-    buffer: &TextBuffer,
-) -> bool {
-    match file_manager.write(fs, path, &buffer.to_text()) {
-        Ok(()) => true,
-        Err(err) => {
-            kprintln!("save error: {:?}", err);
-            false
+fn mount_tmpfs_dirs(fs: &mut FileSystem, cap: usize) {
+    for dir in TMPFS_DIRS {
+        let mounted = fs
+            .mkdir_p(dir)
+            .and_then(|_| fs.chmod(dir, 0o777))
+            .and_then(|()| fs.mount_tmpfs(dir, cap, EvictionPolicy::OldestFirst));
+        if let Err(err) = mounted {
+            kprintln!("tmpfs: {} not mounted: {:?}", dir, err);
         }
     }
 }
 
-fn print_editor_help() {
-    kprintln!("editor commands:");
-    kprintln!("  :w               save");
-    kprintln!("  :q               quit");
-    kprintln!("  :wq              save and quit");
-    kprintln!("  :p | p           print buffer");
-    kprintln!("  a <text>         append line");
-    kprintln!("  i <n> <text>     insert at line n");
-    kprintln!("  r <n> <text>     replace line n");
-    kprintln!("  d <n>            delete line n");
-    kprintln!("  :h | help        show help");
-}
-
 fn build_modules(initramfs: Option<&[u8]>) -> (Vec<ModuleEntry>, Vec<CatalogEntry>) {
     let mut modules = Vec::new();
     let mut catalog = Vec::new();
@@ -1601,12 +4149,11 @@ fn build_modules(initramfs: Option<&[u8]>) -> (Vec<ModuleEntry>, Vec<CatalogEntr
     }
 
     for manifest in manifests {
-        modules.push(ModuleEntry {
-            name: manifest.name.clone(),
-            manifest: Some(manifest),
-            running: false,
-            verified: true,
-        });
+        modules.push(ModuleEntry::new(
+            manifest.name.clone(),
+            Some(manifest),
+            true,
+        ));
     }
 
     for entry in &entries {
@@ -1619,12 +4166,7 @@ fn build_modules(initramfs: Option<&[u8]>) -> (Vec<ModuleEntry>, Vec<CatalogEntr
         if modules.iter().any(|module| module.name == entry.name) {
             continue;
         }
-        modules.push(ModuleEntry {
-            name: entry.name.clone(),
-            manifest: None,
-            running: false,
-            verified: true,
-        });
+        modules.push(ModuleEntry::new(entry.name.clone(), None, true));
     }
 
     mark_running(&mut modules, &["init", "console-service", "tui-shell"]);
@@ -1644,37 +4186,137 @@ fn build_puzzle_board(modules: &[ModuleEntry]) -> PuzzleBoard {
     board
 }
 
+fn build_sandbox(modules: &[ModuleEntry], log_len: usize) -> SandboxTable {
+    let mut sandbox = SandboxTable::with_log_len(log_len);
+    for module in modules {
+        if !module.running {
+            continue;
+        }
+        if let Some(manifest) = &module.manifest {
+            if let Ok(filter) = SyscallFilter::from_manifest(manifest) {
+                sandbox.install(&module.name, filter);
+            }
+        }
+    }
+    sandbox
+}
+
+fn build_shell_registry(modules: &[ModuleEntry]) -> ShellRegistry {
+    let mut shells = ShellRegistry::new();
+    shells.register_module(DEFAULT_SHELL_MODULE, &["ruzzle.slot.shell@1".to_string()]);
+    for module in modules {
+        if let Some(manifest) = &module.manifest {
+            shells.register_module(&module.name, &manifest.slots);
+        }
+    }
+    shells
+}
+
 fn default_slots() -> Vec<PuzzleSlot> {
     vec![
-        PuzzleSlot::new("ruzzle.slot.console@1", true),
-        PuzzleSlot::new("ruzzle.slot.shell@1", true),
-        PuzzleSlot::new("ruzzle.slot.fs@1", true),
-        PuzzleSlot::new("ruzzle.slot.user@1", true),
-        PuzzleSlot::new("ruzzle.slot.settings@1", true),
-        PuzzleSlot::new("ruzzle.slot.session@1", true),
-        PuzzleSlot::new("ruzzle.slot.setup@1", false),
-        PuzzleSlot::new("ruzzle.slot.net@1", false),
-        PuzzleSlot::new("ruzzle.slot.netmgr@1", false),
-        PuzzleSlot::new("ruzzle.slot.input@1", false),
-        PuzzleSlot::new("ruzzle.slot.device@1", false),
-        PuzzleSlot::new("ruzzle.slot.editor@1", false),
-        PuzzleSlot::new("ruzzle.slot.filemgr@1", false),
-        PuzzleSlot::new("ruzzle.slot.sysinfo@1", false),
-        PuzzleSlot::new("ruzzle.slot.toolchain@1", false),
-        PuzzleSlot::new("ruzzle.slot.container@1", false),
-        PuzzleSlot::new("ruzzle.slot.server@1", false),
-        PuzzleSlot::new("ruzzle.slot.gpu@1", false),
-        PuzzleSlot::new("ruzzle.slot.ml@1", false),
+        PuzzleSlot::new("ruzzle.slot.console@1", true)
+            .with_priority("console-service", 10)
+            .with_description("text console for kernel and shell output")
+            .with_category("core")
+            .with_tags(&["io"]),
+        PuzzleSlot::new("ruzzle.slot.shell@1", true)
+            .with_requires(&["ruzzle.slot.console@1"])
+            .with_priority("tui-shell", 10)
+            .with_description("interactive command shell")
+            .with_category("core")
+            .with_tags(&["ui"]),
+        PuzzleSlot::new("ruzzle.slot.fs@1", true)
+            .with_description("root filesystem service")
+            .with_category("core")
+            .with_tags(&["storage"]),
+        PuzzleSlot::new("ruzzle.slot.user@1", true)
+            .with_description("user accounts and passwords")
+            .with_category("core")
+            .with_tags(&["auth"]),
+        PuzzleSlot::new("ruzzle.slot.settings@1", true)
+            .with_description("system settings store")
+            .with_category("core")
+            .with_tags(&["config"]),
+        PuzzleSlot::new("ruzzle.slot.session@1", true)
+            .with_requires(&["ruzzle.slot.user@1"])
+            .with_description("login sessions")
+            .with_category("core")
+            .with_tags(&["auth"]),
+        PuzzleSlot::new("ruzzle.slot.setup@1", false)
+            .with_requires(&[
+                "ruzzle.slot.settings@1",
+                "ruzzle.slot.user@1",
+                "ruzzle.slot.fs@1",
+            ])
+            .with_description("first-boot setup wizard")
+            .with_category("system")
+            .with_tags(&["config"]),
+        PuzzleSlot::new("ruzzle.slot.net@1", false)
+            .with_description("network stack")
+            .with_category("network"),
+        PuzzleSlot::new("ruzzle.slot.netmgr@1", false)
+            .with_requires(&["ruzzle.slot.net@1"])
+            .with_description("network interface manager")
+            .with_category("network")
+            .with_tags(&["config"]),
+        PuzzleSlot::new("ruzzle.slot.input@1", false)
+            .with_description("keyboard and pointer input")
+            .with_category("device")
+            .with_tags(&["io"]),
+        PuzzleSlot::new("ruzzle.slot.device@1", false)
+            .with_description("device discovery and drivers")
+            .with_category("device"),
+        PuzzleSlot::new("ruzzle.slot.editor@1", false)
+            .with_requires(&["ruzzle.slot.fs@1"])
+            .with_description("text editor")
+            .with_category("app")
+            .with_tags(&["ui"]),
+        PuzzleSlot::new("ruzzle.slot.clipboard@1", false)
+            .with_description("shared clipboard")
+            .with_category("system")
+            .with_tags(&["ui"]),
+        PuzzleSlot::new("ruzzle.slot.filemgr@1", false)
+            .with_requires(&["ruzzle.slot.fs@1"])
+            .with_description("file manager")
+            .with_category("app")
+            .with_tags(&["storage"]),
+        PuzzleSlot::new("ruzzle.slot.sysinfo@1", false)
+            .with_requires(&["ruzzle.slot.settings@1", "ruzzle.slot.session@1"])
+            .with_description("system information reports")
+            .with_category("system"),
+        PuzzleSlot::new("ruzzle.slot.toolchain@1", false)
+            .with_description("Rust toolchain for building pieces")
+            .with_category("dev"),
+        PuzzleSlot::new("ruzzle.slot.container@1", false)
+            .with_description("container runtime")
+            .with_category("server"),
+        PuzzleSlot::new("ruzzle.slot.server@1", false)
+            .with_requires(&["ruzzle.slot.net@1"])
+            .with_description("HTTP server stack")
+            .with_category("server")
+            .with_tags(&["network"]),
+        PuzzleSlot::new("ruzzle.slot.gpu@1", false)
+            .with_description("GPU compute service")
+            .with_category("compute"),
+        PuzzleSlot::new("ruzzle.slot.ml@1", false)
+            .with_requires(&["ruzzle.slot.gpu@1"])
+            .with_description("machine learning runtime")
+            .with_category("compute"),
+        PuzzleSlot::new("ruzzle.slot.webconsole@1", false)
+            .with_requires(&["ruzzle.slot.server@1"])
+            .with_description("browser console over WebSocket")
+            .with_category("server")
+            .with_tags(&["ui"]),
+        PuzzleSlot::new("ruzzle.slot.dhcpd@1", false)
+            .with_requires(&["ruzzle.slot.net@1"])
+            .with_description("DHCP server for lab networks")
+            .with_category("network"),
     ]
 }
 
 fn detach_module_slots(board: &mut PuzzleBoard, module: &str, slots: &[String]) {
     for slot in slots {
-        if let Ok(Some(provider)) = board.unplug(slot) {
-            if provider != module {
-                let _ = board.plug(slot, &provider, &[slot.to_string()]);
-            }
-        }
+        let _ = board.unplug_module(slot, module);
     }
 }
 
@@ -1682,17 +4324,40 @@ fn is_piece_bundle(name: &str) -> bool {
     name.ends_with(".rpiece")
 }
 
-fn create_home_dirs(fs: &mut FileSystem, home: &str) -> Result<(), FsError> {
+/// Maps a filesystem error to its stable shell error code.
+fn fs_error_code(err: &FsError) -> ShellErrorCode {
+    match err {
+        FsError::NotFound => ShellErrorCode::NotFound,
+        FsError::NotDir => ShellErrorCode::NotADirectory,
+        FsError::IsDir => ShellErrorCode::IsADirectory,
+        FsError::AlreadyExists => ShellErrorCode::AlreadyExists,
+        FsError::NotEmpty => ShellErrorCode::NotEmpty,
+        FsError::PermissionDenied => ShellErrorCode::PermissionDenied,
+        FsError::NoSpace => ShellErrorCode::NoSpace,
+        FsError::Io | FsError::CorruptSnapshot => ShellErrorCode::Io,
+        FsError::InvalidPath
+        | FsError::InvalidUtf8
+        | FsError::NotSymlink
+        | FsError::SymlinkLoop
+        | FsError::InvalidMode
+        | FsError::BadHandle
+        | FsError::InvalidSeek => ShellErrorCode::InvalidArgument,
+    }
+}
+
+fn create_home_dirs(fs: &mut FileSystem, home: &str, owner: &str) -> Result<(), FsError> {
     match fs.mkdir(home) {
         Ok(()) | Err(FsError::AlreadyExists) => {}
         Err(err) => return Err(err),
     }
+    fs.chown(home, owner)?;
     for suffix in ["docs", "bin", ".config", "downloads"].iter() {
         let path = format!("{}/{}", home, suffix);
         match fs.mkdir(&path) {
             Ok(()) | Err(FsError::AlreadyExists) => {}
             Err(err) => return Err(err),
         }
+        fs.chown(&path, owner)?;
     }
     Ok(())
 }
@@ -1715,13 +4380,27 @@ fn mark_running(modules: &mut [ModuleEntry], names: &[&str]) {
 }
 
 fn read_line() -> String {
+    read_line_with(None)
+}
+
+fn read_line_with(mut clipboard: Option<&mut Clipboard>) -> String {
     let mut line = String::new();
+    let mut osc52 = Osc52Reader::new();
     loop {
         if !console::has_input() {
             core::hint::spin_loop();
             continue;
         }
-        let byte = console::read_byte();
+        let byte = match osc52.feed(console::read_byte()) {
+            Osc52Feed::Byte(byte) => byte,
+            Osc52Feed::Pending => continue,
+            Osc52Feed::Complete(result) => {
+                if let (Ok(text), Some(clipboard)) = (result, clipboard.as_deref_mut()) {
+                    clipboard.copy(&text);
+                }
+                continue;
+            }
+        };
         match byte {
             b'\r' | b'\n' => {
                 kprintln!();
@@ -1733,6 +4412,17 @@ fn read_line() -> String {
                     kprint!("\x08 \x08");
                 }
             }
+            0x16 => {
+                let Some(text) = clipboard.as_deref().and_then(|clip| clip.paste().ok()) else {
+                    continue;
+                };
+                for ch in text.chars().take_while(|ch| *ch != '\n') {
+                    if ch.is_ascii_graphic() || ch == ' ' {
+                        line.push(ch);
+                        kprint!("{}", ch);
+                    }
+                }
+            }
             _ => {
                 if byte.is_ascii_graphic() || byte == b' ' {
                     line.push(byte as char);
@@ -1755,6 +4445,11 @@ fn prompt_line(label: &str) -> Option<String> {
     }
 }
 
+fn confirm_on_console(prompt: &str) -> bool {
+    kprint!("{} [y/N]: ", prompt);
+    is_confirmed(&read_line())
+}
+
 fn prompt_with_default(label: &str, default: &str) -> String {
     kprint!("{} [{}]: ", label, default);
     let input = read_line();
@@ -13,6 +13,17 @@ pub struct BootInfo<'a> {
     pub hhdm_offset: Option<PhysAddr>,
 }
 
+impl BootInfo<'_> {
+    /// Returns the total size of usable memory regions in bytes.
+    pub fn usable_memory_bytes(&self) -> u64 {
+        self.memory_map
+            .iter()
+            .filter(|region| region.kind == MemoryKind::Usable)
+            .map(|region| region.end.saturating_sub(region.start))
+            .sum()
+    }
+}
+
 /// Describes a contiguous physical memory region.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MemoryRegion {
@@ -69,4 +80,36 @@ mod tests {
         assert_eq!(info.memory_map.len(), 1);
         assert_eq!(info.memory_map[0].kind, MemoryKind::Usable);
     }
+
+    #[test]
+    fn usable_memory_sums_usable_regions() {
+        let regions = [
+            MemoryRegion {
+                start: 0x1000,
+                end: 0x3000,
+                kind: MemoryKind::Usable,
+            },
+            MemoryRegion {
+                start: 0x3000,
+                end: 0x4000,
+                kind: MemoryKind::Reserved,
+            },
+            MemoryRegion {
+                start: 0x10000,
+                end: 0x11000,
+                kind: MemoryKind::Usable,
+            },
+        ];
+        let info = BootInfo {
+            memory_map: &regions,
+            kernel_start: 0x0,
+            kernel_end: 0x1000,
+            kernel_virtual_base: 0,
+            initramfs: None,
+            dtb_ptr: None,
+            framebuffer: None,
+            hhdm_offset: None,
+        };
+        assert_eq!(info.usable_memory_bytes(), 0x3000);
+    }
 }
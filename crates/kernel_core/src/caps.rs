@@ -19,6 +19,22 @@ impl Capability {
     const fn bit(self) -> u32 {
         1u32 << (self as u32)
     }
+
+    /// Parses a manifest capability name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ConsoleWrite" => Some(Capability::ConsoleWrite),
+            "EndpointCreate" => Some(Capability::EndpointCreate),
+            "ShmCreate" => Some(Capability::ShmCreate),
+            "ProcessSpawn" => Some(Capability::ProcessSpawn),
+            "Timer" => Some(Capability::Timer),
+            "FsRoot" => Some(Capability::FsRoot),
+            "WindowServer" => Some(Capability::WindowServer),
+            "InputDevice" => Some(Capability::InputDevice),
+            "GpuDevice" => Some(Capability::GpuDevice),
+            _ => None,
+        }
+    }
 }
 
 /// A compact bitset for process capabilities.
@@ -102,6 +118,35 @@ mod tests {
         assert!(caps.contains(Capability::GpuDevice));
     }
 
+    #[test]
+    fn capability_from_name_maps_manifest_names() {
+        assert_eq!(
+            Capability::from_name("ConsoleWrite"),
+            Some(Capability::ConsoleWrite)
+        );
+        assert_eq!(
+            Capability::from_name("EndpointCreate"),
+            Some(Capability::EndpointCreate)
+        );
+        assert_eq!(Capability::from_name("ShmCreate"), Some(Capability::ShmCreate));
+        assert_eq!(
+            Capability::from_name("ProcessSpawn"),
+            Some(Capability::ProcessSpawn)
+        );
+        assert_eq!(Capability::from_name("Timer"), Some(Capability::Timer));
+        assert_eq!(Capability::from_name("FsRoot"), Some(Capability::FsRoot));
+        assert_eq!(
+            Capability::from_name("WindowServer"),
+            Some(Capability::WindowServer)
+        );
+        assert_eq!(
+            Capability::from_name("InputDevice"),
+            Some(Capability::InputDevice)
+        );
+        assert_eq!(Capability::from_name("GpuDevice"), Some(Capability::GpuDevice));
+        assert_eq!(Capability::from_name("consolewrite"), None);
+    }
+
     #[test]
     fn capset_debug_includes_bits() {
         let caps = CapSet::empty();
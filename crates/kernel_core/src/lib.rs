@@ -14,6 +14,7 @@ pub mod pmm;
 pub mod process;
 pub mod protection;
 pub mod runtime;
+pub mod sandbox;
 pub mod scheduler;
 pub mod smp;
 pub mod syscall;
@@ -32,5 +33,9 @@ pub use pmm::{FrameAllocator, PhysFrame, FRAME_SIZE};
 pub use process::{AddressSpace, Context, KernelStack, ProcState, Process};
 pub use protection::{is_user_address, validate_user_buffer, KERNEL_VIRT_BASE};
 pub use runtime::{cap_transfer, endpoint_create, recv as ipc_recv, send as ipc_send};
+pub use sandbox::{
+    manifest_caps, SandboxTable, SandboxVerdict, SandboxViolation, SyscallFilter,
+    ViolationAction,
+};
 pub use scheduler::Scheduler;
-pub use syscall::{Syscall, SyscallResult};
+pub use syscall::{dispatch_sandboxed, Syscall, SyscallResult};
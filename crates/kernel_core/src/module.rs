@@ -1,9 +1,11 @@
 use alloc::format;
-use alloc::string::{String, ToString};
+use alloc::string::String;
 use alloc::vec::Vec;
 
 use hal::Errno;
 
+use crate::sandbox::ViolationAction;
+
 /// Manifest metadata describing a module ("puzzle piece").
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModuleManifest {
@@ -13,6 +15,22 @@ pub struct ModuleManifest {
     pub slots: Vec<String>,
     pub requires_caps: Vec<String>,
     pub depends: Vec<String>,
+    /// Optional dependencies started when present.
+    pub wants: Vec<String>,
+    pub requires_paths: Vec<String>,
+    /// Action taken when the module issues a filtered syscall.
+    pub on_violation: ViolationAction,
+}
+
+impl ModuleManifest {
+    /// Returns the required paths that `exists` reports as missing.
+    pub fn missing_paths(&self, exists: impl Fn(&str) -> bool) -> Vec<String> {
+        self.requires_paths
+            .iter()
+            .filter(|path| !exists(path))
+            .cloned()
+            .collect()
+    }
 }
 
 /// Parses a minimal `module.toml` manifest.
@@ -23,6 +41,9 @@ pub fn parse_module_manifest(input: &str) -> Result<ModuleManifest, Errno> {
     let mut slots: Option<Vec<String>> = None;
     let mut requires_caps: Option<Vec<String>> = None;
     let mut depends: Option<Vec<String>> = None;
+    let mut wants: Option<Vec<String>> = None;
+    let mut requires_paths: Option<Vec<String>> = None;
+    let mut on_violation: Option<ViolationAction> = None;
 
     for line in input.lines() {
         let trimmed = line.trim();
@@ -57,6 +78,23 @@ pub fn parse_module_manifest(input: &str) -> Result<ModuleManifest, Errno> {
                 ensure_unset(&depends)?;
                 depends = Some(parse_list(value)?);
             }
+            "wants" => {
+                ensure_unset(&wants)?;
+                wants = Some(parse_list(value)?);
+            }
+            "requires_paths" => {
+                ensure_unset(&requires_paths)?;
+                let paths = parse_list(value)?;
+                if paths.iter().any(|path| !path.starts_with('/')) {
+                    return Err(Errno::InvalidArg);
+                }
+                requires_paths = Some(paths);
+            }
+            "on_violation" => {
+                ensure_unset(&on_violation)?;
+                let action = parse_string(value)?;
+                on_violation = Some(ViolationAction::from_name(&action).ok_or(Errno::InvalidArg)?);
+            }
             _ => {
                 return Err(Errno::InvalidArg);
             }
@@ -78,6 +116,9 @@ pub fn parse_module_manifest(input: &str) -> Result<ModuleManifest, Errno> {
         slots: normalized_slots,
         requires_caps: requires_caps.unwrap_or_default(),
         depends: depends.unwrap_or_default(),
+        wants: wants.unwrap_or_default(),
+        requires_paths: requires_paths.unwrap_or_default(),
+        on_violation: on_violation.unwrap_or(ViolationAction::Deny),
     })
 }
 
@@ -106,7 +147,14 @@ fn parse_list(value: &str) -> Result<Vec<String>, Errno> {
         return Ok(Vec::new());
     }
     let mut items = Vec::new();
-    for raw in inner.split(',') {
+    let mut quoted = false;
+    let raw_items = inner.split(|ch| {
+        if ch == '"' {
+            quoted = !quoted;
+        }
+        ch == ',' && !quoted
+    });
+    for raw in raw_items {
         let item = raw.trim();
         if item.is_empty() {
             return Err(Errno::InvalidArg);
@@ -122,17 +170,38 @@ fn normalize_slot_version(slot: &str) -> Result<String, Errno> {
         return Err(Errno::InvalidArg);
     }
     if let Some((base, version)) = trimmed.rsplit_once('@') {
-        if base.is_empty()
-            || version.is_empty()
-            || !version.chars().all(|ch| ch.is_ascii_digit())
-        {
+        if base.is_empty() || !is_slot_version(version) {
             return Err(Errno::InvalidArg);
         }
-        return Ok(trimmed.to_string());
+        let version = version
+            .split(',')
+            .map(|part| part.split_whitespace().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Ok(format!("{}@{}", base, version));
     }
     Ok(format!("{}@1", trimmed))
 }
 
+/// Accepts `1`, `1.2`, or comparator ranges such as `>=1.2, <2`.
+fn is_slot_version(version: &str) -> bool {
+    let is_number = |part: &str| !part.is_empty() && part.chars().all(|ch| ch.is_ascii_digit());
+    let is_version = |text: &str| match text.split_once('.') {
+        Some((major, minor)) => is_number(major) && is_number(minor),
+        None => is_number(text),
+    };
+    if is_version(version.trim()) {
+        return true;
+    }
+    version.split(',').all(|part| {
+        let part: String = part.split_whitespace().collect();
+        ["<=", ">=", "<", ">", "="]
+            .iter()
+            .find_map(|op| part.strip_prefix(op))
+            .is_some_and(is_version)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,6 +229,104 @@ mod tests {
             vec!["ConsoleWrite", "EndpointCreate"]
         );
         assert!(manifest.depends.is_empty());
+        assert!(manifest.requires_paths.is_empty());
+        assert_eq!(manifest.on_violation, ViolationAction::Deny);
+    }
+
+    #[test]
+    fn parse_manifest_reads_optional_dependencies() {
+        let manifest = parse_module_manifest(
+            r#"
+            name = "file-manager"
+            version = "0.1.0"
+            depends = ["fs-service"]
+            wants = ["net-manager"]
+            "#,
+        )
+        .expect("manifest should parse");
+
+        assert_eq!(manifest.depends, vec!["fs-service"]);
+        assert_eq!(manifest.wants, vec!["net-manager"]);
+        assert!(
+            parse_module_manifest("name = \"a\"\nversion = \"1\"\nwants = []\nwants = []\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_manifest_reads_required_paths() {
+        let manifest = parse_module_manifest(
+            r#"
+            name = "net-manager"
+            version = "0.1.0"
+            requires_paths = ["/etc/net/profiles", "/var/run"]
+            "#,
+        )
+        .expect("manifest should parse");
+
+        assert_eq!(
+            manifest.requires_paths,
+            vec!["/etc/net/profiles", "/var/run"]
+        );
+        assert_eq!(
+            manifest.missing_paths(|path| path == "/var/run"),
+            vec!["/etc/net/profiles"]
+        );
+        assert!(manifest.missing_paths(|_| true).is_empty());
+    }
+
+    #[test]
+    fn parse_manifest_reads_violation_action() {
+        let manifest = parse_module_manifest(
+            r#"
+            name = "docker-service"
+            version = "0.1.0"
+            on_violation = "terminate"
+            "#,
+        )
+        .expect("manifest should parse");
+        assert_eq!(manifest.on_violation, ViolationAction::Terminate);
+
+        let result =
+            parse_module_manifest("name = \"a\"\nversion = \"1\"\non_violation = \"kill\"\n");
+        assert_eq!(result, Err(Errno::InvalidArg));
+    }
+
+    #[test]
+    fn parse_manifest_rejects_relative_required_path() {
+        let result = parse_module_manifest(
+            r#"
+            name = "net-manager"
+            version = "0.1.0"
+            requires_paths = ["etc/net/profiles"]
+            "#,
+        );
+        assert_eq!(result, Err(Errno::InvalidArg));
+    }
+
+    #[test]
+    fn parse_manifest_rejects_duplicate_required_paths() {
+        let result = parse_module_manifest(
+            r#"
+            name = "net-manager"
+            version = "0.1.0"
+            requires_paths = []
+            requires_paths = ["/etc"]
+            "#,
+        );
+        assert_eq!(result, Err(Errno::InvalidArg));
+    }
+
+    #[test]
+    fn parse_manifest_rejects_invalid_required_paths_list() {
+        let result = parse_module_manifest(
+            r#"
+            name = "net-manager"
+            version = "0.1.0"
+            requires_paths = [/etc]
+            "#,
+        );
+        assert_eq!(result, Err(Errno::InvalidArg));
     }
 
     #[test]
@@ -217,6 +384,30 @@ mod tests {
         assert_eq!(result, Err(Errno::InvalidArg));
     }
 
+    #[test]
+    fn parse_manifest_accepts_minor_versions_and_ranges() {
+        let manifest = parse_module_manifest(
+            r#"
+            name = "net-service"
+            version = "0.2.0"
+            slots = ["ruzzle.slot.net@1.3", "ruzzle.slot.netmgr@>= 1.2,<2"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.slots,
+            vec!["ruzzle.slot.net@1.3", "ruzzle.slot.netmgr@>=1.2, <2"]
+        );
+        for slot in [
+            "ruzzle.slot.net@1.",
+            "ruzzle.slot.net@~1",
+            "ruzzle.slot.net@1, <2",
+        ] {
+            let input = format!("name = \"net\"\nversion = \"0.1.0\"\nslots = [\"{slot}\"]");
+            assert_eq!(parse_module_manifest(&input), Err(Errno::InvalidArg));
+        }
+    }
+
     #[test]
     fn parse_manifest_rejects_empty_slot_string() {
         let result = parse_module_manifest(
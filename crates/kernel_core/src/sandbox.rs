@@ -0,0 +1,468 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use hal::Errno;
+
+use crate::caps::{CapSet, Capability};
+use crate::module::ModuleManifest;
+use crate::syscall::Syscall;
+
+/// Maximum number of violations retained in the sandbox log.
+pub const SANDBOX_LOG_LEN: usize = 64;
+
+/// Action taken when a module issues a filtered syscall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationAction {
+    Deny,
+    Terminate,
+}
+
+impl ViolationAction {
+    /// Parses the manifest `on_violation` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "deny" => Some(ViolationAction::Deny),
+            "terminate" => Some(ViolationAction::Terminate),
+            _ => None,
+        }
+    }
+
+    /// Returns the manifest name of the action.
+    pub const fn name(self) -> &'static str {
+        match self {
+            ViolationAction::Deny => "deny",
+            ViolationAction::Terminate => "terminate",
+        }
+    }
+}
+
+/// Outcome of a sandbox check for a single syscall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxVerdict {
+    Allow,
+    Deny,
+    Terminate,
+}
+
+/// Per-module syscall allowlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyscallFilter {
+    allowed: u32,
+    action: ViolationAction,
+}
+
+impl SyscallFilter {
+    /// Builds an allowlist from a capability set.
+    ///
+    /// Unprivileged syscalls are always allowed; privileged ones require
+    /// their gating capability.
+    pub fn from_caps(caps: CapSet, action: ViolationAction) -> Self {
+        let mut allowed = 0u32;
+        for syscall in Syscall::ALL {
+            let permitted = match syscall.required_cap() {
+                Some(cap) => caps.contains(cap),
+                None => true,
+            };
+            if permitted {
+                allowed |= syscall_bit(syscall);
+            }
+        }
+        Self { allowed, action }
+    }
+
+    /// Builds an allowlist from the capabilities requested in a manifest,
+    /// applying the manifest's `on_violation` action.
+    pub fn from_manifest(manifest: &ModuleManifest) -> Result<Self, Errno> {
+        Ok(Self::from_caps(
+            manifest_caps(manifest)?,
+            manifest.on_violation,
+        ))
+    }
+
+    /// Returns true when the syscall is on the allowlist.
+    pub const fn allows(&self, syscall: Syscall) -> bool {
+        (self.allowed & syscall_bit(syscall)) != 0
+    }
+
+    /// Returns the action applied on violations.
+    pub const fn action(&self) -> ViolationAction {
+        self.action
+    }
+
+    /// Lists allowed syscalls in dispatch order.
+    pub fn allowed(&self) -> Vec<Syscall> {
+        Syscall::ALL
+            .iter()
+            .copied()
+            .filter(|syscall| self.allows(*syscall))
+            .collect()
+    }
+
+    /// Lists filtered syscalls in dispatch order.
+    pub fn denied(&self) -> Vec<Syscall> {
+        Syscall::ALL
+            .iter()
+            .copied()
+            .filter(|syscall| !self.allows(*syscall))
+            .collect()
+    }
+}
+
+/// Recorded sandbox violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxViolation {
+    pub module: String,
+    pub syscall: Syscall,
+    pub action: ViolationAction,
+}
+
+/// Syscall filter profiles and violation log for running modules.
+#[derive(Debug, Clone)]
+pub struct SandboxTable {
+    profiles: BTreeMap<String, SyscallFilter>,
+    violations: Vec<SandboxViolation>,
+    terminated: Vec<String>,
+    log_len: usize,
+}
+
+impl Default for SandboxTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SandboxTable {
+    /// Creates an empty sandbox table.
+    pub fn new() -> Self {
+        Self::with_log_len(SANDBOX_LOG_LEN)
+    }
+
+    /// Creates an empty sandbox table keeping at most `log_len` violations.
+    pub fn with_log_len(log_len: usize) -> Self {
+        Self {
+            profiles: BTreeMap::new(),
+            violations: Vec::new(),
+            terminated: Vec::new(),
+            log_len: log_len.max(1),
+        }
+    }
+
+    /// Returns the violation log capacity.
+    pub fn log_len(&self) -> usize {
+        self.log_len
+    }
+
+    /// Installs or replaces the filter profile for a module.
+    pub fn install(&mut self, module: &str, filter: SyscallFilter) {
+        self.profiles.insert(module.to_string(), filter);
+    }
+
+    /// Removes the filter profile for a module.
+    pub fn remove(&mut self, module: &str) -> Option<SyscallFilter> {
+        self.profiles.remove(module)
+    }
+
+    /// Returns the filter profile for a module, if any.
+    pub fn profile(&self, module: &str) -> Option<&SyscallFilter> {
+        self.profiles.get(module)
+    }
+
+    /// Checks a syscall against the module profile, logging violations.
+    ///
+    /// Modules without a profile are not filtered.
+    pub fn check(&mut self, module: &str, syscall: Syscall) -> SandboxVerdict {
+        let Some(filter) = self.profiles.get(module) else {
+            return SandboxVerdict::Allow;
+        };
+        if filter.allows(syscall) {
+            return SandboxVerdict::Allow;
+        }
+        let action = filter.action();
+        if self.violations.len() >= self.log_len {
+            self.violations.remove(0);
+        }
+        self.violations.push(SandboxViolation {
+            module: module.to_string(),
+            syscall,
+            action,
+        });
+        match action {
+            ViolationAction::Deny => SandboxVerdict::Deny,
+            ViolationAction::Terminate => {
+                if !self.terminated.iter().any(|name| name == module) {
+                    self.terminated.push(module.to_string());
+                }
+                SandboxVerdict::Terminate
+            }
+        }
+    }
+
+    /// Returns all logged violations, oldest first.
+    pub fn violations(&self) -> &[SandboxViolation] {
+        &self.violations
+    }
+
+    /// Returns the logged violations for a module.
+    pub fn violations_for(&self, module: &str) -> Vec<&SandboxViolation> {
+        self.violations
+            .iter()
+            .filter(|violation| violation.module == module)
+            .collect()
+    }
+
+    /// Drains modules that must be terminated after a violation.
+    pub fn take_terminated(&mut self) -> Vec<String> {
+        core::mem::take(&mut self.terminated)
+    }
+
+    /// Stops every module marked for termination and drops its profile.
+    ///
+    /// `stop` returns false when the module could not be stopped; such
+    /// modules keep their profile. Returns the modules that were stopped.
+    pub fn reap_terminated(&mut self, mut stop: impl FnMut(&str) -> bool) -> Vec<String> {
+        let mut stopped = Vec::new();
+        for module in self.take_terminated() {
+            if stop(&module) {
+                self.profiles.remove(&module);
+                stopped.push(module);
+            }
+        }
+        stopped
+    }
+}
+
+/// Collects the capabilities a manifest requests.
+pub fn manifest_caps(manifest: &ModuleManifest) -> Result<CapSet, Errno> {
+    let mut caps = CapSet::empty();
+    for name in &manifest.requires_caps {
+        let cap = Capability::from_name(name).ok_or(Errno::InvalidArg)?;
+        caps.insert(cap);
+    }
+    Ok(caps)
+}
+
+const fn syscall_bit(syscall: Syscall) -> u32 {
+    1u32 << (syscall as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::parse_module_manifest;
+
+    fn manifest(caps: &str) -> ModuleManifest {
+        let text = alloc::format!(
+            "name = \"fs-service\"\nversion = \"0.1.0\"\nrequires_caps = {}\n",
+            caps
+        );
+        parse_module_manifest(&text).expect("manifest should parse")
+    }
+
+    fn terminating_manifest(caps: &str) -> ModuleManifest {
+        let text = alloc::format!(
+            "name = \"fs-service\"\nversion = \"0.1.0\"\nrequires_caps = {}\non_violation = \"terminate\"\n",
+            caps
+        );
+        parse_module_manifest(&text).expect("manifest should parse")
+    }
+
+    #[test]
+    fn filter_from_empty_caps_allows_only_unprivileged() {
+        let filter = SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Deny);
+        assert!(filter.allows(Syscall::Yield));
+        assert!(filter.allows(Syscall::Send));
+        assert!(!filter.allows(Syscall::Spawn));
+        assert!(!filter.allows(Syscall::DebugLog));
+        assert_eq!(
+            filter.denied(),
+            vec![
+                Syscall::Spawn,
+                Syscall::Sleep,
+                Syscall::ShmCreate,
+                Syscall::EndpointCreate,
+                Syscall::DebugLog,
+                Syscall::TimeNowNs,
+            ]
+        );
+        assert_eq!(filter.action(), ViolationAction::Deny);
+    }
+
+    #[test]
+    fn filter_from_manifest_grants_requested_caps() {
+        let filter =
+            SyscallFilter::from_manifest(&terminating_manifest("[\"ConsoleWrite\", \"Timer\"]"))
+                .unwrap();
+        assert!(filter.allows(Syscall::DebugLog));
+        assert!(filter.allows(Syscall::Sleep));
+        assert!(filter.allows(Syscall::TimeNowNs));
+        assert!(!filter.allows(Syscall::Spawn));
+        assert_eq!(
+            filter.allowed().len() + filter.denied().len(),
+            Syscall::ALL.len()
+        );
+        assert_eq!(filter.action(), ViolationAction::Terminate);
+    }
+
+    #[test]
+    fn filter_from_manifest_rejects_unknown_caps() {
+        let result = SyscallFilter::from_manifest(&manifest("[\"Root\"]"));
+        assert_eq!(result, Err(Errno::InvalidArg));
+        assert_eq!(
+            SyscallFilter::from_manifest(&manifest("[]")).map(|filter| filter.action()),
+            Ok(ViolationAction::Deny)
+        );
+    }
+
+    #[test]
+    fn violation_action_names_round_trip() {
+        for action in [ViolationAction::Deny, ViolationAction::Terminate] {
+            assert_eq!(ViolationAction::from_name(action.name()), Some(action));
+        }
+        assert_eq!(ViolationAction::from_name("kill"), None);
+    }
+
+    #[test]
+    fn table_allows_unfiltered_modules() {
+        let mut table = SandboxTable::new();
+        assert_eq!(table.check("init", Syscall::Spawn), SandboxVerdict::Allow);
+        assert!(table.profile("init").is_none());
+    }
+
+    #[test]
+    fn table_logs_denied_syscalls() {
+        let mut table = SandboxTable::new();
+        table.install(
+            "fs-service",
+            SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Deny),
+        );
+        assert_eq!(
+            table.check("fs-service", Syscall::Yield),
+            SandboxVerdict::Allow
+        );
+        assert_eq!(
+            table.check("fs-service", Syscall::Spawn),
+            SandboxVerdict::Deny
+        );
+        assert_eq!(
+            table.violations_for("fs-service"),
+            vec![&SandboxViolation {
+                module: "fs-service".to_string(),
+                syscall: Syscall::Spawn,
+                action: ViolationAction::Deny,
+            }]
+        );
+        assert!(table.violations_for("init").is_empty());
+        assert!(table.take_terminated().is_empty());
+    }
+
+    #[test]
+    fn table_marks_terminated_modules_once() {
+        let mut table = SandboxTable::new();
+        table.install(
+            "net-service",
+            SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Terminate),
+        );
+        assert_eq!(
+            table.check("net-service", Syscall::Spawn),
+            SandboxVerdict::Terminate
+        );
+        assert_eq!(
+            table.check("net-service", Syscall::DebugLog),
+            SandboxVerdict::Terminate
+        );
+        assert_eq!(table.violations().len(), 2);
+        assert_eq!(table.take_terminated(), vec!["net-service".to_string()]);
+        assert!(table.take_terminated().is_empty());
+    }
+
+    #[test]
+    fn terminate_violation_stops_module() {
+        let mut running = vec!["fs-service".to_string(), "net-service".to_string()];
+        let mut table = SandboxTable::new();
+        table.install(
+            "fs-service",
+            SyscallFilter::from_manifest(&terminating_manifest("[]")).unwrap(),
+        );
+        table.install(
+            "net-service",
+            SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Deny),
+        );
+        assert_eq!(
+            table.check("net-service", Syscall::Spawn),
+            SandboxVerdict::Deny
+        );
+        assert_eq!(
+            table.check("fs-service", Syscall::Spawn),
+            SandboxVerdict::Terminate
+        );
+
+        let stopped = table.reap_terminated(|module| {
+            let before = running.len();
+            running.retain(|name| name != module);
+            running.len() != before
+        });
+        assert_eq!(stopped, vec!["fs-service".to_string()]);
+        assert_eq!(running, vec!["net-service".to_string()]);
+        assert!(table.profile("fs-service").is_none());
+        assert!(table.profile("net-service").is_some());
+        assert!(table.reap_terminated(|_| true).is_empty());
+    }
+
+    #[test]
+    fn reap_skips_modules_that_fail_to_stop() {
+        let mut table = SandboxTable::new();
+        table.install(
+            "init",
+            SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Terminate),
+        );
+        table.check("init", Syscall::DebugLog);
+        assert!(table.reap_terminated(|_| false).is_empty());
+        assert!(table.profile("init").is_some());
+    }
+
+    #[test]
+    fn table_bounds_violation_log() {
+        let mut table = SandboxTable::new();
+        table.install(
+            "fs-service",
+            SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Deny),
+        );
+        for _ in 0..SANDBOX_LOG_LEN {
+            table.check("fs-service", Syscall::Spawn);
+        }
+        table.check("fs-service", Syscall::DebugLog);
+        assert_eq!(table.violations().len(), SANDBOX_LOG_LEN);
+        assert_eq!(
+            table.violations().last().map(|violation| violation.syscall),
+            Some(Syscall::DebugLog)
+        );
+    }
+
+    #[test]
+    fn table_install_and_remove_profiles() {
+        let mut table = SandboxTable::new();
+        let filter = SyscallFilter::from_caps(CapSet::all(), ViolationAction::Deny);
+        table.install("gpu-service", filter);
+        assert_eq!(table.profile("gpu-service"), Some(&filter));
+        assert_eq!(table.remove("gpu-service"), Some(filter));
+        assert_eq!(table.remove("gpu-service"), None);
+    }
+
+    #[test]
+    fn table_respects_custom_log_len() {
+        let mut table = SandboxTable::with_log_len(2);
+        assert_eq!(table.log_len(), 2);
+        assert_eq!(SandboxTable::default().log_len(), SANDBOX_LOG_LEN);
+        assert_eq!(SandboxTable::with_log_len(0).log_len(), 1);
+        table.install(
+            "fs-service",
+            SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Deny),
+        );
+        table.check("fs-service", Syscall::Spawn);
+        table.check("fs-service", Syscall::Spawn);
+        table.check("fs-service", Syscall::DebugLog);
+        assert_eq!(table.violations().len(), 2);
+        assert_eq!(table.violations()[1].syscall, Syscall::DebugLog);
+    }
+}
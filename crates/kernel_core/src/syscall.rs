@@ -1,6 +1,7 @@
 use hal::Errno;
 
 use crate::caps::{CapSet, Capability};
+use crate::sandbox::{SandboxTable, SandboxVerdict};
 
 /// Supported syscalls for the v0.1 interface surface.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,7 +33,51 @@ pub enum SyscallResult {
 }
 
 impl Syscall {
-    fn required_cap(self) -> Option<Capability> {
+    /// Every syscall in dispatch order.
+    pub const ALL: [Syscall; 17] = [
+        Syscall::Spawn,
+        Syscall::Exit,
+        Syscall::Wait,
+        Syscall::Yield,
+        Syscall::Sleep,
+        Syscall::Mmap,
+        Syscall::Munmap,
+        Syscall::ShmCreate,
+        Syscall::ShmMap,
+        Syscall::ShmShare,
+        Syscall::EndpointCreate,
+        Syscall::EndpointConnect,
+        Syscall::Send,
+        Syscall::Recv,
+        Syscall::CapTransfer,
+        Syscall::DebugLog,
+        Syscall::TimeNowNs,
+    ];
+
+    /// Returns the stable syscall name used in diagnostics.
+    pub fn name(self) -> &'static str {
+        match self {
+            Syscall::Spawn => "spawn",
+            Syscall::Exit => "exit",
+            Syscall::Wait => "wait",
+            Syscall::Yield => "yield",
+            Syscall::Sleep => "sleep",
+            Syscall::Mmap => "mmap",
+            Syscall::Munmap => "munmap",
+            Syscall::ShmCreate => "shm_create",
+            Syscall::ShmMap => "shm_map",
+            Syscall::ShmShare => "shm_share",
+            Syscall::EndpointCreate => "endpoint_create",
+            Syscall::EndpointConnect => "endpoint_connect",
+            Syscall::Send => "send",
+            Syscall::Recv => "recv",
+            Syscall::CapTransfer => "cap_transfer",
+            Syscall::DebugLog => "debug_log",
+            Syscall::TimeNowNs => "time_now_ns",
+        }
+    }
+
+    pub(crate) fn required_cap(self) -> Option<Capability> {
         match self {
             Syscall::Spawn => Some(Capability::ProcessSpawn),
             Syscall::EndpointCreate => Some(Capability::EndpointCreate),
@@ -72,9 +117,27 @@ pub fn dispatch(
     }
 }
 
+/// Dispatches a syscall for a sandboxed module.
+///
+/// The module's syscall filter is consulted before capability validation.
+/// Filtered calls are recorded in the sandbox table and rejected.
+pub fn dispatch_sandboxed(
+    module: &str,
+    syscall: Syscall,
+    caps: CapSet,
+    transfer_cap: Option<Capability>,
+    sandbox: &mut SandboxTable,
+) -> Result<SyscallResult, Errno> {
+    match sandbox.check(module, syscall) {
+        SandboxVerdict::Allow => dispatch(syscall, caps, transfer_cap),
+        SandboxVerdict::Deny | SandboxVerdict::Terminate => Err(Errno::NoPerm),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sandbox::{SyscallFilter, ViolationAction};
 
     #[test]
     fn dispatch_rejects_missing_capabilities() {
@@ -156,4 +219,61 @@ mod tests {
         let result = dispatch(Syscall::Yield, caps, None);
         assert_eq!(result, Ok(SyscallResult::Unit));
     }
+
+    #[test]
+    fn syscall_names_are_unique() {
+        let mut names = Syscall::ALL
+            .iter()
+            .map(|call| call.name())
+            .collect::<Vec<&str>>();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), Syscall::ALL.len());
+        assert_eq!(Syscall::TimeNowNs.name(), "time_now_ns");
+    }
+
+    #[test]
+    fn dispatch_sandboxed_enforces_filter_before_caps() {
+        let mut caps = CapSet::empty();
+        caps.insert(Capability::ProcessSpawn);
+        let mut sandbox = SandboxTable::new();
+        sandbox.install(
+            "fs-service",
+            SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Deny),
+        );
+
+        let result = dispatch_sandboxed("fs-service", Syscall::Spawn, caps, None, &mut sandbox);
+        assert_eq!(result, Err(Errno::NoPerm));
+        assert_eq!(sandbox.violations_for("fs-service").len(), 1);
+
+        let result = dispatch_sandboxed("fs-service", Syscall::Yield, caps, None, &mut sandbox);
+        assert_eq!(result, Ok(SyscallResult::Unit));
+    }
+
+    #[test]
+    fn dispatch_sandboxed_terminates_on_violation() {
+        let mut sandbox = SandboxTable::new();
+        sandbox.install(
+            "net-service",
+            SyscallFilter::from_caps(CapSet::empty(), ViolationAction::Terminate),
+        );
+        let result = dispatch_sandboxed(
+            "net-service",
+            Syscall::DebugLog,
+            CapSet::all(),
+            None,
+            &mut sandbox,
+        );
+        assert_eq!(result, Err(Errno::NoPerm));
+        assert_eq!(sandbox.take_terminated(), vec!["net-service".to_string()]);
+    }
+
+    #[test]
+    fn dispatch_sandboxed_without_profile_uses_caps() {
+        let mut sandbox = SandboxTable::new();
+        let result =
+            dispatch_sandboxed("init", Syscall::Spawn, CapSet::empty(), None, &mut sandbox);
+        assert_eq!(result, Err(Errno::NoPerm));
+        assert!(sandbox.violations().is_empty());
+    }
 }
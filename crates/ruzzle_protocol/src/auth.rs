@@ -0,0 +1,142 @@
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::tlv::{write_tlv, TlvReader};
+use crate::ProtocolError;
+
+/// TLV type for the caller's credential; reserved in every request protocol.
+pub const TLV_CREDENTIAL: u16 = 240;
+/// Nested TLV type for the session user name.
+pub const TLV_CREDENTIAL_USER: u16 = 1;
+/// Nested TLV type for the admin flag (u8, `1` = admin).
+pub const TLV_CREDENTIAL_ADMIN: u16 = 2;
+
+/// Identity of the session a request is sent on behalf of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credential {
+    pub user: String,
+    pub admin: bool,
+}
+
+impl Credential {
+    /// Creates a credential for `user`.
+    pub fn new(user: &str, admin: bool) -> Self {
+        Self {
+            user: user.to_string(),
+            admin,
+        }
+    }
+}
+
+/// Appends a credential field to an encoded request.
+///
+/// Receivers that predate credentials skip the unknown TLV.
+pub fn append_credential(bytes: &mut Vec<u8>, credential: &Credential) {
+    let mut value = Vec::new();
+    write_tlv(&mut value, TLV_CREDENTIAL_USER, credential.user.as_bytes());
+    write_tlv(&mut value, TLV_CREDENTIAL_ADMIN, &[credential.admin as u8]);
+    write_tlv(bytes, TLV_CREDENTIAL, &value);
+}
+
+/// Returns the credential attached to an encoded request, if any.
+pub fn read_credential(bytes: &[u8]) -> Result<Option<Credential>, ProtocolError> {
+    let mut credential: Option<Credential> = None;
+    let mut reader = TlvReader::new(bytes);
+    while let Some(field) = reader.next()? {
+        if field.tlv_type != TLV_CREDENTIAL {
+            continue;
+        }
+        if credential.is_some() {
+            return Err(ProtocolError::DuplicateField("credential"));
+        }
+        credential = Some(decode_credential(field.value)?);
+    }
+    Ok(credential)
+}
+
+fn decode_credential(bytes: &[u8]) -> Result<Credential, ProtocolError> {
+    let mut user: Option<String> = None;
+    let mut admin: Option<bool> = None;
+    let mut reader = TlvReader::new(bytes);
+    while let Some(field) = reader.next()? {
+        match field.tlv_type {
+            TLV_CREDENTIAL_USER => {
+                if user.is_some() {
+                    return Err(ProtocolError::DuplicateField("user"));
+                }
+                let text =
+                    core::str::from_utf8(field.value).map_err(|_| ProtocolError::InvalidUtf8)?;
+                if text.is_empty() {
+                    return Err(ProtocolError::InvalidValue("user"));
+                }
+                user = Some(text.to_string());
+            }
+            TLV_CREDENTIAL_ADMIN => {
+                if admin.is_some() {
+                    return Err(ProtocolError::DuplicateField("admin"));
+                }
+                admin = match field.value {
+                    [0] => Some(false),
+                    [1] => Some(true),
+                    [_] => return Err(ProtocolError::InvalidValue("admin")),
+                    _ => return Err(ProtocolError::InvalidLength("admin")),
+                };
+            }
+            _ => {}
+        }
+    }
+    Ok(Credential {
+        user: user.ok_or(ProtocolError::MissingField("user"))?,
+        admin: admin.unwrap_or(false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::{decode_command, encode_command, ShellCommand};
+
+    #[test]
+    fn credential_rides_along_with_a_request() {
+        let command = ShellCommand::Stop("net-manager".to_string());
+        let mut bytes = encode_command(&command);
+        assert_eq!(read_credential(&bytes), Ok(None));
+
+        let credential = Credential::new("root", true);
+        append_credential(&mut bytes, &credential);
+        assert_eq!(read_credential(&bytes), Ok(Some(credential)));
+        assert_eq!(decode_command(&bytes), Ok(command));
+    }
+
+    #[test]
+    fn read_credential_rejects_invalid_fields() {
+        let mut bytes = Vec::new();
+        append_credential(&mut bytes, &Credential::new("guest", false));
+        append_credential(&mut bytes, &Credential::new("root", true));
+        assert_eq!(
+            read_credential(&bytes),
+            Err(ProtocolError::DuplicateField("credential"))
+        );
+
+        let mut value = Vec::new();
+        write_tlv(&mut value, TLV_CREDENTIAL_ADMIN, &[1]);
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_CREDENTIAL, &value);
+        assert_eq!(
+            read_credential(&bytes),
+            Err(ProtocolError::MissingField("user"))
+        );
+
+        let mut value = Vec::new();
+        write_tlv(&mut value, TLV_CREDENTIAL_USER, b"root");
+        write_tlv(&mut value, TLV_CREDENTIAL_ADMIN, &[2]);
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_CREDENTIAL, &value);
+        assert_eq!(
+            read_credential(&bytes),
+            Err(ProtocolError::InvalidValue("admin"))
+        );
+    }
+}
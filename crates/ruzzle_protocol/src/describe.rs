@@ -0,0 +1,281 @@
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::tlv::{write_tlv, TlvReader};
+use crate::ProtocolError;
+
+/// TLV type for message type.
+pub const TLV_MSG_TYPE: u16 = 1;
+/// TLV type for the protocol name.
+pub const TLV_PROTOCOL: u16 = 2;
+/// TLV type for the protocol version (u16 LE).
+pub const TLV_VERSION: u16 = 3;
+/// TLV type for one supported command (message type byte + name).
+pub const TLV_COMMAND: u16 = 4;
+
+/// Describe request; reserved in every service protocol.
+pub const MSG_DESCRIBE: u8 = 240;
+/// Describe response carrying the service description.
+pub const MSG_DESCRIBE_REPLY: u8 = 241;
+
+/// One command a service understands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandInfo {
+    pub msg_type: u8,
+    pub name: String,
+}
+
+/// Protocol name, version and supported commands of a service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceDescription {
+    pub protocol: String,
+    pub version: u16,
+    pub commands: Vec<CommandInfo>,
+}
+
+impl ServiceDescription {
+    /// Builds a description from a static `(msg_type, name)` table.
+    pub fn from_table(protocol: &str, version: u16, table: &[(u8, &str)]) -> Self {
+        Self {
+            protocol: protocol.to_string(),
+            version,
+            commands: table
+                .iter()
+                .map(|(msg_type, name)| CommandInfo {
+                    msg_type: *msg_type,
+                    name: name.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns true if the service understands the message type.
+    pub fn supports(&self, msg_type: u8) -> bool {
+        self.commands
+            .iter()
+            .any(|command| command.msg_type == msg_type)
+    }
+
+    /// Returns the required message types the service does not understand.
+    pub fn missing(&self, required: &[u8]) -> Vec<u8> {
+        required
+            .iter()
+            .copied()
+            .filter(|msg_type| !self.supports(*msg_type))
+            .collect()
+    }
+}
+
+/// Encodes a describe request.
+pub fn encode_describe_request() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_DESCRIBE]);
+    bytes
+}
+
+/// Returns true if the bytes are a describe request for any protocol.
+pub fn is_describe_request(bytes: &[u8]) -> bool {
+    let mut reader = TlvReader::new(bytes);
+    matches!(
+        reader.next(),
+        Ok(Some(field)) if field.tlv_type == TLV_MSG_TYPE && field.value == [MSG_DESCRIBE]
+    )
+}
+
+/// Encodes a service description into TLV bytes.
+pub fn encode_description(description: &ServiceDescription) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_DESCRIBE_REPLY]);
+    write_tlv(&mut bytes, TLV_PROTOCOL, description.protocol.as_bytes());
+    write_tlv(&mut bytes, TLV_VERSION, &description.version.to_le_bytes());
+    for command in &description.commands {
+        let mut value = Vec::with_capacity(command.name.len() + 1);
+        value.push(command.msg_type);
+        value.extend_from_slice(command.name.as_bytes());
+        write_tlv(&mut bytes, TLV_COMMAND, &value);
+    }
+    bytes
+}
+
+/// Decodes a service description from TLV bytes.
+pub fn decode_description(bytes: &[u8]) -> Result<ServiceDescription, ProtocolError> {
+    let mut msg_type: Option<u8> = None;
+    let mut protocol: Option<String> = None;
+    let mut version: Option<u16> = None;
+    let mut commands = Vec::new();
+
+    let mut reader = TlvReader::new(bytes);
+    while let Some(field) = reader.next()? {
+        match field.tlv_type {
+            TLV_MSG_TYPE => {
+                if msg_type.is_some() {
+                    return Err(ProtocolError::DuplicateField("msg_type"));
+                }
+                if field.value.len() != 1 {
+                    return Err(ProtocolError::InvalidLength("msg_type"));
+                }
+                msg_type = Some(field.value[0]);
+            }
+            TLV_PROTOCOL => {
+                if protocol.is_some() {
+                    return Err(ProtocolError::DuplicateField("protocol"));
+                }
+                protocol = Some(parse_string(field.value)?);
+            }
+            TLV_VERSION => {
+                if version.is_some() {
+                    return Err(ProtocolError::DuplicateField("version"));
+                }
+                let raw: [u8; 2] = field
+                    .value
+                    .try_into()
+                    .map_err(|_| ProtocolError::InvalidLength("version"))?;
+                version = Some(u16::from_le_bytes(raw));
+            }
+            TLV_COMMAND => {
+                let Some((msg_type, name)) = field.value.split_first() else {
+                    return Err(ProtocolError::InvalidLength("command"));
+                };
+                commands.push(CommandInfo {
+                    msg_type: *msg_type,
+                    name: parse_string(name)?,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    match msg_type.ok_or(ProtocolError::MissingField("msg_type"))? {
+        MSG_DESCRIBE_REPLY => Ok(ServiceDescription {
+            protocol: protocol.ok_or(ProtocolError::MissingField("protocol"))?,
+            version: version.ok_or(ProtocolError::MissingField("version"))?,
+            commands,
+        }),
+        other => Err(ProtocolError::UnknownMessageType(other)),
+    }
+}
+
+fn parse_string(value: &[u8]) -> Result<String, ProtocolError> {
+    let text = core::str::from_utf8(value).map_err(|_| ProtocolError::InvalidUtf8)?;
+    if text.is_empty() {
+        return Err(ProtocolError::InvalidValue("string"));
+    }
+    Ok(text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Fields = &'static [(u16, &'static [u8])];
+
+    fn sample() -> ServiceDescription {
+        ServiceDescription::from_table("shell", 3, &[(1, "ps"), (21, "cat")])
+    }
+
+    #[test]
+    fn describe_request_is_recognized() {
+        let bytes = encode_describe_request();
+        assert!(is_describe_request(&bytes));
+        let mut other = Vec::new();
+        write_tlv(&mut other, TLV_MSG_TYPE, &[1]);
+        assert!(!is_describe_request(&other));
+        let mut wrong_type = Vec::new();
+        write_tlv(&mut wrong_type, TLV_PROTOCOL, &[MSG_DESCRIBE]);
+        assert!(!is_describe_request(&wrong_type));
+        assert!(!is_describe_request(&[1]));
+        assert!(!is_describe_request(&[]));
+    }
+
+    #[test]
+    fn description_round_trips() {
+        let description = sample();
+        let bytes = encode_description(&description);
+        assert_eq!(decode_description(&bytes), Ok(description));
+        let empty = ServiceDescription::from_table("registry", 1, &[]);
+        assert_eq!(decode_description(&encode_description(&empty)), Ok(empty));
+    }
+
+    #[test]
+    fn description_reports_missing_commands() {
+        let description = sample();
+        assert!(description.supports(21));
+        assert!(!description.supports(58));
+        assert_eq!(description.missing(&[1, 58, 21, 57]), vec![58, 57]);
+    }
+
+    #[test]
+    fn decode_description_rejects_invalid_payloads() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_PROTOCOL, b"shell");
+        assert_eq!(
+            decode_description(&bytes),
+            Err(ProtocolError::MissingField("msg_type"))
+        );
+
+        let cases: [(Fields, ProtocolError); 9] = [
+            (
+                &[(TLV_MSG_TYPE, &[MSG_DESCRIBE_REPLY]), (TLV_MSG_TYPE, &[1])],
+                ProtocolError::DuplicateField("msg_type"),
+            ),
+            (
+                &[(TLV_MSG_TYPE, &[1, 2])],
+                ProtocolError::InvalidLength("msg_type"),
+            ),
+            (
+                &[(TLV_PROTOCOL, b"a"), (TLV_PROTOCOL, b"b")],
+                ProtocolError::DuplicateField("protocol"),
+            ),
+            (
+                &[(TLV_VERSION, &[1, 0]), (TLV_VERSION, &[2, 0])],
+                ProtocolError::DuplicateField("version"),
+            ),
+            (
+                &[(TLV_VERSION, &[1])],
+                ProtocolError::InvalidLength("version"),
+            ),
+            (
+                &[(TLV_COMMAND, &[])],
+                ProtocolError::InvalidLength("command"),
+            ),
+            (
+                &[(TLV_COMMAND, &[1])],
+                ProtocolError::InvalidValue("string"),
+            ),
+            (&[(TLV_PROTOCOL, &[0xFF])], ProtocolError::InvalidUtf8),
+            (
+                &[
+                    (TLV_MSG_TYPE, &[MSG_DESCRIBE_REPLY]),
+                    (TLV_VERSION, &[1, 0]),
+                ],
+                ProtocolError::MissingField("protocol"),
+            ),
+        ];
+        for (fields, err) in cases {
+            let mut bytes = Vec::new();
+            for (tlv_type, value) in fields {
+                write_tlv(&mut bytes, *tlv_type, value);
+            }
+            assert_eq!(decode_description(&bytes), Err(err));
+        }
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_DESCRIBE_REPLY]);
+        write_tlv(&mut bytes, TLV_PROTOCOL, b"shell");
+        write_tlv(&mut bytes, 99, b"ignored");
+        assert_eq!(
+            decode_description(&bytes),
+            Err(ProtocolError::MissingField("version"))
+        );
+        assert_eq!(
+            decode_description(&encode_describe_request()),
+            Err(ProtocolError::UnknownMessageType(MSG_DESCRIBE))
+        );
+        assert!(matches!(
+            decode_description(&[1, 0]),
+            Err(ProtocolError::Tlv(_))
+        ));
+    }
+}
@@ -0,0 +1,226 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+/// Bytes that open every frame ("RZ").
+pub const FRAME_MAGIC: [u8; 2] = [0x52, 0x5a];
+/// Magic plus the u16 LE payload length.
+pub const FRAME_HEADER_LEN: usize = 4;
+/// CRC-32 (u32 LE) over the length and payload.
+pub const FRAME_TRAILER_LEN: usize = 4;
+/// Largest payload a frame may carry.
+///
+/// Kept well below the u16 limit so a corrupted length field costs at most
+/// this many bytes of waiting before the decoder resyncs.
+pub const MAX_FRAME_PAYLOAD: usize = 8 * 1024;
+
+/// Errors returned while framing a payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// Payload is longer than `MAX_FRAME_PAYLOAD`.
+    TooLarge,
+}
+
+/// Computes the CRC-32 (IEEE 802.3, as used by zip and PNG) of `data`.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Wraps an encoded message in a length-prefixed, CRC-checked frame.
+pub fn encode_frame(payload: &[u8]) -> Result<Vec<u8>, FrameError> {
+    if payload.len() > MAX_FRAME_PAYLOAD {
+        return Err(FrameError::TooLarge);
+    }
+    let mut bytes = Vec::with_capacity(FRAME_HEADER_LEN + payload.len() + FRAME_TRAILER_LEN);
+    bytes.extend_from_slice(&FRAME_MAGIC);
+    bytes.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(payload);
+    let crc = crc32(&bytes[FRAME_MAGIC.len()..]);
+    bytes.extend_from_slice(&crc.to_le_bytes());
+    Ok(bytes)
+}
+
+/// Reassembles frames from a byte stream that may drop or flip bytes.
+///
+/// Garbage before a frame is skipped, and a frame that fails its length or
+/// CRC check only costs its first byte: the decoder rescans for the next
+/// magic, so one bad byte never wedges the stream.
+#[derive(Debug, Clone, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    dropped: usize,
+    corrupt: usize,
+}
+
+impl FrameDecoder {
+    /// Creates a decoder with an empty buffer.
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            dropped: 0,
+            corrupt: 0,
+        }
+    }
+
+    /// Appends bytes received from the transport.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next intact payload, or None until more bytes arrive.
+    pub fn next_frame(&mut self) -> Option<Vec<u8>> {
+        loop {
+            self.skip_to_magic();
+            if self.buffer.len() < FRAME_HEADER_LEN {
+                return None;
+            }
+            let len = u16::from_le_bytes([self.buffer[2], self.buffer[3]]) as usize;
+            if len > MAX_FRAME_PAYLOAD {
+                self.reject_frame();
+                continue;
+            }
+            let end = FRAME_HEADER_LEN + len;
+            if self.buffer.len() < end + FRAME_TRAILER_LEN {
+                return None;
+            }
+            let expected = u32::from_le_bytes([
+                self.buffer[end],
+                self.buffer[end + 1],
+                self.buffer[end + 2],
+                self.buffer[end + 3],
+            ]);
+            if crc32(&self.buffer[FRAME_MAGIC.len()..end]) != expected {
+                self.reject_frame();
+                continue;
+            }
+            let payload = self.buffer[FRAME_HEADER_LEN..end].to_vec();
+            self.buffer.drain(..end + FRAME_TRAILER_LEN);
+            return Some(payload);
+        }
+    }
+
+    /// Returns the number of bytes discarded while resyncing.
+    pub fn dropped_bytes(&self) -> usize {
+        self.dropped
+    }
+
+    /// Returns the number of frame candidates rejected by length or CRC.
+    pub fn corrupt_frames(&self) -> usize {
+        self.corrupt
+    }
+
+    /// Returns the number of bytes waiting for the rest of a frame.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Drops bytes before the next magic, keeping a trailing first magic byte.
+    fn skip_to_magic(&mut self) {
+        let start = self
+            .buffer
+            .windows(FRAME_MAGIC.len())
+            .position(|window| window == FRAME_MAGIC)
+            .unwrap_or_else(|| {
+                let keep = self.buffer.last() == Some(&FRAME_MAGIC[0]);
+                self.buffer.len() - usize::from(keep)
+            });
+        self.dropped += start;
+        self.buffer.drain(..start);
+    }
+
+    fn reject_frame(&mut self) {
+        self.corrupt += 1;
+        self.dropped += 1;
+        self.buffer.remove(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(decoder: &mut FrameDecoder) -> Vec<Vec<u8>> {
+        let mut out = Vec::new();
+        while let Some(payload) = decoder.next_frame() {
+            out.push(payload);
+        }
+        out
+    }
+
+    #[test]
+    fn crc32_matches_reference_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414f_a339
+        );
+    }
+
+    #[test]
+    fn frames_round_trip_across_split_reads() {
+        let mut stream = encode_frame(b"lsmod").unwrap();
+        stream.extend(encode_frame(b"").unwrap());
+        stream.extend(encode_frame(b"slots").unwrap());
+
+        let mut decoder = FrameDecoder::new();
+        let mut out = Vec::new();
+        for chunk in stream.chunks(3) {
+            decoder.push(chunk);
+            out.extend(frames(&mut decoder));
+        }
+        assert_eq!(out, vec![b"lsmod".to_vec(), Vec::new(), b"slots".to_vec()]);
+        assert_eq!(decoder.dropped_bytes(), 0);
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn decoder_resyncs_after_noise_and_corruption() {
+        let mut stream = vec![0x00, 0x52, 0xff];
+        let mut damaged = encode_frame(b"first").unwrap();
+        damaged[6] ^= 0x01;
+        stream.extend(damaged);
+        stream.extend(encode_frame(b"second").unwrap());
+
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&stream);
+        assert_eq!(frames(&mut decoder), vec![b"second".to_vec()]);
+        assert_eq!(decoder.corrupt_frames(), 1);
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn decoder_recovers_from_a_corrupted_length() {
+        let mut damaged = encode_frame(b"first").unwrap();
+        damaged[3] = 0x40;
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&damaged);
+        decoder.push(&encode_frame(b"second").unwrap());
+        assert_eq!(frames(&mut decoder), vec![b"second".to_vec()]);
+
+        let mut damaged = encode_frame(b"third").unwrap();
+        damaged[2] = 0x20;
+        decoder.push(&damaged);
+        assert_eq!(frames(&mut decoder), Vec::<Vec<u8>>::new());
+        decoder.push(&[0; 32]);
+        decoder.push(&encode_frame(b"fourth").unwrap());
+        assert_eq!(frames(&mut decoder), vec![b"fourth".to_vec()]);
+    }
+
+    #[test]
+    fn encode_frame_rejects_oversized_payloads() {
+        assert_eq!(
+            encode_frame(&vec![0; MAX_FRAME_PAYLOAD + 1]),
+            Err(FrameError::TooLarge)
+        );
+        assert!(encode_frame(&vec![0; MAX_FRAME_PAYLOAD]).is_ok());
+    }
+}
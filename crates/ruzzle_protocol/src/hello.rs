@@ -0,0 +1,349 @@
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::tlv::{write_tlv, TlvReader};
+use crate::ProtocolError;
+
+/// TLV type for message type.
+pub const TLV_MSG_TYPE: u16 = 1;
+/// TLV type for the protocol name.
+pub const TLV_PROTOCOL: u16 = 2;
+/// TLV type for the lowest supported version (u16 LE).
+pub const TLV_MIN_VERSION: u16 = 3;
+/// TLV type for the highest supported version (u16 LE).
+pub const TLV_MAX_VERSION: u16 = 4;
+/// TLV type for the agreed version (u16 LE).
+pub const TLV_VERSION: u16 = 5;
+
+/// Hello request; reserved in every service protocol.
+pub const MSG_HELLO: u8 = 242;
+/// Hello response carrying the agreed version.
+pub const MSG_HELLO_ACCEPT: u8 = 243;
+/// Hello response listing what the service speaks when nothing overlaps.
+pub const MSG_HELLO_REJECT: u8 = 244;
+
+/// Inclusive range of protocol versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl VersionRange {
+    /// Creates a range from `min` to `max` inclusive.
+    pub const fn new(min: u16, max: u16) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns true if `version` lies in the range.
+    pub fn contains(&self, version: u16) -> bool {
+        self.min <= version && version <= self.max
+    }
+
+    /// Returns the newest version both ranges support.
+    pub fn highest_common(&self, other: &VersionRange) -> Option<u16> {
+        let high = self.max.min(other.max);
+        (high >= self.min.max(other.min)).then_some(high)
+    }
+}
+
+/// Opening message naming the protocol and the versions a client speaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hello {
+    pub protocol: String,
+    pub versions: VersionRange,
+}
+
+/// Service answer to a hello.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HelloReply {
+    /// Both sides use `version` from now on.
+    Accepted { version: u16 },
+    /// No common version; the service's own protocol and range are returned.
+    Rejected {
+        protocol: String,
+        versions: VersionRange,
+    },
+}
+
+/// Picks the newest version shared by the client and the service.
+pub fn negotiate(hello: &Hello, protocol: &str, supported: VersionRange) -> HelloReply {
+    let common = if hello.protocol == protocol {
+        hello.versions.highest_common(&supported)
+    } else {
+        None
+    };
+    match common {
+        Some(version) => HelloReply::Accepted { version },
+        None => HelloReply::Rejected {
+            protocol: protocol.to_string(),
+            versions: supported,
+        },
+    }
+}
+
+/// Returns true if the bytes are a hello request for any protocol.
+pub fn is_hello_request(bytes: &[u8]) -> bool {
+    let mut reader = TlvReader::new(bytes);
+    matches!(
+        reader.next(),
+        Ok(Some(field)) if field.tlv_type == TLV_MSG_TYPE && field.value == [MSG_HELLO]
+    )
+}
+
+/// Encodes a hello request.
+pub fn encode_hello(hello: &Hello) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_HELLO]);
+    write_tlv(&mut bytes, TLV_PROTOCOL, hello.protocol.as_bytes());
+    write_versions(&mut bytes, hello.versions);
+    bytes
+}
+
+/// Decodes a hello request.
+pub fn decode_hello(bytes: &[u8]) -> Result<Hello, ProtocolError> {
+    let fields = read_fields(bytes)?;
+    match fields.msg_type {
+        MSG_HELLO => {
+            let versions = fields.versions()?;
+            Ok(Hello {
+                protocol: fields
+                    .protocol
+                    .ok_or(ProtocolError::MissingField("protocol"))?,
+                versions,
+            })
+        }
+        other => Err(ProtocolError::UnknownMessageType(other)),
+    }
+}
+
+/// Encodes a hello reply.
+pub fn encode_hello_reply(reply: &HelloReply) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    match reply {
+        HelloReply::Accepted { version } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_HELLO_ACCEPT]);
+            write_tlv(&mut bytes, TLV_VERSION, &version.to_le_bytes());
+        }
+        HelloReply::Rejected { protocol, versions } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_HELLO_REJECT]);
+            write_tlv(&mut bytes, TLV_PROTOCOL, protocol.as_bytes());
+            write_versions(&mut bytes, *versions);
+        }
+    }
+    bytes
+}
+
+/// Decodes a hello reply.
+pub fn decode_hello_reply(bytes: &[u8]) -> Result<HelloReply, ProtocolError> {
+    let fields = read_fields(bytes)?;
+    match fields.msg_type {
+        MSG_HELLO_ACCEPT => Ok(HelloReply::Accepted {
+            version: fields
+                .version
+                .ok_or(ProtocolError::MissingField("version"))?,
+        }),
+        MSG_HELLO_REJECT => {
+            let versions = fields.versions()?;
+            Ok(HelloReply::Rejected {
+                protocol: fields
+                    .protocol
+                    .ok_or(ProtocolError::MissingField("protocol"))?,
+                versions,
+            })
+        }
+        other => Err(ProtocolError::UnknownMessageType(other)),
+    }
+}
+
+fn write_versions(bytes: &mut Vec<u8>, versions: VersionRange) {
+    write_tlv(bytes, TLV_MIN_VERSION, &versions.min.to_le_bytes());
+    write_tlv(bytes, TLV_MAX_VERSION, &versions.max.to_le_bytes());
+}
+
+struct Fields {
+    msg_type: u8,
+    protocol: Option<String>,
+    min_version: Option<u16>,
+    max_version: Option<u16>,
+    version: Option<u16>,
+}
+
+impl Fields {
+    fn versions(&self) -> Result<VersionRange, ProtocolError> {
+        let min = self
+            .min_version
+            .ok_or(ProtocolError::MissingField("min_version"))?;
+        let max = self
+            .max_version
+            .ok_or(ProtocolError::MissingField("max_version"))?;
+        if min > max {
+            return Err(ProtocolError::InvalidValue("versions"));
+        }
+        Ok(VersionRange::new(min, max))
+    }
+}
+
+fn read_fields(bytes: &[u8]) -> Result<Fields, ProtocolError> {
+    let mut msg_type: Option<u8> = None;
+    let mut protocol: Option<String> = None;
+    let mut min_version: Option<u16> = None;
+    let mut max_version: Option<u16> = None;
+    let mut version: Option<u16> = None;
+
+    let mut reader = TlvReader::new(bytes);
+    while let Some(field) = reader.next()? {
+        match field.tlv_type {
+            TLV_MSG_TYPE => {
+                if msg_type.is_some() {
+                    return Err(ProtocolError::DuplicateField("msg_type"));
+                }
+                if field.value.len() != 1 {
+                    return Err(ProtocolError::InvalidLength("msg_type"));
+                }
+                msg_type = Some(field.value[0]);
+            }
+            TLV_PROTOCOL => {
+                if protocol.is_some() {
+                    return Err(ProtocolError::DuplicateField("protocol"));
+                }
+                let text =
+                    core::str::from_utf8(field.value).map_err(|_| ProtocolError::InvalidUtf8)?;
+                if text.is_empty() {
+                    return Err(ProtocolError::InvalidValue("protocol"));
+                }
+                protocol = Some(text.to_string());
+            }
+            TLV_MIN_VERSION => set_version(&mut min_version, field.value, "min_version")?,
+            TLV_MAX_VERSION => set_version(&mut max_version, field.value, "max_version")?,
+            TLV_VERSION => set_version(&mut version, field.value, "version")?,
+            _ => {}
+        }
+    }
+
+    Ok(Fields {
+        msg_type: msg_type.ok_or(ProtocolError::MissingField("msg_type"))?,
+        protocol,
+        min_version,
+        max_version,
+        version,
+    })
+}
+
+fn set_version(
+    slot: &mut Option<u16>,
+    value: &[u8],
+    name: &'static str,
+) -> Result<(), ProtocolError> {
+    if slot.is_some() {
+        return Err(ProtocolError::DuplicateField(name));
+    }
+    let raw: [u8; 2] = value
+        .try_into()
+        .map_err(|_| ProtocolError::InvalidLength(name))?;
+    *slot = Some(u16::from_le_bytes(raw));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello(protocol: &str, min: u16, max: u16) -> Hello {
+        Hello {
+            protocol: protocol.to_string(),
+            versions: VersionRange::new(min, max),
+        }
+    }
+
+    #[test]
+    fn negotiate_picks_newest_common_version() {
+        let supported = VersionRange::new(1, 3);
+        assert_eq!(
+            negotiate(&hello("shell", 2, 5), "shell", supported),
+            HelloReply::Accepted { version: 3 }
+        );
+        assert_eq!(
+            negotiate(&hello("shell", 1, 1), "shell", supported),
+            HelloReply::Accepted { version: 1 }
+        );
+        let rejected = HelloReply::Rejected {
+            protocol: "shell".to_string(),
+            versions: supported,
+        };
+        assert_eq!(
+            negotiate(&hello("shell", 4, 6), "shell", supported),
+            rejected
+        );
+        assert_eq!(
+            negotiate(&hello("registry", 1, 3), "shell", supported),
+            rejected
+        );
+        assert!(supported.contains(2));
+        assert!(!supported.contains(4));
+    }
+
+    #[test]
+    fn hello_and_replies_round_trip() {
+        let request = hello("shell", 1, 2);
+        let bytes = encode_hello(&request);
+        assert!(is_hello_request(&bytes));
+        assert_eq!(decode_hello(&bytes), Ok(request));
+
+        for reply in [
+            HelloReply::Accepted { version: 2 },
+            HelloReply::Rejected {
+                protocol: "shell".to_string(),
+                versions: VersionRange::new(1, 1),
+            },
+        ] {
+            let bytes = encode_hello_reply(&reply);
+            assert!(!is_hello_request(&bytes));
+            assert_eq!(decode_hello_reply(&bytes), Ok(reply));
+        }
+    }
+
+    #[test]
+    fn decode_hello_rejects_invalid_payloads() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_HELLO]);
+        write_tlv(&mut bytes, TLV_PROTOCOL, b"shell");
+        write_tlv(&mut bytes, TLV_MIN_VERSION, &3u16.to_le_bytes());
+        write_tlv(&mut bytes, TLV_MAX_VERSION, &1u16.to_le_bytes());
+        assert_eq!(
+            decode_hello(&bytes),
+            Err(ProtocolError::InvalidValue("versions"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_HELLO]);
+        write_tlv(&mut bytes, TLV_PROTOCOL, b"shell");
+        write_tlv(&mut bytes, TLV_MIN_VERSION, &[1]);
+        assert_eq!(
+            decode_hello(&bytes),
+            Err(ProtocolError::InvalidLength("min_version"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_HELLO]);
+        write_tlv(&mut bytes, TLV_MIN_VERSION, &1u16.to_le_bytes());
+        write_tlv(&mut bytes, TLV_MAX_VERSION, &1u16.to_le_bytes());
+        assert_eq!(
+            decode_hello(&bytes),
+            Err(ProtocolError::MissingField("protocol"))
+        );
+
+        let accept = encode_hello_reply(&HelloReply::Accepted { version: 1 });
+        assert_eq!(
+            decode_hello(&accept),
+            Err(ProtocolError::UnknownMessageType(MSG_HELLO_ACCEPT))
+        );
+        assert_eq!(
+            decode_hello_reply(&encode_hello(&hello("shell", 1, 1))),
+            Err(ProtocolError::UnknownMessageType(MSG_HELLO))
+        );
+        assert!(!is_hello_request(&[]));
+    }
+}
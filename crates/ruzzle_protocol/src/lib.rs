@@ -2,8 +2,12 @@
 
 extern crate alloc;
 
+pub mod auth;
 pub mod caps;
 pub mod console;
+pub mod describe;
+pub mod frame;
+pub mod hello;
 pub mod registry;
 pub mod shell;
 pub mod tlv;
@@ -25,6 +29,10 @@ pub enum ProtocolError {
     UnknownMessageType(u8),
     /// Field value is semantically invalid.
     InvalidValue(&'static str),
+    /// Field or message is longer than the protocol allows.
+    TooLong(&'static str),
+    /// A repeated field occurs more often than the protocol allows.
+    TooManyEntries(&'static str),
 }
 
 impl ProtocolError {
@@ -38,6 +46,8 @@ impl ProtocolError {
             ProtocolError::InvalidUtf8 => "invalid utf8",
             ProtocolError::UnknownMessageType(_) => "unknown message type",
             ProtocolError::InvalidValue(_) => "invalid value",
+            ProtocolError::TooLong(_) => "too long",
+            ProtocolError::TooManyEntries(_) => "too many entries",
         }
     }
 }
@@ -60,6 +70,8 @@ mod tests {
         assert_eq!(ProtocolError::DuplicateField("x").as_str(), "duplicate field");
         assert_eq!(ProtocolError::UnknownMessageType(1).as_str(), "unknown message type");
         assert_eq!(ProtocolError::InvalidValue("x").as_str(), "invalid value");
+        assert_eq!(ProtocolError::TooLong("x").as_str(), "too long");
+        assert_eq!(ProtocolError::TooManyEntries("x").as_str(), "too many entries");
         assert_eq!(ProtocolError::from(tlv::TlvError::TruncatedHeader).as_str(), "invalid tlv");
     }
 }
@@ -14,6 +14,27 @@ pub const TLV_STATUS: u16 = 2;
 pub const TLV_SERVICE: u16 = 3;
 /// TLV type for module name.
 pub const TLV_MODULE: u16 = 4;
+/// TLV type for a watched service name prefix.
+pub const TLV_PREFIX: u16 = 5;
+/// TLV type for a watch id (u32 LE).
+pub const TLV_WATCH: u16 = 6;
+/// TLV type for a service change (u8).
+pub const TLV_CHANGE: u16 = 7;
+/// TLV type for a service instance id (u32 LE).
+pub const TLV_INSTANCE: u16 = 8;
+/// TLV type for an instance priority (u32 LE).
+pub const TLV_PRIORITY: u16 = 9;
+/// TLV type for a resolve policy (u8).
+pub const TLV_POLICY: u16 = 10;
+
+/// Longest accepted service name or watch prefix, in bytes.
+pub const MAX_SERVICE_NAME_LEN: usize = 128;
+/// Longest accepted module name, in bytes.
+pub const MAX_MODULE_NAME_LEN: usize = 64;
+/// Most service entries accepted in one list reply.
+pub const MAX_LIST_ENTRIES: usize = 512;
+/// Longest accepted request or response, in bytes.
+pub const MAX_MESSAGE_LEN: usize = 128 * 1024;
 
 /// Registry message: register service.
 pub const MSG_REGISTER: u8 = 1;
@@ -21,12 +42,30 @@ pub const MSG_REGISTER: u8 = 1;
 pub const MSG_LOOKUP: u8 = 2;
 /// Registry message: list services.
 pub const MSG_LIST: u8 = 3;
+/// Registry message: unregister one service.
+pub const MSG_UNREGISTER: u8 = 4;
+/// Registry message: unregister every service owned by a module.
+pub const MSG_UNREGISTER_MODULE: u8 = 5;
+/// Registry message: subscribe to services under a name prefix.
+pub const MSG_WATCH: u8 = 6;
+/// Registry message: cancel a subscription.
+pub const MSG_UNWATCH: u8 = 7;
+/// Registry message: add a module as one more instance of a service.
+pub const MSG_REGISTER_INSTANCE: u8 = 8;
+/// Registry message: choose how lookups pick among a service's instances.
+pub const MSG_SET_POLICY: u8 = 9;
 /// Registry response: ack.
 pub const MSG_ACK: u8 = 100;
 /// Registry response: lookup reply.
 pub const MSG_LOOKUP_REPLY: u8 = 101;
 /// Registry response: list reply.
 pub const MSG_LIST_REPLY: u8 = 102;
+/// Registry response: watch reply carrying the subscription id.
+pub const MSG_WATCH_REPLY: u8 = 103;
+/// Registry notification: a watched service appeared or disappeared.
+pub const MSG_NOTIFY: u8 = 104;
+/// Registry response: instance registered, carrying its id.
+pub const MSG_INSTANCE_REPLY: u8 = 105;
 /// Registry response: error.
 pub const MSG_ERROR: u8 = 255;
 
@@ -37,6 +76,8 @@ pub enum RegistryStatus {
     NotFound,
     Invalid,
     AlreadyExists,
+    /// The request needs an admin credential.
+    Denied,
 }
 
 impl RegistryStatus {
@@ -46,6 +87,7 @@ impl RegistryStatus {
             RegistryStatus::NotFound => 1,
             RegistryStatus::Invalid => 2,
             RegistryStatus::AlreadyExists => 3,
+            RegistryStatus::Denied => 4,
         }
     }
 
@@ -55,6 +97,7 @@ impl RegistryStatus {
             1 => Ok(RegistryStatus::NotFound),
             2 => Ok(RegistryStatus::Invalid),
             3 => Ok(RegistryStatus::AlreadyExists),
+            4 => Ok(RegistryStatus::Denied),
             other => Err(ProtocolError::InvalidValue(match other {
                 _ => "status",
             })),
@@ -68,6 +111,12 @@ pub enum RegistryRequest {
     Register { service: String, module: String },
     Lookup { service: String },
     List,
+    Unregister { service: String },
+    UnregisterModule { module: String },
+    Watch { prefix: String },
+    Unwatch { watch: u32 },
+    RegisterInstance { service: String, module: String, priority: u32 },
+    SetPolicy { service: String, policy: ResolvePolicy },
 }
 
 /// Registry response messages.
@@ -76,9 +125,85 @@ pub enum RegistryResponse {
     Ack,
     Lookup { status: RegistryStatus, module: Option<String> },
     List { status: RegistryStatus, entries: Vec<ServiceEntry> },
+    Watching { watch: u32 },
+    Notify(ServiceNotification),
+    Registered { instance: u32 },
     Error { status: RegistryStatus },
 }
 
+/// Whether a watched service was registered or withdrawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceChange {
+    Appeared,
+    Disappeared,
+}
+
+impl ServiceChange {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            ServiceChange::Appeared => 0,
+            ServiceChange::Disappeared => 1,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Result<Self, ProtocolError> {
+        match value {
+            0 => Ok(ServiceChange::Appeared),
+            1 => Ok(ServiceChange::Disappeared),
+            _ => Err(ProtocolError::InvalidValue("change")),
+        }
+    }
+}
+
+/// How a lookup picks among several instances of one service.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResolvePolicy {
+    /// The instance registered first.
+    #[default]
+    First,
+    /// Each lookup moves on to the next instance.
+    RoundRobin,
+    /// The instance with the highest priority; ties go to the older one.
+    Priority,
+}
+
+impl ResolvePolicy {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            ResolvePolicy::First => 0,
+            ResolvePolicy::RoundRobin => 1,
+            ResolvePolicy::Priority => 2,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Result<Self, ProtocolError> {
+        match value {
+            0 => Ok(ResolvePolicy::First),
+            1 => Ok(ResolvePolicy::RoundRobin),
+            2 => Ok(ResolvePolicy::Priority),
+            _ => Err(ProtocolError::InvalidValue("policy")),
+        }
+    }
+
+    /// Parses `first`, `round-robin` or `priority`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "first" => Some(ResolvePolicy::First),
+            "round-robin" => Some(ResolvePolicy::RoundRobin),
+            "priority" => Some(ResolvePolicy::Priority),
+            _ => None,
+        }
+    }
+}
+
+/// Change to a service matched by a watch subscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceNotification {
+    pub watch: u32,
+    pub change: ServiceChange,
+    pub entry: ServiceEntry,
+}
+
 /// Maps a service name to its owning module.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ServiceEntry {
@@ -102,15 +227,53 @@ pub fn encode_request(request: &RegistryRequest) -> Vec<u8> {
         RegistryRequest::List => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LIST]);
         }
+        RegistryRequest::Unregister { service } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_UNREGISTER]);
+            write_tlv(&mut bytes, TLV_SERVICE, service.as_bytes());
+        }
+        RegistryRequest::UnregisterModule { module } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_UNREGISTER_MODULE]);
+            write_tlv(&mut bytes, TLV_MODULE, module.as_bytes());
+        }
+        RegistryRequest::Watch { prefix } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WATCH]);
+            write_tlv(&mut bytes, TLV_PREFIX, prefix.as_bytes());
+        }
+        RegistryRequest::Unwatch { watch } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_UNWATCH]);
+            write_tlv(&mut bytes, TLV_WATCH, &watch.to_le_bytes());
+        }
+        RegistryRequest::RegisterInstance {
+            service,
+            module,
+            priority,
+        } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_REGISTER_INSTANCE]);
+            write_tlv(&mut bytes, TLV_SERVICE, service.as_bytes());
+            write_tlv(&mut bytes, TLV_MODULE, module.as_bytes());
+            write_tlv(&mut bytes, TLV_PRIORITY, &priority.to_le_bytes());
+        }
+        RegistryRequest::SetPolicy { service, policy } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_SET_POLICY]);
+            write_tlv(&mut bytes, TLV_SERVICE, service.as_bytes());
+            write_tlv(&mut bytes, TLV_POLICY, &[policy.as_u8()]);
+        }
     }
     bytes
 }
 
 /// Decodes a registry request from TLV bytes.
+///
+/// Oversized messages and names are rejected before anything is allocated.
 pub fn decode_request(bytes: &[u8]) -> Result<RegistryRequest, ProtocolError> {
+    check_message_len(bytes)?;
     let mut msg_type: Option<u8> = None;
     let mut service: Option<String> = None;
     let mut module: Option<String> = None;
+    let mut prefix: Option<String> = None;
+    let mut watch: Option<u32> = None;
+    let mut priority: Option<u32> = None;
+    let mut policy: Option<ResolvePolicy> = None;
 
     let mut reader = TlvReader::new(bytes);
     while let Some(field) = reader.next()? {
@@ -128,13 +291,43 @@ pub fn decode_request(bytes: &[u8]) -> Result<RegistryRequest, ProtocolError> {
                 if service.is_some() {
                     return Err(ProtocolError::DuplicateField("service"));
                 }
-                service = Some(parse_string(field.value)?);
+                service = Some(parse_service(field.value)?);
             }
             TLV_MODULE => {
                 if module.is_some() {
                     return Err(ProtocolError::DuplicateField("module"));
                 }
-                module = Some(parse_string(field.value)?);
+                module = Some(parse_module(field.value)?);
+            }
+            TLV_PREFIX => {
+                if prefix.is_some() {
+                    return Err(ProtocolError::DuplicateField("prefix"));
+                }
+                if field.value.len() > MAX_SERVICE_NAME_LEN {
+                    return Err(ProtocolError::TooLong("prefix"));
+                }
+                prefix = Some(parse_string(field.value)?);
+            }
+            TLV_WATCH => {
+                if watch.is_some() {
+                    return Err(ProtocolError::DuplicateField("watch"));
+                }
+                watch = Some(parse_watch(field.value)?);
+            }
+            TLV_PRIORITY => {
+                if priority.is_some() {
+                    return Err(ProtocolError::DuplicateField("priority"));
+                }
+                priority = Some(parse_u32(field.value, "priority")?);
+            }
+            TLV_POLICY => {
+                if policy.is_some() {
+                    return Err(ProtocolError::DuplicateField("policy"));
+                }
+                if field.value.len() != 1 {
+                    return Err(ProtocolError::InvalidLength("policy"));
+                }
+                policy = Some(ResolvePolicy::from_u8(field.value[0])?);
             }
             _ => {}
         }
@@ -155,6 +348,27 @@ pub fn decode_request(bytes: &[u8]) -> Result<RegistryRequest, ProtocolError> {
             }
             Ok(RegistryRequest::List)
         }
+        MSG_UNREGISTER => Ok(RegistryRequest::Unregister {
+            service: service.ok_or(ProtocolError::MissingField("service"))?,
+        }),
+        MSG_UNREGISTER_MODULE => Ok(RegistryRequest::UnregisterModule {
+            module: module.ok_or(ProtocolError::MissingField("module"))?,
+        }),
+        MSG_WATCH => Ok(RegistryRequest::Watch {
+            prefix: prefix.ok_or(ProtocolError::MissingField("prefix"))?,
+        }),
+        MSG_UNWATCH => Ok(RegistryRequest::Unwatch {
+            watch: watch.ok_or(ProtocolError::MissingField("watch"))?,
+        }),
+        MSG_REGISTER_INSTANCE => Ok(RegistryRequest::RegisterInstance {
+            service: service.ok_or(ProtocolError::MissingField("service"))?,
+            module: module.ok_or(ProtocolError::MissingField("module"))?,
+            priority: priority.unwrap_or(0),
+        }),
+        MSG_SET_POLICY => Ok(RegistryRequest::SetPolicy {
+            service: service.ok_or(ProtocolError::MissingField("service"))?,
+            policy: policy.ok_or(ProtocolError::MissingField("policy"))?,
+        }),
         other => Err(ProtocolError::UnknownMessageType(other)),
     }
 }
@@ -184,6 +398,25 @@ pub fn encode_response(response: &RegistryResponse) -> Vec<u8> {
                 }
             }
         }
+        RegistryResponse::Watching { watch } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WATCH_REPLY]);
+            write_tlv(&mut bytes, TLV_STATUS, &[RegistryStatus::Ok.as_u8()]);
+            write_tlv(&mut bytes, TLV_WATCH, &watch.to_le_bytes());
+        }
+        RegistryResponse::Notify(notification) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_NOTIFY]);
+            write_tlv(&mut bytes, TLV_STATUS, &[RegistryStatus::Ok.as_u8()]);
+            write_tlv(&mut bytes, TLV_WATCH, &notification.watch.to_le_bytes());
+            write_tlv(&mut bytes, TLV_CHANGE, &[notification.change.as_u8()]);
+            let entry = &notification.entry;
+            write_tlv(&mut bytes, TLV_SERVICE, entry.service.as_bytes());
+            write_tlv(&mut bytes, TLV_MODULE, entry.module.as_bytes());
+        }
+        RegistryResponse::Registered { instance } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_INSTANCE_REPLY]);
+            write_tlv(&mut bytes, TLV_STATUS, &[RegistryStatus::Ok.as_u8()]);
+            write_tlv(&mut bytes, TLV_INSTANCE, &instance.to_le_bytes());
+        }
         RegistryResponse::Error { status } => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_ERROR]);
             write_tlv(&mut bytes, TLV_STATUS, &[status.as_u8()]);
@@ -193,12 +426,19 @@ pub fn encode_response(response: &RegistryResponse) -> Vec<u8> {
 }
 
 /// Decodes a registry response from TLV bytes.
+///
+/// List replies are capped at `MAX_LIST_ENTRIES` so a misbehaving module
+/// cannot make the caller allocate without bound.
 pub fn decode_response(bytes: &[u8]) -> Result<RegistryResponse, ProtocolError> {
+    check_message_len(bytes)?;
     let mut msg_type: Option<u8> = None;
     let mut status: Option<RegistryStatus> = None;
     let mut module: Option<String> = None;
     let mut entries: Vec<ServiceEntry> = Vec::new();
     let mut pending_service: Option<String> = None;
+    let mut watch: Option<u32> = None;
+    let mut change: Option<ServiceChange> = None;
+    let mut instance: Option<u32> = None;
 
     let mut reader = TlvReader::new(bytes);
     while let Some(field) = reader.next()? {
@@ -222,9 +462,15 @@ pub fn decode_response(bytes: &[u8]) -> Result<RegistryResponse, ProtocolError>
                 status = Some(RegistryStatus::from_u8(field.value[0])?);
             }
             TLV_MODULE => {
-                let value = parse_string(field.value)?;
+                let value = parse_module(field.value)?;
                 if let Some(service) = pending_service.take() {
-                    if entries.iter().any(|entry| entry.service == service) {
+                    if entries.len() == MAX_LIST_ENTRIES {
+                        return Err(ProtocolError::TooManyEntries("entries"));
+                    }
+                    if entries
+                        .iter()
+                        .any(|entry| entry.service == service && entry.module == value)
+                    {
                         return Err(ProtocolError::DuplicateField("service"));
                     }
                     entries.push(ServiceEntry {
@@ -242,7 +488,28 @@ pub fn decode_response(bytes: &[u8]) -> Result<RegistryResponse, ProtocolError>
                 if pending_service.is_some() {
                     return Err(ProtocolError::MissingField("module"));
                 }
-                pending_service = Some(parse_string(field.value)?);
+                pending_service = Some(parse_service(field.value)?);
+            }
+            TLV_WATCH => {
+                if watch.is_some() {
+                    return Err(ProtocolError::DuplicateField("watch"));
+                }
+                watch = Some(parse_watch(field.value)?);
+            }
+            TLV_CHANGE => {
+                if change.is_some() {
+                    return Err(ProtocolError::DuplicateField("change"));
+                }
+                if field.value.len() != 1 {
+                    return Err(ProtocolError::InvalidLength("change"));
+                }
+                change = Some(ServiceChange::from_u8(field.value[0])?);
+            }
+            TLV_INSTANCE => {
+                if instance.is_some() {
+                    return Err(ProtocolError::DuplicateField("instance"));
+                }
+                instance = Some(parse_u32(field.value, "instance")?);
             }
             _ => {}
         }
@@ -277,6 +544,37 @@ pub fn decode_response(bytes: &[u8]) -> Result<RegistryResponse, ProtocolError>
             }
             Ok(RegistryResponse::List { status, entries })
         }
+        MSG_WATCH_REPLY => {
+            if status != RegistryStatus::Ok {
+                return Err(ProtocolError::InvalidValue("status"));
+            }
+            Ok(RegistryResponse::Watching {
+                watch: watch.ok_or(ProtocolError::MissingField("watch"))?,
+            })
+        }
+        MSG_NOTIFY => {
+            if status != RegistryStatus::Ok {
+                return Err(ProtocolError::InvalidValue("status"));
+            }
+            if entries.len() > 1 {
+                return Err(ProtocolError::InvalidValue("entries"));
+            }
+            Ok(RegistryResponse::Notify(ServiceNotification {
+                watch: watch.ok_or(ProtocolError::MissingField("watch"))?,
+                change: change.ok_or(ProtocolError::MissingField("change"))?,
+                entry: entries
+                    .pop()
+                    .ok_or(ProtocolError::MissingField("service"))?,
+            }))
+        }
+        MSG_INSTANCE_REPLY => {
+            if status != RegistryStatus::Ok {
+                return Err(ProtocolError::InvalidValue("status"));
+            }
+            Ok(RegistryResponse::Registered {
+                instance: instance.ok_or(ProtocolError::MissingField("instance"))?,
+            })
+        }
         MSG_ERROR => {
             if status == RegistryStatus::Ok {
                 return Err(ProtocolError::InvalidValue("status"));
@@ -287,6 +585,38 @@ pub fn decode_response(bytes: &[u8]) -> Result<RegistryResponse, ProtocolError>
     }
 }
 
+fn parse_watch(value: &[u8]) -> Result<u32, ProtocolError> {
+    parse_u32(value, "watch")
+}
+
+fn parse_u32(value: &[u8], name: &'static str) -> Result<u32, ProtocolError> {
+    let value: [u8; 4] = value
+        .try_into()
+        .map_err(|_| ProtocolError::InvalidLength(name))?;
+    Ok(u32::from_le_bytes(value))
+}
+
+fn check_message_len(bytes: &[u8]) -> Result<(), ProtocolError> {
+    if bytes.len() > MAX_MESSAGE_LEN {
+        return Err(ProtocolError::TooLong("message"));
+    }
+    Ok(())
+}
+
+fn parse_service(value: &[u8]) -> Result<String, ProtocolError> {
+    if value.len() > MAX_SERVICE_NAME_LEN {
+        return Err(ProtocolError::TooLong("service"));
+    }
+    parse_string(value)
+}
+
+fn parse_module(value: &[u8]) -> Result<String, ProtocolError> {
+    if value.len() > MAX_MODULE_NAME_LEN {
+        return Err(ProtocolError::TooLong("module"));
+    }
+    parse_string(value)
+}
+
 fn parse_string(value: &[u8]) -> Result<String, ProtocolError> {
     let text = core::str::from_utf8(value).map_err(|_| ProtocolError::InvalidUtf8)?;
     if text.is_empty() {
@@ -477,10 +807,74 @@ mod tests {
         assert_eq!(decoded, RegistryResponse::Ack);
     }
 
+    #[test]
+    fn decode_rejects_oversized_names_and_messages() {
+        let long_service = "ruzzle.".to_string() + &"a".repeat(MAX_SERVICE_NAME_LEN);
+        let request = RegistryRequest::Lookup {
+            service: long_service.clone(),
+        };
+        assert_eq!(
+            decode_request(&encode_request(&request)),
+            Err(ProtocolError::TooLong("service"))
+        );
+        let request = RegistryRequest::Watch {
+            prefix: long_service,
+        };
+        assert_eq!(
+            decode_request(&encode_request(&request)),
+            Err(ProtocolError::TooLong("prefix"))
+        );
+        let request = RegistryRequest::Register {
+            service: "ruzzle.fs".to_string(),
+            module: "m".repeat(MAX_MODULE_NAME_LEN + 1),
+        };
+        assert_eq!(
+            decode_request(&encode_request(&request)),
+            Err(ProtocolError::TooLong("module"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LIST]);
+        while bytes.len() <= MAX_MESSAGE_LEN {
+            write_tlv(&mut bytes, 0x7777, &[0; 1024]);
+        }
+        assert_eq!(
+            decode_request(&bytes),
+            Err(ProtocolError::TooLong("message"))
+        );
+        assert_eq!(
+            decode_response(&bytes),
+            Err(ProtocolError::TooLong("message"))
+        );
+    }
+
+    #[test]
+    fn decode_response_caps_list_entries() {
+        let entry = |index: usize| ServiceEntry {
+            service: "ruzzle.svc".to_string() + &index.to_string(),
+            module: "module".to_string(),
+        };
+        let full = RegistryResponse::List {
+            status: RegistryStatus::Ok,
+            entries: (0..MAX_LIST_ENTRIES).map(entry).collect(),
+        };
+        assert_eq!(decode_response(&encode_response(&full)), Ok(full));
+
+        let over = RegistryResponse::List {
+            status: RegistryStatus::Ok,
+            entries: (0..=MAX_LIST_ENTRIES).map(entry).collect(),
+        };
+        assert_eq!(
+            decode_response(&encode_response(&over)),
+            Err(ProtocolError::TooManyEntries("entries"))
+        );
+    }
+
     #[test]
     fn registry_status_roundtrip_values() {
         assert_eq!(RegistryStatus::AlreadyExists.as_u8(), 3);
         assert_eq!(RegistryStatus::from_u8(3).unwrap(), RegistryStatus::AlreadyExists);
+        assert_eq!(RegistryStatus::from_u8(4).unwrap(), RegistryStatus::Denied);
         assert_eq!(
             RegistryStatus::from_u8(9),
             Err(ProtocolError::InvalidValue("status"))
@@ -502,6 +896,180 @@ mod tests {
         assert_eq!(list_decoded, list);
     }
 
+    #[test]
+    fn encode_decode_unregister_requests() {
+        let unregister = RegistryRequest::Unregister {
+            service: "ruzzle.console".to_string(),
+        };
+        let bytes = encode_request(&unregister);
+        assert_eq!(decode_request(&bytes), Ok(unregister));
+
+        let unregister_module = RegistryRequest::UnregisterModule {
+            module: "console-service".to_string(),
+        };
+        let bytes = encode_request(&unregister_module);
+        assert_eq!(decode_request(&bytes), Ok(unregister_module));
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_UNREGISTER]);
+        write_tlv(&mut bytes, TLV_MODULE, b"console-service");
+        assert_eq!(
+            decode_request(&bytes),
+            Err(ProtocolError::MissingField("service"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_UNREGISTER_MODULE]);
+        write_tlv(&mut bytes, TLV_SERVICE, b"ruzzle.console");
+        assert_eq!(
+            decode_request(&bytes),
+            Err(ProtocolError::MissingField("module"))
+        );
+    }
+
+    #[test]
+    fn encode_decode_watch_requests() {
+        let watch = RegistryRequest::Watch {
+            prefix: "ruzzle.fs".to_string(),
+        };
+        let bytes = encode_request(&watch);
+        assert_eq!(decode_request(&bytes), Ok(watch));
+
+        let unwatch = RegistryRequest::Unwatch { watch: 7 };
+        let bytes = encode_request(&unwatch);
+        assert_eq!(decode_request(&bytes), Ok(unwatch));
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_UNWATCH]);
+        write_tlv(&mut bytes, TLV_WATCH, &[0x01]);
+        assert_eq!(
+            decode_request(&bytes),
+            Err(ProtocolError::InvalidLength("watch"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WATCH]);
+        assert_eq!(
+            decode_request(&bytes),
+            Err(ProtocolError::MissingField("prefix"))
+        );
+    }
+
+    #[test]
+    fn encode_decode_watch_and_notify_responses() {
+        let watching = RegistryResponse::Watching { watch: 3 };
+        let bytes = encode_response(&watching);
+        assert_eq!(decode_response(&bytes), Ok(watching));
+
+        let notify = RegistryResponse::Notify(ServiceNotification {
+            watch: 3,
+            change: ServiceChange::Disappeared,
+            entry: ServiceEntry {
+                service: "ruzzle.fs".to_string(),
+                module: "fs-service".to_string(),
+            },
+        });
+        let bytes = encode_response(&notify);
+        assert_eq!(decode_response(&bytes), Ok(notify));
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_NOTIFY]);
+        write_tlv(&mut bytes, TLV_STATUS, &[RegistryStatus::Ok.as_u8()]);
+        write_tlv(&mut bytes, TLV_WATCH, &3u32.to_le_bytes());
+        write_tlv(&mut bytes, TLV_CHANGE, &[9]);
+        assert_eq!(
+            decode_response(&bytes),
+            Err(ProtocolError::InvalidValue("change"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_NOTIFY]);
+        write_tlv(&mut bytes, TLV_STATUS, &[RegistryStatus::Ok.as_u8()]);
+        write_tlv(&mut bytes, TLV_WATCH, &3u32.to_le_bytes());
+        write_tlv(&mut bytes, TLV_CHANGE, &[ServiceChange::Appeared.as_u8()]);
+        assert_eq!(
+            decode_response(&bytes),
+            Err(ProtocolError::MissingField("service"))
+        );
+    }
+
+    #[test]
+    fn encode_decode_instance_requests_and_reply() {
+        let register = RegistryRequest::RegisterInstance {
+            service: "ruzzle.console".to_string(),
+            module: "serial-console".to_string(),
+            priority: 10,
+        };
+        let bytes = encode_request(&register);
+        assert_eq!(decode_request(&bytes), Ok(register));
+
+        let policy = RegistryRequest::SetPolicy {
+            service: "ruzzle.console".to_string(),
+            policy: ResolvePolicy::RoundRobin,
+        };
+        let bytes = encode_request(&policy);
+        assert_eq!(decode_request(&bytes), Ok(policy));
+
+        let registered = RegistryResponse::Registered { instance: 4 };
+        let bytes = encode_response(&registered);
+        assert_eq!(decode_response(&bytes), Ok(registered));
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_REGISTER_INSTANCE]);
+        write_tlv(&mut bytes, TLV_SERVICE, b"ruzzle.console");
+        write_tlv(&mut bytes, TLV_MODULE, b"serial-console");
+        assert_eq!(
+            decode_request(&bytes),
+            Ok(RegistryRequest::RegisterInstance {
+                service: "ruzzle.console".to_string(),
+                module: "serial-console".to_string(),
+                priority: 0,
+            })
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_SET_POLICY]);
+        write_tlv(&mut bytes, TLV_SERVICE, b"ruzzle.console");
+        write_tlv(&mut bytes, TLV_POLICY, &[7]);
+        assert_eq!(
+            decode_request(&bytes),
+            Err(ProtocolError::InvalidValue("policy"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_INSTANCE_REPLY]);
+        write_tlv(&mut bytes, TLV_STATUS, &[RegistryStatus::Ok.as_u8()]);
+        write_tlv(&mut bytes, TLV_INSTANCE, &[1, 2]);
+        assert_eq!(
+            decode_response(&bytes),
+            Err(ProtocolError::InvalidLength("instance"))
+        );
+        assert_eq!(
+            ResolvePolicy::parse("round-robin"),
+            Some(ResolvePolicy::RoundRobin)
+        );
+        assert_eq!(ResolvePolicy::parse("random"), None);
+    }
+
+    #[test]
+    fn list_response_keeps_instances_of_one_service() {
+        let list = RegistryResponse::List {
+            status: RegistryStatus::Ok,
+            entries: vec![
+                ServiceEntry {
+                    service: "ruzzle.console".to_string(),
+                    module: "console-service".to_string(),
+                },
+                ServiceEntry {
+                    service: "ruzzle.console".to_string(),
+                    module: "serial-console".to_string(),
+                },
+            ],
+        };
+        let bytes = encode_response(&list);
+        assert_eq!(decode_response(&bytes), Ok(list));
+    }
+
     #[test]
     fn decode_request_rejects_duplicate_msg_type() {
         let mut bytes = Vec::new();
@@ -1,8 +1,12 @@
 extern crate alloc;
 
+use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
+use crate::auth::Credential;
+use crate::describe::ServiceDescription;
+use crate::hello::{negotiate, Hello, HelloReply, VersionRange};
 use crate::tlv::{write_tlv, TlvReader};
 use crate::ProtocolError;
 
@@ -32,6 +36,34 @@ pub const TLV_DST: u16 = 11;
 pub const TLV_FLAG: u16 = 12;
 /// TLV type for raw argument strings.
 pub const TLV_ARGS: u16 = 13;
+/// TLV type for name glob patterns.
+pub const TLV_PATTERN: u16 = 14;
+/// TLV type for repeat intervals in seconds (u32 LE).
+pub const TLV_INTERVAL: u16 = 15;
+/// TLV type for line counts (u32 LE).
+pub const TLV_LINES: u16 = 16;
+/// TLV type for a response error code (u16 LE).
+pub const TLV_ERROR_CODE: u16 = 17;
+/// TLV type for the kind of a structured response (u8).
+pub const TLV_KIND: u16 = 18;
+/// TLV type for one row of a structured response (nested TLVs).
+pub const TLV_ROW: u16 = 19;
+/// TLV type for a module state inside a row.
+pub const TLV_STATE: u16 = 20;
+/// TLV type for a service name inside a row.
+pub const TLV_SERVICE: u16 = 21;
+/// TLV type for a request correlation id (u32 LE).
+pub const TLV_REQUEST_ID: u16 = 22;
+/// TLV type for an `ip`/`route` subcommand (u8).
+pub const TLV_OP: u16 = 23;
+/// TLV type for a network interface name.
+pub const TLV_IFACE: u16 = 24;
+/// TLV type for an address, CIDR, route destination or MAC.
+pub const TLV_ADDRESS: u16 = 25;
+/// TLV type for a route gateway.
+pub const TLV_GATEWAY: u16 = 26;
+/// TLV type for a route metric (u32 LE).
+pub const TLV_METRIC: u16 = 27;
 
 /// Flag bit for recursive copy.
 pub const FLAG_RECURSIVE: u8 = 0b0000_0001;
@@ -43,6 +75,57 @@ pub const FLAG_VERIFIED_ONLY: u8 = 0b0000_0001;
 pub const FLAG_SWAP: u8 = 0b0000_0010;
 /// Flag bit for process tree output.
 pub const FLAG_TREE: u8 = 0b0000_0001;
+/// Flag bit for streaming new log lines.
+pub const FLAG_FOLLOW: u8 = 0b0000_0001;
+/// Flag bit for human-readable sizes.
+pub const FLAG_HUMAN: u8 = 0b0000_0001;
+/// Flag bit to skip the confirmation prompt of destructive commands.
+pub const FLAG_FORCE: u8 = 0b0000_0001;
+/// Flag bit to clear notification history after listing it.
+pub const FLAG_CLEAR: u8 = 0b0000_0001;
+/// Flag bit for verbose listings.
+pub const FLAG_VERBOSE: u8 = 0b0000_0001;
+/// Flag bit for an admin override of a locked slot.
+pub const FLAG_OVERRIDE: u8 = 0b0000_0100;
+/// Flag bit for DOT graph output.
+pub const FLAG_DOT: u8 = 0b0000_0001;
+/// Flag bit for bringing a link up.
+pub const FLAG_UP: u8 = 0b0000_0001;
+/// Row flag bit for a required slot.
+pub const FLAG_REQUIRED: u8 = 0b0000_0001;
+/// Row flag bit for a locked slot.
+pub const FLAG_LOCKED: u8 = 0b0000_0010;
+
+/// Response kind: module rows.
+pub const KIND_MODULES: u8 = 1;
+/// Response kind: slot rows.
+pub const KIND_SLOTS: u8 = 2;
+
+/// `ip` subcommand: list interfaces.
+pub const IP_OP_SHOW: u8 = 1;
+/// `ip` subcommand: interface counters.
+pub const IP_OP_STATS: u8 = 2;
+/// `ip` subcommand: add an interface.
+pub const IP_OP_LINK_ADD: u8 = 3;
+/// `ip` subcommand: remove an interface.
+pub const IP_OP_LINK_DEL: u8 = 4;
+/// `ip` subcommand: set a link up or down.
+pub const IP_OP_LINK_SET: u8 = 5;
+/// `ip` subcommand: set a link MAC address.
+pub const IP_OP_LINK_ADDRESS: u8 = 6;
+/// `ip` subcommand: assign an IPv4 address.
+pub const IP_OP_ADDR_ADD: u8 = 7;
+/// `ip` subcommand: clear the IPv4 address.
+pub const IP_OP_ADDR_DEL: u8 = 8;
+
+/// `route` subcommand: list routes.
+pub const ROUTE_OP_SHOW: u8 = 1;
+/// `route` subcommand: add a route.
+pub const ROUTE_OP_ADD: u8 = 2;
+/// `route` subcommand: remove a route.
+pub const ROUTE_OP_DEL: u8 = 3;
+/// `route` subcommand: look up the route for an address.
+pub const ROUTE_OP_GET: u8 = 4;
 
 /// Shell message: list processes.
 pub const MSG_PS: u8 = 1;
@@ -124,12 +207,180 @@ pub const MSG_DF: u8 = 38;
 pub const MSG_DU: u8 = 39;
 /// Shell message: market scan command.
 pub const MSG_MARKET_SCAN: u8 = 40;
+/// Shell message: piece runtime sandbox report.
+pub const MSG_PIECE_RUNTIME: u8 = 41;
+/// Shell message: clipboard show/copy.
+pub const MSG_CLIP: u8 = 42;
+/// Shell message: time a command.
+pub const MSG_TIME: u8 = 43;
+/// Shell message: latency metrics.
+pub const MSG_METRICS: u8 = 44;
+/// Shell message: line/word/byte counts.
+pub const MSG_WC: u8 = 45;
+/// Shell message: recursive find.
+pub const MSG_FIND: u8 = 46;
+/// Shell message: create a symbolic link.
+pub const MSG_LN: u8 = 47;
+/// Shell message: change or list login shells.
+pub const MSG_CHSH: u8 = 48;
+/// Shell message: change mode bits.
+pub const MSG_CHMOD: u8 = 49;
+/// Shell message: change owner.
+pub const MSG_CHOWN: u8 = 50;
+/// Shell message: gpu tensor command.
+pub const MSG_GPU: u8 = 51;
+/// Shell message: re-run a command periodically.
+pub const MSG_WATCH: u8 = 52;
+/// Shell message: container management command.
+pub const MSG_CONTAINER: u8 = 53;
+/// Shell message: http server command.
+pub const MSG_HTTP: u8 = 54;
+/// Shell message: print text.
+pub const MSG_ECHO: u8 = 55;
+/// Shell message: drain services and halt.
+pub const MSG_SHUTDOWN: u8 = 56;
+/// Shell message: show or change shell settings.
+pub const MSG_SET: u8 = 57;
+/// Shell message: web console command.
+pub const MSG_WEBCONSOLE: u8 = 58;
+/// Shell message: show or set per-module log levels.
+pub const MSG_LOG_LEVEL: u8 = 59;
+/// Shell message: review or clear posted notifications.
+pub const MSG_NOTIFICATIONS: u8 = 60;
+/// Shell message: show file metadata.
+pub const MSG_STAT: u8 = 61;
+/// Shell message: apply a declarative system manifest.
+pub const MSG_APPLY: u8 = 62;
+/// Shell message: print a file's content checksum.
+pub const MSG_CHECKSUM: u8 = 63;
+/// Shell message: plan and plug providers for empty required slots.
+pub const MSG_AUTOPLUG: u8 = 64;
+/// Shell message: validate every slot on the puzzle board.
+pub const MSG_PIECE_CHECK_ALL: u8 = 65;
+/// Shell message: show a module's state and failure diagnostics.
+pub const MSG_STATUS: u8 = 66;
+/// Shell message: show module start times and the boot critical path.
+pub const MSG_BOOT_REPORT: u8 = 67;
+/// Shell message: resolve a host name to IPv4 addresses.
+pub const MSG_RESOLVE: u8 = 68;
+/// Shell message: send ICMP echo requests to an address.
+pub const MSG_PING: u8 = 69;
+/// Shell message: list or edit firewall rules.
+pub const MSG_FW: u8 = 70;
+/// Shell message: run or inspect the DHCP server piece.
+pub const MSG_DHCPD: u8 = 71;
+
+/// Name of the shell protocol in describe replies.
+pub const SHELL_PROTOCOL: &str = "shell";
+/// Shell protocol version reported by describe replies.
+///
+/// Version 2 added typed module/slot rows and error codes to responses;
+/// version 3 echoes request ids so commands can be multiplexed; version 4
+/// carries `ip` and `route` as typed subcommands instead of argument text.
+pub const SHELL_PROTOCOL_VERSION: u16 = 4;
+/// Shell protocol versions this build can encode and decode.
+pub const SHELL_VERSIONS: VersionRange = VersionRange::new(1, SHELL_PROTOCOL_VERSION);
+
+/// Message types and command names understood by this shell protocol.
+pub const SHELL_COMMANDS: [(u8, &str); 71] = [
+    (MSG_PS, "ps"),
+    (MSG_LSMOD, "lsmod"),
+    (MSG_START, "start"),
+    (MSG_STOP, "stop"),
+    (MSG_LOG_TAIL, "log tail"),
+    (MSG_HELP, "help"),
+    (MSG_CATALOG, "catalog"),
+    (MSG_INSTALL, "install"),
+    (MSG_REMOVE, "remove"),
+    (MSG_SETUP, "setup"),
+    (MSG_LOGIN, "login"),
+    (MSG_LOGOUT, "logout"),
+    (MSG_WHOAMI, "whoami"),
+    (MSG_USERS, "users"),
+    (MSG_USERADD, "useradd"),
+    (MSG_PWD, "pwd"),
+    (MSG_LS, "ls"),
+    (MSG_CD, "cd"),
+    (MSG_MKDIR, "mkdir"),
+    (MSG_TOUCH, "touch"),
+    (MSG_CAT, "cat"),
+    (MSG_WRITE, "write"),
+    (MSG_EDIT, "edit"),
+    (MSG_CP, "cp"),
+    (MSG_MV, "mv"),
+    (MSG_MKDIRP, "mkdir -p"),
+    (MSG_RMR, "rm -r"),
+    (MSG_SLOTS, "slots"),
+    (MSG_PLUG, "plug"),
+    (MSG_UNPLUG, "unplug"),
+    (MSG_SYSINFO, "sysinfo"),
+    (MSG_RM, "rm"),
+    (MSG_GRAPH, "graph"),
+    (MSG_PIECE_CHECK, "piece check"),
+    (MSG_IP, "ip"),
+    (MSG_ROUTE, "route"),
+    (MSG_MOUNT, "mount"),
+    (MSG_DF, "df"),
+    (MSG_DU, "du"),
+    (MSG_MARKET_SCAN, "market scan"),
+    (MSG_PIECE_RUNTIME, "piece runtime"),
+    (MSG_CLIP, "clip"),
+    (MSG_TIME, "time"),
+    (MSG_METRICS, "metrics"),
+    (MSG_WC, "wc"),
+    (MSG_FIND, "find"),
+    (MSG_LN, "ln"),
+    (MSG_CHSH, "chsh"),
+    (MSG_CHMOD, "chmod"),
+    (MSG_CHOWN, "chown"),
+    (MSG_GPU, "gpu"),
+    (MSG_WATCH, "watch"),
+    (MSG_CONTAINER, "container"),
+    (MSG_HTTP, "http"),
+    (MSG_ECHO, "echo"),
+    (MSG_SHUTDOWN, "shutdown"),
+    (MSG_SET, "set"),
+    (MSG_WEBCONSOLE, "webconsole"),
+    (MSG_LOG_LEVEL, "log level"),
+    (MSG_NOTIFICATIONS, "notifications"),
+    (MSG_STAT, "stat"),
+    (MSG_APPLY, "apply"),
+    (MSG_CHECKSUM, "checksum"),
+    (MSG_AUTOPLUG, "autoplug"),
+    (MSG_PIECE_CHECK_ALL, "piece check --all"),
+    (MSG_STATUS, "status"),
+    (MSG_BOOT_REPORT, "boot-report"),
+    (MSG_RESOLVE, "resolve"),
+    (MSG_PING, "ping"),
+    (MSG_FW, "fw"),
+    (MSG_DHCPD, "dhcpd"),
+];
+
+/// Returns the hello a shell client of this build opens a session with.
+pub fn shell_hello() -> Hello {
+    Hello {
+        protocol: SHELL_PROTOCOL.to_string(),
+        versions: SHELL_VERSIONS,
+    }
+}
+
+/// Answers a client hello with the newest shell version both sides speak.
+pub fn answer_shell_hello(hello: &Hello) -> HelloReply {
+    negotiate(hello, SHELL_PROTOCOL, SHELL_VERSIONS)
+}
+
+/// Returns the describe reply for this shell protocol.
+pub fn describe_shell() -> ServiceDescription {
+    ServiceDescription::from_table(SHELL_PROTOCOL, SHELL_PROTOCOL_VERSION, &SHELL_COMMANDS)
+}
 
 /// Shell response status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ShellStatus {
     Ok,
     Failed,
+    /// Partial output of a streaming command; more responses follow.
+    Streaming,
 }
 
 impl ShellStatus {
@@ -137,6 +388,7 @@ impl ShellStatus {
         match self {
             ShellStatus::Ok => 0,
             ShellStatus::Failed => 1,
+            ShellStatus::Streaming => 2,
         }
     }
 
@@ -144,6 +396,7 @@ impl ShellStatus {
         match value {
             0 => Ok(ShellStatus::Ok),
             1 => Ok(ShellStatus::Failed),
+            2 => Ok(ShellStatus::Streaming),
             _ => Err(ProtocolError::InvalidValue("status")),
         }
     }
@@ -158,21 +411,34 @@ pub enum ShellCommand {
     Lsmod,
     Start(String),
     Stop(String),
-    LogTail,
+    LogTail {
+        follow: bool,
+        lines: Option<u32>,
+    },
     Help(Option<String>),
     Catalog {
         slot: Option<String>,
         verified_only: bool,
     },
     PieceCheck(String),
-    Ip(Option<String>),
-    Route(Option<String>),
+    PieceRuntime(String),
+    Ip(IpCommand),
+    Route(RouteCommand),
     Mount(Option<String>),
-    Df(Option<String>),
-    Du(String),
+    Df {
+        path: Option<String>,
+        human: bool,
+    },
+    Du {
+        path: String,
+        human: bool,
+    },
     MarketScan,
     Install(String),
-    Remove(String),
+    Remove {
+        module: String,
+        force: bool,
+    },
     Setup,
     Login(String),
     Logout,
@@ -190,25 +456,229 @@ pub enum ShellCommand {
     Mv { src: String, dst: String },
     MkdirP(String),
     Write { path: String, contents: String },
-    RmRecursive(String),
-    Slots,
+    RmRecursive {
+        path: String,
+        force: bool,
+    },
+    Slots {
+        verbose: bool,
+    },
     Plug {
         slot: String,
         module: String,
         dry_run: bool,
         swap: bool,
+        override_lock: bool,
+    },
+    Unplug {
+        slot: String,
+        force: bool,
+        override_lock: bool,
+    },
+    Graph {
+        dot: bool,
     },
-    Unplug(String),
-    Graph,
     Sysinfo,
     Rm(String),
+    Clip(Option<String>),
+    Time(String),
+    Metrics(Option<String>),
+    Wc(String),
+    Find {
+        path: String,
+        name: Option<String>,
+    },
+    Ln { target: String, link: String },
+    Chsh {
+        user: Option<String>,
+        shell: Option<String>,
+    },
+    Chmod { mode: String, path: String },
+    Chown { user: String, path: String },
+    Gpu(Option<String>),
+    Watch { interval_secs: u32, command: String },
+    Container(Option<String>),
+    Http(Option<String>),
+    Echo(String),
+    Shutdown,
+    Set(Option<String>),
+    WebConsole(Option<String>),
+    LogLevel(Option<String>),
+    Notifications {
+        clear: bool,
+    },
+    Stat(String),
+    Apply {
+        path: String,
+        dry_run: bool,
+    },
+    Checksum(String),
+    Autoplug {
+        dry_run: bool,
+    },
+    PieceCheckAll,
+    Status(String),
+    BootReport,
+    Resolve(String),
+    Ping(String),
+    Fw(Option<String>),
+    Dhcpd(Option<String>),
+}
+
+/// Typed `ip` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IpCommand {
+    /// List interfaces.
+    Show,
+    /// Packet counters for one interface, or all of them.
+    Stats(Option<String>),
+    LinkAdd(String),
+    LinkDel(String),
+    LinkSet {
+        iface: String,
+        up: bool,
+    },
+    /// Set the MAC address, as `aa:bb:cc:dd:ee:ff`.
+    LinkAddress {
+        iface: String,
+        mac: String,
+    },
+    /// Assign an IPv4 address as `addr[/prefix]`.
+    AddrAdd {
+        iface: String,
+        cidr: String,
+    },
+    AddrDel(String),
+}
+
+/// Typed `route` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteCommand {
+    /// List routes.
+    Show,
+    Add {
+        destination: String,
+        iface: String,
+        gateway: Option<String>,
+        metric: u32,
+    },
+    Del(String),
+    /// Look up the route chosen for an IPv4 address.
+    Get(String),
+}
+
+/// Stable numeric codes for failed shell commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellErrorCode {
+    /// Failure without a more specific code.
+    Failed,
+    UnknownCommand,
+    NotFound,
+    AlreadyExists,
+    InvalidArgument,
+    PermissionDenied,
+    NotADirectory,
+    IsADirectory,
+    NotEmpty,
+    NoSpace,
+    Io,
+}
+
+impl ShellErrorCode {
+    pub fn as_u16(self) -> u16 {
+        match self {
+            ShellErrorCode::Failed => 1,
+            ShellErrorCode::UnknownCommand => 2,
+            ShellErrorCode::NotFound => 3,
+            ShellErrorCode::AlreadyExists => 4,
+            ShellErrorCode::InvalidArgument => 5,
+            ShellErrorCode::PermissionDenied => 6,
+            ShellErrorCode::NotADirectory => 7,
+            ShellErrorCode::IsADirectory => 8,
+            ShellErrorCode::NotEmpty => 9,
+            ShellErrorCode::NoSpace => 10,
+            ShellErrorCode::Io => 11,
+        }
+    }
+
+    pub fn from_u16(value: u16) -> Result<Self, ProtocolError> {
+        match value {
+            1 => Ok(ShellErrorCode::Failed),
+            2 => Ok(ShellErrorCode::UnknownCommand),
+            3 => Ok(ShellErrorCode::NotFound),
+            4 => Ok(ShellErrorCode::AlreadyExists),
+            5 => Ok(ShellErrorCode::InvalidArgument),
+            6 => Ok(ShellErrorCode::PermissionDenied),
+            7 => Ok(ShellErrorCode::NotADirectory),
+            8 => Ok(ShellErrorCode::IsADirectory),
+            9 => Ok(ShellErrorCode::NotEmpty),
+            10 => Ok(ShellErrorCode::NoSpace),
+            11 => Ok(ShellErrorCode::Io),
+            _ => Err(ProtocolError::InvalidValue("error_code")),
+        }
+    }
+
+    /// Returns a stable, human-readable label.
+    pub fn label(self) -> &'static str {
+        match self {
+            ShellErrorCode::Failed => "failed",
+            ShellErrorCode::UnknownCommand => "unknown command",
+            ShellErrorCode::NotFound => "not found",
+            ShellErrorCode::AlreadyExists => "already exists",
+            ShellErrorCode::InvalidArgument => "invalid argument",
+            ShellErrorCode::PermissionDenied => "permission denied",
+            ShellErrorCode::NotADirectory => "not a directory",
+            ShellErrorCode::IsADirectory => "is a directory",
+            ShellErrorCode::NotEmpty => "directory not empty",
+            ShellErrorCode::NoSpace => "no space left",
+            ShellErrorCode::Io => "i/o error",
+        }
+    }
+}
+
+/// One installed module in a typed `lsmod` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleInfo {
+    pub name: String,
+    pub state: String,
+    pub provides: Vec<String>,
+}
+
+/// One puzzle board slot in a typed `slots` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotInfo {
+    pub name: String,
+    pub required: bool,
+    pub locked: bool,
+    pub providers: Vec<String>,
 }
 
 /// Shell response message.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ShellResponse {
-    pub status: ShellStatus,
-    pub text: String,
+pub enum ShellResponse {
+    /// Free-form command output.
+    Text {
+        status: ShellStatus,
+        text: String,
+    },
+    Modules(Vec<ModuleInfo>),
+    Slots(Vec<SlotInfo>),
+    /// Failed command with a stable code and the command's own message.
+    Error {
+        code: ShellErrorCode,
+        message: String,
+    },
+}
+
+impl ShellResponse {
+    /// Returns the status carried on the wire for this response.
+    pub fn status(&self) -> ShellStatus {
+        match self {
+            ShellResponse::Text { status, .. } => *status,
+            ShellResponse::Modules(_) | ShellResponse::Slots(_) => ShellStatus::Ok,
+            ShellResponse::Error { .. } => ShellStatus::Failed,
+        }
+    }
 }
 
 /// Encodes a shell command into TLV bytes.
@@ -230,7 +700,15 @@ pub fn encode_command(command: &ShellCommand) -> Vec<u8> {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_STOP]);
             write_tlv(&mut bytes, TLV_MODULE, module.as_bytes());
         }
-        ShellCommand::LogTail => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LOG_TAIL]),
+        ShellCommand::LogTail { follow, lines } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LOG_TAIL]);
+            if *follow {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_FOLLOW]);
+            }
+            if let Some(lines) = lines {
+                write_tlv(&mut bytes, TLV_LINES, &lines.to_le_bytes());
+            }
+        }
         ShellCommand::Help(topic) => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_HELP]);
             if let Some(topic) = topic {
@@ -253,17 +731,17 @@ pub fn encode_command(command: &ShellCommand) -> Vec<u8> {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_PIECE_CHECK]);
             write_tlv(&mut bytes, TLV_MODULE, module.as_bytes());
         }
-        ShellCommand::Ip(args) => {
+        ShellCommand::PieceRuntime(module) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_PIECE_RUNTIME]);
+            write_tlv(&mut bytes, TLV_MODULE, module.as_bytes());
+        }
+        ShellCommand::Ip(ip) => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_IP]);
-            if let Some(args) = args {
-                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
-            }
+            encode_ip(&mut bytes, ip);
         }
-        ShellCommand::Route(args) => {
+        ShellCommand::Route(route) => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_ROUTE]);
-            if let Some(args) = args {
-                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
-            }
+            encode_route(&mut bytes, route);
         }
         ShellCommand::Mount(args) => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_MOUNT]);
@@ -271,24 +749,33 @@ pub fn encode_command(command: &ShellCommand) -> Vec<u8> {
                 write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
             }
         }
-        ShellCommand::Df(path) => {
+        ShellCommand::Df { path, human } => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_DF]);
             if let Some(path) = path {
                 write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
             }
+            if *human {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_HUMAN]);
+            }
         }
-        ShellCommand::Du(path) => {
+        ShellCommand::Du { path, human } => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_DU]);
             write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+            if *human {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_HUMAN]);
+            }
         }
         ShellCommand::MarketScan => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_MARKET_SCAN]),
         ShellCommand::Install(module) => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_INSTALL]);
             write_tlv(&mut bytes, TLV_MODULE, module.as_bytes());
         }
-        ShellCommand::Remove(module) => {
+        ShellCommand::Remove { module, force } => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_REMOVE]);
             write_tlv(&mut bytes, TLV_MODULE, module.as_bytes());
+            if *force {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_FORCE]);
+            }
         }
         ShellCommand::Setup => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_SETUP]),
         ShellCommand::Login(user) => {
@@ -351,12 +838,18 @@ pub fn encode_command(command: &ShellCommand) -> Vec<u8> {
             write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
             write_tlv(&mut bytes, TLV_CONTENT, contents.as_bytes());
         }
-        ShellCommand::Slots => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_SLOTS]),
+        ShellCommand::Slots { verbose } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_SLOTS]);
+            if *verbose {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_VERBOSE]);
+            }
+        }
         ShellCommand::Plug {
             slot,
             module,
             dry_run,
             swap,
+            override_lock,
         } => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_PLUG]);
             write_tlv(&mut bytes, TLV_SLOT, slot.as_bytes());
@@ -368,23 +861,203 @@ pub fn encode_command(command: &ShellCommand) -> Vec<u8> {
             if *swap {
                 flags |= FLAG_SWAP;
             }
+            if *override_lock {
+                flags |= FLAG_OVERRIDE;
+            }
             if flags != 0 {
                 write_tlv(&mut bytes, TLV_FLAG, &[flags]);
             }
         }
-        ShellCommand::Unplug(slot) => {
+        ShellCommand::Unplug {
+            slot,
+            force,
+            override_lock,
+        } => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_UNPLUG]);
             write_tlv(&mut bytes, TLV_SLOT, slot.as_bytes());
+            let mut flags = 0u8;
+            if *force {
+                flags |= FLAG_FORCE;
+            }
+            if *override_lock {
+                flags |= FLAG_OVERRIDE;
+            }
+            if flags != 0 {
+                write_tlv(&mut bytes, TLV_FLAG, &[flags]);
+            }
+        }
+        ShellCommand::Graph { dot } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_GRAPH]);
+            if *dot {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_DOT]);
+            }
         }
-        ShellCommand::Graph => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_GRAPH]),
         ShellCommand::Sysinfo => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_SYSINFO]),
         ShellCommand::Rm(path) => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_RM]);
             write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
         }
-        ShellCommand::RmRecursive(path) => {
+        ShellCommand::RmRecursive { path, force } => {
             write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_RMR]);
             write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+            if *force {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_FORCE]);
+            }
+        }
+        ShellCommand::Clip(text) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CLIP]);
+            if let Some(text) = text {
+                write_tlv(&mut bytes, TLV_CONTENT, text.as_bytes());
+            }
+        }
+        ShellCommand::Time(command) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_TIME]);
+            write_tlv(&mut bytes, TLV_ARGS, command.as_bytes());
+        }
+        ShellCommand::Metrics(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_METRICS]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
+        }
+        ShellCommand::Wc(path) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WC]);
+            write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+        }
+        ShellCommand::Find { path, name } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_FIND]);
+            write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+            if let Some(name) = name {
+                write_tlv(&mut bytes, TLV_PATTERN, name.as_bytes());
+            }
+        }
+        ShellCommand::Ln { target, link } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LN]);
+            write_tlv(&mut bytes, TLV_SRC, target.as_bytes());
+            write_tlv(&mut bytes, TLV_DST, link.as_bytes());
+        }
+        ShellCommand::Chsh { user, shell } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CHSH]);
+            if let Some(user) = user {
+                write_tlv(&mut bytes, TLV_USER, user.as_bytes());
+            }
+            if let Some(shell) = shell {
+                write_tlv(&mut bytes, TLV_PATH, shell.as_bytes());
+            }
+        }
+        ShellCommand::Chmod { mode, path } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CHMOD]);
+            write_tlv(&mut bytes, TLV_ARGS, mode.as_bytes());
+            write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+        }
+        ShellCommand::Chown { user, path } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CHOWN]);
+            write_tlv(&mut bytes, TLV_USER, user.as_bytes());
+            write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+        }
+        ShellCommand::Gpu(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_GPU]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
+        }
+        ShellCommand::Watch {
+            interval_secs,
+            command,
+        } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WATCH]);
+            write_tlv(&mut bytes, TLV_INTERVAL, &interval_secs.to_le_bytes());
+            write_tlv(&mut bytes, TLV_ARGS, command.as_bytes());
+        }
+        ShellCommand::Container(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CONTAINER]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
+        }
+        ShellCommand::Http(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_HTTP]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
+        }
+        ShellCommand::Echo(text) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_ECHO]);
+            if !text.is_empty() {
+                write_tlv(&mut bytes, TLV_ARGS, text.as_bytes());
+            }
+        }
+        ShellCommand::Shutdown => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_SHUTDOWN]),
+        ShellCommand::Set(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_SET]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
+        }
+        ShellCommand::WebConsole(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WEBCONSOLE]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
+        }
+        ShellCommand::LogLevel(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LOG_LEVEL]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
+        }
+        ShellCommand::Notifications { clear } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_NOTIFICATIONS]);
+            if *clear {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_CLEAR]);
+            }
+        }
+        ShellCommand::Stat(path) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_STAT]);
+            write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+        }
+        ShellCommand::Apply { path, dry_run } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_APPLY]);
+            write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+            if *dry_run {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_DRY_RUN]);
+            }
+        }
+        ShellCommand::Checksum(path) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CHECKSUM]);
+            write_tlv(&mut bytes, TLV_PATH, path.as_bytes());
+        }
+        ShellCommand::Autoplug { dry_run } => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_AUTOPLUG]);
+            if *dry_run {
+                write_tlv(&mut bytes, TLV_FLAG, &[FLAG_DRY_RUN]);
+            }
+        }
+        ShellCommand::PieceCheckAll => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_PIECE_CHECK_ALL]),
+        ShellCommand::Status(module) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_STATUS]);
+            write_tlv(&mut bytes, TLV_MODULE, module.as_bytes());
+        }
+        ShellCommand::BootReport => write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_BOOT_REPORT]),
+        ShellCommand::Resolve(name) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_RESOLVE]);
+            write_tlv(&mut bytes, TLV_ARGS, name.as_bytes());
+        }
+        ShellCommand::Ping(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_PING]);
+            write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+        }
+        ShellCommand::Fw(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_FW]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
+        }
+        ShellCommand::Dhcpd(args) => {
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_DHCPD]);
+            if let Some(args) = args {
+                write_tlv(&mut bytes, TLV_ARGS, args.as_bytes());
+            }
         }
     }
     bytes
@@ -402,7 +1075,15 @@ pub fn decode_command(bytes: &[u8]) -> Result<ShellCommand, ProtocolError> {
     let mut src: Option<String> = None;
     let mut dst: Option<String> = None;
     let mut args: Option<String> = None;
+    let mut pattern: Option<String> = None;
     let mut flag: Option<u8> = None;
+    let mut interval: Option<u32> = None;
+    let mut lines: Option<u32> = None;
+    let mut op: Option<u8> = None;
+    let mut iface: Option<String> = None;
+    let mut address: Option<String> = None;
+    let mut gateway: Option<String> = None;
+    let mut metric: Option<u32> = None;
 
     let mut reader = TlvReader::new(bytes);
     while let Some(field) = reader.next()? {
@@ -470,6 +1151,12 @@ pub fn decode_command(bytes: &[u8]) -> Result<ShellCommand, ProtocolError> {
                 }
                 args = Some(parse_string(field.value)?);
             }
+            TLV_PATTERN => {
+                if pattern.is_some() {
+                    return Err(ProtocolError::DuplicateField("pattern"));
+                }
+                pattern = Some(parse_string(field.value)?);
+            }
             TLV_FLAG => {
                 if flag.is_some() {
                     return Err(ProtocolError::DuplicateField("flag"));
@@ -479,6 +1166,63 @@ pub fn decode_command(bytes: &[u8]) -> Result<ShellCommand, ProtocolError> {
                 }
                 flag = Some(field.value[0]);
             }
+            TLV_INTERVAL => {
+                if interval.is_some() {
+                    return Err(ProtocolError::DuplicateField("interval"));
+                }
+                let value: [u8; 4] = field
+                    .value
+                    .try_into()
+                    .map_err(|_| ProtocolError::InvalidLength("interval"))?;
+                interval = Some(u32::from_le_bytes(value));
+            }
+            TLV_LINES => {
+                if lines.is_some() {
+                    return Err(ProtocolError::DuplicateField("lines"));
+                }
+                let value: [u8; 4] = field
+                    .value
+                    .try_into()
+                    .map_err(|_| ProtocolError::InvalidLength("lines"))?;
+                lines = Some(u32::from_le_bytes(value));
+            }
+            TLV_OP => {
+                if op.is_some() {
+                    return Err(ProtocolError::DuplicateField("op"));
+                }
+                if field.value.len() != 1 {
+                    return Err(ProtocolError::InvalidLength("op"));
+                }
+                op = Some(field.value[0]);
+            }
+            TLV_IFACE => {
+                if iface.is_some() {
+                    return Err(ProtocolError::DuplicateField("iface"));
+                }
+                iface = Some(parse_string(field.value)?);
+            }
+            TLV_ADDRESS => {
+                if address.is_some() {
+                    return Err(ProtocolError::DuplicateField("address"));
+                }
+                address = Some(parse_string(field.value)?);
+            }
+            TLV_GATEWAY => {
+                if gateway.is_some() {
+                    return Err(ProtocolError::DuplicateField("gateway"));
+                }
+                gateway = Some(parse_string(field.value)?);
+            }
+            TLV_METRIC => {
+                if metric.is_some() {
+                    return Err(ProtocolError::DuplicateField("metric"));
+                }
+                let value: [u8; 4] = field
+                    .value
+                    .try_into()
+                    .map_err(|_| ProtocolError::InvalidLength("metric"))?;
+                metric = Some(u32::from_le_bytes(value));
+            }
             _ => {}
         }
     }
@@ -495,7 +1239,10 @@ pub fn decode_command(bytes: &[u8]) -> Result<ShellCommand, ProtocolError> {
         MSG_STOP => Ok(ShellCommand::Stop(
             module.ok_or(ProtocolError::MissingField("module"))?,
         )),
-        MSG_LOG_TAIL => Ok(ShellCommand::LogTail),
+        MSG_LOG_TAIL => Ok(ShellCommand::LogTail {
+            follow: flag.map(|bits| bits & FLAG_FOLLOW != 0).unwrap_or(false),
+            lines,
+        }),
         MSG_HELP => Ok(ShellCommand::Help(topic)),
         MSG_CATALOG => Ok(ShellCommand::Catalog {
             slot,
@@ -506,20 +1253,30 @@ pub fn decode_command(bytes: &[u8]) -> Result<ShellCommand, ProtocolError> {
         MSG_PIECE_CHECK => Ok(ShellCommand::PieceCheck(
             module.ok_or(ProtocolError::MissingField("module"))?,
         )),
-        MSG_IP => Ok(ShellCommand::Ip(args)),
-        MSG_ROUTE => Ok(ShellCommand::Route(args)),
-        MSG_MOUNT => Ok(ShellCommand::Mount(args)),
-        MSG_DF => Ok(ShellCommand::Df(path)),
-        MSG_DU => Ok(ShellCommand::Du(
-            path.ok_or(ProtocolError::MissingField("path"))?,
+        MSG_PIECE_RUNTIME => Ok(ShellCommand::PieceRuntime(
+            module.ok_or(ProtocolError::MissingField("module"))?,
         )),
+        MSG_IP => Ok(ShellCommand::Ip(decode_ip(op, iface, address, flag)?)),
+        MSG_ROUTE => Ok(ShellCommand::Route(decode_route(
+            op, iface, address, gateway, metric,
+        )?)),
+        MSG_MOUNT => Ok(ShellCommand::Mount(args)),
+        MSG_DF => Ok(ShellCommand::Df {
+            path,
+            human: flag.map(|bits| bits & FLAG_HUMAN != 0).unwrap_or(false),
+        }),
+        MSG_DU => Ok(ShellCommand::Du {
+            path: path.ok_or(ProtocolError::MissingField("path"))?,
+            human: flag.map(|bits| bits & FLAG_HUMAN != 0).unwrap_or(false),
+        }),
         MSG_MARKET_SCAN => Ok(ShellCommand::MarketScan),
         MSG_INSTALL => Ok(ShellCommand::Install(
             module.ok_or(ProtocolError::MissingField("module"))?,
         )),
-        MSG_REMOVE => Ok(ShellCommand::Remove(
-            module.ok_or(ProtocolError::MissingField("module"))?,
-        )),
+        MSG_REMOVE => Ok(ShellCommand::Remove {
+            module: module.ok_or(ProtocolError::MissingField("module"))?,
+            force: flag.map(|bits| bits & FLAG_FORCE != 0).unwrap_or(false),
+        }),
         MSG_SETUP => Ok(ShellCommand::Setup),
         MSG_LOGIN => Ok(ShellCommand::Login(
             user.ok_or(ProtocolError::MissingField("user"))?,
@@ -563,24 +1320,98 @@ pub fn decode_command(bytes: &[u8]) -> Result<ShellCommand, ProtocolError> {
             path: path.ok_or(ProtocolError::MissingField("path"))?,
             contents: content.ok_or(ProtocolError::MissingField("content"))?,
         }),
-        MSG_SLOTS => Ok(ShellCommand::Slots),
+        MSG_SLOTS => Ok(ShellCommand::Slots {
+            verbose: flag.map(|bits| bits & FLAG_VERBOSE != 0).unwrap_or(false),
+        }),
         MSG_PLUG => Ok(ShellCommand::Plug {
             slot: slot.ok_or(ProtocolError::MissingField("slot"))?,
             module: module.ok_or(ProtocolError::MissingField("module"))?,
             dry_run: flag.map(|bits| bits & FLAG_DRY_RUN != 0).unwrap_or(false),
             swap: flag.map(|bits| bits & FLAG_SWAP != 0).unwrap_or(false),
+            override_lock: flag.map(|bits| bits & FLAG_OVERRIDE != 0).unwrap_or(false),
+        }),
+        MSG_UNPLUG => Ok(ShellCommand::Unplug {
+            slot: slot.ok_or(ProtocolError::MissingField("slot"))?,
+            force: flag.map(|bits| bits & FLAG_FORCE != 0).unwrap_or(false),
+            override_lock: flag.map(|bits| bits & FLAG_OVERRIDE != 0).unwrap_or(false),
+        }),
+        MSG_GRAPH => Ok(ShellCommand::Graph {
+            dot: flag.map(|bits| bits & FLAG_DOT != 0).unwrap_or(false),
         }),
-        MSG_UNPLUG => Ok(ShellCommand::Unplug(
-            slot.ok_or(ProtocolError::MissingField("slot"))?,
-        )),
-        MSG_GRAPH => Ok(ShellCommand::Graph),
         MSG_SYSINFO => Ok(ShellCommand::Sysinfo),
         MSG_RM => Ok(ShellCommand::Rm(
             path.ok_or(ProtocolError::MissingField("path"))?,
         )),
-        MSG_RMR => Ok(ShellCommand::RmRecursive(
+        MSG_RMR => Ok(ShellCommand::RmRecursive {
+            path: path.ok_or(ProtocolError::MissingField("path"))?,
+            force: flag.map(|bits| bits & FLAG_FORCE != 0).unwrap_or(false),
+        }),
+        MSG_CLIP => Ok(ShellCommand::Clip(content)),
+        MSG_TIME => Ok(ShellCommand::Time(
+            args.ok_or(ProtocolError::MissingField("args"))?,
+        )),
+        MSG_METRICS => Ok(ShellCommand::Metrics(args)),
+        MSG_WC => Ok(ShellCommand::Wc(
+            path.ok_or(ProtocolError::MissingField("path"))?,
+        )),
+        MSG_FIND => Ok(ShellCommand::Find {
+            path: path.ok_or(ProtocolError::MissingField("path"))?,
+            name: pattern,
+        }),
+        MSG_LN => Ok(ShellCommand::Ln {
+            target: src.ok_or(ProtocolError::MissingField("src"))?,
+            link: dst.ok_or(ProtocolError::MissingField("dst"))?,
+        }),
+        MSG_CHSH => Ok(ShellCommand::Chsh { user, shell: path }),
+        MSG_CHMOD => Ok(ShellCommand::Chmod {
+            mode: args.ok_or(ProtocolError::MissingField("args"))?,
+            path: path.ok_or(ProtocolError::MissingField("path"))?,
+        }),
+        MSG_CHOWN => Ok(ShellCommand::Chown {
+            user: user.ok_or(ProtocolError::MissingField("user"))?,
+            path: path.ok_or(ProtocolError::MissingField("path"))?,
+        }),
+        MSG_GPU => Ok(ShellCommand::Gpu(args)),
+        MSG_WATCH => Ok(ShellCommand::Watch {
+            interval_secs: interval.ok_or(ProtocolError::MissingField("interval"))?,
+            command: args.ok_or(ProtocolError::MissingField("args"))?,
+        }),
+        MSG_CONTAINER => Ok(ShellCommand::Container(args)),
+        MSG_HTTP => Ok(ShellCommand::Http(args)),
+        MSG_ECHO => Ok(ShellCommand::Echo(args.unwrap_or_default())),
+        MSG_SHUTDOWN => Ok(ShellCommand::Shutdown),
+        MSG_SET => Ok(ShellCommand::Set(args)),
+        MSG_WEBCONSOLE => Ok(ShellCommand::WebConsole(args)),
+        MSG_LOG_LEVEL => Ok(ShellCommand::LogLevel(args)),
+        MSG_NOTIFICATIONS => Ok(ShellCommand::Notifications {
+            clear: flag.map(|bits| bits & FLAG_CLEAR != 0).unwrap_or(false),
+        }),
+        MSG_STAT => Ok(ShellCommand::Stat(
+            path.ok_or(ProtocolError::MissingField("path"))?,
+        )),
+        MSG_APPLY => Ok(ShellCommand::Apply {
+            path: path.ok_or(ProtocolError::MissingField("path"))?,
+            dry_run: flag.map(|bits| bits & FLAG_DRY_RUN != 0).unwrap_or(false),
+        }),
+        MSG_CHECKSUM => Ok(ShellCommand::Checksum(
             path.ok_or(ProtocolError::MissingField("path"))?,
         )),
+        MSG_AUTOPLUG => Ok(ShellCommand::Autoplug {
+            dry_run: flag.map(|bits| bits & FLAG_DRY_RUN != 0).unwrap_or(false),
+        }),
+        MSG_PIECE_CHECK_ALL => Ok(ShellCommand::PieceCheckAll),
+        MSG_STATUS => Ok(ShellCommand::Status(
+            module.ok_or(ProtocolError::MissingField("module"))?,
+        )),
+        MSG_BOOT_REPORT => Ok(ShellCommand::BootReport),
+        MSG_RESOLVE => Ok(ShellCommand::Resolve(
+            args.ok_or(ProtocolError::MissingField("args"))?,
+        )),
+        MSG_PING => Ok(ShellCommand::Ping(
+            args.ok_or(ProtocolError::MissingField("args"))?,
+        )),
+        MSG_FW => Ok(ShellCommand::Fw(args)),
+        MSG_DHCPD => Ok(ShellCommand::Dhcpd(args)),
         other => Err(ProtocolError::UnknownMessageType(other)),
     }
 }
@@ -588,15 +1419,58 @@ pub fn decode_command(bytes: &[u8]) -> Result<ShellCommand, ProtocolError> {
 /// Encodes a shell response into TLV bytes.
 pub fn encode_response(response: &ShellResponse) -> Vec<u8> {
     let mut bytes = Vec::new();
-    write_tlv(&mut bytes, TLV_STATUS, &[response.status.as_u8()]);
-    write_tlv(&mut bytes, TLV_TEXT, response.text.as_bytes());
+    write_tlv(&mut bytes, TLV_STATUS, &[response.status().as_u8()]);
+    match response {
+        ShellResponse::Text { text, .. } => write_tlv(&mut bytes, TLV_TEXT, text.as_bytes()),
+        ShellResponse::Modules(modules) => {
+            write_tlv(&mut bytes, TLV_KIND, &[KIND_MODULES]);
+            for module in modules {
+                let mut row = Vec::new();
+                write_tlv(&mut row, TLV_MODULE, module.name.as_bytes());
+                write_tlv(&mut row, TLV_STATE, module.state.as_bytes());
+                for service in &module.provides {
+                    write_tlv(&mut row, TLV_SERVICE, service.as_bytes());
+                }
+                write_tlv(&mut bytes, TLV_ROW, &row);
+            }
+        }
+        ShellResponse::Slots(slots) => {
+            write_tlv(&mut bytes, TLV_KIND, &[KIND_SLOTS]);
+            for slot in slots {
+                let mut row = Vec::new();
+                write_tlv(&mut row, TLV_SLOT, slot.name.as_bytes());
+                let mut flags = 0;
+                if slot.required {
+                    flags |= FLAG_REQUIRED;
+                }
+                if slot.locked {
+                    flags |= FLAG_LOCKED;
+                }
+                write_tlv(&mut row, TLV_FLAG, &[flags]);
+                for provider in &slot.providers {
+                    write_tlv(&mut row, TLV_MODULE, provider.as_bytes());
+                }
+                write_tlv(&mut bytes, TLV_ROW, &row);
+            }
+        }
+        ShellResponse::Error { code, message } => {
+            write_tlv(&mut bytes, TLV_ERROR_CODE, &code.as_u16().to_le_bytes());
+            write_tlv(&mut bytes, TLV_TEXT, message.as_bytes());
+        }
+    }
     bytes
 }
 
 /// Decodes a shell response from TLV bytes.
+///
+/// Version 1 peers only ever send the text form, which still decodes as
+/// `ShellResponse::Text`.
 pub fn decode_response(bytes: &[u8]) -> Result<ShellResponse, ProtocolError> {
     let mut status: Option<ShellStatus> = None;
     let mut text: Option<String> = None;
+    let mut code: Option<ShellErrorCode> = None;
+    let mut kind: Option<u8> = None;
+    let mut rows: Vec<&[u8]> = Vec::new();
 
     let mut reader = TlvReader::new(bytes);
     while let Some(field) = reader.next()? {
@@ -616,27 +1490,343 @@ pub fn decode_response(bytes: &[u8]) -> Result<ShellResponse, ProtocolError> {
                 }
                 text = Some(parse_string(field.value)?);
             }
+            TLV_ERROR_CODE => {
+                if code.is_some() {
+                    return Err(ProtocolError::DuplicateField("error_code"));
+                }
+                let raw: [u8; 2] = field
+                    .value
+                    .try_into()
+                    .map_err(|_| ProtocolError::InvalidLength("error_code"))?;
+                code = Some(ShellErrorCode::from_u16(u16::from_le_bytes(raw))?);
+            }
+            TLV_KIND => {
+                if kind.is_some() {
+                    return Err(ProtocolError::DuplicateField("kind"));
+                }
+                if field.value.len() != 1 {
+                    return Err(ProtocolError::InvalidLength("kind"));
+                }
+                kind = Some(field.value[0]);
+            }
+            TLV_ROW => rows.push(field.value),
             _ => {}
         }
     }
 
-    Ok(ShellResponse {
-        status: status.ok_or(ProtocolError::MissingField("status"))?,
-        text: text.ok_or(ProtocolError::MissingField("text"))?,
-    })
-}
-
-fn parse_string(value: &[u8]) -> Result<String, ProtocolError> {
-    let text = core::str::from_utf8(value).map_err(|_| ProtocolError::InvalidUtf8)?;
-    if text.is_empty() {
-        return Err(ProtocolError::InvalidValue("string"));
+    let status = status.ok_or(ProtocolError::MissingField("status"))?;
+    if let Some(code) = code {
+        if status != ShellStatus::Failed {
+            return Err(ProtocolError::InvalidValue("status"));
+        }
+        return Ok(ShellResponse::Error {
+            code,
+            message: text.ok_or(ProtocolError::MissingField("text"))?,
+        });
+    }
+    match kind {
+        None if rows.is_empty() => Ok(ShellResponse::Text {
+            status,
+            text: text.ok_or(ProtocolError::MissingField("text"))?,
+        }),
+        None => Err(ProtocolError::MissingField("kind")),
+        Some(KIND_MODULES) => Ok(ShellResponse::Modules(
+            rows.into_iter()
+                .map(decode_module_row)
+                .collect::<Result<_, _>>()?,
+        )),
+        Some(KIND_SLOTS) => Ok(ShellResponse::Slots(
+            rows.into_iter()
+                .map(decode_slot_row)
+                .collect::<Result<_, _>>()?,
+        )),
+        Some(_) => Err(ProtocolError::InvalidValue("kind")),
     }
-    Ok(text.to_string())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn decode_module_row(bytes: &[u8]) -> Result<ModuleInfo, ProtocolError> {
+    let mut name: Option<String> = None;
+    let mut state: Option<String> = None;
+    let mut provides = Vec::new();
+    let mut reader = TlvReader::new(bytes);
+    while let Some(field) = reader.next()? {
+        match field.tlv_type {
+            TLV_MODULE => {
+                if name.is_some() {
+                    return Err(ProtocolError::DuplicateField("module"));
+                }
+                name = Some(parse_string(field.value)?);
+            }
+            TLV_STATE => {
+                if state.is_some() {
+                    return Err(ProtocolError::DuplicateField("state"));
+                }
+                state = Some(parse_string(field.value)?);
+            }
+            TLV_SERVICE => provides.push(parse_string(field.value)?),
+            _ => {}
+        }
+    }
+    Ok(ModuleInfo {
+        name: name.ok_or(ProtocolError::MissingField("module"))?,
+        state: state.ok_or(ProtocolError::MissingField("state"))?,
+        provides,
+    })
+}
+
+fn decode_slot_row(bytes: &[u8]) -> Result<SlotInfo, ProtocolError> {
+    let mut name: Option<String> = None;
+    let mut flags: Option<u8> = None;
+    let mut providers = Vec::new();
+    let mut reader = TlvReader::new(bytes);
+    while let Some(field) = reader.next()? {
+        match field.tlv_type {
+            TLV_SLOT => {
+                if name.is_some() {
+                    return Err(ProtocolError::DuplicateField("slot"));
+                }
+                name = Some(parse_string(field.value)?);
+            }
+            TLV_FLAG => {
+                if flags.is_some() {
+                    return Err(ProtocolError::DuplicateField("flag"));
+                }
+                if field.value.len() != 1 {
+                    return Err(ProtocolError::InvalidLength("flag"));
+                }
+                flags = Some(field.value[0]);
+            }
+            TLV_MODULE => providers.push(parse_string(field.value)?),
+            _ => {}
+        }
+    }
+    let flags = flags.unwrap_or(0);
+    Ok(SlotInfo {
+        name: name.ok_or(ProtocolError::MissingField("slot"))?,
+        required: flags & FLAG_REQUIRED != 0,
+        locked: flags & FLAG_LOCKED != 0,
+        providers,
+    })
+}
+
+fn encode_ip(bytes: &mut Vec<u8>, command: &IpCommand) {
+    let (op, iface, address) = match command {
+        IpCommand::Show => (IP_OP_SHOW, None, None),
+        IpCommand::Stats(iface) => (IP_OP_STATS, iface.as_ref(), None),
+        IpCommand::LinkAdd(iface) => (IP_OP_LINK_ADD, Some(iface), None),
+        IpCommand::LinkDel(iface) => (IP_OP_LINK_DEL, Some(iface), None),
+        IpCommand::LinkSet { iface, up } => {
+            if *up {
+                write_tlv(bytes, TLV_FLAG, &[FLAG_UP]);
+            }
+            (IP_OP_LINK_SET, Some(iface), None)
+        }
+        IpCommand::LinkAddress { iface, mac } => (IP_OP_LINK_ADDRESS, Some(iface), Some(mac)),
+        IpCommand::AddrAdd { iface, cidr } => (IP_OP_ADDR_ADD, Some(iface), Some(cidr)),
+        IpCommand::AddrDel(iface) => (IP_OP_ADDR_DEL, Some(iface), None),
+    };
+    write_tlv(bytes, TLV_OP, &[op]);
+    if let Some(iface) = iface {
+        write_tlv(bytes, TLV_IFACE, iface.as_bytes());
+    }
+    if let Some(address) = address {
+        write_tlv(bytes, TLV_ADDRESS, address.as_bytes());
+    }
+}
+
+fn encode_route(bytes: &mut Vec<u8>, command: &RouteCommand) {
+    match command {
+        RouteCommand::Show => write_tlv(bytes, TLV_OP, &[ROUTE_OP_SHOW]),
+        RouteCommand::Add {
+            destination,
+            iface,
+            gateway,
+            metric,
+        } => {
+            write_tlv(bytes, TLV_OP, &[ROUTE_OP_ADD]);
+            write_tlv(bytes, TLV_ADDRESS, destination.as_bytes());
+            write_tlv(bytes, TLV_IFACE, iface.as_bytes());
+            if let Some(gateway) = gateway {
+                write_tlv(bytes, TLV_GATEWAY, gateway.as_bytes());
+            }
+            write_tlv(bytes, TLV_METRIC, &metric.to_le_bytes());
+        }
+        RouteCommand::Del(destination) => {
+            write_tlv(bytes, TLV_OP, &[ROUTE_OP_DEL]);
+            write_tlv(bytes, TLV_ADDRESS, destination.as_bytes());
+        }
+        RouteCommand::Get(addr) => {
+            write_tlv(bytes, TLV_OP, &[ROUTE_OP_GET]);
+            write_tlv(bytes, TLV_ADDRESS, addr.as_bytes());
+        }
+    }
+}
+
+fn decode_ip(
+    op: Option<u8>,
+    iface: Option<String>,
+    address: Option<String>,
+    flag: Option<u8>,
+) -> Result<IpCommand, ProtocolError> {
+    let missing_iface = ProtocolError::MissingField("iface");
+    let missing_address = ProtocolError::MissingField("address");
+    // Peers that predate typed subcommands send a bare `ip` to list interfaces.
+    Ok(match op.unwrap_or(IP_OP_SHOW) {
+        IP_OP_SHOW => IpCommand::Show,
+        IP_OP_STATS => IpCommand::Stats(iface),
+        IP_OP_LINK_ADD => IpCommand::LinkAdd(iface.ok_or(missing_iface)?),
+        IP_OP_LINK_DEL => IpCommand::LinkDel(iface.ok_or(missing_iface)?),
+        IP_OP_LINK_SET => IpCommand::LinkSet {
+            iface: iface.ok_or(missing_iface)?,
+            up: flag.map(|bits| bits & FLAG_UP != 0).unwrap_or(false),
+        },
+        IP_OP_LINK_ADDRESS => IpCommand::LinkAddress {
+            iface: iface.ok_or(missing_iface)?,
+            mac: address.ok_or(missing_address)?,
+        },
+        IP_OP_ADDR_ADD => IpCommand::AddrAdd {
+            iface: iface.ok_or(missing_iface)?,
+            cidr: address.ok_or(missing_address)?,
+        },
+        IP_OP_ADDR_DEL => IpCommand::AddrDel(iface.ok_or(missing_iface)?),
+        _ => return Err(ProtocolError::InvalidValue("op")),
+    })
+}
+
+fn decode_route(
+    op: Option<u8>,
+    iface: Option<String>,
+    address: Option<String>,
+    gateway: Option<String>,
+    metric: Option<u32>,
+) -> Result<RouteCommand, ProtocolError> {
+    let missing_address = ProtocolError::MissingField("address");
+    Ok(match op.unwrap_or(ROUTE_OP_SHOW) {
+        ROUTE_OP_SHOW => RouteCommand::Show,
+        ROUTE_OP_ADD => RouteCommand::Add {
+            destination: address.ok_or(missing_address)?,
+            iface: iface.ok_or(ProtocolError::MissingField("iface"))?,
+            gateway,
+            metric: metric.unwrap_or(0),
+        },
+        ROUTE_OP_DEL => RouteCommand::Del(address.ok_or(missing_address)?),
+        ROUTE_OP_GET => RouteCommand::Get(address.ok_or(missing_address)?),
+        _ => return Err(ProtocolError::InvalidValue("op")),
+    })
+}
+
+fn parse_string(value: &[u8]) -> Result<String, ProtocolError> {
+    let text = core::str::from_utf8(value).map_err(|_| ProtocolError::InvalidUtf8)?;
+    if text.is_empty() {
+        return Err(ProtocolError::InvalidValue("string"));
+    }
+    Ok(text.to_string())
+}
+
+/// Appends a correlation id to an encoded command or response.
+pub fn append_request_id(bytes: &mut Vec<u8>, id: u32) {
+    write_tlv(bytes, TLV_REQUEST_ID, &id.to_le_bytes());
+}
+
+/// Returns the correlation id of an encoded command or response, if any.
+pub fn read_request_id(bytes: &[u8]) -> Result<Option<u32>, ProtocolError> {
+    let mut id: Option<u32> = None;
+    let mut reader = TlvReader::new(bytes);
+    while let Some(field) = reader.next()? {
+        if field.tlv_type != TLV_REQUEST_ID {
+            continue;
+        }
+        if id.is_some() {
+            return Err(ProtocolError::DuplicateField("request_id"));
+        }
+        let raw: [u8; 4] = field
+            .value
+            .try_into()
+            .map_err(|_| ProtocolError::InvalidLength("request_id"))?;
+        id = Some(u32::from_le_bytes(raw));
+    }
+    Ok(id)
+}
+
+/// Returns true if a shell command may only be sent by an admin session.
+pub fn command_requires_admin(command: &ShellCommand) -> bool {
+    matches!(command, ShellCommand::Stop(_) | ShellCommand::UserAdd(_))
+}
+
+/// Returns true if a sender holding `credential` may run `command`.
+pub fn is_authorized(command: &ShellCommand, credential: Option<&Credential>) -> bool {
+    !command_requires_admin(command) || credential.is_some_and(|credential| credential.admin)
+}
+
+/// Client-side table of in-flight commands sharing one channel.
+///
+/// Every command is tagged with a fresh request id; responses are matched
+/// back by the id the service echoes. A command stays in flight while its
+/// responses are `Streaming`, so a `watch` can run beside other commands.
+#[derive(Debug, Clone, Default)]
+pub struct ShellMultiplexer {
+    next_id: u32,
+    pending: BTreeMap<u32, ShellCommand>,
+}
+
+impl ShellMultiplexer {
+    /// Creates a multiplexer with nothing in flight.
+    pub fn new() -> Self {
+        Self {
+            next_id: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Tags a command with a new request id and returns the id and payload.
+    pub fn send(&mut self, command: ShellCommand) -> (u32, Vec<u8>) {
+        loop {
+            self.next_id = self.next_id.wrapping_add(1);
+            if self.next_id != 0 && !self.pending.contains_key(&self.next_id) {
+                break;
+            }
+        }
+        let id = self.next_id;
+        let mut bytes = encode_command(&command);
+        append_request_id(&mut bytes, id);
+        self.pending.insert(id, command);
+        (id, bytes)
+    }
+
+    /// Matches a response to its command and returns the request id.
+    ///
+    /// The command leaves the table once a non-streaming response arrives.
+    pub fn receive(&mut self, bytes: &[u8]) -> Result<(u32, ShellResponse), ProtocolError> {
+        let id = read_request_id(bytes)?.ok_or(ProtocolError::MissingField("request_id"))?;
+        if !self.pending.contains_key(&id) {
+            return Err(ProtocolError::InvalidValue("request_id"));
+        }
+        let response = decode_response(bytes)?;
+        if response.status() != ShellStatus::Streaming {
+            self.pending.remove(&id);
+        }
+        Ok((id, response))
+    }
+
+    /// Returns the command still waiting on `id`.
+    pub fn command(&self, id: u32) -> Option<&ShellCommand> {
+        self.pending.get(&id)
+    }
+
+    /// Returns the ids still in flight, oldest first.
+    pub fn pending(&self) -> Vec<u32> {
+        self.pending.keys().copied().collect()
+    }
+
+    /// Stops tracking a command, e.g. after interrupting a stream.
+    pub fn cancel(&mut self, id: u32) -> Option<ShellCommand> {
+        self.pending.remove(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn encode_decode_start_command() {
@@ -717,35 +1907,155 @@ mod tests {
     }
 
     #[test]
-    fn encode_decode_ip_command() {
-        let cmd = ShellCommand::Ip(Some("add eth0".to_string()));
+    fn encode_decode_piece_runtime_command() {
+        let cmd = ShellCommand::PieceRuntime("fs-service".to_string());
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
         assert_eq!(decoded, cmd);
     }
 
     #[test]
-    fn encode_decode_ip_command_no_args() {
-        let cmd = ShellCommand::Ip(None);
+    fn encode_decode_clip_command() {
+        let cmd = ShellCommand::Clip(None);
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+
+        let cmd = ShellCommand::Clip(Some("ls /etc".to_string()));
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
         assert_eq!(decoded, cmd);
     }
 
     #[test]
-    fn encode_decode_route_command() {
-        let cmd = ShellCommand::Route(Some("add default eth0".to_string()));
+    fn encode_decode_time_command() {
+        let cmd = ShellCommand::Time("ls /etc".to_string());
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
         assert_eq!(decoded, cmd);
     }
 
     #[test]
-    fn encode_decode_route_command_no_args() {
-        let cmd = ShellCommand::Route(None);
+    fn encode_decode_metrics_command() {
+        let cmd = ShellCommand::Metrics(None);
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
         assert_eq!(decoded, cmd);
+
+        let cmd = ShellCommand::Metrics(Some("on".to_string()));
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_ip_commands() {
+        let iface = || "eth0".to_string();
+        for ip in [
+            IpCommand::Show,
+            IpCommand::Stats(None),
+            IpCommand::Stats(Some(iface())),
+            IpCommand::LinkAdd(iface()),
+            IpCommand::LinkDel(iface()),
+            IpCommand::LinkSet {
+                iface: iface(),
+                up: true,
+            },
+            IpCommand::LinkSet {
+                iface: iface(),
+                up: false,
+            },
+            IpCommand::LinkAddress {
+                iface: iface(),
+                mac: "02:00:00:00:00:01".to_string(),
+            },
+            IpCommand::AddrAdd {
+                iface: iface(),
+                cidr: "10.0.0.2/24".to_string(),
+            },
+            IpCommand::AddrDel(iface()),
+        ] {
+            let cmd = ShellCommand::Ip(ip);
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
+    }
+
+    #[test]
+    fn encode_decode_route_commands() {
+        for route in [
+            RouteCommand::Show,
+            RouteCommand::Add {
+                destination: "default".to_string(),
+                iface: "eth0".to_string(),
+                gateway: Some("10.0.0.1".to_string()),
+                metric: 5,
+            },
+            RouteCommand::Add {
+                destination: "10.1.0.0/16".to_string(),
+                iface: "eth0".to_string(),
+                gateway: None,
+                metric: 0,
+            },
+            RouteCommand::Del("default".to_string()),
+            RouteCommand::Get("10.1.2.3".to_string()),
+        ] {
+            let cmd = ShellCommand::Route(route);
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
+    }
+
+    #[test]
+    fn decode_ip_without_op_lists_interfaces() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_IP]);
+        assert_eq!(
+            decode_command(&bytes),
+            Ok(ShellCommand::Ip(IpCommand::Show))
+        );
+    }
+
+    #[test]
+    fn decode_ip_and_route_reject_bad_fields() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_IP]);
+        write_tlv(&mut bytes, TLV_OP, &[IP_OP_ADDR_ADD]);
+        write_tlv(&mut bytes, TLV_IFACE, b"eth0");
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::MissingField("address"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_IP]);
+        write_tlv(&mut bytes, TLV_OP, &[99]);
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::InvalidValue("op"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_ROUTE]);
+        write_tlv(&mut bytes, TLV_OP, &[ROUTE_OP_ADD]);
+        write_tlv(&mut bytes, TLV_ADDRESS, b"default");
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::MissingField("iface"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_ROUTE]);
+        write_tlv(&mut bytes, TLV_OP, &[ROUTE_OP_ADD]);
+        write_tlv(&mut bytes, TLV_ADDRESS, b"default");
+        write_tlv(&mut bytes, TLV_IFACE, b"eth0");
+        write_tlv(&mut bytes, TLV_METRIC, &[1, 0]);
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::InvalidLength("metric"))
+        );
     }
 
     #[test]
@@ -766,7 +2076,10 @@ mod tests {
 
     #[test]
     fn encode_decode_df_command() {
-        let cmd = ShellCommand::Df(Some("/".to_string()));
+        let cmd = ShellCommand::Df {
+            path: Some("/".to_string()),
+            human: true,
+        };
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
         assert_eq!(decoded, cmd);
@@ -774,7 +2087,10 @@ mod tests {
 
     #[test]
     fn encode_decode_df_command_no_path() {
-        let cmd = ShellCommand::Df(None);
+        let cmd = ShellCommand::Df {
+            path: None,
+            human: false,
+        };
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
         assert_eq!(decoded, cmd);
@@ -782,10 +2098,18 @@ mod tests {
 
     #[test]
     fn encode_decode_du_command() {
-        let cmd = ShellCommand::Du("/etc".to_string());
+        let cmd = ShellCommand::Du {
+            path: "/etc".to_string(),
+            human: false,
+        };
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
         assert_eq!(decoded, cmd);
+        let human = ShellCommand::Du {
+            path: "/etc".to_string(),
+            human: true,
+        };
+        assert_eq!(decode_command(&encode_command(&human)), Ok(human));
     }
 
     #[test]
@@ -806,20 +2130,58 @@ mod tests {
 
     #[test]
     fn encode_decode_remove_command() {
-        let cmd = ShellCommand::Remove("fs-service".to_string());
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        for force in [false, true] {
+            let cmd = ShellCommand::Remove {
+                module: "fs-service".to_string(),
+                force,
+            };
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
     }
 
     #[test]
     fn encode_decode_log_tail_command() {
-        let cmd = ShellCommand::LogTail;
+        let cmd = ShellCommand::LogTail {
+            follow: false,
+            lines: None,
+        };
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
         assert_eq!(decoded, cmd);
     }
 
+    #[test]
+    fn encode_decode_log_tail_follow_command() {
+        let cmd = ShellCommand::LogTail {
+            follow: true,
+            lines: Some(25),
+        };
+        let bytes = encode_command(&cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
+    }
+
+    #[test]
+    fn decode_log_tail_rejects_invalid_lines() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LOG_TAIL]);
+        write_tlv(&mut bytes, TLV_LINES, &[0x01]);
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::InvalidLength("lines"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LOG_TAIL]);
+        write_tlv(&mut bytes, TLV_LINES, &1u32.to_le_bytes());
+        write_tlv(&mut bytes, TLV_LINES, &2u32.to_le_bytes());
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::DuplicateField("lines"))
+        );
+    }
+
     #[test]
     fn encode_decode_help_command_without_topic() {
         let cmd = ShellCommand::Help(None);
@@ -853,137 +2215,462 @@ mod tests {
     }
 
     #[test]
-    fn encode_decode_whoami_command() {
-        let cmd = ShellCommand::Whoami;
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+    fn encode_decode_whoami_command() {
+        let cmd = ShellCommand::Whoami;
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_users_command() {
+        let cmd = ShellCommand::Users;
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_useradd_command() {
+        let cmd = ShellCommand::UserAdd("guest".to_string());
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_pwd_command() {
+        let cmd = ShellCommand::Pwd;
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_ls_command_with_path() {
+        let cmd = ShellCommand::Ls(Some("/".to_string()));
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_ls_command_without_path() {
+        let cmd = ShellCommand::Ls(None);
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_cd_command() {
+        let cmd = ShellCommand::Cd("/home".to_string());
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_mkdir_command() {
+        let cmd = ShellCommand::Mkdir("/etc".to_string());
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_touch_command() {
+        let cmd = ShellCommand::Touch("/tmp/notes.txt".to_string());
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_cat_command() {
+        let cmd = ShellCommand::Cat("/etc/hostname".to_string());
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_wc_command() {
+        let cmd = ShellCommand::Wc("/etc/hostname".to_string());
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_find_command() {
+        let cmd = ShellCommand::Find {
+            path: "/home".to_string(),
+            name: Some("*.txt".to_string()),
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+
+        let cmd = ShellCommand::Find {
+            path: "/".to_string(),
+            name: None,
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_edit_command() {
+        let cmd = ShellCommand::Edit("/etc/hostname".to_string());
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_write_command() {
+        let cmd = ShellCommand::Write {
+            path: "/etc/hostname".to_string(),
+            contents: "ruzzle".to_string(),
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_cp_command() {
+        let cmd = ShellCommand::Cp {
+            src: "/etc/hostname".to_string(),
+            dst: "/etc/hostname.bak".to_string(),
+            recursive: false,
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_cp_command_recursive() {
+        let cmd = ShellCommand::Cp {
+            src: "/etc".to_string(),
+            dst: "/backup/etc".to_string(),
+            recursive: true,
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_mv_command() {
+        let cmd = ShellCommand::Mv {
+            src: "/etc/hostname".to_string(),
+            dst: "/etc/hostname.old".to_string(),
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_ln_command() {
+        let cmd = ShellCommand::Ln {
+            target: "/etc/hostname".to_string(),
+            link: "/hostname".to_string(),
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_chsh_command() {
+        let cmd = ShellCommand::Chsh {
+            user: Some("guest".to_string()),
+            shell: Some("/bin/fish".to_string()),
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+
+        let cmd = ShellCommand::Chsh {
+            user: None,
+            shell: None,
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_chmod_chown_commands() {
+        let cmd = ShellCommand::Chmod {
+            mode: "644".to_string(),
+            path: "/etc/hostname".to_string(),
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+
+        let cmd = ShellCommand::Chown {
+            user: "guest".to_string(),
+            path: "/home/guest".to_string(),
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_gpu_command() {
+        for cmd in [
+            ShellCommand::Gpu(Some("save /t.bin".to_string())),
+            ShellCommand::Gpu(None),
+        ] {
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
+    }
+
+    #[test]
+    fn encode_decode_watch_command() {
+        let cmd = ShellCommand::Watch {
+            interval_secs: 5,
+            command: "slots".to_string(),
+        };
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_container_command() {
+        for cmd in [
+            ShellCommand::Container(Some("checkpoint web".to_string())),
+            ShellCommand::Container(None),
+        ] {
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
+    }
+
+    #[test]
+    fn encode_decode_http_command() {
+        for cmd in [
+            ShellCommand::Http(Some("top 3".to_string())),
+            ShellCommand::Http(None),
+        ] {
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
+    }
+
+    #[test]
+    fn encode_decode_echo_command() {
+        for cmd in [
+            ShellCommand::Echo("status 0".to_string()),
+            ShellCommand::Echo(String::new()),
+        ] {
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
+    }
+
+    #[test]
+    fn encode_decode_shutdown_command() {
+        let cmd = ShellCommand::Shutdown;
+        let bytes = encode_command(&cmd);
+        let decoded = decode_command(&bytes).expect("decode should succeed");
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn encode_decode_set_command() {
+        for cmd in [
+            ShellCommand::Set(Some("color=on".to_string())),
+            ShellCommand::Set(None),
+        ] {
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
+    }
+
+    #[test]
+    fn encode_decode_webconsole_command() {
+        for cmd in [
+            ShellCommand::WebConsole(Some("send slots".to_string())),
+            ShellCommand::WebConsole(None),
+        ] {
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
     }
 
     #[test]
-    fn encode_decode_users_command() {
-        let cmd = ShellCommand::Users;
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+    fn encode_decode_log_level_command() {
+        for cmd in [
+            ShellCommand::LogLevel(Some("net-service error".to_string())),
+            ShellCommand::LogLevel(None),
+        ] {
+            let bytes = encode_command(&cmd);
+            assert_eq!(decode_command(&bytes), Ok(cmd));
+        }
     }
 
     #[test]
-    fn encode_decode_useradd_command() {
-        let cmd = ShellCommand::UserAdd("guest".to_string());
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+    fn encode_decode_notifications_command() {
+        for clear in [true, false] {
+            let cmd = ShellCommand::Notifications { clear };
+            let bytes = encode_command(&cmd);
+            assert_eq!(decode_command(&bytes), Ok(cmd));
+        }
     }
 
     #[test]
-    fn encode_decode_pwd_command() {
-        let cmd = ShellCommand::Pwd;
+    fn encode_decode_stat_command() {
+        let cmd = ShellCommand::Stat("/etc/hosts".to_string());
         let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
     }
 
     #[test]
-    fn encode_decode_ls_command_with_path() {
-        let cmd = ShellCommand::Ls(Some("/".to_string()));
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+    fn encode_decode_apply_command() {
+        for dry_run in [true, false] {
+            let cmd = ShellCommand::Apply {
+                path: "/etc/system.conf".to_string(),
+                dry_run,
+            };
+            let bytes = encode_command(&cmd);
+            assert_eq!(decode_command(&bytes), Ok(cmd));
+        }
     }
 
     #[test]
-    fn encode_decode_ls_command_without_path() {
-        let cmd = ShellCommand::Ls(None);
+    fn encode_decode_checksum_command() {
+        let cmd = ShellCommand::Checksum("/etc/*.conf".to_string());
         let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
     }
 
     #[test]
-    fn encode_decode_cd_command() {
-        let cmd = ShellCommand::Cd("/home".to_string());
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+    fn encode_decode_autoplug_command() {
+        for dry_run in [false, true] {
+            let cmd = ShellCommand::Autoplug { dry_run };
+            let bytes = encode_command(&cmd);
+            assert_eq!(decode_command(&bytes), Ok(cmd));
+        }
     }
 
     #[test]
-    fn encode_decode_mkdir_command() {
-        let cmd = ShellCommand::Mkdir("/etc".to_string());
+    fn encode_decode_piece_check_all_command() {
+        let cmd = ShellCommand::PieceCheckAll;
         let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
     }
 
     #[test]
-    fn encode_decode_touch_command() {
-        let cmd = ShellCommand::Touch("/tmp/notes.txt".to_string());
+    fn encode_decode_status_command() {
+        let cmd = ShellCommand::Status("net-manager".to_string());
         let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_STATUS]);
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::MissingField("module"))
+        );
     }
 
     #[test]
-    fn encode_decode_cat_command() {
-        let cmd = ShellCommand::Cat("/etc/hostname".to_string());
+    fn encode_decode_boot_report_command() {
+        let cmd = ShellCommand::BootReport;
         let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
     }
 
     #[test]
-    fn encode_decode_edit_command() {
-        let cmd = ShellCommand::Edit("/etc/hostname".to_string());
+    fn encode_decode_resolve_command() {
+        let cmd = ShellCommand::Resolve("ruzzle.dev".to_string());
         let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
     }
 
     #[test]
-    fn encode_decode_write_command() {
-        let cmd = ShellCommand::Write {
-            path: "/etc/hostname".to_string(),
-            contents: "ruzzle".to_string(),
-        };
+    fn encode_decode_ping_command() {
+        let cmd = ShellCommand::Ping("-c 2 10.0.2.2".to_string());
         let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
     }
 
     #[test]
-    fn encode_decode_cp_command() {
-        let cmd = ShellCommand::Cp {
-            src: "/etc/hostname".to_string(),
-            dst: "/etc/hostname.bak".to_string(),
-            recursive: false,
-        };
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+    fn encode_decode_fw_command() {
+        for cmd in [
+            ShellCommand::Fw(None),
+            ShellCommand::Fw(Some("add deny in tcp port 22".to_string())),
+        ] {
+            let bytes = encode_command(&cmd);
+            assert_eq!(decode_command(&bytes), Ok(cmd));
+        }
     }
 
     #[test]
-    fn encode_decode_cp_command_recursive() {
-        let cmd = ShellCommand::Cp {
-            src: "/etc".to_string(),
-            dst: "/backup/etc".to_string(),
-            recursive: true,
-        };
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+    fn encode_decode_dhcpd_command() {
+        for cmd in [
+            ShellCommand::Dhcpd(None),
+            ShellCommand::Dhcpd(Some("start eth0 10.0.2.100 10.0.2.199".to_string())),
+        ] {
+            let bytes = encode_command(&cmd);
+            assert_eq!(decode_command(&bytes), Ok(cmd));
+        }
     }
 
     #[test]
-    fn encode_decode_mv_command() {
-        let cmd = ShellCommand::Mv {
-            src: "/etc/hostname".to_string(),
-            dst: "/etc/hostname.old".to_string(),
+    fn shell_hello_negotiates_with_itself() {
+        assert_eq!(
+            answer_shell_hello(&shell_hello()),
+            HelloReply::Accepted {
+                version: SHELL_PROTOCOL_VERSION
+            }
+        );
+        let newer = Hello {
+            protocol: SHELL_PROTOCOL.to_string(),
+            versions: VersionRange::new(SHELL_PROTOCOL_VERSION + 1, SHELL_PROTOCOL_VERSION + 2),
         };
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(
+            answer_shell_hello(&newer),
+            HelloReply::Rejected {
+                protocol: SHELL_PROTOCOL.to_string(),
+                versions: SHELL_VERSIONS,
+            }
+        );
+    }
+
+    #[test]
+    fn shell_description_covers_every_message() {
+        let description = describe_shell();
+        assert_eq!(description.protocol, SHELL_PROTOCOL);
+        assert_eq!(description.version, SHELL_PROTOCOL_VERSION);
+        for (index, (msg_type, _)) in SHELL_COMMANDS.iter().enumerate() {
+            assert_eq!(*msg_type as usize, index + 1);
+            let mut bytes = Vec::new();
+            write_tlv(&mut bytes, TLV_MSG_TYPE, &[*msg_type]);
+            assert_ne!(
+                decode_command(&bytes),
+                Err(ProtocolError::UnknownMessageType(*msg_type))
+            );
+        }
+        assert!(description.missing(&[MSG_PS, MSG_WEBCONSOLE]).is_empty());
+        assert!(!description.supports(SHELL_COMMANDS.len() as u8 + 1));
     }
 
     #[test]
@@ -996,18 +2683,25 @@ mod tests {
 
     #[test]
     fn encode_decode_rmr_command() {
-        let cmd = ShellCommand::RmRecursive("/var/tmp".to_string());
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        for force in [false, true] {
+            let cmd = ShellCommand::RmRecursive {
+                path: "/var/tmp".to_string(),
+                force,
+            };
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
     }
 
     #[test]
     fn encode_decode_slots_command() {
-        let cmd = ShellCommand::Slots;
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        for verbose in [false, true] {
+            let cmd = ShellCommand::Slots { verbose };
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
     }
 
     #[test]
@@ -1017,6 +2711,7 @@ mod tests {
             module: "console-service".to_string(),
             dry_run: false,
             swap: false,
+            override_lock: false,
         };
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
@@ -1030,6 +2725,7 @@ mod tests {
             module: "console-service".to_string(),
             dry_run: true,
             swap: false,
+            override_lock: false,
         };
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
@@ -1043,6 +2739,7 @@ mod tests {
             module: "console-service".to_string(),
             dry_run: false,
             swap: true,
+            override_lock: false,
         };
         let bytes = encode_command(&cmd);
         let decoded = decode_command(&bytes).expect("decode should succeed");
@@ -1051,18 +2748,39 @@ mod tests {
 
     #[test]
     fn encode_decode_unplug_command() {
-        let cmd = ShellCommand::Unplug("ruzzle.slot.console@1".to_string());
+        for (force, override_lock) in [(false, false), (true, false), (false, true), (true, true)] {
+            let cmd = ShellCommand::Unplug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                force,
+                override_lock,
+            };
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
+    }
+
+    #[test]
+    fn encode_decode_plug_command_override() {
+        let cmd = ShellCommand::Plug {
+            slot: "ruzzle.slot.shell@1".to_string(),
+            module: "zsh".to_string(),
+            dry_run: true,
+            swap: true,
+            override_lock: true,
+        };
         let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        assert_eq!(decode_command(&bytes), Ok(cmd));
     }
 
     #[test]
     fn encode_decode_graph_command() {
-        let cmd = ShellCommand::Graph;
-        let bytes = encode_command(&cmd);
-        let decoded = decode_command(&bytes).expect("decode should succeed");
-        assert_eq!(decoded, cmd);
+        for dot in [false, true] {
+            let cmd = ShellCommand::Graph { dot };
+            let bytes = encode_command(&cmd);
+            let decoded = decode_command(&bytes).expect("decode should succeed");
+            assert_eq!(decoded, cmd);
+        }
     }
 
     #[test]
@@ -1135,6 +2853,38 @@ mod tests {
         assert_eq!(result, Err(ProtocolError::MissingField("module")));
     }
 
+    #[test]
+    fn decode_command_rejects_missing_module_for_piece_runtime() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_PIECE_RUNTIME]);
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::MissingField("module")));
+    }
+
+    #[test]
+    fn decode_command_rejects_missing_args_for_time() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_TIME]);
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::MissingField("args")));
+    }
+
+    #[test]
+    fn decode_command_rejects_missing_path_for_wc() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WC]);
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::MissingField("path")));
+    }
+
+    #[test]
+    fn decode_command_rejects_missing_path_for_find() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_FIND]);
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::MissingField("path")));
+    }
+
     #[test]
     fn decode_command_rejects_missing_user_for_login() {
         let mut bytes = Vec::new();
@@ -1226,6 +2976,56 @@ mod tests {
         assert_eq!(result, Err(ProtocolError::MissingField("dst")));
     }
 
+    #[test]
+    fn decode_command_rejects_missing_fields_for_ln() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LN]);
+        write_tlv(&mut bytes, TLV_DST, b"/hostname");
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::MissingField("src")));
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_LN]);
+        write_tlv(&mut bytes, TLV_SRC, b"/etc/hostname");
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::MissingField("dst")));
+    }
+
+    #[test]
+    fn decode_command_rejects_missing_fields_for_chmod_chown() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CHMOD]);
+        write_tlv(&mut bytes, TLV_PATH, b"/etc");
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::MissingField("args"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CHMOD]);
+        write_tlv(&mut bytes, TLV_ARGS, b"644");
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::MissingField("path"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CHOWN]);
+        write_tlv(&mut bytes, TLV_PATH, b"/etc");
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::MissingField("user"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_CHOWN]);
+        write_tlv(&mut bytes, TLV_USER, b"guest");
+        assert_eq!(
+            decode_command(&bytes),
+            Err(ProtocolError::MissingField("path"))
+        );
+    }
+
     #[test]
     fn decode_command_rejects_missing_src_for_mv() {
         let mut bytes = Vec::new();
@@ -1357,9 +3157,9 @@ mod tests {
     #[test]
     fn decode_command_rejects_unknown_type() {
         let mut bytes = Vec::new();
-        write_tlv(&mut bytes, TLV_MSG_TYPE, &[0x42]);
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[0xfe]);
         let result = decode_command(&bytes);
-        assert_eq!(result, Err(ProtocolError::UnknownMessageType(0x42)));
+        assert_eq!(result, Err(ProtocolError::UnknownMessageType(0xfe)));
     }
 
     #[test]
@@ -1442,6 +3242,50 @@ mod tests {
         assert_eq!(result, Err(ProtocolError::DuplicateField("args")));
     }
 
+    #[test]
+    fn decode_command_rejects_duplicate_pattern() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_FIND]);
+        write_tlv(&mut bytes, TLV_PATTERN, b"*.txt");
+        write_tlv(&mut bytes, TLV_PATTERN, b"*.md");
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::DuplicateField("pattern")));
+    }
+
+    #[test]
+    fn decode_command_rejects_duplicate_interval() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WATCH]);
+        write_tlv(&mut bytes, TLV_INTERVAL, &1u32.to_le_bytes());
+        write_tlv(&mut bytes, TLV_INTERVAL, &2u32.to_le_bytes());
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::DuplicateField("interval")));
+    }
+
+    #[test]
+    fn decode_command_rejects_invalid_interval_length() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WATCH]);
+        write_tlv(&mut bytes, TLV_INTERVAL, &[0x01]);
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::InvalidLength("interval")));
+    }
+
+    #[test]
+    fn decode_command_rejects_missing_watch_fields() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WATCH]);
+        write_tlv(&mut bytes, TLV_ARGS, b"ps");
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::MissingField("interval")));
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_MSG_TYPE, &[MSG_WATCH]);
+        write_tlv(&mut bytes, TLV_INTERVAL, &2u32.to_le_bytes());
+        let result = decode_command(&bytes);
+        assert_eq!(result, Err(ProtocolError::MissingField("args")));
+    }
+
     #[test]
     fn decode_command_rejects_invalid_utf8_module() {
         let mut bytes = Vec::new();
@@ -1556,7 +3400,7 @@ mod tests {
 
     #[test]
     fn encode_decode_response_roundtrip() {
-        let response = ShellResponse {
+        let response = ShellResponse::Text {
             status: ShellStatus::Ok,
             text: "ok".to_string(),
         };
@@ -1565,9 +3409,19 @@ mod tests {
         assert_eq!(decoded, response);
     }
 
+    #[test]
+    fn encode_decode_streaming_response() {
+        let response = ShellResponse::Text {
+            status: ShellStatus::Streaming,
+            text: "[INFO][0] boot".to_string(),
+        };
+        let bytes = encode_response(&response);
+        assert_eq!(decode_response(&bytes), Ok(response));
+    }
+
     #[test]
     fn encode_decode_failed_response() {
-        let response = ShellResponse {
+        let response = ShellResponse::Text {
             status: ShellStatus::Failed,
             text: "nope".to_string(),
         };
@@ -1576,6 +3430,159 @@ mod tests {
         assert_eq!(decoded, response);
     }
 
+    #[test]
+    fn multiplexer_matches_interleaved_responses() {
+        let mut mux = ShellMultiplexer::new();
+        let watch = ShellCommand::Watch {
+            interval_secs: 2,
+            command: "lsmod".to_string(),
+        };
+        let (watch_id, bytes) = mux.send(watch.clone());
+        assert_eq!(read_request_id(&bytes), Ok(Some(watch_id)));
+        assert_eq!(decode_command(&bytes), Ok(watch.clone()));
+        let (lsmod_id, _) = mux.send(ShellCommand::Lsmod);
+        assert_ne!(watch_id, lsmod_id);
+        assert_eq!(mux.pending(), vec![watch_id, lsmod_id]);
+
+        let reply = |id, status, text: &str| {
+            let mut bytes = encode_response(&ShellResponse::Text {
+                status,
+                text: text.to_string(),
+            });
+            append_request_id(&mut bytes, id);
+            bytes
+        };
+        let (id, _) = mux
+            .receive(&reply(watch_id, ShellStatus::Streaming, "tick"))
+            .unwrap();
+        assert_eq!(id, watch_id);
+        let (id, response) = mux
+            .receive(&reply(lsmod_id, ShellStatus::Ok, "modules:"))
+            .unwrap();
+        assert_eq!(id, lsmod_id);
+        assert_eq!(response.status(), ShellStatus::Ok);
+        assert_eq!(mux.pending(), vec![watch_id]);
+        assert_eq!(mux.command(watch_id), Some(&watch));
+
+        assert_eq!(
+            mux.receive(&reply(lsmod_id, ShellStatus::Ok, "late")),
+            Err(ProtocolError::InvalidValue("request_id"))
+        );
+        let untagged = encode_response(&ShellResponse::Text {
+            status: ShellStatus::Ok,
+            text: String::new(),
+        });
+        assert_eq!(
+            mux.receive(&untagged),
+            Err(ProtocolError::MissingField("request_id"))
+        );
+        assert_eq!(mux.cancel(watch_id), Some(watch));
+        assert!(mux.pending().is_empty());
+    }
+
+    #[test]
+    fn read_request_id_rejects_malformed_ids() {
+        let mut bytes = encode_command(&ShellCommand::Lsmod);
+        write_tlv(&mut bytes, TLV_REQUEST_ID, &[1, 2]);
+        assert_eq!(
+            read_request_id(&bytes),
+            Err(ProtocolError::InvalidLength("request_id"))
+        );
+        let mut bytes = encode_command(&ShellCommand::Lsmod);
+        append_request_id(&mut bytes, 1);
+        append_request_id(&mut bytes, 2);
+        assert_eq!(
+            read_request_id(&bytes),
+            Err(ProtocolError::DuplicateField("request_id"))
+        );
+    }
+
+    #[test]
+    fn encode_decode_typed_responses() {
+        let responses = [
+            ShellResponse::Modules(vec![
+                ModuleInfo {
+                    name: "fs-service".to_string(),
+                    state: "running".to_string(),
+                    provides: vec!["ruzzle.fs".to_string(), "ruzzle.fs.watch".to_string()],
+                },
+                ModuleInfo {
+                    name: "gpu-service".to_string(),
+                    state: "stopped".to_string(),
+                    provides: vec![],
+                },
+            ]),
+            ShellResponse::Modules(vec![]),
+            ShellResponse::Slots(vec![SlotInfo {
+                name: "ruzzle.slot.shell@1".to_string(),
+                required: true,
+                locked: true,
+                providers: vec!["tui-shell".to_string()],
+            }]),
+            ShellResponse::Error {
+                code: ShellErrorCode::NotFound,
+                message: "cat error: NotFound".to_string(),
+            },
+        ];
+        for response in responses {
+            let bytes = encode_response(&response);
+            assert_eq!(decode_response(&bytes), Ok(response));
+        }
+    }
+
+    #[test]
+    fn shell_error_codes_are_stable() {
+        for value in 1..=11 {
+            let code = ShellErrorCode::from_u16(value).unwrap();
+            assert_eq!(code.as_u16(), value);
+            assert!(!code.label().is_empty());
+        }
+        assert_eq!(ShellErrorCode::NotFound.as_u16(), 3);
+        assert_eq!(
+            ShellErrorCode::from_u16(0),
+            Err(ProtocolError::InvalidValue("error_code"))
+        );
+    }
+
+    #[test]
+    fn decode_response_rejects_invalid_typed_payloads() {
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_STATUS, &[ShellStatus::Ok.as_u8()]);
+        write_tlv(&mut bytes, TLV_ERROR_CODE, &3u16.to_le_bytes());
+        write_tlv(&mut bytes, TLV_TEXT, b"missing");
+        assert_eq!(
+            decode_response(&bytes),
+            Err(ProtocolError::InvalidValue("status"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_STATUS, &[ShellStatus::Ok.as_u8()]);
+        write_tlv(&mut bytes, TLV_KIND, &[9]);
+        assert_eq!(
+            decode_response(&bytes),
+            Err(ProtocolError::InvalidValue("kind"))
+        );
+
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_STATUS, &[ShellStatus::Ok.as_u8()]);
+        write_tlv(&mut bytes, TLV_ROW, &[]);
+        assert_eq!(
+            decode_response(&bytes),
+            Err(ProtocolError::MissingField("kind"))
+        );
+
+        let mut row = Vec::new();
+        write_tlv(&mut row, TLV_MODULE, b"fs-service");
+        let mut bytes = Vec::new();
+        write_tlv(&mut bytes, TLV_STATUS, &[ShellStatus::Ok.as_u8()]);
+        write_tlv(&mut bytes, TLV_KIND, &[KIND_MODULES]);
+        write_tlv(&mut bytes, TLV_ROW, &row);
+        assert_eq!(
+            decode_response(&bytes),
+            Err(ProtocolError::MissingField("state"))
+        );
+    }
+
     #[test]
     fn decode_response_rejects_missing_fields() {
         let result = decode_response(&[]);
@@ -1664,10 +3671,27 @@ mod tests {
         let result = decode_response(&bytes).expect("decode should succeed");
         assert_eq!(
             result,
-            ShellResponse {
+            ShellResponse::Text {
                 status: ShellStatus::Ok,
                 text: "ok".to_string()
             }
         );
     }
+
+    #[test]
+    fn admin_commands_require_an_admin_credential() {
+        let guest = Credential::new("guest", false);
+        let root = Credential::new("root", true);
+        for command in [
+            ShellCommand::Stop("net-manager".to_string()),
+            ShellCommand::UserAdd("alice".to_string()),
+        ] {
+            assert!(command_requires_admin(&command));
+            assert!(!is_authorized(&command, None));
+            assert!(!is_authorized(&command, Some(&guest)));
+            assert!(is_authorized(&command, Some(&root)));
+        }
+        assert!(!command_requires_admin(&ShellCommand::Lsmod));
+        assert!(is_authorized(&ShellCommand::Lsmod, None));
+    }
 }
@@ -0,0 +1,89 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Size of one device block in bytes (a virtio-blk sector).
+pub const BLOCK_SIZE: usize = 512;
+
+/// Errors reported by a block device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockError {
+    OutOfRange,
+    Io,
+}
+
+/// Fixed-size block storage that a `BlockFs` is laid out on.
+pub trait BlockDevice {
+    /// Returns the number of addressable blocks.
+    fn block_count(&self) -> u64;
+    /// Reads one block into `buf`.
+    fn read_block(&self, index: u64, buf: &mut [u8; BLOCK_SIZE]) -> Result<(), BlockError>;
+    /// Writes one block from `data`.
+    fn write_block(&mut self, index: u64, data: &[u8; BLOCK_SIZE]) -> Result<(), BlockError>;
+}
+
+/// RAM-backed block device, used as a RAM disk and in tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemBlockDevice {
+    blocks: Vec<[u8; BLOCK_SIZE]>,
+}
+
+impl MemBlockDevice {
+    /// Creates a zero-filled device with `count` blocks.
+    pub fn new(count: usize) -> Self {
+        Self {
+            blocks: vec![[0; BLOCK_SIZE]; count],
+        }
+    }
+}
+
+impl BlockDevice for MemBlockDevice {
+    fn block_count(&self) -> u64 {
+        self.blocks.len() as u64
+    }
+
+    fn read_block(&self, index: u64, buf: &mut [u8; BLOCK_SIZE]) -> Result<(), BlockError> {
+        let block = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.blocks.get(index))
+            .ok_or(BlockError::OutOfRange)?;
+        buf.copy_from_slice(block);
+        Ok(())
+    }
+
+    fn write_block(&mut self, index: u64, data: &[u8; BLOCK_SIZE]) -> Result<(), BlockError> {
+        let block = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.blocks.get_mut(index))
+            .ok_or(BlockError::OutOfRange)?;
+        block.copy_from_slice(data);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mem_device_reads_back_writes() {
+        let mut device = MemBlockDevice::new(2);
+        assert_eq!(device.block_count(), 2);
+        device.write_block(1, &[7; BLOCK_SIZE]).unwrap();
+        let mut buf = [0; BLOCK_SIZE];
+        device.read_block(1, &mut buf).unwrap();
+        assert_eq!(buf, [7; BLOCK_SIZE]);
+        device.read_block(0, &mut buf).unwrap();
+        assert_eq!(buf, [0; BLOCK_SIZE]);
+    }
+
+    #[test]
+    fn mem_device_rejects_out_of_range_blocks() {
+        let mut device = MemBlockDevice::new(1);
+        let mut buf = [0; BLOCK_SIZE];
+        assert_eq!(device.read_block(1, &mut buf), Err(BlockError::OutOfRange));
+        assert_eq!(
+            device.write_block(u64::MAX, &buf),
+            Err(BlockError::OutOfRange)
+        );
+    }
+}
@@ -0,0 +1,1332 @@
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use user_file_manager::Fs;
+use user_fs_service::{
+    crc32, glob_match, is_glob_pattern, DirEntry, FileKind, FsError, FsStats, WalkEntry,
+    DEFAULT_DIR_MODE, DEFAULT_FILE_MODE, SYMLINK_HOP_LIMIT,
+};
+
+mod device;
+
+pub use device::{BlockDevice, BlockError, MemBlockDevice, BLOCK_SIZE};
+
+/// Magic bytes at the start of the superblock.
+pub const BLOCKFS_MAGIC: &[u8; 4] = b"RZBK";
+/// On-disk format version written by `format`.
+pub const BLOCKFS_VERSION: u16 = 1;
+/// Smallest device, in blocks, that `format` accepts.
+pub const MIN_BLOCKS: u64 = 16;
+/// Longest entry name a directory record can hold.
+pub const MAX_NAME_LEN: usize = 27;
+/// Largest file size in blocks (direct pointers plus one indirect block).
+pub const MAX_FILE_BLOCKS: usize = DIRECT_BLOCKS + BLOCK_SIZE / 4;
+
+const DIRECT_BLOCKS: usize = 12;
+const INODE_SIZE: usize = 64;
+const INODES_PER_BLOCK: usize = BLOCK_SIZE / INODE_SIZE;
+const DIRENT_SIZE: usize = 32;
+/// Deepest directory nesting the recursive walks follow before treating the
+/// tree as corrupt (a directory that contains itself would never end).
+const MAX_TREE_DEPTH: usize = 64;
+const ROOT_INODE: u32 = 0;
+
+const KIND_FREE: u8 = 0;
+const KIND_FILE: u8 = 1;
+const KIND_DIR: u8 = 2;
+const KIND_SYMLINK: u8 = 3;
+
+/// Errors returned when formatting or mounting a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFsError {
+    Device(BlockError),
+    TooSmall,
+    BadMagic,
+    UnsupportedVersion,
+    BadLayout,
+}
+
+/// Block 0: where the bitmap, inode table and data area live.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Superblock {
+    block_count: u32,
+    inode_count: u32,
+    bitmap_start: u32,
+    bitmap_blocks: u32,
+    inode_start: u32,
+    inode_blocks: u32,
+    data_start: u32,
+}
+
+impl Superblock {
+    fn layout(block_count: u32) -> Self {
+        let per_block = INODES_PER_BLOCK as u32;
+        let bitmap_blocks = block_count.div_ceil(BLOCK_SIZE as u32 * 8);
+        let inode_count = (block_count / 8).max(per_block).next_multiple_of(per_block);
+        let inode_blocks = inode_count / per_block;
+        let bitmap_start = 1;
+        let inode_start = bitmap_start + bitmap_blocks;
+        Self {
+            block_count,
+            inode_count,
+            bitmap_start,
+            bitmap_blocks,
+            inode_start,
+            inode_blocks,
+            data_start: inode_start + inode_blocks,
+        }
+    }
+
+    fn encode(&self) -> [u8; BLOCK_SIZE] {
+        let mut block = [0; BLOCK_SIZE];
+        block[..4].copy_from_slice(BLOCKFS_MAGIC);
+        block[4..6].copy_from_slice(&BLOCKFS_VERSION.to_le_bytes());
+        let fields = [
+            self.block_count,
+            self.inode_count,
+            self.bitmap_start,
+            self.bitmap_blocks,
+            self.inode_start,
+            self.inode_blocks,
+            self.data_start,
+        ];
+        for (index, value) in fields.iter().enumerate() {
+            put_u32(&mut block, 8 + index * 4, *value);
+        }
+        block
+    }
+
+    fn decode(block: &[u8; BLOCK_SIZE]) -> Result<Self, BlockFsError> {
+        if &block[..4] != BLOCKFS_MAGIC {
+            return Err(BlockFsError::BadMagic);
+        }
+        if u16::from_le_bytes([block[4], block[5]]) != BLOCKFS_VERSION {
+            return Err(BlockFsError::UnsupportedVersion);
+        }
+        let superblock = Self {
+            block_count: get_u32(block, 8),
+            inode_count: get_u32(block, 12),
+            bitmap_start: get_u32(block, 16),
+            bitmap_blocks: get_u32(block, 20),
+            inode_start: get_u32(block, 24),
+            inode_blocks: get_u32(block, 28),
+            data_start: get_u32(block, 32),
+        };
+        if superblock != Self::layout(superblock.block_count) {
+            return Err(BlockFsError::BadLayout);
+        }
+        Ok(superblock)
+    }
+}
+
+/// One 64-byte inode table record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Inode {
+    kind: u8,
+    mode: u16,
+    size: u32,
+    direct: [u32; DIRECT_BLOCKS],
+    indirect: u32,
+}
+
+impl Inode {
+    fn new(kind: u8, mode: u16) -> Self {
+        Self {
+            kind,
+            mode,
+            size: 0,
+            direct: [0; DIRECT_BLOCKS],
+            indirect: 0,
+        }
+    }
+
+    fn encode(&self, out: &mut [u8]) {
+        out[..INODE_SIZE].fill(0);
+        out[0] = self.kind;
+        out[2..4].copy_from_slice(&self.mode.to_le_bytes());
+        put_u32(out, 4, self.size);
+        for (index, block) in self.direct.iter().enumerate() {
+            put_u32(out, 8 + index * 4, *block);
+        }
+        put_u32(out, 8 + DIRECT_BLOCKS * 4, self.indirect);
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let mut direct = [0; DIRECT_BLOCKS];
+        for (index, block) in direct.iter_mut().enumerate() {
+            *block = get_u32(bytes, 8 + index * 4);
+        }
+        Self {
+            kind: bytes[0],
+            mode: u16::from_le_bytes([bytes[2], bytes[3]]),
+            size: get_u32(bytes, 4),
+            direct,
+            indirect: get_u32(bytes, 8 + DIRECT_BLOCKS * 4),
+        }
+    }
+
+    fn file_kind(&self) -> Result<FileKind, FsError> {
+        match self.kind {
+            KIND_FILE => Ok(FileKind::File),
+            KIND_DIR => Ok(FileKind::Dir),
+            KIND_SYMLINK => Ok(FileKind::Symlink),
+            _ => Err(FsError::Io),
+        }
+    }
+}
+
+/// Persistent filesystem laid out as superblock, block bitmap, inode table
+/// and data blocks on a `BlockDevice`.
+///
+/// Entries have no owners or timestamps yet; paths, links and errors follow
+/// the in-memory `FileSystem`.
+#[derive(Debug)]
+pub struct BlockFs<D: BlockDevice> {
+    device: D,
+    superblock: Superblock,
+}
+
+impl<D: BlockDevice> BlockFs<D> {
+    /// Writes an empty filesystem (just the root directory) to the device.
+    pub fn format(mut device: D) -> Result<Self, BlockFsError> {
+        let block_count = device.block_count().min(u64::from(u32::MAX));
+        if block_count < MIN_BLOCKS {
+            return Err(BlockFsError::TooSmall);
+        }
+        let superblock = Superblock::layout(block_count as u32);
+        if superblock.data_start >= superblock.block_count {
+            return Err(BlockFsError::TooSmall);
+        }
+        let zero = [0; BLOCK_SIZE];
+        for block in superblock.bitmap_start..superblock.data_start {
+            device
+                .write_block(block.into(), &zero)
+                .map_err(BlockFsError::Device)?;
+        }
+        device
+            .write_block(0, &superblock.encode())
+            .map_err(BlockFsError::Device)?;
+        let mut fs = Self { device, superblock };
+        fs.reserve_metadata()
+            .map_err(|_| BlockFsError::Device(BlockError::Io))?;
+        Ok(fs)
+    }
+
+    /// Mounts a device previously prepared by `format`.
+    pub fn mount(device: D) -> Result<Self, BlockFsError> {
+        let mut block = [0; BLOCK_SIZE];
+        device
+            .read_block(0, &mut block)
+            .map_err(BlockFsError::Device)?;
+        let superblock = Superblock::decode(&block)?;
+        if u64::from(superblock.block_count) > device.block_count() {
+            return Err(BlockFsError::BadLayout);
+        }
+        Ok(Self { device, superblock })
+    }
+
+    /// Releases the device, e.g. to mount it again after a reboot.
+    pub fn into_device(self) -> D {
+        self.device
+    }
+
+    /// Returns how many blocks are still unallocated.
+    pub fn free_blocks(&self) -> Result<usize, FsError> {
+        let mut free = 0;
+        for block in 0..self.superblock.block_count {
+            if !self.block_used(block)? {
+                free += 1;
+            }
+        }
+        Ok(free)
+    }
+
+    fn reserve_metadata(&mut self) -> Result<(), FsError> {
+        for block in 0..self.superblock.data_start {
+            self.set_block_used(block, true)?;
+        }
+        self.write_inode(ROOT_INODE, &Inode::new(KIND_DIR, DEFAULT_DIR_MODE))
+    }
+
+    fn read(&self, index: u32) -> Result<[u8; BLOCK_SIZE], FsError> {
+        let mut block = [0; BLOCK_SIZE];
+        self.device
+            .read_block(index.into(), &mut block)
+            .map_err(|_| FsError::Io)?;
+        Ok(block)
+    }
+
+    fn write(&mut self, index: u32, block: &[u8; BLOCK_SIZE]) -> Result<(), FsError> {
+        self.device
+            .write_block(index.into(), block)
+            .map_err(|_| FsError::Io)
+    }
+
+    fn bitmap_slot(&self, block: u32) -> (u32, usize, u8) {
+        let bit = block as usize;
+        let bits_per_block = BLOCK_SIZE * 8;
+        (
+            self.superblock.bitmap_start + (bit / bits_per_block) as u32,
+            (bit % bits_per_block) / 8,
+            1 << (bit % 8),
+        )
+    }
+
+    fn block_used(&self, block: u32) -> Result<bool, FsError> {
+        let (index, byte, mask) = self.bitmap_slot(block);
+        Ok(self.read(index)?[byte] & mask != 0)
+    }
+
+    fn set_block_used(&mut self, block: u32, used: bool) -> Result<(), FsError> {
+        let (index, byte, mask) = self.bitmap_slot(block);
+        let mut bitmap = self.read(index)?;
+        if used {
+            bitmap[byte] |= mask;
+        } else {
+            bitmap[byte] &= !mask;
+        }
+        self.write(index, &bitmap)
+    }
+
+    fn alloc_block(&mut self) -> Result<u32, FsError> {
+        for block in self.superblock.data_start..self.superblock.block_count {
+            if !self.block_used(block)? {
+                self.set_block_used(block, true)?;
+                return Ok(block);
+            }
+        }
+        Err(FsError::NoSpace)
+    }
+
+    fn read_inode(&self, number: u32) -> Result<Inode, FsError> {
+        if number >= self.superblock.inode_count {
+            return Err(FsError::Io);
+        }
+        let block = self.read(self.inode_block(number))?;
+        let offset = self.inode_offset(number);
+        Ok(Inode::decode(&block[offset..offset + INODE_SIZE]))
+    }
+
+    fn write_inode(&mut self, number: u32, inode: &Inode) -> Result<(), FsError> {
+        if number >= self.superblock.inode_count {
+            return Err(FsError::Io);
+        }
+        let index = self.inode_block(number);
+        let mut block = self.read(index)?;
+        let offset = self.inode_offset(number);
+        inode.encode(&mut block[offset..offset + INODE_SIZE]);
+        self.write(index, &block)
+    }
+
+    fn inode_block(&self, number: u32) -> u32 {
+        self.superblock.inode_start + number / INODES_PER_BLOCK as u32
+    }
+
+    fn inode_offset(&self, number: u32) -> usize {
+        (number as usize % INODES_PER_BLOCK) * INODE_SIZE
+    }
+
+    fn alloc_inode(&mut self, inode: &Inode) -> Result<u32, FsError> {
+        for number in 1..self.superblock.inode_count {
+            if self.read_inode(number)?.kind == KIND_FREE {
+                self.write_inode(number, inode)?;
+                return Ok(number);
+            }
+        }
+        Err(FsError::NoSpace)
+    }
+
+    /// Rejects block pointers outside the data area; a corrupt inode must
+    /// not free metadata or read past the device.
+    fn check_data_block(&self, block: u32) -> Result<u32, FsError> {
+        if (self.superblock.data_start..self.superblock.block_count).contains(&block) {
+            Ok(block)
+        } else {
+            Err(FsError::Io)
+        }
+    }
+
+    fn data_blocks(&self, inode: &Inode) -> Result<Vec<u32>, FsError> {
+        let count = (inode.size as usize).div_ceil(BLOCK_SIZE);
+        if count > MAX_FILE_BLOCKS {
+            return Err(FsError::Io);
+        }
+        if inode.indirect != 0 {
+            self.check_data_block(inode.indirect)?;
+        }
+        let mut blocks: Vec<u32> = inode
+            .direct
+            .iter()
+            .copied()
+            .take(count.min(DIRECT_BLOCKS))
+            .map(|block| self.check_data_block(block))
+            .collect::<Result<_, _>>()?;
+        if count > DIRECT_BLOCKS {
+            let table = self.read(self.check_data_block(inode.indirect)?)?;
+            for index in 0..count - DIRECT_BLOCKS {
+                blocks.push(self.check_data_block(get_u32(&table, index * 4))?);
+            }
+        }
+        Ok(blocks)
+    }
+
+    fn read_data(&self, inode: &Inode) -> Result<Vec<u8>, FsError> {
+        let blocks = self.data_blocks(inode)?;
+        let mut data = Vec::with_capacity(blocks.len() * BLOCK_SIZE);
+        for block in blocks {
+            data.extend_from_slice(&self.read(block)?);
+        }
+        data.truncate(inode.size as usize);
+        Ok(data)
+    }
+
+    /// Replaces an inode's contents, reusing its blocks where possible.
+    ///
+    /// Space is checked before anything changes; the caller stores the inode.
+    fn write_data(&mut self, inode: &mut Inode, data: &[u8]) -> Result<(), FsError> {
+        let needed = data.len().div_ceil(BLOCK_SIZE);
+        if needed > MAX_FILE_BLOCKS {
+            return Err(FsError::NoSpace);
+        }
+        let mut blocks = self.data_blocks(inode)?;
+        let indirect = needed > DIRECT_BLOCKS;
+        let extra =
+            needed.saturating_sub(blocks.len()) + usize::from(indirect && inode.indirect == 0);
+        if extra > 0 && extra > self.free_blocks()? {
+            return Err(FsError::NoSpace);
+        }
+        for block in blocks.split_off(needed.min(blocks.len())) {
+            self.set_block_used(block, false)?;
+        }
+        while blocks.len() < needed {
+            blocks.push(self.alloc_block()?);
+        }
+        for (chunk, block) in data.chunks(BLOCK_SIZE).zip(&blocks) {
+            let mut buf = [0; BLOCK_SIZE];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            self.write(*block, &buf)?;
+        }
+        inode.direct = [0; DIRECT_BLOCKS];
+        for (slot, block) in inode.direct.iter_mut().zip(&blocks) {
+            *slot = *block;
+        }
+        if indirect {
+            if inode.indirect == 0 {
+                inode.indirect = self.alloc_block()?;
+            }
+            let mut table = [0; BLOCK_SIZE];
+            for (index, block) in blocks[DIRECT_BLOCKS..].iter().enumerate() {
+                put_u32(&mut table, index * 4, *block);
+            }
+            self.write(inode.indirect, &table)?;
+        } else if inode.indirect != 0 {
+            self.set_block_used(inode.indirect, false)?;
+            inode.indirect = 0;
+        }
+        inode.size = data.len() as u32;
+        Ok(())
+    }
+
+    fn release(&mut self, number: u32, inode: &Inode) -> Result<(), FsError> {
+        for block in self.data_blocks(inode)? {
+            self.set_block_used(block, false)?;
+        }
+        if inode.indirect != 0 {
+            self.set_block_used(inode.indirect, false)?;
+        }
+        self.write_inode(number, &Inode::new(KIND_FREE, 0))
+    }
+
+    fn read_dir(&self, inode: &Inode) -> Result<BTreeMap<String, u32>, FsError> {
+        if inode.kind != KIND_DIR {
+            return Err(FsError::NotDir);
+        }
+        let mut entries = BTreeMap::new();
+        for record in self.read_data(inode)?.chunks_exact(DIRENT_SIZE) {
+            let len = usize::from(record[4]);
+            if len == 0 {
+                continue;
+            }
+            if len > MAX_NAME_LEN {
+                return Err(FsError::Io);
+            }
+            let name = core::str::from_utf8(&record[5..5 + len]).map_err(|_| FsError::Io)?;
+            entries.insert(name.to_string(), get_u32(record, 0));
+        }
+        Ok(entries)
+    }
+
+    fn write_dir(
+        &mut self,
+        number: u32,
+        inode: &mut Inode,
+        entries: &BTreeMap<String, u32>,
+    ) -> Result<(), FsError> {
+        let mut data = Vec::with_capacity(entries.len() * DIRENT_SIZE);
+        for (name, child) in entries {
+            let mut record = [0; DIRENT_SIZE];
+            put_u32(&mut record, 0, *child);
+            record[4] = name.len() as u8;
+            record[5..5 + name.len()].copy_from_slice(name.as_bytes());
+            data.extend_from_slice(&record);
+        }
+        self.write_data(inode, &data)?;
+        self.write_inode(number, inode)
+    }
+
+    fn entries_of(&self, number: u32) -> Result<BTreeMap<String, u32>, FsError> {
+        self.read_dir(&self.read_inode(number)?)
+    }
+
+    /// Walks `parts` from the root, returning the chain of directory inodes
+    /// visited with the target last.
+    fn walk_chain(&self, parts: &[&str], follow_final: bool) -> Result<Vec<u32>, FsError> {
+        let mut chain = alloc::vec![ROOT_INODE];
+        let mut pending: Vec<String> = parts.iter().rev().map(|part| part.to_string()).collect();
+        let mut hops = 0;
+        while let Some(segment) = pending.pop() {
+            match segment.as_str() {
+                "." => continue,
+                ".." => {
+                    if chain.len() > 1 {
+                        chain.pop();
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            let dir = chain[chain.len() - 1];
+            let child = *self
+                .entries_of(dir)?
+                .get(&segment)
+                .ok_or(FsError::NotFound)?;
+            let inode = self.read_inode(child)?;
+            if inode.kind == KIND_SYMLINK && (follow_final || !pending.is_empty()) {
+                hops += 1;
+                if hops > SYMLINK_HOP_LIMIT {
+                    return Err(FsError::SymlinkLoop);
+                }
+                let target = self.read_data(&inode)?;
+                let target = core::str::from_utf8(&target).map_err(|_| FsError::Io)?;
+                if target.starts_with('/') {
+                    chain.truncate(1);
+                }
+                for part in target.rsplit('/').filter(|part| !part.is_empty()) {
+                    pending.push(part.to_string());
+                }
+                continue;
+            }
+            chain.push(child);
+        }
+        Ok(chain)
+    }
+
+    fn lookup(&self, path: &str, follow_final: bool) -> Result<u32, FsError> {
+        let chain = self.walk_chain(&split_path(path)?, follow_final)?;
+        Ok(chain[chain.len() - 1])
+    }
+
+    /// Resolves the directory that holds a path's last segment.
+    fn parent_of(&self, path: &str) -> Result<(Vec<u32>, u32, String), FsError> {
+        let parts = split_path(path)?;
+        let Some((name, parent)) = parts.split_last() else {
+            return Err(FsError::InvalidPath);
+        };
+        if name.len() > MAX_NAME_LEN {
+            return Err(FsError::InvalidPath);
+        }
+        let chain = self.walk_chain(parent, true)?;
+        let dir = chain[chain.len() - 1];
+        if self.read_inode(dir)?.kind != KIND_DIR {
+            return Err(FsError::NotDir);
+        }
+        Ok((chain, dir, name.to_string()))
+    }
+
+    fn create(&mut self, path: &str, kind: u8, mode: u16, data: &[u8]) -> Result<u32, FsError> {
+        let (_, parent, name) = self.parent_of(path)?;
+        self.create_in(parent, &name, kind, mode, data)
+    }
+
+    fn create_in(
+        &mut self,
+        parent: u32,
+        name: &str,
+        kind: u8,
+        mode: u16,
+        data: &[u8],
+    ) -> Result<u32, FsError> {
+        let mut parent_inode = self.read_inode(parent)?;
+        let mut entries = self.read_dir(&parent_inode)?;
+        if entries.contains_key(name) {
+            return Err(FsError::AlreadyExists);
+        }
+        let mut inode = Inode::new(kind, mode);
+        let number = self.alloc_inode(&inode)?;
+        let stored = self
+            .write_data(&mut inode, data)
+            .and_then(|()| self.write_inode(number, &inode));
+        if let Err(err) = stored {
+            self.write_inode(number, &Inode::new(KIND_FREE, 0))?;
+            return Err(err);
+        }
+        entries.insert(name.to_string(), number);
+        if let Err(err) = self.write_dir(parent, &mut parent_inode, &entries) {
+            self.release(number, &inode)?;
+            return Err(err);
+        }
+        Ok(number)
+    }
+
+    fn free_tree(
+        &mut self,
+        number: u32,
+        depth: usize,
+        removed: &mut FsStats,
+    ) -> Result<(), FsError> {
+        if depth > MAX_TREE_DEPTH {
+            return Err(FsError::Io);
+        }
+        let inode = self.read_inode(number)?;
+        match inode.kind {
+            KIND_DIR => {
+                removed.dirs += 1;
+                for child in self.read_dir(&inode)?.into_values() {
+                    self.free_tree(child, depth + 1, removed)?;
+                }
+            }
+            KIND_FILE => {
+                removed.files += 1;
+                removed.bytes += inode.size as usize;
+            }
+            _ => {}
+        }
+        self.release(number, &inode)
+    }
+
+    fn walk_into(
+        &self,
+        number: u32,
+        path: String,
+        depth: usize,
+        out: &mut Vec<WalkEntry>,
+    ) -> Result<(), FsError> {
+        if depth > MAX_TREE_DEPTH {
+            return Err(FsError::Io);
+        }
+        let inode = self.read_inode(number)?;
+        let kind = inode.file_kind()?;
+        let children = if kind == FileKind::Dir {
+            self.read_dir(&inode)?
+        } else {
+            BTreeMap::new()
+        };
+        let base = if path == "/" {
+            String::new()
+        } else {
+            path.clone()
+        };
+        out.push(WalkEntry {
+            path,
+            kind,
+            depth,
+            size: if kind == FileKind::Dir {
+                0
+            } else {
+                inode.size as usize
+            },
+        });
+        for (name, child) in children {
+            self.walk_into(child, join_child(&base, &name), depth + 1, out)?;
+        }
+        Ok(())
+    }
+
+    fn copy_into(
+        &mut self,
+        source: u32,
+        parent: u32,
+        name: &str,
+        depth: usize,
+    ) -> Result<(), FsError> {
+        if depth > MAX_TREE_DEPTH {
+            return Err(FsError::Io);
+        }
+        let inode = self.read_inode(source)?;
+        let data = if inode.kind == KIND_DIR {
+            Vec::new()
+        } else {
+            self.read_data(&inode)?
+        };
+        let copy = self.create_in(parent, name, inode.kind, inode.mode, &data)?;
+        if inode.kind == KIND_DIR {
+            for (child_name, child) in self.read_dir(&inode)? {
+                self.copy_into(child, copy, &child_name, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves an entry into `dst`'s directory when `dst` names an existing directory.
+    fn target_of(
+        &self,
+        moving: u32,
+        dst: &str,
+        name: &str,
+    ) -> Result<(Vec<u32>, u32, String), FsError> {
+        let (mut chain, mut parent, mut dst_name) = self.parent_of(dst)?;
+        if let Some(&existing) = self.entries_of(parent)?.get(&dst_name) {
+            if existing != moving && self.read_inode(existing)?.kind == KIND_DIR {
+                chain.push(existing);
+                parent = existing;
+                dst_name = name.to_string();
+            }
+        }
+        Ok((chain, parent, dst_name))
+    }
+}
+
+impl<D: BlockDevice> Fs for BlockFs<D> {
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, FsError> {
+        let number = self.lookup(path, true)?;
+        Ok(self.entries_of(number)?.into_keys().collect())
+    }
+
+    fn list_entries(&self, path: &str) -> Result<Vec<DirEntry>, FsError> {
+        let number = self.lookup(path, true)?;
+        self.entries_of(number)?
+            .into_iter()
+            .map(|(name, child)| {
+                Ok(DirEntry {
+                    name,
+                    kind: self.read_inode(child)?.file_kind()?,
+                })
+            })
+            .collect()
+    }
+
+    fn read_file(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        let inode = self.read_inode(self.lookup(path, true)?)?;
+        match inode.kind {
+            KIND_FILE => self.read_data(&inode),
+            _ => Err(FsError::IsDir),
+        }
+    }
+
+    fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), FsError> {
+        let (_, parent, name) = self.parent_of(path)?;
+        if !self.entries_of(parent)?.contains_key(&name) {
+            return self
+                .create_in(parent, &name, KIND_FILE, DEFAULT_FILE_MODE, data)
+                .map(|_| ());
+        }
+        let number = self.lookup(path, true)?;
+        let mut inode = self.read_inode(number)?;
+        if inode.kind != KIND_FILE {
+            return Err(FsError::IsDir);
+        }
+        self.write_data(&mut inode, data)?;
+        self.write_inode(number, &inode)
+    }
+
+    fn mkdir(&mut self, path: &str) -> Result<(), FsError> {
+        self.create(path, KIND_DIR, DEFAULT_DIR_MODE, &[])
+            .map(|_| ())
+    }
+
+    fn mkdir_p(&mut self, path: &str) -> Result<Vec<String>, FsError> {
+        let parts = split_path(path)?;
+        let mut created = Vec::new();
+        let mut current = String::new();
+        for segment in parts {
+            current.push('/');
+            current.push_str(segment);
+            match self.lookup(&current, true) {
+                Ok(number) if self.read_inode(number)?.kind == KIND_DIR => {}
+                Ok(_) => return Err(FsError::NotDir),
+                Err(FsError::NotFound) => {
+                    self.mkdir(&current)?;
+                    created.push(current.clone());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(created)
+    }
+
+    fn remove(&mut self, path: &str) -> Result<(), FsError> {
+        let (_, parent, name) = self.parent_of(path)?;
+        let mut parent_inode = self.read_inode(parent)?;
+        let mut entries = self.read_dir(&parent_inode)?;
+        let child = entries.remove(&name).ok_or(FsError::NotFound)?;
+        let inode = self.read_inode(child)?;
+        if inode.kind == KIND_DIR && !self.read_dir(&inode)?.is_empty() {
+            return Err(FsError::NotEmpty);
+        }
+        self.write_dir(parent, &mut parent_inode, &entries)?;
+        self.release(child, &inode)
+    }
+
+    fn remove_recursive(&mut self, path: &str) -> Result<FsStats, FsError> {
+        let (_, parent, name) = self.parent_of(path)?;
+        let mut parent_inode = self.read_inode(parent)?;
+        let mut entries = self.read_dir(&parent_inode)?;
+        let child = entries.remove(&name).ok_or(FsError::NotFound)?;
+        self.write_dir(parent, &mut parent_inode, &entries)?;
+        let mut removed = FsStats {
+            files: 0,
+            dirs: 0,
+            bytes: 0,
+        };
+        self.free_tree(child, 0, &mut removed)?;
+        Ok(removed)
+    }
+
+    fn symlink(&mut self, target: &str, link: &str) -> Result<(), FsError> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        self.create(link, KIND_SYMLINK, DEFAULT_DIR_MODE, target.as_bytes())
+            .map(|_| ())
+    }
+
+    fn read_link(&self, path: &str) -> Result<String, FsError> {
+        if split_path(path)?.is_empty() {
+            return Err(FsError::NotSymlink);
+        }
+        let (_, parent, name) = self.parent_of(path)?;
+        let child = *self
+            .entries_of(parent)?
+            .get(&name)
+            .ok_or(FsError::NotFound)?;
+        let inode = self.read_inode(child)?;
+        if inode.kind != KIND_SYMLINK {
+            return Err(FsError::NotSymlink);
+        }
+        String::from_utf8(self.read_data(&inode)?).map_err(|_| FsError::Io)
+    }
+
+    fn glob(&self, pattern: &str) -> Result<Vec<String>, FsError> {
+        let parts = split_path(pattern)?;
+        if parts.is_empty() {
+            return Ok(alloc::vec!["/".to_string()]);
+        }
+        let mut current = alloc::vec![String::new()];
+        for segment in parts {
+            let mut next = Vec::new();
+            for base in &current {
+                if !is_glob_pattern(segment) {
+                    let path = join_child(base, segment);
+                    if self.lookup(&path, false).is_ok() {
+                        next.push(path);
+                    }
+                    continue;
+                }
+                let dir = if base.is_empty() { "/" } else { base.as_str() };
+                let Ok(names) = self.list_dir(dir) else {
+                    continue;
+                };
+                for name in names {
+                    let hidden = name.starts_with('.') && !segment.starts_with('.');
+                    if !hidden && glob_match(segment, &name) {
+                        next.push(join_child(base, &name));
+                    }
+                }
+            }
+            current = next;
+        }
+        Ok(current)
+    }
+
+    fn rename(&mut self, src: &str, dst: &str) -> Result<(), FsError> {
+        let (_, src_parent, src_name) = self.parent_of(src)?;
+        let moving = *self
+            .entries_of(src_parent)?
+            .get(&src_name)
+            .ok_or(FsError::NotFound)?;
+        let (chain, dst_parent, dst_name) = self.target_of(moving, dst, &src_name)?;
+        if src_parent == dst_parent && src_name == dst_name {
+            return Ok(());
+        }
+        if chain.contains(&moving) {
+            return Err(FsError::InvalidPath);
+        }
+        let moving_kind = self.read_inode(moving)?.kind;
+        let replaced = match self.entries_of(dst_parent)?.get(&dst_name) {
+            Some(&existing) => {
+                let inode = self.read_inode(existing)?;
+                if inode.kind != KIND_FILE || moving_kind != KIND_FILE {
+                    return Err(FsError::AlreadyExists);
+                }
+                Some((existing, inode))
+            }
+            None => None,
+        };
+        let mut src_inode = self.read_inode(src_parent)?;
+        let mut src_entries = self.read_dir(&src_inode)?;
+        src_entries.remove(&src_name);
+        if src_parent == dst_parent {
+            src_entries.insert(dst_name, moving);
+            self.write_dir(src_parent, &mut src_inode, &src_entries)?;
+        } else {
+            let mut dst_inode = self.read_inode(dst_parent)?;
+            let mut dst_entries = self.read_dir(&dst_inode)?;
+            dst_entries.insert(dst_name, moving);
+            self.write_dir(dst_parent, &mut dst_inode, &dst_entries)?;
+            self.write_dir(src_parent, &mut src_inode, &src_entries)?;
+        }
+        if let Some((number, inode)) = replaced {
+            self.release(number, &inode)?;
+        }
+        Ok(())
+    }
+
+    fn copy(&mut self, src: &str, dst: &str, recursive: bool) -> Result<(), FsError> {
+        let (_, src_parent, src_name) = self.parent_of(src)?;
+        let source = *self
+            .entries_of(src_parent)?
+            .get(&src_name)
+            .ok_or(FsError::NotFound)?;
+        let source_inode = self.read_inode(source)?;
+        if source_inode.kind == KIND_DIR && !recursive {
+            return Err(FsError::IsDir);
+        }
+        let (chain, dst_parent, dst_name) = self.target_of(source, dst, &src_name)?;
+        if chain.contains(&source) || (src_parent == dst_parent && src_name == dst_name) {
+            return Err(FsError::InvalidPath);
+        }
+        match self.entries_of(dst_parent)?.get(&dst_name) {
+            Some(&existing) => {
+                let mut inode = self.read_inode(existing)?;
+                if inode.kind != KIND_FILE || source_inode.kind != KIND_FILE {
+                    return Err(FsError::AlreadyExists);
+                }
+                let data = self.read_data(&source_inode)?;
+                self.write_data(&mut inode, &data)?;
+                self.write_inode(existing, &inode)
+            }
+            None => self.copy_into(source, dst_parent, &dst_name, 0),
+        }
+    }
+
+    fn checksum(&self, path: &str) -> Result<u32, FsError> {
+        Ok(crc32(&self.read_file(path)?))
+    }
+
+    fn walk(&self, path: &str) -> Result<Vec<WalkEntry>, FsError> {
+        let parts = split_path(path)?;
+        let mut entries = Vec::new();
+        if parts.is_empty() {
+            self.walk_into(ROOT_INODE, "/".to_string(), 0, &mut entries)?;
+            return Ok(entries);
+        }
+        let (_, parent, name) = self.parent_of(path)?;
+        let number = *self
+            .entries_of(parent)?
+            .get(&name)
+            .ok_or(FsError::NotFound)?;
+        let mut canonical = String::new();
+        for segment in parts {
+            canonical.push('/');
+            canonical.push_str(segment);
+        }
+        self.walk_into(number, canonical, 0, &mut entries)?;
+        Ok(entries)
+    }
+}
+
+fn split_path(path: &str) -> Result<Vec<&str>, FsError> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() || trimmed.contains("//") {
+        return Err(FsError::InvalidPath);
+    }
+    if trimmed != "/" && trimmed.ends_with('/') {
+        return Err(FsError::InvalidPath);
+    }
+    let mut parts = Vec::new();
+    for segment in trimmed.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(segment),
+        }
+    }
+    Ok(parts)
+}
+
+fn join_child(base: &str, name: &str) -> String {
+    let mut path = String::from(base);
+    path.push('/');
+    path.push_str(name);
+    path
+}
+
+fn get_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn put_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use user_file_manager::FileManager;
+
+    fn fresh(blocks: usize) -> BlockFs<MemBlockDevice> {
+        BlockFs::format(MemBlockDevice::new(blocks)).unwrap()
+    }
+
+    fn remount(fs: BlockFs<MemBlockDevice>) -> BlockFs<MemBlockDevice> {
+        BlockFs::mount(fs.into_device()).unwrap()
+    }
+
+    #[test]
+    fn layout_reserves_metadata_blocks() {
+        let superblock = Superblock::layout(64);
+        assert_eq!(superblock.bitmap_blocks, 1);
+        assert_eq!(superblock.inode_count, 8);
+        assert_eq!(superblock.inode_start, 2);
+        assert_eq!(superblock.data_start, 3);
+        let superblock = Superblock::layout(8192);
+        assert_eq!(superblock.bitmap_blocks, 2);
+        assert_eq!(superblock.inode_count, 1024);
+        assert_eq!(superblock.data_start, 3 + 128);
+        assert_eq!(Superblock::decode(&superblock.encode()), Ok(superblock));
+    }
+
+    #[test]
+    fn format_rejects_tiny_devices() {
+        let err = BlockFs::format(MemBlockDevice::new(MIN_BLOCKS as usize - 1)).unwrap_err();
+        assert_eq!(err, BlockFsError::TooSmall);
+        let fs = fresh(MIN_BLOCKS as usize);
+        assert_eq!(fs.free_blocks(), Ok(MIN_BLOCKS as usize - 3));
+    }
+
+    #[test]
+    fn mount_rejects_bad_superblocks() {
+        let err = BlockFs::mount(MemBlockDevice::new(32)).unwrap_err();
+        assert_eq!(err, BlockFsError::BadMagic);
+
+        let mut device = fresh(32).into_device();
+        let mut block = [0; BLOCK_SIZE];
+        device.read_block(0, &mut block).unwrap();
+        block[4] = 9;
+        device.write_block(0, &block).unwrap();
+        assert_eq!(
+            BlockFs::mount(device).unwrap_err(),
+            BlockFsError::UnsupportedVersion
+        );
+
+        let mut device = fresh(32).into_device();
+        device.read_block(0, &mut block).unwrap();
+        put_u32(&mut block, 32, 1);
+        device.write_block(0, &block).unwrap();
+        assert_eq!(BlockFs::mount(device).unwrap_err(), BlockFsError::BadLayout);
+
+        let mut small = MemBlockDevice::new(16);
+        let device = fresh(32).into_device();
+        device.read_block(0, &mut block).unwrap();
+        small.write_block(0, &block).unwrap();
+        assert_eq!(BlockFs::mount(small).unwrap_err(), BlockFsError::BadLayout);
+    }
+
+    #[test]
+    fn corrupt_inodes_fail_instead_of_panicking() {
+        let mut fs = fresh(64);
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/motd", b"hello").unwrap();
+        let motd = fs.lookup("/etc/motd", true).unwrap();
+        let mut inode = fs.read_inode(motd).unwrap();
+        inode.size = u32::MAX;
+        fs.write_inode(motd, &inode).unwrap();
+        let mut fs = remount(fs);
+        assert_eq!(fs.read_file("/etc/motd"), Err(FsError::Io));
+        assert_eq!(fs.checksum("/etc/motd"), Err(FsError::Io));
+
+        inode.size = 5;
+        inode.direct[0] = 64;
+        fs.write_inode(motd, &inode).unwrap();
+        let mut fs = remount(fs);
+        assert_eq!(fs.read_file("/etc/motd"), Err(FsError::Io));
+
+        inode.direct[0] = 1;
+        fs.write_inode(motd, &inode).unwrap();
+        let mut fs = remount(fs);
+        assert_eq!(fs.read_file("/etc/motd"), Err(FsError::Io));
+        assert_eq!(fs.remove("/etc/motd"), Err(FsError::Io));
+        assert!(fs.block_used(1).unwrap());
+    }
+
+    #[test]
+    fn directory_cycles_fail_instead_of_recursing_forever() {
+        let mut fs = fresh(1024);
+        fs.mkdir_p("/a/b").unwrap();
+        let a = fs.lookup("/a", true).unwrap();
+        let b = fs.lookup("/a/b", true).unwrap();
+        let mut inode = fs.read_inode(b).unwrap();
+        let mut entries = fs.read_dir(&inode).unwrap();
+        entries.insert("loop".to_string(), a);
+        fs.write_dir(b, &mut inode, &entries).unwrap();
+        let mut fs = remount(fs);
+        assert_eq!(fs.walk("/"), Err(FsError::Io));
+        assert_eq!(fs.copy("/a", "/copy", true), Err(FsError::Io));
+        assert_eq!(fs.remove_recursive("/a").unwrap_err(), FsError::Io);
+    }
+
+    #[test]
+    fn files_survive_remount() {
+        let mut fs = fresh(64);
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/motd", b"hello").unwrap();
+        fs.symlink("/etc/motd", "/motd").unwrap();
+        let fs = remount(fs);
+        assert_eq!(fs.list_dir("/").unwrap(), vec!["etc", "motd"]);
+        assert_eq!(fs.read_file("/etc/motd").unwrap(), b"hello");
+        assert_eq!(fs.checksum("/motd"), Ok(crc32(b"hello")));
+        assert_eq!(fs.read_file("/motd").unwrap(), b"hello");
+        assert_eq!(fs.read_link("/motd").unwrap(), "/etc/motd");
+        assert_eq!(
+            fs.list_entries("/").unwrap(),
+            vec![
+                DirEntry {
+                    name: "etc".to_string(),
+                    kind: FileKind::Dir,
+                },
+                DirEntry {
+                    name: "motd".to_string(),
+                    kind: FileKind::Symlink,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn large_files_use_the_indirect_block() {
+        let mut fs = fresh(256);
+        fs.write_file("/big", &[]).unwrap();
+        let free = fs.free_blocks().unwrap();
+        let data: Vec<u8> = (0..BLOCK_SIZE * 20 + 7).map(|i| i as u8).collect();
+        fs.write_file("/big", &data).unwrap();
+        assert_eq!(fs.free_blocks(), Ok(free - 22));
+        let mut fs = remount(fs);
+        assert_eq!(fs.read_file("/big").unwrap(), data);
+
+        fs.write_file("/big", b"tiny").unwrap();
+        assert_eq!(fs.free_blocks(), Ok(free - 1));
+        assert_eq!(fs.read_file("/big").unwrap(), b"tiny");
+        fs.write_file("/big", &[]).unwrap();
+        assert_eq!(fs.free_blocks(), Ok(free));
+        assert_eq!(fs.read_file("/big").unwrap(), b"");
+    }
+
+    #[test]
+    fn full_device_reports_no_space() {
+        let mut fs = fresh(MIN_BLOCKS as usize);
+        let free = fs.free_blocks().unwrap();
+        let too_big = vec![1; BLOCK_SIZE * free];
+        assert_eq!(fs.write_file("/big", &too_big), Err(FsError::NoSpace));
+        assert_eq!(fs.free_blocks(), Ok(free));
+        assert_eq!(fs.read_file("/big"), Err(FsError::NotFound));
+        let limit = vec![1; BLOCK_SIZE * (MAX_FILE_BLOCKS + 1)];
+        assert_eq!(fs.write_file("/huge", &limit), Err(FsError::NoSpace));
+
+        for index in 0..7 {
+            fs.write_file(&format!("/f{index}"), b"").unwrap();
+        }
+        assert_eq!(fs.write_file("/f7", b""), Err(FsError::NoSpace));
+        fs.remove("/f0").unwrap();
+        fs.write_file("/f7", b"").unwrap();
+    }
+
+    #[test]
+    fn paths_and_names_are_validated() {
+        let mut fs = fresh(32);
+        assert_eq!(fs.mkdir("/"), Err(FsError::InvalidPath));
+        assert_eq!(fs.mkdir("/a/"), Err(FsError::InvalidPath));
+        assert_eq!(fs.mkdir("/a/.."), Err(FsError::InvalidPath));
+        fs.mkdir("/a/../b").unwrap();
+        assert_eq!(fs.list_dir("/b/.").unwrap(), Vec::<String>::new());
+        let long = format!("/{}", "n".repeat(MAX_NAME_LEN + 1));
+        assert_eq!(fs.write_file(&long, b""), Err(FsError::InvalidPath));
+        let longest = format!("/{}", "n".repeat(MAX_NAME_LEN));
+        fs.write_file(&longest, b"ok").unwrap();
+        assert_eq!(fs.read_file(&longest).unwrap(), b"ok");
+        assert_eq!(fs.read_file("/"), Err(FsError::IsDir));
+        assert_eq!(fs.list_dir(&longest), Err(FsError::NotDir));
+        assert_eq!(fs.write_file("/missing/file", b""), Err(FsError::NotFound));
+        assert_eq!(fs.mkdir(&longest), Err(FsError::AlreadyExists));
+    }
+
+    #[test]
+    fn mkdir_p_and_remove() {
+        let mut fs = fresh(64);
+        assert_eq!(
+            fs.mkdir_p("/a/b").unwrap(),
+            vec!["/a".to_string(), "/a/b".to_string()]
+        );
+        assert!(fs.mkdir_p("/a/b").unwrap().is_empty());
+        fs.write_file("/a/b/file", b"data").unwrap();
+        assert_eq!(fs.mkdir_p("/a/b/file/c"), Err(FsError::NotDir));
+        assert_eq!(fs.remove("/a"), Err(FsError::NotEmpty));
+        assert_eq!(fs.remove("/a/nope"), Err(FsError::NotFound));
+        let free = fs.free_blocks().unwrap();
+        fs.remove("/a/b/file").unwrap();
+        assert_eq!(fs.free_blocks(), Ok(free + 2));
+        fs.write_file("/a/b/file", b"data").unwrap();
+        let stats = fs.remove_recursive("/a").unwrap();
+        assert_eq!(
+            stats,
+            FsStats {
+                files: 1,
+                dirs: 2,
+                bytes: 4,
+            }
+        );
+        assert_eq!(fs.remove_recursive("/"), Err(FsError::InvalidPath));
+        assert!(fs.list_dir("/").unwrap().is_empty());
+        assert_eq!(fs.free_blocks(), Ok(fresh(64).free_blocks().unwrap()));
+    }
+
+    #[test]
+    fn symlinks_resolve_relative_targets_and_detect_loops() {
+        let mut fs = fresh(64);
+        fs.mkdir_p("/home/ruzzle").unwrap();
+        fs.write_file("/home/ruzzle/notes", b"n").unwrap();
+        fs.symlink("ruzzle", "/home/me").unwrap();
+        assert_eq!(fs.read_file("/home/me/notes").unwrap(), b"n");
+        fs.symlink("..", "/home/ruzzle/up").unwrap();
+        assert_eq!(
+            fs.list_dir("/home/ruzzle/up").unwrap(),
+            vec!["me", "ruzzle"]
+        );
+        fs.symlink("/loop", "/loop").unwrap();
+        assert_eq!(fs.read_file("/loop"), Err(FsError::SymlinkLoop));
+        assert_eq!(fs.read_link("/home/ruzzle"), Err(FsError::NotSymlink));
+        assert_eq!(fs.read_link("/"), Err(FsError::NotSymlink));
+        assert_eq!(fs.symlink(" ", "/blank"), Err(FsError::InvalidPath));
+        fs.symlink("/gone", "/dangling").unwrap();
+        assert_eq!(fs.read_file("/dangling"), Err(FsError::NotFound));
+        fs.remove("/dangling").unwrap();
+    }
+
+    #[test]
+    fn rename_moves_into_directories() {
+        let mut fs = fresh(64);
+        fs.mkdir("/docs").unwrap();
+        fs.write_file("/a", b"1").unwrap();
+        fs.write_file("/b", b"2").unwrap();
+        fs.rename("/a", "/docs").unwrap();
+        assert_eq!(fs.read_file("/docs/a").unwrap(), b"1");
+        fs.rename("/b", "/docs/a").unwrap();
+        assert_eq!(fs.read_file("/docs/a").unwrap(), b"2");
+        assert_eq!(fs.list_dir("/").unwrap(), vec!["docs"]);
+        fs.rename("/docs/a", "/docs/c").unwrap();
+        assert_eq!(fs.list_dir("/docs").unwrap(), vec!["c"]);
+        fs.rename("/docs", "/docs").unwrap();
+        assert_eq!(fs.rename("/docs", "/docs/sub"), Err(FsError::InvalidPath));
+        fs.mkdir("/other").unwrap();
+        fs.mkdir("/other/docs").unwrap();
+        assert_eq!(fs.rename("/docs", "/other"), Err(FsError::AlreadyExists));
+        assert_eq!(fs.rename("/missing", "/x"), Err(FsError::NotFound));
+        let fs = remount(fs);
+        assert_eq!(fs.read_file("/docs/c").unwrap(), b"2");
+    }
+
+    #[test]
+    fn copy_duplicates_trees() {
+        let mut fs = fresh(256);
+        fs.mkdir_p("/src/sub").unwrap();
+        fs.write_file("/src/sub/file", b"data").unwrap();
+        fs.symlink("sub/file", "/src/link").unwrap();
+        assert_eq!(fs.copy("/src", "/dst", false), Err(FsError::IsDir));
+        fs.copy("/src", "/dst", true).unwrap();
+        assert_eq!(fs.read_file("/dst/sub/file").unwrap(), b"data");
+        assert_eq!(fs.read_link("/dst/link").unwrap(), "sub/file");
+        fs.write_file("/dst/sub/file", b"changed").unwrap();
+        assert_eq!(fs.read_file("/src/sub/file").unwrap(), b"data");
+        fs.copy("/src/sub/file", "/dst/sub/file", false).unwrap();
+        assert_eq!(fs.read_file("/dst/sub/file").unwrap(), b"data");
+        fs.copy("/src/sub/file", "/dst", false).unwrap();
+        assert_eq!(fs.read_file("/dst/file").unwrap(), b"data");
+        assert_eq!(fs.copy("/src", "/src/sub", true), Err(FsError::InvalidPath));
+        assert_eq!(
+            fs.copy("/dst/file", "/dst/file", false),
+            Err(FsError::InvalidPath)
+        );
+        assert_eq!(
+            fs.copy("/src/sub/file", "/src/link", false),
+            Err(FsError::AlreadyExists)
+        );
+    }
+
+    #[test]
+    fn glob_matches_entries() {
+        let mut fs = fresh(64);
+        fs.mkdir("/logs").unwrap();
+        fs.write_file("/logs/a.log", b"").unwrap();
+        fs.write_file("/logs/b.log", b"").unwrap();
+        fs.write_file("/logs/.hidden.log", b"").unwrap();
+        assert_eq!(
+            fs.glob("/logs/*.log").unwrap(),
+            vec!["/logs/a.log", "/logs/b.log"]
+        );
+        assert_eq!(fs.glob("/logs/.*").unwrap(), vec!["/logs/.hidden.log"]);
+        assert_eq!(fs.glob("/*/a.log").unwrap(), vec!["/logs/a.log"]);
+        assert!(fs.glob("/nope/*").unwrap().is_empty());
+        assert_eq!(fs.glob("/").unwrap(), vec!["/"]);
+    }
+
+    #[test]
+    fn walk_lists_subtrees_without_following_links() {
+        let mut fs = fresh(64);
+        fs.mkdir_p("/a/b").unwrap();
+        fs.write_file("/a/b/file", b"abc").unwrap();
+        fs.symlink("/a", "/a/link").unwrap();
+        let entries: Vec<(String, usize, usize)> = fs
+            .walk("/")
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.path, entry.depth, entry.size))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("/".to_string(), 0, 0),
+                ("/a".to_string(), 1, 0),
+                ("/a/b".to_string(), 2, 0),
+                ("/a/b/file".to_string(), 3, 3),
+                ("/a/link".to_string(), 2, 2),
+            ]
+        );
+        assert_eq!(fs.walk("/a/b/file").unwrap().len(), 1);
+        assert_eq!(fs.walk("/nope"), Err(FsError::NotFound));
+    }
+
+    #[test]
+    fn file_manager_runs_over_blockfs() {
+        let mut fs = fresh(64);
+        let mut manager = FileManager::new();
+        manager.mkdir_p(&mut fs, "/home/ruzzle").unwrap();
+        manager.cd(&fs, "/home/ruzzle").unwrap();
+        manager.write(&mut fs, "todo", "ship it").unwrap();
+        manager.cp(&mut fs, "todo", "done", false).unwrap();
+        let fs = remount(fs);
+        assert_eq!(manager.cat(&fs, "done").unwrap(), "ship it");
+        assert_eq!(manager.ls(&fs).unwrap(), vec!["done", "todo"]);
+    }
+}
@@ -0,0 +1,402 @@
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Maximum OSC52 payload accepted from a terminal, in bytes.
+pub const OSC52_MAX_LEN: usize = 4096;
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Errors for clipboard operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardError {
+    Empty,
+    InvalidSequence,
+    InvalidBase64,
+    InvalidUtf8,
+}
+
+/// Shared clipboard buffer for the shell and editor.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Clipboard {
+    contents: Option<String>,
+}
+
+impl Clipboard {
+    /// Creates an empty clipboard.
+    pub fn new() -> Self {
+        Self { contents: None }
+    }
+
+    /// Replaces the clipboard contents.
+    pub fn copy(&mut self, text: &str) {
+        self.contents = Some(text.to_string());
+    }
+
+    /// Returns the clipboard contents.
+    pub fn paste(&self) -> Result<&str, ClipboardError> {
+        self.contents.as_deref().ok_or(ClipboardError::Empty)
+    }
+
+    /// Clears the clipboard.
+    pub fn clear(&mut self) {
+        self.contents = None;
+    }
+
+    /// Returns true when nothing has been copied.
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_none()
+    }
+}
+
+/// Encodes text as an OSC52 set-clipboard escape sequence.
+pub fn encode_osc52(text: &str) -> String {
+    let mut out = String::new();
+    out.push_str("\x1b]52;c;");
+    out.push_str(&encode_base64(text.as_bytes()));
+    out.push('\x07');
+    out
+}
+
+/// Decodes a complete OSC52 escape sequence into clipboard text.
+///
+/// Both BEL and ST (`ESC \`) terminators are accepted.
+pub fn decode_osc52(sequence: &str) -> Result<String, ClipboardError> {
+    let body = sequence
+        .strip_prefix("\x1b]")
+        .ok_or(ClipboardError::InvalidSequence)?;
+    let body = body
+        .strip_suffix('\x07')
+        .or_else(|| body.strip_suffix("\x1b\\"))
+        .ok_or(ClipboardError::InvalidSequence)?;
+    decode_osc52_body(body.as_bytes())
+}
+
+/// Result of feeding one input byte into the OSC52 reader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Osc52Feed {
+    Byte(u8),
+    Pending,
+    Complete(Result<String, ClipboardError>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReaderState {
+    Idle,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Incremental OSC52 reader for serial console input.
+#[derive(Debug, Clone)]
+pub struct Osc52Reader {
+    state: ReaderState,
+    buffer: Vec<u8>,
+}
+
+impl Default for Osc52Reader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Osc52Reader {
+    /// Creates an idle reader.
+    pub fn new() -> Self {
+        Self {
+            state: ReaderState::Idle,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Feeds one byte, passing through bytes outside escape sequences.
+    pub fn feed(&mut self, byte: u8) -> Osc52Feed {
+        match self.state {
+            ReaderState::Idle => {
+                if byte == 0x1b {
+                    self.state = ReaderState::Escape;
+                    Osc52Feed::Pending
+                } else {
+                    Osc52Feed::Byte(byte)
+                }
+            }
+            ReaderState::Escape => {
+                if byte == b']' {
+                    self.state = ReaderState::Osc;
+                    self.buffer.clear();
+                    Osc52Feed::Pending
+                } else {
+                    self.state = ReaderState::Idle;
+                    Osc52Feed::Byte(byte)
+                }
+            }
+            ReaderState::Osc => match byte {
+                0x07 => self.finish(),
+                0x1b => {
+                    self.state = ReaderState::OscEscape;
+                    Osc52Feed::Pending
+                }
+                _ => {
+                    if self.buffer.len() == OSC52_MAX_LEN {
+                        self.reset();
+                        return Osc52Feed::Complete(Err(ClipboardError::InvalidSequence));
+                    }
+                    self.buffer.push(byte);
+                    Osc52Feed::Pending
+                }
+            },
+            ReaderState::OscEscape => {
+                if byte == b'\\' {
+                    self.finish()
+                } else {
+                    self.reset();
+                    Osc52Feed::Complete(Err(ClipboardError::InvalidSequence))
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Osc52Feed {
+        let result = decode_osc52_body(&self.buffer);
+        self.reset();
+        Osc52Feed::Complete(result)
+    }
+
+    fn reset(&mut self) {
+        self.state = ReaderState::Idle;
+        self.buffer.clear();
+    }
+}
+
+fn decode_osc52_body(body: &[u8]) -> Result<String, ClipboardError> {
+    let body = core::str::from_utf8(body).map_err(|_| ClipboardError::InvalidUtf8)?;
+    let mut parts = body.splitn(3, ';');
+    let code = parts.next().unwrap_or("");
+    let selection = parts.next().ok_or(ClipboardError::InvalidSequence)?;
+    let payload = parts.next().ok_or(ClipboardError::InvalidSequence)?;
+    if code != "52" || !selection.chars().all(is_selection_char) || payload == "?" {
+        return Err(ClipboardError::InvalidSequence);
+    }
+    let bytes = decode_base64(payload)?;
+    String::from_utf8(bytes).map_err(|_| ClipboardError::InvalidUtf8)
+}
+
+fn is_selection_char(ch: char) -> bool {
+    matches!(ch, 'c' | 'p' | 'q' | 's' | '0'..='7')
+}
+
+fn encode_base64(input: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in input.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+        out.push(BASE64_TABLE[(triple >> 18) as usize & 0x3f] as char);
+        out.push(BASE64_TABLE[(triple >> 12) as usize & 0x3f] as char);
+        if chunk.len() > 1 {
+            out.push(BASE64_TABLE[(triple >> 6) as usize & 0x3f] as char);
+        } else {
+            out.push('=');
+        }
+        if chunk.len() > 2 {
+            out.push(BASE64_TABLE[triple as usize & 0x3f] as char);
+        } else {
+            out.push('=');
+        }
+    }
+    out
+}
+
+fn decode_base64(input: &str) -> Result<Vec<u8>, ClipboardError> {
+    let bytes = input.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return Err(ClipboardError::InvalidBase64);
+    }
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (index, chunk) in bytes.chunks(4).enumerate() {
+        let last = index + 1 == bytes.len() / 4;
+        let padding = chunk.iter().rev().take_while(|byte| **byte == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return Err(ClipboardError::InvalidBase64);
+        }
+        let mut triple = 0u32;
+        for &byte in &chunk[..4 - padding] {
+            triple = (triple << 6) | base64_value(byte)?;
+        }
+        triple <<= 6 * padding as u32;
+        out.push((triple >> 16) as u8);
+        if padding < 2 {
+            out.push((triple >> 8) as u8);
+        }
+        if padding < 1 {
+            out.push(triple as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn base64_value(byte: u8) -> Result<u32, ClipboardError> {
+    BASE64_TABLE
+        .iter()
+        .position(|candidate| *candidate == byte)
+        .map(|value| value as u32)
+        .ok_or(ClipboardError::InvalidBase64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clipboard_copy_paste_and_clear() {
+        let mut clipboard = Clipboard::new();
+        assert!(clipboard.is_empty());
+        assert_eq!(clipboard.paste(), Err(ClipboardError::Empty));
+        clipboard.copy("hello");
+        assert_eq!(clipboard.paste(), Ok("hello"));
+        assert!(!clipboard.is_empty());
+        clipboard.clear();
+        assert!(clipboard.is_empty());
+    }
+
+    #[test]
+    fn encode_osc52_wraps_base64_payload() {
+        assert_eq!(encode_osc52("hi"), "\x1b]52;c;aGk=\x07");
+        assert_eq!(encode_osc52("h"), "\x1b]52;c;aA==\x07");
+        assert_eq!(encode_osc52("abc"), "\x1b]52;c;YWJj\x07");
+        assert_eq!(encode_osc52(""), "\x1b]52;c;\x07");
+    }
+
+    #[test]
+    fn decode_osc52_roundtrips_and_accepts_st() {
+        let text = "ls /etc\nwrite /tmp/a ok";
+        assert_eq!(decode_osc52(&encode_osc52(text)), Ok(text.to_string()));
+        assert_eq!(decode_osc52("\x1b]52;p;aGk=\x1b\\"), Ok("hi".to_string()));
+        assert_eq!(decode_osc52("\x1b]52;;aGk=\x07"), Ok("hi".to_string()));
+    }
+
+    #[test]
+    fn decode_osc52_rejects_malformed_sequences() {
+        assert_eq!(
+            decode_osc52("52;c;aGk=\x07"),
+            Err(ClipboardError::InvalidSequence)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52;c;aGk="),
+            Err(ClipboardError::InvalidSequence)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]53;c;aGk=\x07"),
+            Err(ClipboardError::InvalidSequence)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52;x;aGk=\x07"),
+            Err(ClipboardError::InvalidSequence)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52;c\x07"),
+            Err(ClipboardError::InvalidSequence)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52\x07"),
+            Err(ClipboardError::InvalidSequence)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52;c;?\x07"),
+            Err(ClipboardError::InvalidSequence)
+        );
+    }
+
+    #[test]
+    fn decode_osc52_rejects_bad_base64() {
+        assert_eq!(
+            decode_osc52("\x1b]52;c;aGk\x07"),
+            Err(ClipboardError::InvalidBase64)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52;c;a!k=\x07"),
+            Err(ClipboardError::InvalidBase64)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52;c;a===\x07"),
+            Err(ClipboardError::InvalidBase64)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52;c;aA==aGk=\x07"),
+            Err(ClipboardError::InvalidBase64)
+        );
+        assert_eq!(
+            decode_osc52("\x1b]52;c;/w==\x07"),
+            Err(ClipboardError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn reader_passes_plain_bytes() {
+        let mut reader = Osc52Reader::default();
+        assert_eq!(reader.feed(b'l'), Osc52Feed::Byte(b'l'));
+        assert_eq!(reader.feed(0x1b), Osc52Feed::Pending);
+        assert_eq!(reader.feed(b'['), Osc52Feed::Byte(b'['));
+        assert_eq!(reader.feed(b's'), Osc52Feed::Byte(b's'));
+    }
+
+    #[test]
+    fn reader_decodes_sequences_with_both_terminators() {
+        let mut reader = Osc52Reader::new();
+        let mut last = Osc52Feed::Pending;
+        for byte in "\x1b]52;c;aGk=\x07".bytes() {
+            last = reader.feed(byte);
+        }
+        assert_eq!(last, Osc52Feed::Complete(Ok("hi".to_string())));
+
+        for byte in "\x1b]52;c;YWJj\x1b\\".bytes() {
+            last = reader.feed(byte);
+        }
+        assert_eq!(last, Osc52Feed::Complete(Ok("abc".to_string())));
+        assert_eq!(reader.feed(b'x'), Osc52Feed::Byte(b'x'));
+    }
+
+    #[test]
+    fn reader_rejects_broken_terminator() {
+        let mut reader = Osc52Reader::new();
+        for byte in "\x1b]52;c;aGk=\x1b".bytes() {
+            assert_eq!(reader.feed(byte), Osc52Feed::Pending);
+        }
+        assert_eq!(
+            reader.feed(b'x'),
+            Osc52Feed::Complete(Err(ClipboardError::InvalidSequence))
+        );
+        assert_eq!(reader.feed(b'y'), Osc52Feed::Byte(b'y'));
+    }
+
+    #[test]
+    fn reader_rejects_oversized_payloads() {
+        let mut reader = Osc52Reader::new();
+        reader.feed(0x1b);
+        reader.feed(b']');
+        for _ in 0..OSC52_MAX_LEN {
+            assert_eq!(reader.feed(b'A'), Osc52Feed::Pending);
+        }
+        assert_eq!(
+            reader.feed(b'A'),
+            Osc52Feed::Complete(Err(ClipboardError::InvalidSequence))
+        );
+    }
+
+    #[test]
+    fn reader_reports_invalid_utf8_body() {
+        let mut reader = Osc52Reader::new();
+        reader.feed(0x1b);
+        reader.feed(b']');
+        reader.feed(0xff);
+        assert_eq!(
+            reader.feed(0x07),
+            Osc52Feed::Complete(Err(ClipboardError::InvalidUtf8))
+        );
+    }
+}
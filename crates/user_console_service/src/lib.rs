@@ -2,14 +2,18 @@
 
 extern crate alloc;
 
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec::Vec;
 
 pub mod protocol;
 
-/// Log levels supported by the console service.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Log levels supported by the console service, least severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
+    Trace,
+    Debug,
     Info,
     Warn,
     Error,
@@ -18,11 +22,81 @@ pub enum LogLevel {
 impl LogLevel {
     fn as_str(self) -> &'static str {
         match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
             LogLevel::Info => "INFO",
             LogLevel::Warn => "WARN",
             LogLevel::Error => "ERROR",
         }
     }
+
+    /// Returns the lowercase name used by `log level` and settings.
+    pub fn name(self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    /// Parses a lowercase level name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "trace" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" => Some(LogLevel::Warn),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Minimum log level per target, with a fallback for unlisted targets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    default: LogLevel,
+    targets: BTreeMap<String, LogLevel>,
+}
+
+impl LogFilter {
+    /// Creates a filter that passes `default` and above for every target.
+    pub const fn new(default: LogLevel) -> Self {
+        Self {
+            default,
+            targets: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the level applied to targets without an override.
+    pub fn default_level(&self) -> LogLevel {
+        self.default
+    }
+
+    /// Sets the minimum level for one target.
+    pub fn set(&mut self, target: &str, level: LogLevel) {
+        self.targets.insert(target.to_string(), level);
+    }
+
+    /// Returns the minimum level applied to a target.
+    pub fn level_for(&self, target: &str) -> LogLevel {
+        self.targets.get(target).copied().unwrap_or(self.default)
+    }
+
+    /// Returns true if a record at `level` from `target` should be kept.
+    pub fn enabled(&self, target: &str, level: LogLevel) -> bool {
+        level >= self.level_for(target)
+    }
+
+    /// Returns the per-target overrides in name order.
+    pub fn targets(&self) -> Vec<(String, LogLevel)> {
+        self.targets
+            .iter()
+            .map(|(target, level)| (target.clone(), *level))
+            .collect()
+    }
 }
 
 /// Formats a log line for UART output.
@@ -41,6 +115,168 @@ pub fn format_log(pid: u32, level: LogLevel, message: &str) -> String {
     line
 }
 
+/// Number of log lines kept by the kernel log buffer.
+pub const LOG_BUFFER_LINES: usize = 512;
+
+/// Bounded ring of recent log lines with monotonic sequence numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogBuffer {
+    lines: VecDeque<String>,
+    capacity: usize,
+    next_seq: u64,
+}
+
+impl LogBuffer {
+    /// Creates an empty buffer holding at most `capacity` lines.
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity,
+            next_seq: 0,
+        }
+    }
+
+    /// Appends a line, dropping the oldest one when full.
+    pub fn push(&mut self, line: &str) {
+        if self.capacity == 0 {
+            self.next_seq += 1;
+            return;
+        }
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.to_string());
+        self.next_seq += 1;
+    }
+
+    /// Returns the number of buffered lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns true if no lines are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the sequence number the next pushed line will get.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Returns the last `count` lines, or every buffered line for `None`.
+    pub fn tail(&self, count: Option<usize>) -> Vec<String> {
+        let count = count.unwrap_or(self.lines.len()).min(self.lines.len());
+        self.lines
+            .iter()
+            .skip(self.lines.len() - count)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns buffered lines with sequence numbers at or after `seq`.
+    pub fn since(&self, seq: u64) -> Vec<String> {
+        let first = self.next_seq - self.lines.len() as u64;
+        let skip = seq.saturating_sub(first).min(self.lines.len() as u64) as usize;
+        self.lines.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// Number of notifications kept for the `notifications` command.
+pub const NOTIFICATION_HISTORY: usize = 64;
+
+/// ASCII bell rung when notifications are shown at the prompt.
+pub const BELL: char = '\x07';
+
+/// Message posted by a background producer for display between prompts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub seq: u64,
+    pub source: String,
+    pub message: String,
+}
+
+impl Notification {
+    /// Formats the notification as "[source] message".
+    pub fn format(&self) -> String {
+        let mut line = String::new();
+        line.push('[');
+        line.push_str(&self.source);
+        line.push_str("] ");
+        line.push_str(&self.message);
+        line
+    }
+}
+
+/// Bounded notification history with a cursor marking what was shown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationQueue {
+    history: VecDeque<Notification>,
+    capacity: usize,
+    next_seq: u64,
+    shown: u64,
+}
+
+impl NotificationQueue {
+    /// Creates an empty queue keeping at most `capacity` notifications.
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            history: VecDeque::new(),
+            capacity,
+            next_seq: 0,
+            shown: 0,
+        }
+    }
+
+    /// Posts a notification, dropping the oldest one when full.
+    pub fn post(&mut self, source: &str, message: &str) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if self.capacity == 0 {
+            return;
+        }
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(Notification {
+            seq,
+            source: source.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    /// Returns the number of notifications not yet shown.
+    pub fn pending(&self) -> usize {
+        self.history
+            .iter()
+            .filter(|notification| notification.seq >= self.shown)
+            .count()
+    }
+
+    /// Returns notifications not yet shown and marks them as shown.
+    pub fn take_pending(&mut self) -> Vec<Notification> {
+        let pending = self
+            .history
+            .iter()
+            .filter(|notification| notification.seq >= self.shown)
+            .cloned()
+            .collect();
+        self.shown = self.next_seq;
+        pending
+    }
+
+    /// Returns every kept notification, oldest first.
+    pub fn history(&self) -> Vec<Notification> {
+        self.history.iter().cloned().collect()
+    }
+
+    /// Drops every kept notification.
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.shown = self.next_seq;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,4 +298,117 @@ mod tests {
         let line = format_log(3, LogLevel::Warn, "heads up");
         assert_eq!(line, "[WARN][3] heads up");
     }
+
+    #[test]
+    fn log_buffer_keeps_recent_lines() {
+        let mut buffer = LogBuffer::new(3);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.tail(None), Vec::<String>::new());
+        for line in ["a", "b", "c", "d"] {
+            buffer.push(line);
+        }
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.next_seq(), 4);
+        assert_eq!(buffer.tail(None), vec!["b", "c", "d"]);
+        assert_eq!(buffer.tail(Some(2)), vec!["c", "d"]);
+        assert_eq!(buffer.tail(Some(10)), vec!["b", "c", "d"]);
+        assert_eq!(buffer.tail(Some(0)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn log_buffer_streams_lines_since_sequence() {
+        let mut buffer = LogBuffer::new(2);
+        buffer.push("a");
+        let cursor = buffer.next_seq();
+        assert_eq!(buffer.since(cursor), Vec::<String>::new());
+        buffer.push("b");
+        assert_eq!(buffer.since(cursor), vec!["b"]);
+        buffer.push("c");
+        buffer.push("d");
+        assert_eq!(buffer.since(0), vec!["c", "d"]);
+        assert_eq!(buffer.since(3), vec!["d"]);
+        assert_eq!(buffer.since(9), Vec::<String>::new());
+    }
+
+    #[test]
+    fn log_buffer_without_capacity_only_counts() {
+        let mut buffer = LogBuffer::new(0);
+        buffer.push("dropped");
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.next_seq(), 1);
+        assert_eq!(buffer.since(0), Vec::<String>::new());
+    }
+
+    #[test]
+    fn notification_queue_tracks_pending_and_history() {
+        let mut queue = NotificationQueue::new(2);
+        assert_eq!(queue.pending(), 0);
+        queue.post("cron", "backup done");
+        assert_eq!(queue.pending(), 1);
+        let shown = queue.take_pending();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].format(), "[cron] backup done");
+        assert!(queue.take_pending().is_empty());
+
+        queue.post("watchdog", "net-service restarted");
+        queue.post("init", "module started: net-manager");
+        assert_eq!(queue.pending(), 2);
+        let history = queue.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].seq, 1);
+        assert_eq!(history[1].source, "init");
+
+        queue.clear();
+        assert!(queue.history().is_empty());
+        assert_eq!(queue.pending(), 0);
+    }
+
+    #[test]
+    fn notification_queue_without_capacity_drops_posts() {
+        let mut queue = NotificationQueue::new(0);
+        queue.post("cron", "ignored");
+        assert!(queue.take_pending().is_empty());
+        assert!(queue.history().is_empty());
+    }
+
+    #[test]
+    fn format_log_handles_verbose_levels() {
+        assert_eq!(format_log(2, LogLevel::Trace, "t"), "[TRACE][2] t");
+        assert_eq!(format_log(2, LogLevel::Debug, "d"), "[DEBUG][2] d");
+    }
+
+    #[test]
+    fn log_level_names_round_trip() {
+        for level in [
+            LogLevel::Trace,
+            LogLevel::Debug,
+            LogLevel::Info,
+            LogLevel::Warn,
+            LogLevel::Error,
+        ] {
+            assert_eq!(LogLevel::parse(level.name()), Some(level));
+        }
+        assert_eq!(LogLevel::parse("INFO"), None);
+        assert!(LogLevel::Trace < LogLevel::Error);
+    }
+
+    #[test]
+    fn log_filter_applies_per_target_levels() {
+        let mut filter = LogFilter::new(LogLevel::Info);
+        assert!(filter.enabled("net-service", LogLevel::Info));
+        assert!(!filter.enabled("net-service", LogLevel::Debug));
+        filter.set("net-service", LogLevel::Error);
+        filter.set("fs-service", LogLevel::Trace);
+        assert!(!filter.enabled("net-service", LogLevel::Warn));
+        assert!(filter.enabled("fs-service", LogLevel::Trace));
+        assert_eq!(filter.level_for("kernel"), LogLevel::Info);
+        assert_eq!(filter.default_level(), LogLevel::Info);
+        assert_eq!(
+            filter.targets(),
+            vec![
+                ("fs-service".to_string(), LogLevel::Trace),
+                ("net-service".to_string(), LogLevel::Error),
+            ]
+        );
+    }
 }
@@ -3,8 +3,15 @@
 extern crate alloc;
 
 use alloc::collections::BTreeMap;
-use alloc::string::String;
+use alloc::format;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use user_fs_service::{FileSystem, FsError, Snapshot};
+
+/// Directory holding per-container fs overlays.
+pub const OVERLAY_DIR: &str = "/var/containers/rootfs";
+/// Directory holding container checkpoints.
+pub const CHECKPOINT_DIR: &str = "/var/containers/checkpoints";
 
 /// Container lifecycle state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +21,26 @@ pub enum ContainerState {
     Stopped,
 }
 
+impl ContainerState {
+    /// Returns the lowercase state label.
+    pub fn name(self) -> &'static str {
+        match self {
+            ContainerState::Created => "created",
+            ContainerState::Running => "running",
+            ContainerState::Stopped => "stopped",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "created" => Some(ContainerState::Created),
+            "running" => Some(ContainerState::Running),
+            "stopped" => Some(ContainerState::Stopped),
+            _ => None,
+        }
+    }
+}
+
 /// Container specification.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ContainerSpec {
@@ -38,6 +65,8 @@ pub enum ContainerError {
     NotFound,
     AlreadyRunning,
     NotRunning,
+    InvalidCheckpoint,
+    Fs(FsError),
 }
 
 /// In-memory container manager.
@@ -119,6 +148,138 @@ impl ContainerManager {
     pub fn list(&self) -> Vec<ContainerInfo> {
         self.containers.values().cloned().collect()
     }
+
+    /// Creates the fs overlay directory for a container and returns its path.
+    pub fn ensure_overlay(
+        &self,
+        fs: &mut FileSystem,
+        name: &str,
+    ) -> Result<String, ContainerError> {
+        if !self.containers.contains_key(name) {
+            return Err(ContainerError::NotFound);
+        }
+        let path = overlay_path(name);
+        ensure_dir(fs, &path)?;
+        Ok(path)
+    }
+
+    /// Saves a container's overlay and declared state under `CHECKPOINT_DIR`.
+    pub fn checkpoint(&self, fs: &mut FileSystem, name: &str) -> Result<String, ContainerError> {
+        let info = self.containers.get(name).ok_or(ContainerError::NotFound)?;
+        let snapshot = fs
+            .snapshot(&overlay_path(name))
+            .map_err(ContainerError::Fs)?;
+        let dir = checkpoint_path(name);
+        ensure_dir(fs, &dir)?;
+        fs.write_file(&format!("{dir}/state"), encode_state(info).as_bytes())
+            .map_err(ContainerError::Fs)?;
+        fs.write_file(&format!("{dir}/rootfs.snap"), &snapshot.to_bytes())
+            .map_err(ContainerError::Fs)?;
+        Ok(dir)
+    }
+
+    /// Restores a container's overlay and declared state from its checkpoint.
+    pub fn restore(
+        &mut self,
+        fs: &mut FileSystem,
+        name: &str,
+    ) -> Result<ContainerState, ContainerError> {
+        let dir = checkpoint_path(name);
+        let state = fs
+            .read_file(&format!("{dir}/state"))
+            .map_err(ContainerError::Fs)?;
+        let text = core::str::from_utf8(&state).map_err(|_| ContainerError::InvalidCheckpoint)?;
+        let info = parse_state(text)?;
+        if info.spec.name != name {
+            return Err(ContainerError::InvalidCheckpoint);
+        }
+        let bytes = fs
+            .read_file(&format!("{dir}/rootfs.snap"))
+            .map_err(ContainerError::Fs)?;
+        let snapshot =
+            Snapshot::from_bytes(&bytes).map_err(|_| ContainerError::InvalidCheckpoint)?;
+        ensure_dir(fs, OVERLAY_DIR)?;
+        fs.restore(&overlay_path(name), &snapshot)
+            .map_err(ContainerError::Fs)?;
+        let state = info.state;
+        self.containers.insert(name.to_string(), info);
+        Ok(state)
+    }
+}
+
+/// Returns the fs overlay directory for a container.
+pub fn overlay_path(name: &str) -> String {
+    format!("{OVERLAY_DIR}/{name}")
+}
+
+/// Returns the checkpoint directory for a container.
+pub fn checkpoint_path(name: &str) -> String {
+    format!("{CHECKPOINT_DIR}/{name}")
+}
+
+fn ensure_dir(fs: &mut FileSystem, path: &str) -> Result<(), ContainerError> {
+    let mut current = String::new();
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        current.push('/');
+        current.push_str(segment);
+        match fs.mkdir(&current) {
+            Ok(()) | Err(FsError::AlreadyExists) => {}
+            Err(err) => return Err(ContainerError::Fs(err)),
+        }
+    }
+    Ok(())
+}
+
+fn encode_state(info: &ContainerInfo) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("name={}\n", info.spec.name));
+    out.push_str(&format!("image={}\n", info.spec.image));
+    out.push_str(&format!("state={}\n", info.state.name()));
+    for arg in &info.spec.command {
+        out.push_str(&format!("arg={arg}\n"));
+    }
+    for (key, value) in &info.spec.env {
+        out.push_str(&format!("env={key}={value}\n"));
+    }
+    out
+}
+
+fn parse_state(text: &str) -> Result<ContainerInfo, ContainerError> {
+    let mut name = None;
+    let mut image = None;
+    let mut state = None;
+    let mut command = Vec::new();
+    let mut env = Vec::new();
+    for line in text.lines().filter(|line| !line.is_empty()) {
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(ContainerError::InvalidCheckpoint)?;
+        match key {
+            "name" => name = Some(value.to_string()),
+            "image" => image = Some(value.to_string()),
+            "state" => state = ContainerState::parse(value),
+            "arg" => command.push(value.to_string()),
+            "env" => {
+                let (key, value) = value
+                    .split_once('=')
+                    .ok_or(ContainerError::InvalidCheckpoint)?;
+                env.push((key.to_string(), value.to_string()));
+            }
+            _ => return Err(ContainerError::InvalidCheckpoint),
+        }
+    }
+    match (name, image, state) {
+        (Some(name), Some(image), Some(state)) if is_valid_name(&name) => Ok(ContainerInfo {
+            spec: ContainerSpec {
+                name,
+                image,
+                command,
+                env,
+            },
+            state,
+        }),
+        _ => Err(ContainerError::InvalidCheckpoint),
+    }
 }
 
 fn is_valid_name(name: &str) -> bool {
@@ -226,4 +387,168 @@ mod tests {
         let manager = ContainerManager::new();
         assert_eq!(manager.state("missing"), Err(ContainerError::NotFound));
     }
+
+    #[test]
+    fn state_names_round_trip() {
+        for state in [
+            ContainerState::Created,
+            ContainerState::Running,
+            ContainerState::Stopped,
+        ] {
+            assert_eq!(ContainerState::parse(state.name()), Some(state));
+        }
+        assert_eq!(ContainerState::parse("paused"), None);
+    }
+
+    #[test]
+    fn checkpoint_and_restore_overlay_and_state() {
+        let mut fs = FileSystem::new();
+        let mut manager = ContainerManager::new();
+        manager.create(spec("web")).unwrap();
+        let overlay = manager.ensure_overlay(&mut fs, "web").unwrap();
+        assert_eq!(overlay, "/var/containers/rootfs/web");
+        fs.write_file("/var/containers/rootfs/web/data", b"v1")
+            .unwrap();
+        manager.start("web").unwrap();
+
+        let dir = manager.checkpoint(&mut fs, "web").unwrap();
+        assert_eq!(dir, "/var/containers/checkpoints/web");
+        let state = fs
+            .read_file("/var/containers/checkpoints/web/state")
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(state).unwrap(),
+            "name=web\nimage=base:latest\nstate=running\narg=/bin/app\nenv=RUST_LOG=info\n"
+        );
+
+        fs.write_file("/var/containers/rootfs/web/data", b"v2")
+            .unwrap();
+        manager.stop("web").unwrap();
+        manager.remove("web").unwrap();
+        assert_eq!(manager.restore(&mut fs, "web"), Ok(ContainerState::Running));
+        assert_eq!(manager.state("web"), Ok(ContainerState::Running));
+        assert_eq!(manager.list()[0].spec, spec("web"));
+        assert_eq!(
+            fs.read_file("/var/containers/rootfs/web/data").unwrap(),
+            b"v1".to_vec()
+        );
+    }
+
+    #[test]
+    fn checkpoint_requires_container_and_overlay() {
+        let mut fs = FileSystem::new();
+        let mut manager = ContainerManager::new();
+        assert_eq!(
+            manager.checkpoint(&mut fs, "web"),
+            Err(ContainerError::NotFound)
+        );
+        assert_eq!(
+            manager.ensure_overlay(&mut fs, "web"),
+            Err(ContainerError::NotFound)
+        );
+        manager.create(spec("web")).unwrap();
+        assert_eq!(
+            manager.checkpoint(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::NotFound))
+        );
+        fs.write_file("/var", b"not a dir").unwrap();
+        assert_eq!(
+            manager.ensure_overlay(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::NotDir))
+        );
+    }
+
+    #[test]
+    fn checkpoint_surfaces_write_failures() {
+        let mut fs = FileSystem::new();
+        let mut manager = ContainerManager::new();
+        manager.create(spec("web")).unwrap();
+        manager.ensure_overlay(&mut fs, "web").unwrap();
+        fs.write_file("/var/containers/checkpoints", b"x").unwrap();
+        assert_eq!(
+            manager.checkpoint(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::NotDir))
+        );
+        fs.remove("/var/containers/checkpoints").unwrap();
+        fs.mkdir("/var/containers/checkpoints").unwrap();
+        fs.mkdir("/var/containers/checkpoints/web").unwrap();
+        fs.mkdir("/var/containers/checkpoints/web/state").unwrap();
+        assert_eq!(
+            manager.checkpoint(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::IsDir))
+        );
+        fs.remove("/var/containers/checkpoints/web/state").unwrap();
+        fs.mkdir("/var/containers/checkpoints/web/rootfs.snap")
+            .unwrap();
+        assert_eq!(
+            manager.checkpoint(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::IsDir))
+        );
+    }
+
+    #[test]
+    fn restore_rejects_missing_or_invalid_checkpoints() {
+        let mut fs = FileSystem::new();
+        let mut manager = ContainerManager::new();
+        assert_eq!(
+            manager.restore(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::NotFound))
+        );
+        fs.mkdir("/var").unwrap();
+        fs.mkdir("/var/containers").unwrap();
+        fs.mkdir("/var/containers/checkpoints").unwrap();
+        fs.mkdir("/var/containers/checkpoints/web").unwrap();
+        let state_path = "/var/containers/checkpoints/web/state";
+        for text in [
+            &b"\xff"[..],
+            b"name=web",
+            b"garbage",
+            b"name=web\nimage=x\nstate=paused\n",
+            b"name=web\nimage=x\nstate=created\nenv=NOVALUE\n",
+            b"name=web\nimage=x\nstate=created\nbogus=1\n",
+            b"name=Bad\nimage=x\nstate=created\n",
+            b"name=api\nimage=x\nstate=created\n",
+        ] {
+            fs.write_file(state_path, text).unwrap();
+            assert_eq!(
+                manager.restore(&mut fs, "web"),
+                Err(ContainerError::InvalidCheckpoint)
+            );
+        }
+        fs.write_file(state_path, b"name=web\nimage=x\nstate=created\n")
+            .unwrap();
+        assert_eq!(
+            manager.restore(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::NotFound))
+        );
+        fs.write_file("/var/containers/checkpoints/web/rootfs.snap", b"junk")
+            .unwrap();
+        assert_eq!(
+            manager.restore(&mut fs, "web"),
+            Err(ContainerError::InvalidCheckpoint)
+        );
+    }
+
+    #[test]
+    fn restore_surfaces_overlay_failures() {
+        let mut fs = FileSystem::new();
+        let mut manager = ContainerManager::new();
+        manager.create(spec("web")).unwrap();
+        manager.ensure_overlay(&mut fs, "web").unwrap();
+        manager.checkpoint(&mut fs, "web").unwrap();
+        fs.remove("/var/containers/rootfs/web").unwrap();
+        fs.write_file("/var/containers/rootfs/web", b"file")
+            .unwrap();
+        assert_eq!(
+            manager.restore(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::NotDir))
+        );
+        fs.remove("/var/containers/rootfs/web").unwrap();
+        fs.remove("/var/containers/rootfs").unwrap();
+        fs.write_file("/var/containers/rootfs", b"file").unwrap();
+        assert_eq!(
+            manager.restore(&mut fs, "web"),
+            Err(ContainerError::Fs(FsError::NotDir))
+        );
+    }
 }
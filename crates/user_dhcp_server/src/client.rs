@@ -0,0 +1,59 @@
+use user_net_service::{MacAddr, NetError, NetManager};
+
+use crate::frame::Datagram;
+use crate::message::{DhcpMessage, MessageType, CLIENT_PORT, FLAG_BROADCAST, SERVER_PORT};
+
+/// Builds the broadcast DHCPDISCOVER `iface` sends to find a server.
+///
+/// The host name set on `net` goes out as option 12, so the server can
+/// record it against the lease.
+pub fn discover(net: &NetManager, iface: &str, xid: u32) -> Result<Datagram, NetError> {
+    let interface = net
+        .list()
+        .into_iter()
+        .find(|entry| entry.name == iface)
+        .ok_or(NetError::NotFound)?;
+    let mut message = DhcpMessage::request(MessageType::Discover, xid, interface.mac.0);
+    message.flags = FLAG_BROADCAST;
+    message.hostname = net.hostname().map(|name| name.into());
+    Ok(Datagram {
+        dst_mac: MacAddr([0xff; 6]),
+        src_mac: interface.mac,
+        src_ip: [0; 4],
+        dst_ip: [255; 4],
+        src_port: CLIENT_PORT,
+        dst_port: SERVER_PORT,
+        payload: message.encode(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discover_announces_the_hostname() {
+        let mut net = NetManager::new();
+        net.add_interface("eth0").unwrap();
+        let mac = net.list()[0].mac;
+        let datagram = discover(&net, "eth0", 7).unwrap();
+        assert_eq!(datagram.src_mac, mac);
+        assert_eq!(datagram.dst_port, SERVER_PORT);
+        let message = DhcpMessage::decode(&datagram.payload).unwrap();
+        assert_eq!(message.kind, MessageType::Discover);
+        assert_eq!(message.chaddr, mac.0);
+        assert_eq!(message.hostname, None);
+
+        net.set_hostname("lab1").unwrap();
+        let datagram = discover(&net, "eth0", 8).unwrap();
+        let message = DhcpMessage::decode(&datagram.payload).unwrap();
+        assert_eq!(message.xid, 8);
+        assert_eq!(message.hostname.as_deref(), Some("lab1"));
+        assert_eq!(
+            Datagram::decode(&datagram.encode()).map(|frame| frame.payload),
+            Some(datagram.payload)
+        );
+
+        assert_eq!(discover(&net, "eth9", 9), Err(NetError::NotFound));
+    }
+}
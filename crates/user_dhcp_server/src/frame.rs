@@ -0,0 +1,144 @@
+use alloc::vec::Vec;
+
+use user_net_service::device::{ethernet_frame, ETHERNET_HEADER_LEN};
+use user_net_service::MacAddr;
+
+/// IP protocol number for UDP.
+pub const IP_PROTOCOL_UDP: u8 = 17;
+/// Ethertype of IPv4 frames.
+pub const ETHERTYPE_IPV4: u16 = 0x0800;
+
+const IPV4_HEADER_LEN: usize = 20;
+const UDP_HEADER_LEN: usize = 8;
+const DEFAULT_TTL: u8 = 64;
+
+/// One UDP datagram carried in an IPv4 Ethernet frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datagram {
+    pub dst_mac: MacAddr,
+    pub src_mac: MacAddr,
+    pub src_ip: [u8; 4],
+    pub dst_ip: [u8; 4],
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub payload: Vec<u8>,
+}
+
+impl Datagram {
+    /// Encodes the datagram as an Ethernet frame; the UDP checksum is left at zero.
+    pub fn encode(&self) -> Vec<u8> {
+        let udp_len = UDP_HEADER_LEN + self.payload.len();
+        let total_len = IPV4_HEADER_LEN + udp_len;
+        let mut packet = Vec::with_capacity(total_len);
+        packet.extend_from_slice(&[0x45, 0]);
+        packet.extend_from_slice(&(total_len as u16).to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0, 0, DEFAULT_TTL, IP_PROTOCOL_UDP, 0, 0]);
+        packet.extend_from_slice(&self.src_ip);
+        packet.extend_from_slice(&self.dst_ip);
+        let sum = header_checksum(&packet);
+        packet[10..12].copy_from_slice(&sum.to_be_bytes());
+        packet.extend_from_slice(&self.src_port.to_be_bytes());
+        packet.extend_from_slice(&self.dst_port.to_be_bytes());
+        packet.extend_from_slice(&(udp_len as u16).to_be_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        packet.extend_from_slice(&self.payload);
+        ethernet_frame(self.dst_mac, self.src_mac, ETHERTYPE_IPV4, &packet)
+    }
+
+    /// Decodes an unfragmented IPv4 UDP frame, checking the IPv4 header checksum.
+    pub fn decode(frame: &[u8]) -> Option<Self> {
+        let ip = frame.get(ETHERNET_HEADER_LEN..)?;
+        if frame[12..14] != ETHERTYPE_IPV4.to_be_bytes() || ip.len() < IPV4_HEADER_LEN {
+            return None;
+        }
+        let header_len = usize::from(ip[0] & 0x0f) * 4;
+        let total_len = usize::from(u16::from_be_bytes([ip[2], ip[3]]));
+        let more_fragments = ip[6] & 0x20 != 0;
+        let offset = u16::from_be_bytes([ip[6] & 0x1f, ip[7]]);
+        if ip[0] >> 4 != 4
+            || header_len < IPV4_HEADER_LEN
+            || total_len < header_len + UDP_HEADER_LEN
+            || total_len > ip.len()
+            || more_fragments
+            || offset != 0
+            || ip[9] != IP_PROTOCOL_UDP
+            || header_checksum(&ip[..header_len]) != 0
+        {
+            return None;
+        }
+        let udp = &ip[header_len..total_len];
+        let udp_len = usize::from(u16::from_be_bytes([udp[4], udp[5]]));
+        if udp_len < UDP_HEADER_LEN || udp_len > udp.len() {
+            return None;
+        }
+        let mac = |at: usize| MacAddr(frame[at..at + 6].try_into().unwrap_or([0; 6]));
+        let addr = |at: usize| [ip[at], ip[at + 1], ip[at + 2], ip[at + 3]];
+        Some(Self {
+            dst_mac: mac(0),
+            src_mac: mac(6),
+            src_ip: addr(12),
+            dst_ip: addr(16),
+            src_port: u16::from_be_bytes([udp[0], udp[1]]),
+            dst_port: u16::from_be_bytes([udp[2], udp[3]]),
+            payload: udp[UDP_HEADER_LEN..udp_len].to_vec(),
+        })
+    }
+}
+
+/// Internet checksum of an IPv4 header; zero when a received header is intact.
+fn header_checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = header
+        .chunks(2)
+        .map(|pair| u32::from(u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Datagram {
+        Datagram {
+            dst_mac: MacAddr([0xff; 6]),
+            src_mac: MacAddr([0x52, 0x54, 0x00, 0, 0, 1]),
+            src_ip: [10, 0, 2, 1],
+            dst_ip: [255, 255, 255, 255],
+            src_port: 67,
+            dst_port: 68,
+            payload: b"lease".to_vec(),
+        }
+    }
+
+    #[test]
+    fn datagram_round_trips() {
+        let datagram = sample();
+        let frame = datagram.encode();
+        assert_eq!(frame.len(), ETHERNET_HEADER_LEN + 20 + 8 + 5);
+        assert_eq!(header_checksum(&frame[14..34]), 0);
+        assert_eq!(Datagram::decode(&frame), Some(datagram));
+
+        let mut padded = frame.clone();
+        padded.extend_from_slice(&[0; 6]);
+        assert_eq!(Datagram::decode(&padded).unwrap().payload, b"lease");
+    }
+
+    #[test]
+    fn decode_skips_other_traffic() {
+        let frame = sample().encode();
+        let mut arp = frame.clone();
+        arp[12..14].copy_from_slice(&0x0806u16.to_be_bytes());
+        assert_eq!(Datagram::decode(&arp), None);
+        let mut tcp = frame.clone();
+        tcp[14 + 9] = 6;
+        assert_eq!(Datagram::decode(&tcp), None);
+        let mut fragment = frame.clone();
+        fragment[14 + 6] = 0x20;
+        assert_eq!(Datagram::decode(&fragment), None);
+        assert_eq!(Datagram::decode(&frame[..frame.len() - 1]), None);
+        assert_eq!(Datagram::decode(&frame[..20]), None);
+    }
+}
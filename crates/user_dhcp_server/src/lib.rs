@@ -0,0 +1,663 @@
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use user_net_service::{is_valid_ipv4, MacAddr};
+
+mod client;
+mod frame;
+mod message;
+
+pub use client::discover;
+pub use frame::{Datagram, ETHERTYPE_IPV4, IP_PROTOCOL_UDP};
+pub use message::{
+    DhcpMessage, MessageError, MessageType, CLIENT_PORT, FIXED_LEN, FLAG_BROADCAST,
+    MAX_DNS_SERVERS, OP_REPLY, OP_REQUEST, SERVER_PORT,
+};
+
+/// Lease length handed out unless the pool is configured otherwise.
+pub const DEFAULT_LEASE_SECS: u32 = 3600;
+/// Shortest lease a pool may be configured with.
+pub const MIN_LEASE_SECS: u32 = 60;
+/// How long an offered address stays reserved for the client it was offered to.
+pub const OFFER_HOLD_SECS: u64 = 60;
+/// How long an address a client declined stays out of the pool.
+pub const DECLINE_HOLD_SECS: u64 = 600;
+/// Most addresses one pool may hold.
+pub const MAX_POOL_SIZE: u32 = 1024;
+
+/// Errors returned when configuring the server or editing leases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhcpError {
+    /// Server address is not a host address of a /1 to /30 subnet.
+    InvalidAddress,
+    /// Pool is reversed, too large, outside the server's subnet or contains the server.
+    InvalidPool,
+    /// Lease length is below `MIN_LEASE_SECS`.
+    InvalidLease,
+    /// More DNS servers than `MAX_DNS_SERVERS`.
+    TooManyDnsServers,
+    NotFound,
+}
+
+/// Addresses and options the server hands out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DhcpConfig {
+    pub server: [u8; 4],
+    pub prefix: u8,
+    pub pool_first: [u8; 4],
+    pub pool_last: [u8; 4],
+    pub lease_secs: u32,
+    pub router: Option<[u8; 4]>,
+    pub dns: Vec<[u8; 4]>,
+}
+
+impl DhcpConfig {
+    /// Creates a config for `server/prefix` handing out `first..=last` with default options.
+    pub fn new(server: [u8; 4], prefix: u8, first: [u8; 4], last: [u8; 4]) -> Self {
+        Self {
+            server,
+            prefix,
+            pool_first: first,
+            pool_last: last,
+            lease_secs: DEFAULT_LEASE_SECS,
+            router: None,
+            dns: Vec::new(),
+        }
+    }
+
+    /// Checks the addresses, lease length and DNS server count.
+    pub fn validate(&self) -> Result<(), DhcpError> {
+        if self.prefix == 0 || self.prefix > 30 || !self.is_host(self.server) {
+            return Err(DhcpError::InvalidAddress);
+        }
+        let (first, last) = (
+            u32::from_be_bytes(self.pool_first),
+            u32::from_be_bytes(self.pool_last),
+        );
+        if first > last
+            || last - first >= MAX_POOL_SIZE
+            || !self.is_host(self.pool_first)
+            || !self.is_host(self.pool_last)
+            || self.in_pool(self.server)
+        {
+            return Err(DhcpError::InvalidPool);
+        }
+        if self.lease_secs < MIN_LEASE_SECS {
+            return Err(DhcpError::InvalidLease);
+        }
+        if self.dns.len() > MAX_DNS_SERVERS {
+            return Err(DhcpError::TooManyDnsServers);
+        }
+        Ok(())
+    }
+
+    /// Returns how many addresses the pool holds.
+    pub fn pool_size(&self) -> u32 {
+        u32::from_be_bytes(self.pool_last)
+            .saturating_sub(u32::from_be_bytes(self.pool_first))
+            .saturating_add(1)
+    }
+
+    /// Returns the netmask for `prefix`.
+    pub fn subnet_mask(&self) -> [u8; 4] {
+        let mask = u32::MAX
+            .checked_shl(32 - u32::from(self.prefix))
+            .unwrap_or(0);
+        mask.to_be_bytes()
+    }
+
+    fn in_pool(&self, addr: [u8; 4]) -> bool {
+        (u32::from_be_bytes(self.pool_first)..=u32::from_be_bytes(self.pool_last))
+            .contains(&u32::from_be_bytes(addr))
+    }
+
+    /// Returns true for an address in the server's subnet other than the network and broadcast.
+    fn is_host(&self, addr: [u8; 4]) -> bool {
+        let mask = u32::from_be_bytes(self.subnet_mask());
+        let (addr, server) = (u32::from_be_bytes(addr), u32::from_be_bytes(self.server));
+        addr & mask == server & mask && addr & !mask != 0 && addr & !mask != !mask
+    }
+}
+
+/// Whether an address is reserved by an offer, held by a client or blocked after a decline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaseState {
+    Offered,
+    Bound,
+    Declined,
+}
+
+/// One pool address in use; `expires` is in seconds on the caller's clock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lease {
+    pub addr: [u8; 4],
+    pub mac: MacAddr,
+    pub state: LeaseState,
+    pub expires: u64,
+    /// Host name the client sent in its last DISCOVER or REQUEST.
+    pub hostname: Option<String>,
+}
+
+/// DHCP server answering from a single address pool.
+///
+/// Like the TCP socket it never touches a device: `handle` takes a decoded
+/// message and returns the reply, and `serve` does the same for UDP
+/// datagrams. Time is passed in as seconds so leases expire on the
+/// caller's clock.
+#[derive(Debug, Clone)]
+pub struct DhcpServer {
+    config: DhcpConfig,
+    leases: BTreeMap<[u8; 4], Lease>,
+}
+
+impl DhcpServer {
+    /// Creates a server with no leases.
+    pub fn new(config: DhcpConfig) -> Result<Self, DhcpError> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            leases: BTreeMap::new(),
+        })
+    }
+
+    /// Returns the active configuration.
+    pub fn config(&self) -> &DhcpConfig {
+        &self.config
+    }
+
+    /// Returns every lease ordered by address.
+    pub fn leases(&self) -> Vec<Lease> {
+        self.leases.values().cloned().collect()
+    }
+
+    /// Returns how many pool addresses are neither leased, offered nor declined.
+    pub fn free_count(&self) -> u32 {
+        self.config.pool_size() - self.leases.len() as u32
+    }
+
+    /// Frees the lease on `addr` so the address can be handed out again.
+    pub fn release(&mut self, addr: [u8; 4]) -> Result<(), DhcpError> {
+        self.leases
+            .remove(&addr)
+            .map(|_| ())
+            .ok_or(DhcpError::NotFound)
+    }
+
+    /// Drops leases that expired at or before `now`, returning how many went.
+    pub fn expire(&mut self, now: u64) -> usize {
+        let before = self.leases.len();
+        self.leases.retain(|_, lease| lease.expires > now);
+        before - self.leases.len()
+    }
+
+    /// Answers one client message, updating the leases.
+    ///
+    /// DISCOVER reserves an address and returns an OFFER; REQUEST binds an
+    /// offered or held address with an ACK, or returns a NAK. DECLINE blocks
+    /// the address for `DECLINE_HOLD_SECS` and RELEASE frees it; neither is
+    /// answered.
+    pub fn handle(&mut self, msg: &DhcpMessage, now: u64) -> Option<DhcpMessage> {
+        if msg.op != OP_REQUEST {
+            return None;
+        }
+        self.expire(now);
+        let mac = MacAddr(msg.chaddr);
+        match msg.kind {
+            MessageType::Discover => {
+                let addr = self.pick(mac, msg.requested_ip)?;
+                let lease = self.leases.entry(addr).or_insert(Lease {
+                    addr,
+                    mac,
+                    state: LeaseState::Offered,
+                    expires: 0,
+                    hostname: None,
+                });
+                if lease.state == LeaseState::Offered {
+                    lease.expires = now + OFFER_HOLD_SECS;
+                }
+                if msg.hostname.is_some() {
+                    lease.hostname = msg.hostname.clone();
+                }
+                Some(self.reply(msg, MessageType::Offer, addr))
+            }
+            MessageType::Request => {
+                if msg.server_id.is_some_and(|id| id != self.config.server) {
+                    // The client took another server's offer.
+                    self.leases
+                        .retain(|_, lease| lease.mac != mac || lease.state != LeaseState::Offered);
+                    return None;
+                }
+                let addr = msg.requested_ip.unwrap_or(msg.ciaddr);
+                let owned = self.owner(addr) == Some(mac);
+                let free = self.config.in_pool(addr) && !self.leases.contains_key(&addr);
+                if !owned && (msg.server_id.is_some() || !free) {
+                    return Some(self.reply(msg, MessageType::Nak, [0; 4]));
+                }
+                let hostname = match &msg.hostname {
+                    Some(name) => Some(name.clone()),
+                    None if owned => self
+                        .leases
+                        .get(&addr)
+                        .and_then(|lease| lease.hostname.clone()),
+                    None => None,
+                };
+                self.leases.retain(|_, lease| lease.mac != mac);
+                self.leases.insert(
+                    addr,
+                    Lease {
+                        addr,
+                        mac,
+                        state: LeaseState::Bound,
+                        expires: now + u64::from(self.config.lease_secs),
+                        hostname,
+                    },
+                );
+                Some(self.reply(msg, MessageType::Ack, addr))
+            }
+            MessageType::Decline => {
+                let addr = msg.requested_ip?;
+                if self.owner(addr) == Some(mac) {
+                    self.leases.insert(
+                        addr,
+                        Lease {
+                            addr,
+                            mac,
+                            state: LeaseState::Declined,
+                            expires: now + DECLINE_HOLD_SECS,
+                            hostname: None,
+                        },
+                    );
+                }
+                None
+            }
+            MessageType::Release => {
+                if self.owner(msg.ciaddr) == Some(mac) {
+                    self.leases.remove(&msg.ciaddr);
+                }
+                None
+            }
+            MessageType::Inform => Some(self.reply(msg, MessageType::Ack, [0; 4])),
+            MessageType::Offer | MessageType::Ack | MessageType::Nak => None,
+        }
+    }
+
+    /// Answers a DHCP request sent to the server port.
+    ///
+    /// Replies to clients that already have an address go to that address;
+    /// everything else is broadcast.
+    pub fn serve(
+        &mut self,
+        datagram: &Datagram,
+        server_mac: MacAddr,
+        now: u64,
+    ) -> Option<Datagram> {
+        if datagram.dst_port != SERVER_PORT {
+            return None;
+        }
+        let msg = DhcpMessage::decode(&datagram.payload).ok()?;
+        let reply = self.handle(&msg, now)?;
+        let (dst_mac, dst_ip) = if msg.ciaddr != [0; 4] && reply.kind != MessageType::Nak {
+            (datagram.src_mac, msg.ciaddr)
+        } else {
+            (MacAddr([0xff; 6]), [255; 4])
+        };
+        Some(Datagram {
+            dst_mac,
+            src_mac: server_mac,
+            src_ip: self.config.server,
+            dst_ip,
+            src_port: SERVER_PORT,
+            dst_port: CLIENT_PORT,
+            payload: reply.encode(),
+        })
+    }
+
+    /// Returns the client an offered or bound address belongs to.
+    fn owner(&self, addr: [u8; 4]) -> Option<MacAddr> {
+        self.leases
+            .get(&addr)
+            .filter(|lease| lease.state != LeaseState::Declined)
+            .map(|lease| lease.mac)
+    }
+
+    /// Picks the address to offer: the client's current one, the one it asked for, or the first free.
+    fn pick(&self, mac: MacAddr, requested: Option<[u8; 4]>) -> Option<[u8; 4]> {
+        if let Some(lease) = self
+            .leases
+            .values()
+            .find(|lease| lease.mac == mac && lease.state != LeaseState::Declined)
+        {
+            return Some(lease.addr);
+        }
+        if let Some(addr) = requested {
+            if self.config.in_pool(addr) && !self.leases.contains_key(&addr) {
+                return Some(addr);
+            }
+        }
+        (u32::from_be_bytes(self.config.pool_first)..=u32::from_be_bytes(self.config.pool_last))
+            .map(u32::to_be_bytes)
+            .find(|addr| !self.leases.contains_key(addr))
+    }
+
+    fn reply(&self, msg: &DhcpMessage, kind: MessageType, yiaddr: [u8; 4]) -> DhcpMessage {
+        let mut reply = DhcpMessage::request(kind, msg.xid, msg.chaddr);
+        reply.op = OP_REPLY;
+        reply.flags = msg.flags;
+        reply.giaddr = msg.giaddr;
+        reply.server_id = Some(self.config.server);
+        if kind == MessageType::Nak {
+            return reply;
+        }
+        reply.ciaddr = msg.ciaddr;
+        reply.yiaddr = yiaddr;
+        reply.subnet_mask = Some(self.config.subnet_mask());
+        reply.router = self.config.router;
+        reply.dns = self.config.dns.clone();
+        if yiaddr != [0; 4] {
+            reply.lease_secs = Some(self.config.lease_secs);
+        }
+        reply
+    }
+}
+
+/// Parses a dotted-quad IPv4 address.
+pub fn parse_ipv4(text: &str) -> Option<[u8; 4]> {
+    if !is_valid_ipv4(text) {
+        return None;
+    }
+    let mut octets = [0u8; 4];
+    for (slot, part) in octets.iter_mut().zip(text.split('.')) {
+        *slot = part.parse().ok()?;
+    }
+    Some(octets)
+}
+
+/// Formats an IPv4 address as a dotted quad.
+pub fn format_ipv4(addr: [u8; 4]) -> String {
+    format!("{}.{}.{}.{}", addr[0], addr[1], addr[2], addr[3])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER: [u8; 4] = [10, 0, 2, 1];
+    const CLIENT: [u8; 6] = [0x52, 0x54, 0x00, 0, 0, 0x10];
+    const OTHER: [u8; 6] = [0x52, 0x54, 0x00, 0, 0, 0x20];
+
+    fn server() -> DhcpServer {
+        DhcpServer::new(DhcpConfig::new(
+            SERVER,
+            24,
+            [10, 0, 2, 100],
+            [10, 0, 2, 102],
+        ))
+        .unwrap()
+    }
+
+    fn msg(kind: MessageType, chaddr: [u8; 6]) -> DhcpMessage {
+        DhcpMessage::request(kind, 42, chaddr)
+    }
+
+    fn request(addr: [u8; 4], chaddr: [u8; 6]) -> DhcpMessage {
+        let mut request = msg(MessageType::Request, chaddr);
+        request.requested_ip = Some(addr);
+        request.server_id = Some(SERVER);
+        request
+    }
+
+    fn bind(server: &mut DhcpServer, chaddr: [u8; 6], now: u64) -> [u8; 4] {
+        let offer = server
+            .handle(&msg(MessageType::Discover, chaddr), now)
+            .unwrap();
+        let ack = server.handle(&request(offer.yiaddr, chaddr), now).unwrap();
+        assert_eq!(ack.kind, MessageType::Ack);
+        ack.yiaddr
+    }
+
+    #[test]
+    fn config_is_validated() {
+        let pool =
+            |first: u8, last: u8| DhcpConfig::new(SERVER, 24, [10, 0, 2, first], [10, 0, 2, last]);
+        assert_eq!(pool(100, 199).validate(), Ok(()));
+        assert_eq!(pool(100, 199).pool_size(), 100);
+        assert_eq!(pool(100, 199).subnet_mask(), [255, 255, 255, 0]);
+        assert_eq!(pool(150, 100).validate(), Err(DhcpError::InvalidPool));
+        assert_eq!(pool(1, 10).validate(), Err(DhcpError::InvalidPool));
+        assert_eq!(pool(100, 255).validate(), Err(DhcpError::InvalidPool));
+        let outside = DhcpConfig::new(SERVER, 24, [10, 0, 3, 1], [10, 0, 3, 9]);
+        assert_eq!(outside.validate(), Err(DhcpError::InvalidPool));
+        let mut config = pool(100, 199);
+        config.prefix = 31;
+        assert_eq!(config.validate(), Err(DhcpError::InvalidAddress));
+        config.prefix = 24;
+        config.server = [10, 0, 2, 0];
+        assert_eq!(config.validate(), Err(DhcpError::InvalidAddress));
+        let mut config = pool(100, 199);
+        config.lease_secs = MIN_LEASE_SECS - 1;
+        assert_eq!(DhcpServer::new(config).err(), Some(DhcpError::InvalidLease));
+        let mut config = pool(100, 199);
+        config.dns = vec![[10, 0, 2, 3]; MAX_DNS_SERVERS];
+        assert_eq!(config.validate(), Ok(()));
+        config.dns.push([1, 1, 1, 1]);
+        assert_eq!(config.validate(), Err(DhcpError::TooManyDnsServers));
+        let big = DhcpConfig::new([10, 0, 0, 1], 16, [10, 0, 1, 0], [10, 0, 5, 0]);
+        assert_eq!(big.validate(), Err(DhcpError::InvalidPool));
+    }
+
+    #[test]
+    fn discover_request_binds_an_address() {
+        let mut server = server();
+        let mut discover = msg(MessageType::Discover, CLIENT);
+        discover.flags = FLAG_BROADCAST;
+        discover.hostname = Some("lab2".to_string());
+        let offer = server.handle(&discover, 10).unwrap();
+        assert_eq!(offer.kind, MessageType::Offer);
+        assert_eq!(
+            (offer.op, offer.xid, offer.flags),
+            (OP_REPLY, 42, FLAG_BROADCAST)
+        );
+        assert_eq!(offer.yiaddr, [10, 0, 2, 100]);
+        assert_eq!(offer.server_id, Some(SERVER));
+        assert_eq!(offer.lease_secs, Some(DEFAULT_LEASE_SECS));
+        assert_eq!(offer.subnet_mask, Some([255, 255, 255, 0]));
+        assert_eq!(server.leases()[0].state, LeaseState::Offered);
+        assert_eq!(server.free_count(), 2);
+
+        assert_eq!(
+            server.handle(&discover, 11).unwrap().yiaddr,
+            [10, 0, 2, 100]
+        );
+        let ack = server
+            .handle(&request([10, 0, 2, 100], CLIENT), 12)
+            .unwrap();
+        assert_eq!((ack.kind, ack.yiaddr), (MessageType::Ack, [10, 0, 2, 100]));
+        assert_eq!(
+            server.leases(),
+            vec![Lease {
+                addr: [10, 0, 2, 100],
+                mac: MacAddr(CLIENT),
+                state: LeaseState::Bound,
+                expires: 12 + u64::from(DEFAULT_LEASE_SECS),
+                hostname: Some("lab2".to_string()),
+            }]
+        );
+
+        let mut renew = msg(MessageType::Request, CLIENT);
+        renew.ciaddr = [10, 0, 2, 100];
+        assert_eq!(server.handle(&renew, 100).unwrap().kind, MessageType::Ack);
+        assert_eq!(
+            server.leases()[0].expires,
+            100 + u64::from(DEFAULT_LEASE_SECS)
+        );
+    }
+
+    #[test]
+    fn requests_for_other_addresses_are_refused() {
+        let mut server = server();
+        server
+            .handle(&msg(MessageType::Discover, CLIENT), 0)
+            .unwrap();
+        let nak = server.handle(&request([10, 0, 2, 100], OTHER), 1).unwrap();
+        assert_eq!(
+            (nak.kind, nak.yiaddr, nak.lease_secs),
+            (MessageType::Nak, [0; 4], None)
+        );
+        assert_eq!(
+            server
+                .handle(&request([10, 0, 2, 101], CLIENT), 1)
+                .unwrap()
+                .kind,
+            MessageType::Nak
+        );
+
+        let mut elsewhere = request([10, 0, 2, 100], CLIENT);
+        elsewhere.server_id = Some([10, 0, 2, 254]);
+        assert_eq!(server.handle(&elsewhere, 2), None);
+        assert!(server.leases().is_empty());
+
+        let mut reboot = msg(MessageType::Request, OTHER);
+        reboot.requested_ip = Some([10, 0, 2, 102]);
+        assert_eq!(server.handle(&reboot, 3).unwrap().kind, MessageType::Ack);
+        reboot.requested_ip = Some([10, 0, 2, 50]);
+        assert_eq!(server.handle(&reboot, 4).unwrap().kind, MessageType::Nak);
+
+        let mut reply = server
+            .handle(&msg(MessageType::Discover, CLIENT), 5)
+            .unwrap();
+        reply.op = OP_REPLY;
+        assert_eq!(server.handle(&reply, 6), None);
+    }
+
+    #[test]
+    fn pool_runs_out_and_leases_expire() {
+        let mut server = server();
+        let mut asks_for_last = msg(MessageType::Discover, CLIENT);
+        asks_for_last.requested_ip = Some([10, 0, 2, 102]);
+        assert_eq!(
+            server.handle(&asks_for_last, 0).unwrap().yiaddr,
+            [10, 0, 2, 102]
+        );
+        let macs = [OTHER, [2, 0, 0, 0, 0, 3], [2, 0, 0, 0, 0, 4]];
+        let offers: Vec<_> = macs
+            .iter()
+            .map(|mac| {
+                server
+                    .handle(&msg(MessageType::Discover, *mac), 0)
+                    .map(|o| o.yiaddr)
+            })
+            .collect();
+        assert_eq!(
+            offers,
+            vec![Some([10, 0, 2, 100]), Some([10, 0, 2, 101]), None]
+        );
+        assert_eq!(server.free_count(), 0);
+
+        let offer = server.handle(&msg(MessageType::Discover, macs[2]), OFFER_HOLD_SECS);
+        assert!(offer.is_some());
+        assert_eq!(server.expire(OFFER_HOLD_SECS * 2), 1);
+        assert_eq!(server.release([10, 0, 2, 100]), Err(DhcpError::NotFound));
+    }
+
+    #[test]
+    fn decline_and_release_update_the_pool() {
+        let mut server = server();
+        server.handle(&msg(MessageType::Discover, CLIENT), 0);
+        let mut decline = msg(MessageType::Decline, CLIENT);
+        decline.requested_ip = Some([10, 0, 2, 100]);
+        assert_eq!(server.handle(&decline, 1), None);
+        assert_eq!(server.leases()[0].state, LeaseState::Declined);
+        let offer = server
+            .handle(&msg(MessageType::Discover, CLIENT), 2)
+            .unwrap();
+        assert_eq!(offer.yiaddr, [10, 0, 2, 101]);
+        assert_eq!(server.expire(1 + DECLINE_HOLD_SECS), 2);
+
+        assert_eq!(bind(&mut server, CLIENT, 3), [10, 0, 2, 100]);
+        let mut release = msg(MessageType::Release, OTHER);
+        release.ciaddr = [10, 0, 2, 100];
+        server.handle(&release, 4);
+        assert_eq!(server.leases().len(), 1);
+        release.chaddr = CLIENT;
+        assert_eq!(server.handle(&release, 4), None);
+        assert!(server.leases().is_empty());
+
+        assert_eq!(bind(&mut server, CLIENT, 5), [10, 0, 2, 100]);
+        assert_eq!(server.release([10, 0, 2, 100]), Ok(()));
+        let mut inform = msg(MessageType::Inform, OTHER);
+        inform.ciaddr = [10, 0, 2, 50];
+        let ack = server.handle(&inform, 6).unwrap();
+        assert_eq!(
+            (ack.kind, ack.yiaddr, ack.ciaddr),
+            (MessageType::Ack, [0; 4], inform.ciaddr)
+        );
+        assert_eq!(ack.lease_secs, None);
+        assert!(server.leases().is_empty());
+    }
+
+    #[test]
+    fn datagrams_are_answered_on_the_client_port() {
+        let mut server = server();
+        let server_mac = MacAddr([0x52, 0x54, 0x00, 0, 0, 1]);
+        let datagram = Datagram {
+            dst_mac: MacAddr([0xff; 6]),
+            src_mac: MacAddr(CLIENT),
+            src_ip: [0; 4],
+            dst_ip: [255; 4],
+            src_port: CLIENT_PORT,
+            dst_port: SERVER_PORT,
+            payload: msg(MessageType::Discover, CLIENT).encode(),
+        };
+        let reply = server.serve(&datagram, server_mac, 0).unwrap();
+        assert_eq!(
+            (reply.dst_mac, reply.src_mac),
+            (MacAddr([0xff; 6]), server_mac)
+        );
+        assert_eq!((reply.src_ip, reply.dst_ip), (SERVER, [255; 4]));
+        assert_eq!((reply.src_port, reply.dst_port), (SERVER_PORT, CLIENT_PORT));
+        let offer = DhcpMessage::decode(&reply.payload).unwrap();
+        assert_eq!(
+            (offer.kind, offer.yiaddr),
+            (MessageType::Offer, [10, 0, 2, 100])
+        );
+
+        let mut renew = msg(MessageType::Request, CLIENT);
+        renew.ciaddr = [10, 0, 2, 100];
+        let unicast = Datagram {
+            src_ip: renew.ciaddr,
+            dst_ip: SERVER,
+            payload: renew.encode(),
+            ..datagram.clone()
+        };
+        let reply = server.serve(&unicast, server_mac, 1).unwrap();
+        assert_eq!(
+            (reply.dst_mac, reply.dst_ip),
+            (MacAddr(CLIENT), [10, 0, 2, 100])
+        );
+
+        let wrong_port = Datagram {
+            dst_port: 53,
+            ..datagram.clone()
+        };
+        assert_eq!(server.serve(&wrong_port, server_mac, 2), None);
+        let garbage = Datagram {
+            payload: vec![1; 10],
+            ..datagram
+        };
+        assert_eq!(server.serve(&garbage, server_mac, 2), None);
+    }
+
+    #[test]
+    fn ipv4_text_round_trips() {
+        assert_eq!(parse_ipv4("10.0.2.15"), Some([10, 0, 2, 15]));
+        assert_eq!(format_ipv4([10, 0, 2, 15]), "10.0.2.15");
+        assert_eq!(parse_ipv4("10.0.2"), None);
+        assert_eq!(parse_ipv4("10.0.2.256"), None);
+    }
+}
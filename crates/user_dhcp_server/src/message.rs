@@ -0,0 +1,317 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// UDP port the server listens on.
+pub const SERVER_PORT: u16 = 67;
+/// UDP port clients listen on.
+pub const CLIENT_PORT: u16 = 68;
+/// `op` of a message sent by a client.
+pub const OP_REQUEST: u8 = 1;
+/// `op` of a message sent by a server.
+pub const OP_REPLY: u8 = 2;
+/// `flags` bit asking the server to broadcast its reply.
+pub const FLAG_BROADCAST: u16 = 0x8000;
+/// Bytes before the options: the BOOTP header and the magic cookie.
+pub const FIXED_LEN: usize = 240;
+/// Most DNS servers one option 6 can carry (255 bytes of 4-byte addresses).
+pub const MAX_DNS_SERVERS: usize = 63;
+
+const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+const HTYPE_ETHERNET: u8 = 1;
+const OPT_PAD: u8 = 0;
+const OPT_SUBNET_MASK: u8 = 1;
+const OPT_ROUTER: u8 = 3;
+const OPT_DNS: u8 = 6;
+const OPT_HOSTNAME: u8 = 12;
+const OPT_REQUESTED_IP: u8 = 50;
+const OPT_LEASE_TIME: u8 = 51;
+const OPT_MESSAGE_TYPE: u8 = 53;
+const OPT_SERVER_ID: u8 = 54;
+const OPT_END: u8 = 255;
+
+/// DHCP message types (option 53).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    Discover,
+    Offer,
+    Request,
+    Decline,
+    Ack,
+    Nak,
+    Release,
+    Inform,
+}
+
+impl MessageType {
+    fn code(self) -> u8 {
+        match self {
+            Self::Discover => 1,
+            Self::Offer => 2,
+            Self::Request => 3,
+            Self::Decline => 4,
+            Self::Ack => 5,
+            Self::Nak => 6,
+            Self::Release => 7,
+            Self::Inform => 8,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        Some(match code {
+            1 => Self::Discover,
+            2 => Self::Offer,
+            3 => Self::Request,
+            4 => Self::Decline,
+            5 => Self::Ack,
+            6 => Self::Nak,
+            7 => Self::Release,
+            8 => Self::Inform,
+            _ => return None,
+        })
+    }
+}
+
+/// Errors returned while decoding a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageError {
+    /// Fewer bytes than the fixed header, or an option runs past the end.
+    TooShort,
+    /// Hardware type is not Ethernet with 6-byte addresses.
+    NotEthernet,
+    BadCookie,
+    /// Option 53 is missing or carries an unknown type.
+    BadMessageType,
+}
+
+/// One DHCP message; options other than the ones below are dropped on decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DhcpMessage {
+    pub op: u8,
+    pub kind: MessageType,
+    pub xid: u32,
+    pub flags: u16,
+    pub ciaddr: [u8; 4],
+    pub yiaddr: [u8; 4],
+    pub siaddr: [u8; 4],
+    pub giaddr: [u8; 4],
+    pub chaddr: [u8; 6],
+    pub requested_ip: Option<[u8; 4]>,
+    pub server_id: Option<[u8; 4]>,
+    pub lease_secs: Option<u32>,
+    pub subnet_mask: Option<[u8; 4]>,
+    pub router: Option<[u8; 4]>,
+    pub dns: Vec<[u8; 4]>,
+    /// Client host name (option 12), at most 255 bytes.
+    pub hostname: Option<String>,
+}
+
+impl DhcpMessage {
+    /// Creates a client message of type `kind` with every address unset.
+    pub fn request(kind: MessageType, xid: u32, chaddr: [u8; 6]) -> Self {
+        Self {
+            op: OP_REQUEST,
+            kind,
+            xid,
+            flags: 0,
+            ciaddr: [0; 4],
+            yiaddr: [0; 4],
+            siaddr: [0; 4],
+            giaddr: [0; 4],
+            chaddr,
+            requested_ip: None,
+            server_id: None,
+            lease_secs: None,
+            subnet_mask: None,
+            router: None,
+            dns: Vec::new(),
+            hostname: None,
+        }
+    }
+
+    /// Encodes the message, ending the options with an end marker.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(FIXED_LEN + 64);
+        bytes.extend_from_slice(&[self.op, HTYPE_ETHERNET, 6, 0]);
+        bytes.extend_from_slice(&self.xid.to_be_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(&self.flags.to_be_bytes());
+        for addr in [self.ciaddr, self.yiaddr, self.siaddr, self.giaddr] {
+            bytes.extend_from_slice(&addr);
+        }
+        bytes.extend_from_slice(&self.chaddr);
+        bytes.resize(FIXED_LEN - MAGIC_COOKIE.len(), 0);
+        bytes.extend_from_slice(&MAGIC_COOKIE);
+        bytes.extend_from_slice(&[OPT_MESSAGE_TYPE, 1, self.kind.code()]);
+        let addrs = [
+            (OPT_SERVER_ID, self.server_id),
+            (OPT_REQUESTED_IP, self.requested_ip),
+            (OPT_SUBNET_MASK, self.subnet_mask),
+            (OPT_ROUTER, self.router),
+        ];
+        for (code, addr) in addrs {
+            if let Some(addr) = addr {
+                bytes.extend_from_slice(&[code, 4]);
+                bytes.extend_from_slice(&addr);
+            }
+        }
+        if let Some(secs) = self.lease_secs {
+            bytes.extend_from_slice(&[OPT_LEASE_TIME, 4]);
+            bytes.extend_from_slice(&secs.to_be_bytes());
+        }
+        if !self.dns.is_empty() {
+            let dns = &self.dns[..self.dns.len().min(MAX_DNS_SERVERS)];
+            bytes.extend_from_slice(&[OPT_DNS, (dns.len() * 4) as u8]);
+            for addr in dns {
+                bytes.extend_from_slice(addr);
+            }
+        }
+        if let Some(name) = self.hostname.as_deref().filter(|name| !name.is_empty()) {
+            let mut len = name.len().min(255);
+            while !name.is_char_boundary(len) {
+                len -= 1;
+            }
+            bytes.extend_from_slice(&[OPT_HOSTNAME, len as u8]);
+            bytes.extend_from_slice(&name.as_bytes()[..len]);
+        }
+        bytes.push(OPT_END);
+        bytes
+    }
+
+    /// Decodes a message from a UDP payload.
+    pub fn decode(bytes: &[u8]) -> Result<Self, MessageError> {
+        if bytes.len() < FIXED_LEN {
+            return Err(MessageError::TooShort);
+        }
+        if bytes[1] != HTYPE_ETHERNET || bytes[2] != 6 {
+            return Err(MessageError::NotEthernet);
+        }
+        if bytes[236..FIXED_LEN] != MAGIC_COOKIE {
+            return Err(MessageError::BadCookie);
+        }
+        let mut message = Self::request(MessageType::Discover, read_u32(&bytes[4..]), [0; 6]);
+        message.op = bytes[0];
+        message.flags = u16::from_be_bytes([bytes[10], bytes[11]]);
+        message.ciaddr = read_addr(&bytes[12..]);
+        message.yiaddr = read_addr(&bytes[16..]);
+        message.siaddr = read_addr(&bytes[20..]);
+        message.giaddr = read_addr(&bytes[24..]);
+        message.chaddr.copy_from_slice(&bytes[28..34]);
+
+        let mut kind = None;
+        let mut rest = &bytes[FIXED_LEN..];
+        while let Some((&code, tail)) = rest.split_first() {
+            match code {
+                OPT_PAD => {
+                    rest = tail;
+                    continue;
+                }
+                OPT_END => break,
+                _ => {}
+            }
+            let (&len, tail) = tail.split_first().ok_or(MessageError::TooShort)?;
+            let value = tail.get(..usize::from(len)).ok_or(MessageError::TooShort)?;
+            rest = &tail[value.len()..];
+            let addr = (value.len() == 4).then(|| read_addr(value));
+            match code {
+                OPT_MESSAGE_TYPE if value.len() == 1 => kind = MessageType::from_code(value[0]),
+                OPT_SERVER_ID => message.server_id = addr,
+                OPT_REQUESTED_IP => message.requested_ip = addr,
+                OPT_SUBNET_MASK => message.subnet_mask = addr,
+                OPT_ROUTER if value.len() >= 4 => message.router = Some(read_addr(value)),
+                OPT_LEASE_TIME if value.len() == 4 => message.lease_secs = Some(read_u32(value)),
+                OPT_DNS => message.dns = value.chunks_exact(4).map(read_addr).collect(),
+                OPT_HOSTNAME if !value.is_empty() => {
+                    message.hostname = core::str::from_utf8(value).ok().map(ToString::to_string);
+                }
+                _ => {}
+            }
+        }
+        message.kind = kind.ok_or(MessageError::BadMessageType)?;
+        Ok(message)
+    }
+}
+
+fn read_addr(bytes: &[u8]) -> [u8; 4] {
+    [bytes[0], bytes[1], bytes[2], bytes[3]]
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(read_addr(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAC: [u8; 6] = [0x52, 0x54, 0x00, 0x12, 0x34, 0x56];
+
+    #[test]
+    fn message_round_trips() {
+        let mut offer = DhcpMessage::request(MessageType::Offer, 0xdead_beef, MAC);
+        offer.op = OP_REPLY;
+        offer.flags = FLAG_BROADCAST;
+        offer.yiaddr = [10, 0, 2, 100];
+        offer.server_id = Some([10, 0, 2, 1]);
+        offer.lease_secs = Some(3600);
+        offer.subnet_mask = Some([255, 255, 255, 0]);
+        offer.router = Some([10, 0, 2, 2]);
+        offer.dns = vec![[10, 0, 2, 3], [1, 1, 1, 1]];
+        offer.hostname = Some("lab1".to_string());
+        let bytes = offer.encode();
+        assert_eq!(bytes[236..240], MAGIC_COOKIE);
+        assert_eq!(DhcpMessage::decode(&bytes), Ok(offer));
+
+        let discover = DhcpMessage::request(MessageType::Discover, 7, MAC);
+        assert_eq!(DhcpMessage::decode(&discover.encode()), Ok(discover));
+    }
+
+    #[test]
+    fn encode_caps_the_dns_list() {
+        let mut ack = DhcpMessage::request(MessageType::Ack, 1, MAC);
+        ack.dns = vec![[10, 0, 2, 3]; MAX_DNS_SERVERS + 1];
+        let decoded = DhcpMessage::decode(&ack.encode()).unwrap();
+        assert_eq!(decoded.dns.len(), MAX_DNS_SERVERS);
+    }
+
+    #[test]
+    fn long_hostnames_are_cut_at_a_char_boundary() {
+        let mut discover = DhcpMessage::request(MessageType::Discover, 1, MAC);
+        discover.hostname = Some("é".repeat(200));
+        let decoded = DhcpMessage::decode(&discover.encode()).unwrap();
+        let name = decoded.hostname.unwrap();
+        assert_eq!(name.len(), 254);
+        assert!(discover.hostname.unwrap().starts_with(&name));
+    }
+
+    #[test]
+    fn decode_rejects_malformed_messages() {
+        let good = DhcpMessage::request(MessageType::Request, 1, MAC).encode();
+        assert_eq!(
+            DhcpMessage::decode(&good[..FIXED_LEN - 1]),
+            Err(MessageError::TooShort)
+        );
+        let mut bad = good.clone();
+        bad[1] = 6;
+        assert_eq!(DhcpMessage::decode(&bad), Err(MessageError::NotEthernet));
+        let mut bad = good.clone();
+        bad[236] = 0;
+        assert_eq!(DhcpMessage::decode(&bad), Err(MessageError::BadCookie));
+        let mut bad = good[..FIXED_LEN].to_vec();
+        bad.push(OPT_END);
+        assert_eq!(DhcpMessage::decode(&bad), Err(MessageError::BadMessageType));
+        let mut bad = good[..FIXED_LEN].to_vec();
+        bad.extend_from_slice(&[OPT_MESSAGE_TYPE, 1, 9, OPT_END]);
+        assert_eq!(DhcpMessage::decode(&bad), Err(MessageError::BadMessageType));
+        let mut bad = good[..FIXED_LEN].to_vec();
+        bad.extend_from_slice(&[OPT_MESSAGE_TYPE, 4, 1]);
+        assert_eq!(DhcpMessage::decode(&bad), Err(MessageError::TooShort));
+
+        let mut padded = good[..FIXED_LEN].to_vec();
+        padded.extend_from_slice(&[OPT_PAD, OPT_PAD, 12, 2, b'p', b'c']);
+        padded.extend_from_slice(&[OPT_MESSAGE_TYPE, 1, 3, OPT_END, 0xaa]);
+        assert_eq!(
+            DhcpMessage::decode(&padded).map(|msg| msg.kind),
+            Ok(MessageType::Request)
+        );
+    }
+}
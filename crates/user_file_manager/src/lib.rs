@@ -6,15 +6,26 @@ use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
-use user_fs_service::{FileSystem, FsError};
+pub use user_fs_service::{canonicalize, glob_match, is_glob_pattern};
+use user_fs_service::{DirEntry, FileKind, FileSystem, FsError, FsStats, WalkEntry};
 
 /// Filesystem abstraction used by the file manager.
 pub trait Fs {
     fn list_dir(&self, path: &str) -> Result<Vec<String>, FsError>;
+    fn list_entries(&self, path: &str) -> Result<Vec<DirEntry>, FsError>;
     fn read_file(&self, path: &str) -> Result<Vec<u8>, FsError>;
     fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), FsError>;
     fn mkdir(&mut self, path: &str) -> Result<(), FsError>;
+    fn mkdir_p(&mut self, path: &str) -> Result<Vec<String>, FsError>;
     fn remove(&mut self, path: &str) -> Result<(), FsError>;
+    fn remove_recursive(&mut self, path: &str) -> Result<FsStats, FsError>;
+    fn symlink(&mut self, target: &str, link: &str) -> Result<(), FsError>;
+    fn read_link(&self, path: &str) -> Result<String, FsError>;
+    fn glob(&self, pattern: &str) -> Result<Vec<String>, FsError>;
+    fn rename(&mut self, src: &str, dst: &str) -> Result<(), FsError>;
+    fn copy(&mut self, src: &str, dst: &str, recursive: bool) -> Result<(), FsError>;
+    fn walk(&self, path: &str) -> Result<Vec<WalkEntry>, FsError>;
+    fn checksum(&self, path: &str) -> Result<u32, FsError>;
 }
 
 impl Fs for FileSystem {
@@ -22,6 +33,10 @@ impl Fs for FileSystem {
         FileSystem::list_dir(self, path)
     }
 
+    fn list_entries(&self, path: &str) -> Result<Vec<DirEntry>, FsError> {
+        FileSystem::list_entries(self, path)
+    }
+
     fn read_file(&self, path: &str) -> Result<Vec<u8>, FsError> {
         FileSystem::read_file(self, path)
     }
@@ -34,9 +49,45 @@ impl Fs for FileSystem {
         FileSystem::mkdir(self, path)
     }
 
+    fn mkdir_p(&mut self, path: &str) -> Result<Vec<String>, FsError> {
+        FileSystem::mkdir_p(self, path)
+    }
+
     fn remove(&mut self, path: &str) -> Result<(), FsError> {
         FileSystem::remove(self, path)
     }
+
+    fn remove_recursive(&mut self, path: &str) -> Result<FsStats, FsError> {
+        FileSystem::remove_recursive(self, path)
+    }
+
+    fn symlink(&mut self, target: &str, link: &str) -> Result<(), FsError> {
+        FileSystem::symlink(self, target, link)
+    }
+
+    fn read_link(&self, path: &str) -> Result<String, FsError> {
+        FileSystem::read_link(self, path)
+    }
+
+    fn glob(&self, pattern: &str) -> Result<Vec<String>, FsError> {
+        FileSystem::glob(self, pattern)
+    }
+
+    fn rename(&mut self, src: &str, dst: &str) -> Result<(), FsError> {
+        FileSystem::rename(self, src, dst)
+    }
+
+    fn copy(&mut self, src: &str, dst: &str, recursive: bool) -> Result<(), FsError> {
+        FileSystem::copy(self, src, dst, recursive)
+    }
+
+    fn walk(&self, path: &str) -> Result<Vec<WalkEntry>, FsError> {
+        Ok(FileSystem::walk(self, path)?.collect())
+    }
+
+    fn checksum(&self, path: &str) -> Result<u32, FsError> {
+        FileSystem::checksum(self, path)
+    }
 }
 
 /// Minimal file manager state (current working directory).
@@ -71,15 +122,33 @@ impl FileManager {
         Ok(())
     }
 
-    /// Lists directory entries.
+    /// Lists directory entries, marking symlinks as `name -> target`.
     pub fn ls(&self, fs: &impl Fs) -> Result<Vec<String>, FsError> {
-        fs.list_dir(&self.cwd)
+        list_marked(fs, &self.cwd)
+    }
+
+    /// Expands a wildcard path into its matches (`NotFound` when nothing matches).
+    ///
+    /// Paths without `*` or `?` resolve to themselves.
+    pub fn glob(&self, fs: &impl Fs, pattern: &str) -> Result<Vec<String>, FsError> {
+        let resolved = resolve_path(&self.cwd, pattern)?;
+        if !is_glob_pattern(&resolved) {
+            return Ok(alloc::vec![resolved]);
+        }
+        let matches = fs.glob(&resolved)?;
+        if matches.is_empty() {
+            return Err(FsError::NotFound);
+        }
+        Ok(matches)
     }
 
-    /// Lists directory entries for an explicit path.
+    /// Lists directory entries for an explicit path, or the paths matching a wildcard.
     pub fn ls_path(&self, fs: &impl Fs, path: &str) -> Result<Vec<String>, FsError> {
+        if is_glob_pattern(path) {
+            return self.glob(fs, path);
+        }
         let resolved = resolve_path(&self.cwd, path)?;
-        fs.list_dir(&resolved)
+        list_marked(fs, &resolved)
     }
 
     /// Reads a file as UTF-8 text.
@@ -102,50 +171,123 @@ impl FileManager {
         fs.mkdir(&resolved)
     }
 
-    /// Removes a file or directory.
-    pub fn rm(&self, fs: &mut impl Fs, path: &str) -> Result<(), FsError> {
+    /// Creates a directory with any missing parents, returning the paths created.
+    pub fn mkdir_p(&self, fs: &mut impl Fs, path: &str) -> Result<Vec<String>, FsError> {
         let resolved = resolve_path(&self.cwd, path)?;
-        fs.remove(&resolved)
+        fs.mkdir_p(&resolved)
     }
-}
 
-fn resolve_path(cwd: &str, path: &str) -> Result<String, FsError> {
-    let trimmed = path.trim();
-    if trimmed.is_empty() {
-        return Err(FsError::InvalidPath);
-    }
-    if trimmed.contains("//") {
-        return Err(FsError::InvalidPath);
+    /// Removes a file or empty directory, or every match of a wildcard.
+    pub fn rm(&self, fs: &mut impl Fs, path: &str) -> Result<(), FsError> {
+        for resolved in self.glob(fs, path)? {
+            fs.remove(&resolved)?;
+        }
+        Ok(())
     }
 
-    let mut segments: Vec<&str> = Vec::new();
-    if !trimmed.starts_with('/') && cwd != "/" {
-        for segment in cwd.split('/') {
-            if segment.is_empty() {
-                continue;
+    /// Copies a file, or a directory tree when `recursive` is set.
+    pub fn cp(
+        &self,
+        fs: &mut impl Fs,
+        src: &str,
+        dst: &str,
+        recursive: bool,
+    ) -> Result<(), FsError> {
+        let src = resolve_path(&self.cwd, src)?;
+        let dst = resolve_path(&self.cwd, dst)?;
+        fs.copy(&src, &dst, recursive)
+    }
+
+    /// Moves or renames a file or directory tree.
+    pub fn mv(&self, fs: &mut impl Fs, src: &str, dst: &str) -> Result<(), FsError> {
+        let src = resolve_path(&self.cwd, src)?;
+        let dst = resolve_path(&self.cwd, dst)?;
+        fs.rename(&src, &dst)
+    }
+
+    /// Removes a path (or every match of a wildcard) and everything below it.
+    pub fn rm_r(&self, fs: &mut impl Fs, path: &str) -> Result<FsStats, FsError> {
+        let mut total = FsStats {
+            files: 0,
+            dirs: 0,
+            bytes: 0,
+        };
+        for resolved in self.glob(fs, path)? {
+            let removed = fs.remove_recursive(&resolved)?;
+            total.files += removed.files;
+            total.dirs += removed.dirs;
+            total.bytes += removed.bytes;
+        }
+        Ok(total)
+    }
+
+    /// Creates a symbolic link at `link` pointing to `target`.
+    pub fn ln_s(&self, fs: &mut impl Fs, target: &str, link: &str) -> Result<(), FsError> {
+        let resolved = resolve_path(&self.cwd, link)?;
+        fs.symlink(target, &resolved)
+    }
+
+    /// Returns the CRC-32 of a file (or of every wildcard match) with its path.
+    pub fn checksum(&self, fs: &impl Fs, path: &str) -> Result<Vec<(String, u32)>, FsError> {
+        self.glob(fs, path)?
+            .into_iter()
+            .map(|resolved| {
+                let sum = fs.checksum(&resolved)?;
+                Ok((resolved, sum))
+            })
+            .collect()
+    }
+
+    /// Recursively walks a path, returning entries whose name matches `name`.
+    pub fn find(
+        &self,
+        fs: &impl Fs,
+        path: &str,
+        name: Option<&str>,
+    ) -> Result<Vec<String>, FsError> {
+        let mut matches = Vec::new();
+        for resolved in self.glob(fs, path)? {
+            for entry in fs.walk(&resolved)? {
+                let base = entry.path.rsplit('/').next().unwrap_or_default();
+                if name.is_none_or(|pattern| glob_match(pattern, base)) {
+                    matches.push(entry.path);
+                }
             }
-            segments.push(segment);
         }
+        Ok(matches)
     }
+}
 
-    let path_body = if trimmed == "/" { "" } else { trimmed };
-    for segment in path_body.split('/') {
-        if segment.is_empty() {
-            continue;
-        }
-        match segment {
-            "." => {}
-            ".." => {
-                segments.pop();
-            }
-            _ => segments.push(segment),
+fn join_child(dir: &str, entry: &str) -> String {
+    if dir == "/" {
+        format!("/{}", entry)
+    } else {
+        format!("{}/{}", dir, entry)
+    }
+}
+
+fn list_marked(fs: &impl Fs, path: &str) -> Result<Vec<String>, FsError> {
+    let mut marked = Vec::new();
+    for entry in fs.list_entries(path)? {
+        if entry.kind == FileKind::Symlink {
+            let target = fs.read_link(&join_child(path, &entry.name))?;
+            marked.push(format!("{} -> {}", entry.name, target));
+        } else {
+            marked.push(entry.name);
         }
     }
+    Ok(marked)
+}
 
-    if segments.is_empty() {
-        Ok("/".to_string())
+fn resolve_path(cwd: &str, path: &str) -> Result<String, FsError> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(FsError::InvalidPath);
+    }
+    if trimmed.starts_with('/') || cwd == "/" {
+        canonicalize(trimmed)
     } else {
-        Ok(format!("/{}", segments.join("/")))
+        canonicalize(&format!("{cwd}/{trimmed}"))
     }
 }
 
@@ -290,6 +432,126 @@ mod tests {
         assert!(manager.ls(&fs).unwrap().is_empty());
     }
 
+    #[test]
+    fn mv_moves_relative_paths() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        fs.mkdir("/home/docs").unwrap();
+        fs.write_file("/home/docs/a", b"a").unwrap();
+        let mut manager = FileManager::new();
+        manager.cd(&fs, "/home").unwrap();
+        manager.mkdir(&mut fs, "archive").unwrap();
+        manager.mv(&mut fs, "docs", "archive").unwrap();
+        assert_eq!(manager.cat(&fs, "archive/docs/a").unwrap(), "a");
+        manager.mv(&mut fs, "archive/docs", "old").unwrap();
+        assert_eq!(manager.ls(&fs).unwrap(), vec!["archive", "old"]);
+        assert_eq!(manager.mv(&mut fs, "", "x"), Err(FsError::InvalidPath));
+        assert_eq!(manager.mv(&mut fs, "old", ""), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn mkdir_p_resolves_relative_paths() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        let mut manager = FileManager::new();
+        manager.cd(&fs, "/home").unwrap();
+        assert_eq!(
+            manager.mkdir_p(&mut fs, "a/b").unwrap(),
+            vec!["/home/a", "/home/a/b"]
+        );
+        assert_eq!(manager.mkdir_p(&mut fs, ""), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn cp_copies_relative_paths() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        fs.mkdir("/home/docs").unwrap();
+        fs.write_file("/home/docs/a", b"a").unwrap();
+        let mut manager = FileManager::new();
+        manager.cd(&fs, "/home").unwrap();
+        assert_eq!(
+            manager.cp(&mut fs, "docs", "copy", false),
+            Err(FsError::IsDir)
+        );
+        manager.cp(&mut fs, "docs", "copy", true).unwrap();
+        assert_eq!(manager.cat(&fs, "copy/a").unwrap(), "a");
+        assert_eq!(manager.cat(&fs, "docs/a").unwrap(), "a");
+        assert_eq!(
+            manager.cp(&mut fs, "", "x", false),
+            Err(FsError::InvalidPath)
+        );
+        assert_eq!(
+            manager.cp(&mut fs, "docs", "", true),
+            Err(FsError::InvalidPath)
+        );
+    }
+
+    #[test]
+    fn rm_r_removes_relative_trees() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        fs.mkdir("/home/docs").unwrap();
+        fs.write_file("/home/docs/a", b"abc").unwrap();
+        let mut manager = FileManager::new();
+        manager.cd(&fs, "/home").unwrap();
+        let removed = manager.rm_r(&mut fs, "docs").unwrap();
+        assert_eq!((removed.files, removed.dirs, removed.bytes), (1, 1, 3));
+        assert!(manager.ls(&fs).unwrap().is_empty());
+        assert_eq!(manager.rm_r(&mut fs, ""), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn checksum_expands_wildcards() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/a.conf", b"123456789").unwrap();
+        fs.write_file("/etc/b.conf", b"").unwrap();
+        let mut manager = FileManager::new();
+        manager.cd(&fs, "/etc").unwrap();
+        assert_eq!(
+            manager.checksum(&fs, "*.conf").unwrap(),
+            vec![
+                ("/etc/a.conf".to_string(), 0xcbf4_3926),
+                ("/etc/b.conf".to_string(), 0),
+            ]
+        );
+        assert_eq!(manager.checksum(&fs, "/etc"), Err(FsError::IsDir));
+        assert_eq!(manager.checksum(&fs, "*.txt"), Err(FsError::NotFound));
+    }
+
+    #[test]
+    fn wildcards_expand_for_ls_rm_and_find() {
+        let mut fs = FileSystem::new();
+        fs.mkdir_p("/home/alice/docs").unwrap();
+        fs.mkdir_p("/home/bob/docs").unwrap();
+        fs.write_file("/home/alice/docs/a.txt", b"a").unwrap();
+        fs.write_file("/home/bob/docs/b.txt", b"bb").unwrap();
+        fs.write_file("/home/bob/docs/c.md", b"c").unwrap();
+        let mut manager = FileManager::new();
+        manager.cd(&fs, "/home").unwrap();
+        assert_eq!(
+            manager.glob(&fs, "*/docs").unwrap(),
+            vec!["/home/alice/docs", "/home/bob/docs"]
+        );
+        assert_eq!(manager.glob(&fs, "alice").unwrap(), vec!["/home/alice"]);
+        assert_eq!(manager.glob(&fs, "*.txt"), Err(FsError::NotFound));
+        assert_eq!(
+            manager.ls_path(&fs, "bob/docs/*").unwrap(),
+            vec!["/home/bob/docs/b.txt", "/home/bob/docs/c.md"]
+        );
+        assert_eq!(
+            manager.find(&fs, "*/docs", Some("*.txt")).unwrap(),
+            vec!["/home/alice/docs/a.txt", "/home/bob/docs/b.txt"]
+        );
+        manager.rm(&mut fs, "bob/docs/*.md").unwrap();
+        assert_eq!(manager.ls_path(&fs, "bob/docs").unwrap(), vec!["b.txt"]);
+        assert_eq!(manager.rm(&mut fs, "*/docs"), Err(FsError::NotEmpty));
+        let removed = manager.rm_r(&mut fs, "*/docs").unwrap();
+        assert_eq!((removed.files, removed.dirs, removed.bytes), (2, 2, 3));
+        assert_eq!(manager.ls_path(&fs, "alice").unwrap(), Vec::<String>::new());
+    }
+
     #[test]
     fn ls_path_resolves_relative_paths() {
         let mut fs = FileSystem::new();
@@ -308,6 +570,84 @@ mod tests {
         assert_eq!(manager.ls_path(&fs, ""), Err(FsError::InvalidPath));
     }
 
+    #[test]
+    fn glob_match_supports_wildcards() {
+        assert!(glob_match("*.txt", "notes.txt"));
+        assert!(glob_match("note?.txt", "notes.txt"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("*.txt", "notes.md"));
+        assert!(!glob_match("?", ""));
+        assert!(!glob_match("a*b", "acc"));
+    }
+
+    #[test]
+    fn find_walks_tree_with_optional_pattern() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        fs.mkdir("/home/docs").unwrap();
+        fs.write_file("/home/docs/a.txt", b"a").unwrap();
+        fs.write_file("/home/b.md", b"b").unwrap();
+        let mut manager = FileManager::new();
+        assert_eq!(
+            manager.find(&fs, "/home", None).unwrap(),
+            vec![
+                "/home".to_string(),
+                "/home/b.md".to_string(),
+                "/home/docs".to_string(),
+                "/home/docs/a.txt".to_string(),
+            ]
+        );
+        assert_eq!(
+            manager.find(&fs, "/", Some("*.txt")).unwrap(),
+            vec!["/home/docs/a.txt".to_string()]
+        );
+        manager.cd(&fs, "/home").unwrap();
+        assert_eq!(
+            manager.find(&fs, "b.md", None).unwrap(),
+            vec!["/home/b.md".to_string()]
+        );
+    }
+
+    #[test]
+    fn find_rejects_missing_or_empty_path() {
+        let fs = FileSystem::new();
+        let manager = FileManager::new();
+        assert_eq!(manager.find(&fs, "/missing", None), Err(FsError::NotFound));
+        assert_eq!(manager.find(&fs, "", None), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn ln_s_links_are_marked_and_followed() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/hostname", b"ruzzle").unwrap();
+        let mut manager = FileManager::new();
+        manager.ln_s(&mut fs, "/etc/hostname", "/name").unwrap();
+        manager.cd(&fs, "/etc").unwrap();
+        manager.ln_s(&mut fs, "hostname", "alias").unwrap();
+        manager.ln_s(&mut fs, "/etc", "loop").unwrap();
+        assert_eq!(
+            manager.find(&fs, "/etc", Some("loop")).unwrap(),
+            vec!["/etc/loop".to_string()]
+        );
+        manager.rm(&mut fs, "loop").unwrap();
+        assert_eq!(manager.cat(&fs, "/name").unwrap(), "ruzzle");
+        assert_eq!(
+            manager.ls(&fs).unwrap(),
+            vec!["alias -> hostname".to_string(), "hostname".to_string()]
+        );
+        assert_eq!(
+            manager.ls_path(&fs, "/").unwrap(),
+            vec!["etc".to_string(), "name -> /etc/hostname".to_string()]
+        );
+        assert_eq!(
+            manager.ln_s(&mut fs, "/etc/hostname", ""),
+            Err(FsError::InvalidPath)
+        );
+    }
+
     #[test]
     fn file_system_dependency_error_paths() {
         let mut fs = FileSystem::new();
@@ -6,6 +6,9 @@ use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
+/// CRC-32 (IEEE 802.3, as used by zip and PNG), shared with IPC framing.
+pub use ruzzle_protocol::frame::crc32;
+
 /// Errors returned by the in-memory filesystem.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FsError {
@@ -16,8 +19,40 @@ pub enum FsError {
     InvalidPath,
     NotEmpty,
     InvalidUtf8,
+    NotSymlink,
+    SymlinkLoop,
+    PermissionDenied,
+    InvalidMode,
+    NoSpace,
+    CorruptSnapshot,
+    BadHandle,
+    InvalidSeek,
+    /// The backing block device failed.
+    Io,
 }
 
+/// Maximum number of symlinks followed while resolving one path.
+pub const SYMLINK_HOP_LIMIT: usize = 8;
+
+/// Events queued per watcher before the oldest are dropped.
+pub const WATCH_QUEUE_LIMIT: usize = 64;
+
+/// Largest size a write or truncate through a handle may grow a file to.
+pub const MAX_FILE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Magic bytes at the start of an encoded snapshot.
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"RZFS";
+
+/// Snapshot format written after `SNAPSHOT_MAGIC`.
+///
+/// Version 1 had no version byte: its root directory's tag (`1`) follows the
+/// magic directly, and it stored no groups or timestamps. Version 2 added
+/// groups and version 3 created/modified times.
+pub const SNAPSHOT_VERSION: u8 = 3;
+
+/// Maximum directory nesting accepted when decoding a snapshot.
+const SNAPSHOT_MAX_DEPTH: usize = 64;
+
 /// Filesystem usage statistics.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FsStats {
@@ -26,16 +61,326 @@ pub struct FsStats {
     pub bytes: usize,
 }
 
+/// Read permission bit (per owner/group/other triple).
+pub const PERM_READ: u16 = 0o4;
+/// Write permission bit (per owner/group/other triple).
+pub const PERM_WRITE: u16 = 0o2;
+/// Execute/search permission bit (per owner/group/other triple).
+pub const PERM_EXEC: u16 = 0o1;
+/// Default mode for new files.
+pub const DEFAULT_FILE_MODE: u16 = 0o644;
+/// Default mode for new directories and links.
+pub const DEFAULT_DIR_MODE: u16 = 0o755;
+/// Owner assigned when no caller identity is set.
+pub const ROOT_OWNER: &str = "root";
+/// Group assigned when no caller identity is set.
+pub const ROOT_GROUP: &str = "root";
+
+/// Caller identity used to enforce ownership and mode bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub user: String,
+    pub admin: bool,
+    /// Group memberships; the first is the primary group given to new entries.
+    pub groups: Vec<String>,
+}
+
+impl Identity {
+    /// Creates an identity for a user whose primary group is their own name.
+    pub fn new(user: &str, admin: bool) -> Self {
+        Self {
+            user: user.to_string(),
+            admin,
+            groups: alloc::vec![user.to_string()],
+        }
+    }
+
+    /// Adds supplementary group memberships.
+    pub fn with_groups(mut self, groups: &[&str]) -> Self {
+        self.groups
+            .extend(groups.iter().map(|group| group.to_string()));
+        self
+    }
+
+    /// Returns true if the identity belongs to `group`.
+    pub fn in_group(&self, group: &str) -> bool {
+        self.groups.iter().any(|member| member == group)
+    }
+}
+
+/// Kind of filesystem entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+impl FileKind {
+    /// Returns the name shown by `stat`.
+    pub fn name(self) -> &'static str {
+        match self {
+            FileKind::File => "file",
+            FileKind::Dir => "directory",
+            FileKind::Symlink => "symlink",
+        }
+    }
+}
+
+/// Ownership and mode information for an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub kind: FileKind,
+    pub owner: String,
+    pub group: String,
+    pub mode: u16,
+    /// Tick at which the entry was created.
+    pub created: u64,
+    /// Tick at which the entry's contents (or a directory's entries) last changed.
+    pub modified: u64,
+}
+
+/// Name and kind of one entry returned by `list_entries`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    pub name: String,
+    pub kind: FileKind,
+}
+
+/// One entry yielded by `FileSystem::walk`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkEntry {
+    pub path: String,
+    pub kind: FileKind,
+    /// Levels below the walk's starting path (which is depth 0).
+    pub depth: usize,
+    /// File length in bytes, or the target length for a link; 0 for directories.
+    pub size: usize,
+}
+
+/// Access requested when opening a file handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenMode {
+    Read,
+    /// Creates the file if it is missing.
+    Write,
+    /// Creates the file if it is missing.
+    ReadWrite,
+}
+
+impl OpenMode {
+    fn readable(self) -> bool {
+        matches!(self, OpenMode::Read | OpenMode::ReadWrite)
+    }
+
+    fn writable(self) -> bool {
+        matches!(self, OpenMode::Write | OpenMode::ReadWrite)
+    }
+}
+
+/// Position passed to `seek`, mirroring `std::io::SeekFrom`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekFrom {
+    Start(usize),
+    End(i64),
+    Current(i64),
+}
+
+/// Handle to an open file returned by `FileSystem::open`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FileHandle(u32);
+
+/// How a tmpfs mount frees space when a write would exceed its cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Fail the write with `NoSpace`.
+    Reject,
+    /// Remove the least recently modified files first.
+    OldestFirst,
+    /// Remove the largest files first.
+    LargestFirst,
+}
+
+impl EvictionPolicy {
+    /// Returns the name shown in mount listings.
+    pub fn name(self) -> &'static str {
+        match self {
+            EvictionPolicy::Reject => "reject",
+            EvictionPolicy::OldestFirst => "oldest",
+            EvictionPolicy::LargestFirst => "largest",
+        }
+    }
+}
+
+/// Size-bounded subtree reported by `FileSystem::tmpfs_mounts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmpfsMount {
+    pub path: String,
+    pub cap: usize,
+    pub used: usize,
+    pub policy: EvictionPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tmpfs {
+    parts: Vec<String>,
+    cap: usize,
+    policy: EvictionPolicy,
+}
+
+/// Kind of change reported to watchers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsEventKind {
+    /// A file was created or its contents changed.
+    Written,
+    /// An entry (with everything below it) was removed.
+    Removed,
+    /// An entry was renamed away from this path.
+    MovedFrom,
+    /// An entry was renamed onto this path.
+    MovedTo,
+}
+
+/// Change notification queued for a watcher; `path` has links resolved.
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Node {
+pub struct FsEvent {
+    pub path: String,
+    pub kind: FsEventKind,
+}
+
+/// Subscription returned by `FileSystem::watch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WatchId(u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Watcher {
+    prefix: String,
+    events: Vec<FsEvent>,
+}
+
+impl Watcher {
+    fn covers(&self, path: &str) -> bool {
+        self.prefix == "/"
+            || path
+                .strip_prefix(self.prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OpenFile {
+    path: Vec<String>,
+    mode: OpenMode,
+    offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    kind: NodeKind,
+    owner: String,
+    group: String,
+    mode: u16,
+    created: u64,
+    modified: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NodeKind {
     File(Vec<u8>),
     Dir(BTreeMap<String, Node>),
+    Symlink(String),
+}
+
+impl Node {
+    fn file_kind(&self) -> FileKind {
+        match self.kind {
+            NodeKind::File(_) => FileKind::File,
+            NodeKind::Dir(_) => FileKind::Dir,
+            NodeKind::Symlink(_) => FileKind::Symlink,
+        }
+    }
+
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            kind: self.file_kind(),
+            owner: self.owner.clone(),
+            group: self.group.clone(),
+            mode: self.mode,
+            created: self.created,
+            modified: self.modified,
+        }
+    }
+}
+
+/// Point-in-time copy of a directory tree, including ownership and modes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    root: Node,
+}
+
+impl Snapshot {
+    /// Returns usage stats for the captured tree.
+    pub fn stats(&self) -> FsStats {
+        let mut stats = FsStats {
+            files: 0,
+            dirs: 0,
+            bytes: 0,
+        };
+        if let NodeKind::Dir(children) = &self.root.kind {
+            count_dir(children, &mut stats);
+        }
+        stats
+    }
+
+    /// Encodes the snapshot into a self-contained byte stream.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = SNAPSHOT_MAGIC.to_vec();
+        bytes.push(SNAPSHOT_VERSION);
+        encode_node(&self.root, &mut bytes);
+        bytes
+    }
+
+    /// Decodes a snapshot produced by `to_bytes` in this or an older format.
+    ///
+    /// Fields an older format lacks get defaults: the owner's name as group
+    /// and zero timestamps.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FsError> {
+        let mut reader = SnapshotReader {
+            bytes,
+            pos: 0,
+            version: 1,
+        };
+        if reader.take(4)? != SNAPSHOT_MAGIC {
+            return Err(FsError::CorruptSnapshot);
+        }
+        match bytes.get(reader.pos) {
+            Some(1) => {}
+            Some(&version) if (2..=SNAPSHOT_VERSION).contains(&version) => {
+                reader.version = version;
+                reader.pos += 1;
+            }
+            _ => return Err(FsError::CorruptSnapshot),
+        }
+        let root = reader.node(0)?;
+        if reader.pos != bytes.len() || !matches!(root.kind, NodeKind::Dir(_)) {
+            return Err(FsError::CorruptSnapshot);
+        }
+        Ok(Self { root })
+    }
 }
 
 /// In-memory filesystem used by the fs-service module.
 #[derive(Debug, Default, Clone)]
 pub struct FileSystem {
     root: BTreeMap<String, Node>,
+    identity: Option<Identity>,
+    quota: Option<usize>,
+    now: u64,
+    open_files: BTreeMap<FileHandle, OpenFile>,
+    next_handle: u32,
+    watchers: BTreeMap<WatchId, Watcher>,
+    next_watch: u32,
+    tmpfs: Vec<Tmpfs>,
 }
 
 impl FileSystem {
@@ -43,70 +388,407 @@ impl FileSystem {
     pub fn new() -> Self {
         Self {
             root: BTreeMap::new(),
+            identity: None,
+            quota: None,
+            now: 0,
+            open_files: BTreeMap::new(),
+            next_handle: 0,
+            watchers: BTreeMap::new(),
+            next_watch: 0,
+            tmpfs: Vec::new(),
         }
     }
 
+    /// Sets the tick stamped on entries created or modified from now on.
+    pub fn set_time(&mut self, now: u64) {
+        self.now = now;
+    }
+
+    /// Returns the tick used for new timestamps.
+    pub fn time(&self) -> u64 {
+        self.now
+    }
+
+    /// Caps the total bytes stored in files (`None` removes the limit).
+    pub fn set_quota(&mut self, quota: Option<usize>) {
+        self.quota = quota;
+    }
+
+    /// Returns the configured byte quota, if any.
+    pub fn quota(&self) -> Option<usize> {
+        self.quota
+    }
+
+    /// Caps the bytes stored below an existing directory, like a tmpfs mount (admin only).
+    ///
+    /// A write that would push the subtree past `cap` first evicts other
+    /// files below it as `policy` dictates, and fails with `NoSpace` if that
+    /// cannot make room. Mounting the same directory again replaces its settings.
+    pub fn mount_tmpfs(
+        &mut self,
+        path: &str,
+        cap: usize,
+        policy: EvictionPolicy,
+    ) -> Result<(), FsError> {
+        self.require_admin()?;
+        let parts = split_path(path)?;
+        if parts.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let resolved = self.follow_links(&parts)?;
+        if !matches!(self.walk_node(&as_parts(&resolved))?.kind, NodeKind::Dir(_)) {
+            return Err(FsError::NotDir);
+        }
+        self.tmpfs.retain(|mount| mount.parts != resolved);
+        self.tmpfs.push(Tmpfs {
+            parts: resolved,
+            cap,
+            policy,
+        });
+        Ok(())
+    }
+
+    /// Lists tmpfs mounts with the bytes currently stored below each.
+    pub fn tmpfs_mounts(&self) -> Vec<TmpfsMount> {
+        self.tmpfs
+            .iter()
+            .map(|mount| {
+                let mut path = String::new();
+                for segment in &mount.parts {
+                    path.push('/');
+                    path.push_str(segment);
+                }
+                TmpfsMount {
+                    path,
+                    cap: mount.cap,
+                    used: self.subtree_bytes(&mount.parts),
+                    policy: mount.policy,
+                }
+            })
+            .collect()
+    }
+
+    /// Sets the caller identity used for permission checks (`None` disables them).
+    pub fn set_identity(&mut self, identity: Option<Identity>) {
+        self.identity = identity;
+    }
+
+    /// Returns the current caller identity, if any.
+    pub fn identity(&self) -> Option<&Identity> {
+        self.identity.as_ref()
+    }
+
     /// Creates a directory at the provided path.
     pub fn mkdir(&mut self, path: &str) -> Result<(), FsError> {
         let parts = split_path(path)?;
         if parts.is_empty() {
             return Err(FsError::InvalidPath);
         }
+        let resolved = self.follow_parent(&parts)?;
+        let parts = as_parts(&resolved);
+        self.require_parent(&parts, PERM_WRITE)?;
+        let node = self.new_node(NodeKind::Dir(BTreeMap::new()), DEFAULT_DIR_MODE);
         let (parent, name) = self.walk_parent_mut(&parts)?;
         if parent.contains_key(&name) {
             return Err(FsError::AlreadyExists);
         }
-        parent.insert(name, Node::Dir(BTreeMap::new()));
+        parent.insert(name, node);
+        self.touch_parent(&parts);
         Ok(())
     }
 
+    /// Creates a directory and any missing parents, returning the paths created.
+    ///
+    /// Existing directories (or links to them) along the way are kept; any
+    /// other existing entry fails with `NotDir`.
+    pub fn mkdir_p(&mut self, path: &str) -> Result<Vec<String>, FsError> {
+        let parts = split_path(path)?;
+        let mut created = Vec::new();
+        let mut current = String::new();
+        for (index, segment) in parts.iter().enumerate() {
+            current.push('/');
+            current.push_str(segment);
+            let resolved = self.follow_links(&parts[..=index])?;
+            if resolved.is_empty() {
+                continue;
+            }
+            match self.walk_node(&as_parts(&resolved)).map(|node| &node.kind) {
+                Ok(NodeKind::Dir(_)) => {}
+                Ok(_) => return Err(FsError::NotDir),
+                Err(FsError::NotFound) => {
+                    self.mkdir(&current)?;
+                    created.push(current.clone());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(created)
+    }
+
     /// Writes a file, creating it if missing.
     pub fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), FsError> {
         let parts = split_path(path)?;
         if parts.is_empty() {
             return Err(FsError::InvalidPath);
         }
+        let resolved = self.follow_links(&parts)?;
+        let parts = as_parts(&resolved);
+        if parts.is_empty() {
+            return Err(FsError::IsDir);
+        }
+        let existing = match self.walk_node(&parts) {
+            Ok(node) => {
+                self.require(node, PERM_WRITE)?;
+                match &node.kind {
+                    NodeKind::File(data) => data.len(),
+                    _ => 0,
+                }
+            }
+            Err(FsError::NotFound) => {
+                self.require_parent(&parts, PERM_WRITE)?;
+                0
+            }
+            Err(err) => return Err(err),
+        };
+        self.reserve(&parts, existing, data.len())?;
+        let now = self.now;
+        let node = self.new_node(NodeKind::File(data.to_vec()), DEFAULT_FILE_MODE);
         let (parent, name) = self.walk_parent_mut(&parts)?;
         match parent.get_mut(&name) {
-            Some(Node::Dir(_)) => Err(FsError::IsDir),
-            Some(Node::File(existing)) => {
+            Some(Node {
+                kind: NodeKind::File(existing),
+                modified,
+                ..
+            }) => {
                 existing.clear();
                 existing.extend_from_slice(data);
-                Ok(())
+                *modified = now;
             }
+            Some(_) => return Err(FsError::IsDir),
             None => {
-                parent.insert(name, Node::File(data.to_vec()));
-                Ok(())
+                parent.insert(name, node);
+                self.touch_parent(&parts);
             }
         }
+        self.notify(&parts, FsEventKind::Written);
+        Ok(())
     }
 
     /// Reads a file and returns its bytes.
     pub fn read_file(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        self.file_data(path).cloned()
+    }
+
+    /// Returns the CRC-32 of a file's contents (requires read permission).
+    pub fn checksum(&self, path: &str) -> Result<u32, FsError> {
+        Ok(crc32(self.file_data(path)?))
+    }
+
+    fn file_data(&self, path: &str) -> Result<&Vec<u8>, FsError> {
         let parts = split_path(path)?;
+        let resolved = self.follow_links(&parts)?;
+        let parts = as_parts(&resolved);
         if parts.is_empty() {
             return Err(FsError::IsDir);
         }
-        match self.walk_node(&parts)? {
-            Node::File(data) => Ok(data.clone()),
-            Node::Dir(_) => Err(FsError::IsDir),
+        let node = self.walk_node(&parts)?;
+        match &node.kind {
+            NodeKind::File(data) => {
+                self.require(node, PERM_READ)?;
+                Ok(data)
+            }
+            _ => Err(FsError::IsDir),
         }
     }
 
     /// Lists a directory, returning entries sorted by name.
     pub fn list_dir(&self, path: &str) -> Result<Vec<String>, FsError> {
+        Ok(self.dir_children(path)?.keys().cloned().collect())
+    }
+
+    /// Lists a directory with each entry's kind, so links are distinguishable.
+    pub fn list_entries(&self, path: &str) -> Result<Vec<DirEntry>, FsError> {
+        Ok(self
+            .dir_children(path)?
+            .iter()
+            .map(|(name, node)| DirEntry {
+                name: name.clone(),
+                kind: node.file_kind(),
+            })
+            .collect())
+    }
+
+    /// Expands a path pattern whose segments may use `*` and `?`, returning sorted matches.
+    ///
+    /// Names starting with `.` only match a segment that also starts with `.`;
+    /// missing or unreadable directories contribute no matches.
+    pub fn glob(&self, pattern: &str) -> Result<Vec<String>, FsError> {
+        let parts = split_path(pattern)?;
+        if parts.is_empty() {
+            return Ok(alloc::vec!["/".to_string()]);
+        }
+        let mut current = alloc::vec![String::new()];
+        for segment in parts {
+            let mut next = Vec::new();
+            for base in &current {
+                if !is_glob_pattern(segment) {
+                    let path = join_child(base, segment);
+                    if self.metadata(&path).is_ok() {
+                        next.push(path);
+                    }
+                    continue;
+                }
+                let dir = if base.is_empty() { "/" } else { base.as_str() };
+                let Ok(children) = self.dir_children(dir) else {
+                    continue;
+                };
+                for name in children.keys() {
+                    let hidden = name.starts_with('.') && !segment.starts_with('.');
+                    if !hidden && glob_match(segment, name) {
+                        next.push(join_child(base, name));
+                    }
+                }
+            }
+            current = next;
+        }
+        Ok(current)
+    }
+
+    /// Visits `path` and everything below it in sorted pre-order, without copying nodes.
+    ///
+    /// Links are reported but not followed, including a link at `path`
+    /// itself; directories the caller cannot read are listed but not entered.
+    pub fn walk(&self, path: &str) -> Result<Walk<'_>, FsError> {
+        let parts = split_path(path)?;
+        let mut walk = Walk {
+            fs: self,
+            start: None,
+            stack: Vec::new(),
+        };
+        if parts.is_empty() {
+            walk.start = Some(WalkEntry {
+                path: "/".to_string(),
+                kind: FileKind::Dir,
+                depth: 0,
+                size: 0,
+            });
+            walk.push_children("", 1, &self.root);
+        } else {
+            let resolved = self.follow_parent(&parts)?;
+            let node = self.walk_node(&as_parts(&resolved))?;
+            let mut path = String::new();
+            for segment in &resolved {
+                path.push('/');
+                path.push_str(segment);
+            }
+            walk.stack.push((path, 0, node));
+        }
+        Ok(walk)
+    }
+
+    /// Creates a symbolic link at `link` pointing to `target`.
+    pub fn symlink(&mut self, target: &str, link: &str) -> Result<(), FsError> {
+        if target.trim().is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let parts = split_path(link)?;
+        if parts.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let resolved = self.follow_parent(&parts)?;
+        let parts = as_parts(&resolved);
+        self.require_parent(&parts, PERM_WRITE)?;
+        let node = self.new_node(
+            NodeKind::Symlink(target.trim().to_string()),
+            DEFAULT_DIR_MODE,
+        );
+        let (parent, name) = self.walk_parent_mut(&parts)?;
+        if parent.contains_key(&name) {
+            return Err(FsError::AlreadyExists);
+        }
+        parent.insert(name, node);
+        self.touch_parent(&parts);
+        Ok(())
+    }
+
+    /// Returns the target stored in a symbolic link.
+    pub fn read_link(&self, path: &str) -> Result<String, FsError> {
+        let parts = split_path(path)?;
+        if parts.is_empty() {
+            return Err(FsError::NotSymlink);
+        }
+        let resolved = self.follow_parent(&parts)?;
+        match &self.walk_node(&as_parts(&resolved))?.kind {
+            NodeKind::Symlink(target) => Ok(target.clone()),
+            _ => Err(FsError::NotSymlink),
+        }
+    }
+
+    /// Returns ownership, mode and timestamps for a path without following a final link.
+    pub fn metadata(&self, path: &str) -> Result<Metadata, FsError> {
         let parts = split_path(path)?;
+        if parts.is_empty() {
+            return Ok(root_node().metadata());
+        }
+        let resolved = self.follow_parent(&parts)?;
+        Ok(self.walk_node(&as_parts(&resolved))?.metadata())
+    }
+
+    /// Changes the mode bits of a path (owner or admin only).
+    pub fn chmod(&mut self, path: &str, mode: u16) -> Result<(), FsError> {
+        if mode > 0o777 {
+            return Err(FsError::InvalidMode);
+        }
+        let node = self.node_mut(path)?;
+        node.mode = mode;
+        Ok(())
+    }
+
+    /// Returns `Ok` if `identity` holds `perm` on a path (following links).
+    pub fn access(&self, path: &str, identity: &Identity, perm: u16) -> Result<(), FsError> {
+        let parts = split_path(path)?;
+        let resolved = self.follow_links(&parts)?;
+        let parts = as_parts(&resolved);
+        let root = root_node();
         let node = if parts.is_empty() {
-            None
+            &root
         } else {
-            Some(self.walk_node(&parts)?)
-        };
-        let dir = match node {
-            None => &self.root,
-            Some(Node::Dir(children)) => children,
-            Some(Node::File(_)) => return Err(FsError::NotDir),
+            self.walk_node(&parts)?
         };
-        Ok(dir.keys().cloned().collect())
+        if permits(Some(identity), &node.owner, &node.group, node.mode, perm) {
+            Ok(())
+        } else {
+            Err(FsError::PermissionDenied)
+        }
+    }
+
+    /// Changes the owner of a path (admin only).
+    pub fn chown(&mut self, path: &str, owner: &str) -> Result<(), FsError> {
+        if owner.trim().is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        self.require_admin()?;
+        let node = self.node_mut(path)?;
+        node.owner = owner.trim().to_string();
+        Ok(())
+    }
+
+    /// Changes the group of a path (admin, or the owner when a member of `group`).
+    pub fn chgrp(&mut self, path: &str, group: &str) -> Result<(), FsError> {
+        let group = group.trim();
+        if group.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        if self
+            .identity
+            .as_ref()
+            .is_some_and(|identity| !identity.admin && !identity.in_group(group))
+        {
+            return Err(FsError::PermissionDenied);
+        }
+        let node = self.node_mut(path)?;
+        node.group = group.to_string();
+        Ok(())
     }
 
     /// Returns usage stats for the entire filesystem.
@@ -132,14 +814,15 @@ impl FileSystem {
             dirs: 0,
             bytes: 0,
         };
-        match node {
-            Node::File(data) => {
+        match &node.kind {
+            NodeKind::File(data) => {
                 stats.files = 1;
                 stats.bytes = data.len();
             }
-            Node::Dir(children) => {
+            NodeKind::Dir(children) => {
                 count_dir(children, &mut stats);
             }
+            NodeKind::Symlink(_) => {}
         }
         Ok(stats)
     }
@@ -149,23 +832,673 @@ impl FileSystem {
         Ok(self.stats_for(path)?.bytes)
     }
 
+    /// Captures the directory tree at `path` (requires read permission).
+    pub fn snapshot(&self, path: &str) -> Result<Snapshot, FsError> {
+        let parts = split_path(path)?;
+        let resolved = self.follow_links(&parts)?;
+        let parts = as_parts(&resolved);
+        if parts.is_empty() {
+            let root = Node {
+                kind: NodeKind::Dir(self.root.clone()),
+                ..root_node()
+            };
+            return Ok(Snapshot { root });
+        }
+        let node = self.walk_node(&parts)?;
+        if !matches!(node.kind, NodeKind::Dir(_)) {
+            return Err(FsError::NotDir);
+        }
+        self.require(node, PERM_READ)?;
+        Ok(Snapshot { root: node.clone() })
+    }
+
+    /// Encodes the whole tree as a self-contained archive (admin only).
+    ///
+    /// The archive uses the snapshot byte format: every entry is written with
+    /// length-prefixed fields, so it can be streamed out and re-imported as is.
+    pub fn export_archive(&self) -> Result<Vec<u8>, FsError> {
+        self.require_admin()?;
+        Ok(self.snapshot("/")?.to_bytes())
+    }
+
+    /// Replaces the whole tree with an archive from `export_archive` (admin only).
+    pub fn import_archive(&mut self, bytes: &[u8]) -> Result<FsStats, FsError> {
+        self.require_admin()?;
+        let snapshot = Snapshot::from_bytes(bytes)?;
+        let stats = snapshot.stats();
+        if self.quota.is_some_and(|quota| stats.bytes > quota) {
+            return Err(FsError::NoSpace);
+        }
+        if let NodeKind::Dir(children) = snapshot.root.kind {
+            self.root = children;
+        }
+        Ok(stats)
+    }
+
+    /// Replaces the directory at `path` (or creates it) with a snapshot.
+    pub fn restore(&mut self, path: &str, snapshot: &Snapshot) -> Result<(), FsError> {
+        let parts = split_path(path)?;
+        if parts.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let resolved = self.follow_parent(&parts)?;
+        let parts = as_parts(&resolved);
+        self.require_parent(&parts, PERM_WRITE)?;
+        let existing = match self.walk_node(&parts) {
+            Ok(Node {
+                kind: NodeKind::Dir(_),
+                ..
+            }) => self.stats_for(&resolved.join("/"))?.bytes,
+            Ok(_) => return Err(FsError::NotDir),
+            Err(FsError::NotFound) => 0,
+            Err(err) => return Err(err),
+        };
+        self.reserve(&parts, existing, snapshot.stats().bytes)?;
+        let (parent, name) = self.walk_parent_mut(&parts)?;
+        parent.insert(name, snapshot.root.clone());
+        self.touch_parent(&parts);
+        Ok(())
+    }
+
     /// Removes a file or an empty directory.
     pub fn remove(&mut self, path: &str) -> Result<(), FsError> {
         let parts = split_path(path)?;
         if parts.is_empty() {
             return Err(FsError::InvalidPath);
         }
+        let resolved = self.follow_parent(&parts)?;
+        let parts = as_parts(&resolved);
+        self.require_parent(&parts, PERM_WRITE)?;
         let (parent, name) = self.walk_parent_mut(&parts)?;
-        match parent.get(&name) {
+        match parent.get(&name).map(|node| &node.kind) {
             None => Err(FsError::NotFound),
-            Some(Node::Dir(children)) if !children.is_empty() => Err(FsError::NotEmpty),
+            Some(NodeKind::Dir(children)) if !children.is_empty() => Err(FsError::NotEmpty),
             _ => {
                 parent.remove(&name);
+                self.touch_parent(&parts);
+                self.notify(&parts, FsEventKind::Removed);
                 Ok(())
             }
         }
     }
 
+    /// Removes a path and everything below it, returning what was removed.
+    ///
+    /// Refuses `/`. Write permission is checked on every directory up front,
+    /// so a denied subtree leaves the tree untouched.
+    pub fn remove_recursive(&mut self, path: &str) -> Result<FsStats, FsError> {
+        let parts = split_path(path)?;
+        if parts.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let resolved = self.follow_parent(&parts)?;
+        let parts = as_parts(&resolved);
+        self.require_parent(&parts, PERM_WRITE)?;
+        let node = self.walk_node(&parts)?;
+        self.require_tree(node, PERM_WRITE)?;
+        let mut removed = FsStats {
+            files: 0,
+            dirs: 0,
+            bytes: 0,
+        };
+        match &node.kind {
+            NodeKind::File(data) => {
+                removed.files = 1;
+                removed.bytes = data.len();
+            }
+            NodeKind::Dir(children) => count_dir(children, &mut removed),
+            NodeKind::Symlink(_) => {}
+        }
+        let (parent, name) = self.walk_parent_mut(&parts)?;
+        parent.remove(&name);
+        self.touch_parent(&parts);
+        self.notify(&parts, FsEventKind::Removed);
+        Ok(removed)
+    }
+
+    /// Copies a file, link or (with `recursive`) a whole directory tree.
+    ///
+    /// Links are copied as links, and copies belong to the caller with fresh
+    /// timestamps. When `dst` is an existing directory the copy lands inside
+    /// it; copying a directory into its own subtree fails with `InvalidPath`.
+    pub fn copy(&mut self, src: &str, dst: &str, recursive: bool) -> Result<(), FsError> {
+        let src_parts = split_path(src)?;
+        let dst_parts = split_path(dst)?;
+        if src_parts.is_empty() || dst_parts.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let src_resolved = self.follow_parent(&src_parts)?;
+        let mut dst_resolved = self.follow_parent(&dst_parts)?;
+        let src_parts = as_parts(&src_resolved);
+        let source = self.walk_node(&src_parts)?;
+        if matches!(source.kind, NodeKind::Dir(_)) && !recursive {
+            return Err(FsError::IsDir);
+        }
+        if matches!(
+            self.walk_node(&as_parts(&dst_resolved))
+                .map(|node| &node.kind),
+            Ok(NodeKind::Dir(_))
+        ) {
+            dst_resolved.push(src_parts[src_parts.len() - 1].to_string());
+        }
+        let dst_parts = as_parts(&dst_resolved);
+        if dst_parts.starts_with(&src_parts) {
+            return Err(FsError::InvalidPath);
+        }
+        let copy = self.copied_node(source)?;
+        let existing = match self.walk_node(&dst_parts) {
+            Ok(Node {
+                kind: NodeKind::File(data),
+                ..
+            }) if matches!(copy.kind, NodeKind::File(_)) => {
+                self.require(self.walk_node(&dst_parts)?, PERM_WRITE)?;
+                data.len()
+            }
+            Ok(_) => return Err(FsError::AlreadyExists),
+            Err(FsError::NotFound) => {
+                self.require_parent(&dst_parts, PERM_WRITE)?;
+                0
+            }
+            Err(err) => return Err(err),
+        };
+        self.reserve(&dst_parts, existing, node_bytes(&copy))?;
+        let (parent, name) = self.walk_parent_mut(&dst_parts)?;
+        parent.insert(name, copy);
+        self.touch_parent(&dst_parts);
+        self.notify(&dst_parts, FsEventKind::Written);
+        Ok(())
+    }
+
+    /// Moves a file, link or whole directory subtree, keeping timestamps and ownership.
+    ///
+    /// When `dst` is an existing directory the entry moves inside it. An existing
+    /// file at the destination is replaced by a file; any other collision fails.
+    pub fn rename(&mut self, src: &str, dst: &str) -> Result<(), FsError> {
+        let src_parts = split_path(src)?;
+        let dst_parts = split_path(dst)?;
+        if src_parts.is_empty() || dst_parts.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let src_resolved = self.follow_parent(&src_parts)?;
+        let mut dst_resolved = self.follow_parent(&dst_parts)?;
+        let src_parts = as_parts(&src_resolved);
+        self.walk_node(&src_parts)?;
+        if src_resolved != dst_resolved
+            && matches!(
+                self.walk_node(&as_parts(&dst_resolved))
+                    .map(|node| &node.kind),
+                Ok(NodeKind::Dir(_))
+            )
+        {
+            dst_resolved.push(src_parts[src_parts.len() - 1].to_string());
+        }
+        let dst_parts = as_parts(&dst_resolved);
+        if src_parts == dst_parts {
+            return Ok(());
+        }
+        if dst_parts.starts_with(&src_parts) {
+            return Err(FsError::InvalidPath);
+        }
+        self.require_parent(&src_parts, PERM_WRITE)?;
+        self.require_parent(&dst_parts, PERM_WRITE)?;
+        let moving = self.walk_node(&src_parts)?;
+        let moving_file = matches!(moving.kind, NodeKind::File(_));
+        let moving_bytes = node_bytes(moving);
+        let replaced = match self.walk_node(&dst_parts).map(|node| &node.kind) {
+            Ok(NodeKind::File(data)) if moving_file => data.len(),
+            Ok(_) => return Err(FsError::AlreadyExists),
+            Err(FsError::NotFound) => 0,
+            Err(err) => return Err(err),
+        };
+        self.make_room(&dst_parts, replaced, moving_bytes, &src_parts)?;
+        self.walk_parent_mut(&dst_parts)?;
+        let (parent, name) = self.walk_parent_mut(&src_parts)?;
+        let node = parent.remove(&name).ok_or(FsError::NotFound)?;
+        self.touch_parent(&src_parts);
+        let (parent, name) = self.walk_parent_mut(&dst_parts)?;
+        parent.insert(name, node);
+        self.touch_parent(&dst_parts);
+        self.notify(&src_parts, FsEventKind::MovedFrom);
+        self.notify(&dst_parts, FsEventKind::MovedTo);
+        Ok(())
+    }
+
+    /// Opens a file for chunked access; permissions are checked once, here.
+    ///
+    /// Handles refer to the path they were opened with, so removing or
+    /// renaming the file makes later calls fail with `NotFound`.
+    pub fn open(&mut self, path: &str, mode: OpenMode) -> Result<FileHandle, FsError> {
+        let parts = split_path(path)?;
+        let resolved = self.follow_links(&parts)?;
+        let parts = as_parts(&resolved);
+        if parts.is_empty() {
+            return Err(FsError::IsDir);
+        }
+        match self.walk_node(&parts) {
+            Ok(node) => {
+                if !matches!(node.kind, NodeKind::File(_)) {
+                    return Err(FsError::IsDir);
+                }
+                if mode.readable() {
+                    self.require(node, PERM_READ)?;
+                }
+                if mode.writable() {
+                    self.require(node, PERM_WRITE)?;
+                }
+            }
+            Err(FsError::NotFound) if mode.writable() => {
+                self.write_file(&resolved.join("/"), &[])?;
+            }
+            Err(err) => return Err(err),
+        }
+        let handle = FileHandle(self.next_handle);
+        self.next_handle = self.next_handle.wrapping_add(1);
+        self.open_files.insert(
+            handle,
+            OpenFile {
+                path: resolved,
+                mode,
+                offset: 0,
+            },
+        );
+        Ok(handle)
+    }
+
+    /// Releases a handle.
+    pub fn close(&mut self, handle: FileHandle) -> Result<(), FsError> {
+        self.open_files
+            .remove(&handle)
+            .map(|_| ())
+            .ok_or(FsError::BadHandle)
+    }
+
+    /// Copies bytes starting at `offset` into `buf`, returning how many were read.
+    pub fn read_at(
+        &self,
+        handle: FileHandle,
+        offset: usize,
+        buf: &mut [u8],
+    ) -> Result<usize, FsError> {
+        let open = self.open_file(handle)?;
+        if !open.mode.readable() {
+            return Err(FsError::PermissionDenied);
+        }
+        let data = self.handle_data(open)?;
+        let start = offset.min(data.len());
+        let count = buf.len().min(data.len() - start);
+        buf[..count].copy_from_slice(&data[start..start + count]);
+        Ok(count)
+    }
+
+    /// Writes `data` at `offset`, zero-filling any gap past the current end.
+    pub fn write_at(
+        &mut self,
+        handle: FileHandle,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<usize, FsError> {
+        let end = offset.checked_add(data.len()).ok_or(FsError::InvalidSeek)?;
+        let contents = self.writable_data(handle, end)?;
+        if contents.len() < end {
+            contents.resize(end, 0);
+        }
+        contents[offset..end].copy_from_slice(data);
+        Ok(data.len())
+    }
+
+    /// Reads from the handle's cursor and advances it.
+    pub fn read(&mut self, handle: FileHandle, buf: &mut [u8]) -> Result<usize, FsError> {
+        let offset = self.open_file(handle)?.offset;
+        let count = self.read_at(handle, offset, buf)?;
+        self.open_file_mut(handle)?.offset = offset + count;
+        Ok(count)
+    }
+
+    /// Writes at the handle's cursor and advances it.
+    pub fn write(&mut self, handle: FileHandle, data: &[u8]) -> Result<usize, FsError> {
+        let offset = self.open_file(handle)?.offset;
+        let count = self.write_at(handle, offset, data)?;
+        self.open_file_mut(handle)?.offset = offset + count;
+        Ok(count)
+    }
+
+    /// Moves the handle's cursor, returning the new offset.
+    pub fn seek(&mut self, handle: FileHandle, pos: SeekFrom) -> Result<usize, FsError> {
+        let open = self.open_file(handle)?;
+        let (base, delta) = match pos {
+            SeekFrom::Start(offset) => (offset, 0),
+            SeekFrom::End(delta) => (self.handle_data(open)?.len(), delta),
+            SeekFrom::Current(delta) => (open.offset, delta),
+        };
+        let offset = if delta < 0 {
+            base.checked_sub(delta.unsigned_abs() as usize)
+        } else {
+            base.checked_add(delta as usize)
+        }
+        .ok_or(FsError::InvalidSeek)?;
+        self.open_file_mut(handle)?.offset = offset;
+        Ok(offset)
+    }
+
+    /// Shrinks or zero-extends the file behind a writable handle to `len` bytes.
+    pub fn truncate(&mut self, handle: FileHandle, len: usize) -> Result<(), FsError> {
+        self.writable_data(handle, len)?.resize(len, 0);
+        Ok(())
+    }
+
+    /// Subscribes to changes at `prefix` or anywhere below it.
+    pub fn watch(&mut self, prefix: &str) -> Result<WatchId, FsError> {
+        let prefix = canonicalize(prefix)?;
+        let id = WatchId(self.next_watch);
+        self.next_watch = self.next_watch.wrapping_add(1);
+        self.watchers.insert(
+            id,
+            Watcher {
+                prefix,
+                events: Vec::new(),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Drops a subscription and any events still queued for it.
+    pub fn unwatch(&mut self, id: WatchId) -> Result<(), FsError> {
+        self.watchers
+            .remove(&id)
+            .map(|_| ())
+            .ok_or(FsError::BadHandle)
+    }
+
+    /// Takes the events queued for a subscription, oldest first.
+    ///
+    /// At most `WATCH_QUEUE_LIMIT` events are kept; older ones are dropped.
+    pub fn poll_events(&mut self, id: WatchId) -> Result<Vec<FsEvent>, FsError> {
+        let watcher = self.watchers.get_mut(&id).ok_or(FsError::BadHandle)?;
+        Ok(core::mem::take(&mut watcher.events))
+    }
+
+    fn notify(&mut self, parts: &[&str], kind: FsEventKind) {
+        if self.watchers.is_empty() {
+            return;
+        }
+        let mut path = String::new();
+        for segment in parts {
+            path.push('/');
+            path.push_str(segment);
+        }
+        for watcher in self.watchers.values_mut() {
+            if !watcher.covers(&path) {
+                continue;
+            }
+            if watcher.events.len() == WATCH_QUEUE_LIMIT {
+                watcher.events.remove(0);
+            }
+            watcher.events.push(FsEvent {
+                path: path.clone(),
+                kind,
+            });
+        }
+    }
+
+    /// Makes room for `incoming` bytes replacing `existing` ones at `parts`,
+    /// evicting from covering tmpfs mounts before checking the quota.
+    fn reserve(&mut self, parts: &[&str], existing: usize, incoming: usize) -> Result<(), FsError> {
+        self.make_room(parts, existing, incoming, &[])?;
+        if let Some(quota) = self.quota {
+            if self.stats().bytes - existing + incoming > quota {
+                return Err(FsError::NoSpace);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies every tmpfs mount covering `parts` but not `from` (an entry
+    /// moving within one mount does not change its usage).
+    fn make_room(
+        &mut self,
+        parts: &[&str],
+        existing: usize,
+        incoming: usize,
+        from: &[&str],
+    ) -> Result<(), FsError> {
+        for mount in self.tmpfs.clone() {
+            let root = as_parts(&mount.parts);
+            if !parts.starts_with(&root) || (!from.is_empty() && from.starts_with(&root)) {
+                continue;
+            }
+            let needed =
+                (self.subtree_bytes(&mount.parts) - existing + incoming).saturating_sub(mount.cap);
+            if needed == 0 {
+                continue;
+            }
+            if mount.policy == EvictionPolicy::Reject || incoming > mount.cap {
+                return Err(FsError::NoSpace);
+            }
+            let mut victims = Vec::new();
+            if let Ok(Node {
+                kind: NodeKind::Dir(children),
+                ..
+            }) = self.walk_node(&root)
+            {
+                collect_files(children, &mount.parts, &mut victims);
+            }
+            victims.retain(|victim| !as_parts(&victim.path).starts_with(parts));
+            if victims.iter().map(|victim| victim.size).sum::<usize>() < needed {
+                return Err(FsError::NoSpace);
+            }
+            match mount.policy {
+                EvictionPolicy::OldestFirst => victims.sort_by_key(|victim| victim.modified),
+                _ => victims.sort_by_key(|victim| core::cmp::Reverse(victim.size)),
+            }
+            let mut freed = 0;
+            for victim in victims {
+                if freed >= needed {
+                    break;
+                }
+                let victim_parts = as_parts(&victim.path);
+                let (parent, name) = self.walk_parent_mut(&victim_parts)?;
+                parent.remove(&name);
+                self.touch_parent(&victim_parts);
+                self.notify(&victim_parts, FsEventKind::Removed);
+                freed += victim.size;
+            }
+        }
+        Ok(())
+    }
+
+    fn subtree_bytes(&self, parts: &[String]) -> usize {
+        self.walk_node(&as_parts(parts)).map_or(0, node_bytes)
+    }
+
+    fn open_file(&self, handle: FileHandle) -> Result<&OpenFile, FsError> {
+        self.open_files.get(&handle).ok_or(FsError::BadHandle)
+    }
+
+    fn open_file_mut(&mut self, handle: FileHandle) -> Result<&mut OpenFile, FsError> {
+        self.open_files.get_mut(&handle).ok_or(FsError::BadHandle)
+    }
+
+    fn handle_data(&self, open: &OpenFile) -> Result<&Vec<u8>, FsError> {
+        match &self.walk_node(&as_parts(&open.path))?.kind {
+            NodeKind::File(data) => Ok(data),
+            _ => Err(FsError::IsDir),
+        }
+    }
+
+    /// Returns the file contents behind a writable handle, checking
+    /// `MAX_FILE_SIZE` and the quota for a resize to at least `min_len` and
+    /// stamping the modified time.
+    fn writable_data(
+        &mut self,
+        handle: FileHandle,
+        min_len: usize,
+    ) -> Result<&mut Vec<u8>, FsError> {
+        let open = self.open_file(handle)?;
+        if !open.mode.writable() {
+            return Err(FsError::PermissionDenied);
+        }
+        if min_len > MAX_FILE_SIZE {
+            return Err(FsError::NoSpace);
+        }
+        let existing = self.handle_data(open)?.len();
+        let path = open.path.clone();
+        self.reserve(&as_parts(&path), existing, min_len.max(existing))?;
+        let now = self.now;
+        self.notify(&as_parts(&path), FsEventKind::Written);
+        let (parent, name) = self.walk_parent_mut(&as_parts(&path))?;
+        match parent.get_mut(&name) {
+            Some(Node {
+                kind: NodeKind::File(data),
+                modified,
+                ..
+            }) => {
+                *modified = now;
+                Ok(data)
+            }
+            Some(_) => Err(FsError::IsDir),
+            None => Err(FsError::NotFound),
+        }
+    }
+
+    fn dir_children(&self, path: &str) -> Result<&BTreeMap<String, Node>, FsError> {
+        let parts = split_path(path)?;
+        let resolved = self.follow_links(&parts)?;
+        let parts = as_parts(&resolved);
+        if parts.is_empty() {
+            return Ok(&self.root);
+        }
+        let node = self.walk_node(&parts)?;
+        match &node.kind {
+            NodeKind::Dir(children) => {
+                self.require(node, PERM_READ)?;
+                Ok(children)
+            }
+            _ => Err(FsError::NotDir),
+        }
+    }
+
+    fn new_node(&self, kind: NodeKind, mode: u16) -> Node {
+        let (owner, group) = match &self.identity {
+            Some(identity) => (
+                identity.user.as_str(),
+                identity
+                    .groups
+                    .first()
+                    .map(String::as_str)
+                    .unwrap_or(identity.user.as_str()),
+            ),
+            None => (ROOT_OWNER, ROOT_GROUP),
+        };
+        Node {
+            kind,
+            owner: owner.to_string(),
+            group: group.to_string(),
+            mode,
+            created: self.now,
+            modified: self.now,
+        }
+    }
+
+    fn copied_node(&self, node: &Node) -> Result<Node, FsError> {
+        let kind = match &node.kind {
+            NodeKind::File(data) => {
+                self.require(node, PERM_READ)?;
+                NodeKind::File(data.clone())
+            }
+            NodeKind::Dir(children) => {
+                self.require(node, PERM_READ)?;
+                let mut copies = BTreeMap::new();
+                for (name, child) in children {
+                    copies.insert(name.clone(), self.copied_node(child)?);
+                }
+                NodeKind::Dir(copies)
+            }
+            NodeKind::Symlink(target) => NodeKind::Symlink(target.clone()),
+        };
+        Ok(self.new_node(kind, node.mode))
+    }
+
+    fn require_tree(&self, node: &Node, perm: u16) -> Result<(), FsError> {
+        if let NodeKind::Dir(children) = &node.kind {
+            self.require(node, perm)?;
+            for child in children.values() {
+                self.require_tree(child, perm)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn touch_parent(&mut self, parts: &[&str]) {
+        let now = self.now;
+        let mut current = &mut self.root;
+        let parent = &parts[..parts.len() - 1];
+        for (index, segment) in parent.iter().enumerate() {
+            let Some(node) = current.get_mut(*segment) else {
+                return;
+            };
+            if index == parent.len() - 1 {
+                node.modified = now;
+                return;
+            }
+            match &mut node.kind {
+                NodeKind::Dir(children) => current = children,
+                _ => return,
+            }
+        }
+    }
+
+    fn require(&self, node: &Node, perm: u16) -> Result<(), FsError> {
+        if permits(
+            self.identity.as_ref(),
+            &node.owner,
+            &node.group,
+            node.mode,
+            perm,
+        ) {
+            Ok(())
+        } else {
+            Err(FsError::PermissionDenied)
+        }
+    }
+
+    fn require_admin(&self) -> Result<(), FsError> {
+        if self.identity.as_ref().is_some_and(|identity| !identity.admin) {
+            return Err(FsError::PermissionDenied);
+        }
+        Ok(())
+    }
+
+    fn require_parent(&self, parts: &[&str], perm: u16) -> Result<(), FsError> {
+        let parent = &parts[..parts.len() - 1];
+        if parent.is_empty() {
+            return self.require(&root_node(), perm);
+        }
+        let node = self.walk_node(parent)?;
+        self.require(node, perm)
+    }
+
+    fn node_mut(&mut self, path: &str) -> Result<&mut Node, FsError> {
+        let parts = split_path(path)?;
+        if parts.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let resolved = self.follow_links(&parts)?;
+        let parts = as_parts(&resolved);
+        if parts.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let node = self.walk_node(&parts)?;
+        let is_owner = self
+            .identity
+            .as_ref()
+            .is_none_or(|identity| identity.admin || identity.user == node.owner);
+        if !is_owner {
+            return Err(FsError::PermissionDenied);
+        }
+        let (parent, name) = self.walk_parent_mut(&parts)?;
+        parent.get_mut(&name).ok_or(FsError::NotFound)
+    }
+
     fn walk_node<'a>(&'a self, parts: &[&str]) -> Result<&'a Node, FsError> {
         let mut current = &self.root;
         for (index, segment) in parts.iter().enumerate() {
@@ -173,14 +1506,56 @@ impl FileSystem {
             if index == parts.len() - 1 {
                 return Ok(node);
             }
-            match node {
-                Node::Dir(children) => current = children,
-                Node::File(_) => return Err(FsError::NotDir),
+            match &node.kind {
+                NodeKind::Dir(children) => current = children,
+                _ => return Err(FsError::NotDir),
             }
         }
         Err(FsError::NotFound)
     }
 
+    fn follow_links(&self, parts: &[&str]) -> Result<Vec<String>, FsError> {
+        let mut resolved: Vec<String> = Vec::new();
+        let mut pending: Vec<String> = parts.iter().rev().map(|part| part.to_string()).collect();
+        let mut hops = 0;
+        while let Some(segment) = pending.pop() {
+            match segment.as_str() {
+                "." => continue,
+                ".." => {
+                    resolved.pop();
+                    continue;
+                }
+                _ => resolved.push(segment),
+            }
+            match self.walk_node(&as_parts(&resolved)).map(|node| &node.kind) {
+                Ok(NodeKind::Symlink(target)) => {
+                    hops += 1;
+                    if hops > SYMLINK_HOP_LIMIT {
+                        return Err(FsError::SymlinkLoop);
+                    }
+                    resolved.pop();
+                    if target.starts_with('/') {
+                        resolved.clear();
+                    }
+                    for part in target.rsplit('/').filter(|part| !part.is_empty()) {
+                        pending.push(part.to_string());
+                    }
+                }
+                Ok(_) => {}
+                Err(FsError::NotFound) if pending.is_empty() => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn follow_parent(&self, parts: &[&str]) -> Result<Vec<String>, FsError> {
+        let (path, name) = parts.split_at(parts.len() - 1);
+        let mut resolved = self.follow_links(path)?;
+        resolved.push(name[0].to_string());
+        Ok(resolved)
+    }
+
     fn walk_parent_mut(
         &mut self,
         parts: &[&str],
@@ -189,51 +1564,372 @@ impl FileSystem {
         let mut current = &mut self.root;
         for segment in path {
             let node = current.get_mut(*segment).ok_or(FsError::NotFound)?;
-            match node {
-                Node::Dir(children) => current = children,
-                Node::File(_) => return Err(FsError::NotDir),
+            match &mut node.kind {
+                NodeKind::Dir(children) => current = children,
+                _ => return Err(FsError::NotDir),
             }
         }
         Ok((current, name[0].to_string()))
     }
 }
 
-fn split_path(path: &str) -> Result<Vec<&str>, FsError> {
-    let trimmed = path.trim();
-    if trimmed.is_empty() {
-        return Err(FsError::InvalidPath);
-    }
-    if trimmed != "/" && trimmed.ends_with('/') {
-        return Err(FsError::InvalidPath);
-    }
-    if trimmed.contains("//") {
-        return Err(FsError::InvalidPath);
-    }
-    if trimmed == "/" {
-        return Ok(Vec::new());
-    }
-    let mut parts = Vec::new();
-    for segment in trimmed.split('/') {
-        if segment.is_empty() {
-            continue;
-        }
-        if segment == "." || segment == ".." {
-            return Err(FsError::InvalidPath);
+/// Pre-order iterator over a subtree, returned by `FileSystem::walk`.
+#[derive(Debug)]
+pub struct Walk<'a> {
+    fs: &'a FileSystem,
+    start: Option<WalkEntry>,
+    stack: Vec<(String, usize, &'a Node)>,
+}
+
+impl<'a> Walk<'a> {
+    fn push_children(&mut self, base: &str, depth: usize, children: &'a BTreeMap<String, Node>) {
+        for (name, child) in children.iter().rev() {
+            self.stack.push((join_child(base, name), depth, child));
+        }
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = WalkEntry;
+
+    fn next(&mut self) -> Option<WalkEntry> {
+        if let Some(start) = self.start.take() {
+            return Some(start);
+        }
+        let (path, depth, node) = self.stack.pop()?;
+        let size = match &node.kind {
+            NodeKind::File(data) => data.len(),
+            NodeKind::Symlink(target) => target.len(),
+            NodeKind::Dir(children) => {
+                if self.fs.require(node, PERM_READ).is_ok() {
+                    self.push_children(&path, depth + 1, children);
+                }
+                0
+            }
+        };
+        Some(WalkEntry {
+            path,
+            kind: node.file_kind(),
+            depth,
+            size,
+        })
+    }
+}
+
+/// Returns true if an identity holds `perm` on an entry with the given owner, group and mode.
+pub fn permits(
+    identity: Option<&Identity>,
+    owner: &str,
+    group: &str,
+    mode: u16,
+    perm: u16,
+) -> bool {
+    let Some(identity) = identity else {
+        return true;
+    };
+    if identity.admin {
+        return true;
+    }
+    let bits = if identity.user == owner {
+        (mode >> 6) & 0o7
+    } else if identity.in_group(group) {
+        (mode >> 3) & 0o7
+    } else {
+        mode & 0o7
+    };
+    bits & perm == perm
+}
+
+/// Parses an octal mode string such as `755` or `0644`.
+pub fn parse_mode(text: &str) -> Result<u16, FsError> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() || trimmed.len() > 4 {
+        return Err(FsError::InvalidMode);
+    }
+    let mode = u16::from_str_radix(trimmed, 8).map_err(|_| FsError::InvalidMode)?;
+    if mode > 0o777 {
+        return Err(FsError::InvalidMode);
+    }
+    Ok(mode)
+}
+
+/// Normalizes a path to absolute form, resolving `.` and `..` lexically.
+///
+/// Relative paths start at `/`, `..` stops at the root and a trailing slash
+/// is dropped; empty paths and `//` are rejected.
+pub fn canonicalize(path: &str) -> Result<String, FsError> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() || trimmed.contains("//") {
+        return Err(FsError::InvalidPath);
+    }
+    let mut canonical = String::new();
+    for segment in normalize_segments(trimmed) {
+        canonical.push('/');
+        canonical.push_str(segment);
+    }
+    if canonical.is_empty() {
+        canonical.push('/');
+    }
+    Ok(canonical)
+}
+
+/// Returns true if a path or name contains `*` or `?` wildcards.
+pub fn is_glob_pattern(text: &str) -> bool {
+    text.contains(['*', '?'])
+}
+
+/// Matches a name against a glob pattern supporting `*` and `?`.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let name = name.as_bytes();
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|byte| *byte == b'*')
+}
+
+/// Formats a mode as `ls -l` style text (e.g. `drwxr-xr-x`).
+pub fn format_mode(kind: FileKind, mode: u16) -> String {
+    let mut out = String::new();
+    out.push(match kind {
+        FileKind::File => '-',
+        FileKind::Dir => 'd',
+        FileKind::Symlink => 'l',
+    });
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        out.push(if bits & PERM_READ != 0 { 'r' } else { '-' });
+        out.push(if bits & PERM_WRITE != 0 { 'w' } else { '-' });
+        out.push(if bits & PERM_EXEC != 0 { 'x' } else { '-' });
+    }
+    out
+}
+
+fn root_node() -> Node {
+    Node {
+        kind: NodeKind::Dir(BTreeMap::new()),
+        owner: ROOT_OWNER.to_string(),
+        group: ROOT_GROUP.to_string(),
+        mode: DEFAULT_DIR_MODE,
+        created: 0,
+        modified: 0,
+    }
+}
+
+fn split_path(path: &str) -> Result<Vec<&str>, FsError> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(FsError::InvalidPath);
+    }
+    if trimmed != "/" && trimmed.ends_with('/') {
+        return Err(FsError::InvalidPath);
+    }
+    if trimmed.contains("//") {
+        return Err(FsError::InvalidPath);
+    }
+    Ok(normalize_segments(trimmed))
+}
+
+fn normalize_segments(path: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(segment),
+        }
+    }
+    parts
+}
+
+fn join_child(base: &str, name: &str) -> String {
+    let mut path = String::from(base);
+    path.push('/');
+    path.push_str(name);
+    path
+}
+
+fn as_parts(segments: &[String]) -> Vec<&str> {
+    segments.iter().map(String::as_str).collect()
+}
+
+fn encode_node(node: &Node, out: &mut Vec<u8>) {
+    let (tag, payload): (u8, &[u8]) = match &node.kind {
+        NodeKind::File(data) => (0, data),
+        NodeKind::Dir(_) => (1, &[]),
+        NodeKind::Symlink(target) => (2, target.as_bytes()),
+    };
+    out.push(tag);
+    encode_bytes(node.owner.as_bytes(), out);
+    encode_bytes(node.group.as_bytes(), out);
+    out.extend_from_slice(&node.mode.to_le_bytes());
+    out.extend_from_slice(&node.created.to_le_bytes());
+    out.extend_from_slice(&node.modified.to_le_bytes());
+    encode_bytes(payload, out);
+    if let NodeKind::Dir(children) = &node.kind {
+        out.extend_from_slice(&(children.len() as u32).to_le_bytes());
+        for (name, child) in children {
+            encode_bytes(name.as_bytes(), out);
+            encode_node(child, out);
+        }
+    }
+}
+
+fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+struct SnapshotReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    version: u8,
+}
+
+impl<'a> SnapshotReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], FsError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(FsError::CorruptSnapshot)?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, FsError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, FsError> {
+        let bytes = self.take(8)?;
+        let mut raw = [0; 8];
+        raw.copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(raw))
+    }
+
+    fn chunk(&mut self) -> Result<&'a [u8], FsError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn text(&mut self) -> Result<String, FsError> {
+        let bytes = self.chunk()?;
+        core::str::from_utf8(bytes)
+            .map(ToString::to_string)
+            .map_err(|_| FsError::CorruptSnapshot)
+    }
+
+    fn node(&mut self, depth: usize) -> Result<Node, FsError> {
+        if depth > SNAPSHOT_MAX_DEPTH {
+            return Err(FsError::CorruptSnapshot);
+        }
+        let tag = self.take(1)?[0];
+        let owner = self.text()?;
+        let group = if self.version >= 2 {
+            self.text()?
+        } else {
+            owner.clone()
+        };
+        let mode = self.take(2)?;
+        let mode = u16::from_le_bytes([mode[0], mode[1]]);
+        if mode > 0o777 {
+            return Err(FsError::CorruptSnapshot);
+        }
+        let (created, modified) = if self.version >= 3 {
+            (self.u64()?, self.u64()?)
+        } else {
+            (0, 0)
+        };
+        let kind = match tag {
+            0 => NodeKind::File(self.chunk()?.to_vec()),
+            1 => {
+                self.chunk()?;
+                let count = self.u32()?;
+                let mut children = BTreeMap::new();
+                for _ in 0..count {
+                    let name = self.text()?;
+                    if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+                        return Err(FsError::CorruptSnapshot);
+                    }
+                    let child = self.node(depth + 1)?;
+                    children.insert(name, child);
+                }
+                NodeKind::Dir(children)
+            }
+            2 => NodeKind::Symlink(self.text()?),
+            _ => return Err(FsError::CorruptSnapshot),
+        };
+        Ok(Node {
+            kind,
+            owner,
+            group,
+            mode,
+            created,
+            modified,
+        })
+    }
+}
+
+fn node_bytes(node: &Node) -> usize {
+    match &node.kind {
+        NodeKind::File(data) => data.len(),
+        NodeKind::Dir(children) => children.values().map(node_bytes).sum(),
+        NodeKind::Symlink(_) => 0,
+    }
+}
+
+/// File considered for tmpfs eviction.
+struct Victim {
+    path: Vec<String>,
+    size: usize,
+    modified: u64,
+}
+
+fn collect_files(children: &BTreeMap<String, Node>, base: &[String], out: &mut Vec<Victim>) {
+    for (name, node) in children {
+        let mut path = base.to_vec();
+        path.push(name.clone());
+        match &node.kind {
+            NodeKind::File(data) => out.push(Victim {
+                path,
+                size: data.len(),
+                modified: node.modified,
+            }),
+            NodeKind::Dir(children) => collect_files(children, &path, out),
+            NodeKind::Symlink(_) => {}
         }
-        parts.push(segment);
     }
-    Ok(parts)
 }
 
 fn count_dir(children: &BTreeMap<String, Node>, stats: &mut FsStats) {
     stats.dirs += 1;
     for node in children.values() {
-        match node {
-            Node::File(data) => {
+        match &node.kind {
+            NodeKind::File(data) => {
                 stats.files += 1;
                 stats.bytes += data.len();
             }
-            Node::Dir(grandchildren) => count_dir(grandchildren, stats),
+            NodeKind::Dir(grandchildren) => count_dir(grandchildren, stats),
+            NodeKind::Symlink(_) => {}
         }
     }
 }
@@ -253,8 +1949,36 @@ mod tests {
         assert_eq!(split_path(""), Err(FsError::InvalidPath));
         assert_eq!(split_path("/foo/"), Err(FsError::InvalidPath));
         assert_eq!(split_path("foo//bar"), Err(FsError::InvalidPath));
-        assert_eq!(split_path("./foo"), Err(FsError::InvalidPath));
-        assert_eq!(split_path("foo/.."), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn split_path_resolves_dot_segments() {
+        assert_eq!(split_path("./foo").unwrap(), vec!["foo"]);
+        assert!(split_path("foo/..").unwrap().is_empty());
+        assert_eq!(split_path("/a/./b/../c").unwrap(), vec!["a", "c"]);
+        assert!(split_path("/../..").unwrap().is_empty());
+    }
+
+    #[test]
+    fn canonicalize_normalizes_paths() {
+        assert_eq!(canonicalize("/").unwrap(), "/");
+        assert_eq!(canonicalize(" etc ").unwrap(), "/etc");
+        assert_eq!(canonicalize("/home/user/../docs/").unwrap(), "/home/docs");
+        assert_eq!(canonicalize("/home/./user/.").unwrap(), "/home/user");
+        assert_eq!(canonicalize("/../..").unwrap(), "/");
+        assert_eq!(canonicalize(""), Err(FsError::InvalidPath));
+        assert_eq!(canonicalize("/a//b"), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn operations_accept_dot_segments() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        fs.mkdir("/home/./user").unwrap();
+        fs.write_file("/home/user/../notes", b"hi").unwrap();
+        assert_eq!(fs.read_file("/home/notes").unwrap(), b"hi");
+        assert_eq!(fs.list_dir("/home/user/..").unwrap(), vec!["notes", "user"]);
+        assert_eq!(fs.mkdir("/home/.."), Err(FsError::InvalidPath));
     }
 
     #[test]
@@ -507,4 +2231,1206 @@ mod tests {
         let fs = FileSystem::new();
         assert_eq!(fs.walk_node(&[]), Err(FsError::NotFound));
     }
+
+    #[test]
+    fn symlink_read_follows_target() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/hostname", b"ruzzle").unwrap();
+        fs.symlink("/etc/hostname", "/hostname").unwrap();
+        fs.symlink("hostname", "/etc/alias").unwrap();
+        assert_eq!(fs.read_file("/hostname").unwrap(), b"ruzzle".to_vec());
+        assert_eq!(fs.read_file("/etc/alias").unwrap(), b"ruzzle".to_vec());
+        assert_eq!(fs.read_link("/hostname").unwrap(), "/etc/hostname");
+        assert_eq!(
+            fs.list_dir("/etc").unwrap(),
+            vec!["alias".to_string(), "hostname".to_string()]
+        );
+    }
+
+    #[test]
+    fn symlink_to_directory_is_traversed() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/var").unwrap();
+        fs.mkdir("/var/log").unwrap();
+        fs.symlink("/var/log", "/logs").unwrap();
+        fs.write_file("/logs/boot", b"ok").unwrap();
+        assert_eq!(fs.read_file("/var/log/boot").unwrap(), b"ok".to_vec());
+        assert_eq!(fs.list_dir("/logs").unwrap(), vec!["boot".to_string()]);
+        fs.mkdir("/logs/old").unwrap();
+        fs.remove("/logs/old").unwrap();
+        fs.symlink("../log/boot", "/logs/again").unwrap();
+        assert_eq!(fs.read_file("/var/log/again").unwrap(), b"ok".to_vec());
+        fs.symlink("./log/boot", "/var/boot").unwrap();
+        assert_eq!(fs.read_file("/var/boot").unwrap(), b"ok".to_vec());
+        fs.symlink("/", "/root").unwrap();
+        assert_eq!(fs.list_dir("/root").unwrap(), fs.list_dir("/").unwrap());
+        assert_eq!(fs.read_file("/root"), Err(FsError::IsDir));
+        assert_eq!(fs.write_file("/root", b"x"), Err(FsError::IsDir));
+        assert_eq!(fs.read_file("/logs"), Err(FsError::IsDir));
+    }
+
+    #[test]
+    fn symlink_writes_through_and_removes_link_only() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/data", b"old").unwrap();
+        fs.symlink("/data", "/link").unwrap();
+        fs.write_file("/link", b"new").unwrap();
+        assert_eq!(fs.read_file("/data").unwrap(), b"new".to_vec());
+        fs.remove("/link").unwrap();
+        assert_eq!(fs.read_file("/data").unwrap(), b"new".to_vec());
+        assert_eq!(fs.read_link("/link"), Err(FsError::NotFound));
+    }
+
+    #[test]
+    fn list_entries_reports_kinds() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/hosts", b"x").unwrap();
+        fs.symlink("/etc/hosts", "/hosts").unwrap();
+        let entry = |name: &str, kind| DirEntry {
+            name: name.to_string(),
+            kind,
+        };
+        assert_eq!(
+            fs.list_entries("/").unwrap(),
+            vec![
+                entry("etc", FileKind::Dir),
+                entry("hosts", FileKind::Symlink)
+            ]
+        );
+        assert_eq!(
+            fs.list_entries("/etc").unwrap(),
+            vec![entry("hosts", FileKind::File)]
+        );
+        assert_eq!(fs.list_entries("/hosts"), Err(FsError::NotDir));
+    }
+
+    #[test]
+    fn symlink_dangling_and_loops() {
+        let mut fs = FileSystem::new();
+        fs.symlink("/missing", "/dangling").unwrap();
+        assert_eq!(fs.read_file("/dangling"), Err(FsError::NotFound));
+        fs.write_file("/dangling", b"created").unwrap();
+        assert_eq!(fs.read_file("/missing").unwrap(), b"created".to_vec());
+        fs.symlink("/b", "/a").unwrap();
+        fs.symlink("/a", "/b").unwrap();
+        assert_eq!(fs.read_file("/a"), Err(FsError::SymlinkLoop));
+        assert_eq!(fs.list_dir("/a/x"), Err(FsError::SymlinkLoop));
+    }
+
+    #[test]
+    fn symlink_rejects_invalid_input() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/file", b"x").unwrap();
+        assert_eq!(fs.symlink("", "/link"), Err(FsError::InvalidPath));
+        assert_eq!(fs.symlink("/file", "/"), Err(FsError::InvalidPath));
+        assert_eq!(fs.symlink("/file", "/file"), Err(FsError::AlreadyExists));
+        assert_eq!(fs.symlink("/file", "bad//path"), Err(FsError::InvalidPath));
+        assert_eq!(fs.read_link("/"), Err(FsError::NotSymlink));
+        assert_eq!(fs.read_link("/file"), Err(FsError::NotSymlink));
+        fs.symlink("/file", "/link").unwrap();
+        assert_eq!(fs.list_dir("/link"), Err(FsError::NotDir));
+        assert_eq!(fs.mkdir("/link/sub"), Err(FsError::NotDir));
+    }
+
+    #[test]
+    fn stats_ignore_symlinks() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/file", b"abc").unwrap();
+        fs.symlink("/file", "/link").unwrap();
+        let stats = fs.stats();
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.bytes, 3);
+        let link = fs.stats_for("/link").unwrap();
+        assert_eq!(link.files, 0);
+        assert_eq!(link.bytes, 0);
+    }
+
+    #[test]
+    fn write_rejects_intermediate_file() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/etc", b"x").unwrap();
+        assert_eq!(fs.write_file("/etc/hosts", b"y"), Err(FsError::NotDir));
+    }
+
+    #[test]
+    fn timestamps_track_creation_and_changes() {
+        let mut fs = FileSystem::new();
+        fs.set_time(5);
+        assert_eq!(fs.time(), 5);
+        fs.mkdir("/docs").unwrap();
+        fs.set_time(9);
+        fs.write_file("/docs/a.txt", b"one").unwrap();
+        let docs = fs.metadata("/docs").unwrap();
+        assert_eq!((docs.created, docs.modified), (5, 9));
+        fs.set_time(12);
+        fs.write_file("/docs/a.txt", b"two").unwrap();
+        let file = fs.metadata("/docs/a.txt").unwrap();
+        assert_eq!((file.created, file.modified), (9, 12));
+        assert_eq!(fs.metadata("/docs").unwrap().modified, 9);
+        fs.set_time(15);
+        fs.symlink("/docs/a.txt", "/docs/link").unwrap();
+        assert_eq!(fs.metadata("/docs").unwrap().modified, 15);
+        fs.set_time(20);
+        fs.remove("/docs/link").unwrap();
+        assert_eq!(fs.metadata("/docs").unwrap().modified, 20);
+        assert_eq!(fs.metadata("/").unwrap().modified, 0);
+    }
+
+    #[test]
+    fn rename_moves_entries_and_keeps_timestamps() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/src").unwrap();
+        fs.mkdir("/dst").unwrap();
+        fs.set_time(3);
+        fs.write_file("/src/a", b"data").unwrap();
+        fs.mkdir("/src/sub").unwrap();
+        fs.set_time(8);
+        fs.rename("/src/a", "/dst/b").unwrap();
+        assert_eq!(fs.read_file("/dst/b").unwrap(), b"data");
+        assert_eq!(fs.read_file("/src/a"), Err(FsError::NotFound));
+        let moved = fs.metadata("/dst/b").unwrap();
+        assert_eq!((moved.created, moved.modified), (3, 3));
+        assert_eq!(fs.metadata("/src").unwrap().modified, 8);
+        assert_eq!(fs.metadata("/dst").unwrap().modified, 8);
+        fs.write_file("/src/c", b"new").unwrap();
+        fs.rename("/src/c", "/dst/b").unwrap();
+        assert_eq!(fs.read_file("/dst/b").unwrap(), b"new");
+        fs.rename("/src/sub", "/dst/sub").unwrap();
+        assert_eq!(fs.list_dir("/dst").unwrap(), vec!["b", "sub"]);
+        fs.rename("/dst/sub", "/dst/sub").unwrap();
+        assert_eq!(
+            fs.rename("/dst/sub", "/dst/sub/x"),
+            Err(FsError::InvalidPath)
+        );
+        fs.mkdir("/dst/sub/b").unwrap();
+        assert_eq!(fs.rename("/dst/b", "/dst/sub"), Err(FsError::AlreadyExists));
+        assert_eq!(fs.rename("/dst/sub", "/dst/b"), Err(FsError::AlreadyExists));
+        assert_eq!(fs.rename("/missing", "/x"), Err(FsError::NotFound));
+        assert_eq!(fs.rename("/dst/b", "/nope/b"), Err(FsError::NotFound));
+        assert_eq!(fs.rename("/", "/x"), Err(FsError::InvalidPath));
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(fs.rename("/dst/b", "/b"), Err(FsError::PermissionDenied));
+    }
+
+    #[test]
+    fn rename_moves_subtrees_into_directories() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/a").unwrap();
+        fs.mkdir("/a/b").unwrap();
+        fs.write_file("/a/b/c", b"deep").unwrap();
+        fs.symlink("/a/b/c", "/a/link").unwrap();
+        fs.mkdir("/dest").unwrap();
+        fs.rename("/a", "/dest").unwrap();
+        assert_eq!(fs.read_file("/dest/a/b/c").unwrap(), b"deep");
+        assert_eq!(fs.read_link("/dest/a/link").unwrap(), "/a/b/c");
+        assert_eq!(fs.metadata("/a"), Err(FsError::NotFound));
+        fs.write_file("/note", b"n").unwrap();
+        fs.rename("/note", "/dest/a/b").unwrap();
+        assert_eq!(fs.list_dir("/dest/a/b").unwrap(), vec!["c", "note"]);
+        fs.rename("/dest/a/b/note", "/dest/a/b").unwrap();
+        assert_eq!(fs.rename("/dest/a", "/dest/a/b"), Err(FsError::InvalidPath));
+        fs.mkdir("/c").unwrap();
+        assert_eq!(fs.rename("/c", "/dest/a/b"), Err(FsError::AlreadyExists));
+        fs.write_file("/dest/a/b/c2", b"x").unwrap();
+        fs.rename("/dest/a/b/c2", "/dest/a/b/c").unwrap();
+        assert_eq!(fs.read_file("/dest/a/b/c").unwrap(), b"x");
+    }
+
+    #[test]
+    fn watchers_receive_events_under_their_prefix() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.mkdir("/var").unwrap();
+        let etc = fs.watch("/etc/").unwrap();
+        let all = fs.watch("/").unwrap();
+        fs.write_file("/etc/net.conf", b"dhcp").unwrap();
+        fs.write_file("/etc/net.conf", b"static").unwrap();
+        fs.write_file("/etcetera", b"x").unwrap();
+        fs.rename("/etc/net.conf", "/var").unwrap();
+        fs.copy("/var/net.conf", "/etc/net.conf", false).unwrap();
+        fs.remove("/var/net.conf").unwrap();
+        let event = |path: &str, kind| FsEvent {
+            path: path.to_string(),
+            kind,
+        };
+        assert_eq!(
+            fs.poll_events(etc).unwrap(),
+            vec![
+                event("/etc/net.conf", FsEventKind::Written),
+                event("/etc/net.conf", FsEventKind::Written),
+                event("/etc/net.conf", FsEventKind::MovedFrom),
+                event("/etc/net.conf", FsEventKind::Written),
+            ]
+        );
+        assert!(fs.poll_events(etc).unwrap().is_empty());
+        let all_events = fs.poll_events(all).unwrap();
+        assert_eq!(all_events.len(), 7);
+        assert_eq!(all_events[4], event("/var/net.conf", FsEventKind::MovedTo));
+        assert_eq!(all_events[6], event("/var/net.conf", FsEventKind::Removed));
+        fs.unwatch(etc).unwrap();
+        assert_eq!(fs.unwatch(etc), Err(FsError::BadHandle));
+        assert_eq!(fs.poll_events(etc), Err(FsError::BadHandle));
+        assert_eq!(fs.watch(""), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn watchers_see_handle_writes_and_links_resolved() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.symlink("/etc", "/config").unwrap();
+        let id = fs.watch("/etc").unwrap();
+        let handle = fs.open("/config/log", OpenMode::Write).unwrap();
+        fs.write(handle, b"line").unwrap();
+        fs.truncate(handle, 0).unwrap();
+        fs.mkdir("/etc/sub").unwrap();
+        fs.write_file("/etc/sub/a", b"").unwrap();
+        fs.remove_recursive("/etc/sub").unwrap();
+        let kinds: Vec<(String, FsEventKind)> = fs
+            .poll_events(id)
+            .unwrap()
+            .into_iter()
+            .map(|event| (event.path, event.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("/etc/log".to_string(), FsEventKind::Written),
+                ("/etc/log".to_string(), FsEventKind::Written),
+                ("/etc/log".to_string(), FsEventKind::Written),
+                ("/etc/sub/a".to_string(), FsEventKind::Written),
+                ("/etc/sub".to_string(), FsEventKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn watch_queue_drops_oldest_events() {
+        let mut fs = FileSystem::new();
+        let id = fs.watch("/").unwrap();
+        for index in 0..WATCH_QUEUE_LIMIT + 2 {
+            fs.write_file(&format!("/f{index}"), b"").unwrap();
+        }
+        let events = fs.poll_events(id).unwrap();
+        assert_eq!(events.len(), WATCH_QUEUE_LIMIT);
+        assert_eq!(events[0].path, "/f2");
+    }
+
+    #[test]
+    fn walk_visits_subtree_in_preorder() {
+        let mut fs = FileSystem::new();
+        fs.mkdir_p("/home/ruzzle/docs").unwrap();
+        fs.write_file("/home/ruzzle/docs/b.txt", b"bb").unwrap();
+        fs.write_file("/home/ruzzle/a.txt", b"a").unwrap();
+        fs.symlink("/home", "/home/ruzzle/up").unwrap();
+        let entries: Vec<(String, usize, usize)> = fs
+            .walk("/home")
+            .unwrap()
+            .map(|entry| (entry.path, entry.depth, entry.size))
+            .collect();
+        let expected = [
+            ("/home", 0, 0),
+            ("/home/ruzzle", 1, 0),
+            ("/home/ruzzle/a.txt", 2, 1),
+            ("/home/ruzzle/docs", 2, 0),
+            ("/home/ruzzle/docs/b.txt", 3, 2),
+            ("/home/ruzzle/up", 2, 5),
+        ];
+        assert_eq!(
+            entries,
+            expected
+                .iter()
+                .map(|(path, depth, size)| (path.to_string(), *depth, *size))
+                .collect::<Vec<_>>()
+        );
+        let link = fs.walk("/home/ruzzle/up").unwrap().collect::<Vec<_>>();
+        assert_eq!(link.len(), 1);
+        assert_eq!(link[0].kind, FileKind::Symlink);
+        let root: Vec<WalkEntry> = fs.walk("/").unwrap().collect();
+        assert_eq!(root.len(), 7);
+        assert_eq!((root[0].path.as_str(), root[0].depth), ("/", 0));
+        assert_eq!((root[1].path.as_str(), root[1].depth), ("/home", 1));
+        assert_eq!(fs.walk("/missing").err(), Some(FsError::NotFound));
+    }
+
+    #[test]
+    fn walk_skips_unreadable_directories() {
+        let mut fs = FileSystem::new();
+        fs.mkdir_p("/secret/inner").unwrap();
+        fs.chmod("/secret", 0o700).unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        let paths: Vec<String> = fs.walk("/").unwrap().map(|entry| entry.path).collect();
+        assert_eq!(paths, vec!["/", "/secret"]);
+    }
+
+    #[test]
+    fn checksum_hashes_file_contents() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/payload", b"123456789").unwrap();
+        fs.symlink("/payload", "/link").unwrap();
+        assert_eq!(fs.checksum("/payload"), Ok(0xcbf4_3926));
+        assert_eq!(fs.checksum("/link"), Ok(0xcbf4_3926));
+        fs.write_file("/payload", b"12345678").unwrap();
+        assert_ne!(fs.checksum("/payload"), Ok(0xcbf4_3926));
+        assert_eq!(fs.checksum("/"), Err(FsError::IsDir));
+        assert_eq!(fs.checksum("/missing"), Err(FsError::NotFound));
+        fs.chmod("/payload", 0o600).unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(fs.checksum("/payload"), Err(FsError::PermissionDenied));
+    }
+
+    #[test]
+    fn tmpfs_evicts_oldest_files_to_stay_under_cap() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/tmp").unwrap();
+        fs.mount_tmpfs("/tmp", 10, EvictionPolicy::OldestFirst)
+            .unwrap();
+        let watch = fs.watch("/tmp").unwrap();
+        fs.set_time(1);
+        fs.write_file("/tmp/a", b"aaaa").unwrap();
+        fs.set_time(2);
+        fs.write_file("/tmp/b", b"bbbb").unwrap();
+        fs.set_time(3);
+        fs.write_file("/tmp/c", b"cccc").unwrap();
+        assert_eq!(fs.list_dir("/tmp").unwrap(), vec!["b", "c"]);
+        fs.write_file("/tmp/c", b"cccccc").unwrap();
+        assert_eq!(fs.list_dir("/tmp").unwrap(), vec!["b", "c"]);
+        assert_eq!(fs.write_file("/tmp/big", &[0; 11]), Err(FsError::NoSpace));
+        assert_eq!(
+            fs.tmpfs_mounts(),
+            vec![TmpfsMount {
+                path: "/tmp".to_string(),
+                cap: 10,
+                used: 10,
+                policy: EvictionPolicy::OldestFirst,
+            }]
+        );
+        let removed: Vec<FsEvent> = fs
+            .poll_events(watch)
+            .unwrap()
+            .into_iter()
+            .filter(|event| event.kind == FsEventKind::Removed)
+            .collect();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].path, "/tmp/a");
+        fs.write_file("/outside", &[0; 64]).unwrap();
+    }
+
+    #[test]
+    fn tmpfs_policies_and_paths_into_the_mount() {
+        let mut fs = FileSystem::new();
+        fs.mkdir_p("/var/tmp/cache").unwrap();
+        fs.mount_tmpfs("/var/tmp", 8, EvictionPolicy::LargestFirst)
+            .unwrap();
+        fs.write_file("/var/tmp/small", b"s").unwrap();
+        fs.write_file("/var/tmp/cache/large", b"lllll").unwrap();
+        fs.write_file("/var/tmp/new", b"nnnn").unwrap();
+        assert_eq!(fs.read_file("/var/tmp/cache/large"), Err(FsError::NotFound));
+        assert_eq!(fs.read_file("/var/tmp/small").unwrap(), b"s");
+
+        fs.write_file("/home", b"hhhhhh").unwrap();
+        fs.rename("/home", "/var/tmp/moved").unwrap();
+        assert_eq!(fs.read_file("/var/tmp/new"), Err(FsError::NotFound));
+        fs.rename("/var/tmp/moved", "/var/tmp/cache").unwrap();
+        assert_eq!(fs.list_dir("/var/tmp/cache").unwrap(), vec!["moved"]);
+        fs.copy("/var/tmp/cache/moved", "/copy", false).unwrap();
+        fs.copy("/copy", "/var/tmp/copy", false).unwrap();
+        assert!(fs.list_dir("/var/tmp/cache").unwrap().is_empty());
+        assert_eq!(fs.tmpfs_mounts()[0].used, 7);
+
+        fs.mount_tmpfs("/var/tmp", 8, EvictionPolicy::Reject)
+            .unwrap();
+        assert_eq!(fs.tmpfs_mounts().len(), 1);
+        assert_eq!(fs.write_file("/var/tmp/x", b"xx"), Err(FsError::NoSpace));
+        let handle = fs.open("/var/tmp/small", OpenMode::Write).unwrap();
+        assert_eq!(fs.write_at(handle, 0, b"sss"), Err(FsError::NoSpace));
+        fs.write_at(handle, 0, b"t").unwrap();
+    }
+
+    #[test]
+    fn mount_tmpfs_validates_target() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/file", b"").unwrap();
+        assert_eq!(
+            fs.mount_tmpfs("/", 1, EvictionPolicy::Reject),
+            Err(FsError::InvalidPath)
+        );
+        assert_eq!(
+            fs.mount_tmpfs("/file", 1, EvictionPolicy::Reject),
+            Err(FsError::NotDir)
+        );
+        assert_eq!(
+            fs.mount_tmpfs("/missing", 1, EvictionPolicy::Reject),
+            Err(FsError::NotFound)
+        );
+        fs.mkdir("/tmp").unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(
+            fs.mount_tmpfs("/tmp", 1, EvictionPolicy::Reject),
+            Err(FsError::PermissionDenied)
+        );
+        assert_eq!(EvictionPolicy::OldestFirst.name(), "oldest");
+    }
+
+    #[test]
+    fn open_handles_stream_in_chunks() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/data").unwrap();
+        fs.write_file("/data/log", b"hello world").unwrap();
+        let handle = fs.open("/data/log", OpenMode::Read).unwrap();
+        let mut buf = [0u8; 4];
+        assert_eq!(fs.read(handle, &mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"hell");
+        assert_eq!(fs.read(handle, &mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"o wo");
+        assert_eq!(fs.read(handle, &mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"rld");
+        assert_eq!(fs.read(handle, &mut buf).unwrap(), 0);
+        assert_eq!(fs.read_at(handle, 6, &mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"worl");
+        assert_eq!(fs.read_at(handle, 99, &mut buf).unwrap(), 0);
+        assert_eq!(fs.seek(handle, SeekFrom::End(-5)).unwrap(), 6);
+        assert_eq!(fs.seek(handle, SeekFrom::Current(-2)).unwrap(), 4);
+        assert_eq!(fs.seek(handle, SeekFrom::Start(1)).unwrap(), 1);
+        assert_eq!(
+            fs.seek(handle, SeekFrom::Current(-2)),
+            Err(FsError::InvalidSeek)
+        );
+        assert_eq!(fs.write(handle, b"x"), Err(FsError::PermissionDenied));
+        assert_eq!(fs.truncate(handle, 0), Err(FsError::PermissionDenied));
+        fs.close(handle).unwrap();
+        assert_eq!(fs.close(handle), Err(FsError::BadHandle));
+        assert_eq!(fs.read(handle, &mut buf), Err(FsError::BadHandle));
+    }
+
+    #[test]
+    fn open_handles_write_and_truncate() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/data").unwrap();
+        fs.set_time(4);
+        let handle = fs.open("/data/new", OpenMode::Write).unwrap();
+        assert_eq!(fs.read_file("/data/new").unwrap(), b"");
+        assert_eq!(fs.write(handle, b"abc").unwrap(), 3);
+        assert_eq!(fs.write(handle, b"def").unwrap(), 3);
+        fs.set_time(9);
+        assert_eq!(fs.write_at(handle, 8, b"z").unwrap(), 1);
+        assert_eq!(fs.read_file("/data/new").unwrap(), b"abcdef\0\0z");
+        assert_eq!(fs.metadata("/data/new").unwrap().modified, 9);
+        let mut buf = [0u8; 2];
+        assert_eq!(
+            fs.read_at(handle, 0, &mut buf),
+            Err(FsError::PermissionDenied)
+        );
+        fs.truncate(handle, 2).unwrap();
+        assert_eq!(fs.read_file("/data/new").unwrap(), b"ab");
+        fs.truncate(handle, 4).unwrap();
+        assert_eq!(fs.read_file("/data/new").unwrap(), b"ab\0\0");
+        assert_eq!(
+            fs.write_at(handle, usize::MAX, b"x"),
+            Err(FsError::InvalidSeek)
+        );
+        let both = fs.open("/data/new", OpenMode::ReadWrite).unwrap();
+        assert_ne!(both, handle);
+        fs.write_at(both, 2, b"cd").unwrap();
+        assert_eq!(fs.read_at(both, 0, &mut buf).unwrap(), 2);
+        assert_eq!(&buf, b"ab");
+        fs.remove("/data/new").unwrap();
+        assert_eq!(fs.read(both, &mut buf), Err(FsError::NotFound));
+        assert_eq!(fs.truncate(both, 0), Err(FsError::NotFound));
+        fs.set_quota(Some(4));
+        let handle = fs.open("/data/q", OpenMode::Write).unwrap();
+        fs.write(handle, b"1234").unwrap();
+        assert_eq!(fs.write(handle, b"5"), Err(FsError::NoSpace));
+        fs.truncate(handle, 1).unwrap();
+    }
+
+    #[test]
+    fn handles_cannot_grow_files_past_the_size_limit() {
+        let mut fs = FileSystem::new();
+        let handle = fs.open("/big", OpenMode::Write).unwrap();
+        fs.write(handle, b"ab").unwrap();
+        assert_eq!(
+            fs.write_at(handle, MAX_FILE_SIZE, b"x"),
+            Err(FsError::NoSpace)
+        );
+        assert_eq!(
+            fs.truncate(handle, MAX_FILE_SIZE + 1),
+            Err(FsError::NoSpace)
+        );
+        assert_eq!(
+            fs.seek(handle, SeekFrom::Start(usize::MAX - 1)),
+            Ok(usize::MAX - 1)
+        );
+        assert_eq!(fs.write(handle, b"x"), Err(FsError::NoSpace));
+        assert_eq!(fs.read_file("/big").unwrap(), b"ab");
+        fs.write_at(handle, MAX_FILE_SIZE - 1, b"z").unwrap();
+        assert_eq!(fs.read_file("/big").unwrap().len(), MAX_FILE_SIZE);
+    }
+
+    #[test]
+    fn open_checks_kind_and_permissions() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/data").unwrap();
+        fs.write_file("/data/secret", b"s").unwrap();
+        fs.chmod("/data/secret", 0o600).unwrap();
+        assert_eq!(fs.open("/", OpenMode::Read), Err(FsError::IsDir));
+        assert_eq!(fs.open("/data", OpenMode::Read), Err(FsError::IsDir));
+        assert_eq!(
+            fs.open("/data/missing", OpenMode::Read),
+            Err(FsError::NotFound)
+        );
+        fs.symlink("/data/secret", "/data/link").unwrap();
+        let handle = fs.open("/data/link", OpenMode::ReadWrite).unwrap();
+        fs.write(handle, b"t").unwrap();
+        assert_eq!(fs.read_file("/data/secret").unwrap(), b"t");
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(
+            fs.open("/data/secret", OpenMode::Read),
+            Err(FsError::PermissionDenied)
+        );
+        fs.set_identity(None);
+        fs.chmod("/data/secret", 0o644).unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert!(fs.open("/data/secret", OpenMode::Read).is_ok());
+        assert_eq!(
+            fs.open("/data/secret", OpenMode::Write),
+            Err(FsError::PermissionDenied)
+        );
+        assert_eq!(
+            fs.open("/data/new", OpenMode::Write),
+            Err(FsError::PermissionDenied)
+        );
+    }
+
+    #[test]
+    fn mkdir_p_creates_missing_parents() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/srv").unwrap();
+        assert_eq!(
+            fs.mkdir_p("/srv/www/static").unwrap(),
+            vec!["/srv/www", "/srv/www/static"]
+        );
+        assert!(fs.mkdir_p("/srv/www").unwrap().is_empty());
+        assert!(fs.mkdir_p("/").unwrap().is_empty());
+        fs.symlink("/srv/www", "/web").unwrap();
+        assert_eq!(fs.mkdir_p("/web/img").unwrap(), vec!["/web/img"]);
+        assert_eq!(fs.list_dir("/srv/www").unwrap(), vec!["img", "static"]);
+        fs.symlink("/", "/top").unwrap();
+        assert_eq!(fs.mkdir_p("/top/opt").unwrap(), vec!["/top/opt"]);
+        assert!(fs.list_dir("/opt").is_ok());
+        fs.write_file("/srv/file", b"x").unwrap();
+        assert_eq!(fs.mkdir_p("/srv/file"), Err(FsError::NotDir));
+        assert_eq!(fs.mkdir_p("/srv/file/sub"), Err(FsError::NotDir));
+        assert_eq!(fs.mkdir_p(""), Err(FsError::InvalidPath));
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(fs.mkdir_p("/home/guest"), Err(FsError::PermissionDenied));
+    }
+
+    #[test]
+    fn copy_duplicates_files_and_trees() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/src").unwrap();
+        fs.mkdir("/src/sub").unwrap();
+        fs.write_file("/src/sub/a", b"alpha").unwrap();
+        fs.symlink("/src/sub/a", "/src/link").unwrap();
+        fs.chmod("/src/sub/a", 0o600).unwrap();
+        assert_eq!(fs.copy("/src", "/dst", false), Err(FsError::IsDir));
+        fs.set_time(6);
+        fs.copy("/src", "/dst", true).unwrap();
+        assert_eq!(fs.read_file("/dst/sub/a").unwrap(), b"alpha");
+        assert_eq!(fs.read_link("/dst/link").unwrap(), "/src/sub/a");
+        let meta = fs.metadata("/dst/sub/a").unwrap();
+        assert_eq!((meta.mode, meta.created), (0o600, 6));
+        assert_eq!(fs.read_file("/src/sub/a").unwrap(), b"alpha");
+        fs.copy("/src/sub/a", "/dst", false).unwrap();
+        assert_eq!(fs.read_file("/dst/a").unwrap(), b"alpha");
+        fs.write_file("/src/b", b"beta").unwrap();
+        fs.copy("/src/b", "/dst/a", false).unwrap();
+        assert_eq!(fs.read_file("/dst/a").unwrap(), b"beta");
+        fs.copy("/src", "/dst", true).unwrap();
+        assert_eq!(fs.list_dir("/dst/src").unwrap(), vec!["b", "link", "sub"]);
+        assert_eq!(fs.copy("/src", "/src", true), Err(FsError::InvalidPath));
+        assert_eq!(fs.copy("/src", "/src/sub", true), Err(FsError::InvalidPath));
+        assert_eq!(
+            fs.copy("/src/b", "/src/b", false),
+            Err(FsError::InvalidPath)
+        );
+        assert_eq!(
+            fs.copy("/dst/sub", "/dst/a", true),
+            Err(FsError::AlreadyExists)
+        );
+        assert_eq!(fs.copy("/missing", "/x", false), Err(FsError::NotFound));
+        assert_eq!(fs.copy("/", "/x", true), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn copy_checks_permissions_and_quota() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/data").unwrap();
+        fs.write_file("/data/secret", b"1234").unwrap();
+        fs.chmod("/data/secret", 0o600).unwrap();
+        fs.write_file("/data/open", b"12").unwrap();
+        fs.mkdir("/home").unwrap();
+        fs.chmod("/home", 0o777).unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(
+            fs.copy("/data", "/home/data", true),
+            Err(FsError::PermissionDenied)
+        );
+        assert_eq!(
+            fs.copy("/data/open", "/data/copy", false),
+            Err(FsError::PermissionDenied)
+        );
+        assert_eq!(
+            fs.copy("/data/open", "/data/secret", false),
+            Err(FsError::PermissionDenied)
+        );
+        fs.copy("/data/open", "/home/open", false).unwrap();
+        assert_eq!(fs.metadata("/home/open").unwrap().owner, "guest");
+        fs.set_identity(None);
+        fs.set_quota(Some(9));
+        assert_eq!(
+            fs.copy("/data/secret", "/home/big", false),
+            Err(FsError::NoSpace)
+        );
+        fs.copy("/data/open", "/home/open", false).unwrap();
+    }
+
+    #[test]
+    fn remove_recursive_reports_counts() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/tmp").unwrap();
+        fs.mkdir("/tmp/a").unwrap();
+        fs.mkdir("/tmp/a/b").unwrap();
+        fs.write_file("/tmp/a/one", b"123").unwrap();
+        fs.write_file("/tmp/a/b/two", b"45").unwrap();
+        fs.symlink("/tmp", "/tmp/a/up").unwrap();
+        fs.write_file("/tmp/keep", b"k").unwrap();
+        fs.symlink("/tmp/keep", "/link").unwrap();
+        assert_eq!(
+            fs.remove_recursive("/tmp/a").unwrap(),
+            FsStats {
+                files: 2,
+                dirs: 2,
+                bytes: 5,
+            }
+        );
+        assert_eq!(fs.list_dir("/tmp").unwrap(), vec!["keep"]);
+        assert_eq!(fs.remove_recursive("/link").unwrap().files, 0);
+        assert_eq!(fs.read_file("/tmp/keep").unwrap(), b"k");
+        assert_eq!(fs.remove_recursive("/tmp/keep").unwrap().bytes, 1);
+        assert_eq!(fs.remove_recursive("/"), Err(FsError::InvalidPath));
+        assert_eq!(fs.remove_recursive("/missing"), Err(FsError::NotFound));
+    }
+
+    #[test]
+    fn remove_recursive_checks_every_directory_first() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        fs.chmod("/home", 0o777).unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        fs.mkdir("/home/tree").unwrap();
+        fs.write_file("/home/tree/a", b"a").unwrap();
+        fs.set_identity(None);
+        fs.mkdir("/home/tree/locked").unwrap();
+        fs.write_file("/home/tree/locked/b", b"b").unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(
+            fs.remove_recursive("/home/tree"),
+            Err(FsError::PermissionDenied)
+        );
+        assert_eq!(fs.list_dir("/home/tree").unwrap(), vec!["a", "locked"]);
+        assert_eq!(fs.remove_recursive("/home"), Err(FsError::PermissionDenied));
+    }
+
+    #[test]
+    fn glob_expands_wildcards_per_segment() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/net.conf", b"").unwrap();
+        fs.write_file("/etc/ruzzle.conf", b"").unwrap();
+        fs.write_file("/etc/.hidden.conf", b"").unwrap();
+        fs.write_file("/etc/hosts", b"").unwrap();
+        fs.mkdir_p("/home/alice/docs").unwrap();
+        fs.mkdir_p("/home/bob/docs").unwrap();
+        fs.mkdir("/home/carol").unwrap();
+        fs.write_file("/home/notes", b"").unwrap();
+        assert_eq!(
+            fs.glob("/etc/*.conf").unwrap(),
+            vec!["/etc/net.conf", "/etc/ruzzle.conf"]
+        );
+        assert_eq!(fs.glob("/etc/.*").unwrap(), vec!["/etc/.hidden.conf"]);
+        assert_eq!(
+            fs.glob("/home/*/docs").unwrap(),
+            vec!["/home/alice/docs", "/home/bob/docs"]
+        );
+        assert_eq!(fs.glob("/home/?o?").unwrap(), vec!["/home/bob"]);
+        assert_eq!(fs.glob("/home/*/*").unwrap().len(), 2);
+        assert_eq!(fs.glob("/etc/hosts").unwrap(), vec!["/etc/hosts"]);
+        assert!(fs.glob("/etc/*.txt").unwrap().is_empty());
+        assert!(fs.glob("/missing/*").unwrap().is_empty());
+        assert_eq!(fs.glob("/").unwrap(), vec!["/"]);
+        assert_eq!(fs.glob("/e?c").unwrap(), vec!["/etc"]);
+        assert_eq!(fs.glob(""), Err(FsError::InvalidPath));
+        fs.chmod("/home/bob", 0o700).unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(fs.glob("/home/*/*").unwrap(), vec!["/home/alice/docs"]);
+        assert!(is_glob_pattern("*.conf"));
+        assert!(!is_glob_pattern("/etc/hosts"));
+    }
+
+    #[test]
+    fn archives_round_trip_the_whole_tree() {
+        let mut fs = FileSystem::new();
+        fs.mkdir_p("/etc/net").unwrap();
+        fs.write_file("/etc/net/conf", b"dhcp").unwrap();
+        fs.symlink("/etc/net/conf", "/net.conf").unwrap();
+        fs.chown("/etc/net", "guest").unwrap();
+        let archive = fs.export_archive().unwrap();
+        assert!(archive.starts_with(SNAPSHOT_MAGIC));
+
+        let mut seeded = FileSystem::new();
+        seeded.write_file("/old", b"gone").unwrap();
+        let stats = seeded.import_archive(&archive).unwrap();
+        assert_eq!((stats.files, stats.dirs, stats.bytes), (1, 3, 4));
+        assert_eq!(seeded.list_dir("/").unwrap(), vec!["etc", "net.conf"]);
+        assert_eq!(seeded.read_file("/net.conf").unwrap(), b"dhcp");
+        assert_eq!(seeded.metadata("/etc/net").unwrap().owner, "guest");
+        assert_eq!(seeded.export_archive().unwrap(), archive);
+
+        assert_eq!(
+            seeded.import_archive(&archive[..archive.len() - 1]),
+            Err(FsError::CorruptSnapshot)
+        );
+        seeded.set_quota(Some(3));
+        assert_eq!(seeded.import_archive(&archive), Err(FsError::NoSpace));
+        seeded.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(seeded.export_archive(), Err(FsError::PermissionDenied));
+        assert_eq!(
+            seeded.import_archive(&archive),
+            Err(FsError::PermissionDenied)
+        );
+        seeded.set_identity(Some(Identity::new("root", true)));
+        assert!(seeded.export_archive().is_ok());
+    }
+
+    #[test]
+    fn snapshots_keep_timestamps() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        fs.set_time(7);
+        fs.write_file("/home/a", b"x").unwrap();
+        let bytes = fs.snapshot("/home").unwrap().to_bytes();
+        let snapshot = Snapshot::from_bytes(&bytes).unwrap();
+        fs.set_time(30);
+        fs.restore("/backup", &snapshot).unwrap();
+        let meta = fs.metadata("/backup/a").unwrap();
+        assert_eq!((meta.created, meta.modified), (7, 7));
+        assert_eq!(fs.metadata("/").unwrap().created, 0);
+    }
+
+    #[test]
+    fn new_entries_are_owned_by_identity() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/home").unwrap();
+        fs.chmod("/home", 0o777).unwrap();
+        assert_eq!(
+            fs.metadata("/home").unwrap(),
+            Metadata {
+                kind: FileKind::Dir,
+                owner: "root".to_string(),
+                group: "root".to_string(),
+                mode: 0o777,
+                created: 0,
+                modified: 0,
+            }
+        );
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(fs.identity().unwrap().user, "guest");
+        fs.write_file("/home/notes", b"hi").unwrap();
+        fs.symlink("/home/notes", "/home/link").unwrap();
+        let meta = fs.metadata("/home/notes").unwrap();
+        assert_eq!(meta.owner, "guest");
+        assert_eq!(meta.group, "guest");
+        assert_eq!(meta.mode, DEFAULT_FILE_MODE);
+        assert_eq!(meta.kind, FileKind::File);
+        assert_eq!(fs.metadata("/home/link").unwrap().kind, FileKind::Symlink);
+        assert_eq!(fs.metadata("/").unwrap().owner, "root");
+        assert_eq!(fs.metadata("/missing"), Err(FsError::NotFound));
+    }
+
+    #[test]
+    fn non_admin_cannot_modify_foreign_entries() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/hostname", b"ruzzle").unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(fs.read_file("/etc/hostname").unwrap(), b"ruzzle".to_vec());
+        assert_eq!(
+            fs.write_file("/etc/hostname", b"x"),
+            Err(FsError::PermissionDenied)
+        );
+        assert_eq!(fs.write_file("/etc/new", b"x"), Err(FsError::PermissionDenied));
+        assert_eq!(fs.remove("/etc/hostname"), Err(FsError::PermissionDenied));
+        assert_eq!(fs.mkdir("/tmp"), Err(FsError::PermissionDenied));
+        assert_eq!(fs.symlink("/etc", "/link"), Err(FsError::PermissionDenied));
+        assert_eq!(fs.chmod("/etc", 0o777), Err(FsError::PermissionDenied));
+        assert_eq!(fs.chown("/etc", "guest"), Err(FsError::PermissionDenied));
+
+        fs.set_identity(Some(Identity::new("root", true)));
+        fs.chown("/etc/hostname", "guest").unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        fs.write_file("/etc/hostname", b"mine").unwrap();
+        fs.chmod("/etc/hostname", 0o600).unwrap();
+        assert_eq!(fs.metadata("/etc/hostname").unwrap().mode, 0o600);
+    }
+
+    #[test]
+    fn chmod_and_chown_validate_input() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/file", b"x").unwrap();
+        fs.symlink("/file", "/link").unwrap();
+        fs.symlink("/", "/top").unwrap();
+        assert_eq!(fs.chmod("/file", 0o1000), Err(FsError::InvalidMode));
+        assert_eq!(fs.chmod("/", 0o700), Err(FsError::InvalidPath));
+        assert_eq!(fs.chmod("/top", 0o700), Err(FsError::InvalidPath));
+        assert_eq!(fs.chmod("bad//path", 0o700), Err(FsError::InvalidPath));
+        assert_eq!(fs.chmod("/missing", 0o700), Err(FsError::NotFound));
+        assert_eq!(fs.chown("/file", " "), Err(FsError::InvalidPath));
+        fs.chmod("/link", 0o600).unwrap();
+        assert_eq!(fs.metadata("/file").unwrap().mode, 0o600);
+        fs.chown("/link", "guest").unwrap();
+        assert_eq!(fs.metadata("/file").unwrap().owner, "guest");
+    }
+
+    #[test]
+    fn permits_checks_owner_and_other_bits() {
+        let guest = Identity::new("guest", false);
+        let admin = Identity::new("root", true);
+        assert!(permits(None, "root", "root", 0o000, PERM_WRITE));
+        assert!(permits(Some(&admin), "guest", "guest", 0o000, PERM_WRITE));
+        assert!(permits(
+            Some(&guest),
+            "guest",
+            "guest",
+            0o600,
+            PERM_READ | PERM_WRITE
+        ));
+        assert!(!permits(Some(&guest), "guest", "guest", 0o400, PERM_WRITE));
+        assert!(permits(
+            Some(&guest),
+            "root",
+            "root",
+            0o755,
+            PERM_READ | PERM_EXEC
+        ));
+        assert!(!permits(Some(&guest), "root", "root", 0o755, PERM_WRITE));
+    }
+
+    #[test]
+    fn permits_uses_group_bits_for_members() {
+        let guest = Identity::new("guest", false).with_groups(&["staff"]);
+        assert!(guest.in_group("guest"));
+        assert!(guest.in_group("staff"));
+        assert!(!guest.in_group("wheel"));
+        assert!(permits(Some(&guest), "root", "staff", 0o660, PERM_WRITE));
+        assert!(!permits(Some(&guest), "root", "wheel", 0o660, PERM_READ));
+        assert!(!permits(Some(&guest), "root", "staff", 0o707, PERM_READ));
+    }
+
+    #[test]
+    fn reads_require_read_permission() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/shadow", b"secret").unwrap();
+        fs.chmod("/etc/shadow", 0o640).unwrap();
+        fs.mkdir("/etc/private").unwrap();
+        fs.chmod("/etc/private", 0o750).unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(fs.read_file("/etc/shadow"), Err(FsError::PermissionDenied));
+        assert_eq!(fs.list_dir("/etc/private"), Err(FsError::PermissionDenied));
+        assert_eq!(fs.list_dir("/etc").unwrap().len(), 2);
+        assert_eq!(fs.list_dir("/etc/shadow"), Err(FsError::NotDir));
+
+        fs.set_identity(Some(Identity::new("guest", false).with_groups(&["root"])));
+        assert_eq!(fs.read_file("/etc/shadow").unwrap(), b"secret".to_vec());
+        assert_eq!(fs.list_dir("/etc/private").unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn access_reports_permissions_for_any_identity() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/etc").unwrap();
+        fs.write_file("/etc/hostname", b"ruzzle").unwrap();
+        fs.symlink("/etc/hostname", "/host").unwrap();
+        let guest = Identity::new("guest", false);
+        assert_eq!(fs.access("/etc/hostname", &guest, PERM_READ), Ok(()));
+        assert_eq!(
+            fs.access("/host", &guest, PERM_WRITE),
+            Err(FsError::PermissionDenied)
+        );
+        assert_eq!(fs.access("/", &guest, PERM_READ | PERM_EXEC), Ok(()));
+        assert_eq!(
+            fs.access("/", &guest, PERM_WRITE),
+            Err(FsError::PermissionDenied)
+        );
+        assert_eq!(
+            fs.access("/missing", &guest, PERM_READ),
+            Err(FsError::NotFound)
+        );
+        assert_eq!(
+            fs.access("/etc/hostname", &Identity::new("root", true), PERM_WRITE),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn chgrp_requires_membership_or_admin() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/srv").unwrap();
+        fs.chmod("/srv", 0o777).unwrap();
+        assert_eq!(fs.chgrp("/srv", " "), Err(FsError::InvalidPath));
+        fs.set_identity(Some(Identity::new("guest", false).with_groups(&["staff"])));
+        fs.write_file("/srv/notes", b"x").unwrap();
+        assert_eq!(
+            fs.chgrp("/srv/notes", "wheel"),
+            Err(FsError::PermissionDenied)
+        );
+        fs.chgrp("/srv/notes", "staff").unwrap();
+        assert_eq!(fs.metadata("/srv/notes").unwrap().group, "staff");
+        assert_eq!(fs.chgrp("/srv", "staff"), Err(FsError::PermissionDenied));
+        fs.set_identity(Some(Identity::new("root", true)));
+        fs.chgrp("/srv/notes", "wheel").unwrap();
+        assert_eq!(fs.metadata("/srv/notes").unwrap().group, "wheel");
+    }
+
+    #[test]
+    fn parse_and_format_modes() {
+        assert_eq!(parse_mode("755"), Ok(0o755));
+        assert_eq!(parse_mode("0644"), Ok(0o644));
+        assert_eq!(parse_mode(""), Err(FsError::InvalidMode));
+        assert_eq!(parse_mode("12345"), Err(FsError::InvalidMode));
+        assert_eq!(parse_mode("rwx"), Err(FsError::InvalidMode));
+        assert_eq!(parse_mode("1777"), Err(FsError::InvalidMode));
+        assert_eq!(format_mode(FileKind::Dir, 0o755), "drwxr-xr-x");
+        assert_eq!(format_mode(FileKind::File, 0o640), "-rw-r-----");
+        assert_eq!(format_mode(FileKind::Symlink, 0o777), "lrwxrwxrwx");
+        assert_eq!(FileKind::File.name(), "file");
+        assert_eq!(FileKind::Dir.name(), "directory");
+        assert_eq!(FileKind::Symlink.name(), "symlink");
+    }
+
+    #[test]
+    fn quota_limits_total_file_bytes() {
+        let mut fs = FileSystem::new();
+        fs.set_quota(Some(8));
+        assert_eq!(fs.quota(), Some(8));
+        fs.write_file("/a", b"12345").unwrap();
+        assert_eq!(fs.write_file("/b", b"1234"), Err(FsError::NoSpace));
+        fs.write_file("/a", b"12345678").unwrap();
+        fs.mkdir("/dir").unwrap();
+        assert_eq!(fs.write_file("/dir", b""), Err(FsError::IsDir));
+        fs.set_quota(None);
+        fs.write_file("/b", b"1234").unwrap();
+    }
+
+    #[test]
+    fn snapshot_round_trips_through_bytes() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/srv").unwrap();
+        fs.mkdir("/srv/www").unwrap();
+        fs.write_file("/srv/www/index.html", b"hello").unwrap();
+        fs.symlink("www/index.html", "/srv/home").unwrap();
+        fs.chmod("/srv/www", 0o700).unwrap();
+        let snapshot = fs.snapshot("/srv").unwrap();
+        assert_eq!(
+            snapshot.stats(),
+            FsStats {
+                files: 1,
+                dirs: 2,
+                bytes: 5,
+            }
+        );
+        let bytes = snapshot.to_bytes();
+        assert_eq!(&bytes[..4], SNAPSHOT_MAGIC);
+        assert_eq!(bytes[4], SNAPSHOT_VERSION);
+        assert_eq!(Snapshot::from_bytes(&bytes), Ok(snapshot));
+    }
+
+    #[test]
+    fn snapshot_decodes_older_formats_with_defaults() {
+        // Version 1: no version byte, groups or timestamps.
+        let mut v1 = SNAPSHOT_MAGIC.to_vec();
+        v1.push(1);
+        encode_bytes(b"alice", &mut v1);
+        v1.extend_from_slice(&0o750u16.to_le_bytes());
+        encode_bytes(b"", &mut v1);
+        v1.extend_from_slice(&1u32.to_le_bytes());
+        encode_bytes(b"f", &mut v1);
+        v1.push(0);
+        encode_bytes(b"root", &mut v1);
+        v1.extend_from_slice(&0o644u16.to_le_bytes());
+        encode_bytes(b"hi", &mut v1);
+        let snapshot = Snapshot::from_bytes(&v1).unwrap();
+        assert_eq!(snapshot.root.owner, "alice");
+        assert_eq!(snapshot.root.group, "alice");
+        assert_eq!(snapshot.root.mode, 0o750);
+        let NodeKind::Dir(children) = &snapshot.root.kind else {
+            panic!("root is a directory");
+        };
+        let file = &children["f"];
+        assert_eq!(file.kind, NodeKind::File(b"hi".to_vec()));
+        assert_eq!(file.group, "root");
+        assert_eq!((file.created, file.modified), (0, 0));
+
+        // Version 2: groups, no timestamps.
+        let mut v2 = SNAPSHOT_MAGIC.to_vec();
+        v2.push(2);
+        v2.push(1);
+        encode_bytes(b"alice", &mut v2);
+        encode_bytes(b"staff", &mut v2);
+        v2.extend_from_slice(&0o770u16.to_le_bytes());
+        encode_bytes(b"", &mut v2);
+        v2.extend_from_slice(&0u32.to_le_bytes());
+        let snapshot = Snapshot::from_bytes(&v2).unwrap();
+        assert_eq!(snapshot.root.group, "staff");
+        assert_eq!(snapshot.root.created, 0);
+
+        let mut future = v2.clone();
+        future[4] = SNAPSHOT_VERSION + 1;
+        assert_eq!(Snapshot::from_bytes(&future), Err(FsError::CorruptSnapshot));
+    }
+
+    #[test]
+    fn restore_replaces_or_creates_tree() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/app").unwrap();
+        fs.write_file("/app/state", b"v1").unwrap();
+        let snapshot = fs.snapshot("/app").unwrap();
+        fs.write_file("/app/state", b"v2").unwrap();
+        fs.write_file("/app/extra", b"x").unwrap();
+        fs.restore("/app", &snapshot).unwrap();
+        assert_eq!(fs.read_file("/app/state").unwrap(), b"v1".to_vec());
+        assert_eq!(fs.read_file("/app/extra"), Err(FsError::NotFound));
+        fs.restore("/copy", &snapshot).unwrap();
+        assert_eq!(fs.list_dir("/copy").unwrap(), vec!["state".to_string()]);
+    }
+
+    #[test]
+    fn snapshot_of_root_and_invalid_targets() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/file", b"abc").unwrap();
+        let root = fs.snapshot("/").unwrap();
+        assert_eq!(root.stats().files, 1);
+        assert_eq!(fs.snapshot("/file"), Err(FsError::NotDir));
+        assert_eq!(fs.snapshot("/missing"), Err(FsError::NotFound));
+        assert_eq!(fs.restore("/", &root), Err(FsError::InvalidPath));
+        assert_eq!(fs.restore("/file", &root), Err(FsError::NotDir));
+        assert_eq!(fs.restore("/file/x", &root), Err(FsError::NotDir));
+        assert_eq!(fs.restore("bad//path", &root), Err(FsError::InvalidPath));
+    }
+
+    #[test]
+    fn snapshot_and_restore_enforce_permissions_and_quota() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/secret").unwrap();
+        fs.write_file("/secret/key", b"1234").unwrap();
+        fs.chmod("/secret", 0o700).unwrap();
+        let snapshot = fs.snapshot("/secret").unwrap();
+        fs.set_identity(Some(Identity::new("guest", false)));
+        assert_eq!(fs.snapshot("/secret"), Err(FsError::PermissionDenied));
+        assert_eq!(
+            fs.restore("/other", &snapshot),
+            Err(FsError::PermissionDenied)
+        );
+        fs.set_identity(None);
+        fs.set_quota(Some(6));
+        assert_eq!(fs.restore("/other", &snapshot), Err(FsError::NoSpace));
+        fs.restore("/secret", &snapshot).unwrap();
+    }
+
+    #[test]
+    fn snapshot_decode_rejects_corruption() {
+        let mut fs = FileSystem::new();
+        fs.mkdir("/d").unwrap();
+        fs.write_file("/d/f", b"x").unwrap();
+        let bytes = fs.snapshot("/d").unwrap().to_bytes();
+        assert_eq!(Snapshot::from_bytes(b"RZ"), Err(FsError::CorruptSnapshot));
+        assert_eq!(
+            Snapshot::from_bytes(b"XXXX\x01"),
+            Err(FsError::CorruptSnapshot)
+        );
+        assert_eq!(
+            Snapshot::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(FsError::CorruptSnapshot)
+        );
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Snapshot::from_bytes(&trailing),
+            Err(FsError::CorruptSnapshot)
+        );
+    }
+
+    fn encoded(tag: u8, owner: &[u8], mode: u16, payload: &[u8]) -> Vec<u8> {
+        let mut out = SNAPSHOT_MAGIC.to_vec();
+        out.push(SNAPSHOT_VERSION);
+        out.push(tag);
+        encode_bytes(owner, &mut out);
+        encode_bytes(b"root", &mut out);
+        out.extend_from_slice(&mode.to_le_bytes());
+        out.extend_from_slice(&[0; 16]);
+        encode_bytes(payload, &mut out);
+        out
+    }
+
+    #[test]
+    fn snapshot_decode_rejects_bad_fields() {
+        let file_root = encoded(0, b"root", 0o644, b"data");
+        assert_eq!(
+            Snapshot::from_bytes(&file_root),
+            Err(FsError::CorruptSnapshot)
+        );
+        let link_root = encoded(2, b"root", 0o755, b"/x");
+        assert_eq!(
+            Snapshot::from_bytes(&link_root),
+            Err(FsError::CorruptSnapshot)
+        );
+        assert_eq!(
+            Snapshot::from_bytes(&encoded(9, b"root", 0o755, b"")),
+            Err(FsError::CorruptSnapshot)
+        );
+        assert_eq!(
+            Snapshot::from_bytes(&encoded(1, b"root", 0o7777, b"")),
+            Err(FsError::CorruptSnapshot)
+        );
+        assert_eq!(
+            Snapshot::from_bytes(&encoded(1, &[0xff], 0o755, b"")),
+            Err(FsError::CorruptSnapshot)
+        );
+        for name in [&b""[..], b"a/b", b".", b".."] {
+            let mut bytes = encoded(1, b"root", 0o755, b"");
+            bytes.extend_from_slice(&1u32.to_le_bytes());
+            encode_bytes(name, &mut bytes);
+            assert_eq!(Snapshot::from_bytes(&bytes), Err(FsError::CorruptSnapshot));
+        }
+    }
+
+    #[test]
+    fn snapshot_decode_limits_depth() {
+        let mut bytes = encoded(1, b"r", 0o755, b"");
+        for _ in 0..=SNAPSHOT_MAX_DEPTH {
+            bytes.extend_from_slice(&1u32.to_le_bytes());
+            encode_bytes(b"d", &mut bytes);
+            bytes.push(1);
+            encode_bytes(b"r", &mut bytes);
+            encode_bytes(b"r", &mut bytes);
+            bytes.extend_from_slice(&0o755u16.to_le_bytes());
+            bytes.extend_from_slice(&[0; 16]);
+            encode_bytes(b"", &mut bytes);
+        }
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        assert_eq!(Snapshot::from_bytes(&bytes), Err(FsError::CorruptSnapshot));
+    }
 }
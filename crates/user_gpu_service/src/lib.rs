@@ -6,12 +6,23 @@ use alloc::format;
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
+use user_file_manager::Fs;
+use user_fs_service::FsError;
+
+/// Magic bytes at the start of a saved tensor.
+pub const TENSOR_MAGIC: &[u8; 4] = b"RZT1";
+
+/// Size of the saved tensor header (magic, rows, cols).
+const HEADER_LEN: usize = 12;
 
 /// GPU computation errors.
 #[derive(Debug, Clone, PartialEq)]
 pub enum GpuError {
     ShapeMismatch,
     EmptyTensor,
+    InvalidFormat,
+    ChecksumMismatch,
+    Fs(FsError),
 }
 
 /// Simple tensor representation.
@@ -58,6 +69,85 @@ impl Tensor {
         }
         out
     }
+
+    /// Encodes the tensor as a header, little-endian values and a checksum.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.data.len() * 4 + 4);
+        bytes.extend_from_slice(TENSOR_MAGIC);
+        bytes.extend_from_slice(&(self.rows as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.cols as u32).to_le_bytes());
+        for value in &self.data {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let sum = checksum(&bytes);
+        bytes.extend_from_slice(&sum.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a tensor produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, GpuError> {
+        if bytes.len() < HEADER_LEN + 4 || &bytes[..4] != TENSOR_MAGIC {
+            return Err(GpuError::InvalidFormat);
+        }
+        let (body, tail) = bytes.split_at(bytes.len() - 4);
+        if checksum(body) != read_u32(tail) {
+            return Err(GpuError::ChecksumMismatch);
+        }
+        let rows = read_u32(&body[4..8]) as usize;
+        let cols = read_u32(&body[8..12]) as usize;
+        let values = &body[HEADER_LEN..];
+        if values.len() % 4 != 0 || values.len() / 4 != rows.saturating_mul(cols) {
+            return Err(GpuError::InvalidFormat);
+        }
+        let data = values
+            .chunks_exact(4)
+            .map(|chunk| f32::from_bits(read_u32(chunk)))
+            .collect();
+        Tensor::new(rows, cols, data)
+    }
+
+    /// Writes the tensor to a file.
+    pub fn save<F: Fs>(&self, fs: &mut F, path: &str) -> Result<(), GpuError> {
+        fs.write_file(path, &self.to_bytes()).map_err(GpuError::Fs)
+    }
+
+    /// Reads a tensor previously written with `save`.
+    pub fn load<F: Fs>(fs: &F, path: &str) -> Result<Self, GpuError> {
+        let bytes = fs.read_file(path).map_err(GpuError::Fs)?;
+        Tensor::from_bytes(&bytes)
+    }
+}
+
+/// Parses a tensor from `rows cols v1 v2 ...` arguments.
+pub fn parse_tensor(args: &[&str]) -> Result<Tensor, GpuError> {
+    if args.len() < 2 {
+        return Err(GpuError::InvalidFormat);
+    }
+    let rows = args[0]
+        .parse::<usize>()
+        .map_err(|_| GpuError::InvalidFormat)?;
+    let cols = args[1]
+        .parse::<usize>()
+        .map_err(|_| GpuError::InvalidFormat)?;
+    let mut data = Vec::with_capacity(args.len() - 2);
+    for value in &args[2..] {
+        data.push(value.parse::<f32>().map_err(|_| GpuError::InvalidFormat)?);
+    }
+    Tensor::new(rows, cols, data)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// FNV-1a checksum over the encoded header and values.
+fn checksum(bytes: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in bytes {
+        hash ^= u32::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
 }
 
 /// Minimal GPU device interface.
@@ -200,4 +290,97 @@ mod tests {
         };
         assert_eq!(tensor.format(), "");
     }
+
+    #[test]
+    fn bytes_round_trip() {
+        let tensor = Tensor::new(2, 2, vec![1.0, -2.5, 3.25, 0.0]).unwrap();
+        let bytes = tensor.to_bytes();
+        assert_eq!(&bytes[..4], TENSOR_MAGIC);
+        assert_eq!(bytes.len(), HEADER_LEN + 16 + 4);
+        assert_eq!(Tensor::from_bytes(&bytes), Ok(tensor));
+    }
+
+    #[test]
+    fn from_bytes_rejects_bad_magic_and_short_input() {
+        assert_eq!(Tensor::from_bytes(b"RZT1"), Err(GpuError::InvalidFormat));
+        let mut bytes = Tensor::new(1, 1, vec![1.0]).unwrap().to_bytes();
+        bytes[0] = b'X';
+        assert_eq!(Tensor::from_bytes(&bytes), Err(GpuError::InvalidFormat));
+    }
+
+    #[test]
+    fn from_bytes_rejects_corrupted_data() {
+        let mut bytes = Tensor::new(1, 2, vec![1.0, 2.0]).unwrap().to_bytes();
+        bytes[HEADER_LEN] ^= 0xff;
+        assert_eq!(Tensor::from_bytes(&bytes), Err(GpuError::ChecksumMismatch));
+    }
+
+    fn sealed(body: &[u8]) -> Vec<u8> {
+        let mut bytes = body.to_vec();
+        bytes.extend_from_slice(&checksum(body).to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn from_bytes_rejects_shape_disagreeing_with_payload() {
+        let mut body = TENSOR_MAGIC.to_vec();
+        body.extend_from_slice(&2u32.to_le_bytes());
+        body.extend_from_slice(&2u32.to_le_bytes());
+        body.extend_from_slice(&1.0f32.to_le_bytes());
+        assert_eq!(
+            Tensor::from_bytes(&sealed(&body)),
+            Err(GpuError::InvalidFormat)
+        );
+        body.push(0);
+        assert_eq!(
+            Tensor::from_bytes(&sealed(&body)),
+            Err(GpuError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn from_bytes_rejects_empty_shape() {
+        let mut body = TENSOR_MAGIC.to_vec();
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+        assert_eq!(
+            Tensor::from_bytes(&sealed(&body)),
+            Err(GpuError::EmptyTensor)
+        );
+    }
+
+    #[test]
+    fn save_and_load_through_filesystem() {
+        let mut fs = user_fs_service::FileSystem::new();
+        let tensor = Tensor::new(1, 3, vec![1.0, 2.0, 3.0]).unwrap();
+        tensor.save(&mut fs, "/t.bin").unwrap();
+        assert_eq!(Tensor::load(&fs, "/t.bin"), Ok(tensor));
+    }
+
+    #[test]
+    fn save_and_load_surface_fs_errors() {
+        let mut fs = user_fs_service::FileSystem::new();
+        let tensor = Tensor::new(1, 1, vec![1.0]).unwrap();
+        assert_eq!(
+            tensor.save(&mut fs, "/missing/t.bin"),
+            Err(GpuError::Fs(FsError::NotFound))
+        );
+        assert_eq!(
+            Tensor::load(&fs, "/nope"),
+            Err(GpuError::Fs(FsError::NotFound))
+        );
+    }
+
+    #[test]
+    fn parse_tensor_reads_shape_and_values() {
+        assert_eq!(
+            parse_tensor(&["1", "2", "1.5", "2"]),
+            Ok(Tensor::new(1, 2, vec![1.5, 2.0]).unwrap())
+        );
+        assert_eq!(parse_tensor(&["1"]), Err(GpuError::InvalidFormat));
+        assert_eq!(parse_tensor(&["x", "1", "1"]), Err(GpuError::InvalidFormat));
+        assert_eq!(parse_tensor(&["1", "x", "1"]), Err(GpuError::InvalidFormat));
+        assert_eq!(parse_tensor(&["1", "1", "x"]), Err(GpuError::InvalidFormat));
+        assert_eq!(parse_tensor(&["1", "2", "1"]), Err(GpuError::ShapeMismatch));
+    }
 }
@@ -2,13 +2,23 @@
 
 extern crate alloc;
 
-use alloc::collections::BTreeMap;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
 use hal::Errno;
+use ruzzle_protocol::auth::{read_credential, Credential};
 use ruzzle_protocol::registry::{
-    decode_request, encode_response, RegistryRequest, RegistryResponse, RegistryStatus, ServiceEntry,
+    decode_request, encode_response, RegistryRequest, RegistryResponse, RegistryStatus,
+    ResolvePolicy, ServiceChange, ServiceEntry, ServiceNotification,
+};
+use user_puzzle_board::SwapLifecycle;
+
+mod loader;
+
+pub use loader::{
+    load_module_configs, parse_module_config, LoadError, LoadReport, MODULE_CONFIG_DIR,
 };
 
 /// Describes a user module and its dependencies.
@@ -80,10 +90,35 @@ pub fn is_valid_service_name(name: &str) -> bool {
     saw_segment
 }
 
-/// Registry mapping service names to module names.
+/// Returns true when `service` is `prefix` itself or nested below it.
+fn prefix_matches(prefix: &str, service: &str) -> bool {
+    service
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// One module's registration of a service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceInstance {
+    pub id: u32,
+    pub module: String,
+    /// Preferred by `ResolvePolicy::Priority` when higher.
+    pub priority: u32,
+}
+
+/// Registry mapping service names to the modules providing them.
 #[derive(Debug, Default)]
 pub struct ServiceRegistry {
-    services: BTreeMap<String, String>,
+    /// Instances of each service in registration order.
+    services: BTreeMap<String, Vec<ServiceInstance>>,
+    policies: BTreeMap<String, ResolvePolicy>,
+    /// Index of the next round-robin pick per service.
+    cursors: BTreeMap<String, usize>,
+    next_instance: u32,
+    /// Watched name prefixes by watch id.
+    watches: BTreeMap<u32, String>,
+    next_watch: u32,
+    notifications: Vec<ServiceNotification>,
 }
 
 impl ServiceRegistry {
@@ -91,6 +126,68 @@ impl ServiceRegistry {
     pub fn new() -> Self {
         Self {
             services: BTreeMap::new(),
+            policies: BTreeMap::new(),
+            cursors: BTreeMap::new(),
+            next_instance: 0,
+            watches: BTreeMap::new(),
+            next_watch: 0,
+            notifications: Vec::new(),
+        }
+    }
+
+    /// Subscribes to services named `prefix` or nested below it.
+    ///
+    /// Services already registered under the prefix are reported as appeared
+    /// right away, so a watcher never misses one registered just before.
+    pub fn watch(&mut self, prefix: String) -> Result<u32, Errno> {
+        if prefix != "ruzzle" && !is_valid_service_name(&prefix) {
+            return Err(Errno::InvalidArg);
+        }
+        self.next_watch += 1;
+        let watch = self.next_watch;
+        for (service, instances) in &self.services {
+            if prefix_matches(&prefix, service) {
+                for instance in instances {
+                    self.notifications.push(ServiceNotification {
+                        watch,
+                        change: ServiceChange::Appeared,
+                        entry: ServiceEntry {
+                            service: service.clone(),
+                            module: instance.module.clone(),
+                        },
+                    });
+                }
+            }
+        }
+        self.watches.insert(watch, prefix);
+        Ok(watch)
+    }
+
+    /// Cancels a subscription and drops its undelivered notifications.
+    pub fn unwatch(&mut self, watch: u32) -> Result<(), Errno> {
+        self.watches.remove(&watch).ok_or(Errno::NotFound)?;
+        self.notifications
+            .retain(|notification| notification.watch != watch);
+        Ok(())
+    }
+
+    /// Returns pending notifications, oldest first, for delivery to watchers.
+    pub fn take_notifications(&mut self) -> Vec<ServiceNotification> {
+        core::mem::take(&mut self.notifications)
+    }
+
+    fn notify(&mut self, service: &str, module: &str, change: ServiceChange) {
+        for (watch, prefix) in &self.watches {
+            if prefix_matches(prefix, service) {
+                self.notifications.push(ServiceNotification {
+                    watch: *watch,
+                    change,
+                    entry: ServiceEntry {
+                        service: service.to_string(),
+                        module: module.to_string(),
+                    },
+                });
+            }
         }
     }
 
@@ -100,64 +197,151 @@ impl ServiceRegistry {
     }
 
     /// Registers a service name for a module.
+    ///
+    /// Fails if the service already has a provider; use `register_instance`
+    /// to add a redundant one.
     pub fn register(&mut self, service: String, module: String) -> Result<(), Errno> {
+        if self.services.contains_key(&service) {
+            return Err(Errno::InvalidArg);
+        }
+        self.register_instance(service, module, 0).map(|_| ())
+    }
+
+    /// Adds a module as one more instance of a service and returns its id.
+    ///
+    /// A module may provide each service only once.
+    pub fn register_instance(
+        &mut self,
+        service: String,
+        module: String,
+        priority: u32,
+    ) -> Result<u32, Errno> {
         if service.is_empty() || module.is_empty() {
             return Err(Errno::InvalidArg);
         }
         if !is_valid_service_name(&service) {
             return Err(Errno::InvalidArg);
         }
-        if self.services.contains_key(&service) {
+        if self
+            .instances(&service)
+            .iter()
+            .any(|instance| instance.module == module)
+        {
             return Err(Errno::InvalidArg);
         }
-        self.services.insert(service, module);
+        self.next_instance += 1;
+        let id = self.next_instance;
+        self.notify(&service, &module, ServiceChange::Appeared);
+        self.services
+            .entry(service)
+            .or_default()
+            .push(ServiceInstance {
+                id,
+                module,
+                priority,
+            });
+        Ok(id)
+    }
+
+    /// Returns the instances of a service in registration order.
+    pub fn instances(&self, service: &str) -> &[ServiceInstance] {
+        self.services.get(service).map_or(&[], Vec::as_slice)
+    }
+
+    /// Sets how lookups of a service pick among its instances.
+    ///
+    /// The policy outlives the instances, so a restarted provider keeps it.
+    pub fn set_policy(&mut self, service: &str, policy: ResolvePolicy) -> Result<(), Errno> {
+        if !is_valid_service_name(service) {
+            return Err(Errno::InvalidArg);
+        }
+        self.policies.insert(service.to_string(), policy);
+        self.cursors.remove(service);
         Ok(())
     }
 
-    /// Unregisters a service name.
+    /// Returns the policy used for a service.
+    pub fn policy(&self, service: &str) -> ResolvePolicy {
+        self.policies.get(service).copied().unwrap_or_default()
+    }
+
+    /// Unregisters every instance of a service name.
     pub fn unregister(&mut self, service: &str) -> Result<(), Errno> {
-        if self.services.remove(service).is_some() {
-            Ok(())
-        } else {
-            Err(Errno::NotFound)
+        let instances = self.services.remove(service).ok_or(Errno::NotFound)?;
+        self.cursors.remove(service);
+        for instance in instances {
+            self.notify(service, &instance.module, ServiceChange::Disappeared);
         }
+        Ok(())
     }
 
-    /// Removes all services owned by a module and returns the count removed.
+    /// Removes all instances owned by a module and returns the count removed.
     pub fn unregister_module(&mut self, module: &str) -> usize {
-        let keys: Vec<String> = self
-            .services
-            .iter()
-            .filter_map(|(service, owner)| {
-                if owner == module {
-                    Some(service.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
-        let count = keys.len();
-        for key in keys {
-            self.services.remove(&key);
+        let mut removed = Vec::new();
+        for (service, instances) in &mut self.services {
+            let before = instances.len();
+            instances.retain(|instance| instance.module != module);
+            if instances.len() != before {
+                removed.push(service.clone());
+            }
+        }
+        for service in &removed {
+            if self.instances(service).is_empty() {
+                self.services.remove(service);
+                self.cursors.remove(service);
+            }
+            self.notify(service, module, ServiceChange::Disappeared);
         }
-        count
+        removed.len()
     }
 
-    /// Resolves a service name to its owning module.
+    /// Resolves a service name to the module its policy currently picks.
+    ///
+    /// Does not advance round-robin; lookups that hand out a provider use
+    /// `select` instead.
     pub fn resolve(&self, service: &str) -> Result<&str, Errno> {
-        self.services
-            .get(service)
-            .map(|name| name.as_str())
-            .ok_or(Errno::NotFound)
+        let instances = self.instances(service);
+        if instances.is_empty() {
+            return Err(Errno::NotFound);
+        }
+        let index = match self.policy(service) {
+            ResolvePolicy::First => 0,
+            ResolvePolicy::RoundRobin => {
+                self.cursors.get(service).copied().unwrap_or(0) % instances.len()
+            }
+            ResolvePolicy::Priority => {
+                let mut best = 0;
+                for (index, instance) in instances.iter().enumerate() {
+                    if instance.priority > instances[best].priority {
+                        best = index;
+                    }
+                }
+                best
+            }
+        };
+        Ok(&instances[index].module)
+    }
+
+    /// Resolves a service like `resolve` and advances round-robin.
+    pub fn select(&mut self, service: &str) -> Result<String, Errno> {
+        let module = self.resolve(service)?.to_string();
+        if self.policy(service) == ResolvePolicy::RoundRobin {
+            let len = self.instances(service).len();
+            let cursor = self.cursors.entry(service.to_string()).or_insert(0);
+            *cursor = (*cursor % len + 1) % len;
+        }
+        Ok(module)
     }
 
-    /// Returns all registered services sorted by name.
+    /// Returns all registered instances sorted by service name.
     pub fn list(&self) -> Vec<ServiceEntry> {
         self.services
             .iter()
-            .map(|(service, module)| ServiceEntry {
-                service: service.clone(),
-                module: module.clone(),
+            .flat_map(|(service, instances)| {
+                instances.iter().map(|instance| ServiceEntry {
+                    service: service.clone(),
+                    module: instance.module.clone(),
+                })
             })
             .collect()
     }
@@ -169,6 +353,183 @@ pub enum ModuleState {
     Stopped,
     Running,
     Failed,
+    Waiting,
+    /// Still serving while a replacement takes over its slot.
+    Draining,
+    /// Running but overdue on heartbeats.
+    Degraded,
+    /// Missed its watchdog deadline and awaits its restart policy.
+    Hung,
+}
+
+impl ModuleState {
+    /// Returns true for states that still own the module's services.
+    pub fn is_live(self) -> bool {
+        matches!(self, ModuleState::Running | ModuleState::Degraded)
+    }
+
+    /// Returns the lowercase label shown by `lsmod`.
+    pub fn label(self) -> &'static str {
+        match self {
+            ModuleState::Stopped => "stopped",
+            ModuleState::Running => "running",
+            ModuleState::Failed => "failed",
+            ModuleState::Waiting => "waiting",
+            ModuleState::Draining => "draining",
+            ModuleState::Degraded => "degraded",
+            ModuleState::Hung => "hung",
+        }
+    }
+}
+
+/// When init should restart a module that exits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    /// Parses `never`, `on-failure` or `always`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "never" => Some(RestartPolicy::Never),
+            "on-failure" => Some(RestartPolicy::OnFailure),
+            "always" => Some(RestartPolicy::Always),
+            _ => None,
+        }
+    }
+}
+
+/// When init starts a module.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Activation {
+    /// Started explicitly, e.g. from the boot start plan.
+    #[default]
+    Eager,
+    /// Started by the first lookup of a service it provides.
+    OnDemand,
+}
+
+impl Activation {
+    /// Parses `eager` or `on-demand`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "eager" => Some(Activation::Eager),
+            "on-demand" => Some(Activation::OnDemand),
+            _ => None,
+        }
+    }
+}
+
+/// A predicate that must hold before a module may start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// No provider is plugged into the slot yet.
+    SlotEmpty(String),
+    FileExists(String),
+    SettingEquals {
+        key: String,
+        value: String,
+    },
+}
+
+impl Condition {
+    /// Parses `slot-empty:<slot>`, `file-exists:<path>` or `setting:<key>=<value>`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (kind, arg) = text.split_once(':')?;
+        if arg.is_empty() {
+            return None;
+        }
+        match kind {
+            "slot-empty" => Some(Condition::SlotEmpty(arg.to_string())),
+            "file-exists" => Some(Condition::FileExists(arg.to_string())),
+            "setting" => {
+                let (key, value) = arg.split_once('=')?;
+                Some(Condition::SettingEquals {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// System state that module start conditions are checked against.
+pub trait ConditionContext: core::fmt::Debug {
+    /// Returns true when no provider is plugged into `slot`.
+    fn slot_empty(&self, slot: &str) -> bool;
+    fn file_exists(&self, path: &str) -> bool;
+    fn setting(&self, key: &str) -> Option<String>;
+
+    /// Returns true when the condition holds.
+    fn holds(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::SlotEmpty(slot) => self.slot_empty(slot),
+            Condition::FileExists(path) => self.file_exists(path),
+            Condition::SettingEquals { key, value } => {
+                self.setting(key).is_some_and(|current| &current == value)
+            }
+        }
+    }
+}
+
+/// Result of starting a module whose required paths may be absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartOutcome {
+    Started,
+    Waiting(Vec<String>),
+}
+
+/// Named capabilities granted to a module, e.g. `ConsoleWrite` or `NetAdmin`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapabilitySet {
+    caps: BTreeSet<String>,
+}
+
+impl CapabilitySet {
+    /// Creates an empty capability set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a capability to the set.
+    pub fn grant(&mut self, cap: &str) {
+        self.caps.insert(cap.to_string());
+    }
+
+    /// Removes a capability, returning true if it was granted.
+    pub fn revoke(&mut self, cap: &str) -> bool {
+        self.caps.remove(cap)
+    }
+
+    /// Returns true when the capability is granted.
+    pub fn contains(&self, cap: &str) -> bool {
+        self.caps.contains(cap)
+    }
+
+    /// Returns the requested capabilities that are not granted.
+    pub fn missing(&self, requested: &[String]) -> Vec<String> {
+        requested
+            .iter()
+            .filter(|cap| !self.contains(cap))
+            .cloned()
+            .collect()
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for CapabilitySet {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self {
+            caps: iter
+                .into_iter()
+                .map(|cap| cap.as_ref().to_string())
+                .collect(),
+        }
+    }
 }
 
 /// Full module metadata tracked by init.
@@ -176,9 +537,32 @@ pub enum ModuleState {
 pub struct ModuleRecord {
     pub name: String,
     pub depends: Vec<String>,
+    /// Optional dependencies started alongside the module when registered.
+    pub wants: Vec<String>,
+    /// Predicates that must all hold for the module to start.
+    pub conditions: Vec<Condition>,
     pub provides: Vec<String>,
     pub requires_caps: Vec<String>,
+    /// Capabilities the module may use; `requires_caps` must fit inside.
+    pub granted_caps: CapabilitySet,
+    pub requires_paths: Vec<String>,
     pub state: ModuleState,
+    pub restart: RestartPolicy,
+    pub activation: Activation,
+    /// Ticks allowed between heartbeats before the module is degraded.
+    pub health_interval: Option<u64>,
+    pub last_heartbeat: Option<u64>,
+    /// Ticks allowed between watchdog pets before the module is hung.
+    pub watchdog_timeout: Option<u64>,
+    pub last_pet: Option<u64>,
+    /// Failures since the module last started successfully.
+    pub failures: u32,
+    pub last_error: Option<Errno>,
+    pub last_failure: Option<u64>,
+    /// Tick of the last successful start.
+    pub started_at: Option<u64>,
+    /// Tick the module reported itself ready after its last start.
+    pub ready_at: Option<u64>,
 }
 
 impl ModuleRecord {
@@ -192,11 +576,177 @@ impl ModuleRecord {
         Self {
             name,
             depends,
+            wants: Vec::new(),
+            conditions: Vec::new(),
             provides,
             requires_caps,
+            granted_caps: CapabilitySet::new(),
+            requires_paths: Vec::new(),
             state: ModuleState::Stopped,
+            restart: RestartPolicy::Never,
+            activation: Activation::Eager,
+            health_interval: None,
+            last_heartbeat: None,
+            watchdog_timeout: None,
+            last_pet: None,
+            failures: 0,
+            last_error: None,
+            last_failure: None,
+            started_at: None,
+            ready_at: None,
         }
     }
+
+    /// Sets the filesystem paths that must exist before the module starts.
+    pub fn with_required_paths(mut self, paths: Vec<String>) -> Self {
+        self.requires_paths = paths;
+        self
+    }
+
+    /// Sets the capabilities granted to the module.
+    pub fn with_granted_caps(mut self, caps: CapabilitySet) -> Self {
+        self.granted_caps = caps;
+        self
+    }
+
+    /// Sets the conditions checked before every start.
+    pub fn with_conditions(mut self, conditions: Vec<Condition>) -> Self {
+        self.conditions = conditions;
+        self
+    }
+
+    /// Sets optional dependencies that never block the module's start.
+    pub fn with_wants(mut self, wants: Vec<String>) -> Self {
+        self.wants = wants;
+        self
+    }
+
+    /// Sets how init reacts when the module exits.
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart = policy;
+        self
+    }
+
+    /// Sets whether the module starts eagerly or on first lookup.
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    /// Enables health checks, expecting a heartbeat every `ticks` ticks.
+    pub fn with_health_interval(mut self, ticks: u64) -> Self {
+        self.health_interval = Some(ticks);
+        self
+    }
+
+    /// Arms the watchdog, requiring a pet every `ticks` ticks.
+    pub fn with_watchdog(mut self, ticks: u64) -> Self {
+        self.watchdog_timeout = Some(ticks);
+        self
+    }
+}
+
+/// What the watchdog did with a module that missed its deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// Left hung because its restart policy is `Never`.
+    Left,
+    Restarted,
+    RestartFailed(Errno),
+}
+
+/// A module found hung by `ModuleManager::watchdog_tick`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchdogEvent {
+    pub module: String,
+    pub action: WatchdogAction,
+}
+
+/// Number of lifecycle events kept by [`ModuleManager::history`].
+pub const MODULE_HISTORY_LEN: usize = 64;
+
+/// A module lifecycle transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleEventKind {
+    Registered,
+    Started,
+    Stopped,
+    Restarted,
+    Failed(Errno),
+    Waiting,
+    Draining,
+    Resumed,
+    Degraded,
+    Recovered,
+    Hung,
+}
+
+/// One recorded lifecycle transition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleEvent {
+    pub tick: u64,
+    pub module: String,
+    pub kind: ModuleEventKind,
+}
+
+impl ModuleEvent {
+    /// Formats the event as "module <name> started" and similar.
+    pub fn format(&self) -> String {
+        let what = match self.kind {
+            ModuleEventKind::Registered => "registered",
+            ModuleEventKind::Started => "started",
+            ModuleEventKind::Stopped => "stopped",
+            ModuleEventKind::Restarted => "restarting",
+            ModuleEventKind::Failed(err) => {
+                return alloc::format!("module {} failed: {:?}", self.module, err);
+            }
+            ModuleEventKind::Waiting => "waiting for paths",
+            ModuleEventKind::Draining => "draining",
+            ModuleEventKind::Resumed => "resumed",
+            ModuleEventKind::Degraded => "degraded: heartbeat overdue",
+            ModuleEventKind::Recovered => "recovered",
+            ModuleEventKind::Hung => "hung: watchdog deadline missed",
+        };
+        alloc::format!("module {} {}", self.module, what)
+    }
+}
+
+/// Failure diagnostics of one module, shown by `status <module>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleStatus {
+    pub name: String,
+    pub state: ModuleState,
+    /// Consecutive failures; reset when the module starts.
+    pub failures: u32,
+    pub last_error: Option<Errno>,
+    pub last_failure: Option<u64>,
+}
+
+/// Start time and duration of one module, as shown by `boot-report`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootTiming {
+    pub name: String,
+    pub started_at: u64,
+    /// Ticks from start until the module reported ready.
+    pub duration: u64,
+}
+
+impl BootTiming {
+    /// Tick at which the module became ready.
+    pub fn ready_at(&self) -> u64 {
+        self.started_at + self.duration
+    }
+}
+
+/// Boot timing summary in the style of `systemd-analyze`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BootReport {
+    /// Ticks from the first start until the last module was ready.
+    pub total: u64,
+    /// Every started module, slowest first.
+    pub modules: Vec<BootTiming>,
+    /// Dependency chain ending at the last module to become ready, in start order.
+    pub critical_path: Vec<BootTiming>,
 }
 
 /// Summary view of a module for UI presentation.
@@ -211,6 +761,10 @@ pub struct ModuleSummary {
 pub struct ModuleManager {
     modules: BTreeMap<String, ModuleRecord>,
     registry: ServiceRegistry,
+    events: Vec<ModuleEvent>,
+    now: u64,
+    log_sink: Option<fn(&ModuleEvent)>,
+    conditions: Option<Box<dyn ConditionContext>>,
 }
 
 impl ModuleManager {
@@ -224,6 +778,52 @@ impl ModuleManager {
         &self.registry
     }
 
+    /// Sets the tick stamped on events recorded from now on.
+    pub fn set_time(&mut self, now: u64) {
+        self.now = now;
+    }
+
+    /// Forwards every recorded event to `sink`, e.g. the system log.
+    pub fn set_log_sink(&mut self, sink: fn(&ModuleEvent)) {
+        self.log_sink = Some(sink);
+    }
+
+    /// Sets the system state that module start conditions are checked against.
+    pub fn set_condition_context(&mut self, context: Box<dyn ConditionContext>) {
+        self.conditions = Some(context);
+    }
+
+    /// Returns the conditions of a module that do not currently hold.
+    ///
+    /// Without a condition context every condition counts as unmet.
+    pub fn unmet_conditions(&self, name: &str) -> Result<Vec<Condition>, Errno> {
+        let record = self.modules.get(name).ok_or(Errno::NotFound)?;
+        Ok(record
+            .conditions
+            .iter()
+            .filter(|condition| {
+                !self
+                    .conditions
+                    .as_ref()
+                    .is_some_and(|context| context.holds(condition))
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// Returns the most recent lifecycle events, oldest first.
+    pub fn history(&self) -> &[ModuleEvent] {
+        &self.events
+    }
+
+    /// Returns the recorded lifecycle events of one module, oldest first.
+    pub fn events(&self, name: &str) -> Vec<&ModuleEvent> {
+        self.events
+            .iter()
+            .filter(|event| event.module == name)
+            .collect()
+    }
+
     /// Registers a module definition without starting it.
     pub fn register_module(&mut self, record: ModuleRecord) -> Result<(), Errno> {
         if record.name.is_empty() {
@@ -232,7 +832,12 @@ impl ModuleManager {
         if self.modules.contains_key(&record.name) {
             return Err(Errno::InvalidArg);
         }
-        if record.depends.iter().any(|dep| dep == &record.name) {
+        if record
+            .depends
+            .iter()
+            .chain(&record.wants)
+            .any(|dep| dep == &record.name)
+        {
             return Err(Errno::InvalidArg);
         }
         for service in &record.provides {
@@ -245,63 +850,266 @@ impl ModuleManager {
                 return Err(Errno::InvalidArg);
             }
         }
-        self.modules.insert(record.name.clone(), record);
+        if record.requires_paths.iter().any(|path| !path.starts_with('/')) {
+            return Err(Errno::InvalidArg);
+        }
+        if record.health_interval == Some(0) || record.watchdog_timeout == Some(0) {
+            return Err(Errno::InvalidArg);
+        }
+        let name = record.name.clone();
+        self.modules.insert(name.clone(), record);
+        self.record(&name, ModuleEventKind::Registered);
         Ok(())
     }
 
     /// Starts a module after validating dependencies and service ownership.
+    ///
+    /// Fails with `Errno::PermissionDenied` when `requires_caps` asks for
+    /// anything outside `granted_caps`, and with `Errno::InvalidArg` while a
+    /// start condition does not hold; the module then stays as it was.
+    /// Registered `wants` are started afterwards on a best-effort basis.
+    /// Required paths are not checked here; see `start_module_when_ready`.
     pub fn start_module(&mut self, name: &str) -> Result<(), Errno> {
-        let (current_state, depends, provides, module_name) = {
+        let (current_state, depends, wants, provides, module_name) = {
             let record = self.modules.get(name).ok_or(Errno::NotFound)?;
+            if !record
+                .granted_caps
+                .missing(&record.requires_caps)
+                .is_empty()
+            {
+                return Err(Errno::PermissionDenied);
+            }
             (
                 record.state,
                 record.depends.clone(),
+                record.wants.clone(),
                 record.provides.clone(),
                 record.name.clone(),
             )
         };
 
         match current_state {
-            ModuleState::Running => return Ok(()),
-            ModuleState::Failed => return Err(Errno::InvalidArg),
-            ModuleState::Stopped => {}
+            ModuleState::Running | ModuleState::Degraded => return Ok(()),
+            ModuleState::Failed | ModuleState::Draining | ModuleState::Hung => {
+                return Err(Errno::InvalidArg)
+            }
+            ModuleState::Stopped | ModuleState::Waiting => {}
+        }
+
+        if !self.unmet_conditions(name)?.is_empty() {
+            return Err(Errno::InvalidArg);
         }
 
         for dep in &depends {
             let dep_record = self.modules.get(dep).ok_or(Errno::NotFound)?;
-            if dep_record.state != ModuleState::Running {
+            if !dep_record.state.is_live() {
                 return Err(Errno::InvalidArg);
             }
         }
 
-        if provides.iter().any(|service| self.registry.contains(service)) {
+        if provides
+            .iter()
+            .any(|service| self.registry.contains(service) && !self.is_draining_owner(service))
+        {
             let record = self.modules.get_mut(name).expect("module exists");
             record.state = ModuleState::Failed;
+            self.record(&module_name, ModuleEventKind::Failed(Errno::InvalidArg));
             return Err(Errno::InvalidArg);
         }
 
         for service in &provides {
+            if self.is_draining_owner(service) {
+                self.registry.unregister(service)?;
+            }
             self.registry
                 .register(service.clone(), module_name.clone())?;
         }
 
         let record = self.modules.get_mut(name).expect("module exists");
         record.state = ModuleState::Running;
+        record.last_heartbeat = None;
+        record.last_pet = None;
+        self.record(&module_name, ModuleEventKind::Started);
+
+        for want in &wants {
+            if self.modules.contains_key(want) {
+                let _ = self.start_module(want);
+            }
+        }
         Ok(())
     }
 
-    /// Stops a running module and unregisters its services.
-    pub fn stop_module(&mut self, name: &str) -> Result<(), Errno> {
+    /// Starts a module once all of its required paths exist, parking it otherwise.
+    pub fn start_module_when_ready(
+        &mut self,
+        name: &str,
+        exists: impl Fn(&str) -> bool,
+    ) -> Result<StartOutcome, Errno> {
+        let record = self.modules.get_mut(name).ok_or(Errno::NotFound)?;
+        if !record.state.is_live() {
+            let missing: Vec<String> = record
+                .requires_paths
+                .iter()
+                .filter(|path| !exists(path))
+                .cloned()
+                .collect();
+            if !missing.is_empty() {
+                let entered = record.state != ModuleState::Waiting;
+                record.state = ModuleState::Waiting;
+                if entered {
+                    self.record(name, ModuleEventKind::Waiting);
+                }
+                return Ok(StartOutcome::Waiting(missing));
+            }
+        }
+        self.start_module(name)?;
+        Ok(StartOutcome::Started)
+    }
+
+    /// Starts waiting modules whose required paths now exist.
+    ///
+    /// Call this when a filesystem watch reports a change; modules that still
+    /// cannot start stay parked and the started names are returned.
+    pub fn start_ready_modules(&mut self, exists: impl Fn(&str) -> bool) -> Vec<String> {
+        let waiting = self.waiting_modules();
+        let mut started = Vec::new();
+        for name in waiting {
+            if let Ok(StartOutcome::Started) = self.start_module_when_ready(&name, &exists) {
+                started.push(name);
+            }
+        }
+        started
+    }
+
+    /// Lists modules parked until their required paths appear.
+    pub fn waiting_modules(&self) -> Vec<String> {
+        self.modules
+            .values()
+            .filter(|record| record.state == ModuleState::Waiting)
+            .map(|record| record.name.clone())
+            .collect()
+    }
+
+    /// Marks a running module as draining ahead of a hot swap.
+    ///
+    /// It keeps its services until a replacement starts and takes them over.
+    pub fn drain_module(&mut self, name: &str) -> Result<(), Errno> {
         let record = self.modules.get_mut(name).ok_or(Errno::NotFound)?;
         if record.state != ModuleState::Running {
             return Err(Errno::InvalidArg);
         }
+        record.state = ModuleState::Draining;
+        self.record(name, ModuleEventKind::Draining);
+        Ok(())
+    }
+
+    /// Returns a draining module to service, e.g. after a failed swap.
+    pub fn resume_module(&mut self, name: &str) -> Result<(), Errno> {
+        let record = self.modules.get_mut(name).ok_or(Errno::NotFound)?;
+        if record.state != ModuleState::Draining {
+            return Err(Errno::InvalidArg);
+        }
+        record.state = ModuleState::Running;
+        self.record(name, ModuleEventKind::Resumed);
+        Ok(())
+    }
+
+    /// Stops a running, degraded, hung or draining module and unregisters its services.
+    ///
+    /// Stopping a waiting module cancels its pending start.
+    pub fn stop_module(&mut self, name: &str) -> Result<(), Errno> {
+        let record = self.modules.get_mut(name).ok_or(Errno::NotFound)?;
+        if record.state == ModuleState::Waiting {
+            record.state = ModuleState::Stopped;
+            self.record(name, ModuleEventKind::Stopped);
+            return Ok(());
+        }
+        if !record.state.is_live()
+            && !matches!(record.state, ModuleState::Draining | ModuleState::Hung)
+        {
+            return Err(Errno::InvalidArg);
+        }
         record.state = ModuleState::Stopped;
+        record.last_heartbeat = None;
+        record.last_pet = None;
         self.registry.unregister_module(&record.name);
+        self.record(name, ModuleEventKind::Stopped);
         Ok(())
     }
 
-    /// Restarts a module, marking it failed on start errors.
+    /// Lists registered modules that directly `depends` on `name`, sorted by name.
+    pub fn dependents_of(&self, name: &str) -> Vec<String> {
+        self.modules
+            .values()
+            .filter(|record| record.depends.iter().any(|dep| dep == name))
+            .map(|record| record.name.clone())
+            .collect()
+    }
+
+    /// Stops a module after every module that transitively depends on it.
+    ///
+    /// Dependents are stopped in reverse start order so none is left holding
+    /// services of a stopped dependency; returns the modules actually stopped.
+    pub fn stop_module_cascade(&mut self, name: &str) -> Result<Vec<String>, Errno> {
+        if !self.modules.contains_key(name) {
+            return Err(Errno::NotFound);
+        }
+        let mut affected = BTreeSet::new();
+        let mut pending = alloc::vec![name.to_string()];
+        while let Some(module) = pending.pop() {
+            if affected.insert(module.clone()) {
+                pending.extend(self.dependents_of(&module));
+            }
+        }
+        let order: Vec<String> = self
+            .resolve_start_plan()?
+            .into_iter()
+            .rev()
+            .filter(|module| affected.contains(module))
+            .collect();
+        Ok(self.stop_in_order(order))
+    }
+
+    /// Starts every eager module in dependency order, returning those started.
+    ///
+    /// On-demand modules are left for their first lookup, and a module that
+    /// fails to start does not stop the rest.
+    pub fn start_all(&mut self) -> Result<Vec<String>, Errno> {
+        let mut started = Vec::new();
+        for name in self.resolve_start_plan()? {
+            let record = self.modules.get(&name).expect("module exists");
+            if record.activation == Activation::OnDemand
+                || !matches!(record.state, ModuleState::Stopped | ModuleState::Waiting)
+            {
+                continue;
+            }
+            if self.start_module(&name).is_ok() {
+                started.push(name);
+            }
+        }
+        Ok(started)
+    }
+
+    /// Stops every module in reverse dependency order, returning those stopped.
+    pub fn stop_all(&mut self) -> Result<Vec<String>, Errno> {
+        let order: Vec<String> = self.resolve_start_plan()?.into_iter().rev().collect();
+        Ok(self.stop_in_order(order))
+    }
+
+    fn stop_in_order(&mut self, order: Vec<String>) -> Vec<String> {
+        order
+            .into_iter()
+            .filter(|name| {
+                let state = self.modules[name].state;
+                !matches!(state, ModuleState::Stopped | ModuleState::Failed)
+                    && self.stop_module(name).is_ok()
+            })
+            .collect()
+    }
+
+    /// Restarts a module, including a failed one, marking it failed on start errors.
+    ///
+    /// Records `Restarted` followed by `Started` or `Failed`.
     pub fn restart_module(&mut self, name: &str) -> Result<(), Errno> {
         let current_state = self
             .modules
@@ -309,22 +1117,210 @@ impl ModuleManager {
             .map(|record| record.state)
             .ok_or(Errno::NotFound)?;
 
-        if current_state == ModuleState::Running {
+        if current_state.is_live() || current_state == ModuleState::Hung {
             let record = self.modules.get_mut(name).expect("module exists");
             record.state = ModuleState::Stopped;
             self.registry.unregister_module(&record.name);
-        }
+        } else if current_state == ModuleState::Failed {
+            let record = self.modules.get_mut(name).expect("module exists");
+            record.state = ModuleState::Stopped;
+        }
+        self.record(name, ModuleEventKind::Restarted);
 
         match self.start_module(name) {
             Ok(()) => Ok(()),
             Err(err) => {
                 let record = self.modules.get_mut(name).expect("module exists");
+                let already_failed = record.state == ModuleState::Failed;
                 record.state = ModuleState::Failed;
+                if !already_failed {
+                    self.record(name, ModuleEventKind::Failed(err));
+                }
                 Err(err)
             }
         }
     }
 
+    /// Resolves a service, starting an on-demand provider on a miss.
+    ///
+    /// Only a stopped module registered with `Activation::OnDemand` is
+    /// started; its start error is returned if activation fails.
+    pub fn resolve_service(&mut self, service: &str) -> Result<String, Errno> {
+        if let Ok(module) = self.registry.select(service) {
+            return Ok(module);
+        }
+        let provider = self
+            .modules
+            .values()
+            .find(|record| {
+                record.activation == Activation::OnDemand
+                    && record.state == ModuleState::Stopped
+                    && record.provides.iter().any(|provided| provided == service)
+            })
+            .map(|record| record.name.clone())
+            .ok_or(Errno::NotFound)?;
+        self.start_module(&provider)?;
+        self.registry.select(service)
+    }
+
+    /// Replaces the capabilities granted to a registered module.
+    pub fn grant_capabilities(&mut self, name: &str, caps: CapabilitySet) -> Result<(), Errno> {
+        let record = self.modules.get_mut(name).ok_or(Errno::NotFound)?;
+        record.granted_caps = caps;
+        Ok(())
+    }
+
+    /// Records a liveness report from a running module at `tick`.
+    ///
+    /// A degraded module that reports again returns to running.
+    pub fn heartbeat(&mut self, name: &str, tick: u64) -> Result<(), Errno> {
+        let record = self.modules.get_mut(name).ok_or(Errno::NotFound)?;
+        if !record.state.is_live() {
+            return Err(Errno::InvalidArg);
+        }
+        record.last_heartbeat = Some(tick);
+        if record.state == ModuleState::Degraded {
+            record.state = ModuleState::Running;
+            self.record_at(tick, name, ModuleEventKind::Recovered);
+        }
+        Ok(())
+    }
+
+    /// Degrades running modules whose heartbeat is overdue at `now`.
+    ///
+    /// A module that has never reported is measured from its first check.
+    /// Returns the names of modules that became degraded.
+    pub fn check_health(&mut self, now: u64) -> Vec<String> {
+        let mut degraded = Vec::new();
+        for record in self.modules.values_mut() {
+            let Some(interval) = record.health_interval else {
+                continue;
+            };
+            if record.state != ModuleState::Running {
+                continue;
+            }
+            let last = *record.last_heartbeat.get_or_insert(now);
+            if now.saturating_sub(last) > interval {
+                record.state = ModuleState::Degraded;
+                degraded.push(record.name.clone());
+            }
+        }
+        for name in &degraded {
+            self.record_at(now, name, ModuleEventKind::Degraded);
+        }
+        degraded
+    }
+
+    /// Pets the watchdog of a running module at `tick`.
+    pub fn pet_watchdog(&mut self, name: &str, tick: u64) -> Result<(), Errno> {
+        let record = self.modules.get_mut(name).ok_or(Errno::NotFound)?;
+        if !record.state.is_live() {
+            return Err(Errno::InvalidArg);
+        }
+        record.last_pet = Some(tick);
+        Ok(())
+    }
+
+    /// Marks modules that missed their watchdog deadline at `now` as hung.
+    ///
+    /// Driven by timer ticks; a module that was never petted is measured from
+    /// its first tick. Each hung module is then handled by its restart policy:
+    /// `OnFailure` and `Always` restart it, `Never` leaves it hung.
+    pub fn watchdog_tick(&mut self, now: u64) -> Vec<WatchdogEvent> {
+        let mut hung = Vec::new();
+        for record in self.modules.values_mut() {
+            let Some(timeout) = record.watchdog_timeout else {
+                continue;
+            };
+            if !record.state.is_live() {
+                continue;
+            }
+            let last = *record.last_pet.get_or_insert(now);
+            if now.saturating_sub(last) > timeout {
+                record.state = ModuleState::Hung;
+                hung.push((record.name.clone(), record.restart));
+            }
+        }
+        hung.into_iter()
+            .map(|(module, policy)| {
+                self.record_at(now, &module, ModuleEventKind::Hung);
+                let action = if policy == RestartPolicy::Never {
+                    WatchdogAction::Left
+                } else {
+                    match self.restart_module(&module) {
+                        Ok(()) => WatchdogAction::Restarted,
+                        Err(err) => WatchdogAction::RestartFailed(err),
+                    }
+                };
+                WatchdogEvent { module, action }
+            })
+            .collect()
+    }
+
+    /// Returns the state and failure diagnostics of a module.
+    pub fn status(&self, name: &str) -> Result<ModuleStatus, Errno> {
+        let record = self.modules.get(name).ok_or(Errno::NotFound)?;
+        Ok(ModuleStatus {
+            name: record.name.clone(),
+            state: record.state,
+            failures: record.failures,
+            last_error: record.last_error,
+            last_failure: record.last_failure,
+        })
+    }
+
+    /// Records that a running module finished starting up.
+    ///
+    /// Modules that never report are counted as ready the moment they started.
+    pub fn mark_ready(&mut self, name: &str) -> Result<(), Errno> {
+        let now = self.now;
+        let record = self.modules.get_mut(name).ok_or(Errno::NotFound)?;
+        if !record.state.is_live() || record.started_at.is_none() {
+            return Err(Errno::InvalidArg);
+        }
+        record.ready_at.get_or_insert(now);
+        Ok(())
+    }
+
+    /// Summarizes module start times and the critical path of the boot.
+    pub fn boot_report(&self) -> BootReport {
+        let timing = |record: &ModuleRecord| {
+            record.started_at.map(|started_at| BootTiming {
+                name: record.name.clone(),
+                started_at,
+                duration: record.ready_at.unwrap_or(started_at) - started_at,
+            })
+        };
+        let mut modules: Vec<BootTiming> = self.modules.values().filter_map(timing).collect();
+        modules.sort_by(|a, b| b.duration.cmp(&a.duration).then(a.name.cmp(&b.name)));
+
+        let first = modules.iter().map(|module| module.started_at).min();
+        let mut critical_path = Vec::new();
+        let mut next = modules
+            .iter()
+            .max_by_key(|module| module.ready_at())
+            .cloned();
+        while let Some(module) = next {
+            next = self.modules[&module.name]
+                .depends
+                .iter()
+                .filter_map(|dep| self.modules.get(dep).and_then(timing))
+                .filter(|dep| dep.ready_at() <= module.started_at)
+                .max_by_key(|dep| dep.ready_at());
+            critical_path.push(module);
+        }
+        critical_path.reverse();
+
+        BootReport {
+            total: critical_path
+                .last()
+                .zip(first)
+                .map_or(0, |(last, first)| last.ready_at() - first),
+            modules,
+            critical_path,
+        }
+    }
+
     /// Lists modules for UI rendering.
     pub fn list_modules(&self) -> Vec<ModuleSummary> {
         self.modules
@@ -348,6 +1344,68 @@ impl ModuleManager {
             .collect();
         resolve_start_order(&modules)
     }
+
+    fn record(&mut self, module: &str, kind: ModuleEventKind) {
+        self.record_at(self.now, module, kind);
+    }
+
+    fn record_at(&mut self, tick: u64, module: &str, kind: ModuleEventKind) {
+        if let Some(record) = self.modules.get_mut(module) {
+            match kind {
+                ModuleEventKind::Started => {
+                    record.failures = 0;
+                    record.started_at = Some(tick);
+                    record.ready_at = None;
+                }
+                ModuleEventKind::Failed(err) => {
+                    record.failures += 1;
+                    record.last_error = Some(err);
+                    record.last_failure = Some(tick);
+                }
+                _ => {}
+            }
+        }
+        if self.events.len() >= MODULE_HISTORY_LEN {
+            self.events.remove(0);
+        }
+        let event = ModuleEvent {
+            tick,
+            module: module.to_string(),
+            kind,
+        };
+        if let Some(sink) = self.log_sink {
+            sink(&event);
+        }
+        self.events.push(event);
+    }
+
+    fn is_draining_owner(&self, service: &str) -> bool {
+        self.registry
+            .resolve(service)
+            .ok()
+            .and_then(|owner| self.modules.get(owner))
+            .is_some_and(|record| record.state == ModuleState::Draining)
+    }
+}
+
+impl SwapLifecycle for ModuleManager {
+    type Error = Errno;
+
+    fn drain(&mut self, module: &str) -> Result<(), Errno> {
+        self.drain_module(module)
+    }
+
+    fn start(&mut self, module: &str) -> Result<(), Errno> {
+        self.start_module(module)
+    }
+
+    fn stop(&mut self, module: &str) -> Result<(), Errno> {
+        self.stop_module(module)
+    }
+
+    fn resume(&mut self, module: &str) {
+        let _ = self.resume_module(module);
+    }
 }
 
 /// Handles a registry request and returns the response.
@@ -384,10 +1442,10 @@ pub fn handle_registry_request(
                     status: RegistryStatus::Invalid,
                 };
             }
-            if let Ok(module) = registry.resolve(&service) {
+            if let Ok(module) = registry.select(&service) {
                 RegistryResponse::Lookup {
                     status: RegistryStatus::Ok,
-                    module: Some(module.to_string()),
+                    module: Some(module),
                 }
             } else {
                 RegistryResponse::Lookup {
@@ -400,27 +1458,121 @@ pub fn handle_registry_request(
             status: RegistryStatus::Ok,
             entries: registry.list(),
         },
+        RegistryRequest::Unregister { service } => {
+            if !is_valid_service_name(&service) {
+                return RegistryResponse::Error {
+                    status: RegistryStatus::Invalid,
+                };
+            }
+            match registry.unregister(&service) {
+                Ok(()) => RegistryResponse::Ack,
+                Err(_) => RegistryResponse::Error {
+                    status: RegistryStatus::NotFound,
+                },
+            }
+        }
+        RegistryRequest::UnregisterModule { module } => {
+            if registry.unregister_module(&module) > 0 {
+                RegistryResponse::Ack
+            } else {
+                RegistryResponse::Error {
+                    status: RegistryStatus::NotFound,
+                }
+            }
+        }
+        RegistryRequest::Watch { prefix } => match registry.watch(prefix) {
+            Ok(watch) => RegistryResponse::Watching { watch },
+            Err(_) => RegistryResponse::Error {
+                status: RegistryStatus::Invalid,
+            },
+        },
+        RegistryRequest::Unwatch { watch } => match registry.unwatch(watch) {
+            Ok(()) => RegistryResponse::Ack,
+            Err(_) => RegistryResponse::Error {
+                status: RegistryStatus::NotFound,
+            },
+        },
+        RegistryRequest::RegisterInstance {
+            service,
+            module,
+            priority,
+        } => {
+            if !is_valid_service_name(&service) {
+                return RegistryResponse::Error {
+                    status: RegistryStatus::Invalid,
+                };
+            }
+            let taken = registry
+                .instances(&service)
+                .iter()
+                .any(|instance| instance.module == module);
+            match registry.register_instance(service, module, priority) {
+                Ok(instance) => RegistryResponse::Registered { instance },
+                Err(_) if taken => RegistryResponse::Error {
+                    status: RegistryStatus::AlreadyExists,
+                },
+                Err(_) => RegistryResponse::Error {
+                    status: RegistryStatus::Invalid,
+                },
+            }
+        }
+        RegistryRequest::SetPolicy { service, policy } => {
+            match registry.set_policy(&service, policy) {
+                Ok(()) => RegistryResponse::Ack,
+                Err(_) => RegistryResponse::Error {
+                    status: RegistryStatus::Invalid,
+                },
+            }
+        }
     }
 }
 
+/// Returns true if a registry request withdraws or reroutes services it may
+/// not own, which only admin sessions may do.
+pub fn registry_request_requires_admin(request: &RegistryRequest) -> bool {
+    matches!(
+        request,
+        RegistryRequest::Unregister { .. }
+            | RegistryRequest::UnregisterModule { .. }
+            | RegistryRequest::SetPolicy { .. }
+    )
+}
+
 /// Decodes a registry request, handles it, and encodes the response.
+///
+/// Requests covered by `registry_request_requires_admin` are answered with
+/// `RegistryStatus::Denied` unless they carry an admin credential.
 pub fn handle_registry_request_bytes(
     registry: &mut ServiceRegistry,
     bytes: &[u8],
 ) -> Vec<u8> {
-    let response = match decode_request(bytes) {
-        Ok(request) => handle_registry_request(registry, request),
-        Err(_) => RegistryResponse::Error {
+    let response = match (decode_request(bytes), read_credential(bytes)) {
+        (Ok(request), Ok(credential)) => {
+            if registry_request_requires_admin(&request) && !is_admin(credential.as_ref()) {
+                RegistryResponse::Error {
+                    status: RegistryStatus::Denied,
+                }
+            } else {
+                handle_registry_request(registry, request)
+            }
+        }
+        _ => RegistryResponse::Error {
             status: RegistryStatus::Invalid,
         },
     };
     encode_response(&response)
 }
 
+fn is_admin(credential: Option<&Credential>) -> bool {
+    credential.is_some_and(|credential| credential.admin)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ruzzle_protocol::auth::append_credential;
     use ruzzle_protocol::registry::{decode_response, encode_request};
+    use user_puzzle_board::{PuzzleBoard, PuzzleSlot, SwapError};
 
     #[test]
     fn resolve_start_order_sorts_dependencies() {
@@ -524,6 +1676,67 @@ mod tests {
         assert_eq!(registry.resolve("ruzzle.shell"), Ok("tui-shell"));
     }
 
+    #[test]
+    fn service_registry_resolves_instances_by_policy() {
+        let mut registry = ServiceRegistry::new();
+        let first = registry
+            .register_instance("ruzzle.console".into(), "vga-console".into(), 1)
+            .unwrap();
+        let second = registry
+            .register_instance("ruzzle.console".into(), "serial-console".into(), 5)
+            .unwrap();
+        assert_ne!(first, second);
+        assert_eq!(
+            registry.register_instance("ruzzle.console".into(), "vga-console".into(), 9),
+            Err(Errno::InvalidArg)
+        );
+        assert_eq!(
+            registry.register("ruzzle.console".into(), "other".into()),
+            Err(Errno::InvalidArg)
+        );
+        assert_eq!(registry.instances("ruzzle.console").len(), 2);
+        assert_eq!(registry.list().len(), 2);
+
+        assert_eq!(
+            registry.select("ruzzle.console"),
+            Ok("vga-console".to_string())
+        );
+        assert_eq!(
+            registry.select("ruzzle.console"),
+            Ok("vga-console".to_string())
+        );
+
+        registry
+            .set_policy("ruzzle.console", ResolvePolicy::Priority)
+            .unwrap();
+        assert_eq!(registry.resolve("ruzzle.console"), Ok("serial-console"));
+
+        registry
+            .set_policy("ruzzle.console", ResolvePolicy::RoundRobin)
+            .unwrap();
+        let picks: Vec<String> = (0..3)
+            .map(|_| registry.select("ruzzle.console").unwrap())
+            .collect();
+        assert_eq!(picks, vec!["vga-console", "serial-console", "vga-console"]);
+
+        assert_eq!(registry.unregister_module("vga-console"), 1);
+        assert_eq!(
+            registry.select("ruzzle.console"),
+            Ok("serial-console".to_string())
+        );
+        assert_eq!(
+            registry.select("ruzzle.console"),
+            Ok("serial-console".to_string())
+        );
+        registry.unregister_module("serial-console");
+        assert!(!registry.contains("ruzzle.console"));
+        assert_eq!(registry.policy("ruzzle.console"), ResolvePolicy::RoundRobin);
+        assert_eq!(
+            registry.set_policy("console", ResolvePolicy::First),
+            Err(Errno::InvalidArg)
+        );
+    }
+
     #[test]
     fn service_registry_rejects_empty_or_invalid_service() {
         let mut registry = ServiceRegistry::new();
@@ -545,12 +1758,15 @@ mod tests {
     fn module_manager_registers_and_starts_modules() {
         let mut manager = ModuleManager::new();
         manager
-            .register_module(ModuleRecord::new(
-                "console-service".to_string(),
-                vec![],
-                vec!["ruzzle.console".to_string()],
-                vec!["ConsoleWrite".to_string()],
-            ))
+            .register_module(
+                ModuleRecord::new(
+                    "console-service".to_string(),
+                    vec![],
+                    vec!["ruzzle.console".to_string()],
+                    vec!["ConsoleWrite".to_string()],
+                )
+                .with_granted_caps(["ConsoleWrite"].into_iter().collect()),
+            )
             .expect("register should succeed");
 
         manager
@@ -573,6 +1789,85 @@ mod tests {
         );
     }
 
+    fn editor_manager() -> ModuleManager {
+        let mut manager = ModuleManager::new();
+        for name in ["text-editor", "vim-piece"] {
+            manager
+                .register_module(ModuleRecord::new(
+                    name.to_string(),
+                    vec![],
+                    vec!["ruzzle.editor".to_string()],
+                    vec![],
+                ))
+                .unwrap();
+        }
+        manager.start_module("text-editor").unwrap();
+        manager
+    }
+
+    fn state_of(manager: &ModuleManager, name: &str) -> ModuleState {
+        manager
+            .list_modules()
+            .into_iter()
+            .find(|module| module.name == name)
+            .map(|module| module.state)
+            .unwrap()
+    }
+
+    #[test]
+    fn draining_module_hands_services_to_its_replacement() {
+        let mut manager = editor_manager();
+        assert_eq!(manager.start_module("vim-piece"), Err(Errno::InvalidArg));
+        manager
+            .register_module(ModuleRecord::new(
+                "vim-next".to_string(),
+                vec![],
+                vec!["ruzzle.editor".to_string()],
+                vec![],
+            ))
+            .unwrap();
+
+        manager.drain_module("text-editor").unwrap();
+        assert_eq!(state_of(&manager, "text-editor"), ModuleState::Draining);
+        assert_eq!(
+            manager.service_registry().resolve("ruzzle.editor"),
+            Ok("text-editor")
+        );
+        manager.start_module("vim-next").unwrap();
+        assert_eq!(
+            manager.service_registry().resolve("ruzzle.editor"),
+            Ok("vim-next")
+        );
+        manager.stop_module("text-editor").unwrap();
+        assert_eq!(state_of(&manager, "text-editor"), ModuleState::Stopped);
+        assert_eq!(
+            manager.service_registry().resolve("ruzzle.editor"),
+            Ok("vim-next")
+        );
+        assert_eq!(manager.drain_module("text-editor"), Err(Errno::InvalidArg));
+        assert_eq!(manager.resume_module("vim-next"), Err(Errno::InvalidArg));
+    }
+
+    #[test]
+    fn module_manager_drives_board_swap() {
+        let mut manager = editor_manager();
+        let mut board = PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.editor@1", false)]);
+        let provides = vec!["ruzzle.slot.editor@1".to_string()];
+        board.mark_running("text-editor", &provides);
+        let report = board
+            .swap("ruzzle.slot.editor", "vim-piece", &provides, &mut manager)
+            .unwrap();
+        assert!(report.old_stopped);
+        assert_eq!(state_of(&manager, "text-editor"), ModuleState::Stopped);
+        assert_eq!(state_of(&manager, "vim-piece"), ModuleState::Running);
+        assert_eq!(board.provider_for("ruzzle.slot.editor"), Some("vim-piece"));
+
+        let result = board.swap("ruzzle.slot.editor", "missing", &provides, &mut manager);
+        assert_eq!(result, Err(SwapError::Start(Errno::NotFound)));
+        assert_eq!(state_of(&manager, "vim-piece"), ModuleState::Running);
+        assert_eq!(board.provider_for("ruzzle.slot.editor"), Some("vim-piece"));
+    }
+
     #[test]
     fn module_manager_rejects_missing_dependency() {
         let mut manager = ModuleManager::new();
@@ -801,79 +2096,782 @@ mod tests {
     }
 
     #[test]
-    fn module_manager_resolves_start_plan() {
+    fn module_manager_waits_for_required_paths() {
         let mut manager = ModuleManager::new();
         manager
-            .register_module(ModuleRecord::new(
-                "a".to_string(),
-                vec![],
-                vec![],
-                vec![],
-            ))
-            .unwrap();
-        manager
-            .register_module(ModuleRecord::new(
-                "b".to_string(),
-                vec!["a".to_string()],
-                vec![],
-                vec![],
-            ))
+            .register_module(
+                ModuleRecord::new(
+                    "net-manager".to_string(),
+                    vec![],
+                    vec!["ruzzle.net".to_string()],
+                    vec![],
+                )
+                .with_required_paths(vec!["/etc/net/profiles".to_string()]),
+            )
             .unwrap();
-        let order = manager.resolve_start_plan().unwrap();
-        assert_eq!(order, vec!["a".to_string(), "b".to_string()]);
-    }
 
-    #[test]
-    fn handle_registry_register_and_lookup() {
-        let mut registry = ServiceRegistry::new();
-        let response = handle_registry_request(
-            &mut registry,
-            RegistryRequest::Register {
-                service: "ruzzle.console".to_string(),
-                module: "console-service".to_string(),
-            },
+        let outcome = manager.start_module_when_ready("net-manager", |_| false);
+        assert_eq!(
+            outcome,
+            Ok(StartOutcome::Waiting(vec!["/etc/net/profiles".to_string()]))
         );
-        assert_eq!(response, RegistryResponse::Ack);
+        assert_eq!(manager.waiting_modules(), vec!["net-manager".to_string()]);
+        assert!(manager.start_ready_modules(|_| false).is_empty());
 
-        let response = handle_registry_request(
-            &mut registry,
-            RegistryRequest::Lookup {
-                service: "ruzzle.console".to_string(),
-            },
+        let started = manager.start_ready_modules(|path| path == "/etc/net/profiles");
+        assert_eq!(started, vec!["net-manager".to_string()]);
+        assert!(manager.waiting_modules().is_empty());
+        assert_eq!(
+            manager.service_registry().resolve("ruzzle.net").unwrap(),
+            "net-manager"
         );
         assert_eq!(
-            response,
-            RegistryResponse::Lookup {
-                status: RegistryStatus::Ok,
-                module: Some("console-service".to_string())
-            }
+            manager.start_module_when_ready("net-manager", |_| false),
+            Ok(StartOutcome::Started)
         );
     }
 
     #[test]
-    fn handle_registry_register_duplicate() {
-        let mut registry = ServiceRegistry::new();
-        registry
-            .register("ruzzle.console".into(), "console-service".into())
+    fn module_manager_stop_cancels_waiting_module() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(
+                ModuleRecord::new("net-manager".to_string(), vec![], vec![], vec![])
+                    .with_required_paths(vec!["/etc/net".to_string()]),
+            )
             .unwrap();
-        let response = handle_registry_request(
-            &mut registry,
-            RegistryRequest::Register {
-                service: "ruzzle.console".to_string(),
-                module: "other".to_string(),
-            },
-        );
-        assert_eq!(
-            response,
-            RegistryResponse::Error {
-                status: RegistryStatus::AlreadyExists
-            }
-        );
+        manager
+            .start_module_when_ready("net-manager", |_| false)
+            .unwrap();
+        manager.stop_module("net-manager").unwrap();
+        assert_eq!(manager.list_modules()[0].state, ModuleState::Stopped);
+        assert!(manager.start_ready_modules(|_| true).is_empty());
     }
 
     #[test]
-    fn handle_registry_register_invalid_input() {
-        let mut registry = ServiceRegistry::new();
+    fn module_manager_ready_modules_keep_waiting_on_dependencies() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(ModuleRecord::new("fs".to_string(), vec![], vec![], vec![]))
+            .unwrap();
+        manager
+            .register_module(
+                ModuleRecord::new("net".to_string(), vec!["fs".to_string()], vec![], vec![])
+                    .with_required_paths(vec!["/etc/net".to_string()]),
+            )
+            .unwrap();
+        manager.start_module_when_ready("net", |_| false).unwrap();
+        assert!(manager.start_ready_modules(|_| true).is_empty());
+        assert_eq!(manager.waiting_modules(), vec!["net".to_string()]);
+        assert_eq!(
+            manager.start_module_when_ready("missing", |_| true),
+            Err(Errno::NotFound)
+        );
+        manager.start_module("fs").unwrap();
+        assert_eq!(manager.start_ready_modules(|_| true), vec!["net".to_string()]);
+    }
+
+    #[test]
+    fn module_manager_rejects_relative_required_paths() {
+        let mut manager = ModuleManager::new();
+        let record = ModuleRecord::new("net".to_string(), vec![], vec![], vec![])
+            .with_required_paths(vec!["etc/net".to_string()]);
+        assert_eq!(manager.register_module(record), Err(Errno::InvalidArg));
+    }
+
+    #[test]
+    fn module_manager_degrades_modules_without_heartbeats() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(
+                ModuleRecord::new(
+                    "net".to_string(),
+                    vec![],
+                    vec!["ruzzle.net".to_string()],
+                    vec![],
+                )
+                .with_health_interval(10),
+            )
+            .unwrap();
+        manager
+            .register_module(ModuleRecord::new("fs".to_string(), vec![], vec![], vec![]))
+            .unwrap();
+        assert_eq!(manager.heartbeat("net", 0), Err(Errno::InvalidArg));
+        manager.start_module("net").unwrap();
+        manager.start_module("fs").unwrap();
+
+        assert!(manager.check_health(100).is_empty());
+        assert_eq!(manager.check_health(111), vec!["net".to_string()]);
+        assert_eq!(manager.list_modules()[1].state, ModuleState::Degraded);
+        assert_eq!(ModuleState::Degraded.label(), "degraded");
+        assert_eq!(
+            manager.service_registry().resolve("ruzzle.net").unwrap(),
+            "net"
+        );
+        assert!(manager.check_health(200).is_empty());
+
+        manager.heartbeat("net", 205).unwrap();
+        assert_eq!(manager.list_modules()[1].state, ModuleState::Running);
+        assert!(manager.check_health(215).is_empty());
+        assert_eq!(manager.heartbeat("missing", 0), Err(Errno::NotFound));
+    }
+
+    #[test]
+    fn module_manager_treats_degraded_modules_as_live() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(
+                ModuleRecord::new("fs".to_string(), vec![], vec![], vec![]).with_health_interval(1),
+            )
+            .unwrap();
+        manager
+            .register_module(ModuleRecord::new(
+                "net".to_string(),
+                vec!["fs".to_string()],
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+        manager.start_module("fs").unwrap();
+        manager.check_health(0);
+        manager.check_health(5);
+        manager.start_module("net").unwrap();
+        assert_eq!(manager.start_module("fs"), Ok(()));
+
+        manager.restart_module("fs").unwrap();
+        assert_eq!(manager.list_modules()[0].state, ModuleState::Running);
+        assert!(manager.check_health(6).is_empty());
+        manager.stop_module("fs").unwrap();
+        assert_eq!(manager.list_modules()[0].state, ModuleState::Stopped);
+
+        let zero =
+            ModuleRecord::new("idle".to_string(), vec![], vec![], vec![]).with_health_interval(0);
+        assert_eq!(manager.register_module(zero), Err(Errno::InvalidArg));
+    }
+
+    #[test]
+    fn module_manager_starts_wanted_modules_best_effort() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(
+                ModuleRecord::new("files".to_string(), vec![], vec![], vec![]).with_wants(vec![
+                    "net".to_string(),
+                    "audio".to_string(),
+                    "missing".to_string(),
+                ]),
+            )
+            .unwrap();
+        manager
+            .register_module(
+                ModuleRecord::new("net".to_string(), vec![], vec![], vec![])
+                    .with_wants(vec!["files".to_string()]),
+            )
+            .unwrap();
+        manager
+            .register_module(ModuleRecord::new(
+                "audio".to_string(),
+                vec!["sound-hw".to_string()],
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+
+        manager.start_module("files").unwrap();
+        let states: Vec<ModuleState> = manager
+            .list_modules()
+            .iter()
+            .map(|module| module.state)
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ModuleState::Stopped,
+                ModuleState::Running,
+                ModuleState::Running
+            ]
+        );
+
+        let looped = ModuleRecord::new("loop".to_string(), vec![], vec![], vec![])
+            .with_wants(vec!["loop".to_string()]);
+        assert_eq!(manager.register_module(looped), Err(Errno::InvalidArg));
+    }
+
+    #[test]
+    fn module_manager_refuses_ungranted_capabilities() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(
+                ModuleRecord::new(
+                    "net-manager".to_string(),
+                    vec![],
+                    vec!["ruzzle.net".to_string()],
+                    vec!["ConsoleWrite".to_string(), "NetAdmin".to_string()],
+                )
+                .with_granted_caps(["ConsoleWrite"].into_iter().collect()),
+            )
+            .unwrap();
+        assert_eq!(
+            manager.start_module("net-manager"),
+            Err(Errno::PermissionDenied)
+        );
+        assert_eq!(manager.list_modules()[0].state, ModuleState::Stopped);
+        assert!(!manager.service_registry().contains("ruzzle.net"));
+
+        let mut caps: CapabilitySet = ["ConsoleWrite", "NetAdmin", "Timer"].into_iter().collect();
+        assert!(caps.revoke("Timer"));
+        assert!(!caps.revoke("Timer"));
+        assert_eq!(
+            caps.missing(&["Timer".to_string(), "NetAdmin".to_string()]),
+            vec!["Timer".to_string()]
+        );
+        manager.grant_capabilities("net-manager", caps).unwrap();
+        manager.start_module("net-manager").unwrap();
+        assert_eq!(
+            manager.grant_capabilities("missing", CapabilitySet::new()),
+            Err(Errno::NotFound)
+        );
+    }
+
+    #[test]
+    fn module_manager_activates_on_demand_providers() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(
+                ModuleRecord::new(
+                    "clipboard".to_string(),
+                    vec![],
+                    vec!["ruzzle.clipboard".to_string()],
+                    vec![],
+                )
+                .with_activation(Activation::OnDemand),
+            )
+            .unwrap();
+        manager
+            .register_module(ModuleRecord::new(
+                "net".to_string(),
+                vec![],
+                vec!["ruzzle.net".to_string()],
+                vec![],
+            ))
+            .unwrap();
+        manager
+            .register_module(
+                ModuleRecord::new(
+                    "audio".to_string(),
+                    vec!["sound-hw".to_string()],
+                    vec!["ruzzle.audio".to_string()],
+                    vec![],
+                )
+                .with_activation(Activation::OnDemand),
+            )
+            .unwrap();
+
+        assert_eq!(
+            manager.resolve_service("ruzzle.clipboard"),
+            Ok("clipboard".to_string())
+        );
+        assert_eq!(state_of(&manager, "clipboard"), ModuleState::Running);
+        assert_eq!(
+            manager.resolve_service("ruzzle.clipboard"),
+            Ok("clipboard".to_string())
+        );
+        assert_eq!(manager.resolve_service("ruzzle.net"), Err(Errno::NotFound));
+        assert_eq!(state_of(&manager, "net"), ModuleState::Stopped);
+        assert_eq!(
+            manager.resolve_service("ruzzle.audio"),
+            Err(Errno::NotFound)
+        );
+        assert_eq!(state_of(&manager, "audio"), ModuleState::Stopped);
+        assert_eq!(Activation::parse("on-demand"), Some(Activation::OnDemand));
+        assert_eq!(Activation::parse("lazy"), None);
+    }
+
+    #[test]
+    fn watchdog_marks_hung_modules_and_applies_restart_policy() {
+        let mut manager = ModuleManager::new();
+        for (name, policy) in [
+            ("audio", RestartPolicy::Never),
+            ("net", RestartPolicy::OnFailure),
+        ] {
+            manager
+                .register_module(
+                    ModuleRecord::new(
+                        name.to_string(),
+                        vec![],
+                        vec![format!("ruzzle.{}", name)],
+                        vec![],
+                    )
+                    .with_restart_policy(policy)
+                    .with_watchdog(10),
+                )
+                .unwrap();
+            manager.start_module(name).unwrap();
+        }
+
+        assert!(manager.watchdog_tick(0).is_empty());
+        manager.pet_watchdog("net", 8).unwrap();
+        assert!(manager.watchdog_tick(10).is_empty());
+        assert_eq!(
+            manager.watchdog_tick(11),
+            vec![WatchdogEvent {
+                module: "audio".to_string(),
+                action: WatchdogAction::Left,
+            }]
+        );
+        assert_eq!(state_of(&manager, "audio"), ModuleState::Hung);
+        assert_eq!(ModuleState::Hung.label(), "hung");
+        assert_eq!(manager.pet_watchdog("audio", 12), Err(Errno::InvalidArg));
+
+        assert_eq!(
+            manager.watchdog_tick(30),
+            vec![WatchdogEvent {
+                module: "net".to_string(),
+                action: WatchdogAction::Restarted,
+            }]
+        );
+        assert_eq!(state_of(&manager, "net"), ModuleState::Running);
+        assert_eq!(
+            manager.service_registry().resolve("ruzzle.net").unwrap(),
+            "net"
+        );
+        assert!(manager.watchdog_tick(35).is_empty());
+
+        manager.restart_module("audio").unwrap();
+        assert_eq!(state_of(&manager, "audio"), ModuleState::Running);
+        assert_eq!(
+            manager.register_module(
+                ModuleRecord::new("idle".to_string(), vec![], vec![], vec![]).with_watchdog(0)
+            ),
+            Err(Errno::InvalidArg)
+        );
+    }
+
+    fn stack_manager() -> ModuleManager {
+        let mut manager = ModuleManager::new();
+        for (name, depends) in [
+            ("fs-service", vec![]),
+            ("net", vec!["fs-service"]),
+            ("web", vec!["net"]),
+            ("audio", vec![]),
+        ] {
+            manager
+                .register_module(ModuleRecord::new(
+                    name.to_string(),
+                    depends.into_iter().map(ToString::to_string).collect(),
+                    vec![format!("ruzzle.{}", name)],
+                    vec![],
+                ))
+                .unwrap();
+        }
+        manager
+    }
+
+    #[test]
+    fn stop_module_cascade_stops_dependents_first() {
+        let mut manager = stack_manager();
+        assert_eq!(
+            manager.start_all().unwrap(),
+            vec!["audio", "fs-service", "net", "web"]
+        );
+        assert!(manager.start_all().unwrap().is_empty());
+
+        assert_eq!(
+            manager.stop_module_cascade("fs-service").unwrap(),
+            vec!["web", "net", "fs-service"]
+        );
+        assert_eq!(state_of(&manager, "audio"), ModuleState::Running);
+        assert!(!manager.service_registry().contains("ruzzle.net"));
+        assert_eq!(manager.stop_module_cascade("missing"), Err(Errno::NotFound));
+    }
+
+    #[test]
+    fn dependents_of_lists_direct_reverse_dependencies() {
+        let mut manager = stack_manager();
+        manager
+            .register_module(ModuleRecord::new(
+                "backup".to_string(),
+                vec!["fs-service".to_string()],
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+        assert_eq!(manager.dependents_of("fs-service"), vec!["backup", "net"]);
+        assert_eq!(manager.dependents_of("net"), vec!["web"]);
+        assert!(manager.dependents_of("web").is_empty());
+        assert!(manager.dependents_of("missing").is_empty());
+    }
+
+    #[test]
+    fn start_all_skips_on_demand_and_stop_all_reverses_order() {
+        let mut manager = stack_manager();
+        manager
+            .register_module(
+                ModuleRecord::new("clipboard".to_string(), vec![], vec![], vec![])
+                    .with_activation(Activation::OnDemand),
+            )
+            .unwrap();
+        manager
+            .register_module(ModuleRecord::new(
+                "cache".to_string(),
+                vec![],
+                vec![],
+                vec!["NetAdmin".to_string()],
+            ))
+            .unwrap();
+        manager.start_all().unwrap();
+        assert_eq!(state_of(&manager, "clipboard"), ModuleState::Stopped);
+        assert_eq!(state_of(&manager, "cache"), ModuleState::Stopped);
+
+        manager.stop_module("audio").unwrap();
+        assert_eq!(
+            manager.stop_all().unwrap(),
+            vec!["web", "net", "fs-service"]
+        );
+        assert!(manager.service_registry().list().is_empty());
+    }
+
+    #[test]
+    fn module_manager_records_lifecycle_events() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(
+                ModuleRecord::new(
+                    "net".to_string(),
+                    vec![],
+                    vec![],
+                    vec!["NetAdmin".to_string()],
+                )
+                .with_granted_caps(["NetAdmin"].into_iter().collect()),
+            )
+            .unwrap();
+        manager.set_time(5);
+        manager.start_module("net").unwrap();
+        manager.set_time(9);
+        manager.stop_module("net").unwrap();
+        manager
+            .grant_capabilities("net", CapabilitySet::new())
+            .unwrap();
+        assert_eq!(manager.restart_module("net"), Err(Errno::PermissionDenied));
+
+        let kinds: Vec<(u64, ModuleEventKind)> = manager
+            .events("net")
+            .iter()
+            .map(|event| (event.tick, event.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (0, ModuleEventKind::Registered),
+                (5, ModuleEventKind::Started),
+                (9, ModuleEventKind::Stopped),
+                (9, ModuleEventKind::Restarted),
+                (9, ModuleEventKind::Failed(Errno::PermissionDenied)),
+            ]
+        );
+        assert_eq!(
+            manager.history().last().unwrap().format(),
+            "module net failed: PermissionDenied"
+        );
+        assert!(manager.events("missing").is_empty());
+    }
+
+    #[test]
+    fn module_manager_forwards_events_to_log_sink() {
+        use std::sync::Mutex;
+        static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+        let mut manager = ModuleManager::new();
+        manager.set_log_sink(|event| LINES.lock().unwrap().push(event.format()));
+        manager
+            .register_module(
+                ModuleRecord::new("fs".to_string(), vec![], vec![], vec![]).with_health_interval(1),
+            )
+            .unwrap();
+        manager.start_module("fs").unwrap();
+        manager.check_health(0);
+        manager.check_health(2);
+        manager.heartbeat("fs", 3).unwrap();
+        for tick in 0..MODULE_HISTORY_LEN as u64 {
+            manager.heartbeat("fs", tick).unwrap();
+            manager.check_health(tick + 2);
+        }
+        assert_eq!(manager.history().len(), MODULE_HISTORY_LEN);
+        assert_eq!(
+            LINES.lock().unwrap()[..5],
+            [
+                "module fs registered",
+                "module fs started",
+                "module fs degraded: heartbeat overdue",
+                "module fs recovered",
+                "module fs degraded: heartbeat overdue",
+            ]
+        );
+    }
+
+    #[test]
+    fn module_manager_counts_consecutive_failures() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(ModuleRecord::new(
+                "net".to_string(),
+                vec![],
+                vec![],
+                vec!["NetAdmin".to_string()],
+            ))
+            .unwrap();
+        for tick in [3, 7] {
+            manager.set_time(tick);
+            assert_eq!(manager.restart_module("net"), Err(Errno::PermissionDenied));
+        }
+        let status = manager.status("net").unwrap();
+        assert_eq!(status.state, ModuleState::Failed);
+        assert_eq!(status.failures, 2);
+        assert_eq!(status.last_error, Some(Errno::PermissionDenied));
+        assert_eq!(status.last_failure, Some(7));
+
+        manager
+            .grant_capabilities("net", ["NetAdmin"].into_iter().collect())
+            .unwrap();
+        manager.restart_module("net").unwrap();
+        let status = manager.status("net").unwrap();
+        assert_eq!(status.state, ModuleState::Running);
+        assert_eq!(status.failures, 0);
+        assert_eq!(status.last_error, Some(Errno::PermissionDenied));
+        assert_eq!(manager.status("missing"), Err(Errno::NotFound));
+    }
+
+    #[derive(Debug, Default)]
+    struct FakeSystem {
+        plugged: Vec<&'static str>,
+        files: Vec<&'static str>,
+        settings: Vec<(&'static str, &'static str)>,
+    }
+
+    impl ConditionContext for FakeSystem {
+        fn slot_empty(&self, slot: &str) -> bool {
+            !self.plugged.contains(&slot)
+        }
+
+        fn file_exists(&self, path: &str) -> bool {
+            self.files.contains(&path)
+        }
+
+        fn setting(&self, key: &str) -> Option<String> {
+            self.settings
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn start_module_checks_conditions() {
+        let mut manager = ModuleManager::new();
+        let conditions = vec![
+            Condition::parse("slot-empty:ruzzle.slot.net@1").unwrap(),
+            Condition::parse("file-exists:/etc/net/wifi").unwrap(),
+            Condition::parse("setting:net.backend=wifi").unwrap(),
+        ];
+        manager
+            .register_module(
+                ModuleRecord::new(
+                    "wifi-manager".to_string(),
+                    vec![],
+                    vec!["ruzzle.net".to_string()],
+                    vec![],
+                )
+                .with_conditions(conditions.clone()),
+            )
+            .unwrap();
+        assert_eq!(
+            manager.unmet_conditions("wifi-manager").unwrap(),
+            conditions
+        );
+        assert_eq!(manager.start_module("wifi-manager"), Err(Errno::InvalidArg));
+        assert_eq!(state_of(&manager, "wifi-manager"), ModuleState::Stopped);
+
+        manager.set_condition_context(Box::new(FakeSystem {
+            plugged: vec!["ruzzle.slot.net@1"],
+            files: vec!["/etc/net/wifi"],
+            settings: vec![("net.backend", "ethernet")],
+        }));
+        assert_eq!(
+            manager.unmet_conditions("wifi-manager").unwrap(),
+            vec![conditions[0].clone(), conditions[2].clone()]
+        );
+
+        manager.set_condition_context(Box::new(FakeSystem {
+            plugged: vec![],
+            files: vec!["/etc/net/wifi"],
+            settings: vec![("net.backend", "wifi")],
+        }));
+        manager.start_module("wifi-manager").unwrap();
+        assert_eq!(state_of(&manager, "wifi-manager"), ModuleState::Running);
+        assert_eq!(manager.unmet_conditions("missing"), Err(Errno::NotFound));
+    }
+
+    #[test]
+    fn condition_parse_rejects_unknown_forms() {
+        assert_eq!(
+            Condition::parse("setting:net.mode=dhcp"),
+            Some(Condition::SettingEquals {
+                key: "net.mode".to_string(),
+                value: "dhcp".to_string(),
+            })
+        );
+        for text in [
+            "slot-empty:",
+            "setting:net.mode",
+            "cpu:x86_64",
+            "file-exists",
+        ] {
+            assert_eq!(Condition::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn boot_report_follows_the_slowest_dependency_chain() {
+        let mut manager = ModuleManager::new();
+        for (name, depends) in [
+            ("fs-service", vec![]),
+            ("settings-service", vec![]),
+            ("net-manager", vec!["fs-service", "settings-service"]),
+            ("file-manager", vec!["fs-service"]),
+        ] {
+            manager
+                .register_module(ModuleRecord::new(
+                    name.to_string(),
+                    depends.into_iter().map(ToString::to_string).collect(),
+                    vec![],
+                    vec![],
+                ))
+                .unwrap();
+        }
+        assert_eq!(manager.boot_report(), BootReport::default());
+
+        manager.set_time(10);
+        manager.start_module("fs-service").unwrap();
+        manager.start_module("settings-service").unwrap();
+        manager.set_time(25);
+        manager.mark_ready("fs-service").unwrap();
+        manager.set_time(40);
+        manager.mark_ready("settings-service").unwrap();
+        manager.start_module("net-manager").unwrap();
+        manager.start_module("file-manager").unwrap();
+        manager.set_time(100);
+        manager.mark_ready("net-manager").unwrap();
+        manager.set_time(120);
+        assert_eq!(manager.mark_ready("net-manager"), Ok(()));
+
+        let report = manager.boot_report();
+        assert_eq!(report.total, 90);
+        let names = |timings: &[BootTiming]| {
+            timings
+                .iter()
+                .map(|timing| timing.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&report.modules),
+            vec![
+                "net-manager",
+                "settings-service",
+                "fs-service",
+                "file-manager"
+            ]
+        );
+        assert_eq!(report.modules[0].duration, 60);
+        assert_eq!(report.modules[3].duration, 0);
+        assert_eq!(
+            names(&report.critical_path),
+            vec!["settings-service", "net-manager"]
+        );
+    }
+
+    #[test]
+    fn mark_ready_requires_a_started_module() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(ModuleRecord::new("fs".to_string(), vec![], vec![], vec![]))
+            .unwrap();
+        assert_eq!(manager.mark_ready("fs"), Err(Errno::InvalidArg));
+        assert_eq!(manager.mark_ready("missing"), Err(Errno::NotFound));
+    }
+
+    #[test]
+    fn module_manager_resolves_start_plan() {
+        let mut manager = ModuleManager::new();
+        manager
+            .register_module(ModuleRecord::new(
+                "a".to_string(),
+                vec![],
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+        manager
+            .register_module(ModuleRecord::new(
+                "b".to_string(),
+                vec!["a".to_string()],
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+        let order = manager.resolve_start_plan().unwrap();
+        assert_eq!(order, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn handle_registry_register_and_lookup() {
+        let mut registry = ServiceRegistry::new();
+        let response = handle_registry_request(
+            &mut registry,
+            RegistryRequest::Register {
+                service: "ruzzle.console".to_string(),
+                module: "console-service".to_string(),
+            },
+        );
+        assert_eq!(response, RegistryResponse::Ack);
+
+        let response = handle_registry_request(
+            &mut registry,
+            RegistryRequest::Lookup {
+                service: "ruzzle.console".to_string(),
+            },
+        );
+        assert_eq!(
+            response,
+            RegistryResponse::Lookup {
+                status: RegistryStatus::Ok,
+                module: Some("console-service".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn handle_registry_register_duplicate() {
+        let mut registry = ServiceRegistry::new();
+        registry
+            .register("ruzzle.console".into(), "console-service".into())
+            .unwrap();
+        let response = handle_registry_request(
+            &mut registry,
+            RegistryRequest::Register {
+                service: "ruzzle.console".to_string(),
+                module: "other".to_string(),
+            },
+        );
+        assert_eq!(
+            response,
+            RegistryResponse::Error {
+                status: RegistryStatus::AlreadyExists
+            }
+        );
+    }
+
+    #[test]
+    fn handle_registry_register_invalid_input() {
+        let mut registry = ServiceRegistry::new();
         let response = handle_registry_request(
             &mut registry,
             RegistryRequest::Register {
@@ -961,6 +2959,199 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handle_registry_unregister_withdraws_services() {
+        let mut registry = ServiceRegistry::new();
+        for service in ["ruzzle.net", "ruzzle.net.dns", "ruzzle.console"] {
+            let module = if service == "ruzzle.console" {
+                "console-service"
+            } else {
+                "net-manager"
+            };
+            registry.register(service.into(), module.into()).unwrap();
+        }
+
+        let unregister = |registry: &mut ServiceRegistry, service: &str| {
+            handle_registry_request(
+                registry,
+                RegistryRequest::Unregister {
+                    service: service.to_string(),
+                },
+            )
+        };
+        assert_eq!(
+            unregister(&mut registry, "ruzzle.console"),
+            RegistryResponse::Ack
+        );
+        assert_eq!(
+            unregister(&mut registry, "ruzzle.console"),
+            RegistryResponse::Error {
+                status: RegistryStatus::NotFound
+            }
+        );
+        assert_eq!(
+            unregister(&mut registry, "console"),
+            RegistryResponse::Error {
+                status: RegistryStatus::Invalid
+            }
+        );
+
+        let unregister_module = |registry: &mut ServiceRegistry| {
+            handle_registry_request(
+                registry,
+                RegistryRequest::UnregisterModule {
+                    module: "net-manager".to_string(),
+                },
+            )
+        };
+        assert_eq!(unregister_module(&mut registry), RegistryResponse::Ack);
+        assert!(registry.list().is_empty());
+        assert_eq!(
+            unregister_module(&mut registry),
+            RegistryResponse::Error {
+                status: RegistryStatus::NotFound
+            }
+        );
+    }
+
+    #[test]
+    fn registry_watch_reports_appearance_and_disappearance() {
+        let mut registry = ServiceRegistry::new();
+        registry
+            .register("ruzzle.fs".into(), "fs-service".into())
+            .unwrap();
+        let watch = registry.watch("ruzzle.fs".into()).unwrap();
+        let all = registry.watch("ruzzle".into()).unwrap();
+        registry
+            .register("ruzzle.fs.index".into(), "indexer".into())
+            .unwrap();
+        registry
+            .register("ruzzle.fsck".into(), "fsck".into())
+            .unwrap();
+        registry.unregister("ruzzle.fs").unwrap();
+
+        let seen = |registry: &mut ServiceRegistry, id: u32| {
+            registry
+                .take_notifications()
+                .into_iter()
+                .filter(|notification| notification.watch == id)
+                .map(|notification| (notification.change, notification.entry.service))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            seen(&mut registry, watch),
+            vec![
+                (ServiceChange::Appeared, "ruzzle.fs".to_string()),
+                (ServiceChange::Appeared, "ruzzle.fs.index".to_string()),
+                (ServiceChange::Disappeared, "ruzzle.fs".to_string()),
+            ]
+        );
+        assert!(registry.take_notifications().is_empty());
+
+        registry.unregister_module("fsck");
+        assert_eq!(
+            seen(&mut registry, all),
+            vec![(ServiceChange::Disappeared, "ruzzle.fsck".to_string())]
+        );
+
+        registry.unwatch(watch).unwrap();
+        registry.unregister("ruzzle.fs.index").unwrap();
+        assert_eq!(registry.take_notifications().len(), 1);
+        assert_eq!(registry.unwatch(watch), Err(Errno::NotFound));
+        assert_eq!(registry.watch("fs".into()), Err(Errno::InvalidArg));
+    }
+
+    #[test]
+    fn handle_registry_watch_requests() {
+        let mut registry = ServiceRegistry::new();
+        let response = handle_registry_request(
+            &mut registry,
+            RegistryRequest::Watch {
+                prefix: "ruzzle.fs".to_string(),
+            },
+        );
+        assert_eq!(response, RegistryResponse::Watching { watch: 1 });
+        assert_eq!(
+            handle_registry_request(
+                &mut registry,
+                RegistryRequest::Watch {
+                    prefix: "fs".to_string()
+                }
+            ),
+            RegistryResponse::Error {
+                status: RegistryStatus::Invalid
+            }
+        );
+        assert_eq!(
+            handle_registry_request(&mut registry, RegistryRequest::Unwatch { watch: 1 }),
+            RegistryResponse::Ack
+        );
+        assert_eq!(
+            handle_registry_request(&mut registry, RegistryRequest::Unwatch { watch: 1 }),
+            RegistryResponse::Error {
+                status: RegistryStatus::NotFound
+            }
+        );
+    }
+
+    #[test]
+    fn handle_registry_instance_requests() {
+        let mut registry = ServiceRegistry::new();
+        let register = |module: &str, priority| RegistryRequest::RegisterInstance {
+            service: "ruzzle.console".to_string(),
+            module: module.to_string(),
+            priority,
+        };
+        assert_eq!(
+            handle_registry_request(&mut registry, register("vga-console", 0)),
+            RegistryResponse::Registered { instance: 1 }
+        );
+        assert_eq!(
+            handle_registry_request(&mut registry, register("serial-console", 3)),
+            RegistryResponse::Registered { instance: 2 }
+        );
+        assert_eq!(
+            handle_registry_request(&mut registry, register("serial-console", 3)),
+            RegistryResponse::Error {
+                status: RegistryStatus::AlreadyExists
+            }
+        );
+        assert_eq!(
+            handle_registry_request(
+                &mut registry,
+                RegistryRequest::SetPolicy {
+                    service: "ruzzle.console".to_string(),
+                    policy: ResolvePolicy::Priority,
+                }
+            ),
+            RegistryResponse::Ack
+        );
+        assert_eq!(
+            handle_registry_request(
+                &mut registry,
+                RegistryRequest::Lookup {
+                    service: "ruzzle.console".to_string()
+                }
+            ),
+            RegistryResponse::Lookup {
+                status: RegistryStatus::Ok,
+                module: Some("serial-console".to_string()),
+            }
+        );
+        assert_eq!(
+            handle_registry_request(
+                &mut registry,
+                RegistryRequest::SetPolicy {
+                    service: "bad".to_string(),
+                    policy: ResolvePolicy::First,
+                }
+            ),
+            RegistryResponse::Error {
+                status: RegistryStatus::Invalid
+            }
+        );
+    }
+
     #[test]
     fn handle_registry_request_bytes_handles_invalid_payload() {
         let mut registry = ServiceRegistry::new();
@@ -986,4 +3177,33 @@ mod tests {
         let response = decode_response(&response_bytes).expect("decode should succeed");
         assert_eq!(response, RegistryResponse::Ack);
     }
+
+    #[test]
+    fn handle_registry_request_bytes_requires_admin_to_withdraw_services() {
+        let mut registry = ServiceRegistry::new();
+        registry
+            .register("ruzzle.net".into(), "net-manager".into())
+            .unwrap();
+        let request = RegistryRequest::UnregisterModule {
+            module: "net-manager".to_string(),
+        };
+        let denied = RegistryResponse::Error {
+            status: RegistryStatus::Denied,
+        };
+
+        let mut bytes = encode_request(&request);
+        let response = handle_registry_request_bytes(&mut registry, &bytes);
+        assert_eq!(decode_response(&response), Ok(denied.clone()));
+
+        append_credential(&mut bytes, &Credential::new("guest", false));
+        let response = handle_registry_request_bytes(&mut registry, &bytes);
+        assert_eq!(decode_response(&response), Ok(denied));
+        assert!(registry.contains("ruzzle.net"));
+
+        let mut bytes = encode_request(&request);
+        append_credential(&mut bytes, &Credential::new("root", true));
+        let response = handle_registry_request_bytes(&mut registry, &bytes);
+        assert_eq!(decode_response(&response), Ok(RegistryResponse::Ack));
+        assert!(!registry.contains("ruzzle.net"));
+    }
 }
@@ -0,0 +1,286 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use hal::Errno;
+use user_fs_service::{FileSystem, FsError};
+
+use crate::{Activation, Condition, ModuleManager, ModuleRecord, RestartPolicy};
+
+/// Directory scanned for module definitions at boot.
+pub const MODULE_CONFIG_DIR: &str = "/system/modules";
+
+/// Why one module definition file was not registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    Fs(FsError),
+    Parse,
+    Register(Errno),
+}
+
+/// Outcome of loading every definition in a directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Registered module names in file order.
+    pub loaded: Vec<String>,
+    /// Definition files that were skipped, with the reason.
+    pub failed: Vec<(String, LoadError)>,
+}
+
+/// Parses a module definition written in the `module.toml` key/value format.
+///
+/// Recognized keys are `name`, `depends`, `wants`, `conditions`, `provides`,
+/// `requires_caps`, `grants`, `requires_paths`, `restart`, `activation`,
+/// `health_interval` and `watchdog`.
+pub fn parse_module_config(input: &str) -> Result<ModuleRecord, Errno> {
+    let mut name: Option<String> = None;
+    let mut depends: Option<Vec<String>> = None;
+    let mut wants: Option<Vec<String>> = None;
+    let mut conditions: Option<Vec<Condition>> = None;
+    let mut provides: Option<Vec<String>> = None;
+    let mut requires_caps: Option<Vec<String>> = None;
+    let mut grants: Option<Vec<String>> = None;
+    let mut requires_paths: Option<Vec<String>> = None;
+    let mut restart: Option<RestartPolicy> = None;
+    let mut activation: Option<Activation> = None;
+    let mut health_interval: Option<u64> = None;
+    let mut watchdog: Option<u64> = None;
+
+    for line in input.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let (key, value) = trimmed.split_once('=').ok_or(Errno::InvalidArg)?;
+        let value = value.trim();
+        match key.trim() {
+            "name" => set_once(&mut name, parse_string(value)?)?,
+            "depends" => set_once(&mut depends, parse_list(value)?)?,
+            "wants" => set_once(&mut wants, parse_list(value)?)?,
+            "conditions" => {
+                let parsed = parse_list(value)?
+                    .iter()
+                    .map(|text| Condition::parse(text).ok_or(Errno::InvalidArg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                set_once(&mut conditions, parsed)?;
+            }
+            "provides" => set_once(&mut provides, parse_list(value)?)?,
+            "requires_caps" => set_once(&mut requires_caps, parse_list(value)?)?,
+            "grants" => set_once(&mut grants, parse_list(value)?)?,
+            "requires_paths" => set_once(&mut requires_paths, parse_list(value)?)?,
+            "restart" => {
+                let policy =
+                    RestartPolicy::parse(&parse_string(value)?).ok_or(Errno::InvalidArg)?;
+                set_once(&mut restart, policy)?;
+            }
+            "activation" => {
+                let mode = Activation::parse(&parse_string(value)?).ok_or(Errno::InvalidArg)?;
+                set_once(&mut activation, mode)?;
+            }
+            "health_interval" => {
+                let ticks = value.parse().map_err(|_| Errno::InvalidArg)?;
+                set_once(&mut health_interval, ticks)?;
+            }
+            "watchdog" => {
+                let ticks = value.parse().map_err(|_| Errno::InvalidArg)?;
+                set_once(&mut watchdog, ticks)?;
+            }
+            _ => return Err(Errno::InvalidArg),
+        }
+    }
+
+    let mut record = ModuleRecord::new(
+        name.ok_or(Errno::InvalidArg)?,
+        depends.unwrap_or_default(),
+        provides.unwrap_or_default(),
+        requires_caps.unwrap_or_default(),
+    )
+    .with_granted_caps(grants.unwrap_or_default().into_iter().collect())
+    .with_wants(wants.unwrap_or_default())
+    .with_conditions(conditions.unwrap_or_default())
+    .with_required_paths(requires_paths.unwrap_or_default())
+    .with_restart_policy(restart.unwrap_or_default())
+    .with_activation(activation.unwrap_or_default());
+    if let Some(ticks) = health_interval {
+        record = record.with_health_interval(ticks);
+    }
+    if let Some(ticks) = watchdog {
+        record = record.with_watchdog(ticks);
+    }
+    Ok(record)
+}
+
+/// Registers every `*.toml` definition in `dir` with the module manager.
+///
+/// A bad file is reported and skipped so the remaining modules still load;
+/// only an unreadable directory fails the whole load.
+pub fn load_module_configs(
+    fs: &FileSystem,
+    dir: &str,
+    manager: &mut ModuleManager,
+) -> Result<LoadReport, FsError> {
+    let mut report = LoadReport::default();
+    for entry in fs.list_dir(dir)? {
+        if !entry.ends_with(".toml") {
+            continue;
+        }
+        let path = if dir.ends_with('/') {
+            alloc::format!("{}{}", dir, entry)
+        } else {
+            alloc::format!("{}/{}", dir, entry)
+        };
+        match load_one(fs, &path, manager) {
+            Ok(name) => report.loaded.push(name),
+            Err(error) => report.failed.push((path, error)),
+        }
+    }
+    Ok(report)
+}
+
+fn load_one(fs: &FileSystem, path: &str, manager: &mut ModuleManager) -> Result<String, LoadError> {
+    let bytes = fs.read_file(path).map_err(LoadError::Fs)?;
+    let text = core::str::from_utf8(&bytes).map_err(|_| LoadError::Parse)?;
+    let record = parse_module_config(text).map_err(|_| LoadError::Parse)?;
+    let name = record.name.clone();
+    manager
+        .register_module(record)
+        .map_err(LoadError::Register)?;
+    Ok(name)
+}
+
+fn set_once<T>(field: &mut Option<T>, value: T) -> Result<(), Errno> {
+    if field.is_some() {
+        return Err(Errno::InvalidArg);
+    }
+    *field = Some(value);
+    Ok(())
+}
+
+fn parse_string(value: &str) -> Result<String, Errno> {
+    value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .map(ToString::to_string)
+        .ok_or(Errno::InvalidArg)
+}
+
+fn parse_list(value: &str) -> Result<Vec<String>, Errno> {
+    let inner = value
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or(Errno::InvalidArg)?
+        .trim();
+    if inner.is_empty() {
+        return Ok(Vec::new());
+    }
+    inner
+        .split(',')
+        .map(|item| parse_string(item.trim()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModuleState;
+
+    const NET: &str = r#"
+        # network manager
+        name = "net-manager"
+        depends = ["fs-service"]
+        wants = ["dns-cache"]
+        provides = ["ruzzle.net"]
+        requires_caps = ["EndpointCreate"]
+        grants = ["EndpointCreate", "NetAdmin"]
+        requires_paths = ["/etc/net"]
+        restart = "on-failure"
+        activation = "on-demand"
+        health_interval = 50
+        watchdog = 200
+    "#;
+
+    #[test]
+    fn parse_module_config_reads_every_key() {
+        let record = parse_module_config(NET).unwrap();
+        assert_eq!(record.name, "net-manager");
+        assert_eq!(record.depends, vec!["fs-service"]);
+        assert_eq!(record.wants, vec!["dns-cache"]);
+        assert_eq!(record.provides, vec!["ruzzle.net"]);
+        assert_eq!(record.requires_caps, vec!["EndpointCreate"]);
+        assert!(record.granted_caps.contains("NetAdmin"));
+        assert_eq!(record.requires_paths, vec!["/etc/net"]);
+        assert_eq!(record.restart, RestartPolicy::OnFailure);
+        assert_eq!(record.activation, Activation::OnDemand);
+        assert_eq!(record.health_interval, Some(50));
+        assert_eq!(record.watchdog_timeout, Some(200));
+        assert_eq!(record.state, ModuleState::Stopped);
+
+        let minimal = parse_module_config("name = \"fs-service\"\n").unwrap();
+        assert_eq!(minimal.restart, RestartPolicy::Never);
+        assert!(minimal.depends.is_empty());
+
+        let conditional = parse_module_config(
+            "name = \"wifi\"\nconditions = [\"slot-empty:ruzzle.slot.net@1\", \"file-exists:/etc/net\"]",
+        )
+        .unwrap();
+        assert_eq!(
+            conditional.conditions,
+            vec![
+                Condition::SlotEmpty("ruzzle.slot.net@1".to_string()),
+                Condition::FileExists("/etc/net".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_module_config_rejects_bad_input() {
+        for text in [
+            "depends = []",
+            "name = fs",
+            "name = \"a\"\nname = \"b\"",
+            "name = \"a\"\nrestart = \"sometimes\"",
+            "name = \"a\"\nhealth_interval = soon",
+            "name = \"a\"\ncolor = \"red\"",
+            "name = \"a\"\nconditions = [\"uptime:5\"]",
+            "name = \"a\"\ndepends = [\"b\",]",
+            "name",
+        ] {
+            assert_eq!(parse_module_config(text), Err(Errno::InvalidArg), "{text}");
+        }
+    }
+
+    #[test]
+    fn load_module_configs_registers_files_and_reports_failures() {
+        let mut fs = FileSystem::new();
+        fs.mkdir_p(MODULE_CONFIG_DIR).unwrap();
+        fs.write_file("/system/modules/net.toml", NET.as_bytes())
+            .unwrap();
+        fs.write_file("/system/modules/fs.toml", b"name = \"fs-service\"\n")
+            .unwrap();
+        fs.write_file("/system/modules/dup.toml", b"name = \"fs-service\"\n")
+            .unwrap();
+        fs.write_file("/system/modules/bad.toml", b"name = oops\n")
+            .unwrap();
+        fs.write_file("/system/modules/README", b"not a module")
+            .unwrap();
+
+        let mut manager = ModuleManager::new();
+        let report = load_module_configs(&fs, MODULE_CONFIG_DIR, &mut manager).unwrap();
+        assert_eq!(report.loaded, vec!["fs-service", "net-manager"]);
+        assert_eq!(
+            report.failed,
+            vec![
+                ("/system/modules/bad.toml".to_string(), LoadError::Parse),
+                (
+                    "/system/modules/fs.toml".to_string(),
+                    LoadError::Register(Errno::InvalidArg)
+                ),
+            ]
+        );
+        manager.start_module("fs-service").unwrap();
+        manager.start_module("net-manager").unwrap();
+        assert_eq!(
+            load_module_configs(&fs, "/system/missing", &mut manager),
+            Err(FsError::NotFound)
+        );
+    }
+}
@@ -3,10 +3,12 @@
 extern crate alloc;
 
 use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
-use user_net_service::{NetError, NetManager, RouteError};
+use user_net_service::events::NetSubscription;
+use user_net_service::{parse_ipv4_cidr, NetError, NetManager, RouteError};
 
 /// Supported network profiles.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -15,12 +17,80 @@ pub enum NetProfile {
     Static { iface: String, ipv4: String, gateway: Option<String> },
 }
 
+impl NetProfile {
+    /// Returns the interface configured by the profile.
+    pub fn iface(&self) -> &str {
+        match self {
+            NetProfile::Dhcp { iface } | NetProfile::Static { iface, .. } => iface,
+        }
+    }
+
+    /// Applies the profile to the given network manager.
+    pub fn apply_to(&self, net: &mut NetManager) -> Result<(), NetProfileError> {
+        match self {
+            NetProfile::Dhcp { iface } => {
+                net.set_up(iface, true).map_err(NetProfileError::Net)?;
+                let _ = net.set_ipv4(iface, None);
+                Ok(())
+            }
+            NetProfile::Static {
+                iface,
+                ipv4,
+                gateway,
+            } => {
+                net.set_up(iface, true).map_err(NetProfileError::Net)?;
+                net.set_ipv4(iface, Some(ipv4))
+                    .map_err(NetProfileError::Net)?;
+                if let Some(gateway) = gateway {
+                    net.add_route("default", iface, Some(gateway), 0)
+                        .map_err(NetProfileError::Route)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns true if the network manager already matches the profile.
+    pub fn is_applied(&self, net: &NetManager) -> bool {
+        let Some(current) = net
+            .list()
+            .into_iter()
+            .find(|entry| entry.name == self.iface())
+        else {
+            return false;
+        };
+        match self {
+            NetProfile::Dhcp { .. } => current.up && current.ipv4.is_none(),
+            NetProfile::Static {
+                iface,
+                ipv4,
+                gateway,
+            } => {
+                current.up
+                    && parse_ipv4_cidr(ipv4).is_some_and(|(addr, prefix)| {
+                        current.ipv4 == Some(addr) && current.prefix == Some(prefix)
+                    })
+                    && (gateway.is_none()
+                        || net.list_routes().iter().any(|route| {
+                            route.destination == "default"
+                                && &route.iface == iface
+                                && route.gateway == *gateway
+                        }))
+            }
+        }
+    }
+}
+
 /// Errors raised by the net manager.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NetProfileError {
     InvalidName,
     AlreadyExists,
     NotFound,
+    /// `changed_profiles` was called before `watch`.
+    NotWatching,
+    /// Line of a saved profile config that could not be parsed (1-based).
+    InvalidConfig(usize),
     Net(NetError),
     Route(RouteError),
 }
@@ -29,6 +99,9 @@ pub enum NetProfileError {
 #[derive(Debug, Default, Clone)]
 pub struct NetProfileManager {
     profiles: BTreeMap<String, NetProfile>,
+    /// Profile `autoselect` tries before any other.
+    boot_profile: Option<String>,
+    subscription: Option<NetSubscription>,
 }
 
 impl NetProfileManager {
@@ -36,6 +109,8 @@ impl NetProfileManager {
     pub fn new() -> Self {
         Self {
             profiles: BTreeMap::new(),
+            boot_profile: None,
+            subscription: None,
         }
     }
 
@@ -51,9 +126,12 @@ impl NetProfileManager {
         Ok(())
     }
 
-    /// Removes a profile by name.
+    /// Removes a profile by name, clearing it as the boot profile.
     pub fn remove_profile(&mut self, name: &str) -> Result<(), NetProfileError> {
         if self.profiles.remove(name).is_some() {
+            if self.boot_profile.as_deref() == Some(name) {
+                self.boot_profile = None;
+            }
             Ok(())
         } else {
             Err(NetProfileError::NotFound)
@@ -67,32 +145,161 @@ impl NetProfileManager {
         net: &mut NetManager,
     ) -> Result<(), NetProfileError> {
         let profile = self.profiles.get(name).ok_or(NetProfileError::NotFound)?;
-        match profile {
-            NetProfile::Dhcp { iface } => {
-                net.set_up(iface, true).map_err(NetProfileError::Net)?;
-                let _ = net.set_ipv4(iface, None);
-                Ok(())
+        profile.apply_to(net)
+    }
+
+    /// Returns the profile stored under `name`.
+    pub fn profile(&self, name: &str) -> Option<&NetProfile> {
+        self.profiles.get(name)
+    }
+
+    /// Lists profile names.
+    pub fn list_profiles(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
+    /// Returns the designated boot profile, if any.
+    pub fn boot_profile(&self) -> Option<&str> {
+        self.boot_profile.as_deref()
+    }
+
+    /// Designates an existing profile as the boot profile, or clears it with None.
+    pub fn set_boot_profile(&mut self, name: Option<&str>) -> Result<(), NetProfileError> {
+        if let Some(name) = name {
+            if !self.profiles.contains_key(name) {
+                return Err(NetProfileError::NotFound);
             }
-            NetProfile::Static {
-                iface,
-                ipv4,
-                gateway,
-            } => {
-                net.set_up(iface, true).map_err(NetProfileError::Net)?;
-                net.set_ipv4(iface, Some(ipv4))
-                    .map_err(NetProfileError::Net)?;
-                if gateway.is_some() {
-                    net.add_route("default", iface)
-                        .map_err(NetProfileError::Route)?;
+        }
+        self.boot_profile = name.map(str::to_string);
+        Ok(())
+    }
+
+    /// Applies the boot profile, or else the first profile by name whose
+    /// interface exists in `net`.
+    ///
+    /// Profiles whose interface is missing are passed over. Returns the
+    /// selected profile, or None when no profile fits; a profile that
+    /// `is_applied` already is selected without being applied again.
+    pub fn autoselect(&self, net: &mut NetManager) -> Result<Option<String>, NetProfileError> {
+        let present: Vec<String> = net.list().into_iter().map(|iface| iface.name).collect();
+        let Some((name, profile)) = self
+            .boot_profile
+            .iter()
+            .chain(self.profiles.keys())
+            .filter_map(|name| self.profiles.get_key_value(name))
+            .find(|(_, profile)| present.iter().any(|iface| iface == profile.iface()))
+        else {
+            return Ok(None);
+        };
+        if !profile.is_applied(net) {
+            profile.apply_to(net)?;
+        }
+        Ok(Some(name.clone()))
+    }
+
+    /// Adds a profile, replacing any existing one with the same name.
+    pub fn set_profile(&mut self, name: &str, profile: NetProfile) -> Result<(), NetProfileError> {
+        if !is_valid_name(name) {
+            return Err(NetProfileError::InvalidName);
+        }
+        self.profiles.insert(name.to_string(), profile);
+        Ok(())
+    }
+
+    /// Serializes profiles as `<name> dhcp <iface>` or
+    /// `<name> static <iface> <addr[/prefix]> [gateway]` lines.
+    pub fn to_config_text(&self) -> String {
+        let mut out = String::new();
+        for (name, profile) in &self.profiles {
+            let line = match profile {
+                NetProfile::Dhcp { iface } => format!("{} dhcp {}\n", name, iface),
+                NetProfile::Static {
+                    iface,
+                    ipv4,
+                    gateway,
+                } => match gateway {
+                    Some(gateway) => format!("{} static {} {} {}\n", name, iface, ipv4, gateway),
+                    None => format!("{} static {} {}\n", name, iface, ipv4),
+                },
+            };
+            out.push_str(&line);
+        }
+        out
+    }
+
+    /// Replaces the profiles with those saved by `to_config_text`.
+    ///
+    /// Blank lines and `#` comments are ignored. Nothing changes when a line
+    /// is malformed; a boot profile missing from `text` is cleared.
+    pub fn load_config(&mut self, text: &str) -> Result<(), NetProfileError> {
+        let mut profiles = BTreeMap::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = NetProfileError::InvalidConfig(index + 1);
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (name, profile) = match words.as_slice() {
+                [name, "dhcp", iface] => (
+                    name,
+                    NetProfile::Dhcp {
+                        iface: iface.to_string(),
+                    },
+                ),
+                [name, "static", iface, ipv4, gateway @ ..] if gateway.len() <= 1 => (
+                    name,
+                    NetProfile::Static {
+                        iface: iface.to_string(),
+                        ipv4: ipv4.to_string(),
+                        gateway: gateway.first().map(|gateway| gateway.to_string()),
+                    },
+                ),
+                _ => return Err(invalid),
+            };
+            if !is_valid_name(name) || !is_valid_name(profile.iface()) {
+                return Err(invalid);
+            }
+            if let NetProfile::Static { ipv4, .. } = &profile {
+                if parse_ipv4_cidr(ipv4).is_none() {
+                    return Err(invalid);
                 }
-                Ok(())
             }
+            profiles.insert(name.to_string(), profile);
         }
+        self.profiles = profiles;
+        if let Some(name) = &self.boot_profile {
+            if !self.profiles.contains_key(name) {
+                self.boot_profile = None;
+            }
+        }
+        Ok(())
     }
 
-    /// Lists profile names.
-    pub fn list_profiles(&self) -> Vec<String> {
-        self.profiles.keys().cloned().collect()
+    /// Subscribes to `net` events, replacing any earlier subscription.
+    pub fn watch(&mut self, net: &mut NetManager) {
+        if let Some(id) = self.subscription.take() {
+            let _ = net.unsubscribe(id);
+        }
+        self.subscription = Some(net.subscribe());
+    }
+
+    /// Returns profiles whose interface changed since the last call, sorted by name.
+    ///
+    /// Callers re-check only these with `NetProfile::is_applied` instead of
+    /// comparing every profile against `NetManager::list`.
+    pub fn changed_profiles(
+        &mut self,
+        net: &mut NetManager,
+    ) -> Result<Vec<String>, NetProfileError> {
+        let id = self.subscription.ok_or(NetProfileError::NotWatching)?;
+        let events = net.poll_events(id).map_err(NetProfileError::Net)?;
+        Ok(self
+            .profiles
+            .iter()
+            .filter(|(_, profile)| events.iter().any(|event| event.iface() == profile.iface()))
+            .map(|(name, _)| name.clone())
+            .collect())
     }
 }
 
@@ -247,6 +454,8 @@ mod tests {
         let mut net = manager_with_iface();
         profiles.apply_profile("static", &mut net).unwrap();
         assert_eq!(net.list_routes().len(), 1);
+        let route = net.lookup_route("8.8.8.8").unwrap();
+        assert_eq!(route.gateway.as_deref(), Some("10.0.0.1"));
     }
 
     #[test]
@@ -321,7 +530,7 @@ mod tests {
             )
             .unwrap();
         let mut net = manager_with_iface();
-        net.add_route("default", "eth0").unwrap();
+        net.add_route("default", "eth0", None, 0).unwrap();
         assert_eq!(
             profiles.apply_profile("static", &mut net),
             Err(NetProfileError::Route(RouteError::AlreadyExists))
@@ -338,6 +547,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn profile_reports_when_applied() {
+        let dhcp = NetProfile::Dhcp {
+            iface: "eth0".to_string(),
+        };
+        let static_profile = NetProfile::Static {
+            iface: "eth0".to_string(),
+            ipv4: "10.0.0.10".to_string(),
+            gateway: Some("10.0.0.1".to_string()),
+        };
+        assert_eq!(dhcp.iface(), "eth0");
+        assert_eq!(static_profile.iface(), "eth0");
+        assert!(!dhcp.is_applied(&NetManager::new()));
+
+        let mut net = manager_with_iface();
+        assert!(!dhcp.is_applied(&net));
+        dhcp.apply_to(&mut net).unwrap();
+        assert!(dhcp.is_applied(&net));
+        assert!(!static_profile.is_applied(&net));
+
+        net.set_ipv4("eth0", Some("10.0.0.10")).unwrap();
+        assert!(!dhcp.is_applied(&net));
+        assert!(!static_profile.is_applied(&net));
+        net.add_route("default", "eth0", Some("10.0.0.2"), 0)
+            .unwrap();
+        assert!(!static_profile.is_applied(&net));
+        net.remove_route("default").unwrap();
+        net.add_route("default", "eth0", Some("10.0.0.1"), 0)
+            .unwrap();
+        assert!(static_profile.is_applied(&net));
+
+        let no_gateway = NetProfile::Static {
+            iface: "eth0".to_string(),
+            ipv4: "10.0.0.10".to_string(),
+            gateway: None,
+        };
+        assert!(no_gateway.is_applied(&net));
+        let subnet = NetProfile::Static {
+            iface: "eth0".to_string(),
+            ipv4: "10.0.0.10/24".to_string(),
+            gateway: None,
+        };
+        assert!(!subnet.is_applied(&net));
+        subnet.apply_to(&mut net).unwrap();
+        assert!(subnet.is_applied(&net));
+        assert!(!no_gateway.is_applied(&net));
+        assert_eq!(net.list_routes()[0].destination, "10.0.0.0/24");
+        net.set_up("eth0", false).unwrap();
+        assert!(!no_gateway.is_applied(&net));
+    }
+
     #[test]
     fn remove_profile() {
         let mut profiles = NetProfileManager::new();
@@ -355,4 +615,183 @@ mod tests {
             Err(NetProfileError::NotFound)
         );
     }
+
+    #[test]
+    fn watched_profiles_report_interface_changes() {
+        let mut profiles = NetProfileManager::new();
+        for (name, iface) in [("home", "eth0"), ("lab", "eth1"), ("office", "eth0")] {
+            profiles
+                .add_profile(
+                    name,
+                    NetProfile::Dhcp {
+                        iface: iface.to_string(),
+                    },
+                )
+                .unwrap();
+        }
+        let mut net = manager_with_iface();
+        assert_eq!(
+            profiles.changed_profiles(&mut net),
+            Err(NetProfileError::NotWatching)
+        );
+
+        profiles.watch(&mut net);
+        assert_eq!(profiles.changed_profiles(&mut net), Ok(Vec::new()));
+        profiles.apply_profile("home", &mut net).unwrap();
+        assert!(profiles.profiles["office"].is_applied(&net));
+        net.set_ipv4("eth0", Some("10.0.0.5")).unwrap();
+        assert_eq!(
+            profiles.changed_profiles(&mut net),
+            Ok(vec!["home".to_string(), "office".to_string()])
+        );
+        assert_eq!(profiles.changed_profiles(&mut net), Ok(Vec::new()));
+
+        profiles.watch(&mut net);
+        net.add_interface("eth1").unwrap();
+        assert_eq!(
+            profiles.changed_profiles(&mut net),
+            Ok(vec!["lab".to_string()])
+        );
+    }
+
+    #[test]
+    fn profiles_round_trip_through_config_text() {
+        let mut profiles = NetProfileManager::new();
+        profiles
+            .set_profile(
+                "office",
+                NetProfile::Static {
+                    iface: "eth0".to_string(),
+                    ipv4: "10.0.0.2/24".to_string(),
+                    gateway: Some("10.0.0.1".to_string()),
+                },
+            )
+            .unwrap();
+        profiles
+            .set_profile(
+                "lab",
+                NetProfile::Static {
+                    iface: "eth1".to_string(),
+                    ipv4: "10.1.0.2".to_string(),
+                    gateway: None,
+                },
+            )
+            .unwrap();
+        profiles
+            .set_profile(
+                "home",
+                NetProfile::Dhcp {
+                    iface: "eth0".to_string(),
+                },
+            )
+            .unwrap();
+        let text = profiles.to_config_text();
+        assert_eq!(
+            text,
+            "home dhcp eth0\nlab static eth1 10.1.0.2\noffice static eth0 10.0.0.2/24 10.0.0.1\n"
+        );
+
+        let mut loaded = NetProfileManager::new();
+        loaded.load_config(&format!("# saved\n\n{}", text)).unwrap();
+        assert_eq!(loaded.list_profiles(), profiles.list_profiles());
+        assert_eq!(loaded.profiles, profiles.profiles);
+        assert_eq!(
+            profiles.set_profile(
+                "Home",
+                NetProfile::Dhcp {
+                    iface: "eth0".to_string(),
+                }
+            ),
+            Err(NetProfileError::InvalidName)
+        );
+    }
+
+    #[test]
+    fn load_config_rejects_bad_lines_without_changes() {
+        let mut profiles = NetProfileManager::new();
+        profiles.load_config("home dhcp eth0").unwrap();
+        for (text, line) in [
+            ("home dhcp", 1),
+            ("home dhcp eth0 extra", 1),
+            ("ok dhcp eth0\nHome dhcp eth0", 2),
+            ("lab static eth1 10.0.0.300", 1),
+            ("lab static eth1 10.0.0.2 10.0.0.1 extra", 1),
+            ("lab manual eth1", 1),
+        ] {
+            assert_eq!(
+                profiles.load_config(text),
+                Err(NetProfileError::InvalidConfig(line)),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(profiles.list_profiles(), vec!["home".to_string()]);
+    }
+
+    #[test]
+    fn boot_profile_must_exist_and_follows_removal() {
+        let mut profiles = NetProfileManager::new();
+        assert_eq!(
+            profiles.set_boot_profile(Some("home")),
+            Err(NetProfileError::NotFound)
+        );
+        profiles
+            .load_config("home dhcp eth0\nlab dhcp eth1")
+            .unwrap();
+        profiles.set_boot_profile(Some("home")).unwrap();
+        assert_eq!(profiles.boot_profile(), Some("home"));
+        profiles.remove_profile("home").unwrap();
+        assert_eq!(profiles.boot_profile(), None);
+
+        profiles.set_boot_profile(Some("lab")).unwrap();
+        profiles.load_config("lab dhcp eth2").unwrap();
+        assert_eq!(profiles.boot_profile(), Some("lab"));
+        profiles.load_config("home dhcp eth0").unwrap();
+        assert_eq!(profiles.boot_profile(), None);
+    }
+
+    #[test]
+    fn autoselect_prefers_boot_profile_then_present_interfaces() {
+        let mut profiles = NetProfileManager::new();
+        profiles
+            .load_config(
+                "a-wifi dhcp wlan0\nhome dhcp eth0\noffice static eth1 10.0.0.2/24 10.0.0.1",
+            )
+            .unwrap();
+        let mut net = NetManager::new();
+        assert_eq!(profiles.autoselect(&mut net), Ok(None));
+
+        net.add_interface("eth0").unwrap();
+        net.add_interface("eth1").unwrap();
+        assert_eq!(profiles.autoselect(&mut net), Ok(Some("home".to_string())));
+        assert!(profiles.profiles["home"].is_applied(&net));
+
+        profiles.set_boot_profile(Some("office")).unwrap();
+        assert_eq!(
+            profiles.autoselect(&mut net),
+            Ok(Some("office".to_string()))
+        );
+        assert!(profiles.profiles["office"].is_applied(&net));
+        assert_eq!(
+            profiles.autoselect(&mut net),
+            Ok(Some("office".to_string()))
+        );
+
+        net.remove_interface("eth1").unwrap();
+        assert_eq!(profiles.autoselect(&mut net), Ok(Some("home".to_string())));
+    }
+
+    #[test]
+    fn autoselect_reports_apply_errors() {
+        let mut profiles = NetProfileManager::new();
+        profiles
+            .load_config("office static eth0 10.0.0.2/24 10.0.0.1")
+            .unwrap();
+        let mut net = manager_with_iface();
+        net.add_route("default", "eth0", None, 0).unwrap();
+        assert_eq!(
+            profiles.autoselect(&mut net),
+            Err(NetProfileError::Route(RouteError::AlreadyExists))
+        );
+    }
 }
@@ -0,0 +1,260 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{
+    is_valid_gateway, is_valid_iface_name, is_valid_route_destination, parse_ipv4_cidr, MacAddr,
+    NetError, NetManager, LOOPBACK_IFACE, MAX_ROUTE_METRIC,
+};
+
+/// Interface line of a saved network config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedInterface {
+    pub name: String,
+    pub up: bool,
+    pub mac: MacAddr,
+    /// Address as `addr/prefix`, or None when unset.
+    pub cidr: Option<String>,
+}
+
+/// Route line of a saved network config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedRoute {
+    pub destination: String,
+    pub iface: String,
+    pub gateway: Option<String>,
+    pub metric: u32,
+}
+
+/// Interfaces and routes read back from `NetManager::to_config_text`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetConfig {
+    pub interfaces: Vec<SavedInterface>,
+    pub routes: Vec<SavedRoute>,
+}
+
+impl NetManager {
+    /// Serializes interfaces and manual routes, one per line.
+    ///
+    /// Lines read `iface <name> <up|down> <mac> <addr/prefix|->` and
+    /// `route <dest> <iface> [via <gateway>] [metric <n>]`. The loopback
+    /// interface and on-link routes are left out because they are recreated
+    /// from `init_defaults` and interface addresses.
+    pub fn to_config_text(&self) -> String {
+        let mut out = String::new();
+        for iface in self.list() {
+            if iface.name == LOOPBACK_IFACE {
+                continue;
+            }
+            out.push_str(&format!(
+                "iface {} {} {} {}\n",
+                iface.name,
+                if iface.up { "up" } else { "down" },
+                iface.mac.format(),
+                iface.cidr().unwrap_or_else(|| "-".to_string())
+            ));
+        }
+        for route in self.list_routes() {
+            if route.on_link {
+                continue;
+            }
+            out.push_str(&format!("route {} {}", route.destination, route.iface));
+            if let Some(gateway) = &route.gateway {
+                out.push_str(&format!(" via {}", gateway));
+            }
+            out.push_str(&format!(" metric {}\n", route.metric));
+        }
+        out
+    }
+
+    /// Applies a saved config on top of the current state.
+    ///
+    /// Missing interfaces are created and existing routes are kept; entries
+    /// that cannot be applied are skipped and returned as `iface <name>` or
+    /// `route <dest>`.
+    pub fn restore_config(&mut self, config: &NetConfig) -> Vec<String> {
+        let mut skipped = Vec::new();
+        for saved in &config.interfaces {
+            let exists = self.list().iter().any(|iface| iface.name == saved.name);
+            let applied = (if exists {
+                Ok(())
+            } else {
+                self.add_interface(&saved.name)
+            })
+            .and_then(|_| self.set_mac(&saved.name, saved.mac))
+            .and_then(|_| self.set_ipv4(&saved.name, saved.cidr.as_deref()))
+            .and_then(|_| self.set_up(&saved.name, saved.up));
+            if applied.is_err() {
+                skipped.push(format!("iface {}", saved.name));
+            }
+        }
+        for saved in &config.routes {
+            if self
+                .list_routes()
+                .iter()
+                .any(|route| route.destination == saved.destination)
+            {
+                continue;
+            }
+            let added = self.add_route(
+                &saved.destination,
+                &saved.iface,
+                saved.gateway.as_deref(),
+                saved.metric,
+            );
+            if added.is_err() {
+                skipped.push(format!("route {}", saved.destination));
+            }
+        }
+        skipped
+    }
+}
+
+/// Parses a saved network config.
+///
+/// Blank lines and `#` comments are ignored; any other malformed line fails
+/// with `NetError::InvalidConfig` carrying its 1-based number.
+pub fn parse_net_config(text: &str) -> Result<NetConfig, NetError> {
+    let mut config = NetConfig::default();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = NetError::InvalidConfig(index + 1);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["iface", name, state, mac, cidr] => {
+                let up = match *state {
+                    "up" => true,
+                    "down" => false,
+                    _ => return Err(invalid),
+                };
+                let mac = MacAddr::parse(mac).ok_or(invalid.clone())?;
+                let cidr = match *cidr {
+                    "-" => None,
+                    spec => {
+                        let (addr, prefix) = parse_ipv4_cidr(spec).ok_or(invalid.clone())?;
+                        Some(format!("{}/{}", addr, prefix))
+                    }
+                };
+                if !is_valid_iface_name(name) {
+                    return Err(invalid);
+                }
+                config.interfaces.push(SavedInterface {
+                    name: name.to_string(),
+                    up,
+                    mac,
+                    cidr,
+                });
+            }
+            ["route", destination, iface, rest @ ..] => {
+                let mut route = SavedRoute {
+                    destination: destination.to_string(),
+                    iface: iface.to_string(),
+                    gateway: None,
+                    metric: 0,
+                };
+                let mut options = rest.iter();
+                while let Some(option) = options.next() {
+                    let value = options.next().ok_or(invalid.clone())?;
+                    match *option {
+                        "via" if route.gateway.is_none() && is_valid_gateway(value) => {
+                            route.gateway = Some(value.to_string());
+                        }
+                        "metric" => {
+                            route.metric = value
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|metric| *metric <= MAX_ROUTE_METRIC)
+                                .ok_or(invalid.clone())?;
+                        }
+                        _ => return Err(invalid),
+                    }
+                }
+                if !is_valid_route_destination(destination) || !is_valid_iface_name(iface) {
+                    return Err(invalid);
+                }
+                config.routes.push(route);
+            }
+            _ => return Err(invalid),
+        }
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_round_trips_interfaces_and_routes() {
+        let mut net = NetManager::new();
+        net.init_defaults();
+        net.add_interface("eth0").unwrap();
+        net.add_interface("wlan0").unwrap();
+        net.set_up("eth0", true).unwrap();
+        net.set_ipv4("eth0", Some("10.0.0.2/24")).unwrap();
+        net.add_route("default", "eth0", Some("10.0.0.1"), 5)
+            .unwrap();
+        net.add_route("192.168.0.0/16", "wlan0", None, 0).unwrap();
+
+        let text = net.to_config_text();
+        assert!(!text.contains(" lo "));
+        assert!(!text.contains("10.0.0.0/24"));
+        assert!(text.contains("iface eth0 up 02:52:5a:00:00:00 10.0.0.2/24\n"));
+        assert!(text.contains("route default eth0 via 10.0.0.1 metric 5\n"));
+
+        let config = parse_net_config(&text).unwrap();
+        assert_eq!(config.interfaces.len(), 2);
+        assert_eq!(config.routes.len(), 2);
+        let mut restored = NetManager::new();
+        restored.init_defaults();
+        assert!(restored.restore_config(&config).is_empty());
+        assert_eq!(restored.list(), net.list());
+        assert_eq!(restored.list_routes(), net.list_routes());
+        assert!(restored.restore_config(&config).is_empty());
+        assert_eq!(restored.list_routes(), net.list_routes());
+    }
+
+    #[test]
+    fn restore_skips_entries_that_do_not_apply() {
+        let mut net = NetManager::new();
+        net.add_interface("eth0").unwrap();
+        let taken = net.list()[0].mac.format();
+        let text = format!(
+            "# saved\n\niface eth1 down {} -\niface eth2 up 02:00:00:00:00:09 10.1.0.2/16\nroute 10.9.0.0/16 eth2 metric 1\n",
+            taken
+        );
+        let config = parse_net_config(&text).unwrap();
+        assert_eq!(net.restore_config(&config), vec!["iface eth1".to_string()]);
+        assert_eq!(
+            net.lookup_route("10.9.1.1").map(|route| route.metric),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn parse_reports_bad_lines() {
+        for (text, line) in [
+            ("iface eth0 up 02:00:00:00:00:01", 1),
+            ("\niface eth0 sideways 02:00:00:00:00:01 -", 2),
+            ("iface eth0 up nope -", 1),
+            ("iface Eth0 up 02:00:00:00:00:01 -", 1),
+            ("iface eth0 up 02:00:00:00:00:01 10.0.0.1/40", 1),
+            ("route default eth0 via", 1),
+            ("route default eth0 via 10.0.0.300", 1),
+            ("route default eth0 metric 10000", 1),
+            ("route default eth0 mtu 1500", 1),
+            ("route nowhere eth0", 1),
+            ("bridge br0", 1),
+        ] {
+            assert_eq!(
+                parse_net_config(text),
+                Err(NetError::InvalidConfig(line)),
+                "{}",
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::MacAddr;
+
+/// Bytes in an Ethernet header (destination, source, ethertype).
+pub const ETHERNET_HEADER_LEN: usize = 14;
+/// Largest Ethernet payload carried without jumbo frames.
+pub const MTU: usize = 1500;
+/// Largest frame a device sends or receives, excluding the FCS.
+pub const MAX_FRAME_LEN: usize = ETHERNET_HEADER_LEN + MTU;
+/// Most frames `NetManager::receive` takes from a device per call.
+pub const RX_BATCH: usize = 64;
+/// Frames a `LoopbackDevice` holds before dropping new ones.
+pub const LOOPBACK_QUEUE_LEN: usize = 64;
+
+/// Errors reported by a network device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceError {
+    /// Frame is shorter than an Ethernet header or longer than `MAX_FRAME_LEN`.
+    BadFrame,
+    /// Transmit ring has no free slot.
+    QueueFull,
+    /// Link is down or the device is not initialized.
+    NotReady,
+}
+
+/// Network card that sends and receives whole Ethernet frames.
+pub trait NetDevice {
+    /// Returns the hardware address.
+    fn mac(&self) -> MacAddr;
+    /// Returns true while the link carries traffic.
+    fn link_up(&self) -> bool;
+    /// Queues one frame for transmission.
+    fn transmit(&mut self, frame: &[u8]) -> Result<(), DeviceError>;
+    /// Takes the next received frame, if any.
+    fn receive(&mut self) -> Option<Vec<u8>>;
+}
+
+/// Device that hands every transmitted frame straight back, used in tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopbackDevice {
+    mac: MacAddr,
+    queue: VecDeque<Vec<u8>>,
+}
+
+impl LoopbackDevice {
+    /// Creates a loopback device with the given address.
+    pub fn new(mac: MacAddr) -> Self {
+        Self {
+            mac,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Queues a frame as if it had arrived from the wire.
+    pub fn inject(&mut self, frame: &[u8]) {
+        if self.queue.len() < LOOPBACK_QUEUE_LEN {
+            self.queue.push_back(frame.to_vec());
+        }
+    }
+}
+
+impl NetDevice for LoopbackDevice {
+    fn mac(&self) -> MacAddr {
+        self.mac
+    }
+
+    fn link_up(&self) -> bool {
+        true
+    }
+
+    fn transmit(&mut self, frame: &[u8]) -> Result<(), DeviceError> {
+        check_frame(frame)?;
+        if self.queue.len() >= LOOPBACK_QUEUE_LEN {
+            return Err(DeviceError::QueueFull);
+        }
+        self.queue.push_back(frame.to_vec());
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        self.queue.pop_front()
+    }
+}
+
+/// Checks that `frame` has an Ethernet header and fits in `MAX_FRAME_LEN`.
+pub fn check_frame(frame: &[u8]) -> Result<(), DeviceError> {
+    if frame.len() < ETHERNET_HEADER_LEN || frame.len() > MAX_FRAME_LEN {
+        return Err(DeviceError::BadFrame);
+    }
+    Ok(())
+}
+
+/// Returns the destination address of an Ethernet frame.
+pub fn frame_destination(frame: &[u8]) -> Option<MacAddr> {
+    let bytes: [u8; 6] = frame.get(..6)?.try_into().ok()?;
+    Some(MacAddr(bytes))
+}
+
+/// Builds an Ethernet frame around `payload`.
+pub fn ethernet_frame(dst: MacAddr, src: MacAddr, ethertype: u16, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(ETHERNET_HEADER_LEN + payload.len());
+    frame.extend_from_slice(&dst.0);
+    frame.extend_from_slice(&src.0);
+    frame.extend_from_slice(&ethertype.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAC: MacAddr = MacAddr([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]);
+
+    #[test]
+    fn loopback_returns_transmitted_frames_in_order() {
+        let mut device = LoopbackDevice::new(MAC);
+        assert_eq!(device.mac(), MAC);
+        assert!(device.link_up());
+        let first = ethernet_frame(MAC, MAC, 0x0800, b"one");
+        let second = ethernet_frame(MacAddr([0xff; 6]), MAC, 0x0806, b"two");
+        device.transmit(&first).unwrap();
+        device.transmit(&second).unwrap();
+        assert_eq!(device.receive(), Some(first));
+        assert_eq!(device.receive(), Some(second));
+        assert_eq!(device.receive(), None);
+    }
+
+    #[test]
+    fn frames_are_checked() {
+        let mut device = LoopbackDevice::new(MAC);
+        assert_eq!(device.transmit(&[0; 13]), Err(DeviceError::BadFrame));
+        assert_eq!(
+            device.transmit(&[0; MAX_FRAME_LEN + 1]),
+            Err(DeviceError::BadFrame)
+        );
+        for _ in 0..LOOPBACK_QUEUE_LEN {
+            device.transmit(&[0; ETHERNET_HEADER_LEN]).unwrap();
+        }
+        assert_eq!(
+            device.transmit(&[0; ETHERNET_HEADER_LEN]),
+            Err(DeviceError::QueueFull)
+        );
+
+        let frame = ethernet_frame(MAC, MacAddr([2; 6]), 0x0800, &[]);
+        assert_eq!(frame.len(), ETHERNET_HEADER_LEN);
+        assert_eq!(frame_destination(&frame), Some(MAC));
+        assert_eq!(frame_destination(&frame[..5]), None);
+    }
+}
@@ -0,0 +1,695 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::is_valid_ipv4;
+
+/// UDP port DNS servers listen on.
+pub const DNS_PORT: u16 = 53;
+/// Record type for IPv4 addresses.
+pub const TYPE_A: u16 = 1;
+/// Record type for canonical-name aliases.
+pub const TYPE_CNAME: u16 = 5;
+/// Internet record class.
+pub const CLASS_IN: u16 = 1;
+/// Longest name accepted in a query.
+pub const MAX_NAME_LEN: usize = 253;
+/// Longest single label accepted in a query.
+pub const MAX_LABEL_LEN: usize = 63;
+/// Upper bound on how long an answer stays cached, in seconds.
+pub const MAX_CACHE_TTL: u32 = 24 * 60 * 60;
+/// Largest number of names kept in the answer cache.
+pub const MAX_CACHE_ENTRIES: usize = 256;
+
+/// Address the host's own name maps to in `/etc/hosts`.
+pub const SELF_HOSTS_ADDR: &str = "127.0.1.1";
+
+const HEADER_LEN: usize = 12;
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_TRUNCATED: u16 = 0x0200;
+const FLAG_RECURSION_DESIRED: u16 = 0x0100;
+const RCODE_MASK: u16 = 0x000f;
+const RCODE_NAME_ERROR: u8 = 3;
+const MAX_POINTER_HOPS: usize = 16;
+
+/// Errors returned while resolving a name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DnsError {
+    InvalidName,
+    /// Packet ended before a field it declares.
+    Truncated,
+    /// Compressed name points outside the packet or loops.
+    BadPointer,
+    /// Packet is a query or answers a different id.
+    UnexpectedPacket,
+    /// Server reported that the name does not exist.
+    NameNotFound,
+    /// Server answered with a failure response code.
+    ServerFailure(u8),
+    /// No servers are configured.
+    NoServers,
+    /// No server sent a usable answer.
+    NoAnswer,
+}
+
+/// A-record answer decoded from a response packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsAnswer {
+    pub addrs: Vec<String>,
+    /// Smallest TTL among the returned records, in seconds.
+    pub ttl: u32,
+}
+
+/// Where a resolved address came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveSource {
+    Hosts,
+    Cache,
+    Server(String),
+}
+
+/// Addresses for a name and where they were found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    pub addrs: Vec<String>,
+    pub source: ResolveSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CacheEntry {
+    addrs: Vec<String>,
+    expires_at: u64,
+}
+
+/// Stub resolver: hosts file first, then cached answers, then servers.
+#[derive(Debug, Clone, Default)]
+pub struct DnsResolver {
+    servers: Vec<String>,
+    hosts: BTreeMap<String, Vec<String>>,
+    cache: BTreeMap<String, CacheEntry>,
+    next_id: u16,
+}
+
+impl DnsResolver {
+    /// Creates a resolver with no servers, hosts, or cached answers.
+    pub fn new() -> Self {
+        Self {
+            servers: Vec::new(),
+            hosts: BTreeMap::new(),
+            cache: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Replaces the server list, dropping entries that are not IPv4 addresses.
+    pub fn set_servers(&mut self, servers: &[&str]) {
+        self.servers = servers
+            .iter()
+            .filter(|server| is_valid_ipv4(server))
+            .map(|server| server.to_string())
+            .collect();
+    }
+
+    /// Returns the configured servers in query order.
+    pub fn servers(&self) -> &[String] {
+        &self.servers
+    }
+
+    /// Replaces the static host table with the entries in `/etc/hosts` text.
+    pub fn load_hosts(&mut self, text: &str) {
+        self.hosts = parse_hosts(text);
+    }
+
+    /// Returns the addresses the host table maps `name` to.
+    pub fn host(&self, name: &str) -> Option<&[String]> {
+        self.hosts
+            .get(&normalize_name(name))
+            .map(|addrs| addrs.as_slice())
+    }
+
+    /// Returns the cached addresses for `name` if they have not expired.
+    pub fn cached(&self, name: &str, now_secs: u64) -> Option<&[String]> {
+        self.cache
+            .get(&normalize_name(name))
+            .filter(|entry| entry.expires_at > now_secs)
+            .map(|entry| entry.addrs.as_slice())
+    }
+
+    /// Drops cached answers whose TTL has run out.
+    pub fn purge_expired(&mut self, now_secs: u64) {
+        self.cache.retain(|_, entry| entry.expires_at > now_secs);
+    }
+
+    /// Forgets every cached answer.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Returns the number of names with a cached answer.
+    pub fn cache_len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Resolves `name`, sending queries through `exchange(server, packet)`.
+    ///
+    /// `exchange` returns the server's reply, or None on timeout. Servers are
+    /// tried in order until one answers; a name error stops the search.
+    pub fn resolve<F>(
+        &mut self,
+        name: &str,
+        now_secs: u64,
+        mut exchange: F,
+    ) -> Result<Resolution, DnsError>
+    where
+        F: FnMut(&str, &[u8]) -> Option<Vec<u8>>,
+    {
+        let name = normalize_name(name);
+        if !is_valid_name(&name) {
+            return Err(DnsError::InvalidName);
+        }
+        if let Some(addrs) = self.host(&name) {
+            return Ok(Resolution {
+                addrs: addrs.to_vec(),
+                source: ResolveSource::Hosts,
+            });
+        }
+        if let Some(addrs) = self.cached(&name, now_secs) {
+            return Ok(Resolution {
+                addrs: addrs.to_vec(),
+                source: ResolveSource::Cache,
+            });
+        }
+        if self.servers.is_empty() {
+            return Err(DnsError::NoServers);
+        }
+        let mut last_error = DnsError::NoAnswer;
+        for server in self.servers.clone() {
+            let id = self.allocate_id();
+            let query = encode_query(id, &name)?;
+            let Some(reply) = exchange(&server, &query) else {
+                continue;
+            };
+            match decode_response(&reply, id) {
+                Ok(answer) if !answer.addrs.is_empty() => {
+                    self.store(&name, &answer, now_secs);
+                    return Ok(Resolution {
+                        addrs: answer.addrs,
+                        source: ResolveSource::Server(server),
+                    });
+                }
+                Ok(_) => last_error = DnsError::NoAnswer,
+                Err(DnsError::NameNotFound) => return Err(DnsError::NameNotFound),
+                Err(err) => last_error = err,
+            }
+        }
+        Err(last_error)
+    }
+
+    fn allocate_id(&mut self) -> u16 {
+        let id = self.next_id.max(1);
+        self.next_id = id.wrapping_add(1);
+        id
+    }
+
+    fn store(&mut self, name: &str, answer: &DnsAnswer, now_secs: u64) {
+        let ttl = answer.ttl.min(MAX_CACHE_TTL);
+        if ttl == 0 {
+            return;
+        }
+        self.purge_expired(now_secs);
+        if self.cache.len() >= MAX_CACHE_ENTRIES && !self.cache.contains_key(name) {
+            let oldest = self
+                .cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.cache.remove(&oldest);
+            }
+        }
+        self.cache.insert(
+            name.to_string(),
+            CacheEntry {
+                addrs: answer.addrs.clone(),
+                expires_at: now_secs + u64::from(ttl),
+            },
+        );
+    }
+}
+
+/// Encodes a recursive A-record query for `name`.
+pub fn encode_query(id: u16, name: &str) -> Result<Vec<u8>, DnsError> {
+    let name = normalize_name(name);
+    if !is_valid_name(&name) {
+        return Err(DnsError::InvalidName);
+    }
+    let mut bytes = Vec::with_capacity(HEADER_LEN + name.len() + 6);
+    bytes.extend_from_slice(&id.to_be_bytes());
+    bytes.extend_from_slice(&FLAG_RECURSION_DESIRED.to_be_bytes());
+    bytes.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.') {
+        bytes.push(label.len() as u8);
+        bytes.extend_from_slice(label.as_bytes());
+    }
+    bytes.push(0);
+    bytes.extend_from_slice(&TYPE_A.to_be_bytes());
+    bytes.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(bytes)
+}
+
+/// Decodes the A records from a response to query `id`.
+///
+/// CNAME records are skipped, so the addresses of an alias's target are
+/// returned as long as the server included them.
+pub fn decode_response(bytes: &[u8], id: u16) -> Result<DnsAnswer, DnsError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(DnsError::Truncated);
+    }
+    let flags = read_u16(bytes, 2)?;
+    if read_u16(bytes, 0)? != id || flags & FLAG_RESPONSE == 0 {
+        return Err(DnsError::UnexpectedPacket);
+    }
+    match (flags & RCODE_MASK) as u8 {
+        0 => {}
+        RCODE_NAME_ERROR => return Err(DnsError::NameNotFound),
+        code => return Err(DnsError::ServerFailure(code)),
+    }
+    let questions = read_u16(bytes, 4)?;
+    let answers = read_u16(bytes, 6)?;
+    let mut offset = HEADER_LEN;
+    for _ in 0..questions {
+        offset = skip_name(bytes, offset)? + 4;
+    }
+    let mut addrs = Vec::new();
+    let mut ttl = u32::MAX;
+    for _ in 0..answers {
+        offset = skip_name(bytes, offset)?;
+        let record_type = read_u16(bytes, offset)?;
+        let class = read_u16(bytes, offset + 2)?;
+        let record_ttl = read_u32(bytes, offset + 4)?;
+        let len = read_u16(bytes, offset + 8)? as usize;
+        let data_start = offset + 10;
+        let data = bytes
+            .get(data_start..data_start + len)
+            .ok_or(DnsError::Truncated)?;
+        if record_type == TYPE_A && class == CLASS_IN && len == 4 {
+            addrs.push(format!("{}.{}.{}.{}", data[0], data[1], data[2], data[3]));
+            ttl = ttl.min(record_ttl);
+        }
+        offset = data_start + len;
+    }
+    if addrs.is_empty() && flags & FLAG_TRUNCATED != 0 {
+        return Err(DnsError::Truncated);
+    }
+    Ok(DnsAnswer {
+        addrs,
+        ttl: if ttl == u32::MAX { 0 } else { ttl },
+    })
+}
+
+/// Returns the `nameserver` addresses listed in `/etc/resolv.conf` text.
+pub fn parse_resolv_conf(text: &str) -> Vec<String> {
+    let mut servers = Vec::new();
+    for line in text.lines() {
+        let line = strip_comment(line);
+        let mut words = line.split_whitespace();
+        if words.next() != Some("nameserver") {
+            continue;
+        }
+        if let Some(addr) = words.next() {
+            if is_valid_ipv4(addr) && !servers.iter().any(|server| server == addr) {
+                servers.push(addr.to_string());
+            }
+        }
+    }
+    servers
+}
+
+/// Parses `/etc/hosts` text into a map from lowercase name to IPv4 addresses.
+///
+/// Lines with an address that is not IPv4 are skipped.
+pub fn parse_hosts(text: &str) -> BTreeMap<String, Vec<String>> {
+    let mut hosts: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for line in text.lines() {
+        let mut words = strip_comment(line).split_whitespace();
+        let Some(addr) = words.next() else {
+            continue;
+        };
+        if !is_valid_ipv4(addr) {
+            continue;
+        }
+        for name in words {
+            let name = normalize_name(name);
+            if !is_valid_name(&name) {
+                continue;
+            }
+            let addrs = hosts.entry(name).or_default();
+            if !addrs.iter().any(|known| known == addr) {
+                addrs.push(addr.to_string());
+            }
+        }
+    }
+    hosts
+}
+
+/// Rewrites `/etc/hosts` text so the host's own name is `hostname`.
+///
+/// `previous` is removed from every entry, dropping entries left without a
+/// name, and the `SELF_HOSTS_ADDR` entry is replaced with one for `hostname`,
+/// placed after the first `127.0.0.1` entry. Comments and other entries stay.
+pub fn set_self_entry(hosts: &str, previous: Option<&str>, hostname: &str) -> String {
+    let previous = previous.map(normalize_name);
+    let mut out = String::new();
+    let mut placed = false;
+    for line in hosts.lines() {
+        let mut words = strip_comment(line).split_whitespace();
+        let Some(addr) = words.next() else {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        };
+        if addr == SELF_HOSTS_ADDR {
+            continue;
+        }
+        let names: Vec<&str> = words.collect();
+        let kept: Vec<&str> = names
+            .iter()
+            .copied()
+            .filter(|name| previous.as_deref() != Some(normalize_name(name).as_str()))
+            .collect();
+        if kept.is_empty() {
+            continue;
+        }
+        if kept.len() == names.len() {
+            out.push_str(line);
+        } else {
+            out.push_str(addr);
+            for name in kept {
+                out.push(' ');
+                out.push_str(name);
+            }
+        }
+        out.push('\n');
+        if addr == "127.0.0.1" && !placed {
+            out.push_str(&format!("{} {}\n", SELF_HOSTS_ADDR, hostname));
+            placed = true;
+        }
+    }
+    if !placed {
+        out.push_str(&format!("{} {}\n", SELF_HOSTS_ADDR, hostname));
+    }
+    out
+}
+
+/// Returns the label text of `name` in lowercase without a trailing dot.
+fn normalize_name(name: &str) -> String {
+    name.strip_suffix('.').unwrap_or(name).to_ascii_lowercase()
+}
+
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return false;
+    }
+    name.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= MAX_LABEL_LEN
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+    })
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap_or("")
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, DnsError> {
+    let raw = bytes.get(offset..offset + 2).ok_or(DnsError::Truncated)?;
+    Ok(u16::from_be_bytes([raw[0], raw[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, DnsError> {
+    let raw = bytes.get(offset..offset + 4).ok_or(DnsError::Truncated)?;
+    Ok(u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]))
+}
+
+/// Returns the offset just past the (possibly compressed) name at `offset`.
+fn skip_name(bytes: &[u8], mut offset: usize) -> Result<usize, DnsError> {
+    let mut hops = 0;
+    let mut end = None;
+    loop {
+        let len = *bytes.get(offset).ok_or(DnsError::Truncated)?;
+        match len & 0xc0 {
+            0x00 if len == 0 => return Ok(end.unwrap_or(offset + 1)),
+            0x00 => offset += 1 + usize::from(len),
+            0xc0 => {
+                let low = *bytes.get(offset + 1).ok_or(DnsError::Truncated)?;
+                end.get_or_insert(offset + 2);
+                hops += 1;
+                let target = (usize::from(len & 0x3f) << 8) | usize::from(low);
+                if hops > MAX_POINTER_HOPS || target >= bytes.len() {
+                    return Err(DnsError::BadPointer);
+                }
+                offset = target;
+            }
+            _ => return Err(DnsError::BadPointer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a response to `query` with the given (type, ttl, data) records.
+    fn response(query: &[u8], rcode: u16, records: &[(u16, u32, &[u8])]) -> Vec<u8> {
+        let mut bytes = query.to_vec();
+        let flags = FLAG_RESPONSE | FLAG_RECURSION_DESIRED | rcode;
+        bytes[2..4].copy_from_slice(&flags.to_be_bytes());
+        bytes[6..8].copy_from_slice(&(records.len() as u16).to_be_bytes());
+        for (record_type, ttl, data) in records {
+            bytes.extend_from_slice(&[0xc0, HEADER_LEN as u8]);
+            bytes.extend_from_slice(&record_type.to_be_bytes());
+            bytes.extend_from_slice(&CLASS_IN.to_be_bytes());
+            bytes.extend_from_slice(&ttl.to_be_bytes());
+            bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(data);
+        }
+        bytes
+    }
+
+    #[test]
+    fn encode_query_builds_a_record_question() {
+        let bytes = encode_query(0x1234, "Example.COM.").unwrap();
+        let mut expected = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+        expected.extend_from_slice(b"\x07example\x03com\x00");
+        expected.extend_from_slice(&[0, 1, 0, 1]);
+        assert_eq!(bytes, expected);
+
+        for name in ["", "bad..name", "-bad.com", "has space.com", "."] {
+            assert_eq!(encode_query(1, name), Err(DnsError::InvalidName), "{name}");
+        }
+        let long_label = "a".repeat(MAX_LABEL_LEN + 1);
+        assert_eq!(encode_query(1, &long_label), Err(DnsError::InvalidName));
+    }
+
+    #[test]
+    fn decode_response_collects_a_records() {
+        let query = encode_query(7, "ruzzle.dev").unwrap();
+        let reply = response(
+            &query,
+            0,
+            &[
+                (TYPE_CNAME, 600, b"\x03www\xc0\x0c"),
+                (TYPE_A, 300, &[10, 0, 0, 1]),
+                (TYPE_A, 120, &[10, 0, 0, 2]),
+            ],
+        );
+        assert_eq!(
+            decode_response(&reply, 7),
+            Ok(DnsAnswer {
+                addrs: vec!["10.0.0.1".to_string(), "10.0.0.2".to_string()],
+                ttl: 120,
+            })
+        );
+        assert_eq!(decode_response(&reply, 8), Err(DnsError::UnexpectedPacket));
+        assert_eq!(decode_response(&query, 7), Err(DnsError::UnexpectedPacket));
+        assert_eq!(
+            decode_response(&reply[..reply.len() - 2], 7),
+            Err(DnsError::Truncated)
+        );
+        assert_eq!(
+            decode_response(&response(&query, 3, &[]), 7),
+            Err(DnsError::NameNotFound)
+        );
+        assert_eq!(
+            decode_response(&response(&query, 2, &[]), 7),
+            Err(DnsError::ServerFailure(2))
+        );
+    }
+
+    #[test]
+    fn decode_response_rejects_pointer_loops() {
+        let query = encode_query(9, "loop.test").unwrap();
+        let mut reply = response(&query, 0, &[(TYPE_A, 60, &[1, 2, 3, 4])]);
+        let answer = query.len();
+        reply[answer..answer + 2].copy_from_slice(&[0xc0, answer as u8]);
+        assert_eq!(decode_response(&reply, 9), Err(DnsError::BadPointer));
+        reply[answer..answer + 2].copy_from_slice(&[0xc0, 0xff]);
+        assert_eq!(decode_response(&reply, 9), Err(DnsError::BadPointer));
+    }
+
+    #[test]
+    fn config_files_parse_servers_and_hosts() {
+        let resolv = "# generated\nnameserver 10.0.2.3\nsearch lan\nnameserver 1.1.1.1 # backup\nnameserver ::1\nnameserver 10.0.2.3\n";
+        assert_eq!(
+            parse_resolv_conf(resolv),
+            vec!["10.0.2.3".to_string(), "1.1.1.1".to_string()]
+        );
+
+        let hosts = parse_hosts(
+            "127.0.0.1 localhost Ruzzle\n::1 localhost\n# 10.0.0.9 ignored\n10.0.0.5 build build.lan\n10.0.0.6 build\n",
+        );
+        assert_eq!(hosts.get("ruzzle"), Some(&vec!["127.0.0.1".to_string()]));
+        assert_eq!(hosts.get("localhost"), Some(&vec!["127.0.0.1".to_string()]));
+        assert_eq!(
+            hosts.get("build"),
+            Some(&vec!["10.0.0.5".to_string(), "10.0.0.6".to_string()])
+        );
+        assert!(hosts.contains_key("build.lan"));
+        assert_eq!(hosts.len(), 4);
+    }
+
+    #[test]
+    fn resolver_prefers_hosts_then_cache_then_servers() {
+        let mut resolver = DnsResolver::new();
+        resolver.load_hosts("10.0.0.5 build\n");
+        resolver.set_servers(&["10.0.2.3", "bogus", "1.1.1.1"]);
+        assert_eq!(resolver.servers(), ["10.0.2.3", "1.1.1.1"]);
+
+        let no_network = |_: &str, _: &[u8]| -> Option<Vec<u8>> { panic!("no query expected") };
+        assert_eq!(
+            resolver.resolve("BUILD", 0, no_network),
+            Ok(Resolution {
+                addrs: vec!["10.0.0.5".to_string()],
+                source: ResolveSource::Hosts,
+            })
+        );
+
+        let mut asked = Vec::new();
+        let result = resolver.resolve("ruzzle.dev", 100, |server, query| {
+            asked.push(server.to_string());
+            if server == "10.0.2.3" {
+                return None;
+            }
+            Some(response(query, 0, &[(TYPE_A, 30, &[93, 184, 216, 34])]))
+        });
+        assert_eq!(
+            result,
+            Ok(Resolution {
+                addrs: vec!["93.184.216.34".to_string()],
+                source: ResolveSource::Server("1.1.1.1".to_string()),
+            })
+        );
+        assert_eq!(asked, ["10.0.2.3", "1.1.1.1"]);
+
+        assert_eq!(
+            resolver
+                .resolve("ruzzle.dev.", 129, no_network)
+                .map(|r| r.source),
+            Ok(ResolveSource::Cache)
+        );
+        assert_eq!(resolver.cached("ruzzle.dev", 130), None);
+        resolver.purge_expired(130);
+        assert_eq!(resolver.cache_len(), 0);
+    }
+
+    #[test]
+    fn resolver_reports_failures() {
+        let mut resolver = DnsResolver::new();
+        assert_eq!(
+            resolver.resolve("ruzzle.dev", 0, |_, _| None),
+            Err(DnsError::NoServers)
+        );
+        assert_eq!(
+            resolver.resolve("bad..name", 0, |_, _| None),
+            Err(DnsError::InvalidName)
+        );
+
+        resolver.set_servers(&["10.0.2.3", "1.1.1.1"]);
+        assert_eq!(
+            resolver.resolve("ruzzle.dev", 0, |_, _| None),
+            Err(DnsError::NoAnswer)
+        );
+        let mut asked = 0;
+        assert_eq!(
+            resolver.resolve("missing.dev", 0, |_, query| {
+                asked += 1;
+                Some(response(query, 3, &[]))
+            }),
+            Err(DnsError::NameNotFound)
+        );
+        assert_eq!(asked, 1);
+        assert_eq!(
+            resolver.resolve("stale.dev", 0, |_, query| {
+                let mut reply = response(query, 0, &[(TYPE_A, 60, &[1, 2, 3, 4])]);
+                reply[0] ^= 0xff;
+                Some(reply)
+            }),
+            Err(DnsError::UnexpectedPacket)
+        );
+
+        let zero_ttl = resolver.resolve("once.dev", 0, |_, query| {
+            Some(response(query, 0, &[(TYPE_A, 0, &[1, 2, 3, 4])]))
+        });
+        assert!(zero_ttl.is_ok());
+        assert_eq!(resolver.cache_len(), 0);
+    }
+
+    #[test]
+    fn cache_evicts_the_entry_closest_to_expiry() {
+        let mut resolver = DnsResolver::new();
+        for index in 0..=MAX_CACHE_ENTRIES {
+            let answer = DnsAnswer {
+                addrs: vec!["10.0.0.1".to_string()],
+                ttl: 100 + index as u32,
+            };
+            resolver.store(&format!("host{index}.lan"), &answer, 0);
+        }
+        assert_eq!(resolver.cache_len(), MAX_CACHE_ENTRIES);
+        assert_eq!(resolver.cached("host0.lan", 0), None);
+        assert!(resolver.cached("host1.lan", 0).is_some());
+        resolver.clear_cache();
+        assert_eq!(resolver.cache_len(), 0);
+    }
+
+    #[test]
+    fn self_entry_follows_the_hostname() {
+        let hosts = "# static\n127.0.0.1 localhost ruzzle\n10.0.2.9 nas Ruzzle # lab\n";
+        let renamed = set_self_entry(hosts, Some("ruzzle"), "lab1");
+        assert_eq!(
+            renamed,
+            "# static\n127.0.0.1 localhost\n127.0.1.1 lab1\n10.0.2.9 nas\n"
+        );
+        assert_eq!(
+            parse_hosts(&renamed).get("lab1"),
+            Some(&vec!["127.0.1.1".to_string()])
+        );
+
+        let again = set_self_entry(&renamed, Some("lab1"), "lab2");
+        assert_eq!(
+            again,
+            "# static\n127.0.0.1 localhost\n127.0.1.1 lab2\n10.0.2.9 nas\n"
+        );
+        assert_eq!(set_self_entry(&again, Some("lab2"), "lab2"), again);
+
+        assert_eq!(set_self_entry("", None, "ruzzle"), "127.0.1.1 ruzzle\n");
+        assert_eq!(
+            set_self_entry("10.0.0.1 old\n", Some("old"), "new"),
+            "127.0.1.1 new\n"
+        );
+    }
+}
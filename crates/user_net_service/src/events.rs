@@ -0,0 +1,126 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Events queued per subscriber before the oldest are dropped.
+pub const NET_EVENT_QUEUE_LIMIT: usize = 64;
+
+/// Configuration change reported to `NetManager` subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetEvent {
+    InterfaceAdded {
+        iface: String,
+    },
+    InterfaceRemoved {
+        iface: String,
+    },
+    LinkUp {
+        iface: String,
+    },
+    LinkDown {
+        iface: String,
+    },
+    /// `cidr` is the new `addr/prefix`, or None when the address was cleared.
+    AddressChanged {
+        iface: String,
+        cidr: Option<String>,
+    },
+    RouteAdded {
+        destination: String,
+        iface: String,
+    },
+    RouteRemoved {
+        destination: String,
+        iface: String,
+    },
+}
+
+impl NetEvent {
+    /// Returns the interface the event concerns.
+    pub fn iface(&self) -> &str {
+        match self {
+            NetEvent::InterfaceAdded { iface }
+            | NetEvent::InterfaceRemoved { iface }
+            | NetEvent::LinkUp { iface }
+            | NetEvent::LinkDown { iface }
+            | NetEvent::AddressChanged { iface, .. }
+            | NetEvent::RouteAdded { iface, .. }
+            | NetEvent::RouteRemoved { iface, .. } => iface,
+        }
+    }
+}
+
+/// Subscription returned by `NetManager::subscribe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NetSubscription(u32);
+
+/// Per-subscriber event queues.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Subscribers {
+    queues: BTreeMap<NetSubscription, Vec<NetEvent>>,
+    next_id: u32,
+}
+
+impl Subscribers {
+    pub(crate) fn subscribe(&mut self) -> NetSubscription {
+        let id = NetSubscription(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.queues.insert(id, Vec::new());
+        id
+    }
+
+    pub(crate) fn unsubscribe(&mut self, id: NetSubscription) -> bool {
+        self.queues.remove(&id).is_some()
+    }
+
+    pub(crate) fn take(&mut self, id: NetSubscription) -> Option<Vec<NetEvent>> {
+        self.queues.get_mut(&id).map(core::mem::take)
+    }
+
+    pub(crate) fn emit(&mut self, event: NetEvent) {
+        for queue in self.queues.values_mut() {
+            if queue.len() == NET_EVENT_QUEUE_LIMIT {
+                queue.remove(0);
+            }
+            queue.push(event.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn link_up(iface: &str) -> NetEvent {
+        NetEvent::LinkUp {
+            iface: iface.to_string(),
+        }
+    }
+
+    #[test]
+    fn queues_are_independent_and_bounded() {
+        let mut subscribers = Subscribers::default();
+        subscribers.emit(link_up("lost"));
+        let first = subscribers.subscribe();
+        subscribers.emit(link_up("eth0"));
+        let second = subscribers.subscribe();
+        assert_ne!(first, second);
+        for index in 0..NET_EVENT_QUEUE_LIMIT {
+            subscribers.emit(link_up(&format!("eth{}", index + 1)));
+        }
+
+        let events = subscribers.take(first).unwrap();
+        assert_eq!(events.len(), NET_EVENT_QUEUE_LIMIT);
+        assert_eq!(events[0].iface(), "eth1");
+        assert_eq!(subscribers.take(first), Some(Vec::new()));
+        assert_eq!(
+            subscribers.take(second).unwrap().len(),
+            NET_EVENT_QUEUE_LIMIT
+        );
+
+        assert!(subscribers.unsubscribe(second));
+        assert!(!subscribers.unsubscribe(second));
+        assert_eq!(subscribers.take(second), None);
+    }
+}
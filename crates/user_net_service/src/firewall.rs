@@ -0,0 +1,375 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{ipv4_octets, is_valid_ipv4, network_address, parse_ipv4_cidr, prefix_mask};
+
+/// Largest number of rules a table holds.
+pub const MAX_FIREWALL_RULES: usize = 128;
+
+/// Errors returned by firewall table operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FirewallError {
+    /// Rule text does not follow `<allow|deny> <in|out> [proto] [addr] [port <n>]`.
+    InvalidRule,
+    /// A port was given without `tcp` or `udp`.
+    PortNeedsProtocol,
+    TableFull,
+    NotFound,
+}
+
+/// What a matching rule does with the packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirewallAction {
+    Allow,
+    Deny,
+}
+
+/// Traffic direction relative to this host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+/// Transport protocol a rule applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Any,
+    Tcp,
+    Udp,
+    Icmp,
+}
+
+/// Packet summary the table is evaluated against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketInfo<'a> {
+    pub direction: Direction,
+    pub protocol: Protocol,
+    /// Peer address: the source of inbound and the destination of outbound packets.
+    pub remote: &'a str,
+    /// Destination port; ignored for ICMP.
+    pub port: u16,
+}
+
+/// One ordered firewall rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirewallRule {
+    pub action: FirewallAction,
+    pub direction: Direction,
+    pub protocol: Protocol,
+    /// Peer network as `addr/prefix`, or None for any address.
+    pub remote: Option<String>,
+    /// Destination port, only for `tcp` and `udp` rules.
+    pub port: Option<u16>,
+}
+
+impl FirewallRule {
+    /// Parses `<allow|deny> <in|out> [tcp|udp|icmp|any] [<ipv4>[/prefix]] [port <n>]`.
+    pub fn parse(text: &str) -> Result<Self, FirewallError> {
+        let mut words = text.split_whitespace().peekable();
+        let action = match words.next() {
+            Some("allow") => FirewallAction::Allow,
+            Some("deny") => FirewallAction::Deny,
+            _ => return Err(FirewallError::InvalidRule),
+        };
+        let direction = match words.next() {
+            Some("in") => Direction::In,
+            Some("out") => Direction::Out,
+            _ => return Err(FirewallError::InvalidRule),
+        };
+        let mut rule = Self {
+            action,
+            direction,
+            protocol: Protocol::Any,
+            remote: None,
+            port: None,
+        };
+        if let Some(protocol) = words.peek().and_then(|word| parse_protocol(word)) {
+            rule.protocol = protocol;
+            words.next();
+        }
+        if let Some(word) = words.next_if(|word| *word != "port") {
+            let (addr, prefix) = parse_ipv4_cidr(word).ok_or(FirewallError::InvalidRule)?;
+            rule.remote = Some(format!("{}/{}", network_address(&addr, prefix), prefix));
+        }
+        if words.next_if_eq(&"port").is_some() {
+            let port = words
+                .next()
+                .and_then(|port| port.parse::<u16>().ok())
+                .filter(|port| *port != 0)
+                .ok_or(FirewallError::InvalidRule)?;
+            if !matches!(rule.protocol, Protocol::Tcp | Protocol::Udp) {
+                return Err(FirewallError::PortNeedsProtocol);
+            }
+            rule.port = Some(port);
+        }
+        if words.next().is_some() {
+            return Err(FirewallError::InvalidRule);
+        }
+        Ok(rule)
+    }
+
+    /// Formats the rule in the syntax accepted by `parse`.
+    pub fn format(&self) -> String {
+        let action = match self.action {
+            FirewallAction::Allow => "allow",
+            FirewallAction::Deny => "deny",
+        };
+        let direction = match self.direction {
+            Direction::In => "in",
+            Direction::Out => "out",
+        };
+        let mut out = format!("{} {} {}", action, direction, protocol_name(self.protocol));
+        if let Some(remote) = &self.remote {
+            out.push(' ');
+            out.push_str(remote);
+        }
+        if let Some(port) = self.port {
+            out.push_str(&format!(" port {}", port));
+        }
+        out
+    }
+
+    /// Returns true if the rule applies to `packet`.
+    pub fn matches(&self, packet: &PacketInfo) -> bool {
+        if self.direction != packet.direction {
+            return false;
+        }
+        if self.protocol != Protocol::Any && self.protocol != packet.protocol {
+            return false;
+        }
+        if self.port.is_some_and(|port| port != packet.port) {
+            return false;
+        }
+        match &self.remote {
+            None => true,
+            Some(network) => {
+                let Some((addr, prefix)) = parse_ipv4_cidr(network) else {
+                    return false;
+                };
+                if !is_valid_ipv4(packet.remote) {
+                    return false;
+                }
+                let mask = prefix_mask(prefix);
+                let network = u32::from_be_bytes(ipv4_octets(&addr));
+                let remote = u32::from_be_bytes(ipv4_octets(packet.remote));
+                network & mask == remote & mask
+            }
+        }
+    }
+}
+
+/// Ordered rule list; the first matching rule decides, else the direction's policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirewallTable {
+    rules: Vec<FirewallRule>,
+    inbound_policy: FirewallAction,
+    outbound_policy: FirewallAction,
+}
+
+impl Default for FirewallTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FirewallTable {
+    /// Creates an empty table that allows all traffic.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            inbound_policy: FirewallAction::Allow,
+            outbound_policy: FirewallAction::Allow,
+        }
+    }
+
+    /// Appends a rule after the existing ones.
+    pub fn add(&mut self, rule: FirewallRule) -> Result<(), FirewallError> {
+        if self.rules.len() >= MAX_FIREWALL_RULES {
+            return Err(FirewallError::TableFull);
+        }
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// Removes the rule at 1-based `position`, as numbered by `rules`.
+    pub fn remove(&mut self, position: usize) -> Result<FirewallRule, FirewallError> {
+        if position == 0 || position > self.rules.len() {
+            return Err(FirewallError::NotFound);
+        }
+        Ok(self.rules.remove(position - 1))
+    }
+
+    /// Returns the rules in evaluation order.
+    pub fn rules(&self) -> &[FirewallRule] {
+        &self.rules
+    }
+
+    /// Returns the action taken when no rule matches traffic in `direction`.
+    pub fn policy(&self, direction: Direction) -> FirewallAction {
+        match direction {
+            Direction::In => self.inbound_policy,
+            Direction::Out => self.outbound_policy,
+        }
+    }
+
+    /// Sets the action taken when no rule matches traffic in `direction`.
+    pub fn set_policy(&mut self, direction: Direction, action: FirewallAction) {
+        match direction {
+            Direction::In => self.inbound_policy = action,
+            Direction::Out => self.outbound_policy = action,
+        }
+    }
+
+    /// Decides whether `packet` may pass.
+    pub fn evaluate(&self, packet: &PacketInfo) -> FirewallAction {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(packet))
+            .map_or(self.policy(packet.direction), |rule| rule.action)
+    }
+}
+
+fn parse_protocol(word: &str) -> Option<Protocol> {
+    match word {
+        "any" => Some(Protocol::Any),
+        "tcp" => Some(Protocol::Tcp),
+        "udp" => Some(Protocol::Udp),
+        "icmp" => Some(Protocol::Icmp),
+        _ => None,
+    }
+}
+
+fn protocol_name(protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::Any => "any",
+        Protocol::Tcp => "tcp",
+        Protocol::Udp => "udp",
+        Protocol::Icmp => "icmp",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inbound<'a>(protocol: Protocol, remote: &'a str, port: u16) -> PacketInfo<'a> {
+        PacketInfo {
+            direction: Direction::In,
+            protocol,
+            remote,
+            port,
+        }
+    }
+
+    #[test]
+    fn rules_parse_and_format() {
+        let rule = FirewallRule::parse("deny in tcp 10.1.2.3/8 port 22").unwrap();
+        assert_eq!(
+            rule,
+            FirewallRule {
+                action: FirewallAction::Deny,
+                direction: Direction::In,
+                protocol: Protocol::Tcp,
+                remote: Some("10.0.0.0/8".to_string()),
+                port: Some(22),
+            }
+        );
+        assert_eq!(rule.format(), "deny in tcp 10.0.0.0/8 port 22");
+        assert_eq!(FirewallRule::parse(&rule.format()), Ok(rule));
+
+        let rule = FirewallRule::parse("allow out 192.168.1.7").unwrap();
+        assert_eq!(rule.protocol, Protocol::Any);
+        assert_eq!(rule.format(), "allow out any 192.168.1.7/32");
+        assert_eq!(
+            FirewallRule::parse("allow in udp port 53")
+                .unwrap()
+                .format(),
+            "allow in udp port 53"
+        );
+    }
+
+    #[test]
+    fn parse_rejects_malformed_rules() {
+        for text in [
+            "",
+            "permit in",
+            "allow sideways",
+            "allow in tcp 10.0.0.0/33",
+            "allow in tcp port",
+            "allow in tcp port 0",
+            "allow in tcp port 70000",
+            "allow in tcp 10.0.0.1 extra",
+        ] {
+            assert_eq!(
+                FirewallRule::parse(text),
+                Err(FirewallError::InvalidRule),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            FirewallRule::parse("deny in icmp port 7"),
+            Err(FirewallError::PortNeedsProtocol)
+        );
+        assert_eq!(
+            FirewallRule::parse("deny in port 7"),
+            Err(FirewallError::PortNeedsProtocol)
+        );
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let mut table = FirewallTable::new();
+        table
+            .add(FirewallRule::parse("allow in tcp 10.0.0.0/24 port 22").unwrap())
+            .unwrap();
+        table
+            .add(FirewallRule::parse("deny in tcp port 22").unwrap())
+            .unwrap();
+        table
+            .add(FirewallRule::parse("deny in icmp").unwrap())
+            .unwrap();
+
+        let ssh = |remote| table.evaluate(&inbound(Protocol::Tcp, remote, 22));
+        assert_eq!(ssh("10.0.0.9"), FirewallAction::Allow);
+        assert_eq!(ssh("10.0.1.9"), FirewallAction::Deny);
+        assert_eq!(ssh("bad"), FirewallAction::Deny);
+        assert_eq!(
+            table.evaluate(&inbound(Protocol::Tcp, "10.0.1.9", 80)),
+            FirewallAction::Allow
+        );
+        assert_eq!(
+            table.evaluate(&inbound(Protocol::Icmp, "8.8.8.8", 0)),
+            FirewallAction::Deny
+        );
+        let outbound = PacketInfo {
+            direction: Direction::Out,
+            ..inbound(Protocol::Icmp, "8.8.8.8", 0)
+        };
+        assert_eq!(table.evaluate(&outbound), FirewallAction::Allow);
+
+        table.set_policy(Direction::Out, FirewallAction::Deny);
+        assert_eq!(table.policy(Direction::Out), FirewallAction::Deny);
+        assert_eq!(table.evaluate(&outbound), FirewallAction::Deny);
+    }
+
+    #[test]
+    fn remove_uses_listed_positions_and_table_is_bounded() {
+        let mut table = FirewallTable::default();
+        let rule = FirewallRule::parse("deny in udp").unwrap();
+        for _ in 0..MAX_FIREWALL_RULES {
+            table.add(rule.clone()).unwrap();
+        }
+        assert_eq!(table.add(rule.clone()), Err(FirewallError::TableFull));
+
+        assert_eq!(table.remove(0), Err(FirewallError::NotFound));
+        assert_eq!(
+            table.remove(MAX_FIREWALL_RULES + 1),
+            Err(FirewallError::NotFound)
+        );
+        assert_eq!(table.remove(MAX_FIREWALL_RULES), Ok(rule));
+        assert_eq!(table.rules().len(), MAX_FIREWALL_RULES - 1);
+    }
+}
@@ -0,0 +1,345 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// ICMP type of an echo reply.
+pub const TYPE_ECHO_REPLY: u8 = 0;
+/// ICMP type of an echo request.
+pub const TYPE_ECHO_REQUEST: u8 = 8;
+/// Bytes in an echo header (type, code, checksum, identifier, sequence).
+pub const ECHO_HEADER_LEN: usize = 8;
+/// Payload `PingSession` puts in every request.
+pub const PING_PAYLOAD: &[u8] = b"ruzzle-ping";
+
+/// Errors returned while decoding an ICMP message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcmpError {
+    /// Message is shorter than an echo header.
+    TooShort,
+    BadChecksum,
+    /// Message is not an echo request or reply.
+    UnsupportedType(u8),
+}
+
+/// Direction of an echo message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EchoKind {
+    Request,
+    Reply,
+}
+
+/// ICMP echo request or reply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EchoPacket {
+    pub kind: EchoKind,
+    pub id: u16,
+    pub seq: u16,
+    pub payload: Vec<u8>,
+}
+
+/// Computes the Internet checksum (RFC 1071) of `bytes`.
+pub fn checksum(bytes: &[u8]) -> u16 {
+    let mut sum = 0u32;
+    for chunk in bytes.chunks(2) {
+        let word = match chunk {
+            [high, low] => u16::from_be_bytes([*high, *low]),
+            [high] => u16::from_be_bytes([*high, 0]),
+            _ => 0,
+        };
+        sum += u32::from(word);
+    }
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Encodes an echo message with its checksum filled in.
+pub fn encode_echo(packet: &EchoPacket) -> Vec<u8> {
+    let kind = match packet.kind {
+        EchoKind::Request => TYPE_ECHO_REQUEST,
+        EchoKind::Reply => TYPE_ECHO_REPLY,
+    };
+    let mut bytes = Vec::with_capacity(ECHO_HEADER_LEN + packet.payload.len());
+    bytes.extend_from_slice(&[kind, 0, 0, 0]);
+    bytes.extend_from_slice(&packet.id.to_be_bytes());
+    bytes.extend_from_slice(&packet.seq.to_be_bytes());
+    bytes.extend_from_slice(&packet.payload);
+    let sum = checksum(&bytes);
+    bytes[2..4].copy_from_slice(&sum.to_be_bytes());
+    bytes
+}
+
+/// Decodes an echo message, verifying its checksum.
+pub fn decode_echo(bytes: &[u8]) -> Result<EchoPacket, IcmpError> {
+    if bytes.len() < ECHO_HEADER_LEN {
+        return Err(IcmpError::TooShort);
+    }
+    if checksum(bytes) != 0 {
+        return Err(IcmpError::BadChecksum);
+    }
+    let kind = match bytes[0] {
+        TYPE_ECHO_REQUEST => EchoKind::Request,
+        TYPE_ECHO_REPLY => EchoKind::Reply,
+        other => return Err(IcmpError::UnsupportedType(other)),
+    };
+    Ok(EchoPacket {
+        kind,
+        id: u16::from_be_bytes([bytes[4], bytes[5]]),
+        seq: u16::from_be_bytes([bytes[6], bytes[7]]),
+        payload: bytes[ECHO_HEADER_LEN..].to_vec(),
+    })
+}
+
+/// Builds the reply a host sends for an echo request, or None for other messages.
+pub fn echo_reply_for(request: &[u8]) -> Option<Vec<u8>> {
+    let packet = decode_echo(request).ok()?;
+    if packet.kind != EchoKind::Request {
+        return None;
+    }
+    Some(encode_echo(&EchoPacket {
+        kind: EchoKind::Reply,
+        ..packet
+    }))
+}
+
+/// Reply matched to an outstanding request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingReply {
+    pub seq: u16,
+    pub rtt_ticks: u64,
+}
+
+/// Totals for a ping session, with round-trip times in ticks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PingStats {
+    pub sent: u32,
+    pub received: u32,
+    pub min_ticks: Option<u64>,
+    pub max_ticks: Option<u64>,
+    pub total_ticks: u64,
+}
+
+impl PingStats {
+    /// Returns the mean round-trip time, if any reply arrived.
+    pub fn avg_ticks(&self) -> Option<u64> {
+        (self.received > 0).then(|| self.total_ticks / u64::from(self.received))
+    }
+
+    /// Returns the share of requests without a reply, in whole percent.
+    pub fn loss_percent(&self) -> u32 {
+        if self.sent == 0 {
+            return 0;
+        }
+        let lost = self.sent.saturating_sub(self.received);
+        (u64::from(lost) * 100 / u64::from(self.sent)) as u32
+    }
+}
+
+/// Echo requests sent to one target and the replies matched to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PingSession {
+    id: u16,
+    next_seq: u16,
+    outstanding: BTreeMap<u16, u64>,
+    stats: PingStats,
+}
+
+impl PingSession {
+    /// Creates a session whose requests carry identifier `id`.
+    pub fn new(id: u16) -> Self {
+        Self {
+            id,
+            next_seq: 1,
+            outstanding: BTreeMap::new(),
+            stats: PingStats::default(),
+        }
+    }
+
+    /// Returns the identifier stamped on this session's requests.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Builds the next echo request and records when it was sent.
+    pub fn next_request(&mut self, now_ticks: u64) -> Vec<u8> {
+        let seq = self.next_seq;
+        self.next_seq = seq.wrapping_add(1);
+        self.outstanding.insert(seq, now_ticks);
+        self.stats.sent += 1;
+        encode_echo(&EchoPacket {
+            kind: EchoKind::Request,
+            id: self.id,
+            seq,
+            payload: PING_PAYLOAD.to_vec(),
+        })
+    }
+
+    /// Matches a received message to an outstanding request.
+    ///
+    /// Replies for another session, duplicates, and late replies already
+    /// expired return `Ok(None)`.
+    pub fn handle_reply(
+        &mut self,
+        bytes: &[u8],
+        now_ticks: u64,
+    ) -> Result<Option<PingReply>, IcmpError> {
+        let packet = decode_echo(bytes)?;
+        if packet.kind != EchoKind::Reply || packet.id != self.id {
+            return Ok(None);
+        }
+        let Some(sent_at) = self.outstanding.remove(&packet.seq) else {
+            return Ok(None);
+        };
+        let rtt_ticks = now_ticks.saturating_sub(sent_at);
+        let stats = &mut self.stats;
+        stats.received += 1;
+        stats.total_ticks += rtt_ticks;
+        stats.min_ticks = Some(stats.min_ticks.map_or(rtt_ticks, |min| min.min(rtt_ticks)));
+        stats.max_ticks = Some(stats.max_ticks.map_or(rtt_ticks, |max| max.max(rtt_ticks)));
+        Ok(Some(PingReply {
+            seq: packet.seq,
+            rtt_ticks,
+        }))
+    }
+
+    /// Gives up on requests older than `timeout_ticks`, returning their sequence numbers.
+    pub fn expire(&mut self, now_ticks: u64, timeout_ticks: u64) -> Vec<u16> {
+        let expired: Vec<u16> = self
+            .outstanding
+            .iter()
+            .filter(|(_, sent_at)| now_ticks.saturating_sub(**sent_at) >= timeout_ticks)
+            .map(|(seq, _)| *seq)
+            .collect();
+        for seq in &expired {
+            self.outstanding.remove(seq);
+        }
+        expired
+    }
+
+    /// Returns the number of requests still waiting for a reply.
+    pub fn outstanding(&self) -> usize {
+        self.outstanding.len()
+    }
+
+    /// Returns the session totals so far.
+    pub fn stats(&self) -> PingStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_matches_rfc1071_example() {
+        let bytes = [0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7];
+        assert_eq!(checksum(&bytes), !0xddf2);
+        assert_eq!(checksum(&[0xab]), !0xab00);
+    }
+
+    #[test]
+    fn echo_packets_round_trip() {
+        let request = EchoPacket {
+            kind: EchoKind::Request,
+            id: 0x1234,
+            seq: 7,
+            payload: b"odd".to_vec(),
+        };
+        let bytes = encode_echo(&request);
+        assert_eq!(&bytes[..2], &[TYPE_ECHO_REQUEST, 0]);
+        assert_eq!(checksum(&bytes), 0);
+        assert_eq!(decode_echo(&bytes), Ok(request.clone()));
+
+        let reply = echo_reply_for(&bytes).unwrap();
+        assert_eq!(
+            decode_echo(&reply),
+            Ok(EchoPacket {
+                kind: EchoKind::Reply,
+                ..request
+            })
+        );
+        assert_eq!(echo_reply_for(&reply), None);
+    }
+
+    #[test]
+    fn decode_echo_rejects_bad_messages() {
+        let mut bytes = encode_echo(&EchoPacket {
+            kind: EchoKind::Reply,
+            id: 1,
+            seq: 1,
+            payload: Vec::new(),
+        });
+        assert_eq!(decode_echo(&bytes[..7]), Err(IcmpError::TooShort));
+        bytes[7] ^= 0x01;
+        assert_eq!(decode_echo(&bytes), Err(IcmpError::BadChecksum));
+
+        let mut unreachable = vec![3, 1, 0, 0, 0, 0, 0, 0];
+        let sum = checksum(&unreachable);
+        unreachable[2..4].copy_from_slice(&sum.to_be_bytes());
+        assert_eq!(
+            decode_echo(&unreachable),
+            Err(IcmpError::UnsupportedType(3))
+        );
+    }
+
+    #[test]
+    fn session_tracks_sequence_and_rtt() {
+        let mut session = PingSession::new(42);
+        let first = session.next_request(100);
+        let second = session.next_request(200);
+        assert_eq!(decode_echo(&first).unwrap().seq, 1);
+        assert_eq!(decode_echo(&second).unwrap().seq, 2);
+        assert_eq!(session.outstanding(), 2);
+
+        let reply = echo_reply_for(&second).unwrap();
+        assert_eq!(
+            session.handle_reply(&reply, 203),
+            Ok(Some(PingReply {
+                seq: 2,
+                rtt_ticks: 3
+            }))
+        );
+        assert_eq!(session.handle_reply(&reply, 204), Ok(None));
+        assert_eq!(session.handle_reply(&first, 204), Ok(None));
+        let reply = echo_reply_for(&first).unwrap();
+        assert_eq!(
+            session.handle_reply(&reply, 111),
+            Ok(Some(PingReply {
+                seq: 1,
+                rtt_ticks: 11
+            }))
+        );
+
+        let stats = session.stats();
+        assert_eq!((stats.sent, stats.received), (2, 2));
+        assert_eq!((stats.min_ticks, stats.max_ticks), (Some(3), Some(11)));
+        assert_eq!(stats.avg_ticks(), Some(7));
+        assert_eq!(stats.loss_percent(), 0);
+    }
+
+    #[test]
+    fn session_ignores_other_ids_and_expires_lost_requests() {
+        let mut session = PingSession::new(1);
+        let mut other = PingSession::new(2);
+        let request = session.next_request(0);
+        session.next_request(50);
+        let foreign = echo_reply_for(&other.next_request(0)).unwrap();
+        assert_eq!(session.handle_reply(&foreign, 5), Ok(None));
+        assert_eq!(
+            session.handle_reply(&foreign[..4], 5),
+            Err(IcmpError::TooShort)
+        );
+
+        assert_eq!(session.expire(100, 100), vec![1]);
+        assert_eq!(session.outstanding(), 1);
+        let late = echo_reply_for(&request).unwrap();
+        assert_eq!(session.handle_reply(&late, 120), Ok(None));
+
+        let stats = session.stats();
+        assert_eq!((stats.sent, stats.received), (2, 0));
+        assert_eq!(stats.avg_ticks(), None);
+        assert_eq!(stats.loss_percent(), 100);
+        assert_eq!(PingStats::default().loss_percent(), 0);
+    }
+}
@@ -3,9 +3,26 @@
 extern crate alloc;
 
 use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
+use device::{check_frame, frame_destination, DeviceError, NetDevice, RX_BATCH};
+use events::{NetEvent, NetSubscription, Subscribers};
+
+/// Text form of interfaces and routes for saving across reboots.
+pub mod config;
+/// Stub DNS resolver backed by `/etc/hosts` and `/etc/resolv.conf`.
+pub mod dns;
+/// Network device trait and an in-memory loopback device.
+pub mod device;
+/// Change notifications queued for `NetManager` subscribers.
+pub mod events;
+/// Ordered allow/deny rules evaluated against packet summaries.
+pub mod firewall;
+/// ICMP echo messages and ping session bookkeeping.
+pub mod icmp;
+
 /// Errors for the net service model.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NetError {
@@ -13,6 +30,14 @@ pub enum NetError {
     AlreadyExists,
     InvalidName,
     InvalidAddress,
+    InvalidMac,
+    /// Interface is required by the system and cannot be removed.
+    Protected,
+    /// Interface or its device link is down.
+    InterfaceDown,
+    Device(DeviceError),
+    /// Line of a saved config that could not be parsed (1-based).
+    InvalidConfig(usize),
 }
 
 /// Errors for route table operations.
@@ -22,6 +47,67 @@ pub enum RouteError {
     AlreadyExists,
     InvalidDestination,
     InvalidInterface,
+    InvalidGateway,
+    /// Metric is above `MAX_ROUTE_METRIC`.
+    InvalidMetric,
+}
+
+/// Ethernet hardware address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl MacAddr {
+    /// Prefix for generated addresses: locally administered, unicast, then "RZ".
+    pub const LOCAL_PREFIX: [u8; 3] = [0x02, 0x52, 0x5a];
+
+    /// Parses `aa:bb:cc:dd:ee:ff` (hex, `:` or `-` separated).
+    pub fn parse(text: &str) -> Option<Self> {
+        let separator = if text.contains('-') { '-' } else { ':' };
+        let mut octets = [0u8; 6];
+        let mut parts = text.split(separator);
+        for slot in octets.iter_mut() {
+            let part = parts.next()?;
+            if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            *slot = u8::from_str_radix(part, 16).ok()?;
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self(octets))
+    }
+
+    /// Returns the locally administered unicast address for `index`.
+    pub fn local(index: u32) -> Self {
+        let [_, a, b, c] = index.to_be_bytes();
+        let [p0, p1, p2] = Self::LOCAL_PREFIX;
+        Self([p0, p1, p2, a, b, c])
+    }
+
+    /// Returns true for group (multicast or broadcast) addresses.
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    /// Returns true if the address was not assigned by a vendor.
+    pub fn is_locally_administered(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    /// Returns true if the address may be assigned to an interface.
+    pub fn is_assignable(&self) -> bool {
+        !self.is_multicast() && self.0 != [0; 6]
+    }
+
+    /// Formats the address as lowercase `aa:bb:cc:dd:ee:ff`.
+    pub fn format(&self) -> String {
+        let [a, b, c, d, e, g] = self.0;
+        format!(
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            a, b, c, d, e, g
+        )
+    }
 }
 
 /// Simple representation of a network interface.
@@ -29,7 +115,35 @@ pub enum RouteError {
 pub struct NetInterface {
     pub name: String,
     pub up: bool,
+    pub mac: MacAddr,
     pub ipv4: Option<String>,
+    pub prefix: Option<u8>,
+    pub stats: InterfaceStats,
+}
+
+/// Traffic counters kept by `NetManager::transmit` and `NetManager::receive`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterfaceStats {
+    pub rx_packets: u64,
+    pub rx_bytes: u64,
+    /// Received frames too short or too long for Ethernet.
+    pub rx_errors: u64,
+    /// Received frames addressed elsewhere or arriving while the interface is down.
+    pub rx_dropped: u64,
+    pub tx_packets: u64,
+    pub tx_bytes: u64,
+    /// Frames the device refused to send.
+    pub tx_errors: u64,
+    /// Frames not sent because the interface or its link was down.
+    pub tx_dropped: u64,
+}
+
+impl NetInterface {
+    /// Returns the address in `addr/prefix` form, if one is set.
+    pub fn cidr(&self) -> Option<String> {
+        let addr = self.ipv4.as_ref()?;
+        Some(format!("{}/{}", addr, self.prefix.unwrap_or(32)))
+    }
 }
 
 /// Simple route table entry.
@@ -37,13 +151,30 @@ pub struct NetInterface {
 pub struct RouteEntry {
     pub destination: String,
     pub iface: String,
+    /// Next hop for destinations not directly reachable on `iface`.
+    pub gateway: Option<String>,
+    /// Preference among equally specific routes; lower wins.
+    pub metric: u32,
+    /// True for the subnet route derived from an interface address.
+    pub on_link: bool,
 }
 
+/// Largest metric accepted by `NetManager::add_route`.
+pub const MAX_ROUTE_METRIC: u32 = 9999;
+
+/// Name of the loopback interface created by `NetManager::init_defaults`.
+pub const LOOPBACK_IFACE: &str = "lo";
+/// Address and prefix assigned to the loopback interface.
+pub const LOOPBACK_CIDR: &str = "127.0.0.1/8";
+
 /// In-memory network configuration manager.
 #[derive(Debug, Default, Clone)]
 pub struct NetManager {
     interfaces: BTreeMap<String, NetInterface>,
     routes: BTreeMap<String, RouteEntry>,
+    next_mac: u32,
+    hostname: Option<String>,
+    subscribers: Subscribers,
 }
 
 impl NetManager {
@@ -52,10 +183,73 @@ impl NetManager {
         Self {
             interfaces: BTreeMap::new(),
             routes: BTreeMap::new(),
+            next_mac: 0,
+            hostname: None,
+            subscribers: Subscribers::default(),
         }
     }
 
-    /// Adds an interface by name.
+    /// Returns the name this host announces as the DHCP client hostname option.
+    pub fn hostname(&self) -> Option<&str> {
+        self.hostname.as_deref()
+    }
+
+    /// Sets the name this host announces on the network.
+    pub fn set_hostname(&mut self, hostname: &str) -> Result<(), NetError> {
+        if !dns::is_valid_name(hostname) {
+            return Err(NetError::InvalidName);
+        }
+        self.hostname = Some(hostname.to_string());
+        Ok(())
+    }
+
+    /// Starts queueing `NetEvent`s for a new subscriber.
+    pub fn subscribe(&mut self) -> NetSubscription {
+        self.subscribers.subscribe()
+    }
+
+    /// Drops a subscription and any events still queued for it.
+    pub fn unsubscribe(&mut self, id: NetSubscription) -> Result<(), NetError> {
+        if self.subscribers.unsubscribe(id) {
+            Ok(())
+        } else {
+            Err(NetError::NotFound)
+        }
+    }
+
+    /// Takes the events queued for a subscription, oldest first.
+    ///
+    /// At most `NET_EVENT_QUEUE_LIMIT` events are kept; older ones are dropped.
+    pub fn poll_events(&mut self, id: NetSubscription) -> Result<Vec<NetEvent>, NetError> {
+        self.subscribers.take(id).ok_or(NetError::NotFound)
+    }
+
+    /// Creates the loopback interface, up with `127.0.0.1/8`, if it is missing.
+    pub fn init_defaults(&mut self) {
+        if self.interfaces.contains_key(LOOPBACK_IFACE) {
+            return;
+        }
+        self.interfaces.insert(
+            LOOPBACK_IFACE.to_string(),
+            NetInterface {
+                name: LOOPBACK_IFACE.to_string(),
+                up: true,
+                mac: MacAddr([0; 6]),
+                ipv4: None,
+                prefix: None,
+                stats: InterfaceStats::default(),
+            },
+        );
+        self.subscribers.emit(NetEvent::InterfaceAdded {
+            iface: LOOPBACK_IFACE.to_string(),
+        });
+        self.subscribers.emit(NetEvent::LinkUp {
+            iface: LOOPBACK_IFACE.to_string(),
+        });
+        let _ = self.set_ipv4(LOOPBACK_IFACE, Some(LOOPBACK_CIDR));
+    }
+
+    /// Adds an interface by name with a generated local MAC address.
     pub fn add_interface(&mut self, name: &str) -> Result<(), NetError> {
         if !is_valid_iface_name(name) {
             return Err(NetError::InvalidName);
@@ -63,20 +257,34 @@ impl NetManager {
         if self.interfaces.contains_key(name) {
             return Err(NetError::AlreadyExists);
         }
+        let mac = self.generate_mac();
         self.interfaces.insert(
             name.to_string(),
             NetInterface {
                 name: name.to_string(),
                 up: false,
+                mac,
                 ipv4: None,
+                prefix: None,
+                stats: InterfaceStats::default(),
             },
         );
+        self.subscribers.emit(NetEvent::InterfaceAdded {
+            iface: name.to_string(),
+        });
         Ok(())
     }
 
-    /// Removes an interface.
+    /// Removes an interface; the loopback interface is protected.
     pub fn remove_interface(&mut self, name: &str) -> Result<(), NetError> {
+        if name == LOOPBACK_IFACE {
+            return Err(NetError::Protected);
+        }
         if self.interfaces.remove(name).is_some() {
+            self.remove_on_link_route(name, None);
+            self.subscribers.emit(NetEvent::InterfaceRemoved {
+                iface: name.to_string(),
+            });
             Ok(())
         } else {
             Err(NetError::NotFound)
@@ -86,20 +294,162 @@ impl NetManager {
     /// Sets interface up/down state.
     pub fn set_up(&mut self, name: &str, up: bool) -> Result<(), NetError> {
         let iface = self.interfaces.get_mut(name).ok_or(NetError::NotFound)?;
+        if iface.up == up {
+            return Ok(());
+        }
         iface.up = up;
+        let iface = name.to_string();
+        self.subscribers.emit(if up {
+            NetEvent::LinkUp { iface }
+        } else {
+            NetEvent::LinkDown { iface }
+        });
+        Ok(())
+    }
+
+    /// Assigns a hardware address, e.g. the one reported by a NIC driver.
+    pub fn set_mac(&mut self, name: &str, mac: MacAddr) -> Result<(), NetError> {
+        if !mac.is_assignable() {
+            return Err(NetError::InvalidMac);
+        }
+        if !self.interfaces.contains_key(name) {
+            return Err(NetError::NotFound);
+        }
+        if self
+            .interfaces
+            .values()
+            .any(|iface| iface.name != name && iface.mac == mac)
+        {
+            return Err(NetError::AlreadyExists);
+        }
+        if let Some(iface) = self.interfaces.get_mut(name) {
+            iface.mac = mac;
+        }
         Ok(())
     }
 
-    /// Sets or clears an IPv4 address.
+    /// Adds or updates interface `name` to carry a device's address and link state.
+    pub fn attach_device(&mut self, name: &str, device: &impl NetDevice) -> Result<(), NetError> {
+        let mac = device.mac();
+        if !mac.is_assignable() {
+            return Err(NetError::InvalidMac);
+        }
+        if self
+            .interfaces
+            .values()
+            .any(|iface| iface.name != name && iface.mac == mac)
+        {
+            return Err(NetError::AlreadyExists);
+        }
+        if !self.interfaces.contains_key(name) {
+            self.add_interface(name)?;
+        }
+        self.set_mac(name, mac)?;
+        self.set_up(name, device.link_up())
+    }
+
+    /// Sends one frame through `device` for interface `name`.
+    pub fn transmit(
+        &mut self,
+        name: &str,
+        device: &mut impl NetDevice,
+        frame: &[u8],
+    ) -> Result<(), NetError> {
+        let iface = self.interfaces.get_mut(name).ok_or(NetError::NotFound)?;
+        if !iface.up || !device.link_up() {
+            iface.stats.tx_dropped += 1;
+            return Err(NetError::InterfaceDown);
+        }
+        if let Err(err) = device.transmit(frame) {
+            iface.stats.tx_errors += 1;
+            return Err(NetError::Device(err));
+        }
+        iface.stats.tx_packets += 1;
+        iface.stats.tx_bytes += frame.len() as u64;
+        Ok(())
+    }
+
+    /// Takes frames from `device` that are addressed to interface `name`.
+    ///
+    /// Malformed frames count as errors and unicast frames for other addresses
+    /// as dropped; broadcast and multicast frames are kept.
+    pub fn receive(
+        &mut self,
+        name: &str,
+        device: &mut impl NetDevice,
+    ) -> Result<Vec<Vec<u8>>, NetError> {
+        let iface = self.interfaces.get_mut(name).ok_or(NetError::NotFound)?;
+        let mut frames = Vec::new();
+        for _ in 0..RX_BATCH {
+            let Some(frame) = device.receive() else {
+                break;
+            };
+            if check_frame(&frame).is_err() {
+                iface.stats.rx_errors += 1;
+                continue;
+            }
+            let for_us =
+                frame_destination(&frame).is_some_and(|dst| dst == iface.mac || dst.is_multicast());
+            if !iface.up || !for_us {
+                iface.stats.rx_dropped += 1;
+                continue;
+            }
+            iface.stats.rx_packets += 1;
+            iface.stats.rx_bytes += frame.len() as u64;
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
+    /// Sets or clears an IPv4 address given as `addr/prefix`.
+    ///
+    /// A bare address is taken as `/32`. Any shorter prefix installs an
+    /// on-link route for the interface's subnet, replacing the previous one.
     pub fn set_ipv4(&mut self, name: &str, addr: Option<&str>) -> Result<(), NetError> {
+        let parsed = match addr {
+            Some(spec) => Some(parse_ipv4_cidr(spec).ok_or(NetError::InvalidAddress)?),
+            None => None,
+        };
         let iface = self.interfaces.get_mut(name).ok_or(NetError::NotFound)?;
-        if let Some(addr) = addr {
-            if !is_valid_ipv4(addr) {
-                return Err(NetError::InvalidAddress);
+        let before = iface.cidr();
+        match &parsed {
+            Some((addr, prefix)) => {
+                iface.ipv4 = Some(addr.clone());
+                iface.prefix = Some(*prefix);
+            }
+            None => {
+                iface.ipv4 = None;
+                iface.prefix = None;
+            }
+        }
+        let cidr = iface.cidr();
+        if cidr != before {
+            self.subscribers.emit(NetEvent::AddressChanged {
+                iface: name.to_string(),
+                cidr,
+            });
+        }
+        let on_link = parsed
+            .filter(|(_, prefix)| *prefix < 32)
+            .map(|(addr, prefix)| format!("{}/{}", network_address(&addr, prefix), prefix));
+        self.remove_on_link_route(name, on_link.as_deref());
+        if let Some(destination) = on_link {
+            if !self.routes.contains_key(&destination) {
+                self.routes.insert(
+                    destination.clone(),
+                    RouteEntry {
+                        destination: destination.clone(),
+                        iface: name.to_string(),
+                        gateway: None,
+                        metric: 0,
+                        on_link: true,
+                    },
+                );
+                self.subscribers.emit(NetEvent::RouteAdded {
+                    destination,
+                    iface: name.to_string(),
+                });
             }
-            iface.ipv4 = Some(addr.to_string());
-        } else {
-            iface.ipv4 = None;
         }
         Ok(())
     }
@@ -109,14 +459,26 @@ impl NetManager {
         self.interfaces.values().cloned().collect()
     }
 
-    /// Adds a route entry.
-    pub fn add_route(&mut self, destination: &str, iface: &str) -> Result<(), RouteError> {
+    /// Adds a route entry, optionally via a gateway.
+    pub fn add_route(
+        &mut self,
+        destination: &str,
+        iface: &str,
+        gateway: Option<&str>,
+        metric: u32,
+    ) -> Result<(), RouteError> {
         if !is_valid_route_destination(destination) {
             return Err(RouteError::InvalidDestination);
         }
         if !is_valid_iface_name(iface) {
             return Err(RouteError::InvalidInterface);
         }
+        if gateway.is_some_and(|gateway| !is_valid_gateway(gateway)) {
+            return Err(RouteError::InvalidGateway);
+        }
+        if metric > MAX_ROUTE_METRIC {
+            return Err(RouteError::InvalidMetric);
+        }
         if self.routes.contains_key(destination) {
             return Err(RouteError::AlreadyExists);
         }
@@ -125,8 +487,15 @@ impl NetManager {
             RouteEntry {
                 destination: destination.to_string(),
                 iface: iface.to_string(),
+                gateway: gateway.map(ToString::to_string),
+                metric,
+                on_link: false,
             },
         );
+        self.subscribers.emit(NetEvent::RouteAdded {
+            destination: destination.to_string(),
+            iface: iface.to_string(),
+        });
         Ok(())
     }
 
@@ -135,20 +504,89 @@ impl NetManager {
         if !is_valid_route_destination(destination) {
             return Err(RouteError::InvalidDestination);
         }
-        if self.routes.remove(destination).is_some() {
-            Ok(())
-        } else {
-            Err(RouteError::NotFound)
+        let route = self
+            .routes
+            .remove(destination)
+            .ok_or(RouteError::NotFound)?;
+        self.subscribers.emit(NetEvent::RouteRemoved {
+            destination: route.destination,
+            iface: route.iface,
+        });
+        Ok(())
+    }
+
+    /// Picks the route for `dest_ip`: longest prefix first, then lowest metric.
+    pub fn lookup_route(&self, dest_ip: &str) -> Option<RouteEntry> {
+        if !is_valid_ipv4(dest_ip) {
+            return None;
         }
+        let target = u32::from_be_bytes(ipv4_octets(dest_ip));
+        self.routes
+            .values()
+            .filter_map(|route| {
+                let (network, prefix) = route_prefix(&route.destination)?;
+                let mask = prefix_mask(prefix);
+                (target & mask == network & mask).then_some((prefix, route))
+            })
+            .min_by_key(|(prefix, route)| (core::cmp::Reverse(*prefix), route.metric))
+            .map(|(_, route)| route.clone())
     }
 
     /// Lists route entries sorted by destination.
     pub fn list_routes(&self) -> Vec<RouteEntry> {
         self.routes.values().cloned().collect()
     }
+
+    fn generate_mac(&mut self) -> MacAddr {
+        loop {
+            let mac = MacAddr::local(self.next_mac);
+            self.next_mac = self.next_mac.wrapping_add(1) & 0x00ff_ffff;
+            if mac.is_assignable() && !self.interfaces.values().any(|iface| iface.mac == mac) {
+                return mac;
+            }
+        }
+    }
+
+    fn remove_on_link_route(&mut self, iface: &str, keep: Option<&str>) {
+        let stale: Vec<String> = self
+            .routes
+            .values()
+            .filter(|route| route.on_link && route.iface == iface)
+            .filter(|route| keep != Some(route.destination.as_str()))
+            .map(|route| route.destination.clone())
+            .collect();
+        for destination in stale {
+            self.routes.remove(&destination);
+            self.subscribers.emit(NetEvent::RouteRemoved {
+                destination,
+                iface: iface.to_string(),
+            });
+        }
+    }
 }
 
-fn is_valid_iface_name(name: &str) -> bool {
+/// Splits `addr/prefix` into the address and prefix length.
+///
+/// A bare address is returned with prefix `32`.
+pub fn parse_ipv4_cidr(spec: &str) -> Option<(String, u8)> {
+    let (addr, prefix) = match spec.split_once('/') {
+        Some((addr, prefix)) => {
+            if prefix.is_empty() || prefix.len() > 2 || !prefix.bytes().all(|b| b.is_ascii_digit())
+            {
+                return None;
+            }
+            (addr, prefix.parse::<u8>().ok()?)
+        }
+        None => (spec, 32),
+    };
+    if !is_valid_ipv4(addr) || prefix > 32 {
+        return None;
+    }
+    Some((addr.to_string(), prefix))
+}
+
+/// Returns true for interface names made of lowercase letters, digits and `-`.
+pub fn is_valid_iface_name(name: &str) -> bool {
     if name.is_empty() {
         return false;
     }
@@ -156,7 +594,8 @@ fn is_valid_iface_name(name: &str) -> bool {
         .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-')
 }
 
-fn is_valid_ipv4(addr: &str) -> bool {
+/// Returns true for a dotted-quad IPv4 address without leading zeros.
+pub fn is_valid_ipv4(addr: &str) -> bool {
     let parts: Vec<&str> = addr.split('.').collect();
     if parts.len() != 4 {
         return false;
@@ -177,7 +616,43 @@ fn is_valid_ipv4(addr: &str) -> bool {
     true
 }
 
-fn is_valid_route_destination(dest: &str) -> bool {
+fn ipv4_octets(addr: &str) -> [u8; 4] {
+    let mut octets = [0u8; 4];
+    for (slot, part) in octets.iter_mut().zip(addr.split('.')) {
+        *slot = part.parse().unwrap_or(0);
+    }
+    octets
+}
+
+fn prefix_mask(prefix: u8) -> u32 {
+    if prefix == 0 {
+        0
+    } else {
+        u32::MAX << (32 - u32::from(prefix))
+    }
+}
+
+fn network_address(addr: &str, prefix: u8) -> String {
+    let [a, b, c, d] = (u32::from_be_bytes(ipv4_octets(addr)) & prefix_mask(prefix)).to_be_bytes();
+    format!("{}.{}.{}.{}", a, b, c, d)
+}
+
+/// Returns a route destination as a network number and prefix length.
+fn route_prefix(destination: &str) -> Option<(u32, u8)> {
+    if destination == "default" {
+        return Some((0, 0));
+    }
+    let (addr, prefix) = parse_ipv4_cidr(destination)?;
+    Some((u32::from_be_bytes(ipv4_octets(&addr)), prefix))
+}
+
+/// Returns true for an IPv4 address usable as a route gateway.
+pub fn is_valid_gateway(addr: &str) -> bool {
+    is_valid_ipv4(addr) && !matches!(ipv4_octets(addr), [0, 0, 0, 0] | [255, 255, 255, 255])
+}
+
+/// Returns true for `default` or an `addr/prefix` route destination.
+pub fn is_valid_route_destination(dest: &str) -> bool {
     if dest == "default" {
         return true;
     }
@@ -232,6 +707,187 @@ mod tests {
         assert_eq!(manager.remove_interface("eth0"), Err(NetError::NotFound));
     }
 
+    #[test]
+    fn attached_device_carries_frames() {
+        use device::{ethernet_frame, LoopbackDevice};
+
+        let nic = MacAddr([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]);
+        let mut device = LoopbackDevice::new(nic);
+        let mut manager = NetManager::new();
+        let frame = ethernet_frame(nic, nic, 0x0800, b"ping");
+        assert_eq!(
+            manager.transmit("eth0", &mut device, &frame),
+            Err(NetError::NotFound)
+        );
+        manager.attach_device("eth0", &device).unwrap();
+        let iface = manager.list().pop().unwrap();
+        assert_eq!((iface.mac, iface.up), (nic, true));
+
+        manager.transmit("eth0", &mut device, &frame).unwrap();
+        let broadcast = ethernet_frame(MacAddr([0xff; 6]), nic, 0x0806, b"who-has");
+        device.inject(&broadcast);
+        device.inject(&ethernet_frame(MacAddr::local(99), nic, 0x0800, b"other"));
+        device.inject(&[0xff; 6]);
+        assert_eq!(
+            manager.receive("eth0", &mut device),
+            Ok(vec![frame.clone(), broadcast])
+        );
+
+        manager.set_up("eth0", false).unwrap();
+        assert_eq!(
+            manager.transmit("eth0", &mut device, &frame),
+            Err(NetError::InterfaceDown)
+        );
+        manager.set_up("eth0", true).unwrap();
+        assert_eq!(
+            manager.transmit("eth0", &mut device, &[]),
+            Err(NetError::Device(DeviceError::BadFrame))
+        );
+        assert_eq!(
+            manager.attach_device("eth1", &LoopbackDevice::new(MacAddr([0; 6]))),
+            Err(NetError::InvalidMac)
+        );
+        assert_eq!(
+            manager.attach_device("eth1", &device),
+            Err(NetError::AlreadyExists)
+        );
+        assert_eq!(manager.list().len(), 1);
+    }
+
+    #[test]
+    fn device_traffic_updates_interface_stats() {
+        use device::{ethernet_frame, LoopbackDevice};
+
+        let nic = MacAddr([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]);
+        let mut device = LoopbackDevice::new(nic);
+        let mut manager = NetManager::new();
+        manager.attach_device("eth0", &device).unwrap();
+        let frame = ethernet_frame(nic, nic, 0x0800, b"ping");
+        let broadcast = ethernet_frame(MacAddr([0xff; 6]), nic, 0x0806, b"who-has");
+        manager.transmit("eth0", &mut device, &frame).unwrap();
+        device.inject(&broadcast);
+        device.inject(&ethernet_frame(MacAddr::local(99), nic, 0x0800, b"other"));
+        device.inject(&[0xff; 6]);
+        manager.receive("eth0", &mut device).unwrap();
+        let stats = manager.list().pop().unwrap().stats;
+        assert_eq!((stats.tx_packets, stats.tx_bytes), (1, frame.len() as u64));
+        assert_eq!(
+            (stats.rx_packets, stats.rx_bytes),
+            (2, (frame.len() + broadcast.len()) as u64)
+        );
+        assert_eq!((stats.rx_errors, stats.rx_dropped), (1, 1));
+
+        manager.set_up("eth0", false).unwrap();
+        let _ = manager.transmit("eth0", &mut device, &frame);
+        let _ = manager.transmit("eth0", &mut device, &[]);
+        manager.set_up("eth0", true).unwrap();
+        let _ = manager.transmit("eth0", &mut device, &[]);
+        let stats = manager.list().pop().unwrap().stats;
+        assert_eq!(
+            (stats.tx_packets, stats.tx_errors, stats.tx_dropped),
+            (1, 1, 2)
+        );
+    }
+
+    #[test]
+    fn init_defaults_creates_protected_loopback() {
+        let mut manager = NetManager::new();
+        manager.init_defaults();
+        manager.init_defaults();
+        let list = manager.list();
+        assert_eq!(list.len(), 1);
+        let lo = &list[0];
+        assert_eq!(lo.name, LOOPBACK_IFACE);
+        assert!(lo.up);
+        assert_eq!(lo.cidr().as_deref(), Some(LOOPBACK_CIDR));
+        assert_eq!(
+            manager.list_routes(),
+            vec![RouteEntry {
+                destination: "127.0.0.0/8".to_string(),
+                iface: LOOPBACK_IFACE.to_string(),
+                gateway: None,
+                metric: 0,
+                on_link: true,
+            }]
+        );
+        assert_eq!(
+            manager.remove_interface(LOOPBACK_IFACE),
+            Err(NetError::Protected)
+        );
+        assert_eq!(manager.list().len(), 1);
+
+        manager.add_interface("eth0").unwrap();
+        assert!(manager.list()[0].mac.is_assignable());
+    }
+
+    #[test]
+    fn interfaces_get_unique_local_macs() {
+        let mut manager = NetManager::new();
+        manager.add_interface("eth0").unwrap();
+        manager.add_interface("eth1").unwrap();
+        let list = manager.list();
+        assert_eq!(list[0].mac.format(), "02:52:5a:00:00:00");
+        assert_eq!(list[1].mac.format(), "02:52:5a:00:00:01");
+        for iface in &list {
+            assert!(iface.mac.is_locally_administered());
+            assert!(iface.mac.is_assignable());
+        }
+
+        manager.remove_interface("eth0").unwrap();
+        manager
+            .set_mac("eth1", MacAddr::parse("02:52:5a:00:00:02").unwrap())
+            .unwrap();
+        manager.add_interface("eth2").unwrap();
+        assert_eq!(manager.list()[1].mac.format(), "02:52:5a:00:00:03");
+    }
+
+    #[test]
+    fn set_mac_validates_addresses() {
+        let mut manager = NetManager::new();
+        manager.add_interface("eth0").unwrap();
+        manager.add_interface("eth1").unwrap();
+        let vendor = MacAddr::parse("00-1b-21-12-34-56").unwrap();
+        manager.set_mac("eth0", vendor).unwrap();
+        assert_eq!(manager.list()[0].mac, vendor);
+        assert!(!vendor.is_locally_administered());
+        assert_eq!(manager.set_mac("eth0", vendor), Ok(()));
+        assert_eq!(
+            manager.set_mac("eth1", vendor),
+            Err(NetError::AlreadyExists)
+        );
+        assert_eq!(
+            manager.set_mac("eth1", MacAddr([0xff; 6])),
+            Err(NetError::InvalidMac)
+        );
+        assert_eq!(
+            manager.set_mac("eth1", MacAddr([0; 6])),
+            Err(NetError::InvalidMac)
+        );
+        assert_eq!(
+            manager.set_mac("eth9", MacAddr::local(7)),
+            Err(NetError::NotFound)
+        );
+    }
+
+    #[test]
+    fn mac_parse_rejects_malformed_text() {
+        assert_eq!(
+            MacAddr::parse("AA:bb:0c:00:00:01"),
+            Some(MacAddr([0xaa, 0xbb, 0x0c, 0, 0, 1]))
+        );
+        for text in [
+            "",
+            "aa:bb:cc:dd:ee",
+            "aa:bb:cc:dd:ee:ff:00",
+            "aa:bb:cc:dd:ee:f",
+            "aa:bb:cc:dd:ee:gg",
+            "aa-bb:cc:dd:ee:ff",
+            "+a:bb:cc:dd:ee:ff",
+        ] {
+            assert_eq!(MacAddr::parse(text), None, "{text}");
+        }
+    }
+
     #[test]
     fn set_up_down() {
         let mut manager = NetManager::new();
@@ -254,8 +910,53 @@ mod tests {
         manager.add_interface("eth0").unwrap();
         manager.set_ipv4("eth0", Some("192.168.0.10")).unwrap();
         assert_eq!(manager.list()[0].ipv4, Some("192.168.0.10".to_string()));
+        assert_eq!(manager.list()[0].prefix, Some(32));
+        assert!(manager.list_routes().is_empty());
         manager.set_ipv4("eth0", None).unwrap();
         assert_eq!(manager.list()[0].ipv4, None);
+        assert_eq!(manager.list()[0].cidr(), None);
+    }
+
+    #[test]
+    fn set_ipv4_with_prefix_derives_on_link_route() {
+        let mut manager = NetManager::new();
+        manager.add_interface("eth0").unwrap();
+        manager.add_route("default", "eth0", None, 0).unwrap();
+        manager.set_ipv4("eth0", Some("192.168.0.10/24")).unwrap();
+        let iface = &manager.list()[0];
+        assert_eq!(iface.ipv4, Some("192.168.0.10".to_string()));
+        assert_eq!(iface.prefix, Some(24));
+        assert_eq!(iface.cidr(), Some("192.168.0.10/24".to_string()));
+        let routes = manager.list_routes();
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].destination, "192.168.0.0/24");
+        assert_eq!(routes[0].iface, "eth0");
+        assert!(routes[0].on_link);
+        assert!(!routes[1].on_link);
+
+        manager.set_ipv4("eth0", Some("10.1.2.3/12")).unwrap();
+        let routes = manager.list_routes();
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].destination, "10.0.0.0/12");
+
+        manager.set_ipv4("eth0", None).unwrap();
+        assert_eq!(manager.list_routes().len(), 1);
+        manager.set_ipv4("eth0", Some("172.16.5.4/16")).unwrap();
+        manager.remove_interface("eth0").unwrap();
+        assert_eq!(manager.list_routes()[0].destination, "default");
+    }
+
+    #[test]
+    fn on_link_route_keeps_existing_static_route() {
+        let mut manager = NetManager::new();
+        manager.add_interface("eth0").unwrap();
+        manager.add_route("10.0.0.0/24", "eth1", None, 0).unwrap();
+        manager.set_ipv4("eth0", Some("10.0.0.5/24")).unwrap();
+        let routes = manager.list_routes();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].iface, "eth1");
+        manager.set_ipv4("eth0", None).unwrap();
+        assert_eq!(manager.list_routes().len(), 1);
     }
 
     #[test]
@@ -282,6 +983,19 @@ mod tests {
             manager.set_ipv4("eth0", Some("10.0.0.0000")),
             Err(NetError::InvalidAddress)
         );
+        for spec in [
+            "10.0.0.1/33",
+            "10.0.0.1/",
+            "10.0.0.1/+8",
+            "10.0.0.1/ab",
+            "/24",
+        ] {
+            assert_eq!(
+                manager.set_ipv4("eth0", Some(spec)),
+                Err(NetError::InvalidAddress)
+            );
+        }
+        assert_eq!(manager.list()[0].ipv4, None);
     }
 
     #[test]
@@ -296,8 +1010,8 @@ mod tests {
     #[test]
     fn add_and_list_routes() {
         let mut manager = NetManager::new();
-        manager.add_route("default", "eth0").unwrap();
-        manager.add_route("10.0.0.0/24", "eth1").unwrap();
+        manager.add_route("default", "eth0", None, 0).unwrap();
+        manager.add_route("10.0.0.0/24", "eth1", None, 0).unwrap();
         let routes = manager.list_routes();
         assert_eq!(routes.len(), 2);
         assert_eq!(routes[0].destination, "10.0.0.0/24");
@@ -308,19 +1022,19 @@ mod tests {
     fn add_route_rejects_invalid_destination() {
         let mut manager = NetManager::new();
         assert_eq!(
-            manager.add_route("10.0.0.0", "eth0"),
+            manager.add_route("10.0.0.0", "eth0", None, 0),
             Err(RouteError::InvalidDestination)
         );
         assert_eq!(
-            manager.add_route("10.0.0.0/33", "eth0"),
+            manager.add_route("10.0.0.0/33", "eth0", None, 0),
             Err(RouteError::InvalidDestination)
         );
         assert_eq!(
-            manager.add_route("300.0.0.0/24", "eth0"),
+            manager.add_route("300.0.0.0/24", "eth0", None, 0),
             Err(RouteError::InvalidDestination)
         );
         assert_eq!(
-            manager.add_route("10.0.0.0/ab", "eth0"),
+            manager.add_route("10.0.0.0/ab", "eth0", None, 0),
             Err(RouteError::InvalidDestination)
         );
     }
@@ -329,7 +1043,7 @@ mod tests {
     fn add_route_rejects_invalid_interface() {
         let mut manager = NetManager::new();
         assert_eq!(
-            manager.add_route("default", "Eth0"),
+            manager.add_route("default", "Eth0", None, 0),
             Err(RouteError::InvalidInterface)
         );
     }
@@ -337,17 +1051,64 @@ mod tests {
     #[test]
     fn add_route_rejects_duplicates() {
         let mut manager = NetManager::new();
-        manager.add_route("default", "eth0").unwrap();
+        manager.add_route("default", "eth0", None, 0).unwrap();
         assert_eq!(
-            manager.add_route("default", "eth1"),
+            manager.add_route("default", "eth1", None, 0),
             Err(RouteError::AlreadyExists)
         );
     }
 
+    #[test]
+    fn add_route_validates_gateway_and_metric() {
+        let mut manager = NetManager::new();
+        for gateway in ["10.0.0", "0.0.0.0", "255.255.255.255"] {
+            assert_eq!(
+                manager.add_route("default", "eth0", Some(gateway), 0),
+                Err(RouteError::InvalidGateway)
+            );
+        }
+        assert_eq!(
+            manager.add_route("default", "eth0", None, MAX_ROUTE_METRIC + 1),
+            Err(RouteError::InvalidMetric)
+        );
+        manager
+            .add_route("default", "eth0", Some("10.0.0.1"), MAX_ROUTE_METRIC)
+            .unwrap();
+        let route = &manager.list_routes()[0];
+        assert_eq!(route.gateway.as_deref(), Some("10.0.0.1"));
+        assert_eq!(route.metric, MAX_ROUTE_METRIC);
+    }
+
+    #[test]
+    fn lookup_route_prefers_longest_prefix_then_metric() {
+        let mut manager = NetManager::new();
+        manager.add_interface("eth0").unwrap();
+        manager.set_ipv4("eth0", Some("10.0.0.5/24")).unwrap();
+        manager
+            .add_route("default", "eth0", Some("10.0.0.1"), 100)
+            .unwrap();
+        manager
+            .add_route("0.0.0.0/0", "wlan0", Some("192.168.1.1"), 10)
+            .unwrap();
+        manager.add_route("10.0.0.0/8", "eth1", None, 0).unwrap();
+
+        let route = |ip: &str| manager.lookup_route(ip).map(|route| route.iface);
+        assert_eq!(route("10.0.0.9").as_deref(), Some("eth0"));
+        assert_eq!(route("10.9.9.9").as_deref(), Some("eth1"));
+        assert_eq!(route("8.8.8.8").as_deref(), Some("wlan0"));
+        assert_eq!(route("8.8.8"), None);
+
+        manager.remove_route("0.0.0.0/0").unwrap();
+        let default = manager.lookup_route("8.8.8.8").unwrap();
+        assert_eq!(default.gateway.as_deref(), Some("10.0.0.1"));
+        manager.remove_route("default").unwrap();
+        assert_eq!(manager.lookup_route("8.8.8.8"), None);
+    }
+
     #[test]
     fn remove_route_roundtrip() {
         let mut manager = NetManager::new();
-        manager.add_route("default", "eth0").unwrap();
+        manager.add_route("default", "eth0", None, 0).unwrap();
         assert_eq!(manager.remove_route("default"), Ok(()));
         assert_eq!(
             manager.remove_route("default"),
@@ -363,4 +1124,79 @@ mod tests {
             Err(RouteError::InvalidDestination)
         );
     }
+
+    #[test]
+    fn subscribers_see_configuration_changes() {
+        let mut manager = NetManager::new();
+        let id = manager.subscribe();
+        manager.add_interface("eth0").unwrap();
+        manager.set_up("eth0", true).unwrap();
+        manager.set_up("eth0", true).unwrap();
+        manager.set_ipv4("eth0", Some("10.0.0.2/24")).unwrap();
+        manager.set_ipv4("eth0", Some("10.0.0.2/24")).unwrap();
+        manager
+            .add_route("default", "eth0", Some("10.0.0.1"), 0)
+            .unwrap();
+        manager.set_ipv4("eth0", None).unwrap();
+        manager.remove_route("default").unwrap();
+        manager.set_up("eth0", false).unwrap();
+        manager.remove_interface("eth0").unwrap();
+
+        let iface = || "eth0".to_string();
+        let subnet = || "10.0.0.0/24".to_string();
+        assert_eq!(
+            manager.poll_events(id).unwrap(),
+            vec![
+                NetEvent::InterfaceAdded { iface: iface() },
+                NetEvent::LinkUp { iface: iface() },
+                NetEvent::AddressChanged {
+                    iface: iface(),
+                    cidr: Some("10.0.0.2/24".to_string()),
+                },
+                NetEvent::RouteAdded {
+                    destination: subnet(),
+                    iface: iface(),
+                },
+                NetEvent::RouteAdded {
+                    destination: "default".to_string(),
+                    iface: iface(),
+                },
+                NetEvent::AddressChanged {
+                    iface: iface(),
+                    cidr: None,
+                },
+                NetEvent::RouteRemoved {
+                    destination: subnet(),
+                    iface: iface(),
+                },
+                NetEvent::RouteRemoved {
+                    destination: "default".to_string(),
+                    iface: iface(),
+                },
+                NetEvent::LinkDown { iface: iface() },
+                NetEvent::InterfaceRemoved { iface: iface() },
+            ]
+        );
+        assert_eq!(manager.poll_events(id), Ok(Vec::new()));
+
+        manager.init_defaults();
+        let events = manager.poll_events(id).unwrap();
+        assert!(events.iter().all(|event| event.iface() == LOOPBACK_IFACE));
+        assert_eq!(events.len(), 4);
+
+        manager.unsubscribe(id).unwrap();
+        assert_eq!(manager.unsubscribe(id), Err(NetError::NotFound));
+        assert_eq!(manager.poll_events(id), Err(NetError::NotFound));
+    }
+
+    #[test]
+    fn hostname_is_validated() {
+        let mut manager = NetManager::new();
+        assert_eq!(manager.hostname(), None);
+        manager.set_hostname("lab1.local").unwrap();
+        assert_eq!(manager.hostname(), Some("lab1.local"));
+        assert_eq!(manager.set_hostname("bad name"), Err(NetError::InvalidName));
+        assert_eq!(manager.set_hostname(""), Err(NetError::InvalidName));
+        assert_eq!(manager.hostname(), Some("lab1.local"));
+    }
 }
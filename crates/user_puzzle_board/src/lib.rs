@@ -5,6 +5,7 @@ extern crate alloc;
 use alloc::collections::BTreeMap;
 use alloc::format;
 use alloc::string::{String, ToString};
+use alloc::vec;
 use alloc::vec::Vec;
 
 /// Errors returned when modifying the puzzle board.
@@ -14,6 +15,199 @@ pub enum BoardError {
     SlotAlreadyFilled,
     SlotNotCompatible,
     InvalidSlot,
+    /// The slot requires other slots that are still empty.
+    SlotBlocked(Vec<String>),
+    /// A multi-provider slot holds several modules; unplug one by name.
+    MultipleProviders,
+    /// The slot has no provider to swap out.
+    SlotEmpty,
+    /// A saved board config line (1-based) is malformed.
+    InvalidConfig(usize),
+    /// The slot is locked against unplug and swap.
+    SlotLocked,
+}
+
+/// Module lifecycle steps a staged swap drives, usually backed by init.
+pub trait SwapLifecycle {
+    type Error;
+
+    /// Marks the outgoing provider as draining; it keeps serving meanwhile.
+    fn drain(&mut self, module: &str) -> Result<(), Self::Error>;
+    /// Starts the replacement provider.
+    fn start(&mut self, module: &str) -> Result<(), Self::Error>;
+    /// Stops the drained provider once the slot points at its replacement.
+    fn stop(&mut self, module: &str) -> Result<(), Self::Error>;
+    /// Returns a draining provider to normal service after a failed swap.
+    fn resume(&mut self, module: &str);
+}
+
+/// Why a staged swap stopped before re-pointing the slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwapError<E> {
+    Board(BoardError),
+    Drain(E),
+    Start(E),
+}
+
+impl<E> From<BoardError> for SwapError<E> {
+    fn from(err: BoardError) -> Self {
+        Self::Board(err)
+    }
+}
+
+/// Outcome of a completed staged swap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapReport {
+    pub slot: String,
+    pub old: String,
+    pub new: String,
+    /// False when the old provider could not be stopped and is still draining.
+    pub old_stopped: bool,
+}
+
+/// One board change staged in a [`BoardTransaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardOp {
+    Plug {
+        slot: String,
+        module: String,
+        module_slots: Vec<String>,
+    },
+    Unplug {
+        slot: String,
+    },
+}
+
+/// Plug and unplug operations committed to a board all at once.
+///
+/// [`PuzzleBoard::commit`] applies them in order and keeps none of them if
+/// any fails, so a compound change never leaves the board half-modified.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardTransaction {
+    ops: Vec<BoardOp>,
+}
+
+impl BoardTransaction {
+    /// Creates an empty transaction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stages plugging `module` into `slot`.
+    pub fn plug(&mut self, slot: &str, module: &str, module_slots: &[String]) -> &mut Self {
+        self.ops.push(BoardOp::Plug {
+            slot: slot.to_string(),
+            module: module.to_string(),
+            module_slots: module_slots.to_vec(),
+        });
+        self
+    }
+
+    /// Stages emptying `slot`.
+    pub fn unplug(&mut self, slot: &str) -> &mut Self {
+        self.ops.push(BoardOp::Unplug {
+            slot: slot.to_string(),
+        });
+        self
+    }
+
+    /// Returns the staged operations in commit order.
+    pub fn ops(&self) -> &[BoardOp] {
+        &self.ops
+    }
+}
+
+/// Why [`PuzzleBoard::commit`] rolled a transaction back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionError {
+    /// Position of the failing operation in the transaction.
+    pub index: usize,
+    pub error: BoardError,
+}
+
+/// How many modules a slot can hold at once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SlotCardinality {
+    #[default]
+    Single,
+    Multiple,
+}
+
+/// A `major.minor` slot interface version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SlotVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl SlotVersion {
+    /// Parses `major` or `major.minor`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (major, minor) = parse_partial(text)?;
+        Some(Self {
+            major,
+            minor: minor.unwrap_or(0),
+        })
+    }
+}
+
+/// Half-open range of slot versions: `min` inclusive, `end` exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRange {
+    pub min: SlotVersion,
+    pub end: Option<SlotVersion>,
+}
+
+impl VersionRange {
+    /// Parses what a board slot requires.
+    ///
+    /// A bare `1` or `1.2` accepts that version and any later minor of the
+    /// same major; otherwise the text is a comma-separated list of `>=`, `>`,
+    /// `<`, `<=` and `=` comparators such as `>=1.2, <2`.
+    pub fn requirement(text: &str) -> Option<Self> {
+        match parse_partial(text.trim()) {
+            Some((major, minor)) => Self::bounded(
+                SlotVersion {
+                    major,
+                    minor: minor.unwrap_or(0),
+                },
+                Some(next_major(major)),
+            ),
+            None => parse_comparators(text),
+        }
+    }
+
+    /// Parses what a module provides: a bare version is exactly that version,
+    /// anything else is a comparator range of versions the module can serve.
+    pub fn provided(text: &str) -> Option<Self> {
+        match parse_partial(text.trim()) {
+            Some((major, minor)) => {
+                let min = SlotVersion {
+                    major,
+                    minor: minor.unwrap_or(0),
+                };
+                Self::bounded(min, Some(next_minor(min)))
+            }
+            None => parse_comparators(text),
+        }
+    }
+
+    /// Returns true if `version` lies inside the range.
+    pub fn contains(&self, version: SlotVersion) -> bool {
+        self.min <= version && self.end.is_none_or(|end| version < end)
+    }
+
+    /// Returns true if some version lies in both ranges.
+    pub fn overlaps(&self, other: &VersionRange) -> bool {
+        self.end.is_none_or(|end| other.min < end) && other.end.is_none_or(|end| self.min < end)
+    }
+
+    fn bounded(min: SlotVersion, end: Option<SlotVersion>) -> Option<Self> {
+        match end {
+            Some(end) if end <= min => None,
+            _ => Some(Self { min, end }),
+        }
+    }
 }
 
 /// Describes a slot on the puzzle board.
@@ -21,7 +215,20 @@ pub enum BoardError {
 pub struct PuzzleSlot {
     pub name: String,
     pub required: bool,
-    pub provider: Option<String>,
+    /// Modules filling the slot, in plug order.
+    pub providers: Vec<String>,
+    pub cardinality: SlotCardinality,
+    /// Slots that must be filled before this one can be plugged.
+    pub requires: Vec<String>,
+    /// What the slot is for, shown by `slots --verbose`.
+    pub description: Option<String>,
+    /// Broad grouping such as `core` or `network`.
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    /// Locked slots refuse `unplug` and `swap`.
+    pub locked: bool,
+    /// Provider priorities; modules not listed rank 0.
+    pub priorities: Vec<(String, i32)>,
 }
 
 impl PuzzleSlot {
@@ -30,15 +237,240 @@ impl PuzzleSlot {
         Self {
             name: normalize_slot_name_or_self(name),
             required,
-            provider: None,
+            providers: Vec::new(),
+            cardinality: SlotCardinality::Single,
+            requires: Vec::new(),
+            description: None,
+            category: None,
+            tags: Vec::new(),
+            locked: false,
+            priorities: Vec::new(),
+        }
+    }
+
+    /// Lets the slot hold more than one provider.
+    pub fn with_cardinality(mut self, cardinality: SlotCardinality) -> Self {
+        self.cardinality = cardinality;
+        self
+    }
+
+    /// Returns the first provider, if any.
+    pub fn provider(&self) -> Option<&str> {
+        self.providers.first().map(String::as_str)
+    }
+
+    fn is_full(&self) -> bool {
+        self.cardinality == SlotCardinality::Single && !self.providers.is_empty()
+    }
+
+    /// Declares slots that must be filled before this one.
+    pub fn with_requires(mut self, slots: &[&str]) -> Self {
+        self.requires = slots
+            .iter()
+            .map(|slot| normalize_slot_name_or_self(slot))
+            .collect();
+        self
+    }
+
+    /// Sets the one-line description of the slot.
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the slot category.
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Ranks `module` among the providers that can fill the slot.
+    pub fn with_priority(mut self, module: &str, priority: i32) -> Self {
+        self.priorities.retain(|(name, _)| name != module);
+        self.priorities.push((module.to_string(), priority));
+        self
+    }
+
+    /// Returns the provider priority of `module`, 0 when unranked.
+    pub fn priority_of(&self, module: &str) -> i32 {
+        self.priorities
+            .iter()
+            .find(|(name, _)| name == module)
+            .map_or(0, |(_, priority)| *priority)
+    }
+
+    /// Sets free-form tags for the slot.
+    pub fn with_tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+}
+
+/// A module the auto-plug planner may use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanCandidate {
+    pub module: String,
+    pub slots: Vec<String>,
+    /// Higher wins when several modules fit a slot.
+    pub priority: i32,
+}
+
+/// One plug proposed by the planner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanStep {
+    pub slot: String,
+    pub module: String,
+}
+
+/// Modules that share the top provider priority for a slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriorityTie {
+    pub slot: String,
+    /// Tied modules; the first is the one the board kept or picked.
+    pub modules: Vec<String>,
+}
+
+/// Plugs that fill the board's empty required slots, in apply order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlugPlan {
+    pub steps: Vec<PlanStep>,
+    /// Slots no candidate can fill.
+    pub unfilled: Vec<String>,
+    /// Slots whose pick was a tie, settled by module name.
+    pub ties: Vec<PriorityTie>,
+}
+
+/// Number of board changes kept by [`PuzzleBoard::history`].
+pub const BOARD_HISTORY_LEN: usize = 64;
+
+/// What happened to a slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardEventKind {
+    Plugged,
+    Unplugged,
+    Swapped { old: String },
+    Failed(String),
+}
+
+/// One recorded board change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardEvent {
+    pub tick: u64,
+    pub slot: String,
+    pub module: String,
+    pub kind: BoardEventKind,
+}
+
+impl BoardEvent {
+    /// Formats the event as "plugged <slot> <- <module>" and similar.
+    pub fn format(&self) -> String {
+        let module = &self.module;
+        match &self.kind {
+            BoardEventKind::Plugged => format!("plugged {} <- {}", self.slot, module),
+            BoardEventKind::Unplugged => format!("unplugged {} (was {})", self.slot, module),
+            BoardEventKind::Swapped { old } => {
+                format!("swapped {} -> {} (was {})", self.slot, module, old)
+            }
+            BoardEventKind::Failed(reason) => {
+                format!("failed {} <- {}: {}", self.slot, module, reason)
+            }
+        }
+    }
+}
+
+/// An installed module as seen by [`PuzzleBoard::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleStatus {
+    pub name: String,
+    pub slots: Vec<String>,
+    pub running: bool,
+}
+
+/// A problem found by [`PuzzleBoard::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardIssue {
+    /// A required slot is empty; `candidates` could fill it.
+    Unfilled {
+        slot: String,
+        candidates: Vec<String>,
+    },
+    /// The provider does not declare the slot's family at all.
+    Incompatible { slot: String, module: String },
+    /// The provider declares the family, but no version the slot accepts.
+    VersionMismatch {
+        slot: String,
+        module: String,
+        provided: Vec<String>,
+    },
+    /// The provider is stopped, or no longer installed.
+    Dangling {
+        slot: String,
+        module: String,
+        installed: bool,
+    },
+}
+
+impl BoardIssue {
+    /// Formats the issue as one report line.
+    pub fn format(&self) -> String {
+        match self {
+            BoardIssue::Unfilled { slot, candidates } if candidates.is_empty() => {
+                format!("unfilled required slot: {} (no candidates)", slot)
+            }
+            BoardIssue::Unfilled { slot, candidates } => format!(
+                "unfilled required slot: {} (candidates: {})",
+                slot,
+                candidates.join(", ")
+            ),
+            BoardIssue::Incompatible { slot, module } => {
+                format!("incompatible provider: {} -> {}", slot, module)
+            }
+            BoardIssue::VersionMismatch {
+                slot,
+                module,
+                provided,
+            } => format!(
+                "version mismatch: {} -> {} provides {}",
+                slot,
+                module,
+                provided.join(", ")
+            ),
+            BoardIssue::Dangling {
+                slot,
+                module,
+                installed,
+            } => {
+                let state = if *installed {
+                    "stopped"
+                } else {
+                    "not installed"
+                };
+                format!("dangling provider: {} -> {} ({})", slot, module, state)
+            }
         }
     }
 }
 
+/// Result of [`PuzzleBoard::validate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardReport {
+    pub slots_checked: usize,
+    pub issues: Vec<BoardIssue>,
+}
+
+impl BoardReport {
+    /// Returns true when no issue was found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
 /// Tracks which modules fill which slots.
 #[derive(Debug, Clone, Default)]
 pub struct PuzzleBoard {
     slots: BTreeMap<String, PuzzleSlot>,
+    history: Vec<BoardEvent>,
+    now: u64,
 }
 
 impl PuzzleBoard {
@@ -50,7 +482,59 @@ impl PuzzleBoard {
             slot.name = normalized.clone();
             map.insert(normalized, slot);
         }
-        Self { slots: map }
+        Self {
+            slots: map,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the tick stamped on events recorded from now on.
+    pub fn set_time(&mut self, now: u64) {
+        self.now = now;
+    }
+
+    /// Serializes the plugged providers as `slot=module[,module...]` lines.
+    pub fn to_config_text(&self) -> String {
+        let mut out = String::new();
+        for slot in self.slots.values() {
+            if slot.providers.is_empty() {
+                continue;
+            }
+            out.push_str(&slot.name);
+            out.push('=');
+            out.push_str(&slot.providers.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Re-plugs providers saved by [`PuzzleBoard::to_config_text`].
+    ///
+    /// Listed slots take the saved providers in place of their current ones
+    /// without compatibility checks; slots missing from the board are skipped
+    /// and returned.
+    pub fn restore(&mut self, entries: &[(String, Vec<String>)]) -> Vec<String> {
+        let mut skipped = Vec::new();
+        for (slot, providers) in entries {
+            let Some(entry) = self.slots.get_mut(slot) else {
+                skipped.push(slot.clone());
+                continue;
+            };
+            let mut providers = providers.clone();
+            if entry.cardinality == SlotCardinality::Single {
+                providers.truncate(1);
+            }
+            entry.providers = providers.clone();
+            for module in &providers {
+                self.record(slot, module, BoardEventKind::Plugged);
+            }
+        }
+        skipped
+    }
+
+    /// Returns recorded board changes, oldest first.
+    pub fn history(&self) -> &[BoardEvent] {
+        &self.history
     }
 
     /// Returns the slot list sorted by name.
@@ -63,115 +547,674 @@ impl PuzzleBoard {
         self.slots
             .values()
             .filter(|slot| slot.required)
-            .all(|slot| slot.provider.is_some())
+            .all(|slot| !slot.providers.is_empty())
     }
 
-    /// Returns required slots that are still empty.
+    /// Returns required slots that are still empty and ready to be plugged.
     pub fn missing_required(&self) -> Vec<String> {
         self.slots
             .values()
-            .filter(|slot| slot.required && slot.provider.is_none())
+            .filter(|slot| slot.required && slot.providers.is_empty())
+            .filter(|slot| self.unmet_requires(slot).is_empty())
             .map(|slot| slot.name.clone())
             .collect()
     }
 
-    /// Returns the provider bound to a slot, if any.
-    pub fn provider_for(&self, slot: &str) -> Option<&str> {
-        let slot_key = normalize_slot_name(slot).ok()?;
+    /// Returns empty required slots with the prerequisite slots they wait on.
+    pub fn blocked_required(&self) -> Vec<(String, Vec<String>)> {
         self.slots
-            .get(&slot_key)
-            .and_then(|entry| entry.provider.as_deref())
+            .values()
+            .filter(|slot| slot.required && slot.providers.is_empty())
+            .filter_map(|slot| {
+                let unmet = self.unmet_requires(slot);
+                (!unmet.is_empty()).then(|| (slot.name.clone(), unmet))
+            })
+            .collect()
+    }
+
+    /// Plans plugs that fill every empty required slot from `candidates`.
+    ///
+    /// Empty prerequisites of those slots are planned too, and steps come in
+    /// an order that satisfies `requires`. Each slot takes the compatible
+    /// candidate ranked highest by the slot's provider priority, then by the
+    /// candidate's own priority; ties go to the first name and are reported.
+    pub fn plan(&self, candidates: &[PlanCandidate]) -> PlugPlan {
+        let is_empty = |board: &PuzzleBoard, key: &str| {
+            board
+                .slots
+                .get(key)
+                .is_some_and(|slot| slot.providers.is_empty())
+        };
+        let mut needed: Vec<String> = self
+            .slots
+            .values()
+            .filter(|slot| slot.required && slot.providers.is_empty())
+            .map(|slot| slot.name.clone())
+            .collect();
+        let mut index = 0;
+        while index < needed.len() {
+            let requires = self
+                .slots
+                .get(&needed[index])
+                .map(|slot| slot.requires.clone())
+                .unwrap_or_default();
+            for required in requires {
+                if let Ok(key) = self.slot_key(&required) {
+                    if is_empty(self, &key) && !needed.contains(&key) {
+                        needed.push(key);
+                    }
+                }
+            }
+            index += 1;
+        }
+
+        let mut board = self.clone();
+        let mut steps = Vec::new();
+        let mut ties = Vec::new();
+        loop {
+            let mut progressed = false;
+            for key in &needed {
+                if !is_empty(&board, key) {
+                    continue;
+                }
+                let Some(slot) = board.slots.get(key) else {
+                    continue;
+                };
+                let rank = |candidate: &PlanCandidate| {
+                    (slot.priority_of(&candidate.module), candidate.priority)
+                };
+                let fitting: Vec<&PlanCandidate> = candidates
+                    .iter()
+                    .filter(|candidate| board.can_plug(key, &candidate.slots).is_ok())
+                    .collect();
+                let Some(top) = fitting.iter().map(|candidate| rank(candidate)).max() else {
+                    continue;
+                };
+                let mut tied: Vec<&PlanCandidate> = fitting
+                    .into_iter()
+                    .filter(|candidate| rank(candidate) == top)
+                    .collect();
+                tied.sort_by(|a, b| a.module.cmp(&b.module));
+                tied.dedup_by(|a, b| a.module == b.module);
+                if tied.len() > 1 {
+                    ties.push(PriorityTie {
+                        slot: key.clone(),
+                        modules: tied
+                            .iter()
+                            .map(|candidate| candidate.module.clone())
+                            .collect(),
+                    });
+                }
+                let best = tied.first().copied();
+                if let Some(candidate) = best {
+                    if board.plug(key, &candidate.module, &candidate.slots).is_ok() {
+                        steps.push(PlanStep {
+                            slot: key.clone(),
+                            module: candidate.module.clone(),
+                        });
+                        progressed = true;
+                    }
+                }
+            }
+            if !progressed {
+                break;
+            }
+        }
+        let unfilled = needed
+            .into_iter()
+            .filter(|key| is_empty(&board, key))
+            .collect();
+        PlugPlan {
+            steps,
+            unfilled,
+            ties,
+        }
+    }
+
+    /// Checks every slot against the installed `modules` and the `catalog`
+    /// of available (name, provided slots) pairs.
+    ///
+    /// Providers are checked against their manifest slots, so entries
+    /// restored from a saved board that no longer fit are reported too.
+    pub fn validate(
+        &self,
+        catalog: &[(String, Vec<String>)],
+        modules: &[ModuleStatus],
+    ) -> BoardReport {
+        let mut issues = Vec::new();
+        for (key, slot) in &self.slots {
+            if slot.required && slot.providers.is_empty() {
+                let mut candidates: Vec<String> = modules
+                    .iter()
+                    .map(|module| (&module.name, &module.slots))
+                    .chain(catalog.iter().map(|(name, slots)| (name, slots)))
+                    .filter(|(_, slots)| provides_slot(key, slots))
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                candidates.sort();
+                candidates.dedup();
+                issues.push(BoardIssue::Unfilled {
+                    slot: key.clone(),
+                    candidates,
+                });
+            }
+            for provider in &slot.providers {
+                let installed = modules.iter().find(|module| module.name == *provider);
+                let declared = installed.map(|module| &module.slots).or_else(|| {
+                    catalog
+                        .iter()
+                        .find(|(name, _)| name == provider)
+                        .map(|(_, slots)| slots)
+                });
+                if let Some(declared) = declared {
+                    if !provides_slot(key, declared) {
+                        issues.push(version_issue(key, provider, declared));
+                    }
+                }
+                if !installed.is_some_and(|module| module.running) {
+                    issues.push(BoardIssue::Dangling {
+                        slot: key.clone(),
+                        module: provider.clone(),
+                        installed: installed.is_some(),
+                    });
+                }
+            }
+        }
+        BoardReport {
+            slots_checked: self.slots.len(),
+            issues,
+        }
+    }
+
+    /// Returns the board slot a slot name or module slot resolves to.
+    pub fn slot(&self, slot: &str) -> Option<&PuzzleSlot> {
+        let slot_key = self.slot_key(slot).ok()?;
+        self.slots.get(&slot_key)
+    }
+
+    /// Returns the provider bound to a slot, if any (the first one for a
+    /// multi-provider slot).
+    pub fn provider_for(&self, slot: &str) -> Option<&str> {
+        self.slot(slot).and_then(PuzzleSlot::provider)
     }
 
-    /// Plugs a module into a slot if it declares compatibility.
+    /// Returns every provider bound to a slot.
+    pub fn providers_for(&self, slot: &str) -> Vec<&str> {
+        self.slot(slot)
+            .map(|entry| entry.providers.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the slot exists and must be filled for a complete board.
+    pub fn is_required(&self, slot: &str) -> bool {
+        self.slot(slot).is_some_and(|entry| entry.required)
+    }
+
+    /// Plugs a module into a slot if it declares compatibility and the slot's
+    /// prerequisites are filled.
     pub fn plug(
         &mut self,
         slot: &str,
         module: &str,
         module_slots: &[String],
     ) -> Result<(), BoardError> {
-        let slot_key = normalize_slot_name(slot)?;
+        match self.try_plug(slot, module, module_slots) {
+            Ok(slot_key) => {
+                self.record(&slot_key, module, BoardEventKind::Plugged);
+                Ok(())
+            }
+            Err(err) => {
+                self.record_failure(slot, module, format!("{:?}", err));
+                Err(err)
+            }
+        }
+    }
+
+    /// Applies every staged operation, or none of them.
+    ///
+    /// Operations run in order against a scratch copy, so later steps see
+    /// earlier ones (unplug a slot, then plug its replacement). On failure
+    /// the board is left as it was, apart from the failed plug's event.
+    pub fn commit(&mut self, transaction: &BoardTransaction) -> Result<(), TransactionError> {
+        let mut scratch = self.clone();
+        for (index, op) in transaction.ops.iter().enumerate() {
+            let result = match op {
+                BoardOp::Plug {
+                    slot,
+                    module,
+                    module_slots,
+                } => scratch
+                    .try_plug(slot, module, module_slots)
+                    .map(|slot_key| {
+                        scratch.record(&slot_key, module, BoardEventKind::Plugged);
+                    }),
+                BoardOp::Unplug { slot } => scratch.unplug(slot).map(|_| ()),
+            };
+            if let Err(error) = result {
+                if let BoardOp::Plug { slot, module, .. } = op {
+                    self.record_failure(slot, module, format!("{:?}", error));
+                }
+                return Err(TransactionError { index, error });
+            }
+        }
+        *self = scratch;
+        Ok(())
+    }
+
+    fn try_plug(
+        &mut self,
+        slot: &str,
+        module: &str,
+        module_slots: &[String],
+    ) -> Result<String, BoardError> {
+        self.can_plug(slot, module_slots)?;
+        let slot_key = self.slot_key(slot)?;
         let entry = self
             .slots
             .get_mut(&slot_key)
             .ok_or(BoardError::SlotNotFound)?;
-        if entry.provider.is_some() {
+        if entry.providers.iter().any(|provider| provider == module) {
             return Err(BoardError::SlotAlreadyFilled);
         }
-        if !module_slots
-            .iter()
-            .any(|item| normalize_slot_name(item).map(|slot| slot == slot_key).unwrap_or(false))
-        {
-            return Err(BoardError::SlotNotCompatible);
-        }
-        entry.provider = Some(module.to_string());
-        Ok(())
+        entry.providers.push(module.to_string());
+        Ok(slot_key)
     }
 
     /// Validates whether a module can be plugged into a slot (no mutation).
     pub fn can_plug(&self, slot: &str, module_slots: &[String]) -> Result<(), BoardError> {
-        let slot_key = normalize_slot_name(slot)?;
+        let slot_key = self.slot_key(slot)?;
         let entry = self
             .slots
             .get(&slot_key)
             .ok_or(BoardError::SlotNotFound)?;
-        if entry.provider.is_some() {
+        if entry.is_full() {
             return Err(BoardError::SlotAlreadyFilled);
         }
-        if !module_slots
-            .iter()
-            .any(|item| normalize_slot_name(item).map(|slot| slot == slot_key).unwrap_or(false))
-        {
+        if !provides_slot(&slot_key, module_slots) {
             return Err(BoardError::SlotNotCompatible);
         }
+        let unmet = self.unmet_requires(entry);
+        if !unmet.is_empty() {
+            return Err(BoardError::SlotBlocked(unmet));
+        }
         Ok(())
     }
 
+    /// Replaces a slot's provider in stages: drain the old module, start the
+    /// new one, re-point the slot, then stop the old module.
+    ///
+    /// The slot keeps its old provider if draining or starting fails.
+    pub fn swap<L: SwapLifecycle>(
+        &mut self,
+        slot: &str,
+        module: &str,
+        module_slots: &[String],
+        lifecycle: &mut L,
+    ) -> Result<SwapReport, SwapError<L::Error>> {
+        let result = self.try_swap(slot, module, module_slots, lifecycle);
+        match &result {
+            Ok(report) => {
+                let kind = BoardEventKind::Swapped {
+                    old: report.old.clone(),
+                };
+                self.record(&report.slot, module, kind);
+            }
+            Err(err) => {
+                let reason = match err {
+                    SwapError::Board(err) => format!("{:?}", err),
+                    SwapError::Drain(_) => "drain failed".to_string(),
+                    SwapError::Start(_) => "start failed".to_string(),
+                };
+                self.record_failure(slot, module, reason);
+            }
+        }
+        result
+    }
+
+    fn try_swap<L: SwapLifecycle>(
+        &mut self,
+        slot: &str,
+        module: &str,
+        module_slots: &[String],
+        lifecycle: &mut L,
+    ) -> Result<SwapReport, SwapError<L::Error>> {
+        let slot_key = self.slot_key(slot)?;
+        let entry = self.slots.get(&slot_key).ok_or(BoardError::SlotNotFound)?;
+        if entry.locked {
+            return Err(BoardError::SlotLocked.into());
+        }
+        if entry.providers.len() > 1 {
+            return Err(BoardError::MultipleProviders.into());
+        }
+        let old = entry.provider().ok_or(BoardError::SlotEmpty)?.to_string();
+        if old == module {
+            return Err(BoardError::SlotAlreadyFilled.into());
+        }
+        if !provides_slot(&slot_key, module_slots) {
+            return Err(BoardError::SlotNotCompatible.into());
+        }
+        let unmet = self.unmet_requires(entry);
+        if !unmet.is_empty() {
+            return Err(BoardError::SlotBlocked(unmet).into());
+        }
+
+        lifecycle.drain(&old).map_err(SwapError::Drain)?;
+        if let Err(err) = lifecycle.start(module) {
+            lifecycle.resume(&old);
+            return Err(SwapError::Start(err));
+        }
+        if let Some(entry) = self.slots.get_mut(&slot_key) {
+            entry.providers = vec![module.to_string()];
+        }
+        let old_stopped = lifecycle.stop(&old).is_ok();
+        Ok(SwapReport {
+            slot: slot_key,
+            old,
+            new: module.to_string(),
+            old_stopped,
+        })
+    }
+
     /// Removes the module from a slot.
+    ///
+    /// A multi-provider slot holding several modules must be emptied one
+    /// module at a time with [`PuzzleBoard::unplug_module`].
     pub fn unplug(&mut self, slot: &str) -> Result<Option<String>, BoardError> {
-        let slot_key = normalize_slot_name(slot)?;
+        let slot_key = self.slot_key(slot)?;
+        let entry = self
+            .slots
+            .get_mut(&slot_key)
+            .ok_or(BoardError::SlotNotFound)?;
+        if entry.locked {
+            return Err(BoardError::SlotLocked);
+        }
+        if entry.providers.len() > 1 {
+            return Err(BoardError::MultipleProviders);
+        }
+        let removed = entry.providers.pop();
+        if let Some(module) = &removed {
+            self.record(&slot_key, module, BoardEventKind::Unplugged);
+        }
+        Ok(removed)
+    }
+
+    /// Removes one module from a slot, returning false if it was not plugged.
+    pub fn unplug_module(&mut self, slot: &str, module: &str) -> Result<bool, BoardError> {
+        let slot_key = self.slot_key(slot)?;
+        let entry = self
+            .slots
+            .get_mut(&slot_key)
+            .ok_or(BoardError::SlotNotFound)?;
+        let before = entry.providers.len();
+        entry.providers.retain(|provider| provider != module);
+        let removed = entry.providers.len() != before;
+        if removed {
+            self.record(&slot_key, module, BoardEventKind::Unplugged);
+        }
+        Ok(removed)
+    }
+
+    /// Locks or unlocks a slot, returning whether it was locked before.
+    pub fn set_locked(&mut self, slot: &str, locked: bool) -> Result<bool, BoardError> {
+        let slot_key = self.slot_key(slot)?;
         let entry = self
             .slots
             .get_mut(&slot_key)
             .ok_or(BoardError::SlotNotFound)?;
-        Ok(entry.provider.take())
+        Ok(core::mem::replace(&mut entry.locked, locked))
     }
 
     /// Seeds the board with an already running module.
-    pub fn mark_running(&mut self, module: &str, module_slots: &[String]) {
+    ///
+    /// Slot prerequisites are not checked: the module is already up. A full
+    /// single-provider slot switches to the module when the slot ranks it
+    /// above the current provider; equal ranks keep the current provider and
+    /// are returned as ties.
+    pub fn mark_running(&mut self, module: &str, module_slots: &[String]) -> Vec<PriorityTie> {
+        let mut ties = Vec::new();
         for slot in module_slots {
-            let Ok(normalized) = normalize_slot_name(slot) else {
+            let Ok(slot_key) = self.slot_key(slot) else {
                 continue;
             };
-            if let Some(entry) = self.slots.get_mut(&normalized) {
-                if entry.provider.is_none() {
-                    entry.provider = Some(module.to_string());
-                }
+            let Some(entry) = self.slots.get_mut(&slot_key) else {
+                continue;
+            };
+            if entry.providers.iter().any(|provider| provider == module) {
+                continue;
+            }
+            if !entry.is_full() {
+                entry.providers.push(module.to_string());
+                self.record(&slot_key, module, BoardEventKind::Plugged);
+                continue;
+            }
+            if entry.locked {
+                continue;
+            }
+            let current = entry.providers[0].clone();
+            let rank = entry.priority_of(module);
+            let current_rank = entry.priority_of(&current);
+            if rank > current_rank {
+                entry.providers = vec![module.to_string()];
+                self.record(&slot_key, module, BoardEventKind::Swapped { old: current });
+            } else if rank == current_rank {
+                ties.push(PriorityTie {
+                    slot: slot_key,
+                    modules: vec![current, module.to_string()],
+                });
             }
         }
+        ties
     }
-}
 
-fn normalize_slot_name(slot: &str) -> Result<String, BoardError> {
-    let trimmed = slot.trim();
-    if trimmed.is_empty() {
-        return Err(BoardError::InvalidSlot);
-    }
-    if let Some((base, version)) = trimmed.rsplit_once('@') {
-        if base.is_empty()
-            || version.is_empty()
-            || !version.chars().all(|ch| ch.is_ascii_digit())
-        {
-            return Err(BoardError::InvalidSlot);
+    fn record(&mut self, slot: &str, module: &str, kind: BoardEventKind) {
+        if self.history.len() >= BOARD_HISTORY_LEN {
+            self.history.remove(0);
         }
-        return Ok(trimmed.to_string());
+        self.history.push(BoardEvent {
+            tick: self.now,
+            slot: slot.to_string(),
+            module: module.to_string(),
+            kind,
+        });
     }
-    Ok(format!("{}@1", trimmed))
-}
 
-fn normalize_slot_name_or_self(slot: &str) -> String {
-    normalize_slot_name(slot).unwrap_or_else(|_| slot.to_string())
-}
+    fn record_failure(&mut self, slot: &str, module: &str, reason: String) {
+        let slot = self
+            .slot_key(slot)
+            .unwrap_or_else(|_| slot.trim().to_string());
+        self.record(&slot, module, BoardEventKind::Failed(reason));
+    }
+
+    fn unmet_requires(&self, slot: &PuzzleSlot) -> Vec<String> {
+        slot.requires
+            .iter()
+            .filter(|required| self.provider_for(required).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Resolves a slot name to a board key.
+    ///
+    /// An exact name wins; otherwise a versioned name picks the slot of the
+    /// same family whose requirement overlaps it, and an unversioned name
+    /// picks the first slot of that family.
+    fn slot_key(&self, slot: &str) -> Result<String, BoardError> {
+        let normalized = normalize_slot_name(slot)?;
+        if self.slots.contains_key(&normalized) {
+            return Ok(normalized);
+        }
+        let (base, provided) =
+            parse_slot(&normalized, VersionRange::provided).ok_or(BoardError::InvalidSlot)?;
+        let versioned = slot.contains('@');
+        self.slots
+            .keys()
+            .find(|key| {
+                parse_slot(key, VersionRange::requirement).is_some_and(|(family, required)| {
+                    family == base && (!versioned || required.overlaps(&provided))
+                })
+            })
+            .cloned()
+            .ok_or(BoardError::SlotNotFound)
+    }
+}
+
+/// Parses a saved board config into `(slot, providers)` entries.
+///
+/// Blank lines and `#` comments are ignored; slot names are normalized.
+pub fn parse_board_config(text: &str) -> Result<Vec<(String, Vec<String>)>, BoardError> {
+    let mut entries = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = BoardError::InvalidConfig(index + 1);
+        let (slot, providers) = line.split_once('=').ok_or(invalid.clone())?;
+        let slot = normalize_slot_name(slot).map_err(|_| invalid.clone())?;
+        let providers: Vec<String> = providers
+            .split(',')
+            .map(|module| module.trim().to_string())
+            .collect();
+        if providers.iter().any(String::is_empty) {
+            return Err(invalid);
+        }
+        entries.push((slot, providers));
+    }
+    Ok(entries)
+}
+
+fn provides_slot(slot_key: &str, module_slots: &[String]) -> bool {
+    let Some((base, required)) = parse_slot(slot_key, VersionRange::requirement) else {
+        return false;
+    };
+    module_slots.iter().any(|item| {
+        normalize_slot_name(item)
+            .ok()
+            .and_then(|item| {
+                parse_slot(&item, VersionRange::provided)
+                    .map(|(family, provided)| family == base && required.overlaps(&provided))
+            })
+            .unwrap_or(false)
+    })
+}
+
+fn version_issue(slot_key: &str, module: &str, module_slots: &[String]) -> BoardIssue {
+    let family = slot_key.rsplit_once('@').map_or(slot_key, |(base, _)| base);
+    let provided: Vec<String> = module_slots
+        .iter()
+        .filter_map(|item| normalize_slot_name(item).ok())
+        .filter(|item| {
+            item.rsplit_once('@')
+                .is_some_and(|(base, _)| base == family)
+        })
+        .collect();
+    if provided.is_empty() {
+        BoardIssue::Incompatible {
+            slot: slot_key.to_string(),
+            module: module.to_string(),
+        }
+    } else {
+        BoardIssue::VersionMismatch {
+            slot: slot_key.to_string(),
+            module: module.to_string(),
+            provided,
+        }
+    }
+}
+
+fn parse_slot(slot: &str, parse: fn(&str) -> Option<VersionRange>) -> Option<(&str, VersionRange)> {
+    let (base, version) = slot.rsplit_once('@')?;
+    Some((base, parse(version)?))
+}
+
+fn normalize_slot_name(slot: &str) -> Result<String, BoardError> {
+    let trimmed = slot.trim();
+    if trimmed.is_empty() {
+        return Err(BoardError::InvalidSlot);
+    }
+    if let Some((base, version)) = trimmed.rsplit_once('@') {
+        if base.is_empty() || VersionRange::requirement(version).is_none() {
+            return Err(BoardError::InvalidSlot);
+        }
+        let version = version
+            .split(',')
+            .map(|part| part.split_whitespace().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Ok(format!("{}@{}", base, version));
+    }
+    Ok(format!("{}@1", trimmed))
+}
+
+fn parse_partial(text: &str) -> Option<(u32, Option<u32>)> {
+    let (major, minor) = match text.split_once('.') {
+        Some((major, minor)) => (major, Some(minor)),
+        None => (text, None),
+    };
+    let number = |part: &str| {
+        if part.is_empty() || !part.chars().all(|ch| ch.is_ascii_digit()) {
+            return None;
+        }
+        part.parse::<u32>().ok()
+    };
+    let minor = match minor {
+        Some(minor) => Some(number(minor)?),
+        None => None,
+    };
+    Some((number(major)?, minor))
+}
+
+fn parse_comparators(text: &str) -> Option<VersionRange> {
+    let mut min = SlotVersion { major: 0, minor: 0 };
+    let mut end: Option<SlotVersion> = None;
+    for part in text.split(',') {
+        let part: String = part.split_whitespace().collect();
+        let (op, version) = ["<=", ">=", "<", ">", "="]
+            .iter()
+            .find_map(|op| part.strip_prefix(op).map(|rest| (*op, rest)))?;
+        let (major, minor) = parse_partial(version)?;
+        let lower = SlotVersion {
+            major,
+            minor: minor.unwrap_or(0),
+        };
+        let upper = match minor {
+            Some(_) => next_minor(lower),
+            None => next_major(major),
+        };
+        let (low, high) = match op {
+            ">=" => (Some(lower), None),
+            ">" => (Some(upper), None),
+            "<" => (None, Some(lower)),
+            "<=" => (None, Some(upper)),
+            _ => (Some(lower), Some(upper)),
+        };
+        if let Some(low) = low {
+            min = min.max(low);
+        }
+        if let Some(high) = high {
+            end = Some(end.map_or(high, |end| end.min(high)));
+        }
+    }
+    VersionRange::bounded(min, end)
+}
+
+fn next_minor(version: SlotVersion) -> SlotVersion {
+    match version.minor.checked_add(1) {
+        Some(minor) => SlotVersion { minor, ..version },
+        None => next_major(version.major),
+    }
+}
+
+fn next_major(major: u32) -> SlotVersion {
+    SlotVersion {
+        major: major.saturating_add(1),
+        minor: 0,
+    }
+}
+
+fn normalize_slot_name_or_self(slot: &str) -> String {
+    normalize_slot_name(slot).unwrap_or_else(|_| slot.to_string())
+}
 
 #[cfg(test)]
 mod tests {
@@ -281,6 +1324,16 @@ mod tests {
         assert_eq!(board.provider_for("ruzzle.slot.net"), Some("net-service"));
     }
 
+    #[test]
+    fn is_required_checks_slot_flags() {
+        let board = board();
+        assert!(board.is_required("ruzzle.slot.console"));
+        assert!(board.is_required("ruzzle.slot.shell@1"));
+        assert!(!board.is_required("ruzzle.slot.net@1"));
+        assert!(!board.is_required("ruzzle.slot.missing"));
+        assert!(!board.is_required("bad@"));
+    }
+
     #[test]
     fn unplug_rejects_missing_slot() {
         let mut board = board();
@@ -295,7 +1348,7 @@ mod tests {
             "console-service",
             &["ruzzle.slot.unknown@1".to_string()],
         );
-        assert!(board.list().iter().all(|slot| slot.provider.is_none()));
+        assert!(board.list().iter().all(|slot| slot.providers.is_empty()));
     }
 
     #[test]
@@ -317,7 +1370,7 @@ mod tests {
             .into_iter()
             .find(|slot| slot.name == "ruzzle.slot.console@1")
             .expect("slot should exist");
-        assert_eq!(slot.provider, Some("console-service".to_string()));
+        assert_eq!(slot.provider(), Some("console-service"));
     }
 
     #[test]
@@ -332,7 +1385,732 @@ mod tests {
             .iter()
             .find(|slot| slot.name == "ruzzle.slot.console@1")
             .unwrap();
-        assert_eq!(console.provider.as_deref(), Some("console-service"));
+        assert_eq!(console.provider(), Some("console-service"));
+    }
+
+    fn layered_board() -> PuzzleBoard {
+        PuzzleBoard::new(vec![
+            PuzzleSlot::new("ruzzle.slot.console", true),
+            PuzzleSlot::new("ruzzle.slot.shell", true).with_requires(&["ruzzle.slot.console"]),
+            PuzzleSlot::new("ruzzle.slot.editor", false).with_requires(&["ruzzle.slot.shell@1"]),
+        ])
+    }
+
+    #[test]
+    fn plug_waits_for_required_slots() {
+        let mut board = layered_board();
+        let shell = vec!["ruzzle.slot.shell@1".to_string()];
+        assert_eq!(
+            board.can_plug("ruzzle.slot.shell", &shell),
+            Err(BoardError::SlotBlocked(vec![
+                "ruzzle.slot.console@1".to_string()
+            ]))
+        );
+        assert_eq!(
+            board.plug("ruzzle.slot.shell", "tui-shell", &shell),
+            Err(BoardError::SlotBlocked(vec![
+                "ruzzle.slot.console@1".to_string()
+            ]))
+        );
+        assert_eq!(board.provider_for("ruzzle.slot.shell"), None);
+        board
+            .plug(
+                "ruzzle.slot.console",
+                "console-service",
+                &["ruzzle.slot.console@1".to_string()],
+            )
+            .unwrap();
+        board
+            .plug("ruzzle.slot.shell", "tui-shell", &shell)
+            .unwrap();
+        assert!(board.is_complete());
+    }
+
+    #[test]
+    fn missing_required_reports_blocked_slots_separately() {
+        let mut board = layered_board();
+        assert_eq!(
+            board.missing_required(),
+            vec!["ruzzle.slot.console@1".to_string()]
+        );
+        assert_eq!(
+            board.blocked_required(),
+            vec![(
+                "ruzzle.slot.shell@1".to_string(),
+                vec!["ruzzle.slot.console@1".to_string()]
+            )]
+        );
+        board.mark_running("tui-shell", &["ruzzle.slot.shell@1".to_string()]);
+        assert_eq!(board.provider_for("ruzzle.slot.shell"), Some("tui-shell"));
+        assert!(board.blocked_required().is_empty());
+        assert_eq!(
+            board.missing_required(),
+            vec!["ruzzle.slot.console@1".to_string()]
+        );
+    }
+
+    #[test]
+    fn multi_provider_slot_holds_several_modules() {
+        let mut board = PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.fs-backend", true)
+            .with_cardinality(SlotCardinality::Multiple)]);
+        let provides = vec!["ruzzle.slot.fs-backend@1".to_string()];
+        board
+            .plug("ruzzle.slot.fs-backend", "memfs", &provides)
+            .unwrap();
+        assert_eq!(board.can_plug("ruzzle.slot.fs-backend", &provides), Ok(()));
+        board
+            .plug("ruzzle.slot.fs-backend", "blockfs", &provides)
+            .unwrap();
+        assert_eq!(
+            board.plug("ruzzle.slot.fs-backend", "memfs", &provides),
+            Err(BoardError::SlotAlreadyFilled)
+        );
+        board.mark_running("tmpfs", &provides);
+        assert_eq!(
+            board.providers_for("ruzzle.slot.fs-backend"),
+            vec!["memfs", "blockfs", "tmpfs"]
+        );
+        assert_eq!(board.provider_for("ruzzle.slot.fs-backend"), Some("memfs"));
+        assert!(board.is_complete());
+        assert_eq!(
+            board.unplug("ruzzle.slot.fs-backend"),
+            Err(BoardError::MultipleProviders)
+        );
+        assert_eq!(
+            board.unplug_module("ruzzle.slot.fs-backend", "memfs"),
+            Ok(true)
+        );
+        assert_eq!(
+            board.unplug_module("ruzzle.slot.fs-backend", "memfs"),
+            Ok(false)
+        );
+        assert_eq!(
+            board.unplug_module("ruzzle.slot.fs-backend", "tmpfs"),
+            Ok(true)
+        );
+        assert_eq!(
+            board.unplug("ruzzle.slot.fs-backend"),
+            Ok(Some("blockfs".to_string()))
+        );
+        assert!(board.providers_for("ruzzle.slot.fs-backend").is_empty());
+    }
+
+    #[test]
+    fn unplug_module_leaves_other_single_providers() {
+        let mut board = board();
+        board.mark_running("console-service", &["ruzzle.slot.console@1".to_string()]);
+        assert_eq!(
+            board.unplug_module("ruzzle.slot.console", "alt-console"),
+            Ok(false)
+        );
+        assert_eq!(
+            board.provider_for("ruzzle.slot.console"),
+            Some("console-service")
+        );
+        assert_eq!(
+            board.providers_for("ruzzle.slot.missing"),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            board.unplug_module("ruzzle.slot.missing", "console-service"),
+            Err(BoardError::SlotNotFound)
+        );
+    }
+
+    #[derive(Default)]
+    struct FakeLifecycle {
+        calls: Vec<String>,
+        fail: Option<&'static str>,
+    }
+
+    impl SwapLifecycle for FakeLifecycle {
+        type Error = &'static str;
+
+        fn drain(&mut self, module: &str) -> Result<(), Self::Error> {
+            self.step("drain", module)
+        }
+
+        fn start(&mut self, module: &str) -> Result<(), Self::Error> {
+            self.step("start", module)
+        }
+
+        fn stop(&mut self, module: &str) -> Result<(), Self::Error> {
+            self.step("stop", module)
+        }
+
+        fn resume(&mut self, module: &str) {
+            self.calls.push(format!("resume {module}"));
+        }
+    }
+
+    impl FakeLifecycle {
+        fn step(&mut self, stage: &'static str, module: &str) -> Result<(), &'static str> {
+            self.calls.push(format!("{stage} {module}"));
+            if self.fail == Some(stage) {
+                return Err(stage);
+            }
+            Ok(())
+        }
+    }
+
+    fn editor_board() -> PuzzleBoard {
+        let mut board = PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.editor", false)]);
+        board.mark_running("text-editor", &["ruzzle.slot.editor@1".to_string()]);
+        board
+    }
+
+    #[test]
+    fn swap_drains_starts_repoints_then_stops() {
+        let mut board = editor_board();
+        let mut lifecycle = FakeLifecycle::default();
+        let report = board
+            .swap(
+                "ruzzle.slot.editor",
+                "vim-piece",
+                &["ruzzle.slot.editor@1".to_string()],
+                &mut lifecycle,
+            )
+            .unwrap();
+        assert_eq!(
+            report,
+            SwapReport {
+                slot: "ruzzle.slot.editor@1".to_string(),
+                old: "text-editor".to_string(),
+                new: "vim-piece".to_string(),
+                old_stopped: true,
+            }
+        );
+        assert_eq!(
+            lifecycle.calls,
+            vec!["drain text-editor", "start vim-piece", "stop text-editor"]
+        );
+        assert_eq!(board.provider_for("ruzzle.slot.editor"), Some("vim-piece"));
+    }
+
+    #[test]
+    fn swap_keeps_old_provider_when_replacement_fails() {
+        let mut board = editor_board();
+        let provides = vec!["ruzzle.slot.editor@1".to_string()];
+        let mut lifecycle = FakeLifecycle {
+            fail: Some("start"),
+            ..FakeLifecycle::default()
+        };
+        let result = board.swap("ruzzle.slot.editor", "vim-piece", &provides, &mut lifecycle);
+        assert_eq!(result, Err(SwapError::Start("start")));
+        assert_eq!(
+            lifecycle.calls,
+            vec!["drain text-editor", "start vim-piece", "resume text-editor"]
+        );
+        assert_eq!(
+            board.provider_for("ruzzle.slot.editor"),
+            Some("text-editor")
+        );
+
+        let mut lifecycle = FakeLifecycle {
+            fail: Some("drain"),
+            ..FakeLifecycle::default()
+        };
+        let result = board.swap("ruzzle.slot.editor", "vim-piece", &provides, &mut lifecycle);
+        assert_eq!(result, Err(SwapError::Drain("drain")));
+        assert_eq!(lifecycle.calls, vec!["drain text-editor"]);
+
+        let mut lifecycle = FakeLifecycle {
+            fail: Some("stop"),
+            ..FakeLifecycle::default()
+        };
+        let report = board
+            .swap("ruzzle.slot.editor", "vim-piece", &provides, &mut lifecycle)
+            .unwrap();
+        assert!(!report.old_stopped);
+        assert_eq!(board.provider_for("ruzzle.slot.editor"), Some("vim-piece"));
+    }
+
+    #[test]
+    fn swap_validates_before_touching_modules() {
+        let mut board = editor_board();
+        let mut lifecycle = FakeLifecycle::default();
+        let provides = vec!["ruzzle.slot.editor@1".to_string()];
+        assert_eq!(
+            board.swap(
+                "ruzzle.slot.editor",
+                "text-editor",
+                &provides,
+                &mut lifecycle
+            ),
+            Err(SwapError::Board(BoardError::SlotAlreadyFilled))
+        );
+        assert_eq!(
+            board.swap("ruzzle.slot.editor", "vim-piece", &[], &mut lifecycle),
+            Err(SwapError::Board(BoardError::SlotNotCompatible))
+        );
+        board.unplug("ruzzle.slot.editor").unwrap();
+        assert_eq!(
+            board.swap("ruzzle.slot.editor", "vim-piece", &provides, &mut lifecycle),
+            Err(SwapError::Board(BoardError::SlotEmpty))
+        );
+        assert!(lifecycle.calls.is_empty());
+    }
+
+    #[test]
+    fn locked_slot_refuses_unplug_and_swap() {
+        let mut board = editor_board();
+        let mut lifecycle = FakeLifecycle::default();
+        let provides = vec!["ruzzle.slot.editor@1".to_string()];
+        assert_eq!(board.set_locked("ruzzle.slot.editor", true), Ok(false));
+        assert_eq!(
+            board.unplug("ruzzle.slot.editor"),
+            Err(BoardError::SlotLocked)
+        );
+        assert_eq!(
+            board.swap("ruzzle.slot.editor", "vim-piece", &provides, &mut lifecycle),
+            Err(SwapError::Board(BoardError::SlotLocked))
+        );
+        assert!(lifecycle.calls.is_empty());
+        assert_eq!(
+            board.provider_for("ruzzle.slot.editor"),
+            Some("text-editor")
+        );
+        assert!(board.slot("ruzzle.slot.editor").unwrap().locked);
+
+        assert_eq!(board.set_locked("ruzzle.slot.editor", false), Ok(true));
+        assert_eq!(
+            board.unplug("ruzzle.slot.editor"),
+            Ok(Some("text-editor".to_string()))
+        );
+        assert_eq!(
+            board.set_locked("ruzzle.slot.gpu", true),
+            Err(BoardError::SlotNotFound)
+        );
+    }
+
+    #[test]
+    fn transaction_swaps_console_and_shell_together() {
+        let mut board = layered_board();
+        board.mark_running("console-service", &["ruzzle.slot.console@1".to_string()]);
+        board.mark_running("tui-shell", &["ruzzle.slot.shell@1".to_string()]);
+        let events = board.history().len();
+        let mut transaction = BoardTransaction::new();
+        transaction
+            .unplug("ruzzle.slot.shell")
+            .unplug("ruzzle.slot.console")
+            .plug(
+                "ruzzle.slot.console",
+                "serial-console",
+                &["ruzzle.slot.console@1".to_string()],
+            )
+            .plug(
+                "ruzzle.slot.shell",
+                "zsh",
+                &["ruzzle.slot.shell@1".to_string()],
+            );
+        assert_eq!(transaction.ops().len(), 4);
+        assert_eq!(board.commit(&transaction), Ok(()));
+        assert_eq!(
+            board.provider_for("ruzzle.slot.console"),
+            Some("serial-console")
+        );
+        assert_eq!(board.provider_for("ruzzle.slot.shell"), Some("zsh"));
+        assert_eq!(board.history().len(), events + 4);
+    }
+
+    #[test]
+    fn transaction_rolls_back_every_step_on_error() {
+        let mut board = layered_board();
+        board.mark_running("console-service", &["ruzzle.slot.console@1".to_string()]);
+        board.mark_running("tui-shell", &["ruzzle.slot.shell@1".to_string()]);
+        let before = board.list();
+        let events = board.history().len();
+        let mut transaction = BoardTransaction::new();
+        transaction
+            .unplug("ruzzle.slot.shell")
+            .unplug("ruzzle.slot.console")
+            .plug(
+                "ruzzle.slot.shell",
+                "zsh",
+                &["ruzzle.slot.shell@1".to_string()],
+            );
+        assert_eq!(
+            board.commit(&transaction),
+            Err(TransactionError {
+                index: 2,
+                error: BoardError::SlotBlocked(vec!["ruzzle.slot.console@1".to_string()]),
+            })
+        );
+        assert_eq!(board.list(), before);
+        assert_eq!(board.history().len(), events + 1);
+        assert!(matches!(
+            board.history().last().unwrap().kind,
+            BoardEventKind::Failed(_)
+        ));
+
+        board.set_locked("ruzzle.slot.console", true).unwrap();
+        let mut transaction = BoardTransaction::new();
+        transaction
+            .unplug("ruzzle.slot.shell")
+            .unplug("ruzzle.slot.console");
+        assert_eq!(
+            board.commit(&transaction).map_err(|err| err.error),
+            Err(BoardError::SlotLocked)
+        );
+        assert_eq!(board.provider_for("ruzzle.slot.shell"), Some("tui-shell"));
+    }
+
+    #[test]
+    fn history_records_board_changes_with_ticks() {
+        let mut board = editor_board();
+        let provides = vec!["ruzzle.slot.editor@1".to_string()];
+        board.set_time(40);
+        board
+            .swap(
+                "ruzzle.slot.editor",
+                "vim-piece",
+                &provides,
+                &mut FakeLifecycle::default(),
+            )
+            .unwrap();
+        board.set_time(55);
+        board.unplug("ruzzle.slot.editor").unwrap();
+        assert_eq!(board.unplug("ruzzle.slot.editor"), Ok(None));
+        board.set_time(60);
+        assert!(board.plug("ruzzle.slot.editor", "note-piece", &[]).is_err());
+        board
+            .plug("ruzzle.slot.editor", "note-piece", &provides)
+            .unwrap();
+
+        let lines: Vec<(u64, String)> = board
+            .history()
+            .iter()
+            .map(|event| (event.tick, event.format()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (0, "plugged ruzzle.slot.editor@1 <- text-editor".to_string()),
+                (
+                    40,
+                    "swapped ruzzle.slot.editor@1 -> vim-piece (was text-editor)".to_string()
+                ),
+                (
+                    55,
+                    "unplugged ruzzle.slot.editor@1 (was vim-piece)".to_string()
+                ),
+                (
+                    60,
+                    "failed ruzzle.slot.editor@1 <- note-piece: SlotNotCompatible".to_string()
+                ),
+                (60, "plugged ruzzle.slot.editor@1 <- note-piece".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn history_keeps_the_latest_events() {
+        let mut board = board();
+        let provides = vec!["ruzzle.slot.net@1".to_string()];
+        for tick in 0..BOARD_HISTORY_LEN as u64 {
+            board.set_time(tick);
+            board
+                .plug("ruzzle.slot.net", "net-service", &provides)
+                .unwrap();
+            board.unplug("ruzzle.slot.net").unwrap();
+        }
+        let history = board.history();
+        assert_eq!(history.len(), BOARD_HISTORY_LEN);
+        assert_eq!(history[0].tick, BOARD_HISTORY_LEN as u64 / 2);
+        assert_eq!(history[0].kind, BoardEventKind::Plugged);
+        assert_eq!(history[0].module, "net-service");
+        let last = history.last().unwrap();
+        assert_eq!(last.kind, BoardEventKind::Unplugged);
+        assert_eq!(last.slot, "ruzzle.slot.net@1");
+    }
+
+    #[test]
+    fn board_config_round_trips_plugged_providers() {
+        let mut saved = board();
+        saved.mark_running("console-service", &["ruzzle.slot.console@1".to_string()]);
+        saved
+            .plug(
+                "ruzzle.slot.net",
+                "net-service",
+                &["ruzzle.slot.net@1".to_string()],
+            )
+            .unwrap();
+        let text = saved.to_config_text();
+        assert_eq!(
+            text,
+            "ruzzle.slot.console@1=console-service\nruzzle.slot.net@1=net-service\n"
+        );
+
+        let mut restored = board();
+        let entries = parse_board_config(&text).unwrap();
+        assert!(restored.restore(&entries).is_empty());
+        assert_eq!(restored.to_config_text(), text);
+        assert_eq!(
+            restored.provider_for("ruzzle.slot.net"),
+            Some("net-service")
+        );
+        assert_eq!(restored.history().len(), 2);
+    }
+
+    #[test]
+    fn board_config_restore_skips_unknown_slots() {
+        let text =
+            "# saved board\n\nruzzle.slot.fs-backend=memfs, blockfs\nruzzle.slot.shell@1=a,b\n";
+        let entries = parse_board_config(text).unwrap();
+        assert_eq!(
+            entries[0],
+            (
+                "ruzzle.slot.fs-backend@1".to_string(),
+                vec!["memfs".to_string(), "blockfs".to_string()]
+            )
+        );
+        let mut board = board();
+        assert_eq!(
+            board.restore(&entries),
+            vec!["ruzzle.slot.fs-backend@1".to_string()]
+        );
+        assert_eq!(board.providers_for("ruzzle.slot.shell"), vec!["a"]);
+    }
+
+    #[test]
+    fn board_config_rejects_malformed_lines() {
+        assert_eq!(
+            parse_board_config("ruzzle.slot.net@1=net\nbroken"),
+            Err(BoardError::InvalidConfig(2))
+        );
+        assert_eq!(
+            parse_board_config("bad@=net"),
+            Err(BoardError::InvalidConfig(1))
+        );
+        assert_eq!(
+            parse_board_config("ruzzle.slot.net=a,,b"),
+            Err(BoardError::InvalidConfig(1))
+        );
+    }
+
+    fn candidate(module: &str, slots: &[&str], priority: i32) -> PlanCandidate {
+        PlanCandidate {
+            module: module.to_string(),
+            slots: slots.iter().map(|slot| slot.to_string()).collect(),
+            priority,
+        }
+    }
+
+    #[test]
+    fn plan_fills_required_slots_in_dependency_order() {
+        let mut board = layered_board();
+        board
+            .slots
+            .get_mut("ruzzle.slot.console@1")
+            .unwrap()
+            .required = false;
+        let candidates = vec![
+            candidate("tui-shell", &["ruzzle.slot.shell@1.2"], 0),
+            candidate("console-service", &["ruzzle.slot.console@1"], 0),
+            candidate("editor", &["ruzzle.slot.editor@1"], 9),
+        ];
+        let plan = board.plan(&candidates);
+        assert_eq!(
+            plan.steps,
+            vec![
+                PlanStep {
+                    slot: "ruzzle.slot.console@1".to_string(),
+                    module: "console-service".to_string(),
+                },
+                PlanStep {
+                    slot: "ruzzle.slot.shell@1".to_string(),
+                    module: "tui-shell".to_string(),
+                },
+            ]
+        );
+        assert!(plan.unfilled.is_empty());
+        assert!(board.provider_for("ruzzle.slot.console").is_none());
+        assert!(board.history().is_empty());
+    }
+
+    #[test]
+    fn plan_prefers_priority_then_name_and_reports_gaps() {
+        let board = board();
+        let candidates = vec![
+            candidate("zconsole", &["ruzzle.slot.console@1"], 0),
+            candidate("aconsole", &["ruzzle.slot.console@1"], 0),
+            candidate("next-shell", &["ruzzle.slot.shell@2"], 5),
+        ];
+        let plan = board.plan(&candidates);
+        assert_eq!(
+            plan.steps,
+            vec![PlanStep {
+                slot: "ruzzle.slot.console@1".to_string(),
+                module: "aconsole".to_string(),
+            }]
+        );
+        assert_eq!(plan.unfilled, vec!["ruzzle.slot.shell@1".to_string()]);
+        assert_eq!(
+            plan.ties,
+            vec![PriorityTie {
+                slot: "ruzzle.slot.console@1".to_string(),
+                modules: vec!["aconsole".to_string(), "zconsole".to_string()],
+            }]
+        );
+
+        let mut candidates = candidates;
+        candidates.push(candidate("console-plus", &["ruzzle.slot.console@1.4"], 1));
+        assert_eq!(board.plan(&candidates).steps[0].module, "console-plus");
+    }
+
+    #[test]
+    fn slot_metadata_survives_plug_and_list() {
+        let mut board = PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.fs@1", true)
+            .with_description("root filesystem service")
+            .with_category("core")
+            .with_tags(&["storage", "vfs"])]);
+        board
+            .plug(
+                "ruzzle.slot.fs@1",
+                "fs-service",
+                &["ruzzle.slot.fs@1".to_string()],
+            )
+            .unwrap();
+        let slot = &board.list()[0];
+        assert_eq!(slot.description.as_deref(), Some("root filesystem service"));
+        assert_eq!(slot.category.as_deref(), Some("core"));
+        assert_eq!(slot.tags, vec!["storage".to_string(), "vfs".to_string()]);
+        let plain = PuzzleSlot::new("ruzzle.slot.net@1", false);
+        assert_eq!(plain.description, None);
+        assert!(plain.tags.is_empty());
+    }
+
+    fn status(name: &str, slots: &[&str], running: bool) -> ModuleStatus {
+        ModuleStatus {
+            name: name.to_string(),
+            slots: slots.iter().map(|slot| slot.to_string()).collect(),
+            running,
+        }
+    }
+
+    #[test]
+    fn validate_reports_every_kind_of_issue() {
+        let mut board = PuzzleBoard::new(vec![
+            PuzzleSlot::new("ruzzle.slot.console@1", true),
+            PuzzleSlot::new("ruzzle.slot.shell@1", true),
+            PuzzleSlot::new("ruzzle.slot.fs@1.2", true),
+            PuzzleSlot::new("ruzzle.slot.net@1", false),
+            PuzzleSlot::new("ruzzle.slot.editor@1", false),
+        ]);
+        board.restore(&[
+            (
+                "ruzzle.slot.console@1".to_string(),
+                vec!["console-service".to_string()],
+            ),
+            ("ruzzle.slot.fs@1.2".to_string(), vec!["old-fs".to_string()]),
+            (
+                "ruzzle.slot.net@1".to_string(),
+                vec!["net-service".to_string()],
+            ),
+            (
+                "ruzzle.slot.editor@1".to_string(),
+                vec!["vim-piece".to_string()],
+            ),
+        ]);
+        let modules = vec![
+            status("console-service", &["ruzzle.slot.console@1"], true),
+            status("old-fs", &["ruzzle.slot.fs@1.1"], true),
+            status("net-service", &["ruzzle.slot.gpu@1"], false),
+        ];
+        let catalog = vec![
+            ("zsh".to_string(), vec!["ruzzle.slot.shell@1.3".to_string()]),
+            (
+                "tui-shell".to_string(),
+                vec!["ruzzle.slot.shell@1".to_string()],
+            ),
+        ];
+        let report = board.validate(&catalog, &modules);
+        assert_eq!(report.slots_checked, 5);
+        let lines: Vec<String> = report.issues.iter().map(BoardIssue::format).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "dangling provider: ruzzle.slot.editor@1 -> vim-piece (not installed)",
+                "version mismatch: ruzzle.slot.fs@1.2 -> old-fs provides ruzzle.slot.fs@1.1",
+                "incompatible provider: ruzzle.slot.net@1 -> net-service",
+                "dangling provider: ruzzle.slot.net@1 -> net-service (stopped)",
+                "unfilled required slot: ruzzle.slot.shell@1 (candidates: tui-shell, zsh)",
+            ]
+        );
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn validate_passes_a_healthy_board() {
+        let mut board = board();
+        let console = status("console-service", &["ruzzle.slot.console@1"], true);
+        let shell = status("tui-shell", &["ruzzle.slot.shell@1"], true);
+        board.mark_running(&console.name, &console.slots);
+        board.mark_running(&shell.name, &shell.slots);
+        let report = board.validate(&[], &[console, shell]);
+        assert!(report.is_clean());
+        assert_eq!(
+            BoardIssue::Unfilled {
+                slot: "ruzzle.slot.gpu@1".to_string(),
+                candidates: Vec::new(),
+            }
+            .format(),
+            "unfilled required slot: ruzzle.slot.gpu@1 (no candidates)"
+        );
+    }
+
+    #[test]
+    fn slot_priority_outranks_candidate_priority_in_plans() {
+        let board = PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.console@1", true)
+            .with_priority("serial-console", 5)
+            .with_priority("serial-console", 10)]);
+        let candidates = vec![
+            candidate("console-service", &["ruzzle.slot.console@1"], 2),
+            candidate("serial-console", &["ruzzle.slot.console@1"], 0),
+        ];
+        let plan = board.plan(&candidates);
+        assert_eq!(plan.steps[0].module, "serial-console");
+        assert!(plan.ties.is_empty());
+        let slot = board.slot("ruzzle.slot.console").unwrap();
+        assert_eq!(slot.priority_of("serial-console"), 10);
+        assert_eq!(slot.priority_of("console-service"), 0);
+    }
+
+    #[test]
+    fn mark_running_prefers_higher_slot_priority_and_reports_ties() {
+        let console = vec!["ruzzle.slot.console@1".to_string()];
+        let mut ranked = PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.console@1", true)
+            .with_priority("console-service", 1)
+            .with_priority("serial-console", 5)]);
+        assert!(ranked.mark_running("console-service", &console).is_empty());
+        assert!(ranked.mark_running("console-service", &console).is_empty());
+        assert!(ranked.mark_running("alt-console", &console).is_empty());
+        assert!(ranked.mark_running("serial-console", &console).is_empty());
+        assert_eq!(
+            ranked.provider_for("ruzzle.slot.console"),
+            Some("serial-console")
+        );
+        assert_eq!(
+            ranked.history().last().unwrap().format(),
+            "swapped ruzzle.slot.console@1 -> serial-console (was console-service)"
+        );
+        assert!(ranked.mark_running("console-service", &console).is_empty());
+
+        let mut board = board();
+        board.mark_running("console-service", &console);
+        assert_eq!(
+            board.mark_running("alt-console", &console),
+            vec![PriorityTie {
+                slot: "ruzzle.slot.console@1".to_string(),
+                modules: vec!["console-service".to_string(), "alt-console".to_string()],
+            }]
+        );
+        assert_eq!(
+            board.provider_for("ruzzle.slot.console"),
+            Some("console-service")
+        );
+        board.set_locked("ruzzle.slot.console", true).unwrap();
+        assert!(board.mark_running("alt-console", &console).is_empty());
     }
 
     #[test]
@@ -353,6 +2131,84 @@ mod tests {
         assert_eq!(slot, Err(BoardError::InvalidSlot));
     }
 
+    #[test]
+    fn normalize_slot_accepts_minor_versions_and_ranges() {
+        assert_eq!(
+            normalize_slot_name("ruzzle.slot.net@1.3").unwrap(),
+            "ruzzle.slot.net@1.3"
+        );
+        assert_eq!(
+            normalize_slot_name(" ruzzle.slot.net@>= 1.2,<2 ").unwrap(),
+            "ruzzle.slot.net@>=1.2, <2"
+        );
+        for bad in ["net@1.", "net@1.2.3", "net@>=2, <1", "net@~1", "net@1, <2"] {
+            assert_eq!(normalize_slot_name(bad), Err(BoardError::InvalidSlot));
+        }
+    }
+
+    #[test]
+    fn version_ranges_follow_requirement_and_provider_rules() {
+        let v = |major, minor| SlotVersion { major, minor };
+        let caret = VersionRange::requirement("1.2").unwrap();
+        assert!(caret.contains(v(1, 2)) && caret.contains(v(1, 9)));
+        assert!(!caret.contains(v(1, 1)) && !caret.contains(v(2, 0)));
+        let exact = VersionRange::provided("1.3").unwrap();
+        assert_eq!(exact.min, v(1, 3));
+        assert_eq!(exact.end, Some(v(1, 4)));
+        let range = VersionRange::provided(">1, <=3.1").unwrap();
+        assert_eq!(range.min, v(2, 0));
+        assert_eq!(range.end, Some(v(3, 2)));
+        let open = VersionRange::requirement(">=2").unwrap();
+        assert!(open.contains(v(9, 0)) && open.overlaps(&range));
+        assert!(!caret.overlaps(&range));
+        assert_eq!(SlotVersion::parse("4"), Some(v(4, 0)));
+    }
+
+    #[test]
+    fn plug_accepts_newer_minor_of_required_major() {
+        let mut board = board();
+        let provides = vec!["ruzzle.slot.net@1.3".to_string()];
+        assert_eq!(board.can_plug("ruzzle.slot.net@1", &provides), Ok(()));
+        board
+            .plug("ruzzle.slot.net", "net-service", &provides)
+            .unwrap();
+        assert_eq!(
+            board.provider_for("ruzzle.slot.net@1.3"),
+            Some("net-service")
+        );
+        assert_eq!(
+            board.unplug("ruzzle.slot.net@1.3"),
+            Ok(Some("net-service".to_string()))
+        );
+        let next_major = vec!["ruzzle.slot.net@2".to_string()];
+        assert_eq!(
+            board.plug("ruzzle.slot.net", "net-next", &next_major),
+            Err(BoardError::SlotNotCompatible)
+        );
+        assert_eq!(board.provider_for("ruzzle.slot.net@2"), None);
+    }
+
+    #[test]
+    fn plug_matches_module_ranges_against_slot_minimums() {
+        let mut board = PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.fs@1.4", true)]);
+        assert_eq!(
+            board.can_plug("ruzzle.slot.fs", &["ruzzle.slot.fs@1.3".to_string()]),
+            Err(BoardError::SlotNotCompatible)
+        );
+        let ranged = vec!["ruzzle.slot.fs@>=1.2, <2".to_string()];
+        board.plug("ruzzle.slot.fs", "fs-service", &ranged).unwrap();
+        assert!(board.is_complete());
+        assert_eq!(
+            board
+                .slot("ruzzle.slot.fs@>=1.2, <2")
+                .map(|slot| slot.name.as_str()),
+            Some("ruzzle.slot.fs@1.4")
+        );
+        let mut board = PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.fs@1.4", true)]);
+        board.mark_running("fs-service", &["ruzzle.slot.fs@1.6".to_string()]);
+        assert_eq!(board.provider_for("ruzzle.slot.fs"), Some("fs-service"));
+    }
+
     #[test]
     fn can_plug_detects_success() {
         let mut board = board();
@@ -426,6 +2282,6 @@ mod tests {
     fn mark_running_skips_invalid_slot() {
         let mut board = board();
         board.mark_running("console-service", &["bad@".to_string()]);
-        assert!(board.list().iter().all(|slot| slot.provider.is_none()));
+        assert!(board.list().iter().all(|slot| slot.providers.is_empty()));
     }
 }
@@ -2,9 +2,18 @@
 
 extern crate alloc;
 
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::cmp::Reverse;
+use user_sysinfo_service::{format_duration_ns, LatencyStats, MetricsRegistry};
+
+/// Upper bounds (inclusive) of the request latency histogram buckets.
+pub const LATENCY_BUCKETS_NS: [u64; 4] = [1_000_000, 10_000_000, 100_000_000, 1_000_000_000];
+
+/// Labels for the latency buckets, including the overflow bucket.
+const LATENCY_BUCKET_LABELS: [&str; 5] = ["1ms", "10ms", "100ms", "1s", "inf"];
 
 /// Server configuration snapshot.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -38,11 +47,147 @@ pub enum ServerError {
     RouteExists,
 }
 
+/// Request metrics for one route and status class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteMetrics {
+    pub method: String,
+    pub path: String,
+    pub status_class: u16,
+    pub latency: LatencyStats,
+    pub buckets: [u64; LATENCY_BUCKETS_NS.len() + 1],
+}
+
+/// Size and time limits enforced on each connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerLimits {
+    pub max_header_bytes: usize,
+    pub max_body_bytes: usize,
+    pub read_timeout_ns: u64,
+    pub write_timeout_ns: u64,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        Self {
+            max_header_bytes: 8 * 1024,
+            max_body_bytes: 64 * 1024,
+            read_timeout_ns: 5_000_000_000,
+            write_timeout_ns: 5_000_000_000,
+        }
+    }
+}
+
+/// Errors produced while parsing a raw HTTP request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    Incomplete,
+    Malformed,
+    HeaderTooLarge,
+    BodyTooLarge,
+}
+
+impl ParseError {
+    /// Returns the HTTP status used to reject the request, if any.
+    pub fn status(self) -> Option<u16> {
+        match self {
+            ParseError::Incomplete => None,
+            ParseError::Malformed => Some(400),
+            ParseError::HeaderTooLarge | ParseError::BodyTooLarge => Some(413),
+        }
+    }
+}
+
+/// Lifecycle of a driven connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Reading,
+    Writing,
+    Closed,
+}
+
+/// One client connection with bounded buffers and a read or write deadline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    limits: ServerLimits,
+    inbound: Vec<u8>,
+    outbound: Vec<u8>,
+    deadline_ns: u64,
+    state: ConnectionState,
+}
+
+impl Connection {
+    /// Opens a connection whose read deadline starts at `now_ns`.
+    pub fn new(limits: ServerLimits, now_ns: u64) -> Self {
+        Self {
+            limits,
+            inbound: Vec::new(),
+            outbound: Vec::new(),
+            deadline_ns: now_ns.saturating_add(limits.read_timeout_ns),
+            state: ConnectionState::Reading,
+        }
+    }
+
+    /// Buffers bytes sent by the client, keeping at most one byte past the limits.
+    pub fn receive(&mut self, bytes: &[u8]) {
+        if self.state != ConnectionState::Reading {
+            return;
+        }
+        let cap = self
+            .limits
+            .max_header_bytes
+            .saturating_add(self.limits.max_body_bytes)
+            .saturating_add(1);
+        let room = cap.saturating_sub(self.inbound.len());
+        self.inbound
+            .extend_from_slice(&bytes[..bytes.len().min(room)]);
+    }
+
+    /// Hands up to `max` response bytes to the client.
+    pub fn take_output(&mut self, max: usize) -> Vec<u8> {
+        let count = max.min(self.outbound.len());
+        self.outbound.drain(..count).collect()
+    }
+
+    /// Returns the current connection state.
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Returns the number of buffered request bytes.
+    pub fn buffered(&self) -> usize {
+        self.inbound.len()
+    }
+
+    fn respond(&mut self, response: &HttpResponse, now_ns: u64) {
+        self.inbound.clear();
+        self.outbound = encode_response(response);
+        self.deadline_ns = now_ns.saturating_add(self.limits.write_timeout_ns);
+        self.state = ConnectionState::Writing;
+    }
+}
+
+/// Outcome of a graceful shutdown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    pub drained: usize,
+    pub aborted: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ClassMetrics {
+    latency: LatencyStats,
+    buckets: [u64; LATENCY_BUCKETS_NS.len() + 1],
+}
+
 /// Simple server stack for in-memory routing.
 #[derive(Debug, Clone)]
 pub struct ServerStack {
     config: ServerConfig,
     routes: BTreeMap<(String, String), HttpResponse>,
+    metrics: BTreeMap<(String, String, u16), ClassMetrics>,
+    unmatched: u64,
+    in_flight: VecDeque<HttpRequest>,
+    limits: ServerLimits,
     running: bool,
 }
 
@@ -52,6 +197,10 @@ impl ServerStack {
         Self {
             config,
             routes: BTreeMap::new(),
+            metrics: BTreeMap::new(),
+            unmatched: 0,
+            in_flight: VecDeque::new(),
+            limits: ServerLimits::default(),
             running: false,
         }
     }
@@ -61,6 +210,48 @@ impl ServerStack {
         &self.config
     }
 
+    /// Returns the per-connection limits.
+    pub fn limits(&self) -> ServerLimits {
+        self.limits
+    }
+
+    /// Replaces the per-connection limits used by new connections.
+    pub fn set_limits(&mut self, limits: ServerLimits) {
+        self.limits = limits;
+    }
+
+    /// Opens a connection using the current limits.
+    pub fn open_connection(&self, now_ns: u64) -> Connection {
+        Connection::new(self.limits, now_ns)
+    }
+
+    /// Advances a connection: parses and answers its request, or enforces its deadline.
+    pub fn drive(&mut self, connection: &mut Connection, now_ns: u64) -> ConnectionState {
+        match connection.state {
+            ConnectionState::Reading => {
+                match parse_request(&connection.inbound, &connection.limits) {
+                    Ok(request) => {
+                        let response = self.serve(&request, || now_ns);
+                        connection.respond(&response, now_ns);
+                    }
+                    Err(ParseError::Incomplete) if now_ns < connection.deadline_ns => {}
+                    Err(err) => {
+                        let status = err.status().unwrap_or(408);
+                        connection.respond(&error_response(status), now_ns);
+                    }
+                }
+            }
+            ConnectionState::Writing => {
+                if connection.outbound.is_empty() || now_ns >= connection.deadline_ns {
+                    connection.outbound.clear();
+                    connection.state = ConnectionState::Closed;
+                }
+            }
+            ConnectionState::Closed => {}
+        }
+        connection.state
+    }
+
     /// Registers a static route response.
     pub fn register_route(
         &mut self,
@@ -85,15 +276,56 @@ impl ServerStack {
         Ok(())
     }
 
-    /// Stops the server stack.
+    /// Stops the server stack immediately, dropping in-flight requests.
     pub fn stop(&mut self) -> Result<(), ServerError> {
         if !self.running {
             return Err(ServerError::NotRunning);
         }
         self.running = false;
+        self.in_flight.clear();
         Ok(())
     }
 
+    /// Accepts a connection and queues its request as in flight.
+    pub fn accept(&mut self, request: HttpRequest) -> Result<(), ServerError> {
+        if !self.running {
+            return Err(ServerError::NotRunning);
+        }
+        self.in_flight.push_back(request);
+        Ok(())
+    }
+
+    /// Returns the number of accepted requests not yet answered.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Serves the oldest in-flight request, if any.
+    pub fn process_next(&mut self, now_ns: impl FnMut() -> u64) -> Option<HttpResponse> {
+        let request = self.in_flight.pop_front()?;
+        Some(self.serve(&request, now_ns))
+    }
+
+    /// Stops accepting connections and drains in-flight requests until `deadline_ns`.
+    pub fn shutdown(
+        &mut self,
+        deadline_ns: u64,
+        mut now_ns: impl FnMut() -> u64,
+    ) -> Result<ShutdownReport, ServerError> {
+        if !self.running {
+            return Err(ServerError::NotRunning);
+        }
+        self.running = false;
+        let mut report = ShutdownReport::default();
+        while !self.in_flight.is_empty() && now_ns() < deadline_ns {
+            self.process_next(&mut now_ns);
+            report.drained += 1;
+        }
+        report.aborted = self.in_flight.len();
+        self.in_flight.clear();
+        Ok(report)
+    }
+
     /// Handles a request with the registered routes.
     pub fn handle(&self, request: &HttpRequest) -> HttpResponse {
         let key = (request.method.clone(), request.path.clone());
@@ -106,6 +338,76 @@ impl ServerStack {
         }
     }
 
+    /// Handles a request and records its latency using the provided clock.
+    pub fn serve(
+        &mut self,
+        request: &HttpRequest,
+        mut now_ns: impl FnMut() -> u64,
+    ) -> HttpResponse {
+        let start = now_ns();
+        let response = self.handle(request);
+        let elapsed = now_ns().saturating_sub(start);
+        self.record(request, response.status, elapsed);
+        response
+    }
+
+    /// Records one request against its route and status class.
+    pub fn record(&mut self, request: &HttpRequest, status: u16, elapsed_ns: u64) {
+        if !self.config.metrics_enabled {
+            return;
+        }
+        let key = (request.method.clone(), request.path.clone());
+        if !self.routes.contains_key(&key) {
+            self.unmatched += 1;
+            return;
+        }
+        let entry = self
+            .metrics
+            .entry((key.0, key.1, status_class(status)))
+            .or_default();
+        entry.latency.record(elapsed_ns);
+        let bucket = LATENCY_BUCKETS_NS
+            .iter()
+            .position(|bound| elapsed_ns <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_NS.len());
+        entry.buckets[bucket] += 1;
+    }
+
+    /// Returns per-route metrics, hottest (most requests) first.
+    pub fn route_metrics(&self) -> Vec<RouteMetrics> {
+        let mut rows = self
+            .metrics
+            .iter()
+            .map(|((method, path, class), metrics)| RouteMetrics {
+                method: method.clone(),
+                path: path.clone(),
+                status_class: *class,
+                latency: metrics.latency,
+                buckets: metrics.buckets,
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|row| Reverse(row.latency.count));
+        rows
+    }
+
+    /// Returns the number of requests that matched no registered route.
+    pub fn unmatched_requests(&self) -> u64 {
+        self.unmatched
+    }
+
+    /// Publishes route latency stats into a metrics registry.
+    pub fn export_metrics(&self, registry: &mut MetricsRegistry) {
+        for row in self.route_metrics() {
+            registry.set_latency(&route_metric_name(&row), row.latency);
+        }
+    }
+
+    /// Drops all recorded request metrics.
+    pub fn reset_metrics(&mut self) {
+        self.metrics.clear();
+        self.unmatched = 0;
+    }
+
     /// Returns whether the server is running.
     pub fn is_running(&self) -> bool {
         self.running
@@ -117,6 +419,139 @@ impl ServerStack {
     }
 }
 
+/// Parses a buffered HTTP/1.x request, enforcing header and body limits.
+pub fn parse_request(raw: &[u8], limits: &ServerLimits) -> Result<HttpRequest, ParseError> {
+    let Some(header_end) = raw.windows(4).position(|window| window == b"\r\n\r\n") else {
+        if raw.len() > limits.max_header_bytes {
+            return Err(ParseError::HeaderTooLarge);
+        }
+        return Err(ParseError::Incomplete);
+    };
+    if header_end + 4 > limits.max_header_bytes {
+        return Err(ParseError::HeaderTooLarge);
+    }
+    let head = core::str::from_utf8(&raw[..header_end]).map_err(|_| ParseError::Malformed)?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split(' ');
+    let (Some(method), Some(path), Some(version), None) = (
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+    ) else {
+        return Err(ParseError::Malformed);
+    };
+    if method.is_empty() || !path.starts_with('/') || !version.starts_with("HTTP/1.") {
+        return Err(ParseError::Malformed);
+    }
+    let mut content_length = 0usize;
+    for line in lines {
+        let (name, value) = line.split_once(':').ok_or(ParseError::Malformed)?;
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| ParseError::Malformed)?;
+        }
+    }
+    if content_length > limits.max_body_bytes {
+        return Err(ParseError::BodyTooLarge);
+    }
+    let body = &raw[header_end + 4..];
+    if body.len() < content_length {
+        return Err(ParseError::Incomplete);
+    }
+    let body = core::str::from_utf8(&body[..content_length]).map_err(|_| ParseError::Malformed)?;
+    Ok(HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        body: body.to_string(),
+    })
+}
+
+/// Encodes a response as HTTP/1.1 bytes with a `Content-Length` header.
+pub fn encode_response(response: &HttpResponse) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\n\r\n{}",
+        response.status,
+        reason_phrase(response.status),
+        response.body.len(),
+        response.body
+    )
+    .into_bytes()
+}
+
+/// Returns the reason phrase for common status codes.
+pub fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        408 => "Request Timeout",
+        413 => "Content Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+fn error_response(status: u16) -> HttpResponse {
+    HttpResponse {
+        status,
+        body: reason_phrase(status).to_ascii_lowercase(),
+    }
+}
+
+/// Returns the status class (1-5) for an HTTP status code.
+pub fn status_class(status: u16) -> u16 {
+    (status / 100).clamp(1, 5)
+}
+
+/// Returns the metrics registry name for a route row, e.g. `http.GET /.2xx`.
+pub fn route_metric_name(row: &RouteMetrics) -> String {
+    format!("http.{} {}.{}xx", row.method, row.path, row.status_class)
+}
+
+/// Formats a shutdown report as a single line.
+pub fn format_shutdown_report(report: &ShutdownReport) -> String {
+    format!(
+        "server shutdown: drained={} aborted={}",
+        report.drained, report.aborted
+    )
+}
+
+/// Formats the hottest routes for the `http top` command.
+pub fn format_http_top(rows: &[RouteMetrics], unmatched: u64, limit: usize) -> String {
+    let mut out = String::new();
+    out.push_str("http top:\n");
+    if rows.is_empty() {
+        out.push_str("  <none>\n");
+    }
+    for row in rows.iter().take(limit) {
+        out.push_str(&format!(
+            "  {} {} {}xx count={} avg={} max={} hist=",
+            row.method,
+            row.path,
+            row.status_class,
+            row.latency.count,
+            format_duration_ns(row.latency.mean_ns()),
+            format_duration_ns(row.latency.max_ns),
+        ));
+        let buckets = row
+            .buckets
+            .iter()
+            .zip(LATENCY_BUCKET_LABELS)
+            .map(|(count, label)| format!("{label}:{count}"))
+            .collect::<Vec<_>>();
+        out.push_str(&buckets.join(","));
+        out.push('\n');
+    }
+    if unmatched > 0 {
+        out.push_str(&format!("  unmatched count={unmatched}\n"));
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,4 +669,339 @@ mod tests {
         let mut server = ServerStack::new(config());
         assert_eq!(server.stop(), Err(ServerError::NotRunning));
     }
+
+    fn request(method: &str, path: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            body: "".to_string(),
+        }
+    }
+
+    fn server_with_routes() -> ServerStack {
+        let mut server = ServerStack::new(config());
+        for (path, status) in [("/", 200), ("/fail", 503)] {
+            server
+                .register_route(
+                    "GET",
+                    path,
+                    HttpResponse {
+                        status,
+                        body: "x".to_string(),
+                    },
+                )
+                .unwrap();
+        }
+        server
+    }
+
+    #[test]
+    fn serve_records_latency_per_route_and_class() {
+        let mut server = server_with_routes();
+        let mut ticks = [0, 2_000_000, 10, 20, 30, 40].into_iter();
+        let mut clock = move || ticks.next().unwrap();
+        assert_eq!(server.serve(&request("GET", "/"), &mut clock).status, 200);
+        assert_eq!(server.serve(&request("GET", "/"), &mut clock).status, 200);
+        assert_eq!(
+            server.serve(&request("GET", "/missing"), &mut clock).status,
+            404
+        );
+        server.record(&request("GET", "/fail"), 503, 2_000_000_000);
+
+        let rows = server.route_metrics();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].path, "/");
+        assert_eq!(rows[0].status_class, 2);
+        assert_eq!(rows[0].latency.count, 2);
+        assert_eq!(rows[0].latency.max_ns, 2_000_000);
+        assert_eq!(rows[0].buckets, [1, 1, 0, 0, 0]);
+        assert_eq!(rows[1].status_class, 5);
+        assert_eq!(rows[1].buckets, [0, 0, 0, 0, 1]);
+        assert_eq!(server.unmatched_requests(), 1);
+
+        server.reset_metrics();
+        assert!(server.route_metrics().is_empty());
+        assert_eq!(server.unmatched_requests(), 0);
+    }
+
+    #[test]
+    fn record_is_disabled_without_metrics() {
+        let mut cfg = config();
+        cfg.metrics_enabled = false;
+        let mut server = ServerStack::new(cfg);
+        server.record(&request("GET", "/"), 200, 5);
+        assert!(server.route_metrics().is_empty());
+        assert_eq!(server.unmatched_requests(), 0);
+    }
+
+    #[test]
+    fn status_classes_are_clamped() {
+        assert_eq!(status_class(0), 1);
+        assert_eq!(status_class(204), 2);
+        assert_eq!(status_class(404), 4);
+        assert_eq!(status_class(999), 5);
+    }
+
+    #[test]
+    fn export_metrics_publishes_route_latency() {
+        let mut server = server_with_routes();
+        server.record(&request("GET", "/"), 200, 1_500_000);
+        let mut registry = MetricsRegistry::new();
+        server.export_metrics(&mut registry);
+        let stats = registry.latency("http.GET /.2xx").unwrap();
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.total_ns, 1_500_000);
+    }
+
+    #[test]
+    fn format_http_top_lists_hottest_routes() {
+        assert_eq!(format_http_top(&[], 0, 5), "http top:\n  <none>\n");
+        let mut server = server_with_routes();
+        server.record(&request("GET", "/fail"), 503, 500_000);
+        server.record(&request("GET", "/"), 200, 1_500_000);
+        server.record(&request("GET", "/"), 200, 2_500_000);
+        server.record(&request("POST", "/"), 200, 1);
+        let text = format_http_top(&server.route_metrics(), server.unmatched_requests(), 1);
+        assert_eq!(
+            text,
+            "http top:\n  GET / 2xx count=2 avg=2.000ms max=2.500ms hist=1ms:0,10ms:2,100ms:0,1s:0,inf:0\n  unmatched count=1\n"
+        );
+    }
+
+    #[test]
+    fn accept_queues_requests_until_processed() {
+        let mut server = server_with_routes();
+        assert_eq!(
+            server.accept(request("GET", "/")),
+            Err(ServerError::NotRunning)
+        );
+        server.start().unwrap();
+        server.accept(request("GET", "/")).unwrap();
+        server.accept(request("GET", "/fail")).unwrap();
+        assert_eq!(server.in_flight(), 2);
+        assert_eq!(server.process_next(|| 0).map(|r| r.status), Some(200));
+        assert_eq!(server.process_next(|| 0).map(|r| r.status), Some(503));
+        assert_eq!(server.process_next(|| 0), None);
+        assert_eq!(server.route_metrics().len(), 2);
+    }
+
+    #[test]
+    fn shutdown_drains_in_flight_requests_before_deadline() {
+        let mut server = server_with_routes();
+        server.start().unwrap();
+        for _ in 0..3 {
+            server.accept(request("GET", "/")).unwrap();
+        }
+        let report = server.shutdown(100, || 10).unwrap();
+        assert_eq!(
+            report,
+            ShutdownReport {
+                drained: 3,
+                aborted: 0
+            }
+        );
+        assert!(!server.is_running());
+        assert_eq!(
+            server.accept(request("GET", "/")),
+            Err(ServerError::NotRunning)
+        );
+        assert_eq!(server.shutdown(100, || 10), Err(ServerError::NotRunning));
+    }
+
+    #[test]
+    fn shutdown_aborts_requests_past_deadline() {
+        let mut server = server_with_routes();
+        server.start().unwrap();
+        for _ in 0..4 {
+            server.accept(request("GET", "/")).unwrap();
+        }
+        let mut now = 0;
+        let report = server
+            .shutdown(30, || {
+                now += 5;
+                now
+            })
+            .unwrap();
+        assert_eq!(
+            report,
+            ShutdownReport {
+                drained: 2,
+                aborted: 2
+            }
+        );
+        assert_eq!(server.in_flight(), 0);
+        assert_eq!(
+            format_shutdown_report(&report),
+            "server shutdown: drained=2 aborted=2"
+        );
+    }
+
+    #[test]
+    fn stop_drops_in_flight_requests() {
+        let mut server = server_with_routes();
+        server.start().unwrap();
+        server.accept(request("GET", "/")).unwrap();
+        server.stop().unwrap();
+        assert_eq!(server.in_flight(), 0);
+    }
+
+    fn small_limits() -> ServerLimits {
+        ServerLimits {
+            max_header_bytes: 64,
+            max_body_bytes: 8,
+            read_timeout_ns: 100,
+            write_timeout_ns: 50,
+        }
+    }
+
+    #[test]
+    fn parse_request_reads_line_headers_and_body() {
+        let limits = ServerLimits::default();
+        let raw = b"POST /echo HTTP/1.1\r\nHost: x\r\ncontent-length: 5\r\n\r\nhello!";
+        assert_eq!(
+            parse_request(raw, &limits),
+            Ok(HttpRequest {
+                method: "POST".to_string(),
+                path: "/echo".to_string(),
+                body: "hello".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_request(b"GET / HTTP/1.0\r\n\r\n", &limits),
+            Ok(request("GET", "/"))
+        );
+    }
+
+    #[test]
+    fn parse_request_waits_for_more_bytes() {
+        let limits = ServerLimits::default();
+        assert_eq!(
+            parse_request(b"GET / HTTP/1.1\r\n", &limits),
+            Err(ParseError::Incomplete)
+        );
+        assert_eq!(
+            parse_request(b"POST / HTTP/1.1\r\nContent-Length: 4\r\n\r\nab", &limits),
+            Err(ParseError::Incomplete)
+        );
+    }
+
+    #[test]
+    fn parse_request_enforces_size_limits() {
+        let limits = small_limits();
+        assert_eq!(
+            parse_request(&[b'a'; 65], &limits),
+            Err(ParseError::HeaderTooLarge)
+        );
+        let mut long = b"GET / HTTP/1.1\r\nX: ".to_vec();
+        long.extend_from_slice(&[b'a'; 60]);
+        long.extend_from_slice(b"\r\n\r\n");
+        assert_eq!(
+            parse_request(&long, &limits),
+            Err(ParseError::HeaderTooLarge)
+        );
+        assert_eq!(
+            parse_request(b"POST / HTTP/1.1\r\nContent-Length: 9\r\n\r\n", &limits),
+            Err(ParseError::BodyTooLarge)
+        );
+    }
+
+    #[test]
+    fn parse_request_rejects_malformed_input() {
+        let limits = ServerLimits::default();
+        for raw in [
+            &b"GET /\r\n\r\n"[..],
+            b"GET / HTTP/1.1 extra\r\n\r\n",
+            b"GET nope HTTP/1.1\r\n\r\n",
+            b" / HTTP/1.1\r\n\r\n",
+            b"GET / SPDY/3\r\n\r\n",
+            b"GET / HTTP/1.1\r\nbroken\r\n\r\n",
+            b"GET / HTTP/1.1\r\nContent-Length: x\r\n\r\n",
+            b"GET / HTTP/1.1\r\nX: \xff\r\n\r\n",
+            b"POST / HTTP/1.1\r\nContent-Length: 1\r\n\r\n\xff",
+        ] {
+            assert_eq!(parse_request(raw, &limits), Err(ParseError::Malformed));
+        }
+        assert_eq!(ParseError::Incomplete.status(), None);
+    }
+
+    #[test]
+    fn encode_response_writes_status_line() {
+        let bytes = encode_response(&HttpResponse {
+            status: 200,
+            body: "ok".to_string(),
+        });
+        assert_eq!(bytes, b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+        for (status, phrase) in [
+            (400, "Bad Request"),
+            (404, "Not Found"),
+            (500, "Internal Server Error"),
+            (503, "Service Unavailable"),
+            (299, "Unknown"),
+        ] {
+            assert_eq!(reason_phrase(status), phrase);
+        }
+    }
+
+    #[test]
+    fn drive_serves_request_and_closes_after_write() {
+        let mut server = server_with_routes();
+        assert_eq!(server.limits(), ServerLimits::default());
+        server.set_limits(small_limits());
+        let mut conn = server.open_connection(0);
+        conn.receive(b"GET / HTTP/1.1\r\n");
+        assert_eq!(server.drive(&mut conn, 10), ConnectionState::Reading);
+        conn.receive(b"\r\n");
+        assert_eq!(server.drive(&mut conn, 20), ConnectionState::Writing);
+        conn.receive(b"ignored");
+        assert_eq!(conn.buffered(), 0);
+        let mut out = conn.take_output(8);
+        out.extend(conn.take_output(usize::MAX));
+        assert!(out.starts_with(b"HTTP/1.1 200 OK"));
+        assert_eq!(server.drive(&mut conn, 30), ConnectionState::Closed);
+        assert_eq!(server.drive(&mut conn, 40), ConnectionState::Closed);
+        assert_eq!(server.route_metrics()[0].latency.count, 1);
+    }
+
+    #[test]
+    fn drive_times_out_slow_readers_with_408() {
+        let mut server = server_with_routes();
+        server.set_limits(small_limits());
+        let mut conn = server.open_connection(0);
+        conn.receive(b"GET / HT");
+        assert_eq!(server.drive(&mut conn, 99), ConnectionState::Reading);
+        assert_eq!(server.drive(&mut conn, 100), ConnectionState::Writing);
+        let out = conn.take_output(usize::MAX);
+        assert!(out.starts_with(b"HTTP/1.1 408 Request Timeout"));
+    }
+
+    #[test]
+    fn drive_rejects_oversized_requests_with_413() {
+        let mut server = server_with_routes();
+        server.set_limits(small_limits());
+        let mut conn = server.open_connection(0);
+        conn.receive(&[b'a'; 500]);
+        assert_eq!(conn.buffered(), 64 + 8 + 1);
+        assert_eq!(server.drive(&mut conn, 1), ConnectionState::Writing);
+        assert!(conn
+            .take_output(usize::MAX)
+            .starts_with(b"HTTP/1.1 413 Content Too Large"));
+
+        let mut conn = server.open_connection(0);
+        conn.receive(b"GET\r\n\r\n");
+        server.drive(&mut conn, 1);
+        assert!(conn.take_output(usize::MAX).starts_with(b"HTTP/1.1 400"));
+    }
+
+    #[test]
+    fn drive_aborts_slow_writers() {
+        let mut server = server_with_routes();
+        server.set_limits(small_limits());
+        let mut conn = server.open_connection(0);
+        conn.receive(b"GET / HTTP/1.1\r\n\r\n");
+        assert_eq!(server.drive(&mut conn, 10), ConnectionState::Writing);
+        conn.take_output(4);
+        assert_eq!(server.drive(&mut conn, 59), ConnectionState::Writing);
+        assert_eq!(server.drive(&mut conn, 60), ConnectionState::Closed);
+        assert!(conn.take_output(usize::MAX).is_empty());
+    }
 }
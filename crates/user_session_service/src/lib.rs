@@ -2,9 +2,19 @@
 
 extern crate alloc;
 
+use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
-use user_user_service::UserManager;
+use ruzzle_protocol::auth::Credential;
+use user_user_service::{default_shell, UserManager};
+
+/// Slot family that shell providers plug into (`ruzzle.slot.shell@N`).
+pub const SHELL_SLOT_FAMILY: &str = "ruzzle.slot.shell";
+
+/// Module that provides the default login shell.
+pub const DEFAULT_SHELL_MODULE: &str = "tui-shell";
 
 /// Errors returned by session management.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,18 +22,86 @@ pub enum SessionError {
     UserNotFound,
     AlreadyLoggedIn,
     NotLoggedIn,
+    UnknownShell,
+}
+
+/// Returns true if the slot belongs to the shell slot family.
+pub fn is_shell_slot(slot: &str) -> bool {
+    match slot.trim().strip_prefix(SHELL_SLOT_FAMILY) {
+        Some("") => true,
+        Some(rest) => rest.strip_prefix('@').is_some_and(|version| {
+            !version.is_empty() && version.chars().all(|ch| ch.is_ascii_digit())
+        }),
+        None => false,
+    }
+}
+
+/// Returns the shell path registered for a shell provider module.
+pub fn shell_path_for(module: &str) -> String {
+    if module == DEFAULT_SHELL_MODULE {
+        default_shell().to_string()
+    } else {
+        format!("/bin/{}", module)
+    }
+}
+
+/// Maps login shell paths to the modules that provide them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShellRegistry {
+    shells: BTreeMap<String, String>,
+}
+
+impl ShellRegistry {
+    /// Creates an empty shell registry.
+    pub fn new() -> Self {
+        Self {
+            shells: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a module if any of its slots is in the shell slot family.
+    pub fn register_module(&mut self, module: &str, slots: &[String]) -> Option<String> {
+        if !slots.iter().any(|slot| is_shell_slot(slot)) {
+            return None;
+        }
+        let path = shell_path_for(module);
+        self.shells.insert(path.clone(), module.to_string());
+        Some(path)
+    }
+
+    /// Removes every shell path provided by a module.
+    pub fn unregister_module(&mut self, module: &str) {
+        self.shells.retain(|_, provider| provider != module);
+    }
+
+    /// Returns the module providing a shell path, if registered.
+    pub fn provider(&self, path: &str) -> Option<&str> {
+        self.shells.get(path).map(String::as_str)
+    }
+
+    /// Lists `(path, module)` pairs sorted by path.
+    pub fn list(&self) -> Vec<(String, String)> {
+        self.shells
+            .iter()
+            .map(|(path, module)| (path.clone(), module.clone()))
+            .collect()
+    }
 }
 
 /// Tracks the active login session.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct SessionManager {
     active: Option<String>,
+    shell: Option<String>,
 }
 
 impl SessionManager {
     /// Creates an empty session manager.
     pub fn new() -> Self {
-        Self { active: None }
+        Self {
+            active: None,
+            shell: None,
+        }
     }
 
     /// Returns true if a user is logged in.
@@ -36,6 +114,31 @@ impl SessionManager {
         self.active.as_deref()
     }
 
+    /// Returns the credential attached to IPC requests from the active session.
+    pub fn credential(&self, users: &UserManager) -> Option<Credential> {
+        let user = users.get_user(self.active.as_deref()?)?;
+        Some(Credential::new(&user.name, user.is_admin))
+    }
+
+    /// Returns the shell module serving the active session, if dispatched.
+    pub fn active_shell(&self) -> Option<&str> {
+        self.shell.as_deref()
+    }
+
+    /// Binds the active session to the module providing the user's shell.
+    pub fn dispatch(
+        &mut self,
+        users: &UserManager,
+        shells: &ShellRegistry,
+    ) -> Result<&str, SessionError> {
+        let name = self.active.as_deref().ok_or(SessionError::NotLoggedIn)?;
+        let user = users.get_user(name).ok_or(SessionError::UserNotFound)?;
+        let module = shells
+            .provider(&user.shell)
+            .ok_or(SessionError::UnknownShell)?;
+        Ok(self.shell.insert(module.to_string()))
+    }
+
     /// Logs in a user if they exist.
     pub fn login(&mut self, users: &UserManager, name: &str) -> Result<(), SessionError> {
         if self.active.is_some() {
@@ -54,6 +157,7 @@ impl SessionManager {
             return Err(SessionError::NotLoggedIn);
         }
         self.active = None;
+        self.shell = None;
         Ok(())
     }
 }
@@ -79,6 +183,27 @@ mod tests {
         assert_eq!(session.active_user(), None);
     }
 
+    #[test]
+    fn credential_reflects_the_active_user() {
+        let mut users = UserManager::new();
+        users.add_user("root", true).unwrap();
+        users.add_user("guest", false).unwrap();
+
+        let mut session = SessionManager::new();
+        assert_eq!(session.credential(&users), None);
+        session.login(&users, "guest").unwrap();
+        assert_eq!(
+            session.credential(&users),
+            Some(Credential::new("guest", false))
+        );
+        session.logout().unwrap();
+        session.login(&users, "root").unwrap();
+        assert_eq!(
+            session.credential(&users),
+            Some(Credential::new("root", true))
+        );
+    }
+
     #[test]
     fn login_rejects_missing_user() {
         let users = UserManager::new();
@@ -105,4 +230,78 @@ mod tests {
         let mut session = SessionManager::new();
         assert_eq!(session.logout(), Err(SessionError::NotLoggedIn));
     }
+
+    #[test]
+    fn shell_slot_family_matches_versions() {
+        assert!(is_shell_slot("ruzzle.slot.shell@1"));
+        assert!(is_shell_slot("ruzzle.slot.shell@12"));
+        assert!(is_shell_slot("ruzzle.slot.shell"));
+        assert!(!is_shell_slot("ruzzle.slot.shell@"));
+        assert!(!is_shell_slot("ruzzle.slot.shell@x"));
+        assert!(!is_shell_slot("ruzzle.slot.shellx@1"));
+        assert!(!is_shell_slot("ruzzle.slot.editor@1"));
+    }
+
+    #[test]
+    fn shell_registry_tracks_providers() {
+        let mut shells = ShellRegistry::new();
+        assert_eq!(
+            shells.register_module("tui-shell", &["ruzzle.slot.shell@1".to_string()]),
+            Some("/bin/ruzzle-shell".to_string())
+        );
+        assert_eq!(
+            shells.register_module("fish", &["ruzzle.slot.shell@2".to_string()]),
+            Some("/bin/fish".to_string())
+        );
+        assert_eq!(
+            shells.register_module("vim", &["ruzzle.slot.editor@1".to_string()]),
+            None
+        );
+        assert_eq!(shells.provider("/bin/fish"), Some("fish"));
+        assert_eq!(
+            shells.list(),
+            vec![
+                ("/bin/fish".to_string(), "fish".to_string()),
+                ("/bin/ruzzle-shell".to_string(), "tui-shell".to_string()),
+            ]
+        );
+        shells.unregister_module("fish");
+        assert_eq!(shells.provider("/bin/fish"), None);
+    }
+
+    #[test]
+    fn dispatch_binds_session_to_user_shell() {
+        let mut users = UserManager::new();
+        users.add_user("root", true).unwrap();
+        let mut shells = ShellRegistry::new();
+        shells.register_module("tui-shell", &["ruzzle.slot.shell@1".to_string()]);
+        shells.register_module("fish", &["ruzzle.slot.shell@2".to_string()]);
+
+        let mut session = SessionManager::new();
+        assert_eq!(
+            session.dispatch(&users, &shells),
+            Err(SessionError::NotLoggedIn)
+        );
+        session.login(&users, "root").unwrap();
+        assert_eq!(session.dispatch(&users, &shells), Ok("tui-shell"));
+        users.set_shell("root", "/bin/fish").unwrap();
+        assert_eq!(session.dispatch(&users, &shells), Ok("fish"));
+        assert_eq!(session.active_shell(), Some("fish"));
+
+        users.set_shell("root", "/bin/zsh").unwrap();
+        assert_eq!(
+            session.dispatch(&users, &shells),
+            Err(SessionError::UnknownShell)
+        );
+        session.logout().unwrap();
+        assert_eq!(session.active_shell(), None);
+
+        users.add_user("guest", false).unwrap();
+        session.login(&users, "guest").unwrap();
+        users.remove_user("guest").unwrap();
+        assert_eq!(
+            session.dispatch(&users, &shells),
+            Err(SessionError::UserNotFound)
+        );
+    }
 }
@@ -2,7 +2,24 @@
 
 extern crate alloc;
 
+use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Log level names accepted by `set_log_level`.
+const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+/// Prompt template used until one is configured.
+pub const DEFAULT_PROMPT: &str = "{user}@{hostname}:{cwd}$";
+
+/// Placeholders a prompt template may reference as `{name}`.
+pub const PROMPT_FIELDS: [&str; 3] = ["user", "hostname", "cwd"];
+
+/// Longest prompt template accepted by `set_prompt`.
+const MAX_PROMPT_LEN: usize = 64;
+
+/// Most DNS servers accepted by `set_dns_servers`.
+pub const MAX_DNS_SERVERS: usize = 3;
 
 /// Errors returned when updating system settings.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -11,6 +28,12 @@ pub enum SettingsError {
     InvalidLocale,
     InvalidTimezone,
     InvalidKeyboard,
+    InvalidLogTarget,
+    InvalidLogLevel,
+    InvalidPrompt,
+    InvalidDnsServers,
+    InvalidNetProfile,
+    UnknownKey,
 }
 
 /// System-wide settings configured during first boot.
@@ -20,6 +43,10 @@ pub struct SystemSettings {
     locale: String,
     timezone: String,
     keyboard: String,
+    prompt: String,
+    dns_servers: Vec<String>,
+    net_profile: Option<String>,
+    log_levels: BTreeMap<String, String>,
 }
 
 impl SystemSettings {
@@ -30,6 +57,10 @@ impl SystemSettings {
             locale: "en_US.UTF-8".to_string(),
             timezone: "UTC".to_string(),
             keyboard: "us".to_string(),
+            prompt: DEFAULT_PROMPT.to_string(),
+            dns_servers: Vec::new(),
+            net_profile: None,
+            log_levels: BTreeMap::new(),
         }
     }
 
@@ -53,6 +84,11 @@ impl SystemSettings {
         &self.keyboard
     }
 
+    /// Returns the shell prompt template.
+    pub fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
     /// Updates the hostname.
     pub fn set_hostname(&mut self, hostname: &str) -> Result<(), SettingsError> {
         if !is_valid_hostname(hostname) {
@@ -89,6 +125,116 @@ impl SystemSettings {
         Ok(())
     }
 
+    /// Updates the shell prompt template (`{user}`, `{hostname}`, `{cwd}`).
+    pub fn set_prompt(&mut self, prompt: &str) -> Result<(), SettingsError> {
+        if !is_valid_prompt(prompt) {
+            return Err(SettingsError::InvalidPrompt);
+        }
+        self.prompt = prompt.to_string();
+        Ok(())
+    }
+
+    /// Returns the configured DNS servers; empty defers to `/etc/resolv.conf`.
+    pub fn dns_servers(&self) -> &[String] {
+        &self.dns_servers
+    }
+
+    /// Updates the DNS servers from a comma-separated IPv4 list (empty clears).
+    pub fn set_dns_servers(&mut self, servers: &str) -> Result<(), SettingsError> {
+        let servers: Vec<String> = servers
+            .split(',')
+            .map(str::trim)
+            .filter(|server| !server.is_empty())
+            .map(str::to_string)
+            .collect();
+        if servers.len() > MAX_DNS_SERVERS || !servers.iter().all(|server| is_ipv4(server)) {
+            return Err(SettingsError::InvalidDnsServers);
+        }
+        self.dns_servers = servers;
+        Ok(())
+    }
+
+    /// Returns the network profile applied at boot, if one is designated.
+    pub fn net_profile(&self) -> Option<&str> {
+        self.net_profile.as_deref()
+    }
+
+    /// Designates the network profile applied at boot (empty clears).
+    pub fn set_net_profile(&mut self, name: &str) -> Result<(), SettingsError> {
+        if name.is_empty() {
+            self.net_profile = None;
+            return Ok(());
+        }
+        if !name
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-')
+        {
+            return Err(SettingsError::InvalidNetProfile);
+        }
+        self.net_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Returns the log level configured for a module, if any.
+    pub fn log_level(&self, module: &str) -> Option<&str> {
+        self.log_levels.get(module).map(String::as_str)
+    }
+
+    /// Returns every per-module log level in module order.
+    pub fn log_levels(&self) -> Vec<(&str, &str)> {
+        self.log_levels
+            .iter()
+            .map(|(module, level)| (module.as_str(), level.as_str()))
+            .collect()
+    }
+
+    /// Sets the minimum log level for a module.
+    pub fn set_log_level(&mut self, module: &str, level: &str) -> Result<(), SettingsError> {
+        if !is_valid_log_target(module) {
+            return Err(SettingsError::InvalidLogTarget);
+        }
+        if !LOG_LEVELS.contains(&level) {
+            return Err(SettingsError::InvalidLogLevel);
+        }
+        self.log_levels
+            .insert(module.to_string(), level.to_string());
+        Ok(())
+    }
+
+    /// Returns a setting by config key (`hostname`, `log.<module>`, ...).
+    ///
+    /// `dns` is multi-valued and read through `dns_servers` instead.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match key {
+            "hostname" => Some(self.hostname()),
+            "locale" => Some(self.locale()),
+            "timezone" => Some(self.timezone()),
+            "keyboard" => Some(self.keyboard()),
+            "prompt" => Some(self.prompt()),
+            "net.profile" => self.net_profile(),
+            _ => key
+                .strip_prefix("log.")
+                .and_then(|module| self.log_level(module)),
+        }
+    }
+
+    /// Updates a setting by config key, validating the value.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), SettingsError> {
+        match key {
+            "hostname" => self.set_hostname(value),
+            "locale" => self.set_locale(value),
+            "timezone" => self.set_timezone(value),
+            "keyboard" => self.set_keyboard(value),
+            "prompt" => self.set_prompt(value),
+            "dns" => self.set_dns_servers(value),
+            "net.profile" => self.set_net_profile(value),
+            _ => match key.strip_prefix("log.") {
+                Some(module) => self.set_log_level(module, value),
+                None => Err(SettingsError::UnknownKey),
+            },
+        }
+    }
+
     /// Serializes settings into a simple config text.
     pub fn to_config_text(&self) -> String {
         let mut out = String::new();
@@ -104,10 +250,97 @@ impl SystemSettings {
         out.push_str("keyboard=");
         out.push_str(&self.keyboard);
         out.push('\n');
+        out.push_str("prompt=");
+        out.push_str(&self.prompt);
+        out.push('\n');
+        if !self.dns_servers.is_empty() {
+            out.push_str("dns=");
+            out.push_str(&self.dns_servers.join(","));
+            out.push('\n');
+        }
+        if let Some(profile) = &self.net_profile {
+            out.push_str("net.profile=");
+            out.push_str(profile);
+            out.push('\n');
+        }
+        for (module, level) in &self.log_levels {
+            out.push_str("log.");
+            out.push_str(module);
+            out.push('=');
+            out.push_str(level);
+            out.push('\n');
+        }
         out
     }
 }
 
+/// Setting whose value differs from the one seen by the last `SettingsWatcher::poll`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsChange {
+    /// Config key, as accepted by `SystemSettings::set`.
+    pub key: String,
+    /// Previous value, or None if the key was unset.
+    pub old: Option<String>,
+    /// New value, or None if the key is now unset.
+    pub new: Option<String>,
+}
+
+/// Reports which settings changed between polls.
+///
+/// Settings are usually edited on a copy that replaces the original once it
+/// is saved, so the watcher compares config values instead of hooking setters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsWatcher {
+    seen: BTreeMap<String, String>,
+}
+
+impl SettingsWatcher {
+    /// Creates a watcher that reports only changes made after `settings`.
+    pub fn new(settings: &SystemSettings) -> Self {
+        Self {
+            seen: config_values(settings),
+        }
+    }
+
+    /// Returns the settings that changed since the last poll, ordered by key.
+    ///
+    /// A default watcher reports every set key on its first poll.
+    pub fn poll(&mut self, settings: &SystemSettings) -> Vec<SettingsChange> {
+        let current = config_values(settings);
+        let mut changes = Vec::new();
+        for (key, value) in &current {
+            if self.seen.get(key) != Some(value) {
+                changes.push(SettingsChange {
+                    key: key.clone(),
+                    old: self.seen.get(key).cloned(),
+                    new: Some(value.clone()),
+                });
+            }
+        }
+        for (key, value) in &self.seen {
+            if !current.contains_key(key) {
+                changes.push(SettingsChange {
+                    key: key.clone(),
+                    old: Some(value.clone()),
+                    new: None,
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.key.cmp(&b.key));
+        self.seen = current;
+        changes
+    }
+}
+
+fn config_values(settings: &SystemSettings) -> BTreeMap<String, String> {
+    settings
+        .to_config_text()
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
 fn is_valid_hostname(hostname: &str) -> bool {
     let trimmed = hostname.trim();
     if trimmed.is_empty() || trimmed.len() > 63 {
@@ -165,6 +398,48 @@ fn is_valid_keyboard(keyboard: &str) -> bool {
         .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-'))
 }
 
+fn is_valid_prompt(prompt: &str) -> bool {
+    if prompt.is_empty() || prompt.len() > MAX_PROMPT_LEN || prompt.trim() != prompt {
+        return false;
+    }
+    if prompt.chars().any(char::is_control) {
+        return false;
+    }
+    let mut rest = prompt;
+    while let Some(open) = rest.find(['{', '}']) {
+        if rest[open..].starts_with('}') {
+            return false;
+        }
+        let Some(close) = rest[open..].find('}') else {
+            return false;
+        };
+        if !PROMPT_FIELDS.contains(&&rest[open + 1..open + close]) {
+            return false;
+        }
+        rest = &rest[open + close + 1..];
+    }
+    true
+}
+
+fn is_ipv4(addr: &str) -> bool {
+    let parts: Vec<&str> = addr.split('.').collect();
+    parts.len() == 4
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part.len() <= 3
+                && part.bytes().all(|byte| byte.is_ascii_digit())
+                && (part.len() == 1 || !part.starts_with('0'))
+                && part.parse::<u8>().is_ok()
+        })
+}
+
+fn is_valid_log_target(module: &str) -> bool {
+    !module.is_empty()
+        && module
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '.'))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,6 +451,7 @@ mod tests {
         assert_eq!(settings.locale(), "en_US.UTF-8");
         assert_eq!(settings.timezone(), "UTC");
         assert_eq!(settings.keyboard(), "us");
+        assert_eq!(settings.prompt(), DEFAULT_PROMPT);
     }
 
     #[test]
@@ -262,5 +538,163 @@ mod tests {
         assert!(text.contains("locale=en_US.UTF-8"));
         assert!(text.contains("timezone=UTC"));
         assert!(text.contains("keyboard=us"));
+        assert!(text.contains("prompt={user}@{hostname}:{cwd}$\n"));
+    }
+
+    #[test]
+    fn keyed_access_matches_setters() {
+        let mut settings = SystemSettings::new_defaults();
+        assert_eq!(settings.get("hostname"), Some("ruzzle"));
+        assert_eq!(settings.get("log.net-service"), None);
+        assert_eq!(settings.get("color"), None);
+        for (key, value) in [
+            ("hostname", "lab"),
+            ("locale", "ko_KR"),
+            ("timezone", "Asia/Seoul"),
+            ("keyboard", "kr"),
+            ("prompt", "{user}>"),
+            ("log.net-service", "debug"),
+        ] {
+            settings.set(key, value).unwrap();
+            assert_eq!(settings.get(key), Some(value));
+        }
+        assert_eq!(settings.set("color", "on"), Err(SettingsError::UnknownKey));
+        assert_eq!(
+            settings.set("log.net-service", "loud"),
+            Err(SettingsError::InvalidLogLevel)
+        );
+    }
+
+    #[test]
+    fn dns_servers_are_validated_and_persisted() {
+        let mut settings = SystemSettings::new_defaults();
+        assert!(settings.dns_servers().is_empty());
+        assert!(!settings.to_config_text().contains("dns="));
+        settings.set("dns", "10.0.2.3, 1.1.1.1").unwrap();
+        assert_eq!(settings.dns_servers(), ["10.0.2.3", "1.1.1.1"]);
+        assert!(settings.to_config_text().contains("dns=10.0.2.3,1.1.1.1\n"));
+        for bad in [
+            "10.0.2",
+            "10.0.2.300",
+            "10.0.2.03",
+            "dns.lan",
+            "1.1.1.1,2.2.2.2,3.3.3.3,4.4.4.4",
+        ] {
+            assert_eq!(
+                settings.set_dns_servers(bad),
+                Err(SettingsError::InvalidDnsServers)
+            );
+        }
+        assert_eq!(settings.dns_servers().len(), 2);
+        settings.set("dns", "").unwrap();
+        assert!(settings.dns_servers().is_empty());
+    }
+
+    #[test]
+    fn net_profile_is_validated_and_persisted() {
+        let mut settings = SystemSettings::new_defaults();
+        assert_eq!(settings.net_profile(), None);
+        assert!(!settings.to_config_text().contains("net.profile="));
+        settings.set("net.profile", "office-2").unwrap();
+        assert_eq!(settings.get("net.profile"), Some("office-2"));
+        assert!(settings.to_config_text().contains("net.profile=office-2\n"));
+        assert_eq!(
+            settings.set_net_profile("Office"),
+            Err(SettingsError::InvalidNetProfile)
+        );
+        assert_eq!(settings.net_profile(), Some("office-2"));
+        settings.set("net.profile", "").unwrap();
+        assert_eq!(settings.net_profile(), None);
+    }
+
+    #[test]
+    fn prompt_validation_rules() {
+        assert!(is_valid_prompt(DEFAULT_PROMPT));
+        assert!(is_valid_prompt("ruzzle>"));
+        assert!(is_valid_prompt("[{user} {cwd}]#"));
+        assert!(!is_valid_prompt(""));
+        assert!(!is_valid_prompt(" {user}$"));
+        assert!(!is_valid_prompt("{user}$ "));
+        assert!(!is_valid_prompt("{uid}$"));
+        assert!(!is_valid_prompt("{user$"));
+        assert!(!is_valid_prompt("user}$"));
+        assert!(!is_valid_prompt("a\tb"));
+        assert!(!is_valid_prompt(&"x".repeat(MAX_PROMPT_LEN + 1)));
+        let mut settings = SystemSettings::new_defaults();
+        assert_eq!(
+            settings.set_prompt("{oops}"),
+            Err(SettingsError::InvalidPrompt)
+        );
+        settings.set_prompt("{hostname}:{cwd}#").unwrap();
+        assert_eq!(settings.prompt(), "{hostname}:{cwd}#");
+    }
+
+    #[test]
+    fn log_levels_are_validated_and_persisted() {
+        let mut settings = SystemSettings::new_defaults();
+        assert_eq!(settings.log_level("net-service"), None);
+        settings.set_log_level("net-service", "error").unwrap();
+        settings.set_log_level("fs-service", "debug").unwrap();
+        settings.set_log_level("net-service", "warn").unwrap();
+        assert_eq!(settings.log_level("net-service"), Some("warn"));
+        assert_eq!(
+            settings.log_levels(),
+            vec![("fs-service", "debug"), ("net-service", "warn")]
+        );
+        assert_eq!(
+            settings.set_log_level("", "info"),
+            Err(SettingsError::InvalidLogTarget)
+        );
+        assert_eq!(
+            settings.set_log_level("bad module", "info"),
+            Err(SettingsError::InvalidLogTarget)
+        );
+        assert_eq!(
+            settings.set_log_level("net-service", "loud"),
+            Err(SettingsError::InvalidLogLevel)
+        );
+        let text = settings.to_config_text();
+        assert!(text.ends_with("log.fs-service=debug\nlog.net-service=warn\n"));
+    }
+
+    #[test]
+    fn watcher_reports_changes_across_copies() {
+        let mut settings = SystemSettings::new_defaults();
+        let mut watcher = SettingsWatcher::new(&settings);
+        assert!(watcher.poll(&settings).is_empty());
+
+        let mut edited = settings.clone();
+        edited.set_hostname("lab1").unwrap();
+        edited.set("net.profile", "eth0").unwrap();
+        settings = edited;
+        assert_eq!(
+            watcher.poll(&settings),
+            vec![
+                SettingsChange {
+                    key: "hostname".to_string(),
+                    old: Some("ruzzle".to_string()),
+                    new: Some("lab1".to_string()),
+                },
+                SettingsChange {
+                    key: "net.profile".to_string(),
+                    old: None,
+                    new: Some("eth0".to_string()),
+                },
+            ]
+        );
+        assert!(watcher.poll(&settings).is_empty());
+
+        settings.set("net.profile", "").unwrap();
+        let changes = watcher.poll(&settings);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            (changes[0].old.as_deref(), changes[0].new.as_deref()),
+            (Some("eth0"), None)
+        );
+
+        let first = SettingsWatcher::default().poll(&settings);
+        assert!(first
+            .iter()
+            .any(|change| change.key == "hostname" && change.old.is_none()));
     }
 }
@@ -0,0 +1,439 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use user_net_manager::{NetProfile, NetProfileError};
+use user_net_service::NetManager;
+use user_puzzle_board::PuzzleBoard;
+use user_settings_service::SystemSettings;
+use user_user_service::{is_valid_user_name, UserManager};
+
+/// Errors raised while reading a manifest, tagged with the 1-based line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestError {
+    InvalidLine(usize),
+    UnknownKey(usize),
+    Duplicate(usize),
+    InvalidValue(usize),
+}
+
+/// Desired system state read from a manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemManifest {
+    pub settings: Vec<(String, String)>,
+    pub users: Vec<(String, bool)>,
+    pub install: Vec<String>,
+    pub start: Vec<String>,
+    pub plugs: Vec<(String, String)>,
+    pub net: Option<NetProfile>,
+}
+
+/// Current system state the manifest is compared against.
+#[derive(Debug, Clone)]
+pub struct SystemState<'a> {
+    pub installed: Vec<String>,
+    pub running: Vec<String>,
+    pub board: &'a PuzzleBoard,
+    pub users: &'a UserManager,
+    pub settings: &'a SystemSettings,
+    pub net: &'a NetManager,
+}
+
+/// One change needed to reach the manifest state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyStep {
+    Set { key: String, value: String },
+    AddUser { name: String, admin: bool },
+    Install(String),
+    Start(String),
+    Plug { slot: String, module: String },
+    Net(NetProfile),
+}
+
+impl ApplyStep {
+    /// Returns a one-line description for plan output.
+    pub fn describe(&self) -> String {
+        match self {
+            ApplyStep::Set { key, value } => format!("set {}={}", key, value),
+            ApplyStep::AddUser { name, admin: true } => format!("add user {} (admin)", name),
+            ApplyStep::AddUser { name, admin: false } => format!("add user {}", name),
+            ApplyStep::Install(module) => format!("install {}", module),
+            ApplyStep::Start(module) => format!("start {}", module),
+            ApplyStep::Plug { slot, module } => format!("plug {} -> {}", slot, module),
+            ApplyStep::Net(NetProfile::Dhcp { iface }) => format!("net dhcp {}", iface),
+            ApplyStep::Net(NetProfile::Static {
+                iface,
+                ipv4,
+                gateway,
+            }) => match gateway {
+                Some(gateway) => format!("net static {} {} via {}", iface, ipv4, gateway),
+                None => format!("net static {} {}", iface, ipv4),
+            },
+        }
+    }
+
+    /// Returns true if the state already reflects this step.
+    pub fn is_satisfied(&self, state: &SystemState) -> bool {
+        match self {
+            ApplyStep::Set { key, value } => state.settings.get(key) == Some(value.as_str()),
+            ApplyStep::AddUser { name, .. } => state.users.has_user(name),
+            ApplyStep::Install(module) => state.installed.contains(module),
+            ApplyStep::Start(module) => state.running.contains(module),
+            ApplyStep::Plug { slot, module } => {
+                state.board.provider_for(slot) == Some(module.as_str())
+            }
+            ApplyStep::Net(profile) => profile.is_applied(state.net),
+        }
+    }
+}
+
+/// Parses a system manifest.
+pub fn parse_system_manifest(input: &str) -> Result<SystemManifest, ManifestError> {
+    let mut manifest = SystemManifest::default();
+    for (index, line) in input.lines().enumerate() {
+        let line_no = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once('=') else {
+            return Err(ManifestError::InvalidLine(line_no));
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if value.is_empty() {
+            return Err(ManifestError::InvalidLine(line_no));
+        }
+        if key == "install" || key == "start" {
+            if value.contains(char::is_whitespace) {
+                return Err(ManifestError::InvalidValue(line_no));
+            }
+            let list = if key == "install" {
+                &mut manifest.install
+            } else {
+                &mut manifest.start
+            };
+            if list.iter().any(|module| module == value) {
+                return Err(ManifestError::Duplicate(line_no));
+            }
+            list.push(value.to_string());
+        } else if key == "net" {
+            if manifest.net.is_some() {
+                return Err(ManifestError::Duplicate(line_no));
+            }
+            manifest.net =
+                Some(parse_net_profile(value).ok_or(ManifestError::InvalidValue(line_no))?);
+        } else if let Some(slot) = key.strip_prefix("plug.") {
+            if slot.is_empty() || value.contains(char::is_whitespace) {
+                return Err(ManifestError::InvalidValue(line_no));
+            }
+            if manifest.plugs.iter().any(|(existing, _)| existing == slot) {
+                return Err(ManifestError::Duplicate(line_no));
+            }
+            manifest.plugs.push((slot.to_string(), value.to_string()));
+        } else if let Some(name) = key.strip_prefix("user.") {
+            let admin = match value {
+                "admin" => true,
+                "user" => false,
+                _ => return Err(ManifestError::InvalidValue(line_no)),
+            };
+            if !is_valid_user_name(name) {
+                return Err(ManifestError::InvalidValue(line_no));
+            }
+            if manifest.users.iter().any(|(existing, _)| existing == name) {
+                return Err(ManifestError::Duplicate(line_no));
+            }
+            manifest.users.push((name.to_string(), admin));
+        } else if let Some(setting) = key.strip_prefix("setting.") {
+            if SystemSettings::new_defaults().set(setting, value).is_err() {
+                return Err(ManifestError::InvalidValue(line_no));
+            }
+            if manifest
+                .settings
+                .iter()
+                .any(|(existing, _)| existing == setting)
+            {
+                return Err(ManifestError::Duplicate(line_no));
+            }
+            manifest
+                .settings
+                .push((setting.to_string(), value.to_string()));
+        } else {
+            return Err(ManifestError::UnknownKey(line_no));
+        }
+    }
+    Ok(manifest)
+}
+
+/// Returns the steps still needed to bring `state` in line with `manifest`.
+///
+/// Steps are ordered settings, users, installs, starts, plugs, then network.
+pub fn plan_apply(manifest: &SystemManifest, state: &SystemState) -> Vec<ApplyStep> {
+    let mut steps = Vec::new();
+    for (key, value) in &manifest.settings {
+        steps.push(ApplyStep::Set {
+            key: key.clone(),
+            value: value.clone(),
+        });
+    }
+    for (name, admin) in &manifest.users {
+        steps.push(ApplyStep::AddUser {
+            name: name.clone(),
+            admin: *admin,
+        });
+    }
+    steps.extend(manifest.install.iter().cloned().map(ApplyStep::Install));
+    steps.extend(manifest.start.iter().cloned().map(ApplyStep::Start));
+    for (slot, module) in &manifest.plugs {
+        steps.push(ApplyStep::Plug {
+            slot: slot.clone(),
+            module: module.clone(),
+        });
+    }
+    steps.extend(manifest.net.iter().cloned().map(ApplyStep::Net));
+    steps.retain(|step| !step.is_satisfied(state));
+    steps
+}
+
+/// Applies a network profile, creating the interface and replacing the default route.
+pub fn apply_net_profile(
+    profile: &NetProfile,
+    net: &mut NetManager,
+) -> Result<(), NetProfileError> {
+    if !net.list().iter().any(|iface| iface.name == profile.iface()) {
+        net.add_interface(profile.iface())
+            .map_err(NetProfileError::Net)?;
+    }
+    if let NetProfile::Static {
+        gateway: Some(_), ..
+    } = profile
+    {
+        let _ = net.remove_route("default");
+    }
+    profile.apply_to(net)
+}
+
+fn parse_net_profile(value: &str) -> Option<NetProfile> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let profile = match parts.as_slice() {
+        ["dhcp", iface] => NetProfile::Dhcp {
+            iface: iface.to_string(),
+        },
+        ["static", iface, ipv4] | ["static", iface, ipv4, _] => NetProfile::Static {
+            iface: iface.to_string(),
+            ipv4: ipv4.to_string(),
+            gateway: parts.get(3).map(|gateway| gateway.to_string()),
+        },
+        _ => return None,
+    };
+    let mut scratch = NetManager::new();
+    apply_net_profile(&profile, &mut scratch).ok()?;
+    Some(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use user_puzzle_board::PuzzleSlot;
+
+    const MANIFEST: &str = "\
+# lab machine
+setting.hostname=lab
+setting.log.net-service=debug
+user.alice=admin
+user.bob=user
+install=net-panel
+start=net-manager
+plug.ruzzle.slot.editor=note-piece
+net=static eth0 10.0.2.15 10.0.2.2
+";
+
+    struct Fixture {
+        board: PuzzleBoard,
+        users: UserManager,
+        settings: SystemSettings,
+        net: NetManager,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let mut users = UserManager::new();
+            users.add_user("alice", true).unwrap();
+            Self {
+                board: PuzzleBoard::new(vec![PuzzleSlot::new("ruzzle.slot.editor@1", false)]),
+                users,
+                settings: SystemSettings::new_defaults(),
+                net: NetManager::new(),
+            }
+        }
+
+        fn state(&self, installed: &[&str], running: &[&str]) -> SystemState<'_> {
+            SystemState {
+                installed: installed.iter().map(|name| name.to_string()).collect(),
+                running: running.iter().map(|name| name.to_string()).collect(),
+                board: &self.board,
+                users: &self.users,
+                settings: &self.settings,
+                net: &self.net,
+            }
+        }
+    }
+
+    #[test]
+    fn parse_manifest_reads_every_section() {
+        let manifest = parse_system_manifest(MANIFEST).expect("manifest should parse");
+        assert_eq!(
+            manifest.settings,
+            vec![
+                ("hostname".to_string(), "lab".to_string()),
+                ("log.net-service".to_string(), "debug".to_string()),
+            ]
+        );
+        assert_eq!(
+            manifest.users,
+            vec![("alice".to_string(), true), ("bob".to_string(), false)]
+        );
+        assert_eq!(manifest.install, vec!["net-panel".to_string()]);
+        assert_eq!(manifest.start, vec!["net-manager".to_string()]);
+        assert_eq!(
+            manifest.plugs,
+            vec![("ruzzle.slot.editor".to_string(), "note-piece".to_string())]
+        );
+        assert_eq!(
+            manifest.net,
+            Some(NetProfile::Static {
+                iface: "eth0".to_string(),
+                ipv4: "10.0.2.15".to_string(),
+                gateway: Some("10.0.2.2".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_system_manifest("net = dhcp eth0").unwrap().net,
+            Some(NetProfile::Dhcp {
+                iface: "eth0".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_manifest_reports_line_errors() {
+        let cases = [
+            ("install", ManifestError::InvalidLine(1)),
+            ("\ninstall=", ManifestError::InvalidLine(2)),
+            ("color=on", ManifestError::UnknownKey(1)),
+            ("install=a b", ManifestError::InvalidValue(1)),
+            ("start=a\nstart=a", ManifestError::Duplicate(2)),
+            ("install=a\ninstall=a", ManifestError::Duplicate(2)),
+            ("net=dhcp eth0\nnet=dhcp eth1", ManifestError::Duplicate(2)),
+            ("net=static eth0 10.0.2", ManifestError::InvalidValue(1)),
+            ("net=static eth0", ManifestError::InvalidValue(1)),
+            ("net=dhcp Bad!", ManifestError::InvalidValue(1)),
+            ("plug.=note-piece", ManifestError::InvalidValue(1)),
+            ("plug.a=b c", ManifestError::InvalidValue(1)),
+            ("plug.a=b\nplug.a=c", ManifestError::Duplicate(2)),
+            ("user.alice=root", ManifestError::InvalidValue(1)),
+            ("user.Alice=user", ManifestError::InvalidValue(1)),
+            ("user.bob=user\nuser.bob=admin", ManifestError::Duplicate(2)),
+            ("setting.hostname=Bad Host", ManifestError::InvalidValue(1)),
+            ("setting.color=on", ManifestError::InvalidValue(1)),
+            (
+                "setting.locale=ko_KR\nsetting.locale=en_US",
+                ManifestError::Duplicate(2),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_system_manifest(input), Err(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn plan_skips_satisfied_steps() {
+        let manifest = parse_system_manifest(MANIFEST).unwrap();
+        let fixture = Fixture::new();
+        let steps = plan_apply(&manifest, &fixture.state(&["net-manager"], &[]));
+        let described: Vec<String> = steps.iter().map(ApplyStep::describe).collect();
+        assert_eq!(
+            described,
+            vec![
+                "set hostname=lab",
+                "set log.net-service=debug",
+                "add user bob",
+                "install net-panel",
+                "start net-manager",
+                "plug ruzzle.slot.editor -> note-piece",
+                "net static eth0 10.0.2.15 via 10.0.2.2",
+            ]
+        );
+
+        let mut fixture = Fixture::new();
+        for (key, value) in &manifest.settings {
+            fixture.settings.set(key, value).unwrap();
+        }
+        fixture.users.add_user("bob", false).unwrap();
+        fixture
+            .board
+            .plug(
+                "ruzzle.slot.editor@1",
+                "note-piece",
+                &["ruzzle.slot.editor@1".to_string()],
+            )
+            .unwrap();
+        apply_net_profile(manifest.net.as_ref().unwrap(), &mut fixture.net).unwrap();
+        let state = fixture.state(&["net-panel", "net-manager"], &["net-manager"]);
+        assert!(plan_apply(&manifest, &state).is_empty());
+    }
+
+    #[test]
+    fn describe_covers_every_step() {
+        assert_eq!(
+            ApplyStep::AddUser {
+                name: "alice".to_string(),
+                admin: true
+            }
+            .describe(),
+            "add user alice (admin)"
+        );
+        assert_eq!(
+            ApplyStep::Net(NetProfile::Dhcp {
+                iface: "eth0".to_string()
+            })
+            .describe(),
+            "net dhcp eth0"
+        );
+        assert_eq!(
+            ApplyStep::Net(NetProfile::Static {
+                iface: "eth0".to_string(),
+                ipv4: "10.0.2.15".to_string(),
+                gateway: None,
+            })
+            .describe(),
+            "net static eth0 10.0.2.15"
+        );
+    }
+
+    #[test]
+    fn apply_net_profile_replaces_default_route() {
+        let mut net = NetManager::new();
+        net.add_interface("eth1").unwrap();
+        net.add_route("default", "eth1", None, 0).unwrap();
+        let profile = NetProfile::Static {
+            iface: "eth0".to_string(),
+            ipv4: "10.0.2.15".to_string(),
+            gateway: Some("10.0.2.2".to_string()),
+        };
+        apply_net_profile(&profile, &mut net).unwrap();
+        assert!(profile.is_applied(&net));
+        assert_eq!(net.list_routes()[0].iface, "eth0");
+        assert_eq!(
+            apply_net_profile(
+                &NetProfile::Dhcp {
+                    iface: "Bad!".to_string()
+                },
+                &mut net
+            ),
+            Err(NetProfileError::Net(
+                user_net_service::NetError::InvalidName
+            ))
+        );
+    }
+}
@@ -13,6 +13,9 @@ use user_user_service::{default_home_dir, is_valid_user_name, UserError, UserMan
 #[cfg(test)]
 use core::cell::Cell;
 
+/// Declarative system manifests diffed and applied by the `apply` command.
+pub mod apply;
+
 const BASE_DIRECTORIES: [&str; 11] = [
     "/system",
     "/etc",
@@ -128,7 +131,6 @@ pub fn run_first_boot(
     }
 
     let home = default_home_dir(&plan.username);
-    ensure_dir(fs, &home, &mut report)?;
     for suffix in ["docs", "bin", ".config", "downloads"].iter() {
         let path = format!("{}/{}", home, suffix);
         ensure_dir(fs, &path, &mut report)?;
@@ -172,14 +174,9 @@ fn ensure_dir(
     path: &str,
     report: &mut BootstrapReport,
 ) -> Result<(), SetupError> {
-    match fs.mkdir(path) {
-        Ok(()) => {
-            report.created_dirs.push(path.to_string());
-            Ok(())
-        }
-        Err(FsError::AlreadyExists) => Ok(()),
-        Err(err) => Err(SetupError::Fs(err)),
-    }
+    let created = fs.mkdir_p(path).map_err(SetupError::Fs)?;
+    report.created_dirs.extend(created);
+    Ok(())
 }
 
 fn write_file(
@@ -306,6 +303,17 @@ mod tests {
         assert!(fs.list_dir("/etc").is_ok());
     }
 
+    #[test]
+    fn run_first_boot_reports_parents_before_children() {
+        let mut fs = FileSystem::new();
+        let mut users = UserManager::new();
+        let mut settings = SystemSettings::new_defaults();
+        let report = run_first_boot(&mut fs, &mut users, &mut settings, &plan()).unwrap();
+        let position = |path: &str| report.created_dirs.iter().position(|dir| dir == path);
+        assert!(position("/home/root").unwrap() < position("/home/root/docs").unwrap());
+        assert_eq!(position("/home"), Some(6));
+    }
+
     #[test]
     fn run_first_boot_rejects_invalid_base_directory() {
         static BAD_BASE: [&str; 1] = ["/bad//path"];
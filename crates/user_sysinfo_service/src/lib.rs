@@ -2,7 +2,11 @@
 
 extern crate alloc;
 
+use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use user_net_service::events::{NetEvent, NetSubscription};
+use user_net_service::NetManager;
 use user_puzzle_board::PuzzleBoard;
 use user_session_service::SessionManager;
 use user_settings_service::SystemSettings;
@@ -20,6 +24,10 @@ pub struct SystemInfo {
     pub cpu_total: usize,
     pub cpu_online: usize,
     pub gpu_devices: usize,
+    pub memory_bytes: u64,
+    pub profile: BootProfile,
+    pub net_links_up: usize,
+    pub net_interfaces: usize,
 }
 
 /// Runtime metrics supplied by the kernel.
@@ -28,6 +36,10 @@ pub struct SystemMetrics {
     pub cpu_total: usize,
     pub cpu_online: usize,
     pub gpu_devices: usize,
+    pub memory_bytes: u64,
+    pub profile: BootProfile,
+    pub net_links_up: usize,
+    pub net_interfaces: usize,
 }
 
 impl Default for SystemMetrics {
@@ -36,10 +48,265 @@ impl Default for SystemMetrics {
             cpu_total: 1,
             cpu_online: 1,
             gpu_devices: 0,
+            memory_bytes: 0,
+            profile: BootProfile::Full,
+            net_links_up: 0,
+            net_interfaces: 0,
         }
     }
 }
 
+/// Boots with less usable memory than this select the low-memory profile.
+pub const LOW_MEMORY_THRESHOLD_BYTES: u64 = 256 * 1024 * 1024;
+
+const LOW_MEMORY_DISABLED: [&str; 4] =
+    ["gpu-service", "ml-runtime", "docker-service", "server-stack"];
+
+/// Target profile selected from the memory detected at boot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BootProfile {
+    #[default]
+    Full,
+    LowMemory,
+}
+
+impl BootProfile {
+    /// Selects a profile for the usable memory size (0 means unknown).
+    pub fn select(memory_bytes: u64) -> Self {
+        if memory_bytes != 0 && memory_bytes < LOW_MEMORY_THRESHOLD_BYTES {
+            BootProfile::LowMemory
+        } else {
+            BootProfile::Full
+        }
+    }
+
+    /// Returns the profile name shown in sysinfo.
+    pub fn name(self) -> &'static str {
+        match self {
+            BootProfile::Full => "full",
+            BootProfile::LowMemory => "low-memory",
+        }
+    }
+
+    /// Returns modules that stay disabled under this profile.
+    pub fn disabled_modules(self) -> &'static [&'static str] {
+        match self {
+            BootProfile::Full => &[],
+            BootProfile::LowMemory => &LOW_MEMORY_DISABLED,
+        }
+    }
+
+    /// Returns true if a module may be installed or started.
+    pub fn allows_module(self, name: &str) -> bool {
+        !self.disabled_modules().contains(&name)
+    }
+
+    /// Returns the capacity used for in-kernel log rings.
+    pub fn log_ring_len(self) -> usize {
+        match self {
+            BootProfile::Full => 64,
+            BootProfile::LowMemory => 16,
+        }
+    }
+
+    /// Returns the byte quota applied to the in-memory filesystem.
+    pub fn fs_quota_bytes(self) -> Option<usize> {
+        match self {
+            BootProfile::Full => None,
+            BootProfile::LowMemory => Some(1024 * 1024),
+        }
+    }
+
+    /// Returns the byte cap for each tmpfs mount (`/tmp`, `/var/tmp`).
+    pub fn tmpfs_bytes(self) -> usize {
+        match self {
+            BootProfile::Full => 4 * 1024 * 1024,
+            BootProfile::LowMemory => 128 * 1024,
+        }
+    }
+}
+
+/// Aggregated latency samples for one metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyStats {
+    pub count: u64,
+    pub total_ns: u64,
+    pub min_ns: u64,
+    pub max_ns: u64,
+}
+
+impl LatencyStats {
+    /// Adds one latency sample.
+    pub fn record(&mut self, elapsed_ns: u64) {
+        if self.count == 0 || elapsed_ns < self.min_ns {
+            self.min_ns = elapsed_ns;
+        }
+        if elapsed_ns > self.max_ns {
+            self.max_ns = elapsed_ns;
+        }
+        self.count += 1;
+        self.total_ns = self.total_ns.saturating_add(elapsed_ns);
+    }
+
+    /// Returns the mean latency, or zero when no samples exist.
+    pub fn mean_ns(&self) -> u64 {
+        self.total_ns.checked_div(self.count).unwrap_or(0)
+    }
+}
+
+/// In-memory registry of named latency metrics.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MetricsRegistry {
+    latencies: BTreeMap<String, LatencyStats>,
+}
+
+impl MetricsRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            latencies: BTreeMap::new(),
+        }
+    }
+
+    /// Records one latency sample under the given name.
+    pub fn record_latency(&mut self, name: &str, elapsed_ns: u64) {
+        self.latencies
+            .entry(name.to_string())
+            .or_default()
+            .record(elapsed_ns);
+    }
+
+    /// Replaces the stats for a metric with pre-aggregated values.
+    pub fn set_latency(&mut self, name: &str, stats: LatencyStats) {
+        self.latencies.insert(name.to_string(), stats);
+    }
+
+    /// Returns latency stats for a metric, if recorded.
+    pub fn latency(&self, name: &str) -> Option<LatencyStats> {
+        self.latencies.get(name).copied()
+    }
+
+    /// Lists latency metrics sorted by name.
+    pub fn latencies(&self) -> Vec<(String, LatencyStats)> {
+        self.latencies
+            .iter()
+            .map(|(name, stats)| (name.clone(), *stats))
+            .collect()
+    }
+
+    /// Drops all recorded samples.
+    pub fn reset(&mut self) {
+        self.latencies.clear();
+    }
+}
+
+/// Interface link states kept current from `NetManager` events.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NetworkStatus {
+    subscription: Option<NetSubscription>,
+    links: BTreeMap<String, bool>,
+}
+
+impl NetworkStatus {
+    /// Snapshots the interfaces of `net` once and subscribes to its events.
+    pub fn watch(net: &mut NetManager) -> Self {
+        let mut status = Self::default();
+        status.resync(net);
+        status
+    }
+
+    /// Drops queued events and snapshots `net` again, e.g. after it was replaced.
+    pub fn resync(&mut self, net: &mut NetManager) {
+        let subscribed = self
+            .subscription
+            .is_some_and(|id| net.poll_events(id).is_ok());
+        if !subscribed {
+            self.subscription = Some(net.subscribe());
+        }
+        self.links = net
+            .list()
+            .into_iter()
+            .map(|iface| (iface.name, iface.up))
+            .collect();
+    }
+
+    /// Applies the events queued since the last update; returns how many there were.
+    pub fn update(&mut self, net: &mut NetManager) -> usize {
+        let Some(id) = self.subscription else {
+            return 0;
+        };
+        let events = net.poll_events(id).unwrap_or_default();
+        for event in &events {
+            match event {
+                NetEvent::InterfaceAdded { iface } => {
+                    self.links.insert(iface.clone(), false);
+                }
+                NetEvent::InterfaceRemoved { iface } => {
+                    self.links.remove(iface);
+                }
+                NetEvent::LinkUp { iface } => {
+                    self.links.insert(iface.clone(), true);
+                }
+                NetEvent::LinkDown { iface } => {
+                    self.links.insert(iface.clone(), false);
+                }
+                _ => {}
+            }
+        }
+        events.len()
+    }
+
+    /// Returns how many known interfaces are up.
+    pub fn links_up(&self) -> usize {
+        self.links.values().filter(|up| **up).count()
+    }
+
+    /// Returns how many interfaces are known.
+    pub fn interfaces(&self) -> usize {
+        self.links.len()
+    }
+}
+
+/// Formats latency metrics into a CLI-friendly table.
+pub fn format_metrics(registry: &MetricsRegistry) -> String {
+    let mut out = String::new();
+    out.push_str("metrics:\n");
+    let rows = registry.latencies();
+    if rows.is_empty() {
+        out.push_str("  <none>\n");
+        return out;
+    }
+    for (name, stats) in rows {
+        out.push_str("  ");
+        out.push_str(&name);
+        out.push_str(" count=");
+        out.push_str(&stats.count.to_string());
+        out.push_str(" avg=");
+        out.push_str(&format_duration_ns(stats.mean_ns()));
+        out.push_str(" min=");
+        out.push_str(&format_duration_ns(stats.min_ns));
+        out.push_str(" max=");
+        out.push_str(&format_duration_ns(stats.max_ns));
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats nanoseconds as milliseconds with microsecond precision.
+pub fn format_duration_ns(elapsed_ns: u64) -> String {
+    let micros = elapsed_ns / 1_000;
+    let mut out = String::new();
+    out.push_str(&(micros / 1_000).to_string());
+    out.push('.');
+    let frac = (micros % 1_000).to_string();
+    for _ in frac.len()..3 {
+        out.push('0');
+    }
+    out.push_str(&frac);
+    out.push_str("ms");
+    out
+}
+
 /// Builds a system info snapshot from active services.
 pub fn build_system_info(
     settings: &SystemSettings,
@@ -48,7 +315,10 @@ pub fn build_system_info(
     metrics: SystemMetrics,
 ) -> SystemInfo {
     let slots = board.list();
-    let filled = slots.iter().filter(|slot| slot.provider.is_some()).count();
+    let filled = slots
+        .iter()
+        .filter(|slot| !slot.providers.is_empty())
+        .count();
     SystemInfo {
         hostname: settings.hostname().to_string(),
         locale: settings.locale().to_string(),
@@ -60,6 +330,10 @@ pub fn build_system_info(
         cpu_total: metrics.cpu_total,
         cpu_online: metrics.cpu_online,
         gpu_devices: metrics.gpu_devices,
+        memory_bytes: metrics.memory_bytes,
+        profile: metrics.profile,
+        net_links_up: metrics.net_links_up,
+        net_interfaces: metrics.net_interfaces,
     }
 }
 
@@ -99,6 +373,30 @@ pub fn format_system_info(info: &SystemInfo) -> String {
     out.push_str("  gpu: ");
     out.push_str(&info.gpu_devices.to_string());
     out.push('\n');
+    out.push_str("  memory: ");
+    if info.memory_bytes == 0 {
+        out.push_str("unknown");
+    } else {
+        out.push_str(&(info.memory_bytes / (1024 * 1024)).to_string());
+        out.push_str(" MiB");
+    }
+    out.push('\n');
+    out.push_str("  profile: ");
+    out.push_str(info.profile.name());
+    let disabled = info.profile.disabled_modules();
+    if !disabled.is_empty() {
+        out.push_str(" (below ");
+        out.push_str(&(LOW_MEMORY_THRESHOLD_BYTES / (1024 * 1024)).to_string());
+        out.push_str(" MiB; disabled: ");
+        out.push_str(&disabled.join(", "));
+        out.push(')');
+    }
+    out.push('\n');
+    out.push_str("  network: ");
+    out.push_str(&info.net_links_up.to_string());
+    out.push('/');
+    out.push_str(&info.net_interfaces.to_string());
+    out.push_str(" links up\n");
     out
 }
 
@@ -141,6 +439,7 @@ mod tests {
                 cpu_total: 4,
                 cpu_online: 2,
                 gpu_devices: 1,
+                ..SystemMetrics::default()
             },
         );
         assert_eq!(info.hostname, "ruzzle");
@@ -164,6 +463,44 @@ mod tests {
         assert!(text.contains("slots: 0/2"));
         assert!(text.contains("cpu: 1/1"));
         assert!(text.contains("gpu: 0"));
+        assert!(text.contains("memory: unknown"));
+        assert!(text.contains("profile: full\n"));
+        assert!(text.contains("network: 0/0 links up"));
+    }
+
+    #[test]
+    fn boot_profile_selects_low_memory_below_threshold() {
+        assert_eq!(BootProfile::select(0), BootProfile::Full);
+        assert_eq!(BootProfile::select(LOW_MEMORY_THRESHOLD_BYTES), BootProfile::Full);
+        assert_eq!(
+            BootProfile::select(LOW_MEMORY_THRESHOLD_BYTES - 1),
+            BootProfile::LowMemory
+        );
+        let low = BootProfile::LowMemory;
+        assert!(!low.allows_module("gpu-service"));
+        assert!(low.allows_module("fs-service"));
+        assert!(BootProfile::Full.allows_module("gpu-service"));
+        assert!(low.log_ring_len() < BootProfile::Full.log_ring_len());
+        assert_eq!(BootProfile::Full.fs_quota_bytes(), None);
+        assert_eq!(low.fs_quota_bytes(), Some(1024 * 1024));
+        assert!(low.tmpfs_bytes() < BootProfile::Full.tmpfs_bytes());
+    }
+
+    #[test]
+    fn format_records_low_memory_decision() {
+        let settings = SystemSettings::new_defaults();
+        let session = SessionManager::new();
+        let metrics = SystemMetrics {
+            memory_bytes: 128 * 1024 * 1024,
+            profile: BootProfile::LowMemory,
+            ..SystemMetrics::default()
+        };
+        let info = build_system_info(&settings, &session, &board(), metrics);
+        let text = format_system_info(&info);
+        assert!(text.contains("memory: 128 MiB"));
+        assert!(text.contains(
+            "profile: low-memory (below 256 MiB; disabled: gpu-service, ml-runtime, docker-service, server-stack)"
+        ));
     }
 
     #[test]
@@ -182,4 +519,86 @@ mod tests {
         let text = format_system_info(&info);
         assert!(text.contains("user: root"));
     }
+
+    #[test]
+    fn metrics_registry_aggregates_samples() {
+        let mut registry = MetricsRegistry::new();
+        assert_eq!(registry.latency("ls"), None);
+        registry.record_latency("ls", 3_000_000);
+        registry.record_latency("ls", 1_000_000);
+        registry.record_latency("ls", 2_000_000);
+        let stats = registry.latency("ls").unwrap();
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.total_ns, 6_000_000);
+        assert_eq!(stats.min_ns, 1_000_000);
+        assert_eq!(stats.max_ns, 3_000_000);
+        assert_eq!(stats.mean_ns(), 2_000_000);
+        assert_eq!(LatencyStats::default().mean_ns(), 0);
+
+        registry.reset();
+        assert!(registry.latencies().is_empty());
+
+        registry.set_latency("http", stats);
+        assert_eq!(registry.latency("http"), Some(stats));
+    }
+
+    #[test]
+    fn format_metrics_lists_latencies() {
+        let mut registry = MetricsRegistry::new();
+        assert!(format_metrics(&registry).contains("<none>"));
+        registry.record_latency("cat", 1_500_000);
+        registry.record_latency("ls", 250_000);
+        let text = format_metrics(&registry);
+        assert!(text.contains("  cat count=1 avg=1.500ms min=1.500ms max=1.500ms"));
+        assert!(text.find("cat").unwrap() < text.find("ls").unwrap());
+    }
+
+    #[test]
+    fn format_duration_pads_fraction() {
+        assert_eq!(format_duration_ns(0), "0.000ms");
+        assert_eq!(format_duration_ns(42_000), "0.042ms");
+        assert_eq!(format_duration_ns(12_345_678), "12.345ms");
+    }
+
+    #[test]
+    fn network_status_follows_link_events() {
+        let mut net = NetManager::new();
+        net.init_defaults();
+        net.add_interface("eth0").unwrap();
+        let mut status = NetworkStatus::watch(&mut net);
+        assert_eq!((status.links_up(), status.interfaces()), (1, 2));
+        assert_eq!(status.update(&mut net), 0);
+
+        net.set_up("eth0", true).unwrap();
+        net.add_interface("eth1").unwrap();
+        net.set_ipv4("eth1", Some("10.0.0.2")).unwrap();
+        assert_eq!(status.update(&mut net), 3);
+        assert_eq!((status.links_up(), status.interfaces()), (2, 3));
+
+        net.set_up("lo", false).unwrap();
+        net.remove_interface("eth1").unwrap();
+        status.update(&mut net);
+        assert_eq!((status.links_up(), status.interfaces()), (1, 2));
+
+        let info = build_system_info(
+            &SystemSettings::new_defaults(),
+            &SessionManager::new(),
+            &board(),
+            SystemMetrics {
+                net_links_up: status.links_up(),
+                net_interfaces: status.interfaces(),
+                ..SystemMetrics::default()
+            },
+        );
+        assert!(format_system_info(&info).contains("network: 1/2 links up"));
+
+        let mut replaced = NetManager::new();
+        replaced.add_interface("eth9").unwrap();
+        status.resync(&mut replaced);
+        assert_eq!((status.links_up(), status.interfaces()), (0, 1));
+        replaced.set_up("eth9", true).unwrap();
+        assert_eq!(status.update(&mut replaced), 1);
+        assert_eq!(status.links_up(), 1);
+        assert_eq!(NetworkStatus::default().update(&mut net), 0);
+    }
 }
@@ -0,0 +1,819 @@
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+
+mod segment;
+
+pub use segment::{
+    Segment, SegmentError, FLAG_ACK, FLAG_FIN, FLAG_PSH, FLAG_RST, FLAG_SYN, HEADER_LEN,
+    IP_PROTOCOL_TCP,
+};
+
+/// Largest payload put in one segment (the IPv4 default MSS).
+pub const DEFAULT_MSS: usize = 536;
+/// Bytes of received data buffered before the advertised window closes.
+pub const RECV_BUFFER: usize = 8 * 1024;
+/// Bytes `send` accepts before the application must wait for acks.
+pub const SEND_BUFFER: usize = 16 * 1024;
+/// First retransmission timeout, in timer ticks (1s at 100 Hz).
+pub const INITIAL_RTO_TICKS: u64 = 100;
+/// Ceiling for the doubled retransmission timeout.
+pub const MAX_RTO_TICKS: u64 = 6_000;
+/// Retransmissions of one segment before the connection times out.
+pub const MAX_RETRIES: u32 = 6;
+/// How long a closed connection lingers in TIME-WAIT, in ticks.
+pub const TIME_WAIT_TICKS: u64 = 200;
+
+/// Connection states from RFC 793.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpState {
+    Closed,
+    Listen,
+    SynSent,
+    SynReceived,
+    Established,
+    FinWait1,
+    FinWait2,
+    CloseWait,
+    Closing,
+    LastAck,
+    TimeWait,
+}
+
+/// Errors returned by socket operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpError {
+    /// Operation is not allowed in the current state.
+    InvalidState,
+    /// Peer reset the connection.
+    Reset,
+    /// A segment went unacknowledged through every retransmission.
+    TimedOut,
+}
+
+/// One TCP connection driven by received segments and timer ticks.
+///
+/// The socket never touches a device: `on_segment` feeds it input and `poll`
+/// returns the segments to transmit, so the same state machine runs over a
+/// NIC driver or an in-memory loop. A listening socket becomes the
+/// connection for the first SYN it accepts.
+#[derive(Debug, Clone)]
+pub struct TcpSocket {
+    state: TcpState,
+    local_port: u16,
+    remote_port: u16,
+    iss: u32,
+    snd_una: u32,
+    snd_nxt: u32,
+    snd_wnd: u32,
+    rcv_nxt: u32,
+    send_queue: VecDeque<u8>,
+    in_flight: VecDeque<Segment>,
+    retransmit_at: Option<u64>,
+    rto: u64,
+    retries: u32,
+    reassembly: BTreeMap<u32, Vec<u8>>,
+    received: VecDeque<u8>,
+    outbox: Vec<Segment>,
+    ack_pending: bool,
+    fin_queued: bool,
+    fin_sent: bool,
+    fin_received: bool,
+    time_wait_until: Option<u64>,
+    error: Option<TcpError>,
+}
+
+impl TcpSocket {
+    fn new(state: TcpState, local_port: u16, remote_port: u16, iss: u32) -> Self {
+        Self {
+            state,
+            local_port,
+            remote_port,
+            iss,
+            snd_una: iss,
+            snd_nxt: iss,
+            snd_wnd: 0,
+            rcv_nxt: 0,
+            send_queue: VecDeque::new(),
+            in_flight: VecDeque::new(),
+            retransmit_at: None,
+            rto: INITIAL_RTO_TICKS,
+            retries: 0,
+            reassembly: BTreeMap::new(),
+            received: VecDeque::new(),
+            outbox: Vec::new(),
+            ack_pending: false,
+            fin_queued: false,
+            fin_sent: false,
+            fin_received: false,
+            time_wait_until: None,
+            error: None,
+        }
+    }
+
+    /// Creates a socket waiting for a SYN on `local_port`.
+    pub fn listen(local_port: u16, iss: u32) -> Self {
+        Self::new(TcpState::Listen, local_port, 0, iss)
+    }
+
+    /// Creates a socket and queues a SYN to `remote_port`.
+    pub fn connect(local_port: u16, remote_port: u16, iss: u32, now_ticks: u64) -> Self {
+        let mut socket = Self::new(TcpState::SynSent, local_port, remote_port, iss);
+        socket.send_syn(FLAG_SYN, now_ticks);
+        socket
+    }
+
+    /// Returns the connection state.
+    pub fn state(&self) -> TcpState {
+        self.state
+    }
+
+    /// Returns the local port.
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+
+    /// Returns the peer's port, or 0 while listening.
+    pub fn remote_port(&self) -> u16 {
+        self.remote_port
+    }
+
+    /// Returns why the connection closed abnormally, if it did.
+    pub fn error(&self) -> Option<TcpError> {
+        self.error
+    }
+
+    /// Returns true once the peer has sent FIN and all its data was delivered.
+    pub fn peer_closed(&self) -> bool {
+        self.fin_received
+    }
+
+    /// Returns the number of sent bytes (plus SYN/FIN) awaiting an ack.
+    pub fn unacked(&self) -> u32 {
+        self.snd_nxt.wrapping_sub(self.snd_una)
+    }
+
+    /// Returns the current retransmission timeout in ticks.
+    pub fn rto(&self) -> u64 {
+        self.rto
+    }
+
+    /// Queues data for sending and returns how many bytes were accepted.
+    pub fn send(&mut self, data: &[u8]) -> Result<usize, TcpError> {
+        let open = matches!(
+            self.state,
+            TcpState::SynSent | TcpState::SynReceived | TcpState::Established | TcpState::CloseWait
+        );
+        if !open || self.fin_queued {
+            return Err(self.error.unwrap_or(TcpError::InvalidState));
+        }
+        let accepted = data.len().min(SEND_BUFFER - self.send_queue.len());
+        self.send_queue.extend(&data[..accepted]);
+        Ok(accepted)
+    }
+
+    /// Takes the in-order data received so far.
+    pub fn recv(&mut self) -> Vec<u8> {
+        let before = self.recv_window();
+        let data: Vec<u8> = self.received.drain(..).collect();
+        if before < DEFAULT_MSS as u32 && self.recv_window() >= DEFAULT_MSS as u32 {
+            self.ack_pending = self.is_synchronized();
+        }
+        data
+    }
+
+    /// Starts an orderly close; queued data is sent before the FIN.
+    pub fn close(&mut self) {
+        match self.state {
+            TcpState::Listen | TcpState::SynSent => self.reset_state(TcpState::Closed),
+            TcpState::SynReceived | TcpState::Established => {
+                self.fin_queued = true;
+                self.state = TcpState::FinWait1;
+            }
+            TcpState::CloseWait => {
+                self.fin_queued = true;
+                self.state = TcpState::LastAck;
+            }
+            _ => {}
+        }
+    }
+
+    /// Drops the connection at once, telling a synchronized peer with RST.
+    pub fn abort(&mut self) {
+        if self.is_synchronized() {
+            let reset = self.segment(FLAG_RST, self.snd_nxt, Vec::new());
+            self.outbox.push(reset);
+        }
+        self.reset_state(TcpState::Closed);
+    }
+
+    /// Processes one segment addressed to this socket's local port.
+    pub fn on_segment(&mut self, seg: &Segment, now_ticks: u64) {
+        if seg.dst_port != self.local_port {
+            return;
+        }
+        match self.state {
+            TcpState::Closed => {
+                self.reply_reset(seg);
+                return;
+            }
+            TcpState::Listen => {
+                self.on_listen(seg, now_ticks);
+                return;
+            }
+            TcpState::SynSent => {
+                self.on_syn_sent(seg, now_ticks);
+                return;
+            }
+            _ => {}
+        }
+        if seg.src_port != self.remote_port {
+            return;
+        }
+        if !self.is_acceptable(seg) {
+            if !seg.has(FLAG_RST) {
+                self.ack_pending = true;
+            }
+            return;
+        }
+        if seg.has(FLAG_RST) {
+            self.fail(TcpError::Reset);
+            return;
+        }
+        if seg.has(FLAG_SYN) {
+            self.abort();
+            self.error = Some(TcpError::Reset);
+            return;
+        }
+        if !seg.has(FLAG_ACK) {
+            return;
+        }
+        if self.state == TcpState::SynReceived {
+            if !in_range(self.snd_una, seg.ack, self.snd_nxt) {
+                self.reply_reset(seg);
+                return;
+            }
+            self.state = TcpState::Established;
+        }
+        if seq_lt(self.snd_nxt, seg.ack) {
+            self.ack_pending = true;
+            return;
+        }
+        self.acknowledge(seg, now_ticks);
+        let fin_acked = self.fin_sent && self.snd_una == self.snd_nxt;
+        match self.state {
+            TcpState::FinWait1 if fin_acked => self.state = TcpState::FinWait2,
+            TcpState::Closing if fin_acked => self.enter_time_wait(now_ticks),
+            TcpState::LastAck if fin_acked => {
+                self.reset_state(TcpState::Closed);
+                return;
+            }
+            _ => {}
+        }
+        if !seg.payload.is_empty() {
+            self.accept_data(seg);
+        }
+        if seg.has(FLAG_FIN) && seg.seq.wrapping_add(seg.payload.len() as u32) == self.rcv_nxt {
+            self.accept_fin(now_ticks);
+        }
+    }
+
+    /// Runs timers and returns the segments to transmit now.
+    pub fn poll(&mut self, now_ticks: u64) -> Vec<Segment> {
+        if self.state == TcpState::TimeWait
+            && self.time_wait_until.is_some_and(|until| now_ticks >= until)
+        {
+            self.reset_state(TcpState::Closed);
+        }
+        if self.retransmit_at.is_some_and(|at| now_ticks >= at) {
+            self.retransmit(now_ticks);
+        }
+        self.send_pending(now_ticks);
+        let carries_ack = self
+            .outbox
+            .iter()
+            .any(|seg| seg.has(FLAG_ACK) && !seg.has(FLAG_RST));
+        if self.ack_pending && !carries_ack && self.is_synchronized() {
+            let ack = self.segment(FLAG_ACK, self.snd_nxt, Vec::new());
+            self.outbox.push(ack);
+        }
+        self.ack_pending = false;
+        core::mem::take(&mut self.outbox)
+    }
+
+    fn on_listen(&mut self, seg: &Segment, now_ticks: u64) {
+        if seg.has(FLAG_RST) {
+            return;
+        }
+        if seg.has(FLAG_ACK) {
+            self.reply_reset(seg);
+            return;
+        }
+        if seg.has(FLAG_SYN) {
+            self.remote_port = seg.src_port;
+            self.rcv_nxt = seg.seq.wrapping_add(1);
+            self.snd_wnd = u32::from(seg.window);
+            self.state = TcpState::SynReceived;
+            self.send_syn(FLAG_SYN | FLAG_ACK, now_ticks);
+        }
+    }
+
+    fn on_syn_sent(&mut self, seg: &Segment, now_ticks: u64) {
+        if seg.src_port != self.remote_port {
+            return;
+        }
+        if seg.has(FLAG_ACK) && seg.ack != self.snd_nxt {
+            self.reply_reset(seg);
+            return;
+        }
+        if seg.has(FLAG_RST) {
+            if seg.has(FLAG_ACK) {
+                self.fail(TcpError::Reset);
+            }
+            return;
+        }
+        if !seg.has(FLAG_SYN) {
+            return;
+        }
+        self.rcv_nxt = seg.seq.wrapping_add(1);
+        self.ack_pending = true;
+        if seg.has(FLAG_ACK) {
+            self.acknowledge(seg, now_ticks);
+            self.state = TcpState::Established;
+        } else {
+            // Simultaneous open: answer the peer's SYN with our own again.
+            self.state = TcpState::SynReceived;
+            self.in_flight.clear();
+            self.snd_nxt = self.iss;
+            self.send_syn(FLAG_SYN | FLAG_ACK, now_ticks);
+            self.ack_pending = false;
+        }
+    }
+
+    fn send_syn(&mut self, flags: u8, now_ticks: u64) {
+        let syn = self.segment(flags, self.iss, Vec::new());
+        self.snd_nxt = self.iss.wrapping_add(1);
+        self.transmit(syn, now_ticks);
+    }
+
+    fn send_pending(&mut self, now_ticks: u64) {
+        let sending = matches!(
+            self.state,
+            TcpState::Established
+                | TcpState::CloseWait
+                | TcpState::FinWait1
+                | TcpState::Closing
+                | TcpState::LastAck
+        );
+        if !sending || self.fin_sent {
+            return;
+        }
+        while !self.send_queue.is_empty() {
+            let room = self.snd_wnd.saturating_sub(self.unacked()) as usize;
+            let mut len = DEFAULT_MSS.min(room).min(self.send_queue.len());
+            if len == 0 && self.snd_wnd == 0 && self.in_flight.is_empty() {
+                // Zero-window probe; it shares the retransmission timer.
+                len = 1;
+            }
+            if len == 0 {
+                break;
+            }
+            let payload: Vec<u8> = self.send_queue.drain(..len).collect();
+            let seg = self.segment(FLAG_ACK | FLAG_PSH, self.snd_nxt, payload);
+            self.snd_nxt = self.snd_nxt.wrapping_add(len as u32);
+            self.transmit(seg, now_ticks);
+            if self.snd_wnd == 0 {
+                break;
+            }
+        }
+        if self.fin_queued && self.send_queue.is_empty() {
+            let fin = self.segment(FLAG_FIN | FLAG_ACK, self.snd_nxt, Vec::new());
+            self.snd_nxt = self.snd_nxt.wrapping_add(1);
+            self.fin_sent = true;
+            self.transmit(fin, now_ticks);
+        }
+    }
+
+    fn retransmit(&mut self, now_ticks: u64) {
+        if self.retries >= MAX_RETRIES {
+            self.abort();
+            self.error = Some(TcpError::TimedOut);
+            return;
+        }
+        self.retries += 1;
+        self.rto = (self.rto * 2).min(MAX_RTO_TICKS);
+        self.retransmit_at = Some(now_ticks + self.rto);
+        if let Some(oldest) = self.in_flight.front() {
+            let mut seg = oldest.clone();
+            if seg.has(FLAG_ACK) {
+                seg.ack = self.rcv_nxt;
+            }
+            seg.window = self.recv_window().min(u32::from(u16::MAX)) as u16;
+            self.outbox.push(seg);
+        }
+    }
+
+    fn transmit(&mut self, seg: Segment, now_ticks: u64) {
+        if seg.seq_len() > 0 {
+            self.in_flight.push_back(seg.clone());
+            if self.retransmit_at.is_none() {
+                self.retransmit_at = Some(now_ticks + self.rto);
+            }
+        }
+        self.outbox.push(seg);
+    }
+
+    fn acknowledge(&mut self, seg: &Segment, now_ticks: u64) {
+        if seq_lt(self.snd_una, seg.ack) && !seq_lt(self.snd_nxt, seg.ack) {
+            self.snd_una = seg.ack;
+            while self
+                .in_flight
+                .front()
+                .is_some_and(|sent| !seq_lt(seg.ack, sent.seq.wrapping_add(sent.seq_len())))
+            {
+                self.in_flight.pop_front();
+            }
+            self.retries = 0;
+            self.rto = INITIAL_RTO_TICKS;
+            self.retransmit_at = (!self.in_flight.is_empty()).then_some(now_ticks + self.rto);
+        }
+        if !seq_lt(seg.ack, self.snd_una) {
+            self.snd_wnd = u32::from(seg.window);
+        }
+    }
+
+    fn accept_data(&mut self, seg: &Segment) {
+        self.ack_pending = true;
+        if !matches!(
+            self.state,
+            TcpState::Established | TcpState::FinWait1 | TcpState::FinWait2
+        ) {
+            return;
+        }
+        let ahead = seg.seq.wrapping_sub(self.rcv_nxt);
+        let mut payload = seg.payload.as_slice();
+        if !seq_lt(seg.seq, self.rcv_nxt) {
+            let room = self.recv_window().saturating_sub(ahead) as usize;
+            payload = &payload[..payload.len().min(room)];
+        }
+        if payload.is_empty() {
+            return;
+        }
+        let keep = self
+            .reassembly
+            .get(&seg.seq)
+            .is_none_or(|held| held.len() < payload.len());
+        if keep {
+            self.reassembly.insert(seg.seq, payload.to_vec());
+        }
+        loop {
+            let next = self
+                .reassembly
+                .keys()
+                .copied()
+                .find(|seq| !seq_lt(self.rcv_nxt, *seq));
+            let Some(seq) = next else {
+                break;
+            };
+            let data = self.reassembly.remove(&seq).unwrap_or_default();
+            let skip = self.rcv_nxt.wrapping_sub(seq) as usize;
+            if skip < data.len() {
+                self.received.extend(&data[skip..]);
+                self.rcv_nxt = self.rcv_nxt.wrapping_add((data.len() - skip) as u32);
+            }
+        }
+    }
+
+    fn accept_fin(&mut self, now_ticks: u64) {
+        self.rcv_nxt = self.rcv_nxt.wrapping_add(1);
+        self.fin_received = true;
+        self.ack_pending = true;
+        let fin_acked = self.fin_sent && self.snd_una == self.snd_nxt;
+        match self.state {
+            TcpState::SynReceived | TcpState::Established => self.state = TcpState::CloseWait,
+            TcpState::FinWait1 if fin_acked => self.enter_time_wait(now_ticks),
+            TcpState::FinWait1 => self.state = TcpState::Closing,
+            TcpState::FinWait2 | TcpState::TimeWait => self.enter_time_wait(now_ticks),
+            _ => {}
+        }
+    }
+
+    fn enter_time_wait(&mut self, now_ticks: u64) {
+        self.state = TcpState::TimeWait;
+        self.time_wait_until = Some(now_ticks + TIME_WAIT_TICKS);
+        self.retransmit_at = None;
+    }
+
+    fn is_acceptable(&self, seg: &Segment) -> bool {
+        let window = self.recv_window().max(1);
+        let in_window = |seq: u32| seq.wrapping_sub(self.rcv_nxt) < window;
+        match seg.seq_len() {
+            0 => seg.seq == self.rcv_nxt || in_window(seg.seq),
+            len => in_window(seg.seq) || in_window(seg.seq.wrapping_add(len - 1)),
+        }
+    }
+
+    fn is_synchronized(&self) -> bool {
+        !matches!(
+            self.state,
+            TcpState::Closed | TcpState::Listen | TcpState::SynSent
+        )
+    }
+
+    fn recv_window(&self) -> u32 {
+        let held: usize = self.reassembly.values().map(Vec::len).sum();
+        RECV_BUFFER.saturating_sub(self.received.len() + held) as u32
+    }
+
+    fn segment(&self, flags: u8, seq: u32, payload: Vec<u8>) -> Segment {
+        Segment {
+            src_port: self.local_port,
+            dst_port: self.remote_port,
+            seq,
+            ack: if flags & FLAG_ACK != 0 {
+                self.rcv_nxt
+            } else {
+                0
+            },
+            flags,
+            window: self.recv_window().min(u32::from(u16::MAX)) as u16,
+            payload,
+        }
+    }
+
+    /// Answers an unexpected segment with RST, as RFC 793 requires.
+    fn reply_reset(&mut self, seg: &Segment) {
+        if seg.has(FLAG_RST) {
+            return;
+        }
+        let (flags, seq, ack) = if seg.has(FLAG_ACK) {
+            (FLAG_RST, seg.ack, 0)
+        } else {
+            (FLAG_RST | FLAG_ACK, 0, seg.seq.wrapping_add(seg.seq_len()))
+        };
+        self.outbox.push(Segment {
+            src_port: self.local_port,
+            dst_port: seg.src_port,
+            seq,
+            ack,
+            flags,
+            window: 0,
+            payload: Vec::new(),
+        });
+    }
+
+    fn fail(&mut self, error: TcpError) {
+        self.reset_state(TcpState::Closed);
+        self.error = Some(error);
+    }
+
+    fn reset_state(&mut self, state: TcpState) {
+        self.state = state;
+        self.send_queue.clear();
+        self.in_flight.clear();
+        self.retransmit_at = None;
+        self.time_wait_until = None;
+    }
+}
+
+/// Returns true if `a` comes before `b` in wrapping sequence space.
+fn seq_lt(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) < 0
+}
+
+/// Returns true if `low < value <= high` in wrapping sequence space.
+fn in_range(low: u32, value: u32, high: u32) -> bool {
+    seq_lt(low, value) && !seq_lt(high, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT_PORT: u16 = 49152;
+    const SERVER_PORT: u16 = 80;
+
+    /// Shuttles segments between two sockets until both go quiet.
+    fn pump(a: &mut TcpSocket, b: &mut TcpSocket, now: u64) {
+        for _ in 0..64 {
+            let from_a = a.poll(now);
+            let from_b = b.poll(now);
+            if from_a.is_empty() && from_b.is_empty() {
+                return;
+            }
+            for seg in &from_a {
+                b.on_segment(seg, now);
+            }
+            for seg in &from_b {
+                a.on_segment(seg, now);
+            }
+        }
+        panic!("sockets never went quiet");
+    }
+
+    fn connected() -> (TcpSocket, TcpSocket) {
+        let mut server = TcpSocket::listen(SERVER_PORT, 7_000);
+        let mut client = TcpSocket::connect(CLIENT_PORT, SERVER_PORT, u32::MAX - 2, 0);
+        pump(&mut client, &mut server, 0);
+        assert_eq!(client.state(), TcpState::Established);
+        assert_eq!(server.state(), TcpState::Established);
+        (client, server)
+    }
+
+    #[test]
+    fn handshake_then_data_flows_both_ways() {
+        let (mut client, mut server) = connected();
+        assert_eq!(server.remote_port(), CLIENT_PORT);
+        assert_eq!(client.local_port(), CLIENT_PORT);
+
+        assert_eq!(client.send(b"GET / HTTP/1.1\r\n\r\n"), Ok(18));
+        pump(&mut client, &mut server, 1);
+        assert_eq!(server.recv(), b"GET / HTTP/1.1\r\n\r\n");
+        assert_eq!(client.unacked(), 0);
+
+        let body = vec![b'x'; 3 * DEFAULT_MSS + 10];
+        server.send(&body).unwrap();
+        let first = server.poll(2);
+        assert_eq!(first.len(), 4);
+        assert!(first.iter().all(|seg| seg.payload.len() <= DEFAULT_MSS));
+        for seg in &first {
+            client.on_segment(seg, 2);
+        }
+        pump(&mut client, &mut server, 2);
+        assert_eq!(client.recv(), body);
+        assert_eq!(server.unacked(), 0);
+    }
+
+    #[test]
+    fn orderly_close_passes_through_time_wait() {
+        let (mut client, mut server) = connected();
+        client.send(b"bye").unwrap();
+        client.close();
+        assert_eq!(client.state(), TcpState::FinWait1);
+        assert_eq!(client.send(b"more"), Err(TcpError::InvalidState));
+        pump(&mut client, &mut server, 5);
+        assert_eq!(client.state(), TcpState::FinWait2);
+        assert_eq!(server.state(), TcpState::CloseWait);
+        assert!(server.peer_closed());
+        assert_eq!(server.recv(), b"bye");
+
+        server.send(b"ok").unwrap();
+        server.close();
+        assert_eq!(server.state(), TcpState::LastAck);
+        pump(&mut client, &mut server, 6);
+        assert_eq!(server.state(), TcpState::Closed);
+        assert_eq!(server.error(), None);
+        assert_eq!(client.state(), TcpState::TimeWait);
+        assert_eq!(client.recv(), b"ok");
+
+        client.poll(6 + TIME_WAIT_TICKS - 1);
+        assert_eq!(client.state(), TcpState::TimeWait);
+        client.poll(6 + TIME_WAIT_TICKS);
+        assert_eq!(client.state(), TcpState::Closed);
+    }
+
+    #[test]
+    fn simultaneous_close_goes_through_closing() {
+        let (mut client, mut server) = connected();
+        client.close();
+        server.close();
+        let from_client = client.poll(1);
+        let from_server = server.poll(1);
+        for seg in &from_client {
+            server.on_segment(seg, 1);
+        }
+        for seg in &from_server {
+            client.on_segment(seg, 1);
+        }
+        assert_eq!(client.state(), TcpState::Closing);
+        assert_eq!(server.state(), TcpState::Closing);
+        pump(&mut client, &mut server, 2);
+        assert_eq!(client.state(), TcpState::TimeWait);
+        assert_eq!(server.state(), TcpState::TimeWait);
+    }
+
+    #[test]
+    fn lost_segment_is_retransmitted_with_backoff() {
+        let (mut client, mut server) = connected();
+        client.send(b"hello").unwrap();
+        let lost = client.poll(10);
+        assert_eq!(lost.len(), 1);
+        assert!(client.poll(10 + INITIAL_RTO_TICKS - 1).is_empty());
+
+        let resent = client.poll(10 + INITIAL_RTO_TICKS);
+        assert_eq!(resent, lost);
+        assert_eq!(client.rto(), 2 * INITIAL_RTO_TICKS);
+        let now = 10 + INITIAL_RTO_TICKS;
+        for seg in &resent {
+            server.on_segment(seg, now);
+        }
+        server.on_segment(&lost[0], now);
+        pump(&mut client, &mut server, now);
+        assert_eq!(server.recv(), b"hello");
+        assert_eq!(client.unacked(), 0);
+        assert_eq!(client.rto(), INITIAL_RTO_TICKS);
+        assert!(client.poll(now + MAX_RTO_TICKS).is_empty());
+    }
+
+    #[test]
+    fn out_of_order_segments_are_delivered_in_order() {
+        let (mut client, mut server) = connected();
+        client.send(&vec![b'a'; DEFAULT_MSS]).unwrap();
+        client.send(&vec![b'b'; DEFAULT_MSS]).unwrap();
+        client.send(b"c").unwrap();
+        let segments = client.poll(1);
+        assert_eq!(segments.len(), 3);
+
+        server.on_segment(&segments[2], 1);
+        server.on_segment(&segments[1], 1);
+        assert!(server.recv().is_empty());
+        server.on_segment(&segments[0], 1);
+        server.on_segment(&segments[1], 1);
+        let mut expected = vec![b'a'; DEFAULT_MSS];
+        expected.extend(vec![b'b'; DEFAULT_MSS]);
+        expected.push(b'c');
+        assert_eq!(server.recv(), expected);
+        pump(&mut client, &mut server, 1);
+        assert_eq!(client.unacked(), 0);
+    }
+
+    #[test]
+    fn unanswered_syn_times_out() {
+        let mut client = TcpSocket::connect(CLIENT_PORT, SERVER_PORT, 1, 0);
+        assert_eq!(client.poll(0).len(), 1);
+        let mut now = 0;
+        for _ in 0..MAX_RETRIES {
+            now += client.rto();
+            let resent = client.poll(now);
+            assert_eq!(resent.len(), 1);
+            assert!(resent[0].has(FLAG_SYN));
+        }
+        now += client.rto();
+        assert!(client.poll(now).is_empty());
+        assert_eq!(client.state(), TcpState::Closed);
+        assert_eq!(client.error(), Some(TcpError::TimedOut));
+        assert_eq!(client.send(b"x"), Err(TcpError::TimedOut));
+    }
+
+    #[test]
+    fn resets_are_sent_and_honoured() {
+        let mut closed = TcpSocket::listen(SERVER_PORT, 1);
+        closed.close();
+        let probe = TcpSocket::connect(CLIENT_PORT, SERVER_PORT, 100, 0).poll(0);
+        closed.on_segment(&probe[0], 0);
+        let reply = closed.poll(0);
+        assert_eq!(reply.len(), 1);
+        assert!(reply[0].has(FLAG_RST | FLAG_ACK));
+        assert_eq!(reply[0].ack, 101);
+
+        let mut client = TcpSocket::connect(CLIENT_PORT, SERVER_PORT, 100, 0);
+        client.poll(0);
+        client.on_segment(&reply[0], 0);
+        assert_eq!(client.state(), TcpState::Closed);
+        assert_eq!(client.error(), Some(TcpError::Reset));
+
+        let (mut client, mut server) = connected();
+        server.abort();
+        let reset = server.poll(1);
+        assert!(reset[0].has(FLAG_RST));
+        client.on_segment(&reset[0], 1);
+        assert_eq!(client.error(), Some(TcpError::Reset));
+
+        let mut stale = reset[0].clone();
+        stale.flags = FLAG_ACK;
+        let mut listener = TcpSocket::listen(SERVER_PORT, 1);
+        stale.dst_port = SERVER_PORT;
+        listener.on_segment(&stale, 1);
+        assert!(listener.poll(1)[0].has(FLAG_RST));
+        assert_eq!(listener.state(), TcpState::Listen);
+    }
+
+    #[test]
+    fn receive_window_closes_and_reopens() {
+        let (mut client, mut server) = connected();
+        let data = vec![7u8; RECV_BUFFER + 100];
+        assert_eq!(client.send(&data), Ok(data.len()));
+        pump(&mut client, &mut server, 1);
+        // The window is shut, so a one-byte probe waits for its timer.
+        assert_eq!(client.unacked(), 1);
+        let mut got = server.recv();
+        assert_eq!(got.len(), RECV_BUFFER);
+
+        pump(&mut client, &mut server, 2);
+        pump(&mut client, &mut server, 1 + INITIAL_RTO_TICKS);
+        got.extend(server.recv());
+        assert_eq!(got, data);
+        assert_eq!(client.unacked(), 0);
+    }
+
+    #[test]
+    fn sequence_comparison_wraps() {
+        assert!(seq_lt(u32::MAX, 0));
+        assert!(!seq_lt(0, u32::MAX));
+        assert!(in_range(u32::MAX - 1, 1, 2));
+        assert!(!in_range(1, 1, 2));
+    }
+}
@@ -0,0 +1,184 @@
+use alloc::vec::Vec;
+
+/// Bytes in a TCP header without options.
+pub const HEADER_LEN: usize = 20;
+/// IP protocol number for TCP, used in the checksum pseudo-header.
+pub const IP_PROTOCOL_TCP: u8 = 6;
+
+/// Segment flag: no more data from sender.
+pub const FLAG_FIN: u8 = 0x01;
+/// Segment flag: synchronize sequence numbers.
+pub const FLAG_SYN: u8 = 0x02;
+/// Segment flag: reset the connection.
+pub const FLAG_RST: u8 = 0x04;
+/// Segment flag: push buffered data to the application.
+pub const FLAG_PSH: u8 = 0x08;
+/// Segment flag: the acknowledgment field is significant.
+pub const FLAG_ACK: u8 = 0x10;
+
+/// Errors returned while decoding a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentError {
+    /// Fewer bytes than the header declares.
+    TooShort,
+    /// Data offset is below the minimum header size.
+    BadOffset,
+    BadChecksum,
+}
+
+/// One TCP segment; options are dropped on decode and never sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub seq: u32,
+    pub ack: u32,
+    pub flags: u8,
+    pub window: u16,
+    pub payload: Vec<u8>,
+}
+
+impl Segment {
+    /// Returns true if every bit in `flag` is set.
+    pub fn has(&self, flag: u8) -> bool {
+        self.flags & flag == flag
+    }
+
+    /// Returns the sequence space the segment occupies (SYN and FIN count as one).
+    pub fn seq_len(&self) -> u32 {
+        self.payload.len() as u32 + u32::from(self.has(FLAG_SYN)) + u32::from(self.has(FLAG_FIN))
+    }
+
+    /// Encodes the segment with a checksum over the IPv4 pseudo-header.
+    pub fn encode(&self, src_ip: [u8; 4], dst_ip: [u8; 4]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.payload.len());
+        bytes.extend_from_slice(&self.src_port.to_be_bytes());
+        bytes.extend_from_slice(&self.dst_port.to_be_bytes());
+        bytes.extend_from_slice(&self.seq.to_be_bytes());
+        bytes.extend_from_slice(&self.ack.to_be_bytes());
+        bytes.push(((HEADER_LEN / 4) as u8) << 4);
+        bytes.push(self.flags);
+        bytes.extend_from_slice(&self.window.to_be_bytes());
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+        bytes.extend_from_slice(&self.payload);
+        let sum = checksum(&bytes, src_ip, dst_ip);
+        bytes[16..18].copy_from_slice(&sum.to_be_bytes());
+        bytes
+    }
+
+    /// Decodes a segment, verifying its checksum against the pseudo-header.
+    pub fn decode(bytes: &[u8], src_ip: [u8; 4], dst_ip: [u8; 4]) -> Result<Self, SegmentError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(SegmentError::TooShort);
+        }
+        let offset = usize::from(bytes[12] >> 4) * 4;
+        if offset < HEADER_LEN {
+            return Err(SegmentError::BadOffset);
+        }
+        if bytes.len() < offset {
+            return Err(SegmentError::TooShort);
+        }
+        if checksum(bytes, src_ip, dst_ip) != 0 {
+            return Err(SegmentError::BadChecksum);
+        }
+        let word = |at: usize| u16::from_be_bytes([bytes[at], bytes[at + 1]]);
+        let long = |at: usize| {
+            u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        Ok(Self {
+            src_port: word(0),
+            dst_port: word(2),
+            seq: long(4),
+            ack: long(8),
+            flags: bytes[13] & 0x3f,
+            window: word(14),
+            payload: bytes[offset..].to_vec(),
+        })
+    }
+}
+
+/// Computes the TCP checksum of `bytes` with the IPv4 pseudo-header.
+fn checksum(bytes: &[u8], src_ip: [u8; 4], dst_ip: [u8; 4]) -> u16 {
+    let mut sum = 0u32;
+    let mut add = |chunk: &[u8]| {
+        for pair in chunk.chunks(2) {
+            let high = u32::from(pair[0]) << 8;
+            sum += high | pair.get(1).map_or(0, |low| u32::from(*low));
+        }
+    };
+    add(&src_ip);
+    add(&dst_ip);
+    add(&[0, IP_PROTOCOL_TCP]);
+    add(&(bytes.len() as u16).to_be_bytes());
+    add(bytes);
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: [u8; 4] = [10, 0, 0, 2];
+    const SERVER: [u8; 4] = [10, 0, 0, 1];
+
+    fn segment(payload: &[u8]) -> Segment {
+        Segment {
+            src_port: 49152,
+            dst_port: 80,
+            seq: 0xfffffff0,
+            ack: 7,
+            flags: FLAG_ACK | FLAG_PSH,
+            window: 4096,
+            payload: payload.to_vec(),
+        }
+    }
+
+    #[test]
+    fn segments_round_trip_with_checksum() {
+        for payload in [&b""[..], b"GET / HTTP/1.1\r\n", b"odd"] {
+            let segment = segment(payload);
+            let bytes = segment.encode(CLIENT, SERVER);
+            assert_eq!(bytes.len(), HEADER_LEN + payload.len());
+            assert_eq!(Segment::decode(&bytes, CLIENT, SERVER), Ok(segment));
+        }
+        let syn = Segment {
+            flags: FLAG_SYN | FLAG_FIN,
+            ..segment(b"ab")
+        };
+        assert_eq!(syn.seq_len(), 4);
+        assert!(syn.has(FLAG_SYN));
+        assert!(!syn.has(FLAG_SYN | FLAG_ACK));
+    }
+
+    #[test]
+    fn decode_rejects_damaged_segments() {
+        let mut bytes = segment(b"data").encode(CLIENT, SERVER);
+        assert_eq!(
+            Segment::decode(&bytes, [10, 0, 0, 3], SERVER),
+            Err(SegmentError::BadChecksum)
+        );
+        assert_eq!(
+            Segment::decode(&bytes[..19], CLIENT, SERVER),
+            Err(SegmentError::TooShort)
+        );
+        bytes[21] ^= 0x20;
+        assert_eq!(
+            Segment::decode(&bytes, CLIENT, SERVER),
+            Err(SegmentError::BadChecksum)
+        );
+        bytes[21] ^= 0x20;
+        bytes[12] = 4 << 4;
+        assert_eq!(
+            Segment::decode(&bytes, CLIENT, SERVER),
+            Err(SegmentError::BadOffset)
+        );
+        bytes[12] = 15 << 4;
+        assert_eq!(
+            Segment::decode(&bytes, CLIENT, SERVER),
+            Err(SegmentError::TooShort)
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::ExitCode;
+
+/// Serial line that starts collecting a batch.
+pub const BATCH_BEGIN: &str = "@batch";
+
+/// Serial line that ends a batch and runs it.
+pub const BATCH_END: &str = "@end";
+
+/// Prefix of every delimiter line emitted around batch results.
+pub const BATCH_MARKER: &str = "@@ruzzle";
+
+/// Serial line that dumps the filesystem archive as hex.
+pub const ARCHIVE_EXPORT: &str = "@export";
+
+/// Serial line that switches the console to framed binary shell messages.
+///
+/// Frames use `ruzzle_protocol::frame`; an empty frame switches back.
+pub const FRAMED_BEGIN: &str = "@frames";
+
+/// Archive bytes carried by each hex line of an export.
+pub const ARCHIVE_LINE_BYTES: usize = 48;
+
+/// Collects batch lines read from the serial port until [`BATCH_END`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchCollector {
+    commands: Vec<String>,
+}
+
+impl BatchCollector {
+    /// Creates an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds one line, returning the collected commands once the batch ends.
+    ///
+    /// Blank lines and `#` comments are dropped.
+    pub fn push_line(&mut self, line: &str) -> Option<Vec<String>> {
+        let trimmed = line.trim();
+        if trimmed == BATCH_END {
+            return Some(core::mem::take(&mut self.commands));
+        }
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            self.commands.push(trimmed.to_string());
+        }
+        None
+    }
+}
+
+/// Formats the delimiter printed before a batch command's output.
+pub fn format_batch_begin(index: usize, command: &str) -> String {
+    let mut out = String::from(BATCH_MARKER);
+    out.push_str(" begin ");
+    out.push_str(&index.to_string());
+    out.push(' ');
+    out.push_str(command);
+    out
+}
+
+/// Formats the delimiter printed after a batch command with its exit status.
+pub fn format_batch_end(index: usize, status: ExitCode) -> String {
+    let mut out = String::from(BATCH_MARKER);
+    out.push_str(" end ");
+    out.push_str(&index.to_string());
+    out.push_str(" status=");
+    out.push_str(&status.0.to_string());
+    out
+}
+
+/// Formats the summary line printed after the whole batch.
+pub fn format_batch_done(total: usize, failed: usize) -> String {
+    let mut out = String::from(BATCH_MARKER);
+    out.push_str(" done total=");
+    out.push_str(&total.to_string());
+    out.push_str(" failed=");
+    out.push_str(&failed.to_string());
+    out
+}
+
+/// Formats an archive as marker-framed hex lines for the host to reassemble.
+pub fn format_archive_export(bytes: &[u8]) -> Vec<String> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut lines = Vec::new();
+    let mut header = String::from(BATCH_MARKER);
+    header.push_str(" archive begin bytes=");
+    header.push_str(&bytes.len().to_string());
+    lines.push(header);
+    for chunk in bytes.chunks(ARCHIVE_LINE_BYTES) {
+        let mut line = String::with_capacity(chunk.len() * 2);
+        for byte in chunk {
+            line.push(char::from(HEX[usize::from(byte >> 4)]));
+            line.push(char::from(HEX[usize::from(byte & 0x0f)]));
+        }
+        lines.push(line);
+    }
+    let mut footer = String::from(BATCH_MARKER);
+    footer.push_str(" archive end");
+    lines.push(footer);
+    lines
+}
+
+/// Runs every command in order, framing each one's output with delimiters.
+///
+/// `run` executes one command line (its output goes to the console between
+/// the delimiters); `emit` prints a delimiter line. Returns failure if any
+/// command failed.
+pub fn run_batch(
+    commands: &[String],
+    mut run: impl FnMut(&str) -> ExitCode,
+    mut emit: impl FnMut(&str),
+) -> ExitCode {
+    let mut failed = 0;
+    for (index, command) in commands.iter().enumerate() {
+        emit(&format_batch_begin(index + 1, command));
+        let status = run(command);
+        if !status.is_success() {
+            failed += 1;
+        }
+        emit(&format_batch_end(index + 1, status));
+    }
+    emit(&format_batch_done(commands.len(), failed));
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collector_skips_comments_until_end() {
+        let mut collector = BatchCollector::new();
+        assert_eq!(collector.push_line("  ls /  "), None);
+        assert_eq!(collector.push_line(""), None);
+        assert_eq!(collector.push_line("# setup"), None);
+        assert_eq!(collector.push_line("cat /missing"), None);
+        assert_eq!(
+            collector.push_line(" @end\r"),
+            Some(vec!["ls /".to_string(), "cat /missing".to_string()])
+        );
+        assert_eq!(collector.push_line(BATCH_END), Some(Vec::new()));
+    }
+
+    #[test]
+    fn run_batch_frames_results() {
+        let commands = vec!["pwd".to_string(), "cat /missing".to_string()];
+        let mut ran = Vec::new();
+        let mut lines = Vec::new();
+        let status = run_batch(
+            &commands,
+            |command| {
+                ran.push(command.to_string());
+                if command == "pwd" {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::FAILURE
+                }
+            },
+            |line| lines.push(line.to_string()),
+        );
+        assert_eq!(status, ExitCode::FAILURE);
+        assert_eq!(ran, commands);
+        assert_eq!(
+            lines,
+            vec![
+                "@@ruzzle begin 1 pwd",
+                "@@ruzzle end 1 status=0",
+                "@@ruzzle begin 2 cat /missing",
+                "@@ruzzle end 2 status=1",
+                "@@ruzzle done total=2 failed=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn archive_export_frames_hex_lines() {
+        let bytes: Vec<u8> = (0..=ARCHIVE_LINE_BYTES as u8).collect();
+        let lines = format_archive_export(&bytes);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "@@ruzzle archive begin bytes=49");
+        assert_eq!(lines[1].len(), ARCHIVE_LINE_BYTES * 2);
+        assert!(lines[1].starts_with("000102030405060708090a0b"));
+        assert_eq!(lines[2], "30");
+        assert_eq!(lines[3], "@@ruzzle archive end");
+        assert_eq!(
+            format_archive_export(&[0xab, 0x0f]),
+            vec![
+                "@@ruzzle archive begin bytes=2",
+                "ab0f",
+                "@@ruzzle archive end",
+            ]
+        );
+    }
+
+    #[test]
+    fn run_batch_succeeds_when_every_command_does() {
+        let mut lines = Vec::new();
+        let status = run_batch(
+            &["echo hi".to_string()],
+            |_| ExitCode::SUCCESS,
+            |line| lines.push(line.to_string()),
+        );
+        assert_eq!(status, ExitCode::SUCCESS);
+        assert_eq!(lines.last().unwrap(), "@@ruzzle done total=1 failed=0");
+        let status = run_batch(&[], |_| ExitCode::FAILURE, |_| {});
+        assert_eq!(status, ExitCode::SUCCESS);
+    }
+}
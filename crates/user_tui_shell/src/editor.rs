@@ -0,0 +1,371 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use user_text_editor::{EditError, TextBuffer};
+
+/// Commands accepted at the `edit>` prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditorCommand {
+    Append(String),
+    Insert { index: usize, text: String },
+    Replace { index: usize, text: String },
+    Delete(Option<usize>),
+    Goto(usize),
+    Yank(usize),
+    Paste(Option<usize>),
+    Print,
+    Save,
+    Quit,
+    ForceQuit,
+    SaveQuit,
+    Help,
+    Unknown,
+}
+
+/// Parses one `edit>` line; line numbers are 1-based on input and 0-based here.
+pub fn parse_editor_command(input: &str) -> EditorCommand {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return EditorCommand::Unknown;
+    }
+    match trimmed {
+        ":w" => return EditorCommand::Save,
+        ":q" => return EditorCommand::Quit,
+        ":q!" => return EditorCommand::ForceQuit,
+        ":wq" => return EditorCommand::SaveQuit,
+        ":p" | "p" => return EditorCommand::Print,
+        ":h" | ":help" | "help" => return EditorCommand::Help,
+        _ => {}
+    }
+
+    let mut parts = trimmed.split_whitespace();
+    let cmd = parts.next().unwrap_or("");
+    match cmd {
+        "a" => {
+            let text = parts.collect::<Vec<&str>>().join(" ");
+            if text.is_empty() {
+                EditorCommand::Unknown
+            } else {
+                EditorCommand::Append(text)
+            }
+        }
+        "i" | "r" => {
+            let Some(index) = parse_editor_index(parts.next()) else {
+                return EditorCommand::Unknown;
+            };
+            let text = parts.collect::<Vec<&str>>().join(" ");
+            if text.is_empty() {
+                EditorCommand::Unknown
+            } else if cmd == "i" {
+                EditorCommand::Insert { index, text }
+            } else {
+                EditorCommand::Replace { index, text }
+            }
+        }
+        "d" => match parts.next() {
+            None => EditorCommand::Delete(None),
+            value => match parse_editor_index(value) {
+                Some(index) => EditorCommand::Delete(Some(index)),
+                None => EditorCommand::Unknown,
+            },
+        },
+        "g" => match parse_editor_index(parts.next()) {
+            Some(index) => EditorCommand::Goto(index),
+            None => EditorCommand::Unknown,
+        },
+        "y" => match parse_editor_index(parts.next()) {
+            Some(index) => EditorCommand::Yank(index),
+            None => EditorCommand::Unknown,
+        },
+        "P" => match parts.next() {
+            None => EditorCommand::Paste(None),
+            value => match parse_editor_index(value) {
+                Some(index) => EditorCommand::Paste(Some(index)),
+                None => EditorCommand::Unknown,
+            },
+        },
+        _ => EditorCommand::Unknown,
+    }
+}
+
+fn parse_editor_index(value: Option<&str>) -> Option<usize> {
+    let raw = value?.parse::<usize>().ok()?;
+    raw.checked_sub(1)
+}
+
+/// Editable file buffer with a current line and unsaved-change tracking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineEditor {
+    buffer: TextBuffer,
+    cursor: usize,
+    dirty: bool,
+}
+
+impl LineEditor {
+    /// Loads file contents into a clean buffer positioned on the first line.
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            buffer: TextBuffer::from_text(text),
+            cursor: 0,
+            dirty: false,
+        }
+    }
+
+    /// Returns the number of lines.
+    pub fn line_count(&self) -> usize {
+        self.buffer.line_count()
+    }
+
+    /// Returns the current lines (read-only).
+    pub fn lines(&self) -> &[String] {
+        self.buffer.lines()
+    }
+
+    /// Returns a line by 0-based index.
+    pub fn line(&self, index: usize) -> Option<&str> {
+        self.buffer.lines().get(index).map(String::as_str)
+    }
+
+    /// Returns the 0-based current line.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Returns true when the buffer differs from the last save.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Appends a line at the end and moves to it.
+    pub fn append(&mut self, text: &str) {
+        let index = self.buffer.line_count();
+        // Inserting at the end is always in bounds.
+        let _ = self.buffer.insert_line(index, text);
+        self.touch(index);
+    }
+
+    /// Inserts a line before `index` and moves to it.
+    pub fn insert(&mut self, index: usize, text: &str) -> Result<(), EditError> {
+        self.buffer.insert_line(index, text)?;
+        self.touch(index);
+        Ok(())
+    }
+
+    /// Replaces the line at `index` and moves to it.
+    pub fn replace(&mut self, index: usize, text: &str) -> Result<(), EditError> {
+        self.buffer.replace_line(index, text)?;
+        self.touch(index);
+        Ok(())
+    }
+
+    /// Deletes the line at `index`, or the current line when `None`.
+    pub fn delete(&mut self, index: Option<usize>) -> Result<(), EditError> {
+        let index = index.unwrap_or(self.cursor);
+        self.buffer.remove_line(index)?;
+        let last = self.buffer.line_count().saturating_sub(1);
+        self.touch(index.min(last));
+        Ok(())
+    }
+
+    /// Moves the current line to `index`.
+    pub fn goto(&mut self, index: usize) -> Result<(), EditError> {
+        if index >= self.buffer.line_count() {
+            return Err(EditError::IndexOutOfBounds);
+        }
+        self.cursor = index;
+        Ok(())
+    }
+
+    /// Inserts each line of `text` before `index` (or at the end) and moves to the last one.
+    pub fn paste(&mut self, index: Option<usize>, text: &str) -> Result<(), EditError> {
+        let start = index.unwrap_or(self.buffer.line_count());
+        if start > self.buffer.line_count() {
+            return Err(EditError::IndexOutOfBounds);
+        }
+        let mut at = start;
+        for line in text.split('\n') {
+            self.buffer.insert_line(at, line)?;
+            at += 1;
+        }
+        self.touch(at - 1);
+        Ok(())
+    }
+
+    /// Returns the buffer as file contents.
+    pub fn to_text(&self) -> String {
+        self.buffer.to_text()
+    }
+
+    /// Marks the buffer as saved.
+    pub fn mark_saved(&mut self) {
+        self.dirty = false;
+    }
+
+    fn touch(&mut self, cursor: usize) {
+        self.cursor = cursor;
+        self.dirty = true;
+    }
+}
+
+/// Renders the buffer with 1-based line numbers and `>` on the current line.
+pub fn format_editor_buffer(editor: &LineEditor) -> String {
+    if editor.line_count() == 0 {
+        return "<empty>\n".to_string();
+    }
+    let mut out = String::new();
+    for (index, line) in editor.lines().iter().enumerate() {
+        let number = (index + 1).to_string();
+        for _ in number.len()..3 {
+            out.push(' ');
+        }
+        out.push_str(&number);
+        out.push(if index == editor.cursor() { '>' } else { ' ' });
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats the `edit>` command reference.
+pub fn format_editor_help() -> String {
+    let mut out = String::new();
+    out.push_str("editor commands:\n");
+    out.push_str("  :w               save\n");
+    out.push_str("  :q               quit (refused with unsaved changes)\n");
+    out.push_str("  :q!              quit without saving\n");
+    out.push_str("  :wq              save and quit\n");
+    out.push_str("  :p | p           print buffer\n");
+    out.push_str("  a <text>         append line\n");
+    out.push_str("  i <n> <text>     insert at line n\n");
+    out.push_str("  r <n> <text>     replace line n\n");
+    out.push_str("  d [n]            delete line n (default: current line)\n");
+    out.push_str("  g <n>            go to line n\n");
+    out.push_str("  y <n>            copy line n to clipboard\n");
+    out.push_str("  P [n]            paste clipboard (before line n)\n");
+    out.push_str("  :h | help        show help\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_editor_commands() {
+        assert_eq!(parse_editor_command(":w"), EditorCommand::Save);
+        assert_eq!(parse_editor_command(":q"), EditorCommand::Quit);
+        assert_eq!(parse_editor_command(":q!"), EditorCommand::ForceQuit);
+        assert_eq!(parse_editor_command(" :wq "), EditorCommand::SaveQuit);
+        assert_eq!(parse_editor_command("p"), EditorCommand::Print);
+        assert_eq!(parse_editor_command("help"), EditorCommand::Help);
+        assert_eq!(
+            parse_editor_command("a hello  world"),
+            EditorCommand::Append("hello world".to_string())
+        );
+        assert_eq!(
+            parse_editor_command("i 2 two"),
+            EditorCommand::Insert {
+                index: 1,
+                text: "two".to_string()
+            }
+        );
+        assert_eq!(
+            parse_editor_command("r 1 one"),
+            EditorCommand::Replace {
+                index: 0,
+                text: "one".to_string()
+            }
+        );
+        assert_eq!(parse_editor_command("d"), EditorCommand::Delete(None));
+        assert_eq!(parse_editor_command("d 3"), EditorCommand::Delete(Some(2)));
+        assert_eq!(parse_editor_command("g 4"), EditorCommand::Goto(3));
+        assert_eq!(parse_editor_command("y 1"), EditorCommand::Yank(0));
+        assert_eq!(parse_editor_command("P"), EditorCommand::Paste(None));
+        assert_eq!(parse_editor_command("P 2"), EditorCommand::Paste(Some(1)));
+    }
+
+    #[test]
+    fn parse_editor_rejects_bad_input() {
+        for input in [
+            "", "a", "i 1", "i x text", "r 0 text", "d x", "g", "g 0", "y", "P x", "zz",
+        ] {
+            assert_eq!(
+                parse_editor_command(input),
+                EditorCommand::Unknown,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn editor_tracks_cursor_and_dirty_state() {
+        let mut editor = LineEditor::from_text("one\ntwo");
+        assert_eq!(editor.cursor(), 0);
+        assert!(!editor.is_dirty());
+        editor.goto(1).unwrap();
+        assert_eq!(editor.cursor(), 1);
+        assert!(!editor.is_dirty());
+        assert_eq!(editor.goto(2), Err(EditError::IndexOutOfBounds));
+
+        editor.append("three");
+        assert_eq!(editor.cursor(), 2);
+        assert!(editor.is_dirty());
+        editor.insert(0, "zero").unwrap();
+        assert_eq!(editor.cursor(), 0);
+        editor.replace(1, "ONE").unwrap();
+        assert_eq!(editor.line(editor.cursor()), Some("ONE"));
+        assert_eq!(editor.insert(9, "x"), Err(EditError::IndexOutOfBounds));
+        assert_eq!(editor.replace(9, "x"), Err(EditError::IndexOutOfBounds));
+        assert_eq!(editor.to_text(), "zero\nONE\ntwo\nthree");
+
+        editor.mark_saved();
+        assert!(!editor.is_dirty());
+        editor.delete(None).unwrap();
+        assert_eq!(editor.to_text(), "zero\ntwo\nthree");
+        assert_eq!(editor.cursor(), 1);
+        editor.delete(Some(2)).unwrap();
+        assert_eq!(editor.cursor(), 1);
+        assert_eq!(editor.delete(Some(5)), Err(EditError::IndexOutOfBounds));
+        assert!(editor.is_dirty());
+    }
+
+    #[test]
+    fn editor_delete_last_line_empties_buffer() {
+        let mut editor = LineEditor::from_text("only");
+        editor.delete(None).unwrap();
+        assert_eq!(editor.line_count(), 0);
+        assert_eq!(editor.cursor(), 0);
+        assert_eq!(editor.delete(None), Err(EditError::IndexOutOfBounds));
+    }
+
+    #[test]
+    fn editor_paste_inserts_lines() {
+        let mut editor = LineEditor::from_text("a\nd");
+        editor.paste(Some(1), "b\nc").unwrap();
+        assert_eq!(editor.to_text(), "a\nb\nc\nd");
+        assert_eq!(editor.cursor(), 2);
+        editor.paste(None, "e").unwrap();
+        assert_eq!(editor.cursor(), 4);
+        assert_eq!(editor.paste(Some(9), "x"), Err(EditError::IndexOutOfBounds));
+        assert_eq!(editor.line_count(), 5);
+    }
+
+    #[test]
+    fn format_editor_buffer_marks_cursor() {
+        assert_eq!(
+            format_editor_buffer(&LineEditor::from_text("")),
+            "<empty>\n"
+        );
+        let mut editor = LineEditor::from_text("one\ntwo");
+        editor.goto(1).unwrap();
+        assert_eq!(format_editor_buffer(&editor), "  1 one\n  2>two\n");
+    }
+
+    #[test]
+    fn format_editor_help_lists_commands() {
+        let help = format_editor_help();
+        assert!(help.contains("g <n>"));
+        assert!(help.contains(":q!"));
+        assert!(help.contains("d [n]"));
+    }
+}
@@ -5,7 +5,18 @@ extern crate alloc;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
+use ruzzle_protocol::describe::ServiceDescription;
 use ruzzle_protocol::shell as shell_protocol;
+use ruzzle_protocol::shell::{IpCommand, RouteCommand};
+use user_net_service::{
+    is_valid_gateway, is_valid_iface_name, is_valid_ipv4, is_valid_route_destination,
+    parse_ipv4_cidr, MacAddr, MAX_ROUTE_METRIC,
+};
+
+/// Host-driven batch mode for the serial console.
+pub mod batch;
+/// Line editor behind the `edit`/`vim` command.
+pub mod editor;
 
 /// Commands supported by the TUI shell.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -16,21 +27,41 @@ pub enum Command {
     Lsmod,
     Start(String),
     Stop(String),
-    LogTail,
+    LogTail {
+        follow: bool,
+        lines: Option<usize>,
+    },
     Help(Option<String>),
     Catalog {
         slot: Option<String>,
         verified_only: bool,
     },
     PieceCheck(String),
-    Ip(Option<String>),
-    Route(Option<String>),
+    PieceCheckAll,
+    PieceRuntime(String),
+    Status(String),
+    BootReport,
+    Resolve(String),
+    Ping(String),
+    Fw(Option<String>),
+    Dhcpd(Option<String>),
+    Ip(IpCommand),
+    Route(RouteCommand),
     Mount(Option<String>),
-    Df(Option<String>),
-    Du(String),
+    Df {
+        path: Option<String>,
+        human: bool,
+    },
+    Du {
+        path: String,
+        human: bool,
+    },
     MarketScan,
     Install(String),
-    Remove(String),
+    Remove {
+        module: String,
+        force: bool,
+    },
     Setup,
     Login(String),
     Logout,
@@ -49,20 +80,130 @@ pub enum Command {
     MkdirP(String),
     Write { path: String, contents: String },
     Rm(String),
-    RmRecursive(String),
-    Slots,
+    RmRecursive {
+        path: String,
+        force: bool,
+    },
+    Slots {
+        verbose: bool,
+    },
     Plug {
         slot: String,
         module: String,
         dry_run: bool,
         swap: bool,
+        /// Admin override for a locked slot.
+        override_lock: bool,
+    },
+    Unplug {
+        slot: String,
+        force: bool,
+        /// Admin override for a locked slot.
+        override_lock: bool,
+    },
+    Graph {
+        /// Emit Graphviz DOT instead of the text tree.
+        dot: bool,
     },
-    Unplug(String),
-    Graph,
     Sysinfo,
+    Clip(Option<String>),
+    Time(String),
+    Metrics(Option<String>),
+    Wc(String),
+    Find {
+        path: String,
+        name: Option<String>,
+    },
+    Ln {
+        target: String,
+        link: String,
+    },
+    Chsh {
+        user: Option<String>,
+        shell: Option<String>,
+    },
+    Chmod {
+        mode: String,
+        path: String,
+    },
+    Chown {
+        user: String,
+        path: String,
+    },
+    Gpu(Option<String>),
+    Watch {
+        interval_secs: u32,
+        command: String,
+    },
+    Container(Option<String>),
+    Http(Option<String>),
+    Echo(String),
+    Shutdown,
+    Set(Option<String>),
+    WebConsole(Option<String>),
+    LogLevel(Option<String>),
+    Notifications {
+        clear: bool,
+    },
+    Stat(String),
+    Apply {
+        path: String,
+        dry_run: bool,
+    },
+    Checksum(String),
+    Autoplug {
+        dry_run: bool,
+    },
+    Describe,
+    Sequence(Vec<ChainStep>),
     Unknown(String),
 }
 
+/// Usage row for `df` formatting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DfRow {
+    pub path: String,
+    pub files: usize,
+    pub dirs: usize,
+    pub bytes: usize,
+}
+
+/// File metadata shown by `stat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatInfo {
+    pub path: String,
+    pub kind: String,
+    pub size: usize,
+    pub owner: String,
+    pub group: String,
+    pub mode: u16,
+    pub permissions: String,
+    pub target: Option<String>,
+    /// Creation time in milliseconds since boot.
+    pub created: u64,
+    /// Last modification time in milliseconds since boot.
+    pub modified: u64,
+}
+
+/// Line, word, and byte counts for `wc`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WordCount {
+    pub lines: usize,
+    pub words: usize,
+    pub bytes: usize,
+}
+
+impl WordCount {
+    /// Counts lines, whitespace-separated words, and bytes in text.
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            lines: text.lines().count(),
+            words: text.split_whitespace().count(),
+            bytes: text.len(),
+        }
+    }
+}
+
 /// Lightweight module row for UI formatting.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModuleRow {
@@ -71,6 +212,27 @@ pub struct ModuleRow {
     pub provides: Vec<String>,
 }
 
+/// State and failure diagnostics of one module for `status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleStatusRow {
+    pub name: String,
+    pub state: String,
+    /// Failures since the module last started.
+    pub failures: u32,
+    pub last_error: Option<String>,
+    /// Milliseconds since boot of the last failure.
+    pub last_failure: Option<u64>,
+}
+
+/// Start time and duration of one module for `boot-report`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootTimingRow {
+    pub name: String,
+    /// Microseconds since boot when the start began.
+    pub started_us: u64,
+    pub duration_us: u64,
+}
+
 /// Lightweight process row for UI formatting.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProcessRow {
@@ -85,6 +247,53 @@ pub struct SlotRow {
     pub name: String,
     pub required: bool,
     pub provider: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub locked: bool,
+}
+
+/// Output styling applied by formatters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Style {
+    #[default]
+    Plain,
+    Color,
+}
+
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+impl Style {
+    /// Returns the style for a `color=on|off` setting value.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "on" => Some(Style::Color),
+            "off" => Some(Style::Plain),
+            _ => None,
+        }
+    }
+
+    /// Returns the setting value for this style.
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Plain => "off",
+            Style::Color => "on",
+        }
+    }
+
+    fn paint(self, text: &str, color: &str) -> String {
+        match self {
+            Style::Plain => text.to_string(),
+            Style::Color => {
+                let mut out = String::from(color);
+                out.push_str(text);
+                out.push_str(ANSI_RESET);
+                out
+            }
+        }
+    }
 }
 
 /// Lightweight dependency graph row.
@@ -93,11 +302,292 @@ pub struct GraphRow {
     pub name: String,
     pub state: String,
     pub depends: Vec<String>,
+    /// Optional dependencies, drawn as dashed edges.
+    pub wants: Vec<String>,
+    /// Installed modules that depend on this one.
+    pub required_by: Vec<String>,
+}
+
+/// One planned plug shown by `autoplug`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanRow {
+    pub slot: String,
+    pub module: String,
+    /// Where the module comes from: `running`, `installed` or `catalog`.
+    pub source: String,
+}
+
+/// Condition under which a chained step runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainOp {
+    /// First step or `;`: always runs.
+    Always,
+    /// `&&`: runs when the previous status succeeded.
+    OnSuccess,
+    /// `||`: runs when the previous status failed.
+    OnFailure,
+}
+
+/// One step of a `;`/`&&`/`||` command chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainStep {
+    pub op: ChainOp,
+    pub command: Command,
+}
+
+/// Outcome of a chained step after execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// Exit status of a dispatched command; zero means success.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ExitCode(pub u8);
+
+impl ExitCode {
+    pub const SUCCESS: ExitCode = ExitCode(0);
+    pub const FAILURE: ExitCode = ExitCode(1);
+    pub const UNKNOWN_COMMAND: ExitCode = ExitCode(127);
+
+    /// Returns true for a zero status.
+    pub fn is_success(self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Most output bytes a `CommandResult` keeps; its response text travels in
+/// one TLV, whose length field is a u16.
+pub const MAX_OUTPUT_BYTES: usize = u16::MAX as usize;
+
+/// Structured result returned by the shell dispatch layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandResult {
+    pub status: ExitCode,
+    pub output: String,
+}
+
+impl CommandResult {
+    /// Creates a result with the given status and output, cutting the output
+    /// at a char boundary to at most `MAX_OUTPUT_BYTES`.
+    pub fn new(status: ExitCode, mut output: String) -> Self {
+        if output.len() > MAX_OUTPUT_BYTES {
+            let mut end = MAX_OUTPUT_BYTES;
+            while !output.is_char_boundary(end) {
+                end -= 1;
+            }
+            output.truncate(end);
+        }
+        Self { status, output }
+    }
+
+    /// Converts the result into an IPC shell response.
+    pub fn to_response(&self) -> shell_protocol::ShellResponse {
+        let status = if self.status.is_success() {
+            shell_protocol::ShellStatus::Ok
+        } else {
+            shell_protocol::ShellStatus::Failed
+        };
+        shell_protocol::ShellResponse::Text {
+            status,
+            text: self.output.clone(),
+        }
+    }
+}
+
+/// Replaces every `$?` in the line with the last exit status.
+///
+/// Backslash escapes are honoured first, so `\$?` stays as written for
+/// `echo` to print a literal `$?`.
+pub fn expand_last_status(input: &str, last: ExitCode) -> String {
+    let mut out = String::new();
+    let mut chars = input.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                out.push(ch);
+                out.extend(chars.next());
+            }
+            '$' if chars.as_str().starts_with('?') => {
+                chars.next();
+                out.push_str(&last.0.to_string());
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Expands `$NAME`/`${NAME}` variables and `\n`, `\t`, `\\`, `\$` escapes for `echo`.
+///
+/// Unknown variables expand to nothing; a `$` not followed by a name is kept.
+pub fn expand_echo(text: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(other @ ('\\' | '$')) => out.push(other),
+                Some(other) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => out.push('\\'),
+            },
+            '$' => {
+                let braced = chars.peek() == Some(&'{');
+                if braced {
+                    chars.next();
+                }
+                let mut name = String::new();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_ascii_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    name.push(next);
+                    chars.next();
+                }
+                if braced && chars.next_if_eq(&'}').is_none() {
+                    out.push_str("${");
+                    out.push_str(&name);
+                } else if name.is_empty() {
+                    out.push('$');
+                    if braced {
+                        out.push_str("{}");
+                    }
+                } else if let Some(value) = lookup(&name) {
+                    out.push_str(&value);
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Renders a prompt template, replacing `{user}`, `{hostname}` and `{cwd}`.
+///
+/// Without a logged-in user `{user}` renders as `nobody`; other text is kept.
+pub fn render_prompt(template: &str, user: Option<&str>, hostname: &str, cwd: &str) -> String {
+    template
+        .replace("{user}", user.unwrap_or("nobody"))
+        .replace("{hostname}", hostname)
+        .replace("{cwd}", cwd)
 }
 
+/// Units used by human-readable sizes.
+const SIZE_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+/// Default refresh interval for `watch` without `-n`.
+pub const DEFAULT_WATCH_INTERVAL_SECS: u32 = 2;
+
+/// Lines shown by `log tail` without `-n`.
+pub const DEFAULT_LOG_TAIL_LINES: usize = 10;
+
 /// Parses a shell command string into a structured command.
+///
+/// `;`, `&&` and `||` must stand as separate words (a trailing `;` may be
+/// attached to the previous word) and split the line into a `Sequence`.
 pub fn parse_command(input: &str) -> Command {
     let trimmed = input.trim();
+    match split_chain(trimmed) {
+        Some(segments) if segments.len() > 1 => Command::Sequence(
+            segments
+                .into_iter()
+                .map(|(op, text)| ChainStep {
+                    op,
+                    command: parse_simple_command(&text),
+                })
+                .collect(),
+        ),
+        Some(segments) if trimmed.ends_with(';') => parse_simple_command(&segments[0].1),
+        Some(_) => parse_simple_command(trimmed),
+        None => Command::Unknown(trimmed.to_string()),
+    }
+}
+
+fn split_chain(input: &str) -> Option<Vec<(ChainOp, String)>> {
+    let mut segments = Vec::new();
+    let mut op = ChainOp::Always;
+    // Byte range of the current segment's words, so the text between them is kept as typed.
+    let mut span: Option<(usize, usize)> = None;
+    let mut pending = false;
+    for token in input.split_whitespace() {
+        let offset = token.as_ptr() as usize - input.as_ptr() as usize;
+        let (word, next) = match token {
+            ";" => ("", Some(ChainOp::Always)),
+            "&&" => ("", Some(ChainOp::OnSuccess)),
+            "||" => ("", Some(ChainOp::OnFailure)),
+            _ => match token.strip_suffix(';') {
+                Some(word) => (word, Some(ChainOp::Always)),
+                None => (token, None),
+            },
+        };
+        if !word.is_empty() {
+            let start = span.map_or(offset, |(start, _)| start);
+            span = Some((start, offset + word.len()));
+        }
+        if let Some(next) = next {
+            let (start, end) = span.take()?;
+            segments.push((op, input[start..end].to_string()));
+            op = next;
+            pending = next != ChainOp::Always;
+        } else {
+            pending = false;
+        }
+    }
+    if pending {
+        return None;
+    }
+    if let Some((start, end)) = span {
+        segments.push((op, input[start..end].to_string()));
+    }
+    Some(segments)
+}
+
+/// Runs chained steps in order, skipping steps whose condition does not hold.
+///
+/// A skipped step leaves the previous status in place, matching POSIX shells.
+pub fn run_sequence(
+    steps: &[ChainStep],
+    mut run: impl FnMut(&Command) -> bool,
+) -> Vec<StepOutcome> {
+    let mut ok = true;
+    let mut outcomes = Vec::with_capacity(steps.len());
+    for step in steps {
+        let runs = match step.op {
+            ChainOp::Always => true,
+            ChainOp::OnSuccess => ok,
+            ChainOp::OnFailure => !ok,
+        };
+        if !runs {
+            outcomes.push(StepOutcome::Skipped);
+            continue;
+        }
+        ok = run(&step.command);
+        outcomes.push(if ok {
+            StepOutcome::Succeeded
+        } else {
+            StepOutcome::Failed
+        });
+    }
+    outcomes
+}
+
+/// Returns the status of the last step that actually ran.
+pub fn sequence_succeeded(outcomes: &[StepOutcome]) -> bool {
+    outcomes
+        .iter()
+        .rev()
+        .find(|outcome| **outcome != StepOutcome::Skipped)
+        .is_none_or(|outcome| *outcome == StepOutcome::Succeeded)
+}
+
+fn parse_simple_command(trimmed: &str) -> Command {
     if trimmed.is_empty() {
         return Command::Unknown("".to_string());
     }
@@ -123,24 +613,30 @@ pub fn parse_command(input: &str) -> Command {
     if trimmed == "whoami" {
         return Command::Whoami;
     }
+    if trimmed == "shutdown" {
+        return Command::Shutdown;
+    }
+    if trimmed == "describe" {
+        return Command::Describe;
+    }
     if trimmed == "users" {
         return Command::Users;
     }
     if trimmed == "pwd" {
         return Command::Pwd;
     }
-    if trimmed == "slots" {
-        return Command::Slots;
+    if trimmed == "boot-report" {
+        return Command::BootReport;
     }
     if trimmed == "graph" {
-        return Command::Graph;
+        return Command::Graph { dot: false };
+    }
+    if trimmed == "graph --dot" {
+        return Command::Graph { dot: true };
     }
     if trimmed == "sysinfo" {
         return Command::Sysinfo;
     }
-    if trimmed == "log tail" {
-        return Command::LogTail;
-    }
     if trimmed.starts_with("help") {
         let rest = trimmed.strip_prefix("help").unwrap_or("").trim();
         if rest.is_empty() {
@@ -168,9 +664,24 @@ pub fn parse_command(input: &str) -> Command {
             if sub != "check" {
                 return Command::Unknown(trimmed.to_string());
             }
+            let mut parts = parts.peekable();
+            if parts.peek() == Some(&"--all") {
+                parts.next();
+                return if parts.next().is_none() {
+                    Command::PieceCheckAll
+                } else {
+                    Command::Unknown(trimmed.to_string())
+                };
+            }
+            let runtime = parts.peek() == Some(&"--runtime");
+            if runtime {
+                parts.next();
+            }
             let name = parts.collect::<Vec<&str>>().join(" ");
             if name.is_empty() {
                 Command::Unknown(trimmed.to_string())
+            } else if runtime {
+                Command::PieceRuntime(name)
             } else {
                 Command::PieceCheck(name)
             }
@@ -186,22 +697,14 @@ pub fn parse_command(input: &str) -> Command {
             }
             Command::Ps { tree }
         }
-        "ip" => {
-            let args = parts.collect::<Vec<&str>>().join(" ");
-            if args.is_empty() {
-                Command::Ip(None)
-            } else {
-                Command::Ip(Some(args))
-            }
-        }
-        "route" => {
-            let args = parts.collect::<Vec<&str>>().join(" ");
-            if args.is_empty() {
-                Command::Route(None)
-            } else {
-                Command::Route(Some(args))
-            }
-        }
+        "ip" => match parse_ip_args(&parts.collect::<Vec<&str>>()) {
+            Some(ip) => Command::Ip(ip),
+            None => Command::Unknown(trimmed.to_string()),
+        },
+        "route" => match parse_route_args(&parts.collect::<Vec<&str>>()) {
+            Some(route) => Command::Route(route),
+            None => Command::Unknown(trimmed.to_string()),
+        },
         "mount" => {
             let args = parts.collect::<Vec<&str>>().join(" ");
             if args.is_empty() {
@@ -211,19 +714,22 @@ pub fn parse_command(input: &str) -> Command {
             }
         }
         "df" => {
-            let path = parts.collect::<Vec<&str>>().join(" ");
+            let (human, path) = split_flag(parts, &["-h"]);
             if path.is_empty() {
-                Command::Df(None)
+                Command::Df { path: None, human }
             } else {
-                Command::Df(Some(path))
+                Command::Df {
+                    path: Some(path),
+                    human,
+                }
             }
         }
         "du" => {
-            let path = parts.collect::<Vec<&str>>().join(" ");
+            let (human, path) = split_flag(parts, &["-h"]);
             if path.is_empty() {
                 Command::Unknown(trimmed.to_string())
             } else {
-                Command::Du(path)
+                Command::Du { path, human }
             }
         }
         "start" => {
@@ -234,6 +740,42 @@ pub fn parse_command(input: &str) -> Command {
                 Command::Start(module)
             }
         }
+        "resolve" => match (parts.next(), parts.next()) {
+            (Some(name), None) => Command::Resolve(name.to_string()),
+            _ => Command::Unknown(trimmed.to_string()),
+        },
+        "ping" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::Unknown(trimmed.to_string())
+            } else {
+                Command::Ping(args)
+            }
+        }
+        "fw" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::Fw(None)
+            } else {
+                Command::Fw(Some(args))
+            }
+        }
+        "dhcpd" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::Dhcpd(None)
+            } else {
+                Command::Dhcpd(Some(args))
+            }
+        }
+        "status" => {
+            let module = parts.collect::<Vec<&str>>().join(" ");
+            if module.is_empty() {
+                Command::Unknown(trimmed.to_string())
+            } else {
+                Command::Status(module)
+            }
+        }
         "login" => {
             let user = parts.collect::<Vec<&str>>().join(" ");
             if user.is_empty() {
@@ -250,6 +792,82 @@ pub fn parse_command(input: &str) -> Command {
                 Command::UserAdd(user)
             }
         }
+        "chsh" => {
+            let args = parts.collect::<Vec<&str>>();
+            match args.as_slice() {
+                [] => Command::Chsh {
+                    user: None,
+                    shell: None,
+                },
+                [shell] => Command::Chsh {
+                    user: None,
+                    shell: Some(shell.to_string()),
+                },
+                [user, shell] => Command::Chsh {
+                    user: Some(user.to_string()),
+                    shell: Some(shell.to_string()),
+                },
+                _ => Command::Unknown(trimmed.to_string()),
+            }
+        }
+        "chmod" | "chown" => {
+            let first = parts.next().unwrap_or("");
+            let path = parts.collect::<Vec<&str>>().join(" ");
+            if first.is_empty() || path.is_empty() {
+                Command::Unknown(trimmed.to_string())
+            } else if cmd == "chmod" {
+                Command::Chmod {
+                    mode: first.to_string(),
+                    path,
+                }
+            } else {
+                Command::Chown {
+                    user: first.to_string(),
+                    path,
+                }
+            }
+        }
+        "container" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::Container(None)
+            } else {
+                Command::Container(Some(args))
+            }
+        }
+        "http" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::Http(None)
+            } else {
+                Command::Http(Some(args))
+            }
+        }
+        "echo" => Command::Echo(parts.collect::<Vec<&str>>().join(" ")),
+        "webconsole" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::WebConsole(None)
+            } else {
+                Command::WebConsole(Some(args))
+            }
+        }
+        "set" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::Set(None)
+            } else {
+                Command::Set(Some(args))
+            }
+        }
+        "gpu" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::Gpu(None)
+            } else {
+                Command::Gpu(Some(args))
+            }
+        }
         "stop" => {
             let module = parts.collect::<Vec<&str>>().join(" ");
             if module.is_empty() {
@@ -313,58 +931,101 @@ pub fn parse_command(input: &str) -> Command {
                 Command::Cat(path)
             }
         }
-        "edit" | "vim" => {
+        "apply" => {
+            let (dry_run, path) = split_flag(parts, &["-n", "--dry-run"]);
+            if path.is_empty() {
+                Command::Unknown(trimmed.to_string())
+            } else {
+                Command::Apply { path, dry_run }
+            }
+        }
+        "stat" => {
             let path = parts.collect::<Vec<&str>>().join(" ");
             if path.is_empty() {
                 Command::Unknown(trimmed.to_string())
             } else {
-                Command::Edit(path)
+                Command::Stat(path)
             }
         }
-        "rm" => {
-            let first = parts.next().unwrap_or("");
-            if first == "-r" || first == "-R" {
-                let path = parts.collect::<Vec<&str>>().join(" ");
-                if path.is_empty() {
-                    Command::Unknown(trimmed.to_string())
-                } else {
-                    Command::RmRecursive(path)
-                }
+        "slots" => {
+            let (verbose, rest) = split_flag(parts, &["-v", "--verbose"]);
+            if rest.is_empty() {
+                Command::Slots { verbose }
             } else {
-                let mut collected = Vec::new();
-                if !first.is_empty() {
-                    collected.push(first);
-                }
-                collected.extend(parts);
-                let path = collected.join(" ");
-                if path.is_empty() {
-                    Command::Unknown(trimmed.to_string())
-                } else {
-                    Command::Rm(path)
-                }
+                Command::Unknown(trimmed.to_string())
             }
         }
-        "write" => {
-            let path = parts.next().unwrap_or("");
-            let contents = parts.collect::<Vec<&str>>().join(" ");
-            if path.is_empty() || contents.is_empty() {
+        "autoplug" => {
+            let (dry_run, rest) = split_flag(parts, &["-n", "--dry-run"]);
+            if rest.is_empty() {
+                Command::Autoplug { dry_run }
+            } else {
+                Command::Unknown(trimmed.to_string())
+            }
+        }
+        "checksum" => {
+            let path = parts.collect::<Vec<&str>>().join(" ");
+            if path.is_empty() {
                 Command::Unknown(trimmed.to_string())
             } else {
-                Command::Write {
-                    path: path.to_string(),
-                    contents,
-                }
+                Command::Checksum(path)
             }
         }
-        "plug" => {
-            let mut dry_run = false;
-            let mut swap = false;
-            let mut args = Vec::new();
+        "wc" => {
+            let path = parts.collect::<Vec<&str>>().join(" ");
+            if path.is_empty() {
+                Command::Unknown(trimmed.to_string())
+            } else {
+                Command::Wc(path)
+            }
+        }
+        "find" => {
+            let args = parts.collect::<Vec<&str>>();
+            match args.as_slice() {
+                [path] => Command::Find {
+                    path: path.to_string(),
+                    name: None,
+                },
+                [path, "-name", name] => Command::Find {
+                    path: path.to_string(),
+                    name: Some(name.to_string()),
+                },
+                _ => Command::Unknown(trimmed.to_string()),
+            }
+        }
+        "edit" | "vim" => {
+            let path = parts.collect::<Vec<&str>>().join(" ");
+            if path.is_empty() {
+                Command::Unknown(trimmed.to_string())
+            } else {
+                Command::Edit(path)
+            }
+        }
+        "rm" => parse_rm_args(parts, trimmed),
+        "write" => {
+            let path = parts.next().unwrap_or("");
+            let contents = parts.collect::<Vec<&str>>().join(" ");
+            if path.is_empty() || contents.is_empty() {
+                Command::Unknown(trimmed.to_string())
+            } else {
+                Command::Write {
+                    path: path.to_string(),
+                    contents,
+                }
+            }
+        }
+        "plug" => {
+            let mut dry_run = false;
+            let mut swap = false;
+            let mut override_lock = false;
+            let mut args = Vec::new();
             for part in parts {
                 if part == "--dry-run" || part == "-n" {
                     dry_run = true;
                 } else if part == "--swap" || part == "-s" {
                     swap = true;
+                } else if part == "--override" {
+                    override_lock = true;
                 } else if part.starts_with('-') {
                     return Command::Unknown(trimmed.to_string());
                 } else {
@@ -385,15 +1046,21 @@ pub fn parse_command(input: &str) -> Command {
                     module,
                     dry_run,
                     swap,
+                    override_lock,
                 }
             }
         }
         "unplug" => {
-            let slot = parts.collect::<Vec<&str>>().join(" ");
+            let (override_lock, rest) = split_flag(parts, &["--override"]);
+            let (force, slot) = split_flag(rest.split_whitespace(), &["-f", "--force"]);
             if slot.is_empty() {
                 Command::Unknown(trimmed.to_string())
             } else {
-                Command::Unplug(slot)
+                Command::Unplug {
+                    slot,
+                    force,
+                    override_lock,
+                }
             }
         }
         "install" => {
@@ -424,6 +1091,16 @@ pub fn parse_command(input: &str) -> Command {
                 }
             }
         }
+        "ln" => {
+            let args = parts.collect::<Vec<&str>>();
+            match args.as_slice() {
+                ["-s", target, link] => Command::Ln {
+                    target: target.to_string(),
+                    link: link.to_string(),
+                },
+                _ => Command::Unknown(trimmed.to_string()),
+            }
+        }
         "mv" => {
             let src = parts.next().unwrap_or("");
             let dst = parts.collect::<Vec<&str>>().join(" ");
@@ -436,12 +1113,43 @@ pub fn parse_command(input: &str) -> Command {
                 }
             }
         }
+        "time" => {
+            let command = parts.collect::<Vec<&str>>().join(" ");
+            if command.is_empty() {
+                Command::Unknown(trimmed.to_string())
+            } else {
+                Command::Time(command)
+            }
+        }
+        "watch" => parse_watch_args(parts, trimmed),
+        "log" => parse_log_args(parts, trimmed),
+        "metrics" => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            if args.is_empty() {
+                Command::Metrics(None)
+            } else {
+                Command::Metrics(Some(args))
+            }
+        }
+        "clip" => {
+            let text = parts.collect::<Vec<&str>>().join(" ");
+            if text.is_empty() {
+                Command::Clip(None)
+            } else {
+                Command::Clip(Some(text))
+            }
+        }
+        "notifications" => match (parts.next(), parts.next()) {
+            (None, _) => Command::Notifications { clear: false },
+            (Some("clear"), None) => Command::Notifications { clear: true },
+            _ => Command::Unknown(trimmed.to_string()),
+        },
         "remove" => {
-            let module = parts.collect::<Vec<&str>>().join(" ");
+            let (force, module) = split_flag(parts, &["-f", "--force"]);
             if module.is_empty() {
                 Command::Unknown(trimmed.to_string())
             } else {
-                Command::Remove(module)
+                Command::Remove { module, force }
             }
         }
         _ => Command::Unknown(trimmed.to_string()),
@@ -470,6 +1178,177 @@ fn parse_catalog_args<'a>(mut parts: impl Iterator<Item = &'a str>, raw: &str) -
     }
 }
 
+/// Parses `ip` arguments, rejecting malformed names and addresses.
+fn parse_ip_args(args: &[&str]) -> Option<IpCommand> {
+    let iface = |name: &str| is_valid_iface_name(name).then(|| name.to_string());
+    match args {
+        [] | ["addr"] | ["link"] => Some(IpCommand::Show),
+        ["-s"] => Some(IpCommand::Stats(None)),
+        ["-s", name] => Some(IpCommand::Stats(Some(iface(name)?))),
+        ["link", "add", name] => Some(IpCommand::LinkAdd(iface(name)?)),
+        ["link", "del", name] => Some(IpCommand::LinkDel(iface(name)?)),
+        ["link", "set", name, "up"] => Some(IpCommand::LinkSet {
+            iface: iface(name)?,
+            up: true,
+        }),
+        ["link", "set", name, "down"] => Some(IpCommand::LinkSet {
+            iface: iface(name)?,
+            up: false,
+        }),
+        ["link", "set", name, "address", mac] => {
+            MacAddr::parse(mac)?;
+            Some(IpCommand::LinkAddress {
+                iface: iface(name)?,
+                mac: mac.to_string(),
+            })
+        }
+        ["addr", "add", name, cidr] => {
+            parse_ipv4_cidr(cidr)?;
+            Some(IpCommand::AddrAdd {
+                iface: iface(name)?,
+                cidr: cidr.to_string(),
+            })
+        }
+        ["addr", "del", name] => Some(IpCommand::AddrDel(iface(name)?)),
+        _ => None,
+    }
+}
+
+/// Parses `route` arguments, rejecting malformed destinations and gateways.
+fn parse_route_args(args: &[&str]) -> Option<RouteCommand> {
+    match args {
+        [] => Some(RouteCommand::Show),
+        ["add", destination, options @ ..] => {
+            if !is_valid_route_destination(destination) {
+                return None;
+            }
+            let mut iface = None;
+            let mut gateway = None;
+            let mut metric = None;
+            for option in options.chunks(2) {
+                match option {
+                    ["dev", name] if iface.is_none() && is_valid_iface_name(name) => {
+                        iface = Some(name.to_string());
+                    }
+                    ["via", addr] if gateway.is_none() && is_valid_gateway(addr) => {
+                        gateway = Some(addr.to_string());
+                    }
+                    ["metric", value] if metric.is_none() => {
+                        metric = Some(
+                            value
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|metric| *metric <= MAX_ROUTE_METRIC)?,
+                        );
+                    }
+                    _ => return None,
+                }
+            }
+            Some(RouteCommand::Add {
+                destination: destination.to_string(),
+                iface: iface?,
+                gateway,
+                metric: metric.unwrap_or(0),
+            })
+        }
+        ["del", destination] if is_valid_route_destination(destination) => {
+            Some(RouteCommand::Del(destination.to_string()))
+        }
+        ["get", addr] if is_valid_ipv4(addr) => Some(RouteCommand::Get(addr.to_string())),
+        _ => None,
+    }
+}
+
+fn parse_watch_args<'a>(mut parts: impl Iterator<Item = &'a str>, raw: &str) -> Command {
+    let mut interval_secs = DEFAULT_WATCH_INTERVAL_SECS;
+    let mut words = Vec::new();
+    while let Some(part) = parts.next() {
+        if part == "-n" && words.is_empty() {
+            match parts.next().and_then(|value| value.parse::<u32>().ok()) {
+                Some(value) if value > 0 => interval_secs = value,
+                _ => return Command::Unknown(raw.to_string()),
+            }
+        } else {
+            words.push(part);
+        }
+    }
+    if words.is_empty() {
+        return Command::Unknown(raw.to_string());
+    }
+    Command::Watch {
+        interval_secs,
+        command: words.join(" "),
+    }
+}
+
+fn split_flag<'a>(parts: impl Iterator<Item = &'a str>, names: &[&str]) -> (bool, String) {
+    let mut set = false;
+    let mut words = Vec::new();
+    for part in parts {
+        if names.contains(&part) {
+            set = true;
+        } else {
+            words.push(part);
+        }
+    }
+    (set, words.join(" "))
+}
+
+fn parse_rm_args<'a>(parts: impl Iterator<Item = &'a str>, raw: &str) -> Command {
+    let mut recursive = false;
+    let mut force = false;
+    let mut words = Vec::new();
+    for part in parts {
+        let is_flag = words.is_empty()
+            && (part == "--force"
+                || (part.len() > 1
+                    && part.starts_with('-')
+                    && part[1..].chars().all(|ch| matches!(ch, 'r' | 'R' | 'f'))));
+        if is_flag {
+            recursive |= part.contains(['r', 'R']);
+            force |= part == "--force" || part.contains('f');
+        } else {
+            words.push(part);
+        }
+    }
+    let path = words.join(" ");
+    if path.is_empty() {
+        Command::Unknown(raw.to_string())
+    } else if recursive {
+        Command::RmRecursive { path, force }
+    } else {
+        Command::Rm(path)
+    }
+}
+
+fn parse_log_args<'a>(mut parts: impl Iterator<Item = &'a str>, raw: &str) -> Command {
+    match parts.next() {
+        Some("tail") => {}
+        Some("level") => {
+            let args = parts.collect::<Vec<&str>>().join(" ");
+            return if args.is_empty() {
+                Command::LogLevel(None)
+            } else {
+                Command::LogLevel(Some(args))
+            };
+        }
+        _ => return Command::Unknown(raw.to_string()),
+    }
+    let mut follow = false;
+    let mut lines = None;
+    while let Some(part) = parts.next() {
+        match part {
+            "-f" | "--follow" => follow = true,
+            "-n" | "--lines" => match parts.next().and_then(|value| value.parse::<usize>().ok()) {
+                Some(value) => lines = Some(value),
+                None => return Command::Unknown(raw.to_string()),
+            },
+            _ => return Command::Unknown(raw.to_string()),
+        }
+    }
+    Command::LogTail { follow, lines }
+}
+
 /// Converts a parsed command into the IPC wire representation.
 pub fn to_ipc(command: &Command) -> Option<shell_protocol::ShellCommand> {
     match command {
@@ -477,7 +1356,10 @@ pub fn to_ipc(command: &Command) -> Option<shell_protocol::ShellCommand> {
         Command::Lsmod => Some(shell_protocol::ShellCommand::Lsmod),
         Command::Start(name) => Some(shell_protocol::ShellCommand::Start(name.clone())),
         Command::Stop(name) => Some(shell_protocol::ShellCommand::Stop(name.clone())),
-        Command::LogTail => Some(shell_protocol::ShellCommand::LogTail),
+        Command::LogTail { follow, lines } => Some(shell_protocol::ShellCommand::LogTail {
+            follow: *follow,
+            lines: lines.map(|lines| u32::try_from(lines).unwrap_or(u32::MAX)),
+        }),
         Command::Help(topic) => Some(shell_protocol::ShellCommand::Help(topic.clone())),
         Command::Catalog {
             slot,
@@ -489,14 +1371,33 @@ pub fn to_ipc(command: &Command) -> Option<shell_protocol::ShellCommand> {
         Command::PieceCheck(name) => {
             Some(shell_protocol::ShellCommand::PieceCheck(name.clone()))
         }
-        Command::Ip(args) => Some(shell_protocol::ShellCommand::Ip(args.clone())),
-        Command::Route(args) => Some(shell_protocol::ShellCommand::Route(args.clone())),
+        Command::PieceCheckAll => Some(shell_protocol::ShellCommand::PieceCheckAll),
+        Command::Status(name) => Some(shell_protocol::ShellCommand::Status(name.clone())),
+        Command::BootReport => Some(shell_protocol::ShellCommand::BootReport),
+        Command::Resolve(name) => Some(shell_protocol::ShellCommand::Resolve(name.clone())),
+        Command::Ping(args) => Some(shell_protocol::ShellCommand::Ping(args.clone())),
+        Command::Fw(args) => Some(shell_protocol::ShellCommand::Fw(args.clone())),
+        Command::Dhcpd(args) => Some(shell_protocol::ShellCommand::Dhcpd(args.clone())),
+        Command::PieceRuntime(name) => {
+            Some(shell_protocol::ShellCommand::PieceRuntime(name.clone()))
+        }
+        Command::Ip(ip) => Some(shell_protocol::ShellCommand::Ip(ip.clone())),
+        Command::Route(route) => Some(shell_protocol::ShellCommand::Route(route.clone())),
         Command::Mount(args) => Some(shell_protocol::ShellCommand::Mount(args.clone())),
-        Command::Df(path) => Some(shell_protocol::ShellCommand::Df(path.clone())),
-        Command::Du(path) => Some(shell_protocol::ShellCommand::Du(path.clone())),
+        Command::Df { path, human } => Some(shell_protocol::ShellCommand::Df {
+            path: path.clone(),
+            human: *human,
+        }),
+        Command::Du { path, human } => Some(shell_protocol::ShellCommand::Du {
+            path: path.clone(),
+            human: *human,
+        }),
         Command::MarketScan => Some(shell_protocol::ShellCommand::MarketScan),
         Command::Install(name) => Some(shell_protocol::ShellCommand::Install(name.clone())),
-        Command::Remove(name) => Some(shell_protocol::ShellCommand::Remove(name.clone())),
+        Command::Remove { module, force } => Some(shell_protocol::ShellCommand::Remove {
+            module: module.clone(),
+            force: *force,
+        }),
         Command::Setup => Some(shell_protocol::ShellCommand::Setup),
         Command::Login(user) => Some(shell_protocol::ShellCommand::Login(user.clone())),
         Command::Logout => Some(shell_protocol::ShellCommand::Logout),
@@ -525,23 +1426,91 @@ pub fn to_ipc(command: &Command) -> Option<shell_protocol::ShellCommand> {
             contents: contents.clone(),
         }),
         Command::Rm(path) => Some(shell_protocol::ShellCommand::Rm(path.clone())),
-        Command::RmRecursive(path) => Some(shell_protocol::ShellCommand::RmRecursive(path.clone())),
-        Command::Slots => Some(shell_protocol::ShellCommand::Slots),
+        Command::RmRecursive { path, force } => Some(shell_protocol::ShellCommand::RmRecursive {
+            path: path.clone(),
+            force: *force,
+        }),
+        Command::Slots { verbose } => {
+            Some(shell_protocol::ShellCommand::Slots { verbose: *verbose })
+        }
         Command::Plug {
             slot,
             module,
             dry_run,
             swap,
+            override_lock,
         } => Some(shell_protocol::ShellCommand::Plug {
             slot: slot.clone(),
             module: module.clone(),
             dry_run: *dry_run,
             swap: *swap,
+            override_lock: *override_lock,
+        }),
+        Command::Unplug {
+            slot,
+            force,
+            override_lock,
+        } => Some(shell_protocol::ShellCommand::Unplug {
+            slot: slot.clone(),
+            force: *force,
+            override_lock: *override_lock,
         }),
-        Command::Unplug(slot) => Some(shell_protocol::ShellCommand::Unplug(slot.clone())),
-        Command::Graph => Some(shell_protocol::ShellCommand::Graph),
+        Command::Graph { dot } => Some(shell_protocol::ShellCommand::Graph { dot: *dot }),
         Command::Sysinfo => Some(shell_protocol::ShellCommand::Sysinfo),
-        Command::Unknown(_) => None,
+        Command::Clip(text) => Some(shell_protocol::ShellCommand::Clip(text.clone())),
+        Command::Time(command) => Some(shell_protocol::ShellCommand::Time(command.clone())),
+        Command::Metrics(args) => Some(shell_protocol::ShellCommand::Metrics(args.clone())),
+        Command::Wc(path) => Some(shell_protocol::ShellCommand::Wc(path.clone())),
+        Command::Stat(path) => Some(shell_protocol::ShellCommand::Stat(path.clone())),
+        Command::Apply { path, dry_run } => Some(shell_protocol::ShellCommand::Apply {
+            path: path.clone(),
+            dry_run: *dry_run,
+        }),
+        Command::Checksum(path) => Some(shell_protocol::ShellCommand::Checksum(path.clone())),
+        Command::Autoplug { dry_run } => {
+            Some(shell_protocol::ShellCommand::Autoplug { dry_run: *dry_run })
+        }
+        Command::Find { path, name } => Some(shell_protocol::ShellCommand::Find {
+            path: path.clone(),
+            name: name.clone(),
+        }),
+        Command::Ln { target, link } => Some(shell_protocol::ShellCommand::Ln {
+            target: target.clone(),
+            link: link.clone(),
+        }),
+        Command::Chsh { user, shell } => Some(shell_protocol::ShellCommand::Chsh {
+            user: user.clone(),
+            shell: shell.clone(),
+        }),
+        Command::Chmod { mode, path } => Some(shell_protocol::ShellCommand::Chmod {
+            mode: mode.clone(),
+            path: path.clone(),
+        }),
+        Command::Chown { user, path } => Some(shell_protocol::ShellCommand::Chown {
+            user: user.clone(),
+            path: path.clone(),
+        }),
+        Command::Gpu(args) => Some(shell_protocol::ShellCommand::Gpu(args.clone())),
+        Command::Container(args) => {
+            Some(shell_protocol::ShellCommand::Container(args.clone()))
+        }
+        Command::Http(args) => Some(shell_protocol::ShellCommand::Http(args.clone())),
+        Command::Echo(text) => Some(shell_protocol::ShellCommand::Echo(text.clone())),
+        Command::Shutdown => Some(shell_protocol::ShellCommand::Shutdown),
+        Command::Set(args) => Some(shell_protocol::ShellCommand::Set(args.clone())),
+        Command::WebConsole(args) => Some(shell_protocol::ShellCommand::WebConsole(args.clone())),
+        Command::LogLevel(args) => Some(shell_protocol::ShellCommand::LogLevel(args.clone())),
+        Command::Notifications { clear } => {
+            Some(shell_protocol::ShellCommand::Notifications { clear: *clear })
+        }
+        Command::Watch {
+            interval_secs,
+            command,
+        } => Some(shell_protocol::ShellCommand::Watch {
+            interval_secs: *interval_secs,
+            command: command.clone(),
+        }),
+        Command::Describe | Command::Sequence(_) | Command::Unknown(_) => None,
     }
 }
 
@@ -552,7 +1521,10 @@ pub fn from_ipc(command: shell_protocol::ShellCommand) -> Command {
         shell_protocol::ShellCommand::Lsmod => Command::Lsmod,
         shell_protocol::ShellCommand::Start(name) => Command::Start(name),
         shell_protocol::ShellCommand::Stop(name) => Command::Stop(name),
-        shell_protocol::ShellCommand::LogTail => Command::LogTail,
+        shell_protocol::ShellCommand::LogTail { follow, lines } => Command::LogTail {
+            follow,
+            lines: lines.map(|lines| lines as usize),
+        },
         shell_protocol::ShellCommand::Help(topic) => Command::Help(topic),
         shell_protocol::ShellCommand::Catalog {
             slot,
@@ -562,14 +1534,22 @@ pub fn from_ipc(command: shell_protocol::ShellCommand) -> Command {
             verified_only,
         },
         shell_protocol::ShellCommand::PieceCheck(name) => Command::PieceCheck(name),
-        shell_protocol::ShellCommand::Ip(args) => Command::Ip(args),
-        shell_protocol::ShellCommand::Route(args) => Command::Route(args),
+        shell_protocol::ShellCommand::PieceCheckAll => Command::PieceCheckAll,
+        shell_protocol::ShellCommand::Status(name) => Command::Status(name),
+        shell_protocol::ShellCommand::BootReport => Command::BootReport,
+        shell_protocol::ShellCommand::Resolve(name) => Command::Resolve(name),
+        shell_protocol::ShellCommand::Ping(args) => Command::Ping(args),
+        shell_protocol::ShellCommand::Fw(args) => Command::Fw(args),
+        shell_protocol::ShellCommand::Dhcpd(args) => Command::Dhcpd(args),
+        shell_protocol::ShellCommand::PieceRuntime(name) => Command::PieceRuntime(name),
+        shell_protocol::ShellCommand::Ip(ip) => Command::Ip(ip),
+        shell_protocol::ShellCommand::Route(route) => Command::Route(route),
         shell_protocol::ShellCommand::Mount(args) => Command::Mount(args),
-        shell_protocol::ShellCommand::Df(path) => Command::Df(path),
-        shell_protocol::ShellCommand::Du(path) => Command::Du(path),
+        shell_protocol::ShellCommand::Df { path, human } => Command::Df { path, human },
+        shell_protocol::ShellCommand::Du { path, human } => Command::Du { path, human },
         shell_protocol::ShellCommand::MarketScan => Command::MarketScan,
         shell_protocol::ShellCommand::Install(name) => Command::Install(name),
-        shell_protocol::ShellCommand::Remove(name) => Command::Remove(name),
+        shell_protocol::ShellCommand::Remove { module, force } => Command::Remove { module, force },
         shell_protocol::ShellCommand::Setup => Command::Setup,
         shell_protocol::ShellCommand::Login(user) => Command::Login(user),
         shell_protocol::ShellCommand::Logout => Command::Logout,
@@ -592,88 +1572,732 @@ pub fn from_ipc(command: shell_protocol::ShellCommand) -> Command {
             Command::Write { path, contents }
         }
         shell_protocol::ShellCommand::Rm(path) => Command::Rm(path),
-        shell_protocol::ShellCommand::RmRecursive(path) => Command::RmRecursive(path),
-        shell_protocol::ShellCommand::Slots => Command::Slots,
+        shell_protocol::ShellCommand::RmRecursive { path, force } => {
+            Command::RmRecursive { path, force }
+        }
+        shell_protocol::ShellCommand::Slots { verbose } => Command::Slots { verbose },
         shell_protocol::ShellCommand::Plug {
             slot,
             module,
             dry_run,
             swap,
+            override_lock,
         } => Command::Plug {
             slot,
             module,
             dry_run,
             swap,
+            override_lock,
+        },
+        shell_protocol::ShellCommand::Unplug {
+            slot,
+            force,
+            override_lock,
+        } => Command::Unplug {
+            slot,
+            force,
+            override_lock,
         },
-        shell_protocol::ShellCommand::Unplug(slot) => Command::Unplug(slot),
-        shell_protocol::ShellCommand::Graph => Command::Graph,
+        shell_protocol::ShellCommand::Graph { dot } => Command::Graph { dot },
         shell_protocol::ShellCommand::Sysinfo => Command::Sysinfo,
+        shell_protocol::ShellCommand::Clip(text) => Command::Clip(text),
+        shell_protocol::ShellCommand::Time(command) => Command::Time(command),
+        shell_protocol::ShellCommand::Metrics(args) => Command::Metrics(args),
+        shell_protocol::ShellCommand::Wc(path) => Command::Wc(path),
+        shell_protocol::ShellCommand::Stat(path) => Command::Stat(path),
+        shell_protocol::ShellCommand::Apply { path, dry_run } => Command::Apply { path, dry_run },
+        shell_protocol::ShellCommand::Checksum(path) => Command::Checksum(path),
+        shell_protocol::ShellCommand::Autoplug { dry_run } => Command::Autoplug { dry_run },
+        shell_protocol::ShellCommand::Find { path, name } => Command::Find { path, name },
+        shell_protocol::ShellCommand::Ln { target, link } => Command::Ln { target, link },
+        shell_protocol::ShellCommand::Chsh { user, shell } => Command::Chsh { user, shell },
+        shell_protocol::ShellCommand::Chmod { mode, path } => Command::Chmod { mode, path },
+        shell_protocol::ShellCommand::Chown { user, path } => Command::Chown { user, path },
+        shell_protocol::ShellCommand::Gpu(args) => Command::Gpu(args),
+        shell_protocol::ShellCommand::Container(args) => Command::Container(args),
+        shell_protocol::ShellCommand::Http(args) => Command::Http(args),
+        shell_protocol::ShellCommand::Echo(text) => Command::Echo(text),
+        shell_protocol::ShellCommand::Shutdown => Command::Shutdown,
+        shell_protocol::ShellCommand::Set(args) => Command::Set(args),
+        shell_protocol::ShellCommand::WebConsole(args) => Command::WebConsole(args),
+        shell_protocol::ShellCommand::LogLevel(args) => Command::LogLevel(args),
+        shell_protocol::ShellCommand::Notifications { clear } => Command::Notifications { clear },
+        shell_protocol::ShellCommand::Watch {
+            interval_secs,
+            command,
+        } => Command::Watch {
+            interval_secs,
+            command,
+        },
+    }
+}
+
+/// Usage metadata for one shell command, rendered by `help <command>`.
+struct CommandHelp {
+    name: &'static str,
+    summary: &'static str,
+    usage: &'static [&'static str],
+    flags: &'static [(&'static str, &'static str)],
+    examples: &'static [&'static str],
+}
+
+const fn help_entry(
+    name: &'static str,
+    summary: &'static str,
+    usage: &'static [&'static str],
+    flags: &'static [(&'static str, &'static str)],
+    examples: &'static [&'static str],
+) -> CommandHelp {
+    CommandHelp {
+        name,
+        summary,
+        usage,
+        flags,
+        examples,
     }
 }
 
+/// Every shell command in the order `help` lists them.
+static COMMAND_HELP: &[CommandHelp] = &[
+    help_entry(
+        "ps",
+        "list running processes",
+        &["ps [--tree]"],
+        &[("--tree", "show processes nested under their parents")],
+        &["ps", "ps --tree"],
+    ),
+    help_entry(
+        "lsmod",
+        "list registered modules and their state",
+        &["lsmod"],
+        &[],
+        &["lsmod"],
+    ),
+    help_entry(
+        "start",
+        "start a module, waiting for its required paths if needed",
+        &["start <module>"],
+        &[],
+        &["start text-editor"],
+    ),
+    help_entry(
+        "stop",
+        "stop a running or waiting module",
+        &["stop <module>"],
+        &[],
+        &["stop text-editor"],
+    ),
+    help_entry(
+        "status",
+        "show a module's state and failure diagnostics",
+        &["status <module>"],
+        &[],
+        &["status net-manager"],
+    ),
+    help_entry(
+        "boot-report",
+        "show module start times and the boot critical path",
+        &["boot-report"],
+        &[],
+        &["boot-report"],
+    ),
+    help_entry(
+        "catalog",
+        "list installable modules",
+        &["catalog [--slot <slot>@<ver>] [--verified]"],
+        &[
+            (
+                "--slot <slot>@<ver>",
+                "only show modules that fill the slot",
+            ),
+            ("--verified", "only show modules with a verified signature"),
+        ],
+        &["catalog", "catalog --slot ruzzle.slot.editor@1 --verified"],
+    ),
+    help_entry(
+        "piece",
+        "check a piece manifest before installing it",
+        &["piece check [--runtime] <name>", "piece check --all"],
+        &[
+            (
+                "--runtime",
+                "show the syscall filter profile and logged violations",
+            ),
+            (
+                "--all",
+                "validate every slot: unfilled, incompatible and dangling providers",
+            ),
+        ],
+        &[
+            "piece check vim-piece",
+            "piece check --runtime vim-piece",
+            "piece check --all",
+        ],
+    ),
+    help_entry(
+        "ip",
+        "show or configure network interfaces",
+        &[
+            "ip [addr|link]",
+            "ip -s [iface]",
+            "ip link add|del <iface>",
+            "ip link set <iface> up|down",
+            "ip link set <iface> address <mac>",
+            "ip addr add <iface> <addr[/prefix]>",
+            "ip addr del <iface>",
+        ],
+        &[("-s", "show packet, byte and error counters")],
+        &[
+            "ip",
+            "ip -s eth0",
+            "ip link set eth0 up",
+            "ip addr add eth0 10.0.2.15/24",
+        ],
+    ),
+    help_entry(
+        "route",
+        "show or configure network routes",
+        &[
+            "route",
+            "route add <dest> [via <gateway>] dev <iface> [metric <n>]",
+            "route del <dest>",
+            "route get <ipv4>",
+        ],
+        &[],
+        &[
+            "route",
+            "route add default via 10.0.2.2 dev eth0",
+            "route get 10.0.2.2",
+        ],
+    ),
+    help_entry(
+        "resolve",
+        "look up a host name in /etc/hosts, the DNS cache, then DNS servers",
+        &["resolve <name>"],
+        &[],
+        &["resolve localhost", "resolve ruzzle.dev"],
+    ),
+    help_entry(
+        "ping",
+        "send ICMP echo requests and report round-trip times in timer ticks",
+        &["ping [-c <count>] <ipv4>"],
+        &[("-c <count>", "number of requests, 1-100 (default 4)")],
+        &["ping 127.0.0.1", "ping -c 1 10.0.2.2"],
+    ),
+    help_entry(
+        "fw",
+        "list or edit the firewall; the first matching rule decides",
+        &[
+            "fw [list]",
+            "fw add <allow|deny> <in|out> [tcp|udp|icmp|any] [<ipv4>[/prefix]] [port <n>]",
+            "fw del <n>",
+            "fw policy <in|out> <allow|deny>",
+        ],
+        &[],
+        &["fw add deny in tcp port 22", "fw add allow in tcp 10.0.2.0/24 port 22", "fw del 1"],
+    ),
+    help_entry(
+        "dhcpd",
+        "hand out addresses to other guests on a lab network (needs the dhcp-server piece)",
+        &[
+            "dhcpd [status]",
+            "dhcpd start <iface> <first> <last> [lease <secs>] [router <ipv4>] [dns <ipv4>]",
+            "dhcpd stop",
+            "dhcpd leases",
+            "dhcpd release <ipv4>",
+        ],
+        &[],
+        &["dhcpd start eth0 10.0.2.100 10.0.2.199", "dhcpd leases"],
+    ),
+    help_entry(
+        "mount",
+        "show or change mounted filesystems",
+        &["mount [args]"],
+        &[],
+        &["mount"],
+    ),
+    help_entry(
+        "df",
+        "show filesystem usage",
+        &["df [-h] [path]"],
+        &[("-h", "print sizes in K/M/G units")],
+        &["df", "df -h /home"],
+    ),
+    help_entry(
+        "du",
+        "show the bytes used below a path",
+        &["du [-h] <path>"],
+        &[("-h", "print sizes in K/M/G units")],
+        &["du -h /var"],
+    ),
+    help_entry(
+        "market",
+        "refresh the module catalog from the market",
+        &["market scan"],
+        &[],
+        &["market scan"],
+    ),
+    help_entry(
+        "install",
+        "install a module from the catalog",
+        &["install <module>"],
+        &[],
+        &["install vim-piece"],
+    ),
+    help_entry(
+        "remove",
+        "remove an installed module",
+        &["remove [-f|--force] <module>"],
+        &[("-f, --force", "skip the confirmation prompt")],
+        &["remove vim-piece", "remove -f vim-piece"],
+    ),
+    help_entry(
+        "setup",
+        "run the first-boot setup wizard",
+        &["setup"],
+        &[],
+        &["setup"],
+    ),
+    help_entry(
+        "apply",
+        "apply a declarative system manifest, rolling back on failure",
+        &["apply [-n|--dry-run] <file>"],
+        &[("-n, --dry-run", "print the plan without changing anything")],
+        &["apply -n /etc/system.conf", "apply /etc/system.conf"],
+    ),
+    help_entry(
+        "login",
+        "switch to another user",
+        &["login <user>"],
+        &[],
+        &["login alice"],
+    ),
+    help_entry(
+        "logout",
+        "end the current session",
+        &["logout"],
+        &[],
+        &["logout"],
+    ),
+    help_entry(
+        "whoami",
+        "print the current user",
+        &["whoami"],
+        &[],
+        &["whoami"],
+    ),
+    help_entry("users", "list user accounts", &["users"], &[], &["users"]),
+    help_entry(
+        "useradd",
+        "create a user account",
+        &["useradd <user>"],
+        &[],
+        &["useradd alice"],
+    ),
+    help_entry(
+        "chsh",
+        "list login shells or change one",
+        &["chsh [user] [shell]"],
+        &[],
+        &["chsh", "chsh alice /bin/ruzzle-shell"],
+    ),
+    help_entry(
+        "pwd",
+        "print the working directory",
+        &["pwd"],
+        &[],
+        &["pwd"],
+    ),
+    help_entry(
+        "ls",
+        "list a directory",
+        &["ls [path]"],
+        &[],
+        &["ls", "ls /etc"],
+    ),
+    help_entry(
+        "cd",
+        "change the working directory",
+        &["cd <path>"],
+        &[],
+        &["cd /home"],
+    ),
+    help_entry(
+        "mkdir",
+        "create a directory",
+        &["mkdir <path>", "mkdir -p <path>"],
+        &[(
+            "-p",
+            "create missing parents and ignore existing directories",
+        )],
+        &["mkdir docs", "mkdir -p /srv/www/static"],
+    ),
+    help_entry(
+        "touch",
+        "create an empty file",
+        &["touch <path>"],
+        &[],
+        &["touch notes.txt"],
+    ),
+    help_entry(
+        "cat",
+        "print a file",
+        &["cat <path>"],
+        &[],
+        &["cat /etc/ruzzle.conf"],
+    ),
+    help_entry(
+        "wc",
+        "count lines, words and bytes in a file",
+        &["wc <path>"],
+        &[],
+        &["wc notes.txt"],
+    ),
+    help_entry(
+        "stat",
+        "show type, size, owner and mode of a path",
+        &["stat <path>"],
+        &[],
+        &["stat /etc/ruzzle.conf"],
+    ),
+    help_entry(
+        "checksum",
+        "print the CRC-32 of a file's contents",
+        &["checksum <path>"],
+        &[],
+        &["checksum /etc/ruzzle.conf", "checksum /pieces/*.bin"],
+    ),
+    help_entry(
+        "find",
+        "list paths below a directory",
+        &["find <path> [-name <glob>]"],
+        &[("-name <glob>", "only list names matching the glob")],
+        &["find /etc", "find / -name *.conf"],
+    ),
+    help_entry(
+        "edit",
+        "open a file in the editor",
+        &["edit <path>"],
+        &[],
+        &["edit notes.txt"],
+    ),
+    help_entry(
+        "vim",
+        "open a file in the editor",
+        &["vim <path>"],
+        &[],
+        &["vim notes.txt"],
+    ),
+    help_entry(
+        "cp",
+        "copy a file or directory",
+        &["cp <src> <dst>", "cp -r <src> <dst>"],
+        &[("-r", "copy directories recursively")],
+        &["cp notes.txt notes.bak", "cp -r docs docs.bak"],
+    ),
+    help_entry(
+        "mv",
+        "move or rename a path",
+        &["mv <src> <dst>"],
+        &[],
+        &["mv notes.txt docs/"],
+    ),
+    help_entry(
+        "ln",
+        "create a symbolic link",
+        &["ln -s <target> <link>"],
+        &[("-s", "create a symbolic link (required)")],
+        &["ln -s /etc/ruzzle.conf conf"],
+    ),
+    help_entry(
+        "chmod",
+        "change the mode bits of a path",
+        &["chmod <mode> <path>"],
+        &[],
+        &["chmod 644 notes.txt"],
+    ),
+    help_entry(
+        "chown",
+        "change the owner and/or group of a path",
+        &["chown <user>[:<group>] <path>"],
+        &[],
+        &["chown alice notes.txt", "chown alice:staff notes.txt", "chown :staff notes.txt"],
+    ),
+    help_entry(
+        "write",
+        "replace a file's contents with text",
+        &["write <path> <text>"],
+        &[],
+        &["write notes.txt hello world"],
+    ),
+    help_entry(
+        "rm",
+        "remove a file or directory",
+        &["rm <path>", "rm -r[f] <path>"],
+        &[
+            ("-r", "remove directories recursively"),
+            ("-f", "skip the confirmation prompt"),
+        ],
+        &["rm notes.txt", "rm -rf docs.bak"],
+    ),
+    help_entry(
+        "slots",
+        "list puzzle slots and their providers",
+        &["slots [--verbose|-v]"],
+        &[("-v, --verbose", "show each slot's description, category and tags")],
+        &["slots", "slots -v"],
+    ),
+    help_entry(
+        "plug",
+        "plug a module into a puzzle slot",
+        &["plug [--dry-run|-n] [--swap|-s] [--override] <slot> <module>"],
+        &[
+            ("-n, --dry-run", "check compatibility without plugging"),
+            (
+                "-s, --swap",
+                "replace the current provider, rolling back on failure",
+            ),
+            ("--override", "swap a locked slot (admin only)"),
+        ],
+        &[
+            "plug ruzzle.slot.editor@1 vim-piece",
+            "plug --swap ruzzle.slot.editor@1 note-piece",
+        ],
+    ),
+    help_entry(
+        "unplug",
+        "remove the provider from a puzzle slot",
+        &["unplug [-f|--force] [--override] <slot>"],
+        &[
+            (
+                "-f, --force",
+                "skip the confirmation prompt for required slots",
+            ),
+            ("--override", "unplug a locked slot (admin only)"),
+        ],
+        &["unplug ruzzle.slot.editor@1"],
+    ),
+    help_entry(
+        "autoplug",
+        "fill empty required slots from installed and catalog modules",
+        &["autoplug [--dry-run|-n]"],
+        &[("-n, --dry-run", "show the plug plan without applying it")],
+        &["autoplug -n", "autoplug"],
+    ),
+    help_entry(
+        "graph",
+        "show the slot dependency graph",
+        &["graph [--dot]"],
+        &[("--dot", "print Graphviz DOT to render on the host")],
+        &["graph", "graph --dot"],
+    ),
+    help_entry(
+        "sysinfo",
+        "show system information",
+        &["sysinfo"],
+        &[],
+        &["sysinfo"],
+    ),
+    help_entry(
+        "gpu",
+        "work with GPU buffers",
+        &["gpu [show|new|add|matmul|save|load] [args]"],
+        &[],
+        &["gpu show"],
+    ),
+    help_entry(
+        "container",
+        "manage containers",
+        &["container [list|create|start|stop|rm|checkpoint|restore] [args]"],
+        &[],
+        &["container list", "container create web"],
+    ),
+    help_entry(
+        "http",
+        "inspect and control the HTTP server",
+        &["http [top [n]|routes|route <method> <path> <status> [body]|request <method> <path> [body]|accept <method> <path>|limits [header body]|start|stop]"],
+        &[],
+        &["http top 5", "http route GET /health 200 ok"],
+    ),
+    help_entry(
+        "webconsole",
+        "manage the web console",
+        &["webconsole [status|install|send <line>]"],
+        &[],
+        &["webconsole status"],
+    ),
+    help_entry(
+        "echo",
+        "print text with $NAME and escape expansion",
+        &["echo [text]"],
+        &[],
+        &["echo hello $USER", "echo $?"],
+    ),
+    help_entry(
+        "set",
+        "show or change shell options",
+        &[
+            "set [color|bell|timing=on|off]",
+            "set prompt=<template>",
+            "set net.profile=<name>",
+        ],
+        &[],
+        &[
+            "set",
+            "set timing=on",
+            "set prompt={user}@{hostname}:{cwd}$",
+            "set net.profile=eth0",
+        ],
+    ),
+    help_entry(
+        "shutdown",
+        "drain services and halt",
+        &["shutdown"],
+        &[],
+        &["shutdown"],
+    ),
+    help_entry(
+        "describe",
+        "describe the shell protocol",
+        &["describe"],
+        &[],
+        &["describe"],
+    ),
+    help_entry(
+        "clip",
+        "show or set the clipboard",
+        &["clip [text]"],
+        &[],
+        &["clip", "clip hello"],
+    ),
+    help_entry(
+        "time",
+        "run a command and report how long it took",
+        &["time <command>"],
+        &[],
+        &["time ls /"],
+    ),
+    help_entry(
+        "watch",
+        "re-run a command on a timer until a key is pressed",
+        &["watch [-n seconds] <command>"],
+        &[("-n seconds", "seconds between refreshes")],
+        &["watch ps", "watch -n 5 df -h"],
+    ),
+    help_entry(
+        "metrics",
+        "show or control metric collection",
+        &["metrics [on|off|reset]"],
+        &[],
+        &["metrics", "metrics reset"],
+    ),
+    help_entry(
+        "log",
+        "read the kernel log or change log levels",
+        &[
+            "log tail [-f|--follow] [-n <lines>]",
+            "log level [<module> <trace|debug|info|warn|error>]",
+        ],
+        &[
+            (
+                "-f, --follow",
+                "keep printing new lines until a key is pressed",
+            ),
+            ("-n <lines>", "number of lines to print"),
+        ],
+        &["log tail -n 20", "log level net debug"],
+    ),
+    help_entry(
+        "notifications",
+        "list notification history",
+        &["notifications [clear]"],
+        &[],
+        &["notifications", "notifications clear"],
+    ),
+    help_entry(
+        "help",
+        "show commands or usage for one command",
+        &["help [command]", "help slot | help market"],
+        &[],
+        &["help", "help plug"],
+    ),
+];
+
 /// Formats the help text shown by the shell.
 pub fn format_help(topic: Option<&str>) -> String {
     match topic.map(str::trim) {
         None | Some("") => format_help_all(),
         Some("slot") | Some("slots") => format_help_slot(),
         Some("market") => format_help_market(),
-        Some(other) => {
-            let mut out = String::new();
-            out.push_str("unknown help topic: ");
-            out.push_str(other);
+        Some(other) => match find_command_help(other) {
+            Some(entry) => format_command_help(entry),
+            None => {
+                let mut out = String::new();
+                out.push_str("unknown help topic: ");
+                out.push_str(other);
+                out.push('\n');
+                out.push_str(&format_help_all());
+                out
+            }
+        },
+    }
+}
+
+fn find_command_help(topic: &str) -> Option<&'static CommandHelp> {
+    let name = topic.split_whitespace().next()?;
+    COMMAND_HELP.iter().find(|entry| entry.name == name)
+}
+
+fn format_command_help(entry: &CommandHelp) -> String {
+    let mut out = String::new();
+    out.push_str(entry.name);
+    out.push_str(" - ");
+    out.push_str(entry.summary);
+    out.push_str("\nusage:\n");
+    for line in entry.usage {
+        out.push_str("  ");
+        out.push_str(line);
+        out.push('\n');
+    }
+    if !entry.flags.is_empty() {
+        out.push_str("flags:\n");
+        let width = entry
+            .flags
+            .iter()
+            .map(|(flag, _)| flag.len())
+            .max()
+            .unwrap_or(0);
+        for (flag, text) in entry.flags {
+            out.push_str("  ");
+            out.push_str(flag);
+            for _ in flag.len()..width + 2 {
+                out.push(' ');
+            }
+            out.push_str(text);
+            out.push('\n');
+        }
+    }
+    if !entry.examples.is_empty() {
+        out.push_str("examples:\n");
+        for example in entry.examples {
+            out.push_str("  ");
+            out.push_str(example);
             out.push('\n');
-            out.push_str(&format_help_all());
-            out
         }
     }
+    out
 }
 
 fn format_help_all() -> String {
     let mut out = String::new();
     out.push_str("commands:\n");
-    out.push_str("  ps [--tree]\n");
-    out.push_str("  lsmod\n");
-    out.push_str("  start <module>\n");
-    out.push_str("  stop <module>\n");
-    out.push_str("  catalog [--slot <slot>@<ver>] [--verified]\n");
-    out.push_str("  piece check <name>\n");
-    out.push_str("  ip [args]\n");
-    out.push_str("  route [args]\n");
-    out.push_str("  mount [args]\n");
-    out.push_str("  df [path]\n");
-    out.push_str("  du <path>\n");
-    out.push_str("  market scan\n");
-    out.push_str("  install <module>\n");
-    out.push_str("  remove <module>\n");
-    out.push_str("  setup\n");
-    out.push_str("  login <user>\n");
-    out.push_str("  logout\n");
-    out.push_str("  whoami\n");
-    out.push_str("  users\n");
-    out.push_str("  useradd <user>\n");
-    out.push_str("  pwd\n");
-    out.push_str("  ls [path]\n");
-    out.push_str("  cd <path>\n");
-    out.push_str("  mkdir <path>\n");
-    out.push_str("  mkdir -p <path>\n");
-    out.push_str("  touch <path>\n");
-    out.push_str("  cat <path>\n");
-    out.push_str("  edit <path>\n");
-    out.push_str("  vim <path>\n");
-    out.push_str("  cp <src> <dst>\n");
-    out.push_str("  cp -r <src> <dst>\n");
-    out.push_str("  mv <src> <dst>\n");
-    out.push_str("  write <path> <text>\n");
-    out.push_str("  rm <path>\n");
-    out.push_str("  rm -r <path>\n");
-    out.push_str("  slots\n");
-    out.push_str("  plug [--dry-run|-n] [--swap|-s] <slot> <module>\n");
-    out.push_str("  unplug <slot>\n");
-    out.push_str("  graph\n");
-    out.push_str("  sysinfo\n");
-    out.push_str("  log tail\n");
-    out.push_str("  help [command]\n");
-    out.push_str("  help slot | help market\n");
+    for entry in COMMAND_HELP {
+        for line in entry.usage {
+            out.push_str("  ");
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
     out
 }
 
@@ -682,9 +2306,9 @@ fn format_help_slot() -> String {
     out.push_str("slot help:\n");
     out.push_str("  slots\n");
     out.push_str("  plug [--dry-run|-n] [--swap|-s] <slot> <module>\n");
-    out.push_str("  unplug <slot>\n");
+    out.push_str("  unplug [-f|--force] <slot>\n");
     out.push_str("  graph\n");
-    out.push_str("  piece check <name>\n");
+    out.push_str("  piece check [--runtime] <name>\n");
     out
 }
 
@@ -694,11 +2318,49 @@ fn format_help_market() -> String {
     out.push_str("  catalog [--slot <slot>@<ver>] [--verified]\n");
     out.push_str("  market scan\n");
     out.push_str("  install <module>\n");
-    out.push_str("  remove <module>\n");
-    out.push_str("  piece check <name>\n");
+    out.push_str("  remove [-f|--force] <module>\n");
+    out.push_str("  piece check [--runtime] <name>\n");
     out
 }
 
+/// Returns the `y/N` question to ask before a destructive command, if any.
+pub fn confirmation_prompt(
+    command: &Command,
+    is_required_slot: impl Fn(&str) -> bool,
+) -> Option<String> {
+    match command {
+        Command::RmRecursive { path, force: false } => {
+            let mut out = String::from("remove ");
+            out.push_str(path);
+            out.push_str(" and everything under it?");
+            Some(out)
+        }
+        Command::Remove {
+            module,
+            force: false,
+        } => {
+            let mut out = String::from("remove module ");
+            out.push_str(module);
+            out.push('?');
+            Some(out)
+        }
+        Command::Unplug {
+            slot, force: false, ..
+        } if is_required_slot(slot) => {
+            let mut out = String::from("unplug required slot ");
+            out.push_str(slot);
+            out.push('?');
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if a confirmation answer means yes; anything else is no.
+pub fn is_confirmed(answer: &str) -> bool {
+    matches!(answer.trim(), "y" | "Y" | "yes" | "YES" | "Yes")
+}
+
 /// Formats the available module catalog.
 pub fn format_catalog(rows: &[ModuleRow]) -> String {
     let mut out = String::new();
@@ -721,8 +2383,8 @@ pub fn format_catalog(rows: &[ModuleRow]) -> String {
     out
 }
 
-/// Formats the puzzle slot board.
-pub fn format_slots(rows: &[SlotRow]) -> String {
+/// Formats the puzzle slot board, adding slot metadata when `verbose`.
+pub fn format_slots(rows: &[SlotRow], style: Style, verbose: bool) -> String {
     let mut out = String::new();
     out.push_str("puzzle board:\n");
     if rows.is_empty() {
@@ -738,12 +2400,12 @@ pub fn format_slots(rows: &[SlotRow]) -> String {
             optional.push(row.clone());
         }
     }
-    out.push_str(&format_slot_group("REQUIRED", &required));
-    out.push_str(&format_slot_group("OPTIONAL", &optional));
+    out.push_str(&format_slot_group("REQUIRED", &required, style, verbose));
+    out.push_str(&format_slot_group("OPTIONAL", &optional, style, verbose));
     out
 }
 
-fn format_slot_group(label: &str, rows: &[SlotRow]) -> String {
+fn format_slot_group(label: &str, rows: &[SlotRow], style: Style, verbose: bool) -> String {
     if rows.is_empty() {
         let mut out = String::new();
         out.push_str("  ");
@@ -752,29 +2414,45 @@ fn format_slot_group(label: &str, rows: &[SlotRow]) -> String {
         return out;
     }
     let mut lines = Vec::new();
-    lines.push(label.to_string());
+    lines.push((label.len(), label.to_string()));
     for row in rows {
         let status = if row.provider.is_some() { "OK " } else { "EMPTY" };
         let provider = row.provider.as_deref().unwrap_or("<empty>");
         let mut line = String::new();
         line.push('[');
-        line.push_str(status);
+        if row.required && row.provider.is_none() {
+            line.push_str(&style.paint(status, ANSI_RED));
+        } else {
+            line.push_str(status);
+        }
         line.push_str("] ");
         line.push_str(&row.name);
         line.push_str(" -> ");
         line.push_str(provider);
-        lines.push(line);
+        let mut visible = status.len() + row.name.len() + provider.len() + 7;
+        if row.locked {
+            line.push_str(" [locked]");
+            visible += 9;
+        }
+        lines.push((visible, line));
+        if verbose {
+            for detail in slot_details(row) {
+                let mut line = String::from("      ");
+                line.push_str(&detail);
+                lines.push((line.len(), line));
+            }
+        }
     }
-    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    let width = lines.iter().map(|(len, _)| *len).max().unwrap_or(0);
     let mut out = String::new();
     out.push_str("  +");
     out.push_str(&"-".repeat(width + 2));
     out.push_str("+\n");
-    for line in lines {
+    for (len, line) in lines {
         out.push_str("  | ");
         out.push_str(&line);
-        if line.len() < width {
-            out.push_str(&" ".repeat(width - line.len()));
+        if len < width {
+            out.push_str(&" ".repeat(width - len));
         }
         out.push_str(" |\n");
     }
@@ -784,6 +2462,72 @@ fn format_slot_group(label: &str, rows: &[SlotRow]) -> String {
     out
 }
 
+fn slot_details(row: &SlotRow) -> Vec<String> {
+    let mut details = Vec::new();
+    if let Some(description) = &row.description {
+        details.push(description.clone());
+    }
+    let mut meta = String::new();
+    if let Some(category) = &row.category {
+        meta.push_str("category: ");
+        meta.push_str(category);
+    }
+    if !row.tags.is_empty() {
+        if !meta.is_empty() {
+            meta.push_str("; ");
+        }
+        meta.push_str("tags: ");
+        meta.push_str(&row.tags.join(", "));
+    }
+    if !meta.is_empty() {
+        details.push(meta);
+    }
+    details
+}
+
+/// Formats an `autoplug` plan, the slots it cannot fill, and priority ties
+/// given as (slot, tied modules with the picked one first).
+pub fn format_plug_plan(
+    rows: &[PlanRow],
+    unfilled: &[String],
+    ties: &[(String, Vec<String>)],
+) -> String {
+    let mut out = String::new();
+    out.push_str("plug plan:\n");
+    if rows.is_empty() {
+        out.push_str("  <nothing to plug>\n");
+    }
+    for (index, row) in rows.iter().enumerate() {
+        out.push_str("  ");
+        out.push_str(&(index + 1).to_string());
+        out.push_str(". ");
+        out.push_str(&row.slot);
+        out.push_str(" <- ");
+        out.push_str(&row.module);
+        out.push_str(" (");
+        out.push_str(&row.source);
+        out.push_str(")\n");
+    }
+    if !unfilled.is_empty() {
+        out.push_str("  unfilled: ");
+        out.push_str(&unfilled.join(", "));
+        out.push('\n');
+    }
+    for (slot, modules) in ties {
+        out.push_str("  tie: ");
+        out.push_str(slot);
+        out.push_str(" between ");
+        out.push_str(&modules.join(", "));
+        if let Some(picked) = modules.first() {
+            out.push_str(" (picked ");
+            out.push_str(picked);
+            out.push(')');
+        }
+        out.push('\n');
+    }
+    out
+}
+
 /// Formats a dependency graph view.
 pub fn format_graph(rows: &[GraphRow]) -> String {
     let mut out = String::new();
@@ -798,28 +2542,169 @@ pub fn format_graph(rows: &[GraphRow]) -> String {
         out.push_str("] ");
         out.push_str(&row.name);
         out.push('\n');
-        if row.depends.is_empty() {
+        let edges: Vec<(&String, bool)> = row
+            .depends
+            .iter()
+            .map(|dep| (dep, false))
+            .chain(row.wants.iter().map(|want| (want, true)))
+            .collect();
+        if edges.is_empty() {
             out.push_str("     `- <none>\n");
         } else {
-            for (index, dep) in row.depends.iter().enumerate() {
-                let branch = if index + 1 == row.depends.len() {
-                    "`-"
-                } else {
-                    "|-"
-                };
+            for (index, (dep, optional)) in edges.iter().enumerate() {
+                let branch = if index + 1 == edges.len() { '`' } else { '|' };
                 out.push_str("     ");
-                out.push_str(branch);
+                out.push(branch);
+                out.push(if *optional { '.' } else { '-' });
                 out.push(' ');
                 out.push_str(dep);
+                if *optional {
+                    out.push_str(" (wants)");
+                }
                 out.push('\n');
             }
         }
+        if !row.required_by.is_empty() {
+            out.push_str("     required by: ");
+            out.push_str(&row.required_by.join(", "));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Formats the dependency graph and slot providers as Graphviz DOT.
+///
+/// `slots` pairs each slot with its plugged providers; wants edges are dashed.
+pub fn format_graph_dot(rows: &[GraphRow], slots: &[(String, Vec<String>)]) -> String {
+    let mut out = String::new();
+    out.push_str("digraph ruzzle {\n");
+    out.push_str("  rankdir=LR;\n");
+    for row in rows {
+        out.push_str("  ");
+        push_dot_id(&mut out, &row.name);
+        let mut label = row.name.clone();
+        label.push_str(" (");
+        label.push_str(&row.state);
+        label.push(')');
+        out.push_str(" [label=");
+        push_dot_id(&mut out, &label);
+        out.push_str("];\n");
+        for dep in &row.depends {
+            out.push_str("  ");
+            push_dot_id(&mut out, &row.name);
+            out.push_str(" -> ");
+            push_dot_id(&mut out, dep);
+            out.push_str(";\n");
+        }
+        for want in &row.wants {
+            out.push_str("  ");
+            push_dot_id(&mut out, &row.name);
+            out.push_str(" -> ");
+            push_dot_id(&mut out, want);
+            out.push_str(" [style=dashed];\n");
+        }
+    }
+    for (slot, providers) in slots {
+        out.push_str("  ");
+        push_dot_id(&mut out, slot);
+        out.push_str(" [shape=box];\n");
+        for provider in providers {
+            out.push_str("  ");
+            push_dot_id(&mut out, slot);
+            out.push_str(" -> ");
+            push_dot_id(&mut out, provider);
+            out.push_str(" [color=blue];\n");
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Appends `id` as a quoted DOT identifier.
+fn push_dot_id(out: &mut String, id: &str) {
+    out.push('"');
+    for ch in id.chars() {
+        if ch == '"' || ch == '\\' {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out.push('"');
+}
+
+/// Formats a module's state and failure diagnostics.
+pub fn format_module_status(row: &ModuleStatusRow) -> String {
+    let mut out = String::from("status: ");
+    out.push_str(&row.name);
+    out.push_str("\n  state: ");
+    out.push_str(&row.state);
+    out.push_str("\n  failures: ");
+    out.push_str(&row.failures.to_string());
+    out.push_str(" consecutive\n  last error: ");
+    out.push_str(row.last_error.as_deref().unwrap_or("<none>"));
+    out.push('\n');
+    if let Some(tick) = row.last_failure {
+        out.push_str("  last failure: ");
+        out.push_str(&tick.to_string());
+        out.push_str(" ms\n");
+    }
+    out
+}
+
+/// Formats boot timing: total time, slowest modules and the critical path.
+pub fn format_boot_report(
+    total_us: u64,
+    modules: &[BootTimingRow],
+    critical_path: &[BootTimingRow],
+) -> String {
+    let mut out = String::from("boot: ");
+    push_micros_as_ms(&mut out, total_us);
+    out.push_str("\nslowest modules:\n");
+    if modules.is_empty() {
+        out.push_str("  <none>\n");
+        return out;
+    }
+    for row in modules {
+        out.push_str("  ");
+        push_micros_as_ms(&mut out, row.duration_us);
+        out.push(' ');
+        out.push_str(&row.name);
+        out.push('\n');
+    }
+    out.push_str("critical path:\n");
+    for (index, row) in critical_path.iter().enumerate() {
+        out.push_str("  ");
+        for _ in 0..index {
+            out.push_str("  ");
+        }
+        if index > 0 {
+            out.push_str("`- ");
+        }
+        out.push_str(&row.name);
+        out.push_str(" @");
+        push_micros_as_ms(&mut out, row.started_us);
+        out.push_str(" +");
+        push_micros_as_ms(&mut out, row.duration_us);
+        out.push('\n');
     }
     out
 }
 
+/// Appends microseconds as milliseconds with three decimals.
+fn push_micros_as_ms(out: &mut String, micros: u64) {
+    out.push_str(&(micros / 1_000).to_string());
+    out.push('.');
+    let frac = (micros % 1_000).to_string();
+    for _ in frac.len()..3 {
+        out.push('0');
+    }
+    out.push_str(&frac);
+    out.push_str("ms");
+}
+
 /// Formats a module list into a user-friendly table.
-pub fn format_modules(rows: &[ModuleRow]) -> String {
+pub fn format_modules(rows: &[ModuleRow], style: Style) -> String {
     let mut out = String::new();
     out.push_str("modules:\n");
     if rows.is_empty() {
@@ -831,7 +2716,13 @@ pub fn format_modules(rows: &[ModuleRow]) -> String {
         out.push_str("  ");
         out.push_str(&row.name);
         out.push_str(" [");
-        out.push_str(&row.state);
+        if row.state == "running" {
+            out.push_str(&style.paint(&row.state, ANSI_GREEN));
+        } else if row.state == "degraded" || row.state == "hung" {
+            out.push_str(&style.paint(&row.state, ANSI_RED));
+        } else {
+            out.push_str(&row.state);
+        }
         out.push_str("] provides: ");
         out.push_str(&provides);
         out.push('\n');
@@ -839,7 +2730,75 @@ pub fn format_modules(rows: &[ModuleRow]) -> String {
     out
 }
 
-/// Formats a process list into a user-friendly table.
+/// Formats a typed shell response for local display.
+pub fn format_response(response: &shell_protocol::ShellResponse, style: Style) -> String {
+    match response {
+        shell_protocol::ShellResponse::Text { text, .. } => text.clone(),
+        shell_protocol::ShellResponse::Modules(modules) => {
+            let rows = modules
+                .iter()
+                .map(|module| ModuleRow {
+                    name: module.name.clone(),
+                    state: module.state.clone(),
+                    provides: module.provides.clone(),
+                })
+                .collect::<Vec<ModuleRow>>();
+            format_modules(&rows, style)
+        }
+        shell_protocol::ShellResponse::Slots(slots) => {
+            let rows = slots
+                .iter()
+                .map(|slot| SlotRow {
+                    name: slot.name.clone(),
+                    required: slot.required,
+                    provider: (!slot.providers.is_empty()).then(|| slot.providers.join(", ")),
+                    description: None,
+                    category: None,
+                    tags: Vec::new(),
+                    locked: slot.locked,
+                })
+                .collect::<Vec<SlotRow>>();
+            format_slots(&rows, style, false)
+        }
+        shell_protocol::ShellResponse::Error { code, message } => {
+            let mut out = String::from("error[");
+            out.push_str(&code.as_u16().to_string());
+            out.push_str("] ");
+            out.push_str(&style.paint(code.label(), ANSI_RED));
+            let message = message.trim_end();
+            if !message.is_empty() {
+                out.push_str(": ");
+                out.push_str(message);
+            }
+            out.push('\n');
+            out
+        }
+    }
+}
+
+/// Formats a protocol describe reply into a command listing.
+pub fn format_description(description: &ServiceDescription) -> String {
+    let mut out = String::new();
+    out.push_str(&description.protocol);
+    out.push_str(" protocol v");
+    out.push_str(&description.version.to_string());
+    out.push_str(":\n");
+    if description.commands.is_empty() {
+        out.push_str("  <none>\n");
+        return out;
+    }
+    for command in &description.commands {
+        let id = command.msg_type.to_string();
+        out.push_str(&" ".repeat(5 - id.len()));
+        out.push_str(&id);
+        out.push(' ');
+        out.push_str(&command.name);
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats a process list into a user-friendly table.
 pub fn format_processes(rows: &[ProcessRow]) -> String {
     let mut out = String::new();
     out.push_str("processes:\n");
@@ -867,6 +2826,166 @@ pub fn format_log_tail_empty() -> String {
     "log tail: no buffered logs available".to_string()
 }
 
+/// Formats a byte count, as KiB/MiB/GiB/TiB with one decimal when `human` is set.
+pub fn format_size(bytes: usize, human: bool) -> String {
+    if !human {
+        return bytes.to_string();
+    }
+    let value = bytes as u128;
+    let mut unit: u128 = 1;
+    let mut index = 0;
+    while index + 1 < SIZE_UNITS.len() && value >= unit * 1024 {
+        unit *= 1024;
+        index += 1;
+    }
+    let mut out = String::new();
+    if index == 0 {
+        out.push_str(&bytes.to_string());
+    } else {
+        let mut tenths = (value * 10 + unit / 2) / unit;
+        if tenths >= 10240 && index + 1 < SIZE_UNITS.len() {
+            unit *= 1024;
+            index += 1;
+            tenths = (value * 10 + unit / 2) / unit;
+        }
+        out.push_str(&(tenths / 10).to_string());
+        out.push('.');
+        out.push_str(&(tenths % 10).to_string());
+    }
+    out.push(' ');
+    out.push_str(SIZE_UNITS[index]);
+    out
+}
+
+/// Formats `df` rows as an aligned Path/Files/Dirs/Size table.
+pub fn format_df(rows: &[DfRow], human: bool) -> String {
+    let mut table: Vec<[String; 4]> = Vec::with_capacity(rows.len() + 1);
+    table.push([
+        "Path".to_string(),
+        "Files".to_string(),
+        "Dirs".to_string(),
+        "Size".to_string(),
+    ]);
+    for row in rows {
+        table.push([
+            row.path.clone(),
+            row.files.to_string(),
+            row.dirs.to_string(),
+            format_size(row.bytes, human),
+        ]);
+    }
+    let mut widths = [0usize; 4];
+    for cells in &table {
+        for (width, cell) in widths.iter_mut().zip(cells.iter()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    for cells in &table {
+        for (column, cell) in cells.iter().enumerate() {
+            out.push_str(cell);
+            if column + 1 < cells.len() {
+                out.push_str(&" ".repeat(widths[column] - cell.len() + 2));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats `du` output as `<size>  <path>`.
+pub fn format_du(path: &str, bytes: usize, human: bool) -> String {
+    let mut out = format_size(bytes, human);
+    out.push_str("  ");
+    out.push_str(path);
+    out
+}
+
+/// Formats one `checksum` line as `<crc32 hex>  <path>`, like `sha256sum`.
+pub fn format_checksum(sum: u32, path: &str) -> String {
+    let mut out = String::new();
+    for shift in (0..8).rev() {
+        out.push(char::from_digit((sum >> (shift * 4)) & 0xf, 16).unwrap_or('0'));
+    }
+    out.push_str("  ");
+    out.push_str(path);
+    out
+}
+
+/// Formats `stat` output with type, size, owner, and permissions.
+pub fn format_stat(info: &StatInfo) -> String {
+    let mut out = String::new();
+    out.push_str("  File: ");
+    out.push_str(&info.path);
+    if let Some(target) = &info.target {
+        out.push_str(" -> ");
+        out.push_str(target);
+    }
+    out.push_str("\n  Type: ");
+    out.push_str(&info.kind);
+    out.push_str("\n  Size: ");
+    out.push_str(&info.size.to_string());
+    out.push_str("\n Owner: ");
+    out.push_str(&info.owner);
+    out.push_str("\n Group: ");
+    out.push_str(&info.group);
+    out.push_str("\nAccess: ");
+    for shift in [9, 6, 3, 0] {
+        out.push(char::from(b'0' + ((info.mode >> shift) & 0o7) as u8));
+    }
+    out.push_str(" (");
+    out.push_str(&info.permissions);
+    out.push(')');
+    out.push_str("\nCreate: ");
+    push_boot_time(&mut out, info.created);
+    out.push_str("\nModify: ");
+    push_boot_time(&mut out, info.modified);
+    out
+}
+
+fn push_boot_time(out: &mut String, millis: u64) {
+    out.push('+');
+    out.push_str(&(millis / 1_000).to_string());
+    out.push('.');
+    let frac = (millis % 1_000).to_string();
+    for _ in frac.len()..3 {
+        out.push('0');
+    }
+    out.push_str(&frac);
+    out.push('s');
+}
+
+/// Formats `wc` output as `<lines> <words> <bytes> <label>`.
+pub fn format_word_count(count: &WordCount, label: &str) -> String {
+    let mut out = String::new();
+    out.push_str(&count.lines.to_string());
+    out.push(' ');
+    out.push_str(&count.words.to_string());
+    out.push(' ');
+    out.push_str(&count.bytes.to_string());
+    if !label.is_empty() {
+        out.push(' ');
+        out.push_str(label);
+    }
+    out
+}
+
+/// Formats the header shown above each `watch` refresh.
+pub fn format_watch_header(interval_secs: u32, command: &str) -> String {
+    let mut out = String::new();
+    out.push_str("every ");
+    out.push_str(&interval_secs.to_string());
+    out.push_str("s: ");
+    out.push_str(command);
+    out.push_str("  (press any key to stop)\n");
+    out
+}
+
+/// Formats the banner shown while `log tail --follow` streams.
+pub fn format_log_follow_header() -> String {
+    "following log  (press any key to stop)".to_string()
+}
+
 /// Formats an unknown command response.
 pub fn format_unknown_command(raw: &str) -> String {
     let mut out = String::new();
@@ -907,12 +3026,34 @@ mod tests {
         assert_eq!(parse_command("setup"), Command::Setup);
         assert_eq!(parse_command("logout"), Command::Logout);
         assert_eq!(parse_command("whoami"), Command::Whoami);
+        assert_eq!(parse_command("shutdown"), Command::Shutdown);
+        assert_eq!(parse_command("describe"), Command::Describe);
         assert_eq!(parse_command("users"), Command::Users);
         assert_eq!(parse_command("pwd"), Command::Pwd);
-        assert_eq!(parse_command("slots"), Command::Slots);
-        assert_eq!(parse_command("graph"), Command::Graph);
+        assert_eq!(parse_command("slots"), Command::Slots { verbose: false });
+        assert_eq!(
+            parse_command("slots --verbose"),
+            Command::Slots { verbose: true }
+        );
+        assert_eq!(parse_command("slots -v"), Command::Slots { verbose: true });
+        assert_eq!(
+            parse_command("slots all"),
+            Command::Unknown("slots all".to_string())
+        );
+        assert_eq!(parse_command("graph"), Command::Graph { dot: false });
+        assert_eq!(parse_command("graph --dot"), Command::Graph { dot: true });
+        assert_eq!(
+            parse_command("graph --svg"),
+            Command::Unknown("graph --svg".to_string())
+        );
         assert_eq!(parse_command("sysinfo"), Command::Sysinfo);
-        assert_eq!(parse_command("log tail"), Command::LogTail);
+        assert_eq!(
+            parse_command("log tail"),
+            Command::LogTail {
+                follow: false,
+                lines: None
+            }
+        );
         assert_eq!(parse_command("help"), Command::Help(None));
         assert_eq!(
             parse_command("help ps"),
@@ -930,6 +3071,15 @@ mod tests {
             parse_command("stop gpu-service"),
             Command::Stop("gpu-service".to_string())
         );
+        assert_eq!(
+            parse_command("status net-manager"),
+            Command::Status("net-manager".to_string())
+        );
+        assert_eq!(
+            parse_command("status"),
+            Command::Unknown("status".to_string())
+        );
+        assert_eq!(parse_command("boot-report"), Command::BootReport);
         assert_eq!(
             parse_command("login root"),
             Command::Login("root".to_string())
@@ -944,7 +3094,21 @@ mod tests {
         );
         assert_eq!(
             parse_command("remove fs-service"),
-            Command::Remove("fs-service".to_string())
+            Command::Remove {
+                module: "fs-service".to_string(),
+                force: false
+            }
+        );
+        assert_eq!(
+            parse_command("remove --force fs-service"),
+            Command::Remove {
+                module: "fs-service".to_string(),
+                force: true
+            }
+        );
+        assert_eq!(
+            parse_command("remove -f"),
+            Command::Unknown("remove -f".to_string())
         );
     }
 
@@ -954,34 +3118,698 @@ mod tests {
             parse_command("piece check fs-service"),
             Command::PieceCheck("fs-service".to_string())
         );
+        assert_eq!(
+            parse_command("piece check --runtime fs-service"),
+            Command::PieceRuntime("fs-service".to_string())
+        );
+        assert_eq!(parse_command("piece check --all"), Command::PieceCheckAll);
+        assert_eq!(
+            parse_command("piece check --all fs-service"),
+            Command::Unknown("piece check --all fs-service".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_clip_command() {
+        assert_eq!(parse_command("clip"), Command::Clip(None));
+        assert_eq!(
+            parse_command("clip ls /etc"),
+            Command::Clip(Some("ls /etc".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_time_and_metrics_commands() {
+        assert_eq!(
+            parse_command("time ls /etc"),
+            Command::Time("ls /etc".to_string())
+        );
+        assert_eq!(parse_command("time"), Command::Unknown("time".to_string()));
+        assert_eq!(parse_command("metrics"), Command::Metrics(None));
+        assert_eq!(
+            parse_command("metrics on"),
+            Command::Metrics(Some("on".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_wc_command() {
+        assert_eq!(
+            parse_command("wc /etc/hostname"),
+            Command::Wc("/etc/hostname".to_string())
+        );
+        assert_eq!(parse_command("wc"), Command::Unknown("wc".to_string()));
+    }
+
+    #[test]
+    fn parse_stat_command() {
+        assert_eq!(
+            parse_command("stat /etc/hosts"),
+            Command::Stat("/etc/hosts".to_string())
+        );
+        assert_eq!(parse_command("stat"), Command::Unknown("stat".to_string()));
+    }
+
+    #[test]
+    fn parse_checksum_command() {
+        assert_eq!(
+            parse_command("checksum /etc/*.conf"),
+            Command::Checksum("/etc/*.conf".to_string())
+        );
+        assert_eq!(
+            parse_command("checksum"),
+            Command::Unknown("checksum".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_autoplug_command() {
+        assert_eq!(
+            parse_command("autoplug"),
+            Command::Autoplug { dry_run: false }
+        );
+        assert_eq!(
+            parse_command("autoplug --dry-run"),
+            Command::Autoplug { dry_run: true }
+        );
+        assert_eq!(
+            parse_command("autoplug -n"),
+            Command::Autoplug { dry_run: true }
+        );
+        assert_eq!(
+            parse_command("autoplug editor"),
+            Command::Unknown("autoplug editor".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_apply_command() {
+        assert_eq!(
+            parse_command("apply /etc/system.conf"),
+            Command::Apply {
+                path: "/etc/system.conf".to_string(),
+                dry_run: false
+            }
+        );
+        assert_eq!(
+            parse_command("apply --dry-run /etc/system.conf"),
+            Command::Apply {
+                path: "/etc/system.conf".to_string(),
+                dry_run: true
+            }
+        );
+        assert_eq!(
+            parse_command("apply -n"),
+            Command::Unknown("apply -n".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_find_command() {
+        assert_eq!(
+            parse_command("find /home"),
+            Command::Find {
+                path: "/home".to_string(),
+                name: None,
+            }
+        );
+        assert_eq!(
+            parse_command("find / -name *.txt"),
+            Command::Find {
+                path: "/".to_string(),
+                name: Some("*.txt".to_string()),
+            }
+        );
+        assert_eq!(parse_command("find"), Command::Unknown("find".to_string()));
+        assert_eq!(
+            parse_command("find / -type f"),
+            Command::Unknown("find / -type f".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_ln_command() {
+        assert_eq!(
+            parse_command("ln -s /etc/hostname /hostname"),
+            Command::Ln {
+                target: "/etc/hostname".to_string(),
+                link: "/hostname".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_command("ln /etc/hostname /hostname"),
+            Command::Unknown("ln /etc/hostname /hostname".to_string())
+        );
+        assert_eq!(parse_command("ln -s /a"), Command::Unknown("ln -s /a".to_string()));
+    }
+
+    #[test]
+    fn parse_chsh_command() {
+        assert_eq!(
+            parse_command("chsh"),
+            Command::Chsh {
+                user: None,
+                shell: None,
+            }
+        );
+        assert_eq!(
+            parse_command("chsh /bin/fish"),
+            Command::Chsh {
+                user: None,
+                shell: Some("/bin/fish".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_command("chsh guest /bin/fish"),
+            Command::Chsh {
+                user: Some("guest".to_string()),
+                shell: Some("/bin/fish".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_command("chsh a b c"),
+            Command::Unknown("chsh a b c".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_chmod_chown_commands() {
+        assert_eq!(
+            parse_command("chmod 644 /etc/hostname"),
+            Command::Chmod {
+                mode: "644".to_string(),
+                path: "/etc/hostname".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_command("chown guest /home/guest"),
+            Command::Chown {
+                user: "guest".to_string(),
+                path: "/home/guest".to_string(),
+            }
+        );
+        assert_eq!(parse_command("chmod"), Command::Unknown("chmod".to_string()));
+        assert_eq!(
+            parse_command("chown guest"),
+            Command::Unknown("chown guest".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_gpu_command() {
+        assert_eq!(parse_command("gpu"), Command::Gpu(None));
+        assert_eq!(
+            parse_command("gpu save /tmp/t.bin"),
+            Command::Gpu(Some("save /tmp/t.bin".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_watch_command() {
+        assert_eq!(
+            parse_command("watch slots"),
+            Command::Watch {
+                interval_secs: DEFAULT_WATCH_INTERVAL_SECS,
+                command: "slots".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_command("watch -n 5 ps --tree"),
+            Command::Watch {
+                interval_secs: 5,
+                command: "ps --tree".to_string(),
+            }
+        );
+        for raw in [
+            "watch",
+            "watch -n",
+            "watch -n 0 ps",
+            "watch -n x ps",
+            "watch -n 3",
+        ] {
+            assert_eq!(parse_command(raw), Command::Unknown(raw.to_string()));
+        }
+    }
+
+    #[test]
+    fn format_watch_header_names_command_and_interval() {
+        assert_eq!(
+            format_watch_header(2, "slots"),
+            "every 2s: slots  (press any key to stop)\n"
+        );
+    }
+
+    #[test]
+    fn parse_chained_commands() {
+        assert_eq!(
+            parse_command("mkdir -p /srv/www && write /srv/www/index.html hello"),
+            Command::Sequence(vec![
+                ChainStep {
+                    op: ChainOp::Always,
+                    command: Command::MkdirP("/srv/www".to_string()),
+                },
+                ChainStep {
+                    op: ChainOp::OnSuccess,
+                    command: Command::Write {
+                        path: "/srv/www/index.html".to_string(),
+                        contents: "hello".to_string(),
+                    },
+                },
+            ])
+        );
+        assert_eq!(
+            parse_command("cat /a || pwd; ps ; lsmod"),
+            Command::Sequence(vec![
+                ChainStep {
+                    op: ChainOp::Always,
+                    command: Command::Cat("/a".to_string()),
+                },
+                ChainStep {
+                    op: ChainOp::OnFailure,
+                    command: Command::Pwd,
+                },
+                ChainStep {
+                    op: ChainOp::Always,
+                    command: Command::Ps { tree: false },
+                },
+                ChainStep {
+                    op: ChainOp::Always,
+                    command: Command::Lsmod,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_chain_allows_trailing_semicolon_only() {
+        assert_eq!(parse_command("pwd;"), Command::Pwd);
+        assert_eq!(parse_command("pwd ;"), Command::Pwd);
+        assert_eq!(
+            parse_command("write /tmp/a x;y"),
+            Command::Write {
+                path: "/tmp/a".to_string(),
+                contents: "x;y".to_string(),
+            }
+        );
+        for raw in ["&& pwd", "pwd &&", "pwd ||", "pwd ; ; ps", ";"] {
+            assert_eq!(parse_command(raw), Command::Unknown(raw.to_string()));
+        }
+    }
+
+    #[test]
+    fn split_chain_keeps_spacing_inside_steps() {
+        assert_eq!(
+            split_chain("echo a   b &&  echo c\td;"),
+            Some(vec![
+                (ChainOp::Always, "echo a   b".to_string()),
+                (ChainOp::OnSuccess, "echo c\td".to_string()),
+            ])
+        );
+        assert_eq!(
+            split_chain("grep  x /f  ||  ls"),
+            Some(vec![
+                (ChainOp::Always, "grep  x /f".to_string()),
+                (ChainOp::OnFailure, "ls".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn run_sequence_applies_conditions() {
+        let steps = match parse_command("cat /a && pwd || ps ; lsmod && whoami") {
+            Command::Sequence(steps) => steps,
+            other => panic!("unexpected {other:?}"),
+        };
+        let mut seen = Vec::new();
+        let outcomes = run_sequence(&steps, |command| {
+            seen.push(command.clone());
+            !matches!(command, Command::Cat(_) | Command::Whoami)
+        });
+        assert_eq!(
+            outcomes,
+            vec![
+                StepOutcome::Failed,
+                StepOutcome::Skipped,
+                StepOutcome::Succeeded,
+                StepOutcome::Succeeded,
+                StepOutcome::Failed,
+            ]
+        );
+        assert_eq!(seen.len(), 4);
+        assert!(!sequence_succeeded(&outcomes));
+    }
+
+    #[test]
+    fn sequence_status_ignores_skipped_steps() {
+        assert!(sequence_succeeded(&[
+            StepOutcome::Succeeded,
+            StepOutcome::Skipped
+        ]));
+        assert!(!sequence_succeeded(&[
+            StepOutcome::Failed,
+            StepOutcome::Skipped
+        ]));
+        assert!(sequence_succeeded(&[]));
+    }
+
+    #[test]
+    fn parse_container_command() {
+        assert_eq!(parse_command("container"), Command::Container(None));
+        assert_eq!(
+            parse_command("container restore web"),
+            Command::Container(Some("restore web".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_http_command() {
+        assert_eq!(parse_command("http"), Command::Http(None));
+        assert_eq!(
+            parse_command("http top 3"),
+            Command::Http(Some("top 3".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_echo_command() {
+        assert_eq!(parse_command("echo"), Command::Echo(String::new()));
+        assert_eq!(
+            parse_command("echo  status  $?"),
+            Command::Echo("status $?".to_string())
+        );
+    }
+
+    #[test]
+    fn exit_code_reports_success() {
+        assert!(ExitCode::SUCCESS.is_success());
+        assert!(!ExitCode::FAILURE.is_success());
+        assert!(!ExitCode::UNKNOWN_COMMAND.is_success());
+        assert_eq!(ExitCode::default(), ExitCode::SUCCESS);
+    }
+
+    #[test]
+    fn command_result_maps_to_response() {
+        let ok = CommandResult::new(ExitCode::SUCCESS, "done\n".to_string());
+        assert_eq!(
+            ok.to_response(),
+            shell_protocol::ShellResponse::Text {
+                status: shell_protocol::ShellStatus::Ok,
+                text: "done\n".to_string(),
+            }
+        );
+        let failed = CommandResult::new(ExitCode::UNKNOWN_COMMAND, String::new());
+        assert_eq!(
+            failed.to_response().status(),
+            shell_protocol::ShellStatus::Failed
+        );
+    }
+
+    #[test]
+    fn capped_command_output_round_trips() {
+        let result = CommandResult::new(ExitCode::SUCCESS, "é".repeat(MAX_OUTPUT_BYTES));
+        assert_eq!(result.output.len(), MAX_OUTPUT_BYTES - 1);
+        assert!(result.output.chars().all(|ch| ch == 'é'));
+        let response = result.to_response();
+        let bytes = shell_protocol::encode_response(&response);
+        assert_eq!(shell_protocol::decode_response(&bytes), Ok(response));
+    }
+
+    #[test]
+    fn expand_echo_handles_variables_and_escapes() {
+        let lookup = |name: &str| match name {
+            "USER" => Some("alice".to_string()),
+            "HOME" => Some("/home/alice".to_string()),
+            _ => None,
+        };
+        assert_eq!(expand_echo("hi $USER", lookup), "hi alice");
+        assert_eq!(expand_echo("${HOME}/docs", lookup), "/home/alice/docs");
+        assert_eq!(expand_echo("[$MISSING]", lookup), "[]");
+        assert_eq!(expand_echo("a\\nb\\tc", lookup), "a\nb\tc");
+        assert_eq!(expand_echo("\\$USER \\\\ \\q\\", lookup), "$USER \\ \\q\\");
+        assert_eq!(expand_echo("cost $5 and $", lookup), "cost  and $");
+        assert_eq!(expand_echo("${} ${USER", lookup), "${} ${USER");
+        assert_eq!(expand_echo("$-x", lookup), "$-x");
+    }
+
+    #[test]
+    fn render_prompt_fills_placeholders() {
+        assert_eq!(
+            render_prompt(
+                "{user}@{hostname}:{cwd}$",
+                Some("alice"),
+                "lab",
+                "/home/alice"
+            ),
+            "alice@lab:/home/alice$"
+        );
+        assert_eq!(
+            render_prompt("[{user}] {cwd}>", None, "lab", "/"),
+            "[nobody] />"
+        );
+        assert_eq!(
+            render_prompt("ruzzle>", Some("root"), "lab", "/"),
+            "ruzzle>"
+        );
+    }
+
+    #[test]
+    fn expand_last_status_replaces_marker() {
+        assert_eq!(
+            expand_last_status("echo $? and $?", ExitCode(127)),
+            "echo 127 and 127"
+        );
+        assert_eq!(expand_last_status("echo $", ExitCode::FAILURE), "echo $");
+        assert_eq!(
+            expand_last_status("echo \\$? \\\\$?", ExitCode(2)),
+            "echo \\$? \\\\2"
+        );
+        assert_eq!(
+            expand_echo(&expand_last_status("\\$? is $?", ExitCode(2)), |_| None),
+            "$? is 2"
+        );
+    }
+
+    #[test]
+    fn parse_notifications_command() {
+        assert_eq!(
+            parse_command("notifications"),
+            Command::Notifications { clear: false }
+        );
+        assert_eq!(
+            parse_command("notifications clear"),
+            Command::Notifications { clear: true }
+        );
+        for raw in ["notifications all", "notifications clear now"] {
+            assert_eq!(parse_command(raw), Command::Unknown(raw.to_string()));
+        }
+    }
+
+    #[test]
+    fn parse_set_command() {
+        assert_eq!(parse_command("set"), Command::Set(None));
+        assert_eq!(
+            parse_command("set color=on"),
+            Command::Set(Some("color=on".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_webconsole_command() {
+        assert_eq!(parse_command("webconsole"), Command::WebConsole(None));
+        assert_eq!(
+            parse_command("webconsole send slots"),
+            Command::WebConsole(Some("send slots".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_ip_subcommands() {
+        let eth0 = || "eth0".to_string();
+        for (line, ip) in [
+            ("ip addr", IpCommand::Show),
+            ("ip -s", IpCommand::Stats(None)),
+            ("ip link add eth1", IpCommand::LinkAdd("eth1".to_string())),
+            ("ip link del eth0", IpCommand::LinkDel(eth0())),
+            (
+                "ip link set eth0 up",
+                IpCommand::LinkSet {
+                    iface: eth0(),
+                    up: true,
+                },
+            ),
+            (
+                "ip link set eth0 down",
+                IpCommand::LinkSet {
+                    iface: eth0(),
+                    up: false,
+                },
+            ),
+            (
+                "ip link set eth0 address 02:00:00:00:00:01",
+                IpCommand::LinkAddress {
+                    iface: eth0(),
+                    mac: "02:00:00:00:00:01".to_string(),
+                },
+            ),
+            (
+                "ip addr add eth0 10.0.2.15/24",
+                IpCommand::AddrAdd {
+                    iface: eth0(),
+                    cidr: "10.0.2.15/24".to_string(),
+                },
+            ),
+            ("ip addr del eth0", IpCommand::AddrDel(eth0())),
+        ] {
+            assert_eq!(parse_command(line), Command::Ip(ip), "{}", line);
+        }
+    }
+
+    #[test]
+    fn parse_ip_rejects_invalid_arguments() {
+        for line in [
+            "ip add eth0",
+            "ip -s Eth0",
+            "ip link set eth0 sideways",
+            "ip link set eth0 address 02:00:00",
+            "ip addr add eth0 10.0.2.300/24",
+            "ip addr add eth0 10.0.2.15/33",
+            "ip addr del",
+        ] {
+            assert_eq!(
+                parse_command(line),
+                Command::Unknown(line.to_string()),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn parse_route_subcommands() {
+        assert_eq!(
+            parse_command("route add default via 10.0.2.2 dev eth0"),
+            Command::Route(RouteCommand::Add {
+                destination: "default".to_string(),
+                iface: "eth0".to_string(),
+                gateway: Some("10.0.2.2".to_string()),
+                metric: 0,
+            })
+        );
+        assert_eq!(
+            parse_command("route add 10.1.0.0/16 dev eth1 metric 20"),
+            Command::Route(RouteCommand::Add {
+                destination: "10.1.0.0/16".to_string(),
+                iface: "eth1".to_string(),
+                gateway: None,
+                metric: 20,
+            })
+        );
+        assert_eq!(
+            parse_command("route del default"),
+            Command::Route(RouteCommand::Del("default".to_string()))
+        );
+        assert_eq!(
+            parse_command("route get 10.1.2.3"),
+            Command::Route(RouteCommand::Get("10.1.2.3".to_string()))
+        );
+        for line in [
+            "route add default eth0",
+            "route add default via 10.0.2.2",
+            "route add default via 0.0.0.0 dev eth0",
+            "route add default dev eth0 dev eth1",
+            "route add default dev eth0 metric 10000",
+            "route add 10.1.0.0 dev eth0",
+            "route del nowhere",
+            "route get default",
+        ] {
+            assert_eq!(
+                parse_command(line),
+                Command::Unknown(line.to_string()),
+                "{}",
+                line
+            );
+        }
     }
 
     #[test]
     fn parse_system_tool_commands() {
         assert_eq!(parse_command("ps --tree"), Command::Ps { tree: true });
-        assert_eq!(parse_command("ip"), Command::Ip(None));
+        assert_eq!(parse_command("ip"), Command::Ip(IpCommand::Show));
+        assert_eq!(
+            parse_command("ip -s eth0"),
+            Command::Ip(IpCommand::Stats(Some("eth0".to_string())))
+        );
+        assert_eq!(parse_command("route"), Command::Route(RouteCommand::Show));
+        assert_eq!(
+            parse_command("resolve ruzzle.dev"),
+            Command::Resolve("ruzzle.dev".to_string())
+        );
+        assert_eq!(
+            parse_command("resolve"),
+            Command::Unknown("resolve".to_string())
+        );
         assert_eq!(
-            parse_command("ip add eth0"),
-            Command::Ip(Some("add eth0".to_string()))
+            parse_command("resolve a b"),
+            Command::Unknown("resolve a b".to_string())
         );
-        assert_eq!(parse_command("route"), Command::Route(None));
         assert_eq!(
-            parse_command("route add default eth0"),
-            Command::Route(Some("add default eth0".to_string()))
+            parse_command("ping -c 2 10.0.2.2"),
+            Command::Ping("-c 2 10.0.2.2".to_string())
+        );
+        assert_eq!(parse_command("ping"), Command::Unknown("ping".to_string()));
+        assert_eq!(parse_command("fw"), Command::Fw(None));
+        assert_eq!(
+            parse_command("fw add deny in  icmp"),
+            Command::Fw(Some("add deny in icmp".to_string()))
+        );
+        assert_eq!(parse_command("dhcpd"), Command::Dhcpd(None));
+        assert_eq!(
+            parse_command("dhcpd  release 10.0.2.100"),
+            Command::Dhcpd(Some("release 10.0.2.100".to_string()))
         );
         assert_eq!(parse_command("mount"), Command::Mount(None));
         assert_eq!(
             parse_command("mount memfs /mnt"),
             Command::Mount(Some("memfs /mnt".to_string()))
         );
-        assert_eq!(parse_command("df"), Command::Df(None));
+        assert_eq!(
+            parse_command("df"),
+            Command::Df {
+                path: None,
+                human: false
+            }
+        );
         assert_eq!(
             parse_command("df /"),
-            Command::Df(Some("/".to_string()))
+            Command::Df {
+                path: Some("/".to_string()),
+                human: false
+            }
+        );
+        assert_eq!(
+            parse_command("df -h"),
+            Command::Df {
+                path: None,
+                human: true
+            }
         );
         assert_eq!(
             parse_command("du /etc"),
-            Command::Du("/etc".to_string())
+            Command::Du {
+                path: "/etc".to_string(),
+                human: false
+            }
+        );
+        assert_eq!(
+            parse_command("du -h /etc"),
+            Command::Du {
+                path: "/etc".to_string(),
+                human: true
+            }
+        );
+        assert_eq!(
+            parse_command("du -h"),
+            Command::Unknown("du -h".to_string())
         );
         assert_eq!(parse_command("market scan"), Command::MarketScan);
     }
@@ -1056,7 +3884,36 @@ mod tests {
         );
         assert_eq!(
             parse_command("rm -r /var/tmp"),
-            Command::RmRecursive("/var/tmp".to_string())
+            Command::RmRecursive {
+                path: "/var/tmp".to_string(),
+                force: false
+            }
+        );
+        assert_eq!(
+            parse_command("rm -rf /var/tmp"),
+            Command::RmRecursive {
+                path: "/var/tmp".to_string(),
+                force: true
+            }
+        );
+        assert_eq!(
+            parse_command("rm -R --force /var/tmp"),
+            Command::RmRecursive {
+                path: "/var/tmp".to_string(),
+                force: true
+            }
+        );
+        assert_eq!(
+            parse_command("rm -f /tmp/a"),
+            Command::Rm("/tmp/a".to_string())
+        );
+        assert_eq!(
+            parse_command("rm /tmp/-r"),
+            Command::Rm("/tmp/-r".to_string())
+        );
+        assert_eq!(
+            parse_command("rm -rf"),
+            Command::Unknown("rm -rf".to_string())
         );
         assert_eq!(
             parse_command("cp /etc/hostname /etc/hostname.bak"),
@@ -1087,7 +3944,8 @@ mod tests {
                 slot: "ruzzle.slot.console@1".to_string(),
                 module: "console-service".to_string(),
                 dry_run: false,
-                swap: false
+                swap: false,
+                override_lock: false
             }
         );
         assert_eq!(
@@ -1096,7 +3954,8 @@ mod tests {
                 slot: "ruzzle.slot.console@1".to_string(),
                 module: "console-service".to_string(),
                 dry_run: true,
-                swap: false
+                swap: false,
+                override_lock: false
             }
         );
         assert_eq!(
@@ -1105,7 +3964,8 @@ mod tests {
                 slot: "ruzzle.slot.console@1".to_string(),
                 module: "console-service".to_string(),
                 dry_run: true,
-                swap: false
+                swap: false,
+                override_lock: false
             }
         );
         assert_eq!(
@@ -1114,7 +3974,8 @@ mod tests {
                 slot: "ruzzle.slot.console@1".to_string(),
                 module: "console-service".to_string(),
                 dry_run: false,
-                swap: true
+                swap: true,
+                override_lock: false
             }
         );
         assert_eq!(
@@ -1123,12 +3984,43 @@ mod tests {
                 slot: "ruzzle.slot.console@1".to_string(),
                 module: "console-service".to_string(),
                 dry_run: true,
-                swap: true
+                swap: true,
+                override_lock: false
             }
         );
         assert_eq!(
             parse_command("unplug ruzzle.slot.console@1"),
-            Command::Unplug("ruzzle.slot.console@1".to_string())
+            Command::Unplug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                force: false,
+                override_lock: false
+            }
+        );
+        assert_eq!(
+            parse_command("unplug -f ruzzle.slot.console@1"),
+            Command::Unplug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                force: true,
+                override_lock: false
+            }
+        );
+        assert_eq!(
+            parse_command("unplug --override -f ruzzle.slot.console@1"),
+            Command::Unplug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                force: true,
+                override_lock: true
+            }
+        );
+        assert_eq!(
+            parse_command("plug --swap --override ruzzle.slot.shell@1 zsh"),
+            Command::Plug {
+                slot: "ruzzle.slot.shell@1".to_string(),
+                module: "zsh".to_string(),
+                dry_run: false,
+                swap: true,
+                override_lock: true
+            }
         );
     }
 
@@ -1172,6 +4064,10 @@ mod tests {
             parse_command("piece check"),
             Command::Unknown("piece check".to_string())
         );
+        assert_eq!(
+            parse_command("piece check --runtime"),
+            Command::Unknown("piece check --runtime".to_string())
+        );
         assert_eq!(parse_command("du"), Command::Unknown("du".to_string()));
         assert_eq!(parse_command("market"), Command::Unknown("market".to_string()));
         assert_eq!(
@@ -1226,8 +4122,24 @@ mod tests {
             Some(shell_protocol::ShellCommand::Stop("fs".to_string()))
         );
         assert_eq!(
-            to_ipc(&Command::LogTail),
-            Some(shell_protocol::ShellCommand::LogTail)
+            to_ipc(&Command::LogTail {
+                follow: true,
+                lines: Some(5)
+            }),
+            Some(shell_protocol::ShellCommand::LogTail {
+                follow: true,
+                lines: Some(5)
+            })
+        );
+        assert_eq!(
+            to_ipc(&Command::LogTail {
+                follow: false,
+                lines: Some(usize::MAX)
+            }),
+            Some(shell_protocol::ShellCommand::LogTail {
+                follow: false,
+                lines: Some(u32::MAX)
+            })
         );
         assert_eq!(
             to_ipc(&Command::Help(None)),
@@ -1248,24 +4160,72 @@ mod tests {
             Some(shell_protocol::ShellCommand::PieceCheck("fs".to_string()))
         );
         assert_eq!(
-            to_ipc(&Command::Ip(Some("add eth0".to_string()))),
-            Some(shell_protocol::ShellCommand::Ip(Some("add eth0".to_string())))
+            to_ipc(&Command::PieceRuntime("fs".to_string())),
+            Some(shell_protocol::ShellCommand::PieceRuntime("fs".to_string()))
+        );
+        assert_eq!(
+            to_ipc(&Command::PieceCheckAll),
+            Some(shell_protocol::ShellCommand::PieceCheckAll)
+        );
+        assert_eq!(
+            to_ipc(&Command::Status("fs".to_string())),
+            Some(shell_protocol::ShellCommand::Status("fs".to_string()))
+        );
+        assert_eq!(
+            to_ipc(&Command::BootReport),
+            Some(shell_protocol::ShellCommand::BootReport)
+        );
+        assert_eq!(
+            to_ipc(&Command::Resolve("ruzzle.dev".to_string())),
+            Some(shell_protocol::ShellCommand::Resolve(
+                "ruzzle.dev".to_string()
+            ))
+        );
+        assert_eq!(
+            to_ipc(&Command::Ping("127.0.0.1".to_string())),
+            Some(shell_protocol::ShellCommand::Ping("127.0.0.1".to_string()))
+        );
+        assert_eq!(
+            to_ipc(&Command::Fw(None)),
+            Some(shell_protocol::ShellCommand::Fw(None))
+        );
+        assert_eq!(
+            to_ipc(&Command::Dhcpd(None)),
+            Some(shell_protocol::ShellCommand::Dhcpd(None))
+        );
+        assert_eq!(
+            to_ipc(&Command::Ip(IpCommand::LinkAdd("eth0".to_string()))),
+            Some(shell_protocol::ShellCommand::Ip(IpCommand::LinkAdd(
+                "eth0".to_string()
+            )))
         );
         assert_eq!(
-            to_ipc(&Command::Route(None)),
-            Some(shell_protocol::ShellCommand::Route(None))
+            to_ipc(&Command::Route(RouteCommand::Show)),
+            Some(shell_protocol::ShellCommand::Route(RouteCommand::Show))
         );
         assert_eq!(
             to_ipc(&Command::Mount(Some("memfs /mnt".to_string()))),
             Some(shell_protocol::ShellCommand::Mount(Some("memfs /mnt".to_string())))
         );
         assert_eq!(
-            to_ipc(&Command::Df(Some("/".to_string()))),
-            Some(shell_protocol::ShellCommand::Df(Some("/".to_string())))
+            to_ipc(&Command::Df {
+                path: Some("/".to_string()),
+                human: true
+            }),
+            Some(shell_protocol::ShellCommand::Df {
+                path: Some("/".to_string()),
+                human: true
+            })
         );
         assert_eq!(
-            to_ipc(&Command::Du("/etc".to_string())),
-            Some(shell_protocol::ShellCommand::Du("/etc".to_string()))
+            to_ipc(&Command::Du {
+                path: "/etc".to_string(),
+                human: false
+            }),
+            Some(shell_protocol::ShellCommand::Du {
+                path: "/etc".to_string(),
+                human: false
+            })
         );
         assert_eq!(
             to_ipc(&Command::MarketScan),
@@ -1276,8 +4236,14 @@ mod tests {
             Some(shell_protocol::ShellCommand::Install("fs".to_string()))
         );
         assert_eq!(
-            to_ipc(&Command::Remove("fs".to_string())),
-            Some(shell_protocol::ShellCommand::Remove("fs".to_string()))
+            to_ipc(&Command::Remove {
+                module: "fs".to_string(),
+                force: true
+            }),
+            Some(shell_protocol::ShellCommand::Remove {
+                module: "fs".to_string(),
+                force: true
+            })
         );
         assert_eq!(
             to_ipc(&Command::Setup),
@@ -1372,41 +4338,201 @@ mod tests {
             Some(shell_protocol::ShellCommand::Rm("/tmp/a".to_string()))
         );
         assert_eq!(
-            to_ipc(&Command::RmRecursive("/var/tmp".to_string())),
-            Some(shell_protocol::ShellCommand::RmRecursive("/var/tmp".to_string()))
+            to_ipc(&Command::RmRecursive {
+                path: "/var/tmp".to_string(),
+                force: false
+            }),
+            Some(shell_protocol::ShellCommand::RmRecursive {
+                path: "/var/tmp".to_string(),
+                force: false
+            })
         );
         assert_eq!(
-            to_ipc(&Command::Slots),
-            Some(shell_protocol::ShellCommand::Slots)
+            to_ipc(&Command::Slots { verbose: true }),
+            Some(shell_protocol::ShellCommand::Slots { verbose: true })
         );
         assert_eq!(
             to_ipc(&Command::Plug {
                 slot: "ruzzle.slot.console@1".to_string(),
                 module: "console-service".to_string(),
                 dry_run: false,
-                swap: false
+                swap: false,
+                override_lock: false
             }),
             Some(shell_protocol::ShellCommand::Plug {
                 slot: "ruzzle.slot.console@1".to_string(),
                 module: "console-service".to_string(),
                 dry_run: false,
-                swap: false
+                swap: false,
+                override_lock: false
             })
         );
         assert_eq!(
-            to_ipc(&Command::Unplug("ruzzle.slot.console@1".to_string())),
-            Some(shell_protocol::ShellCommand::Unplug(
-                "ruzzle.slot.console@1".to_string()
-            ))
+            to_ipc(&Command::Unplug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                force: false,
+                override_lock: false
+            }),
+            Some(shell_protocol::ShellCommand::Unplug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                force: false,
+                override_lock: false
+            })
         );
         assert_eq!(
-            to_ipc(&Command::Graph),
-            Some(shell_protocol::ShellCommand::Graph)
+            to_ipc(&Command::Graph { dot: true }),
+            Some(shell_protocol::ShellCommand::Graph { dot: true })
         );
         assert_eq!(
             to_ipc(&Command::Sysinfo),
             Some(shell_protocol::ShellCommand::Sysinfo)
         );
+        assert_eq!(
+            to_ipc(&Command::Clip(Some("ls".to_string()))),
+            Some(shell_protocol::ShellCommand::Clip(Some("ls".to_string())))
+        );
+        assert_eq!(
+            to_ipc(&Command::Time("ls".to_string())),
+            Some(shell_protocol::ShellCommand::Time("ls".to_string()))
+        );
+        assert_eq!(
+            to_ipc(&Command::Metrics(None)),
+            Some(shell_protocol::ShellCommand::Metrics(None))
+        );
+        assert_eq!(
+            to_ipc(&Command::Wc("/etc/hostname".to_string())),
+            Some(shell_protocol::ShellCommand::Wc("/etc/hostname".to_string()))
+        );
+        assert_eq!(
+            to_ipc(&Command::Find {
+                path: "/".to_string(),
+                name: Some("*.txt".to_string()),
+            }),
+            Some(shell_protocol::ShellCommand::Find {
+                path: "/".to_string(),
+                name: Some("*.txt".to_string()),
+            })
+        );
+        assert_eq!(
+            to_ipc(&Command::Ln {
+                target: "/etc/hostname".to_string(),
+                link: "/hostname".to_string(),
+            }),
+            Some(shell_protocol::ShellCommand::Ln {
+                target: "/etc/hostname".to_string(),
+                link: "/hostname".to_string(),
+            })
+        );
+        assert_eq!(
+            to_ipc(&Command::Chsh {
+                user: None,
+                shell: Some("/bin/fish".to_string()),
+            }),
+            Some(shell_protocol::ShellCommand::Chsh {
+                user: None,
+                shell: Some("/bin/fish".to_string()),
+            })
+        );
+        assert_eq!(
+            to_ipc(&Command::Chmod {
+                mode: "600".to_string(),
+                path: "/tmp/a".to_string(),
+            }),
+            Some(shell_protocol::ShellCommand::Chmod {
+                mode: "600".to_string(),
+                path: "/tmp/a".to_string(),
+            })
+        );
+        assert_eq!(
+            to_ipc(&Command::Chown {
+                user: "guest".to_string(),
+                path: "/tmp/a".to_string(),
+            }),
+            Some(shell_protocol::ShellCommand::Chown {
+                user: "guest".to_string(),
+                path: "/tmp/a".to_string(),
+            })
+        );
+        assert_eq!(
+            to_ipc(&Command::Gpu(Some("load /tmp/t.bin".to_string()))),
+            Some(shell_protocol::ShellCommand::Gpu(Some(
+                "load /tmp/t.bin".to_string()
+            )))
+        );
+        assert_eq!(
+            to_ipc(&Command::Watch {
+                interval_secs: 3,
+                command: "ps".to_string(),
+            }),
+            Some(shell_protocol::ShellCommand::Watch {
+                interval_secs: 3,
+                command: "ps".to_string(),
+            })
+        );
+        assert_eq!(to_ipc(&Command::Describe), None);
+        assert_eq!(
+            to_ipc(&Command::Sequence(vec![ChainStep {
+                op: ChainOp::Always,
+                command: Command::Pwd,
+            }])),
+            None
+        );
+        assert_eq!(
+            to_ipc(&Command::Container(None)),
+            Some(shell_protocol::ShellCommand::Container(None))
+        );
+        assert_eq!(
+            to_ipc(&Command::Http(None)),
+            Some(shell_protocol::ShellCommand::Http(None))
+        );
+        assert_eq!(
+            to_ipc(&Command::Echo("hi".to_string())),
+            Some(shell_protocol::ShellCommand::Echo("hi".to_string()))
+        );
+        assert_eq!(
+            to_ipc(&Command::Shutdown),
+            Some(shell_protocol::ShellCommand::Shutdown)
+        );
+        assert_eq!(
+            to_ipc(&Command::Set(None)),
+            Some(shell_protocol::ShellCommand::Set(None))
+        );
+        assert_eq!(
+            to_ipc(&Command::WebConsole(None)),
+            Some(shell_protocol::ShellCommand::WebConsole(None))
+        );
+        assert_eq!(
+            to_ipc(&Command::LogLevel(Some("fs-service debug".to_string()))),
+            Some(shell_protocol::ShellCommand::LogLevel(Some(
+                "fs-service debug".to_string()
+            )))
+        );
+        assert_eq!(
+            to_ipc(&Command::Notifications { clear: true }),
+            Some(shell_protocol::ShellCommand::Notifications { clear: true })
+        );
+        assert_eq!(
+            to_ipc(&Command::Stat("/etc".to_string())),
+            Some(shell_protocol::ShellCommand::Stat("/etc".to_string()))
+        );
+        assert_eq!(
+            to_ipc(&Command::Apply {
+                path: "/etc/system.conf".to_string(),
+                dry_run: true
+            }),
+            Some(shell_protocol::ShellCommand::Apply {
+                path: "/etc/system.conf".to_string(),
+                dry_run: true
+            })
+        );
+        assert_eq!(
+            to_ipc(&Command::Checksum("/etc".to_string())),
+            Some(shell_protocol::ShellCommand::Checksum("/etc".to_string()))
+        );
+        assert_eq!(
+            to_ipc(&Command::Autoplug { dry_run: true }),
+            Some(shell_protocol::ShellCommand::Autoplug { dry_run: true })
+        );
     }
 
     #[test]
@@ -1425,8 +4551,14 @@ mod tests {
             Command::Stop("fs".to_string())
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::LogTail),
-            Command::LogTail
+            from_ipc(shell_protocol::ShellCommand::LogTail {
+                follow: true,
+                lines: None
+            }),
+            Command::LogTail {
+                follow: true,
+                lines: None
+            }
         );
         assert_eq!(
             from_ipc(shell_protocol::ShellCommand::Help(None)),
@@ -1447,24 +4579,74 @@ mod tests {
             Command::PieceCheck("fs".to_string())
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Ip(Some("add eth0".to_string()))),
-            Command::Ip(Some("add eth0".to_string()))
+            from_ipc(shell_protocol::ShellCommand::PieceRuntime("fs".to_string())),
+            Command::PieceRuntime("fs".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::PieceCheckAll),
+            Command::PieceCheckAll
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Status("fs".to_string())),
+            Command::Status("fs".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::BootReport),
+            Command::BootReport
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Resolve(
+                "ruzzle.dev".to_string()
+            )),
+            Command::Resolve("ruzzle.dev".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Ping("127.0.0.1".to_string())),
+            Command::Ping("127.0.0.1".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Fw(Some("del 1".to_string()))),
+            Command::Fw(Some("del 1".to_string()))
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Dhcpd(Some(
+                "stop".to_string()
+            ))),
+            Command::Dhcpd(Some("stop".to_string()))
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Ip(IpCommand::AddrDel(
+                "eth0".to_string()
+            ))),
+            Command::Ip(IpCommand::AddrDel("eth0".to_string()))
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Route(None)),
-            Command::Route(None)
+            from_ipc(shell_protocol::ShellCommand::Route(RouteCommand::Show)),
+            Command::Route(RouteCommand::Show)
         );
         assert_eq!(
             from_ipc(shell_protocol::ShellCommand::Mount(Some("memfs /mnt".to_string()))),
             Command::Mount(Some("memfs /mnt".to_string()))
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Df(Some("/".to_string()))),
-            Command::Df(Some("/".to_string()))
+            from_ipc(shell_protocol::ShellCommand::Df {
+                path: Some("/".to_string()),
+                human: false
+            }),
+            Command::Df {
+                path: Some("/".to_string()),
+                human: false
+            }
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Du("/etc".to_string())),
-            Command::Du("/etc".to_string())
+            from_ipc(shell_protocol::ShellCommand::Du {
+                path: "/etc".to_string(),
+                human: true
+            }),
+            Command::Du {
+                path: "/etc".to_string(),
+                human: true
+            }
         );
         assert_eq!(
             from_ipc(shell_protocol::ShellCommand::MarketScan),
@@ -1475,8 +4657,14 @@ mod tests {
             Command::Install("fs".to_string())
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Remove("fs".to_string())),
-            Command::Remove("fs".to_string())
+            from_ipc(shell_protocol::ShellCommand::Remove {
+                module: "fs".to_string(),
+                force: false
+            }),
+            Command::Remove {
+                module: "fs".to_string(),
+                force: false
+            }
         );
         assert_eq!(
             from_ipc(shell_protocol::ShellCommand::Setup),
@@ -1534,76 +4722,226 @@ mod tests {
                 path: "/etc/hostname".to_string(),
                 contents: "ruzzle".to_string()
             }),
-            Command::Write {
-                path: "/etc/hostname".to_string(),
-                contents: "ruzzle".to_string()
+            Command::Write {
+                path: "/etc/hostname".to_string(),
+                contents: "ruzzle".to_string()
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Cp {
+                src: "/etc/hostname".to_string(),
+                dst: "/etc/hostname.bak".to_string(),
+                recursive: false
+            }),
+            Command::Cp {
+                src: "/etc/hostname".to_string(),
+                dst: "/etc/hostname.bak".to_string(),
+                recursive: false
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Mv {
+                src: "/etc/hostname".to_string(),
+                dst: "/etc/hostname.old".to_string()
+            }),
+            Command::Mv {
+                src: "/etc/hostname".to_string(),
+                dst: "/etc/hostname.old".to_string()
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::MkdirP("/var/tmp".to_string())),
+            Command::MkdirP("/var/tmp".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Rm("/tmp/a".to_string())),
+            Command::Rm("/tmp/a".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::RmRecursive {
+                path: "/var/tmp".to_string(),
+                force: true
+            }),
+            Command::RmRecursive {
+                path: "/var/tmp".to_string(),
+                force: true
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Slots { verbose: false }),
+            Command::Slots { verbose: false }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Plug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                module: "console-service".to_string(),
+                dry_run: false,
+                swap: false,
+                override_lock: false
+            }),
+            Command::Plug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                module: "console-service".to_string(),
+                dry_run: false,
+                swap: false,
+                override_lock: false
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Unplug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                force: true,
+                override_lock: false
+            }),
+            Command::Unplug {
+                slot: "ruzzle.slot.console@1".to_string(),
+                force: true,
+                override_lock: false
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Graph { dot: true }),
+            Command::Graph { dot: true }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Sysinfo),
+            Command::Sysinfo
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Clip(None)),
+            Command::Clip(None)
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Time("ls".to_string())),
+            Command::Time("ls".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Metrics(Some("on".to_string()))),
+            Command::Metrics(Some("on".to_string()))
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Wc("/etc/hostname".to_string())),
+            Command::Wc("/etc/hostname".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Find {
+                path: "/".to_string(),
+                name: None,
+            }),
+            Command::Find {
+                path: "/".to_string(),
+                name: None,
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Ln {
+                target: "/etc/hostname".to_string(),
+                link: "/hostname".to_string(),
+            }),
+            Command::Ln {
+                target: "/etc/hostname".to_string(),
+                link: "/hostname".to_string(),
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Chsh {
+                user: Some("guest".to_string()),
+                shell: None,
+            }),
+            Command::Chsh {
+                user: Some("guest".to_string()),
+                shell: None,
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Chmod {
+                mode: "600".to_string(),
+                path: "/tmp/a".to_string(),
+            }),
+            Command::Chmod {
+                mode: "600".to_string(),
+                path: "/tmp/a".to_string(),
             }
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Cp {
-                src: "/etc/hostname".to_string(),
-                dst: "/etc/hostname.bak".to_string(),
-                recursive: false
+            from_ipc(shell_protocol::ShellCommand::Chown {
+                user: "guest".to_string(),
+                path: "/tmp/a".to_string(),
             }),
-            Command::Cp {
-                src: "/etc/hostname".to_string(),
-                dst: "/etc/hostname.bak".to_string(),
-                recursive: false
+            Command::Chown {
+                user: "guest".to_string(),
+                path: "/tmp/a".to_string(),
             }
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Mv {
-                src: "/etc/hostname".to_string(),
-                dst: "/etc/hostname.old".to_string()
+            from_ipc(shell_protocol::ShellCommand::Gpu(None)),
+            Command::Gpu(None)
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Watch {
+                interval_secs: 3,
+                command: "ps".to_string(),
             }),
-            Command::Mv {
-                src: "/etc/hostname".to_string(),
-                dst: "/etc/hostname.old".to_string()
+            Command::Watch {
+                interval_secs: 3,
+                command: "ps".to_string(),
             }
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::MkdirP("/var/tmp".to_string())),
-            Command::MkdirP("/var/tmp".to_string())
+            from_ipc(shell_protocol::ShellCommand::Container(Some("list".to_string()))),
+            Command::Container(Some("list".to_string()))
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Rm("/tmp/a".to_string())),
-            Command::Rm("/tmp/a".to_string())
+            from_ipc(shell_protocol::ShellCommand::Http(Some("top".to_string()))),
+            Command::Http(Some("top".to_string()))
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::RmRecursive("/var/tmp".to_string())),
-            Command::RmRecursive("/var/tmp".to_string())
+            from_ipc(shell_protocol::ShellCommand::Echo("hi".to_string())),
+            Command::Echo("hi".to_string())
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Slots),
-            Command::Slots
+            from_ipc(shell_protocol::ShellCommand::Shutdown),
+            Command::Shutdown
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Plug {
-                slot: "ruzzle.slot.console@1".to_string(),
-                module: "console-service".to_string(),
-                dry_run: false,
-                swap: false
-            }),
-            Command::Plug {
-                slot: "ruzzle.slot.console@1".to_string(),
-                module: "console-service".to_string(),
-                dry_run: false,
-                swap: false
-            }
+            from_ipc(shell_protocol::ShellCommand::Set(Some("color=off".to_string()))),
+            Command::Set(Some("color=off".to_string()))
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Unplug(
-                "ruzzle.slot.console@1".to_string()
-            )),
-            Command::Unplug("ruzzle.slot.console@1".to_string())
+            from_ipc(shell_protocol::ShellCommand::WebConsole(Some(
+                "install".to_string()
+            ))),
+            Command::WebConsole(Some("install".to_string()))
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Graph),
-            Command::Graph
+            from_ipc(shell_protocol::ShellCommand::LogLevel(None)),
+            Command::LogLevel(None)
         );
         assert_eq!(
-            from_ipc(shell_protocol::ShellCommand::Sysinfo),
-            Command::Sysinfo
+            from_ipc(shell_protocol::ShellCommand::Notifications { clear: false }),
+            Command::Notifications { clear: false }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Stat("/etc".to_string())),
+            Command::Stat("/etc".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Apply {
+                path: "/etc/system.conf".to_string(),
+                dry_run: false
+            }),
+            Command::Apply {
+                path: "/etc/system.conf".to_string(),
+                dry_run: false
+            }
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Checksum("/etc".to_string())),
+            Command::Checksum("/etc".to_string())
+        );
+        assert_eq!(
+            from_ipc(shell_protocol::ShellCommand::Autoplug { dry_run: false }),
+            Command::Autoplug { dry_run: false }
         );
     }
 
@@ -1642,6 +4980,54 @@ mod tests {
         assert!(help.contains("install"));
     }
 
+    #[test]
+    fn format_help_command_topic_shows_usage_block() {
+        let help = format_help(Some("plug"));
+        assert!(help.starts_with("plug - plug a module into a puzzle slot\nusage:\n"));
+        assert!(help.contains("  plug [--dry-run|-n] [--swap|-s] [--override] <slot> <module>\n"));
+        assert!(help.contains("flags:\n  -n, --dry-run  check compatibility"));
+        assert!(help.contains("  -s, --swap     replace the current provider"));
+        assert!(help.contains("examples:\n  plug ruzzle.slot.editor@1 vim-piece\n"));
+        assert!(!help.contains("commands:"));
+    }
+
+    #[test]
+    fn format_help_command_topic_uses_first_word() {
+        let help = format_help(Some("log tail"));
+        assert!(help.starts_with("log - "));
+        assert!(help.contains("  log level [<module> <trace|debug|info|warn|error>]\n"));
+        let help = format_help(Some("pwd"));
+        assert!(help.contains("usage:\n  pwd\n"));
+        assert!(!help.contains("flags:"));
+    }
+
+    #[test]
+    fn command_help_table_covers_listing() {
+        let all = format_help(None);
+        for entry in COMMAND_HELP {
+            assert!(!entry.usage.is_empty(), "{}", entry.name);
+            assert!(!entry.examples.is_empty(), "{}", entry.name);
+            for line in entry.usage {
+                assert!(all.contains(line));
+            }
+            if entry.name == "slots" || entry.name == "market" {
+                continue;
+            }
+            let help = format_help(Some(entry.name));
+            assert!(help.starts_with(entry.name));
+            assert!(!help.contains("unknown help topic"));
+        }
+    }
+
+    #[test]
+    fn format_command_help_skips_empty_examples() {
+        let entry = help_entry("noop", "does nothing", &["noop"], &[], &[]);
+        assert_eq!(
+            format_command_help(&entry),
+            "noop - does nothing\nusage:\n  noop\n"
+        );
+    }
+
     #[test]
     fn format_help_unknown_topic() {
         let help = format_help(Some("mystery"));
@@ -1651,7 +5037,7 @@ mod tests {
 
     #[test]
     fn format_modules_handles_empty() {
-        let output = format_modules(&[]);
+        let output = format_modules(&[], Style::Plain);
         assert!(output.contains("<none>"));
     }
 
@@ -1662,7 +5048,7 @@ mod tests {
             state: "running".to_string(),
             provides: vec!["ruzzle.console".to_string()],
         }];
-        let output = format_modules(&rows);
+        let output = format_modules(&rows, Style::Plain);
         assert!(output.contains("console-service"));
         assert!(output.contains("running"));
         assert!(output.contains("ruzzle.console"));
@@ -1675,7 +5061,7 @@ mod tests {
             state: "running".to_string(),
             provides: vec!["ruzzle.console".to_string(), "ruzzle.shell".to_string()],
         }];
-        let output = format_modules(&rows);
+        let output = format_modules(&rows, Style::Plain);
         assert!(output.contains("ruzzle.console, ruzzle.shell"));
     }
 
@@ -1686,10 +5072,42 @@ mod tests {
             state: "running".to_string(),
             provides: vec![],
         }];
-        let output = format_modules(&rows);
+        let output = format_modules(&rows, Style::Plain);
         assert!(output.contains("provides: -"));
     }
 
+    #[test]
+    fn format_response_renders_typed_results() {
+        let modules = shell_protocol::ShellResponse::Modules(vec![shell_protocol::ModuleInfo {
+            name: "fs-service".to_string(),
+            state: "running".to_string(),
+            provides: vec!["ruzzle.fs".to_string()],
+        }]);
+        let output = format_response(&modules, Style::Plain);
+        assert!(output.contains("fs-service [running] provides: ruzzle.fs"));
+
+        let slots = shell_protocol::ShellResponse::Slots(vec![shell_protocol::SlotInfo {
+            name: "ruzzle.slot.console@1".to_string(),
+            required: true,
+            locked: true,
+            providers: vec!["console-service".to_string()],
+        }]);
+        let output = format_response(&slots, Style::Plain);
+        assert!(output.contains("ruzzle.slot.console@1 -> console-service [locked]"));
+
+        let error = shell_protocol::ShellResponse::Error {
+            code: shell_protocol::ShellErrorCode::NotFound,
+            message: "cat error: NotFound\n".to_string(),
+        };
+        assert_eq!(
+            format_response(&error, Style::Plain),
+            "error[3] not found: cat error: NotFound\n"
+        );
+
+        let text = CommandResult::new(ExitCode::SUCCESS, "done\n".to_string()).to_response();
+        assert_eq!(format_response(&text, Style::Plain), "done\n");
+    }
+
     #[test]
     fn format_catalog_handles_empty() {
         let output = format_catalog(&[]);
@@ -1712,7 +5130,7 @@ mod tests {
 
     #[test]
     fn format_slots_handles_empty() {
-        let output = format_slots(&[]);
+        let output = format_slots(&[], Style::Plain, false);
         assert!(output.contains("puzzle board:"));
         assert!(output.contains("<none>"));
     }
@@ -1723,8 +5141,12 @@ mod tests {
             name: "ruzzle.slot.console@1".to_string(),
             required: true,
             provider: Some("console-service".to_string()),
+            description: None,
+            category: None,
+            tags: Vec::new(),
+            locked: false,
         }];
-        let output = format_slots(&rows);
+        let output = format_slots(&rows, Style::Plain, false);
         assert!(output.contains("ruzzle.slot.console@1"));
         assert!(output.contains("REQUIRED"));
         assert!(output.contains("[OK ]"));
@@ -1737,8 +5159,12 @@ mod tests {
             name: "ruzzle.slot.net@1".to_string(),
             required: false,
             provider: None,
+            description: None,
+            category: None,
+            tags: Vec::new(),
+            locked: false,
         }];
-        let output = format_slots(&rows);
+        let output = format_slots(&rows, Style::Plain, false);
         assert!(output.contains("ruzzle.slot.net@1"));
         assert!(output.contains("OPTIONAL"));
         assert!(output.contains("[EMPTY]"));
@@ -1752,18 +5178,188 @@ mod tests {
                 name: "ruzzle.slot.console@1".to_string(),
                 required: true,
                 provider: Some("console-service".to_string()),
+                description: None,
+                category: None,
+                tags: Vec::new(),
+                locked: false,
             },
             SlotRow {
                 name: "ruzzle.slot.net@1".to_string(),
                 required: false,
                 provider: None,
+                description: None,
+                category: None,
+                tags: Vec::new(),
+                locked: false,
             },
         ];
-        let output = format_slots(&rows);
+        let output = format_slots(&rows, Style::Plain, false);
         assert!(output.contains("ruzzle.slot.console@1"));
         assert!(output.contains("ruzzle.slot.net@1"));
     }
 
+    #[test]
+    fn format_slots_marks_locked_slots() {
+        let rows = vec![SlotRow {
+            name: "ruzzle.slot.console@1".to_string(),
+            required: true,
+            provider: Some("console-service".to_string()),
+            description: None,
+            category: None,
+            tags: Vec::new(),
+            locked: true,
+        }];
+        let output = format_slots(&rows, Style::Plain, false);
+        assert!(output.contains("| [OK ] ruzzle.slot.console@1 -> console-service [locked] |"));
+    }
+
+    #[test]
+    fn format_slots_colors_empty_required_slots() {
+        let rows = vec![
+            SlotRow {
+                name: "ruzzle.slot.shell@1".to_string(),
+                required: true,
+                provider: None,
+                description: None,
+                category: None,
+                tags: Vec::new(),
+                locked: false,
+            },
+            SlotRow {
+                name: "ruzzle.slot.net@1".to_string(),
+                required: false,
+                provider: None,
+                description: None,
+                category: None,
+                tags: Vec::new(),
+                locked: false,
+            },
+        ];
+        let output = format_slots(&rows, Style::Color, false);
+        assert!(output.contains("[\x1b[31mEMPTY\x1b[0m] ruzzle.slot.shell@1"));
+        assert!(output.contains("[EMPTY] ruzzle.slot.net@1"));
+        let plain = format_slots(&rows, Style::Plain, false);
+        let plain_lines = plain.lines().collect::<Vec<_>>();
+        let colored_lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(
+            colored_lines[3].len(),
+            plain_lines[3].len() + ANSI_RED.len() + ANSI_RESET.len()
+        );
+        assert_eq!(colored_lines[2], plain_lines[2]);
+    }
+
+    #[test]
+    fn format_slots_verbose_shows_metadata() {
+        let rows = vec![
+            SlotRow {
+                name: "ruzzle.slot.fs@1".to_string(),
+                required: true,
+                provider: Some("fs-service".to_string()),
+                description: Some("root filesystem service".to_string()),
+                category: Some("core".to_string()),
+                tags: vec!["storage".to_string(), "vfs".to_string()],
+                locked: false,
+            },
+            SlotRow {
+                name: "ruzzle.slot.gpu@1".to_string(),
+                required: false,
+                provider: None,
+                description: None,
+                category: None,
+                tags: vec!["compute".to_string()],
+                locked: false,
+            },
+        ];
+        let output = format_slots(&rows, Style::Plain, true);
+        assert!(output.contains("|       root filesystem service "));
+        assert!(output.contains("|       category: core; tags: storage, vfs "));
+        assert!(output.contains("|       tags: compute "));
+        let required_widths = output
+            .lines()
+            .filter(|line| line.starts_with("  |"))
+            .take(4)
+            .map(str::len)
+            .collect::<Vec<_>>();
+        assert!(required_widths
+            .iter()
+            .all(|width| *width == required_widths[0]));
+        let terse = format_slots(&rows, Style::Plain, false);
+        assert!(!terse.contains("root filesystem service"));
+        assert!(!terse.contains("tags:"));
+    }
+
+    #[test]
+    fn format_modules_colors_running_state() {
+        let rows = vec![
+            ModuleRow {
+                name: "init".to_string(),
+                state: "running".to_string(),
+                provides: vec![],
+            },
+            ModuleRow {
+                name: "net".to_string(),
+                state: "stopped".to_string(),
+                provides: vec![],
+            },
+            ModuleRow {
+                name: "fs".to_string(),
+                state: "degraded".to_string(),
+                provides: vec![],
+            },
+        ];
+        let output = format_modules(&rows, Style::Color);
+        assert!(output.contains("init [\x1b[32mrunning\x1b[0m]"));
+        assert!(output.contains("net [stopped]"));
+        assert!(output.contains("fs [\x1b[31mdegraded\x1b[0m]"));
+        let hung = ModuleRow {
+            name: "audio".to_string(),
+            state: "hung".to_string(),
+            provides: vec![],
+        };
+        assert!(format_modules(&[hung], Style::Color).contains("audio [\x1b[31mhung\x1b[0m]"));
+        assert!(!format_modules(&rows, Style::Plain).contains('\x1b'));
+    }
+
+    #[test]
+    fn style_parses_setting_values() {
+        assert_eq!(Style::parse("on"), Some(Style::Color));
+        assert_eq!(Style::parse("off"), Some(Style::Plain));
+        assert_eq!(Style::parse("maybe"), None);
+        assert_eq!(Style::Color.name(), "on");
+        assert_eq!(Style::default().name(), "off");
+    }
+
+    #[test]
+    fn format_plug_plan_numbers_steps_and_lists_gaps() {
+        let rows = vec![
+            PlanRow {
+                slot: "ruzzle.slot.fs@1".to_string(),
+                module: "fs-service".to_string(),
+                source: "installed".to_string(),
+            },
+            PlanRow {
+                slot: "ruzzle.slot.editor@1".to_string(),
+                module: "text-editor".to_string(),
+                source: "catalog".to_string(),
+            },
+        ];
+        assert_eq!(
+            format_plug_plan(&rows, &["ruzzle.slot.gpu@1".to_string()], &[]),
+            "plug plan:\n  1. ruzzle.slot.fs@1 <- fs-service (installed)\n  2. ruzzle.slot.editor@1 <- text-editor (catalog)\n  unfilled: ruzzle.slot.gpu@1\n"
+        );
+        let ties = vec![(
+            "ruzzle.slot.editor@1".to_string(),
+            vec!["text-editor".to_string(), "vim-piece".to_string()],
+        )];
+        assert!(format_plug_plan(&rows, &[], &ties).ends_with(
+            "  tie: ruzzle.slot.editor@1 between text-editor, vim-piece (picked text-editor)\n"
+        ));
+        assert_eq!(
+            format_plug_plan(&[], &[], &[]),
+            "plug plan:\n  <nothing to plug>\n"
+        );
+    }
+
     #[test]
     fn format_graph_handles_empty() {
         let output = format_graph(&[]);
@@ -1777,6 +5373,8 @@ mod tests {
             name: "file-manager".to_string(),
             state: "installed".to_string(),
             depends: vec!["fs-service".to_string(), "user-service".to_string()],
+            wants: Vec::new(),
+            required_by: Vec::new(),
         }];
         let output = format_graph(&rows);
         assert!(output.contains("file-manager"));
@@ -1791,12 +5389,127 @@ mod tests {
             name: "console-service".to_string(),
             state: "running".to_string(),
             depends: Vec::new(),
+            wants: Vec::new(),
+            required_by: Vec::new(),
         }];
         let output = format_graph(&rows);
         assert!(output.contains("console-service"));
         assert!(output.contains("`- <none>"));
     }
 
+    #[test]
+    fn format_graph_dashes_optional_edges() {
+        let rows = vec![GraphRow {
+            name: "file-manager".to_string(),
+            state: "installed".to_string(),
+            depends: vec!["fs-service".to_string()],
+            wants: vec!["net-manager".to_string()],
+            required_by: Vec::new(),
+        }];
+        let output = format_graph(&rows);
+        assert!(output.contains("     |- fs-service\n"));
+        assert!(output.contains("     `. net-manager (wants)\n"));
+        assert!(!output.contains("required by"));
+    }
+
+    #[test]
+    fn format_graph_dot_emits_modules_and_slots() {
+        let rows = vec![GraphRow {
+            name: "net-manager".to_string(),
+            state: "running".to_string(),
+            depends: vec!["fs-service".to_string()],
+            wants: vec!["dns \"cache\"".to_string()],
+            required_by: vec![],
+        }];
+        let slots = vec![
+            (
+                "ruzzle.slot.net@1".to_string(),
+                vec!["net-manager".to_string()],
+            ),
+            ("ruzzle.slot.editor@1".to_string(), vec![]),
+        ];
+        assert_eq!(
+            format_graph_dot(&rows, &slots),
+            "digraph ruzzle {\n  rankdir=LR;\n  \"net-manager\" [label=\"net-manager (running)\"];\n  \"net-manager\" -> \"fs-service\";\n  \"net-manager\" -> \"dns \\\"cache\\\"\" [style=dashed];\n  \"ruzzle.slot.net@1\" [shape=box];\n  \"ruzzle.slot.net@1\" -> \"net-manager\" [color=blue];\n  \"ruzzle.slot.editor@1\" [shape=box];\n}\n"
+        );
+        assert_eq!(
+            format_graph_dot(&[], &[]),
+            "digraph ruzzle {\n  rankdir=LR;\n}\n"
+        );
+    }
+
+    #[test]
+    fn format_graph_lists_reverse_dependencies() {
+        let rows = vec![GraphRow {
+            name: "fs-service".to_string(),
+            state: "running".to_string(),
+            depends: Vec::new(),
+            wants: Vec::new(),
+            required_by: vec!["file-manager".to_string(), "net-manager".to_string()],
+        }];
+        assert_eq!(
+            format_graph(&rows),
+            "puzzle graph:\n  +-[running] fs-service\n     `- <none>\n     required by: file-manager, net-manager\n"
+        );
+    }
+
+    #[test]
+    fn format_boot_report_lists_slowest_and_critical_path() {
+        let fs = BootTimingRow {
+            name: "fs-service".to_string(),
+            started_us: 1_200,
+            duration_us: 350,
+        };
+        let net = BootTimingRow {
+            name: "net-manager".to_string(),
+            started_us: 1_600,
+            duration_us: 12_045,
+        };
+        assert_eq!(
+            format_boot_report(13_700, &[net.clone(), fs.clone()], &[fs, net]),
+            "boot: 13.700ms\nslowest modules:\n  12.045ms net-manager\n  0.350ms fs-service\ncritical path:\n  fs-service @1.200ms +0.350ms\n    `- net-manager @1.600ms +12.045ms\n"
+        );
+        assert_eq!(
+            format_boot_report(0, &[], &[]),
+            "boot: 0.000ms\nslowest modules:\n  <none>\n"
+        );
+    }
+
+    #[test]
+    fn format_module_status_shows_failure_diagnostics() {
+        let mut row = ModuleStatusRow {
+            name: "net-manager".to_string(),
+            state: "stopped".to_string(),
+            failures: 2,
+            last_error: Some("invalid capabilities".to_string()),
+            last_failure: Some(1500),
+        };
+        assert_eq!(
+            format_module_status(&row),
+            "status: net-manager\n  state: stopped\n  failures: 2 consecutive\n  last error: invalid capabilities\n  last failure: 1500 ms\n"
+        );
+        row.failures = 0;
+        row.last_error = None;
+        row.last_failure = None;
+        assert!(
+            format_module_status(&row).ends_with("failures: 0 consecutive\n  last error: <none>\n")
+        );
+    }
+
+    #[test]
+    fn format_description_lists_commands() {
+        let description = ServiceDescription::from_table("shell", 1, &[(1, "ps"), (55, "echo")]);
+        assert_eq!(
+            format_description(&description),
+            "shell protocol v1:\n    1 ps\n   55 echo\n"
+        );
+        let empty = ServiceDescription::from_table("registry", 2, &[]);
+        assert_eq!(
+            format_description(&empty),
+            "registry protocol v2:\n  <none>\n"
+        );
+    }
+
     #[test]
     fn format_processes_handles_empty() {
         let output = format_processes(&[]);
@@ -1830,6 +5543,172 @@ mod tests {
     #[test]
     fn format_log_tail_is_stable() {
         assert_eq!(format_log_tail_empty(), "log tail: no buffered logs available");
+        assert_eq!(
+            format_log_follow_header(),
+            "following log  (press any key to stop)"
+        );
+    }
+
+    #[test]
+    fn parse_log_tail_options() {
+        assert_eq!(
+            parse_command("log tail -f"),
+            Command::LogTail {
+                follow: true,
+                lines: None
+            }
+        );
+        assert_eq!(
+            parse_command("log tail --lines 20 --follow"),
+            Command::LogTail {
+                follow: true,
+                lines: Some(20)
+            }
+        );
+        assert_eq!(
+            parse_command("log tail -n 0"),
+            Command::LogTail {
+                follow: false,
+                lines: Some(0)
+            }
+        );
+        assert_eq!(parse_command("log level"), Command::LogLevel(None));
+        assert_eq!(
+            parse_command("log level net-service error"),
+            Command::LogLevel(Some("net-service error".to_string()))
+        );
+        for raw in [
+            "log",
+            "log head",
+            "log tail -n",
+            "log tail -n x",
+            "log tail -x",
+        ] {
+            assert_eq!(parse_command(raw), Command::Unknown(raw.to_string()));
+        }
+    }
+
+    #[test]
+    fn word_count_counts_lines_words_bytes() {
+        let count = WordCount::from_text("hello world\nruzzle os\n");
+        assert_eq!(
+            count,
+            WordCount {
+                lines: 2,
+                words: 4,
+                bytes: 22,
+            }
+        );
+        assert_eq!(WordCount::from_text(""), WordCount::default());
+    }
+
+    #[test]
+    fn format_size_scales_to_binary_units() {
+        assert_eq!(format_size(1536, false), "1536");
+        assert_eq!(format_size(0, true), "0 B");
+        assert_eq!(format_size(1023, true), "1023 B");
+        assert_eq!(format_size(1024, true), "1.0 KiB");
+        assert_eq!(format_size(1536, true), "1.5 KiB");
+        assert_eq!(format_size(1_048_575, true), "1.0 MiB");
+        assert_eq!(format_size(5 * 1024 * 1024 + 512 * 1024, true), "5.5 MiB");
+        assert_eq!(format_size(3 << 30, true), "3.0 GiB");
+        assert_eq!(format_size(usize::MAX, true), "16777216.0 TiB");
+    }
+
+    #[test]
+    fn format_df_aligns_columns() {
+        let rows = [DfRow {
+            path: "/".to_string(),
+            files: 12,
+            dirs: 4,
+            bytes: 1536,
+        }];
+        assert_eq!(
+            format_df(&rows, true),
+            "Path  Files  Dirs  Size\n/     12     4     1.5 KiB\n"
+        );
+        assert_eq!(
+            format_df(&rows, false),
+            "Path  Files  Dirs  Size\n/     12     4     1536\n"
+        );
+        assert_eq!(format_df(&[], false), "Path  Files  Dirs  Size\n");
+    }
+
+    #[test]
+    fn format_du_prefixes_size() {
+        assert_eq!(format_du("/etc", 2048, true), "2.0 KiB  /etc");
+        assert_eq!(format_du("/etc", 2048, false), "2048  /etc");
+    }
+
+    #[test]
+    fn confirmation_prompt_covers_destructive_commands() {
+        let required = |slot: &str| slot == "ruzzle.slot.console@1";
+        assert_eq!(
+            confirmation_prompt(&parse_command("rm -r /var/tmp"), required),
+            Some("remove /var/tmp and everything under it?".to_string())
+        );
+        assert_eq!(
+            confirmation_prompt(&parse_command("remove fs-service"), required),
+            Some("remove module fs-service?".to_string())
+        );
+        assert_eq!(
+            confirmation_prompt(&parse_command("unplug ruzzle.slot.console@1"), required),
+            Some("unplug required slot ruzzle.slot.console@1?".to_string())
+        );
+        for raw in [
+            "rm -rf /var/tmp",
+            "remove -f fs-service",
+            "unplug --force ruzzle.slot.console@1",
+            "unplug ruzzle.slot.net@1",
+            "rm /tmp/a",
+        ] {
+            assert_eq!(confirmation_prompt(&parse_command(raw), required), None);
+        }
+    }
+
+    #[test]
+    fn is_confirmed_accepts_only_yes() {
+        for answer in ["y", "Y", " yes\n", "Yes", "YES"] {
+            assert!(is_confirmed(answer));
+        }
+        for answer in ["", "n", "no", "yep", "N"] {
+            assert!(!is_confirmed(answer));
+        }
+    }
+
+    #[test]
+    fn format_word_count_includes_label() {
+        let count = WordCount::from_text("one two\nthree");
+        assert_eq!(format_word_count(&count, "/tmp/a"), "2 3 13 /tmp/a");
+        assert_eq!(format_word_count(&count, ""), "2 3 13");
+    }
+
+    #[test]
+    fn format_checksum_pads_hex() {
+        assert_eq!(format_checksum(0xcbf4_3926, "/a"), "cbf43926  /a");
+        assert_eq!(format_checksum(0x1f, "/b c"), "0000001f  /b c");
+    }
+
+    #[test]
+    fn format_stat_lists_metadata() {
+        let mut info = StatInfo {
+            path: "/etc/hosts".to_string(),
+            kind: "file".to_string(),
+            size: 12,
+            owner: "root".to_string(),
+            group: "wheel".to_string(),
+            mode: 0o644,
+            permissions: "-rw-r--r--".to_string(),
+            target: None,
+            created: 1_005,
+            modified: 62_340,
+        };
+        assert_eq!(
+            format_stat(&info),
+            "  File: /etc/hosts\n  Type: file\n  Size: 12\n Owner: root\n Group: wheel\nAccess: 0644 (-rw-r--r--)\nCreate: +1.005s\nModify: +62.340s"
+        );
+        info.target = Some("/etc/hosts.real".to_string());
+        assert!(format_stat(&info).starts_with("  File: /etc/hosts -> /etc/hosts.real\n"));
     }
 
     #[test]
@@ -14,6 +14,7 @@ pub enum UserError {
     AlreadyExists,
     InvalidName,
     NoActiveUser,
+    InvalidShell,
 }
 
 /// Represents a user account.
@@ -102,6 +103,16 @@ impl UserManager {
         self.users.get(active).ok_or(UserError::NotFound)
     }
 
+    /// Changes a user's login shell path.
+    pub fn set_shell(&mut self, name: &str, shell: &str) -> Result<(), UserError> {
+        if !shell.starts_with('/') || shell.contains(char::is_whitespace) {
+            return Err(UserError::InvalidShell);
+        }
+        let user = self.users.get_mut(name).ok_or(UserError::NotFound)?;
+        user.shell = shell.to_string();
+        Ok(())
+    }
+
     /// Lists users sorted by name.
     pub fn list_users(&self) -> Vec<UserRecord> {
         self.users.values().cloned().collect()
@@ -253,6 +264,17 @@ mod tests {
         assert_eq!(manager.remove_user("missing"), Err(UserError::NotFound));
     }
 
+    #[test]
+    fn set_shell_updates_record() {
+        let mut manager = UserManager::new();
+        manager.add_user("root", true).unwrap();
+        manager.set_shell("root", "/bin/fish").unwrap();
+        assert_eq!(manager.get_user("root").unwrap().shell, "/bin/fish");
+        assert_eq!(manager.set_shell("root", "fish"), Err(UserError::InvalidShell));
+        assert_eq!(manager.set_shell("root", "/bin/a b"), Err(UserError::InvalidShell));
+        assert_eq!(manager.set_shell("missing", "/bin/fish"), Err(UserError::NotFound));
+    }
+
     #[test]
     fn active_user_requires_presence() {
         let manager = UserManager::new();
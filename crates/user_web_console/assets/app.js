@@ -0,0 +1,66 @@
+// Browser side of the web console: text frames carry shell lines, binary
+// frames carry TLV-encoded shell responses (TLV_STATUS = 2, TLV_TEXT = 5).
+const TLV_STATUS = 2;
+const TLV_TEXT = 5;
+const ANSI = /\x1b\[[0-9;]*m/g;
+const pending = [];
+const socket = new WebSocket(`ws://${location.host}/ws`);
+socket.binaryType = "arraybuffer";
+
+function decodeResponse(buffer) {
+  const view = new DataView(buffer);
+  const response = { ok: false, text: "" };
+  for (let offset = 0; offset + 4 <= view.byteLength;) {
+    const type = view.getUint16(offset, true);
+    const len = view.getUint16(offset + 2, true);
+    const value = new Uint8Array(buffer, offset + 4, len);
+    if (type === TLV_STATUS) response.ok = value[0] === 0;
+    if (type === TLV_TEXT) response.text = new TextDecoder().decode(value);
+    offset += 4 + len;
+  }
+  return response;
+}
+
+function send(line, target) {
+  if (socket.readyState !== WebSocket.OPEN) return;
+  pending.push(target);
+  socket.send(line);
+}
+
+function append(text) {
+  const output = document.getElementById("output");
+  output.textContent += text;
+  output.scrollTop = output.scrollHeight;
+}
+
+function refresh() {
+  send("slots", "board");
+  send("lsmod", "modules");
+}
+
+socket.onopen = () => {
+  document.getElementById("status").textContent = "connected";
+  refresh();
+  setInterval(refresh, 2000);
+};
+socket.onclose = () => {
+  document.getElementById("status").textContent = "disconnected";
+};
+socket.onmessage = (event) => {
+  const target = pending.shift() || "output";
+  const response = decodeResponse(event.data);
+  const text = response.text.replace(ANSI, "");
+  if (target === "output") {
+    append(response.ok ? text : `[failed]\n${text}`);
+  } else {
+    document.getElementById(target).textContent = text;
+  }
+};
+
+document.getElementById("prompt").onsubmit = (event) => {
+  event.preventDefault();
+  const input = document.getElementById("line");
+  append(`ruzzle> ${input.value}\n`);
+  send(input.value, "output");
+  input.value = "";
+};
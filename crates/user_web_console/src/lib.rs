@@ -0,0 +1,985 @@
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use ruzzle_protocol::auth::Credential;
+use ruzzle_protocol::describe::{encode_description, is_describe_request};
+use ruzzle_protocol::frame::{self as wire, FrameDecoder};
+use ruzzle_protocol::hello::{decode_hello, encode_hello_reply, is_hello_request};
+use ruzzle_protocol::shell::{
+    answer_shell_hello, append_request_id, decode_command, describe_shell, encode_response,
+    is_authorized, read_request_id, ShellCommand, ShellErrorCode, ShellResponse, ShellStatus,
+};
+use ruzzle_protocol::ProtocolError;
+use user_file_manager::Fs;
+use user_fs_service::FsError;
+use user_server_stack::{HttpResponse, ServerError, ServerStack};
+
+/// VFS directory holding the browser assets.
+pub const ASSET_ROOT: &str = "/srv/webconsole";
+
+/// HTTP path upgraded to the WebSocket shell bridge.
+pub const WS_PATH: &str = "/ws";
+
+/// Largest WebSocket payload accepted from a browser.
+pub const MAX_FRAME_PAYLOAD: usize = 64 * 1024;
+
+/// GUID appended to the client key when computing `Sec-WebSocket-Accept`.
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Static browser asset served over HTTP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Asset {
+    pub route: &'static str,
+    pub file: &'static str,
+    pub contents: &'static str,
+}
+
+/// Browser UI: puzzle board, module list and a terminal.
+pub const ASSETS: [Asset; 3] = [
+    Asset {
+        route: "/",
+        file: "index.html",
+        contents: include_str!("../assets/index.html"),
+    },
+    Asset {
+        route: "/app.js",
+        file: "app.js",
+        contents: include_str!("../assets/app.js"),
+    },
+    Asset {
+        route: "/style.css",
+        file: "style.css",
+        contents: include_str!("../assets/style.css"),
+    },
+];
+
+/// Errors for the web console piece.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebConsoleError {
+    Fs(FsError),
+    Server(ServerError),
+    Protocol(ProtocolError),
+    Frame(FrameError),
+    InvalidUtf8,
+}
+
+/// Errors produced while decoding a WebSocket frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    Unmasked,
+    Fragmented,
+    UnknownOpcode(u8),
+    TooLarge,
+}
+
+/// WebSocket frame opcodes understood by the bridge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl Opcode {
+    /// Returns the wire value of the opcode.
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Opcode::Text => 0x1,
+            Opcode::Binary => 0x2,
+            Opcode::Close => 0x8,
+            Opcode::Ping => 0x9,
+            Opcode::Pong => 0xA,
+        }
+    }
+
+    /// Parses a wire opcode.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x1 => Some(Opcode::Text),
+            0x2 => Some(Opcode::Binary),
+            0x8 => Some(Opcode::Close),
+            0x9 => Some(Opcode::Ping),
+            0xA => Some(Opcode::Pong),
+            _ => None,
+        }
+    }
+}
+
+/// A single unfragmented WebSocket frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub opcode: Opcode,
+    pub payload: Vec<u8>,
+}
+
+/// Request forwarded from the browser to the shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeRequest {
+    /// Text frame: a raw shell line typed in the terminal.
+    Line(String),
+    /// Binary frame: a TLV-encoded shell protocol command.
+    Command(ShellCommand),
+}
+
+/// Writes the browser assets into the VFS under `root`.
+pub fn install_assets<F: Fs>(fs: &mut F, root: &str) -> Result<usize, WebConsoleError> {
+    let mut current = String::new();
+    for segment in root.split('/').filter(|segment| !segment.is_empty()) {
+        current.push('/');
+        current.push_str(segment);
+        match fs.mkdir(&current) {
+            Ok(()) | Err(FsError::AlreadyExists) => {}
+            Err(err) => return Err(WebConsoleError::Fs(err)),
+        }
+    }
+    for asset in ASSETS {
+        fs.write_file(&asset_path(root, asset.file), asset.contents.as_bytes())
+            .map_err(WebConsoleError::Fs)?;
+    }
+    Ok(ASSETS.len())
+}
+
+/// Registers `GET` routes serving the assets currently stored under `root`.
+pub fn register_routes<F: Fs>(
+    server: &mut ServerStack,
+    fs: &F,
+    root: &str,
+) -> Result<usize, WebConsoleError> {
+    for asset in ASSETS {
+        let bytes = fs
+            .read_file(&asset_path(root, asset.file))
+            .map_err(WebConsoleError::Fs)?;
+        let body = String::from_utf8(bytes).map_err(|_| WebConsoleError::InvalidUtf8)?;
+        server
+            .register_route("GET", asset.route, HttpResponse { status: 200, body })
+            .map_err(WebConsoleError::Server)?;
+    }
+    Ok(ASSETS.len())
+}
+
+fn asset_path(root: &str, file: &str) -> String {
+    let mut path = String::from(root.trim_end_matches('/'));
+    path.push('/');
+    path.push_str(file);
+    path
+}
+
+/// Returns the `Sec-WebSocket-Key` of a raw upgrade request for [`WS_PATH`].
+pub fn upgrade_key(raw: &str) -> Option<&str> {
+    let mut lines = raw.split("\r\n");
+    let target = lines.next()?.strip_prefix("GET ")?.split(' ').next()?;
+    if target != WS_PATH {
+        return None;
+    }
+    let mut upgrade = false;
+    let mut key = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+        if name.eq_ignore_ascii_case("upgrade") {
+            upgrade = value.eq_ignore_ascii_case("websocket");
+        } else if name.eq_ignore_ascii_case("sec-websocket-key") {
+            key = Some(value);
+        }
+    }
+    key.filter(|_| upgrade)
+}
+
+/// Computes the `Sec-WebSocket-Accept` value for a client key.
+pub fn accept_key(client_key: &str) -> String {
+    let mut input = Vec::from(client_key.as_bytes());
+    input.extend_from_slice(WS_GUID.as_bytes());
+    base64_encode(&sha1(&input))
+}
+
+/// Builds the `101 Switching Protocols` response for a client key.
+pub fn upgrade_response(client_key: &str) -> Vec<u8> {
+    let mut out = String::from(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n",
+    );
+    out.push_str("Sec-WebSocket-Accept: ");
+    out.push_str(&accept_key(client_key));
+    out.push_str("\r\n\r\n");
+    out.into_bytes()
+}
+
+/// Decodes one masked client frame; returns `None` until the frame is complete.
+pub fn decode_frame(bytes: &[u8]) -> Result<Option<(Frame, usize)>, FrameError> {
+    if bytes.len() < 2 {
+        return Ok(None);
+    }
+    if bytes[0] & 0x80 == 0 {
+        return Err(FrameError::Fragmented);
+    }
+    let code = bytes[0] & 0x0F;
+    let opcode = Opcode::from_u8(code).ok_or(FrameError::UnknownOpcode(code))?;
+    if bytes[1] & 0x80 == 0 {
+        return Err(FrameError::Unmasked);
+    }
+    let (len, mut offset) = match bytes[1] & 0x7F {
+        126 => match bytes.get(2..4) {
+            Some(raw) => (usize::from(u16::from_be_bytes([raw[0], raw[1]])), 4),
+            None => return Ok(None),
+        },
+        127 => match bytes.get(2..10) {
+            Some(raw) => {
+                let mut len = [0u8; 8];
+                len.copy_from_slice(raw);
+                (
+                    usize::try_from(u64::from_be_bytes(len)).unwrap_or(usize::MAX),
+                    10,
+                )
+            }
+            None => return Ok(None),
+        },
+        len => (usize::from(len), 2),
+    };
+    if len > MAX_FRAME_PAYLOAD {
+        return Err(FrameError::TooLarge);
+    }
+    let Some(mask) = bytes.get(offset..offset + 4) else {
+        return Ok(None);
+    };
+    let mask = [mask[0], mask[1], mask[2], mask[3]];
+    offset += 4;
+    let Some(data) = bytes.get(offset..offset + len) else {
+        return Ok(None);
+    };
+    let payload = apply_mask(data, mask);
+    Ok(Some((Frame { opcode, payload }, offset + len)))
+}
+
+/// Encodes a frame; servers pass `None`, clients pass a masking key.
+pub fn encode_frame(frame: &Frame, mask: Option<[u8; 4]>) -> Vec<u8> {
+    let mut out = vec![0x80 | frame.opcode.as_u8()];
+    let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+    let len = frame.payload.len();
+    if len < 126 {
+        out.push(mask_bit | len as u8);
+    } else if let Ok(short) = u16::try_from(len) {
+        out.push(mask_bit | 126);
+        out.extend_from_slice(&short.to_be_bytes());
+    } else {
+        out.push(mask_bit | 127);
+        out.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+    match mask {
+        Some(mask) => {
+            out.extend_from_slice(&mask);
+            out.extend(apply_mask(&frame.payload, mask));
+        }
+        None => out.extend_from_slice(&frame.payload),
+    }
+    out
+}
+
+fn apply_mask(data: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    data.iter()
+        .enumerate()
+        .map(|(index, byte)| byte ^ mask[index % 4])
+        .collect()
+}
+
+/// Bridges one browser frame to the shell and returns the frame to send back.
+///
+/// A binary command's request id is echoed in its response so clients can
+/// keep several commands in flight. Admin-only commands are answered with
+/// `PermissionDenied` unless `credential`, the one of the session bound to
+/// this connection, is an admin's; credentials inside the frame are ignored.
+pub fn bridge_frame(
+    frame: &Frame,
+    credential: Option<&Credential>,
+    mut dispatch: impl FnMut(BridgeRequest) -> ShellResponse,
+) -> Result<Option<Frame>, WebConsoleError> {
+    let request = match frame.opcode {
+        Opcode::Text => BridgeRequest::Line(
+            String::from_utf8(frame.payload.clone()).map_err(|_| WebConsoleError::InvalidUtf8)?,
+        ),
+        Opcode::Binary => {
+            return Ok(Some(Frame {
+                opcode: Opcode::Binary,
+                payload: bridge_message(&frame.payload, credential, dispatch)?,
+            }))
+        }
+        Opcode::Ping => {
+            return Ok(Some(Frame {
+                opcode: Opcode::Pong,
+                payload: frame.payload.clone(),
+            }))
+        }
+        Opcode::Close => {
+            return Ok(Some(Frame {
+                opcode: Opcode::Close,
+                payload: Vec::new(),
+            }))
+        }
+        Opcode::Pong => return Ok(None),
+    };
+    Ok(Some(Frame {
+        opcode: Opcode::Binary,
+        payload: encode_reply(&dispatch(request), None),
+    }))
+}
+
+/// Shell bridge for byte streams without message boundaries, such as serial.
+///
+/// Binary shell messages travel in `ruzzle_protocol::frame` frames, so a
+/// dropped or flipped byte costs one message instead of wedging the stream.
+/// An empty frame ends the session.
+#[derive(Debug, Clone, Default)]
+pub struct StreamBridge {
+    decoder: FrameDecoder,
+    closed: bool,
+    /// Request id of the command whose last response was `Streaming`.
+    stream: Option<Option<u32>>,
+}
+
+impl StreamBridge {
+    /// Creates a bridge waiting for its first frame.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds bytes from the transport and returns the framed replies.
+    ///
+    /// Commands are authorized against `credential`, as in `bridge_frame`.
+    /// A message that does not decode is answered with `InvalidArgument`, so
+    /// a client waiting on its request id is not left hanging. Bytes after
+    /// the closing frame are ignored.
+    pub fn push(
+        &mut self,
+        bytes: &[u8],
+        credential: Option<&Credential>,
+        mut dispatch: impl FnMut(BridgeRequest) -> ShellResponse,
+    ) -> Vec<u8> {
+        let mut out = Vec::new();
+        if self.closed {
+            return out;
+        }
+        self.decoder.push(bytes);
+        while let Some(payload) = self.decoder.next_frame() {
+            if payload.is_empty() {
+                self.closed = true;
+                break;
+            }
+            let request_id = read_request_id(&payload).ok().flatten();
+            let mut streaming = false;
+            let reply = bridge_message(&payload, credential, |request| {
+                let response = dispatch(request);
+                streaming = response.status() == ShellStatus::Streaming;
+                response
+            })
+            .unwrap_or_else(|_| {
+                encode_reply(
+                    &ShellResponse::Error {
+                        code: ShellErrorCode::InvalidArgument,
+                        message: "malformed request".to_string(),
+                    },
+                    request_id,
+                )
+            });
+            self.stream = streaming.then_some(request_id);
+            out.extend(frame_reply(&reply, request_id));
+        }
+        out
+    }
+
+    /// Frames a further response to the command left streaming by `push`.
+    ///
+    /// A response whose status is not `Streaming` ends the stream. Returns
+    /// nothing when no command is streaming.
+    pub fn stream(&mut self, response: &ShellResponse) -> Vec<u8> {
+        let Some(request_id) = self.stream else {
+            return Vec::new();
+        };
+        if response.status() != ShellStatus::Streaming {
+            self.stream = None;
+        }
+        frame_reply(&encode_reply(response, request_id), request_id)
+    }
+
+    /// Returns true once the client has sent the closing empty frame.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns the frame decoder, for its drop and corruption counters.
+    pub fn decoder(&self) -> &FrameDecoder {
+        &self.decoder
+    }
+}
+
+/// Answers one binary shell protocol message: describe, hello or a command.
+fn bridge_message(
+    payload: &[u8],
+    credential: Option<&Credential>,
+    mut dispatch: impl FnMut(BridgeRequest) -> ShellResponse,
+) -> Result<Vec<u8>, WebConsoleError> {
+    if is_describe_request(payload) {
+        return Ok(encode_description(&describe_shell()));
+    }
+    if is_hello_request(payload) {
+        let hello = decode_hello(payload).map_err(WebConsoleError::Protocol)?;
+        return Ok(encode_hello_reply(&answer_shell_hello(&hello)));
+    }
+    let request_id = read_request_id(payload).map_err(WebConsoleError::Protocol)?;
+    let command = decode_command(payload).map_err(WebConsoleError::Protocol)?;
+    let response = if is_authorized(&command, credential) {
+        dispatch(BridgeRequest::Command(command))
+    } else {
+        ShellResponse::Error {
+            code: ShellErrorCode::PermissionDenied,
+            message: "admin privilege required".to_string(),
+        }
+    };
+    Ok(encode_reply(&response, request_id))
+}
+
+/// Wraps a reply in a wire frame, or a `Failed` error if it does not fit.
+fn frame_reply(reply: &[u8], request_id: Option<u32>) -> Vec<u8> {
+    wire::encode_frame(reply)
+        .or_else(|_| {
+            wire::encode_frame(&encode_reply(
+                &ShellResponse::Error {
+                    code: ShellErrorCode::Failed,
+                    message: "response too large".to_string(),
+                },
+                request_id,
+            ))
+        })
+        .unwrap_or_default()
+}
+
+fn encode_reply(response: &ShellResponse, request_id: Option<u32>) -> Vec<u8> {
+    let mut payload = encode_response(response);
+    if let Some(id) = request_id {
+        append_request_id(&mut payload, id);
+    }
+    payload
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = Vec::from(data);
+    let bit_len = (data.len() as u64).wrapping_mul(8);
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&bit_len.to_be_bytes());
+    for block in message.chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for index in 16..80 {
+            words[index] =
+                (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16])
+                    .rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (value, delta) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(delta);
+        }
+    }
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let value = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (value >> (18 - 6 * index)) & 0x3F;
+                out.push(char::from(BASE64_ALPHABET[sextet as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruzzle_protocol::auth::append_credential;
+    use ruzzle_protocol::shell::{decode_response, ShellMultiplexer};
+    use user_fs_service::FileSystem;
+    use user_server_stack::{HttpRequest, ServerConfig};
+
+    fn server() -> ServerStack {
+        ServerStack::new(ServerConfig {
+            host: "0.0.0.0".to_string(),
+            port: 80,
+            tls_enabled: false,
+            metrics_enabled: false,
+        })
+    }
+
+    fn get(path: &str) -> HttpRequest {
+        HttpRequest {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn install_and_serve_assets_from_vfs() {
+        let mut fs = FileSystem::new();
+        assert_eq!(install_assets(&mut fs, ASSET_ROOT), Ok(3));
+        assert_eq!(install_assets(&mut fs, "/srv/webconsole/"), Ok(3));
+        fs.write_file("/srv/webconsole/style.css", b"body {}")
+            .unwrap();
+        let mut server = server();
+        assert_eq!(register_routes(&mut server, &fs, ASSET_ROOT), Ok(3));
+        let index = server.handle(&get("/"));
+        assert_eq!(index.status, 200);
+        assert!(index.body.contains("Puzzle board"));
+        assert!(server.handle(&get("/app.js")).body.contains("WebSocket"));
+        assert_eq!(server.handle(&get("/style.css")).body, "body {}");
+        assert_eq!(
+            register_routes(&mut server, &fs, ASSET_ROOT),
+            Err(WebConsoleError::Server(ServerError::RouteExists))
+        );
+    }
+
+    #[test]
+    fn install_and_register_report_fs_errors() {
+        let mut fs = FileSystem::new();
+        fs.write_file("/srv", b"file").unwrap();
+        assert_eq!(
+            install_assets(&mut fs, ASSET_ROOT),
+            Err(WebConsoleError::Fs(FsError::NotDir))
+        );
+        let mut fs = FileSystem::new();
+        fs.mkdir("/srv").unwrap();
+        fs.mkdir("/srv/webconsole").unwrap();
+        fs.mkdir("/srv/webconsole/index.html").unwrap();
+        assert_eq!(
+            install_assets(&mut fs, ASSET_ROOT),
+            Err(WebConsoleError::Fs(FsError::IsDir))
+        );
+        let mut server = server();
+        assert_eq!(
+            register_routes(&mut server, &FileSystem::new(), ASSET_ROOT),
+            Err(WebConsoleError::Fs(FsError::NotFound))
+        );
+        let mut fs = FileSystem::new();
+        install_assets(&mut fs, ASSET_ROOT).unwrap();
+        fs.write_file("/srv/webconsole/index.html", &[0xFF])
+            .unwrap();
+        assert_eq!(
+            register_routes(&mut server, &fs, ASSET_ROOT),
+            Err(WebConsoleError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        let response = upgrade_response("dGhlIHNhbXBsZSBub25jZQ==");
+        let text = String::from_utf8(response).unwrap();
+        assert!(text.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert!(text.ends_with("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n"));
+    }
+
+    #[test]
+    fn sha1_and_base64_handle_padding() {
+        assert_eq!(base64_encode(&sha1(b"")), "2jmj7l5rSw0yVb/vlWAYkK/YBwk=");
+        assert_eq!(base64_encode(b"a"), "YQ==");
+        assert_eq!(base64_encode(b"ab"), "YWI=");
+        assert_eq!(base64_encode(b"abc"), "YWJj");
+        let long = [b'x'; 100];
+        assert_eq!(base64_encode(&sha1(&long)), "UOSDaQ7EgfSvf2+1JLK5nrFxZWU=");
+    }
+
+    #[test]
+    fn upgrade_key_requires_websocket_upgrade_on_ws_path() {
+        let raw = "GET /ws HTTP/1.1\r\nHost: x\r\nUpgrade: WebSocket\r\nbroken\r\nSec-WebSocket-Key: abc==\r\n\r\n";
+        assert_eq!(upgrade_key(raw), Some("abc=="));
+        assert_eq!(
+            upgrade_key("GET /ws HTTP/1.1\r\nSec-WebSocket-Key: abc==\r\n\r\n"),
+            None
+        );
+        assert_eq!(
+            upgrade_key("GET /ws HTTP/1.1\r\nUpgrade: h2c\r\nSec-WebSocket-Key: k\r\n\r\n"),
+            None
+        );
+        assert_eq!(
+            upgrade_key("GET / HTTP/1.1\r\nUpgrade: websocket\r\nSec-WebSocket-Key: k\r\n\r\n"),
+            None
+        );
+        assert_eq!(upgrade_key("POST /ws HTTP/1.1\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn frames_round_trip_with_all_length_encodings() {
+        for len in [0, 5, 125, 126, 300, 70_000] {
+            let frame = Frame {
+                opcode: Opcode::Binary,
+                payload: vec![0x5A; len],
+            };
+            let bytes = encode_frame(&frame, Some([1, 2, 3, 4]));
+            if len <= MAX_FRAME_PAYLOAD {
+                assert_eq!(decode_frame(&bytes), Ok(Some((frame, bytes.len()))));
+                assert_eq!(decode_frame(&bytes[..bytes.len() - 1]), Ok(None));
+            } else {
+                assert_eq!(decode_frame(&bytes), Err(FrameError::TooLarge));
+            }
+        }
+        let server_frame = encode_frame(
+            &Frame {
+                opcode: Opcode::Text,
+                payload: b"hi".to_vec(),
+            },
+            None,
+        );
+        assert_eq!(server_frame, vec![0x81, 0x02, b'h', b'i']);
+    }
+
+    #[test]
+    fn decode_frame_rejects_invalid_frames() {
+        assert_eq!(decode_frame(&[0x81]), Ok(None));
+        assert_eq!(decode_frame(&[0x81, 0xFE, 0x01]), Ok(None));
+        assert_eq!(decode_frame(&[0x81, 0xFF, 0, 0]), Ok(None));
+        assert_eq!(decode_frame(&[0x81, 0x80, 1, 2]), Ok(None));
+        assert_eq!(decode_frame(&[0x01, 0x80]), Err(FrameError::Fragmented));
+        assert_eq!(
+            decode_frame(&[0x83, 0x80]),
+            Err(FrameError::UnknownOpcode(3))
+        );
+        assert_eq!(decode_frame(&[0x81, 0x00]), Err(FrameError::Unmasked));
+        for (code, opcode) in [
+            (0x1, Opcode::Text),
+            (0x2, Opcode::Binary),
+            (0x8, Opcode::Close),
+            (0x9, Opcode::Ping),
+            (0xA, Opcode::Pong),
+        ] {
+            assert_eq!(Opcode::from_u8(code), Some(opcode));
+            assert_eq!(opcode.as_u8(), code);
+        }
+    }
+
+    #[test]
+    fn bridge_forwards_lines_and_commands_to_the_shell() {
+        let mut seen = Vec::new();
+        let mut dispatch = |request: BridgeRequest| {
+            seen.push(request);
+            ShellResponse::Text {
+                status: ShellStatus::Ok,
+                text: "done".to_string(),
+            }
+        };
+        let text = Frame {
+            opcode: Opcode::Text,
+            payload: b"slots".to_vec(),
+        };
+        let reply = bridge_frame(&text, None, &mut dispatch).unwrap().unwrap();
+        assert_eq!(reply.opcode, Opcode::Binary);
+        assert_eq!(
+            decode_response(&reply.payload),
+            Ok(ShellResponse::Text {
+                status: ShellStatus::Ok,
+                text: "done".to_string(),
+            })
+        );
+        let binary = Frame {
+            opcode: Opcode::Binary,
+            payload: ruzzle_protocol::shell::encode_command(&ShellCommand::Lsmod),
+        };
+        bridge_frame(&binary, None, &mut dispatch).unwrap();
+        assert_eq!(
+            seen,
+            vec![
+                BridgeRequest::Line("slots".to_string()),
+                BridgeRequest::Command(ShellCommand::Lsmod),
+            ]
+        );
+    }
+
+    #[test]
+    fn bridge_echoes_request_ids() {
+        let mut mux = ShellMultiplexer::new();
+        let (id, payload) = mux.send(ShellCommand::Lsmod);
+        let frame = Frame {
+            opcode: Opcode::Binary,
+            payload,
+        };
+        let reply = bridge_frame(&frame, None, |_| ShellResponse::Modules(Vec::new()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            mux.receive(&reply.payload),
+            Ok((id, ShellResponse::Modules(Vec::new())))
+        );
+        assert!(mux.pending().is_empty());
+    }
+
+    #[test]
+    fn stream_bridge_survives_corruption_and_closes_on_empty_frame() {
+        let mut mux = ShellMultiplexer::new();
+        let (first, payload) = mux.send(ShellCommand::Lsmod);
+        let mut damaged = wire::encode_frame(&payload).unwrap();
+        damaged[6] ^= 0x01;
+        let (second, payload) = mux.send(ShellCommand::Lsmod);
+        let mut stream = vec![0x00, 0x52];
+        stream.extend(damaged);
+        stream.extend(wire::encode_frame(&payload).unwrap());
+        stream.extend(wire::encode_frame(&[]).unwrap());
+        stream.extend(wire::encode_frame(&payload).unwrap());
+
+        let mut bridge = StreamBridge::new();
+        let mut calls = 0;
+        let mut replies = FrameDecoder::new();
+        for chunk in stream.chunks(5) {
+            let out = bridge.push(chunk, None, |_| {
+                calls += 1;
+                ShellResponse::Modules(Vec::new())
+            });
+            replies.push(&out);
+        }
+        assert!(bridge.is_closed());
+        assert_eq!(bridge.decoder().corrupt_frames(), 1);
+        assert_eq!(calls, 1);
+        let reply = replies.next_frame().unwrap();
+        assert_eq!(
+            mux.receive(&reply),
+            Ok((second, ShellResponse::Modules(Vec::new())))
+        );
+        assert!(replies.next_frame().is_none());
+        assert_eq!(mux.pending(), vec![first]);
+    }
+
+    #[test]
+    fn stream_bridge_answers_malformed_messages() {
+        let mut payload = Vec::new();
+        ruzzle_protocol::tlv::write_tlv(
+            &mut payload,
+            ruzzle_protocol::shell::TLV_MSG_TYPE,
+            &[0xff],
+        );
+        append_request_id(&mut payload, 9);
+        let mut bridge = StreamBridge::new();
+        let out = bridge.push(&wire::encode_frame(&payload).unwrap(), None, |_| {
+            unreachable!("malformed messages are not dispatched")
+        });
+        let mut replies = FrameDecoder::new();
+        replies.push(&out);
+        let reply = replies.next_frame().unwrap();
+        assert_eq!(read_request_id(&reply), Ok(Some(9)));
+        assert_eq!(
+            decode_response(&reply),
+            Ok(ShellResponse::Error {
+                code: ShellErrorCode::InvalidArgument,
+                message: "malformed request".to_string(),
+            })
+        );
+        assert!(!bridge.is_closed());
+    }
+
+    #[test]
+    fn stream_bridge_keeps_the_request_id_of_a_streaming_command() {
+        let mut mux = ShellMultiplexer::new();
+        let (id, payload) = mux.send(ShellCommand::LogTail {
+            follow: true,
+            lines: None,
+        });
+        let text = |status, text: &str| ShellResponse::Text {
+            status,
+            text: text.to_string(),
+        };
+        let mut bridge = StreamBridge::new();
+        assert!(bridge
+            .stream(&text(ShellStatus::Streaming, "early"))
+            .is_empty());
+        let mut replies = FrameDecoder::new();
+        replies.push(
+            &bridge.push(&wire::encode_frame(&payload).unwrap(), None, |_| {
+                text(ShellStatus::Streaming, "a")
+            }),
+        );
+        replies.push(&bridge.stream(&text(ShellStatus::Streaming, "b")));
+        replies.push(&bridge.stream(&text(ShellStatus::Ok, "stopped")));
+        assert!(bridge
+            .stream(&text(ShellStatus::Streaming, "late"))
+            .is_empty());
+
+        for expected in [
+            text(ShellStatus::Streaming, "a"),
+            text(ShellStatus::Streaming, "b"),
+            text(ShellStatus::Ok, "stopped"),
+        ] {
+            let reply = replies.next_frame().unwrap();
+            assert_eq!(mux.receive(&reply), Ok((id, expected)));
+        }
+        assert!(replies.next_frame().is_none());
+        assert!(mux.pending().is_empty());
+    }
+
+    #[test]
+    fn bridge_denies_admin_commands_without_admin_credential() {
+        let mut seen = Vec::new();
+        let mut dispatch = |request: BridgeRequest| {
+            seen.push(request);
+            ShellResponse::Text {
+                status: ShellStatus::Ok,
+                text: String::new(),
+            }
+        };
+        let stop = ShellCommand::Stop("net-manager".to_string());
+        let root = Credential::new("root", true);
+        let guest = Credential::new("guest", false);
+        for session in [None, Some(&guest)] {
+            // A peer claiming admin in the message does not change the session's rights.
+            let mut payload = ruzzle_protocol::shell::encode_command(&stop);
+            append_credential(&mut payload, &root);
+            append_request_id(&mut payload, 7);
+            let frame = Frame {
+                opcode: Opcode::Binary,
+                payload,
+            };
+            let reply = bridge_frame(&frame, session, &mut dispatch)
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                decode_response(&reply.payload),
+                Ok(ShellResponse::Error {
+                    code: ShellErrorCode::PermissionDenied,
+                    message: "admin privilege required".to_string(),
+                })
+            );
+            assert_eq!(read_request_id(&reply.payload), Ok(Some(7)));
+        }
+
+        let frame = Frame {
+            opcode: Opcode::Binary,
+            payload: ruzzle_protocol::shell::encode_command(&stop),
+        };
+        bridge_frame(&frame, Some(&root), &mut dispatch).unwrap();
+        assert_eq!(seen, vec![BridgeRequest::Command(stop)]);
+    }
+
+    #[test]
+    fn bridge_handles_control_frames_and_errors() {
+        let mut calls = 0;
+        let mut dispatch = |_: BridgeRequest| {
+            calls += 1;
+            ShellResponse::Text {
+                status: ShellStatus::Failed,
+                text: String::new(),
+            }
+        };
+        let ping = Frame {
+            opcode: Opcode::Ping,
+            payload: b"p".to_vec(),
+        };
+        assert_eq!(
+            bridge_frame(&ping, None, &mut dispatch),
+            Ok(Some(Frame {
+                opcode: Opcode::Pong,
+                payload: b"p".to_vec(),
+            }))
+        );
+        let close = Frame {
+            opcode: Opcode::Close,
+            payload: vec![3, 232],
+        };
+        assert_eq!(
+            bridge_frame(&close, None, &mut dispatch)
+                .unwrap()
+                .unwrap()
+                .opcode,
+            Opcode::Close
+        );
+        let pong = Frame {
+            opcode: Opcode::Pong,
+            payload: Vec::new(),
+        };
+        assert_eq!(bridge_frame(&pong, None, &mut dispatch), Ok(None));
+        let bad_text = Frame {
+            opcode: Opcode::Text,
+            payload: vec![0xFF],
+        };
+        assert_eq!(
+            bridge_frame(&bad_text, None, &mut dispatch),
+            Err(WebConsoleError::InvalidUtf8)
+        );
+        let bad_binary = Frame {
+            opcode: Opcode::Binary,
+            payload: vec![1],
+        };
+        assert!(matches!(
+            bridge_frame(&bad_binary, None, &mut dispatch),
+            Err(WebConsoleError::Protocol(_))
+        ));
+        let describe = Frame {
+            opcode: Opcode::Binary,
+            payload: ruzzle_protocol::describe::encode_describe_request(),
+        };
+        let reply = bridge_frame(&describe, None, &mut dispatch)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            ruzzle_protocol::describe::decode_description(&reply.payload),
+            Ok(describe_shell())
+        );
+        let hello = Frame {
+            opcode: Opcode::Binary,
+            payload: ruzzle_protocol::hello::encode_hello(&ruzzle_protocol::shell::shell_hello()),
+        };
+        let reply = bridge_frame(&hello, None, &mut dispatch).unwrap().unwrap();
+        assert_eq!(
+            ruzzle_protocol::hello::decode_hello_reply(&reply.payload),
+            Ok(ruzzle_protocol::hello::HelloReply::Accepted {
+                version: ruzzle_protocol::shell::SHELL_PROTOCOL_VERSION
+            })
+        );
+        assert_eq!(calls, 0);
+    }
+}
@@ -44,6 +44,7 @@ cargo build -p user_fs_service --target aarch64-unknown-none --release
 cargo build -p user_net_service --target aarch64-unknown-none --release
 cargo build -p user_user_service --target aarch64-unknown-none --release
 cargo build -p user_text_editor --target aarch64-unknown-none --release
+cargo build -p user_clipboard_service --target aarch64-unknown-none --release
 cargo build -p user_file_manager --target aarch64-unknown-none --release
 cargo build -p user_settings_service --target aarch64-unknown-none --release
 cargo build -p user_session_service --target aarch64-unknown-none --release
@@ -57,6 +58,8 @@ cargo build -p user_device_manager --target aarch64-unknown-none --release
 cargo build -p user_input_service --target aarch64-unknown-none --release
 cargo build -p user_gpu_service --target aarch64-unknown-none --release
 cargo build -p user_ml_runtime --target aarch64-unknown-none --release
+cargo build -p user_web_console --target aarch64-unknown-none --release
+cargo build -p user_dhcp_server --target aarch64-unknown-none --release
 
 cp "${ROOT_DIR}/target/aarch64-unknown-none/release/init" "${INITRAMFS_DIR}/init"
 cp "${ROOT_DIR}/target/aarch64-unknown-none/release/console-service" "${INITRAMFS_DIR}/console-service"
@@ -89,6 +92,11 @@ python3 "${ROOT_DIR}/tools/pack_module.py" \
   "${ROOT_DIR}/crates/user_text_editor/module.toml" \
   "${ROOT_DIR}/target/aarch64-unknown-none/release/text-editor"
 
+python3 "${ROOT_DIR}/tools/pack_module.py" \
+  "${STORE_DIR}/clipboard-service.rpiece" \
+  "${ROOT_DIR}/crates/user_clipboard_service/module.toml" \
+  "${ROOT_DIR}/target/aarch64-unknown-none/release/clipboard-service"
+
 python3 "${ROOT_DIR}/tools/pack_module.py" \
   "${STORE_DIR}/file-manager.rpiece" \
   "${ROOT_DIR}/crates/user_file_manager/module.toml" \
@@ -154,6 +162,16 @@ python3 "${ROOT_DIR}/tools/pack_module.py" \
   "${ROOT_DIR}/crates/user_ml_runtime/module.toml" \
   "${ROOT_DIR}/target/aarch64-unknown-none/release/ml-runtime"
 
+python3 "${ROOT_DIR}/tools/pack_module.py" \
+  "${STORE_DIR}/web-console.rpiece" \
+  "${ROOT_DIR}/crates/user_web_console/module.toml" \
+  "${ROOT_DIR}/target/aarch64-unknown-none/release/web-console"
+
+python3 "${ROOT_DIR}/tools/pack_module.py" \
+  "${STORE_DIR}/dhcp-server.rpiece" \
+  "${ROOT_DIR}/crates/user_dhcp_server/module.toml" \
+  "${ROOT_DIR}/target/aarch64-unknown-none/release/dhcp-server"
+
 EXTERNAL_DIR="${ROOT_DIR}/modules"
 if compgen -G "${EXTERNAL_DIR}/*.rpiece" > /dev/null; then
   cp "${EXTERNAL_DIR}"/*.rpiece "${STORE_DIR}/"
@@ -47,6 +47,7 @@ cargo build -p user_fs_service --target x86_64-unknown-none --release
 cargo build -p user_net_service --target x86_64-unknown-none --release
 cargo build -p user_user_service --target x86_64-unknown-none --release
 cargo build -p user_text_editor --target x86_64-unknown-none --release
+cargo build -p user_clipboard_service --target x86_64-unknown-none --release
 cargo build -p user_file_manager --target x86_64-unknown-none --release
 cargo build -p user_settings_service --target x86_64-unknown-none --release
 cargo build -p user_session_service --target x86_64-unknown-none --release
@@ -60,6 +61,8 @@ cargo build -p user_device_manager --target x86_64-unknown-none --release
 cargo build -p user_input_service --target x86_64-unknown-none --release
 cargo build -p user_gpu_service --target x86_64-unknown-none --release
 cargo build -p user_ml_runtime --target x86_64-unknown-none --release
+cargo build -p user_web_console --target x86_64-unknown-none --release
+cargo build -p user_dhcp_server --target x86_64-unknown-none --release
 
 cp "${ROOT_DIR}/target/x86_64-unknown-none/release/init" "${INITRAMFS_DIR}/init"
 cp "${ROOT_DIR}/target/x86_64-unknown-none/release/console-service" "${INITRAMFS_DIR}/console-service"
@@ -92,6 +95,11 @@ python3 "${ROOT_DIR}/tools/pack_module.py" \
   "${ROOT_DIR}/crates/user_text_editor/module.toml" \
   "${ROOT_DIR}/target/x86_64-unknown-none/release/text-editor"
 
+python3 "${ROOT_DIR}/tools/pack_module.py" \
+  "${STORE_DIR}/clipboard-service.rpiece" \
+  "${ROOT_DIR}/crates/user_clipboard_service/module.toml" \
+  "${ROOT_DIR}/target/x86_64-unknown-none/release/clipboard-service"
+
 python3 "${ROOT_DIR}/tools/pack_module.py" \
   "${STORE_DIR}/file-manager.rpiece" \
   "${ROOT_DIR}/crates/user_file_manager/module.toml" \
@@ -157,6 +165,16 @@ python3 "${ROOT_DIR}/tools/pack_module.py" \
   "${ROOT_DIR}/crates/user_ml_runtime/module.toml" \
   "${ROOT_DIR}/target/x86_64-unknown-none/release/ml-runtime"
 
+python3 "${ROOT_DIR}/tools/pack_module.py" \
+  "${STORE_DIR}/web-console.rpiece" \
+  "${ROOT_DIR}/crates/user_web_console/module.toml" \
+  "${ROOT_DIR}/target/x86_64-unknown-none/release/web-console"
+
+python3 "${ROOT_DIR}/tools/pack_module.py" \
+  "${STORE_DIR}/dhcp-server.rpiece" \
+  "${ROOT_DIR}/crates/user_dhcp_server/module.toml" \
+  "${ROOT_DIR}/target/x86_64-unknown-none/release/dhcp-server"
+
 EXTERNAL_DIR="${ROOT_DIR}/modules"
 if compgen -G "${EXTERNAL_DIR}/*.rpiece" > /dev/null; then
   cp "${EXTERNAL_DIR}"/*.rpiece "${STORE_DIR}/"
@@ -166,6 +184,11 @@ python3 "${ROOT_DIR}/tools/market_scan.py" \
   --input "${STORE_DIR}" \
   --output "${STORE_DIR}/index.toml"
 
+ROOTFS_ARCHIVE="${EXTERNAL_DIR}/rootfs.rzfs"
+if [ -f "${ROOTFS_ARCHIVE}" ]; then
+  cp "${ROOTFS_ARCHIVE}" "${INITRAMFS_DIR}/rootfs.rzfs"
+fi
+
 "${ROOT_DIR}/tools/mk_initramfs.py" "${INITRAMFS_IMG}" "${INITRAMFS_DIR}"
 
 rm -rf "${ISO_DIR}"
@@ -6,14 +6,19 @@ BUILD_DIR="${ROOT_DIR}/build"
 ISO_PATH="${BUILD_DIR}/ruzzle-x86_64.iso"
 NO_REBUILD=0
 ENABLE_GDB=0
+LAB_NET=""
+NIC_MAC=""
 
 usage() {
   cat <<EOF
-Usage: $(basename "$0") [--no-rebuild] [--gdb]
+Usage: $(basename "$0") [--no-rebuild] [--gdb] [--lab-net GROUP:PORT] [--mac MAC]
 
 Options:
-  --no-rebuild   Skip ISO rebuild (requires existing ${ISO_PATH})
-  --gdb          Wait for GDB on tcp::1234 (-s -S)
+  --no-rebuild      Skip ISO rebuild (requires existing ${ISO_PATH})
+  --gdb             Wait for GDB on tcp::1234 (-s -S)
+  --lab-net G:P     Join the multicast socket network G:P shared with other guests
+                    (e.g. 230.0.0.1:1234), for dhcpd labs
+  --mac MAC         MAC address of the virtio-net card; give each lab guest its own
 EOF
 }
 
@@ -41,6 +46,13 @@ run_qemu() {
   if [ "${ENABLE_GDB}" -eq 1 ]; then
     cmd+=(-s -S)
   fi
+  local nic="virtio-net-pci,netdev=net0"
+  if [ -n "${NIC_MAC}" ]; then
+    nic+=",mac=${NIC_MAC}"
+  fi
+  if [ -n "${LAB_NET}" ]; then
+    cmd+=(-netdev "socket,id=net0,mcast=${LAB_NET}" -device "${nic}")
+  fi
   if [ -n "${timeout_bin}" ]; then
     if "${timeout_bin}" "${QEMU_TIMEOUT}" "${cmd[@]}"; then
       return 0
@@ -64,6 +76,22 @@ while [ $# -gt 0 ]; do
     --gdb)
       ENABLE_GDB=1
       ;;
+    --lab-net)
+      if [ $# -lt 2 ]; then
+        echo "--lab-net requires GROUP:PORT" >&2
+        exit 1
+      fi
+      LAB_NET="$2"
+      shift
+      ;;
+    --mac)
+      if [ $# -lt 2 ]; then
+        echo "--mac requires an address" >&2
+        exit 1
+      fi
+      NIC_MAC="$2"
+      shift
+      ;;
     -h|--help)
       usage
       exit 0